@@ -0,0 +1,287 @@
+//! Per-code analytics: when, from where and which items were opened.
+//!
+//! Built from the access-control audit log, which records every access
+//! decision made with an access key together with the client IP and user
+//! agent. Claims and view counters come from the code itself.
+
+use crate::{describe_restrictions, AccessCodeState};
+use access_control::AuditLogEntry;
+use askama::Template;
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{Html, Json},
+};
+use common::ResourceType;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::str::FromStr;
+use std::sync::Arc;
+use tower_sessions::Session;
+
+/// Most recent audit entries taken into account
+const ANALYTICS_EVENT_LIMIT: i32 = 500;
+
+#[derive(Serialize)]
+pub struct AccessCodeAnalytics {
+    pub code: String,
+    pub description: String,
+    pub current_uses: i64,
+    pub max_uses: Option<i64>,
+    pub current_views: i64,
+    pub max_views: Option<i64>,
+    pub restrictions: Vec<String>,
+    pub granted_count: usize,
+    pub denied_count: usize,
+    pub unique_ips: usize,
+    pub items: Vec<ItemOpens>,
+    pub events: Vec<AccessEvent>,
+}
+
+/// Opens of one item via the code.
+#[derive(Serialize)]
+pub struct ItemOpens {
+    pub resource_type: String,
+    pub resource_id: i32,
+    pub title: String,
+    pub opens: usize,
+    pub denied: usize,
+    pub first_opened_at: String,
+    pub last_opened_at: String,
+}
+
+/// One access decision made with the code.
+#[derive(Serialize)]
+pub struct AccessEvent {
+    pub created_at: String,
+    pub ip_address: String,
+    pub user_agent: String,
+    pub resource_type: String,
+    pub title: String,
+    pub granted: bool,
+    pub reason: String,
+}
+
+#[derive(Template)]
+#[template(path = "codes/analytics.html")]
+struct AccessCodeAnalyticsTemplate {
+    authenticated: bool,
+    analytics: AccessCodeAnalytics,
+}
+
+/// Analytics page of an access code (owner only).
+pub async fn access_code_analytics_page(
+    Path(code): Path<String>,
+    session: Session,
+    State(state): State<Arc<AccessCodeState>>,
+) -> Result<Html<String>, StatusCode> {
+    let analytics = load_analytics(&session, &state, &code).await?;
+    let template = AccessCodeAnalyticsTemplate {
+        authenticated: true,
+        analytics,
+    };
+    let html = template
+        .render()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(Html(html))
+}
+
+/// Analytics of an access code as JSON (owner only).
+pub async fn access_code_analytics(
+    Path(code): Path<String>,
+    session: Session,
+    State(state): State<Arc<AccessCodeState>>,
+) -> Result<Json<AccessCodeAnalytics>, StatusCode> {
+    load_analytics(&session, &state, &code).await.map(Json)
+}
+
+async fn load_analytics(
+    session: &Session,
+    state: &AccessCodeState,
+    code: &str,
+) -> Result<AccessCodeAnalytics, StatusCode> {
+    let authenticated: bool = session
+        .get("authenticated")
+        .await
+        .ok()
+        .flatten()
+        .unwrap_or(false);
+    if !authenticated {
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+    let user_id: String = session
+        .get("user_id")
+        .await
+        .ok()
+        .flatten()
+        .unwrap_or_else(|| "unknown".to_string());
+
+    let ac = state
+        .repo
+        .get_code_by_code_and_user(code, &user_id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    let entries = state
+        .access_control
+        .audit_logger()
+        .get_access_key_audit_log(&ac.code, ANALYTICS_EVENT_LIMIT)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let mut titles = HashMap::new();
+    for entry in &entries {
+        let key = (entry.resource_type.clone(), entry.resource_id);
+        if !titles.contains_key(&key) {
+            let title = resource_title(state, entry).await;
+            titles.insert(key, title);
+        }
+    }
+
+    Ok(summarize(&ac, &entries, &titles))
+}
+
+async fn resource_title(state: &AccessCodeState, entry: &AuditLogEntry) -> String {
+    let fallback = format!("{} #{} (deleted)", entry.resource_type, entry.resource_id);
+    let Ok(resource_type) = ResourceType::from_str(&entry.resource_type) else {
+        return fallback;
+    };
+    state
+        .access_control
+        .repository()
+        .get_resource_title(resource_type, entry.resource_id)
+        .await
+        .unwrap_or(fallback)
+}
+
+fn summarize(
+    ac: &db::access_codes::AccessCode,
+    entries: &[AuditLogEntry],
+    titles: &HashMap<(String, i32), String>,
+) -> AccessCodeAnalytics {
+    let title_of = |e: &AuditLogEntry| {
+        titles
+            .get(&(e.resource_type.clone(), e.resource_id))
+            .cloned()
+            .unwrap_or_default()
+    };
+
+    // Entries are newest first
+    let mut items: BTreeMap<(String, i32), ItemOpens> = BTreeMap::new();
+    for entry in entries.iter().rev() {
+        let item = items
+            .entry((entry.resource_type.clone(), entry.resource_id))
+            .or_insert_with(|| ItemOpens {
+                resource_type: entry.resource_type.clone(),
+                resource_id: entry.resource_id,
+                title: title_of(entry),
+                opens: 0,
+                denied: 0,
+                first_opened_at: String::new(),
+                last_opened_at: String::new(),
+            });
+        if entry.access_granted {
+            item.opens += 1;
+            if item.first_opened_at.is_empty() {
+                item.first_opened_at = entry.created_at.clone();
+            }
+            item.last_opened_at = entry.created_at.clone();
+        } else {
+            item.denied += 1;
+        }
+    }
+    let mut items: Vec<ItemOpens> = items.into_values().collect();
+    items.sort_by(|a, b| b.opens.cmp(&a.opens).then(a.title.cmp(&b.title)));
+
+    let granted_count = entries.iter().filter(|e| e.access_granted).count();
+    let unique_ips = entries
+        .iter()
+        .filter_map(|e| e.ip_address.as_deref())
+        .collect::<HashSet<_>>()
+        .len();
+
+    AccessCodeAnalytics {
+        code: ac.code.clone(),
+        description: ac.description.clone().unwrap_or_default(),
+        current_uses: ac.current_uses,
+        max_uses: ac.max_uses,
+        current_views: ac.current_views,
+        max_views: ac.max_views,
+        restrictions: describe_restrictions(ac),
+        granted_count,
+        denied_count: entries.len() - granted_count,
+        unique_ips,
+        items,
+        events: entries
+            .iter()
+            .map(|e| AccessEvent {
+                created_at: e.created_at.clone(),
+                ip_address: e.ip_address.clone().unwrap_or_default(),
+                user_agent: e.user_agent.clone().unwrap_or_default(),
+                resource_type: e.resource_type.clone(),
+                title: title_of(e),
+                granted: e.access_granted,
+                reason: e.reason.clone(),
+            })
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(resource_id: i32, ip: &str, granted: bool, at: &str) -> AuditLogEntry {
+        AuditLogEntry {
+            id: 0,
+            user_id: None,
+            access_key: Some("code".to_string()),
+            ip_address: Some(ip.to_string()),
+            user_agent: None,
+            resource_type: "video".to_string(),
+            resource_id,
+            permission_requested: "read".to_string(),
+            permission_granted: granted.then(|| "read".to_string()),
+            access_granted: granted,
+            access_layer: "AccessKey".to_string(),
+            reason: String::new(),
+            created_at: at.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_summarize() {
+        let ac: db::access_codes::AccessCode = serde_json::from_value(serde_json::json!({
+            "id": 1, "code": "code", "description": null, "expires_at": null,
+            "created_at": "2026-01-01 00:00:00", "created_by": "u", "vault_id": null,
+            "is_active": true, "current_downloads": 0, "has_passphrase": false,
+            "max_uses": null, "current_uses": 0, "max_views": 10, "current_views": 3,
+            "allowed_ips": null, "allowed_referrers": null, "burn_after_claim": false
+        }))
+        .unwrap();
+        // Newest first, as returned by the audit log
+        let entries = vec![
+            entry(2, "10.0.0.2", false, "2026-01-03 00:00:00"),
+            entry(1, "10.0.0.2", true, "2026-01-02 00:00:00"),
+            entry(1, "10.0.0.1", true, "2026-01-01 00:00:00"),
+        ];
+        let titles = HashMap::from([
+            (("video".to_string(), 1), "First".to_string()),
+            (("video".to_string(), 2), "Second".to_string()),
+        ]);
+
+        let analytics = summarize(&ac, &entries, &titles);
+
+        assert_eq!(analytics.granted_count, 2);
+        assert_eq!(analytics.denied_count, 1);
+        assert_eq!(analytics.unique_ips, 2);
+        assert_eq!(analytics.items[0].title, "First");
+        assert_eq!(analytics.items[0].opens, 2);
+        assert_eq!(analytics.items[0].first_opened_at, "2026-01-01 00:00:00");
+        assert_eq!(analytics.items[0].last_opened_at, "2026-01-02 00:00:00");
+        assert_eq!(analytics.items[1].denied, 1);
+        assert_eq!(analytics.events[0].title, "Second");
+        assert_eq!(analytics.restrictions, vec!["Viewed 3 of 10 times"]);
+    }
+}
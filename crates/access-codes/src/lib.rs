@@ -1,8 +1,8 @@
 use askama::Template;
 use axum::{
-    extract::{Path, Query, State},
-    http::{HeaderMap, StatusCode},
-    response::{Html, Json},
+    extract::{Form, Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{Html, IntoResponse, Json, Redirect, Response},
     routing::{delete, get, post},
     Router,
 };
@@ -13,11 +13,18 @@ use tower_sessions::Session;
 use tracing::{self, info, warn};
 
 // Import access control functionality
-use access_control::{AccessContext, AccessControlService, Permission};
+use access_control::restrictions::{claim_tokens_from_headers, client_ip, hash_claim_token};
+use access_control::{AccessContext, AccessControlService, AccessKeyData, Permission};
 use common::ResourceType;
-pub use db::access_codes::{AccessCode, AccessCodePermission, AccessCodeRepository};
+pub use db::access_codes::{
+    AccessCode, AccessCodePermission, AccessCodeRepository, AccessCodeRestrictions,
+    NewAccessCodeClaim,
+};
 use db::media::MediaRepository;
 
+mod analytics;
+mod restrictions;
+
 #[derive(Clone)]
 pub struct AccessCodeState {
     pub repo: Arc<dyn AccessCodeRepository>,
@@ -48,6 +55,24 @@ pub struct CreateAccessCodeRequest {
     /// When set, creates a folder-scoped code granting access to all media in the vault.
     /// media_items is ignored when vault_id is provided.
     pub vault_id: Option<String>,
+    /// Passphrase asked for when the code is claimed (stored hashed)
+    #[serde(default)]
+    pub passphrase: Option<String>,
+    /// Maximum number of claims
+    #[serde(default)]
+    pub max_uses: Option<i64>,
+    /// Maximum number of item views
+    #[serde(default)]
+    pub max_views: Option<i64>,
+    /// IP addresses or CIDR ranges the code works from
+    #[serde(default)]
+    pub allowed_ips: Vec<String>,
+    /// Referrer hosts the landing page must be reached from
+    #[serde(default)]
+    pub allowed_referrers: Vec<String>,
+    /// Only the first visitor can claim the code
+    #[serde(default)]
+    pub burn_after_claim: bool,
 }
 
 #[derive(Deserialize, Serialize, Clone)]
@@ -105,6 +130,14 @@ pub struct AccessCodeResponse {
     pub expires_at: Option<String>,
     pub created_at: String,
     pub media_items: Vec<MediaItem>,
+    pub has_passphrase: bool,
+    pub max_uses: Option<i64>,
+    pub current_uses: i64,
+    pub max_views: Option<i64>,
+    pub current_views: i64,
+    pub allowed_ips: Vec<String>,
+    pub allowed_referrers: Vec<String>,
+    pub burn_after_claim: bool,
 }
 
 #[derive(Serialize)]
@@ -137,6 +170,11 @@ pub struct PreviewTemplate {
     pub resource_count: usize,
     pub resources: Vec<ResourcePreview>,
     pub base_url: String,
+    /// False while the code still has to be claimed in this browser
+    pub claimed: bool,
+    pub needs_passphrase: bool,
+    pub burn_after_claim: bool,
+    pub error: Option<String>,
 }
 
 #[derive(Template)]
@@ -168,6 +206,8 @@ pub struct AccessCodeDisplay {
     pub resource_count: usize,
     pub usage_count: usize,
     pub media_items: Vec<MediaItem>,
+    /// Human-readable summary of the code's restrictions
+    pub restrictions: Vec<String>,
 }
 
 // UI Page Handlers
@@ -243,6 +283,7 @@ pub async fn view_access_code_page(
     let description = ac.description.clone();
     let expires_at = ac.expires_at.clone();
     let created_at = ac.created_at.clone();
+    let restrictions = describe_restrictions(&ac);
 
     // Get permissions then enrich with media_items data for display
     let media_items = enrich_permissions(&state, id).await?;
@@ -286,8 +327,9 @@ pub async fn view_access_code_page(
         is_group_code: false,
         group_name: String::new(),
         resource_count: media_items.len(),
-        usage_count: 0,
+        usage_count: ac.current_views as usize,
         media_items,
+        restrictions,
     };
 
     #[derive(Template)]
@@ -312,6 +354,10 @@ pub async fn view_access_code_page(
 
 /// Public preview page for access code - shows all resources available with this code
 /// This is the page users land on when they click the shared access code URL
+///
+/// Codes with a passphrase, use limit, referrer allowlist or burn flag first
+/// show a claim form (see [`claim_access_code`]); the resources are listed
+/// once the code has been claimed in this browser.
 pub async fn preview_access_code_page(
     Query(params): Query<std::collections::HashMap<String, String>>,
     headers: HeaderMap,
@@ -323,43 +369,227 @@ pub async fn preview_access_code_page(
         .to_string();
 
     // Get access code details (no auth required - this is public)
+    let (ac, key_data) = load_shared_code(&state, &code, &headers).await?;
+
+    if key_data.requires_claim() && !has_valid_claim(&state, &key_data, &headers).await? {
+        let referer = headers.get(header::REFERER).and_then(|h| h.to_str().ok());
+        if !key_data.allows_referrer(referer) {
+            warn!(
+                event = "access_denied",
+                code = %code,
+                referer = ?referer,
+                reason = "referrer_not_allowed",
+                "Access code opened from a referrer outside its allowlist"
+            );
+            return Err(StatusCode::FORBIDDEN);
+        }
+        if uses_exhausted(&ac) {
+            return Err(StatusCode::GONE);
+        }
+        return render_preview(&state, &ac, &headers, false, None).await;
+    }
+
+    render_preview(&state, &ac, &headers, true, None).await
+}
+
+#[derive(Deserialize)]
+pub struct ClaimAccessCodeForm {
+    pub code: String,
+    #[serde(default)]
+    pub passphrase: Option<String>,
+}
+
+/// Claim a restricted access code from its landing page.
+///
+/// Checks the passphrase, counts a use and binds the code to this browser
+/// with a claim cookie, then redirects back to the preview page.
+pub async fn claim_access_code(
+    headers: HeaderMap,
+    State(state): State<Arc<AccessCodeState>>,
+    Form(form): Form<ClaimAccessCodeForm>,
+) -> Result<Response, StatusCode> {
+    let (ac, key_data) = load_shared_code(&state, &form.code, &headers).await?;
+    let preview_url = format!("/access/preview?code={}", urlencoding::encode(&ac.code));
+
+    if !key_data.requires_claim() || has_valid_claim(&state, &key_data, &headers).await? {
+        return Ok(Redirect::to(&preview_url).into_response());
+    }
+
+    // The form is posted from our own landing page, which already checked
+    // the referrer allowlist; anything else must match the allowlist itself.
+    let referer = headers.get(header::REFERER).and_then(|h| h.to_str().ok());
+    let request_host = headers
+        .get(header::HOST)
+        .and_then(|h| h.to_str().ok())
+        .and_then(|h| h.split(':').next())
+        .map(str::to_lowercase)
+        .unwrap_or_default();
+    let from_landing_page =
+        access_control::restrictions::referrer_allowed(&[request_host], referer);
+    if !from_landing_page && !key_data.allows_referrer(referer) {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    if ac.has_passphrase {
+        let passphrase_hash = state
+            .repo
+            .get_passphrase_hash(ac.id)
+            .await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+            .unwrap_or_default();
+        let passphrase = form.passphrase.unwrap_or_default();
+
+        if !restrictions::verify_passphrase(passphrase, passphrase_hash).await {
+            warn!(
+                event = "access_code_passphrase_failed",
+                code = %ac.code,
+                ip = ?client_ip(&headers),
+                "Wrong passphrase for access code"
+            );
+            let page = render_preview(
+                &state,
+                &ac,
+                &headers,
+                false,
+                Some("Incorrect passphrase".to_string()),
+            )
+            .await?;
+            return Ok((StatusCode::UNAUTHORIZED, page).into_response());
+        }
+    }
+
+    let token = restrictions::generate_claim_token();
+    let token_hash = hash_claim_token(&token);
+    let ip_address = client_ip(&headers);
+    let user_agent = headers
+        .get(header::USER_AGENT)
+        .and_then(|h| h.to_str().ok());
+
+    let claimed = state
+        .repo
+        .claim_code(
+            ac.id,
+            &NewAccessCodeClaim {
+                token_hash: &token_hash,
+                ip_address: ip_address.as_deref(),
+                user_agent,
+                referer,
+            },
+        )
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    if !claimed {
+        warn!(
+            event = "access_code_exhausted",
+            code = %ac.code,
+            "Access code claim refused: use limit reached or already burned"
+        );
+        return Err(StatusCode::GONE);
+    }
+
+    info!(
+        event = "access_code_claimed",
+        code = %ac.code,
+        ip = ?ip_address,
+        "Access code claimed"
+    );
+
+    let secure = headers
+        .get("x-forwarded-proto")
+        .and_then(|h| h.to_str().ok())
+        == Some("https");
+    Ok((
+        [(
+            header::SET_COOKIE,
+            restrictions::claim_cookie(ac.id, &token, secure),
+        )],
+        Redirect::to(&preview_url),
+    )
+        .into_response())
+}
+
+/// Load an active, unexpired code and check the requester's IP against it.
+async fn load_shared_code(
+    state: &AccessCodeState,
+    code: &str,
+    headers: &HeaderMap,
+) -> Result<(AccessCode, AccessKeyData), StatusCode> {
     let ac = state
         .repo
-        .get_active_code(&code)
+        .get_active_code(code)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    let key_data = state
+        .access_control
+        .repository()
+        .get_access_key_data(code)
         .await
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
         .ok_or(StatusCode::NOT_FOUND)?;
 
-    let id = ac.id;
-    let code_name = ac.code.clone();
-    let description = ac.description.clone();
-    let expires_at = ac.expires_at.clone();
+    if key_data.is_expired() || key_data.is_view_limit_reached() {
+        return Err(StatusCode::GONE); // 410 Gone for expired or used-up codes
+    }
 
-    // Check if expired
-    if let Some(ref exp) = expires_at {
-        if let Ok(exp_dt) =
-            OffsetDateTime::parse(exp, &time::format_description::well_known::Iso8601::DEFAULT)
-        {
-            if exp_dt < OffsetDateTime::now_utc() {
-                return Err(StatusCode::GONE); // 410 Gone for expired codes
-            }
-            false
-        } else {
-            false
-        }
-    } else {
-        false
+    let ip = client_ip(headers);
+    if !key_data.allows_ip(ip.as_deref()) {
+        warn!(
+            event = "access_denied",
+            code = %code,
+            ip = ?ip,
+            reason = "ip_not_allowed",
+            "Access code opened from an IP outside its allowlist"
+        );
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    Ok((ac, key_data))
+}
+
+/// Whether this browser presents a claim cookie issued for the code.
+async fn has_valid_claim(
+    state: &AccessCodeState,
+    key_data: &AccessKeyData,
+    headers: &HeaderMap,
+) -> Result<bool, StatusCode> {
+    let tokens = claim_tokens_from_headers(headers);
+    let Some((_, token)) = tokens.iter().find(|(id, _)| *id == key_data.id) else {
+        return Ok(false);
     };
+    state
+        .access_control
+        .repository()
+        .access_key_claim_exists(key_data, token)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+/// Whether no further claims are possible.
+fn uses_exhausted(ac: &AccessCode) -> bool {
+    (ac.burn_after_claim && ac.current_uses > 0)
+        || ac.max_uses.is_some_and(|max| ac.current_uses >= max)
+}
 
+async fn render_preview(
+    state: &AccessCodeState,
+    ac: &AccessCode,
+    headers: &HeaderMap,
+    claimed: bool,
+    error: Option<String>,
+) -> Result<Html<String>, StatusCode> {
     // Get permissions then enrich with media_items data for display
     let perms = state
         .repo
-        .get_permissions(id)
+        .get_permissions(ac.id)
         .await
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let resource_count = perms.len();
 
+    // Item titles are only shown once the code is claimed
     let mut resources = Vec::new();
-    for p in perms {
+    for p in perms.into_iter().filter(|_| claimed) {
         let title: Option<String> = state
             .media_repo
             .get_media_title(&p.media_slug, &p.media_type)
@@ -375,12 +605,16 @@ pub async fn preview_access_code_page(
 
     let template = PreviewTemplate {
         authenticated: false, // Preview page is public
-        code: code_name,
-        description: description.clone().unwrap_or_default(),
-        has_description: description.is_some(),
-        resource_count: resources.len(),
+        code: ac.code.clone(),
+        description: ac.description.clone().unwrap_or_default(),
+        has_description: ac.description.is_some(),
+        resource_count,
         resources,
-        base_url: get_base_url(&headers),
+        base_url: get_base_url(headers),
+        claimed,
+        needs_passphrase: ac.has_passphrase,
+        burn_after_claim: ac.burn_after_claim,
+        error,
     };
 
     let html = template
@@ -427,6 +661,16 @@ pub async fn create_access_code(
         return Err(StatusCode::BAD_REQUEST);
     }
 
+    // Validate optional passphrase, limits and allowlists
+    let validated = restrictions::validate_restrictions(&request).map_err(|reason| {
+        warn!(
+            event = "invalid_request",
+            reason = %reason,
+            "Invalid access code restrictions"
+        );
+        StatusCode::BAD_REQUEST
+    })?;
+
     // Check if code already exists
     let exists = state
         .repo
@@ -462,6 +706,15 @@ pub async fn create_access_code(
         dt.format(&time::format_description::well_known::Iso8601::DEFAULT)
             .unwrap()
     });
+    let passphrase_hash = match validated.passphrase.clone() {
+        Some(passphrase) => Some(restrictions::hash_passphrase(passphrase).await.map_err(
+            |e| {
+                warn!(error = %e, "Failed to hash access code passphrase");
+                StatusCode::INTERNAL_SERVER_ERROR
+            },
+        )?),
+        None => None,
+    };
     let code_id = state
         .repo
         .create_code(
@@ -470,6 +723,14 @@ pub async fn create_access_code(
             expires_at_str.as_deref(),
             &user_id,
             request.vault_id.as_deref(),
+            &AccessCodeRestrictions {
+                passphrase_hash: passphrase_hash.as_deref(),
+                max_uses: validated.max_uses,
+                max_views: validated.max_views,
+                allowed_ips: validated.allowed_ips.as_deref(),
+                allowed_referrers: validated.allowed_referrers.as_deref(),
+                burn_after_claim: validated.burn_after_claim,
+            },
         )
         .await
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
@@ -484,14 +745,7 @@ pub async fn create_access_code(
             vault_id = ?request.vault_id,
             "Folder-scoped access code created"
         );
-        return Ok(Json(AccessCodeResponse {
-            id: code_id,
-            code: request.code,
-            description: request.description,
-            expires_at: request.expires_at,
-            created_at: OffsetDateTime::now_utc().to_string(),
-            media_items: vec![],
-        }));
+        return Ok(Json(created_response(code_id, request, &validated, vec![])));
     }
 
     // Insert permissions (only for owned media)
@@ -569,14 +823,33 @@ pub async fn create_access_code(
         "Access code created successfully"
     );
 
-    Ok(Json(AccessCodeResponse {
+    let media_items = request.media_items.clone();
+    Ok(Json(created_response(code_id, request, &validated, media_items)))
+}
+
+/// Response for a freshly created code.
+fn created_response(
+    code_id: i32,
+    request: CreateAccessCodeRequest,
+    validated: &restrictions::ValidatedRestrictions,
+    media_items: Vec<MediaItem>,
+) -> AccessCodeResponse {
+    AccessCodeResponse {
         id: code_id,
         code: request.code,
         description: request.description,
         expires_at: request.expires_at,
         created_at: OffsetDateTime::now_utc().to_string(),
-        media_items: request.media_items,
-    }))
+        media_items,
+        has_passphrase: validated.passphrase.is_some(),
+        max_uses: validated.max_uses,
+        current_uses: 0,
+        max_views: validated.max_views,
+        current_views: 0,
+        allowed_ips: split_list(&validated.allowed_ips),
+        allowed_referrers: split_list(&validated.allowed_referrers),
+        burn_after_claim: validated.burn_after_claim,
+    }
 }
 
 #[tracing::instrument(skip(session, state))]
@@ -626,6 +899,14 @@ pub async fn list_access_codes(
 
         access_codes.push(AccessCodeResponse {
             id: ac.id,
+            has_passphrase: ac.has_passphrase,
+            max_uses: ac.max_uses,
+            current_uses: ac.current_uses,
+            max_views: ac.max_views,
+            current_views: ac.current_views,
+            allowed_ips: split_list(&ac.allowed_ips),
+            allowed_referrers: split_list(&ac.allowed_referrers),
+            burn_after_claim: ac.burn_after_claim,
             code: ac.code,
             description: ac.description,
             expires_at: ac.expires_at,
@@ -742,10 +1023,11 @@ pub async fn list_access_codes_page(
         // Get permissions then enrich with media_items data for display
         let media_items = enrich_permissions(&state, ac.id).await?;
 
+        let restrictions = describe_restrictions(&ac);
         let description = ac.description;
         let expires_at = ac.expires_at;
         let created_at = ac.created_at;
-        let current_downloads = ac.current_downloads;
+        let current_views = ac.current_views;
 
         // Check if expired
         let is_expired = if let Some(ref exp) = expires_at {
@@ -787,8 +1069,9 @@ pub async fn list_access_codes_page(
             is_group_code: false, // For now, all are individual codes
             group_name: String::new(),
             resource_count: media_items.len(),
-            usage_count: current_downloads as usize,
+            usage_count: current_views as usize,
             media_items,
+            restrictions,
         });
     }
 
@@ -842,6 +1125,42 @@ async fn enrich_permissions(
     Ok(items)
 }
 
+/// Split a stored newline-separated allowlist.
+fn split_list(value: &Option<String>) -> Vec<String> {
+    value
+        .as_deref()
+        .map(|v| v.lines().map(str::to_string).collect())
+        .unwrap_or_default()
+}
+
+/// Human-readable summary of a code's restrictions for the code pages.
+fn describe_restrictions(ac: &AccessCode) -> Vec<String> {
+    let mut lines = Vec::new();
+    if ac.has_passphrase {
+        lines.push("Passphrase protected".to_string());
+    }
+    if ac.burn_after_claim {
+        lines.push("Burns after the first claim".to_string());
+    }
+    match ac.max_uses {
+        Some(max) => lines.push(format!("Claimed {} of {} times", ac.current_uses, max)),
+        None if ac.requires_claim() => lines.push(format!("Claimed {} times", ac.current_uses)),
+        None => {}
+    }
+    if let Some(max) = ac.max_views {
+        lines.push(format!("Viewed {} of {} times", ac.current_views, max));
+    }
+    let ips = split_list(&ac.allowed_ips);
+    if !ips.is_empty() {
+        lines.push(format!("Only from IPs: {}", ips.join(", ")));
+    }
+    let referrers = split_list(&ac.allowed_referrers);
+    if !referrers.is_empty() {
+        lines.push(format!("Only via links on: {}", referrers.join(", ")));
+    }
+    lines
+}
+
 /// Extract base URL from request headers
 /// Falls back to localhost:3000 for development if headers are missing
 fn get_base_url(headers: &HeaderMap) -> String {
@@ -1069,6 +1388,10 @@ pub fn access_code_routes(state: Arc<AccessCodeState>) -> Router {
         .route("/api/access-codes", get(list_access_codes))
         .route("/api/access-codes/{code}", delete(delete_access_code))
         .route("/api/access-codes/{code}/media", post(add_media_to_code))
+        .route(
+            "/api/access-codes/{code}/analytics",
+            get(analytics::access_code_analytics),
+        )
         .route(
             "/api/access-codes/{code}/media/{slug}",
             delete(remove_media_from_code),
@@ -1077,6 +1400,10 @@ pub fn access_code_routes(state: Arc<AccessCodeState>) -> Router {
         .route("/access/codes", get(list_access_codes_page))
         .route("/access/codes/new", get(new_access_code_page))
         .route("/access/codes/{code}", get(view_access_code_page))
+        .route(
+            "/access/codes/{code}/analytics",
+            get(analytics::access_code_analytics_page),
+        )
         .with_state(state)
 }
 
@@ -1085,6 +1412,9 @@ pub fn access_code_routes(state: Arc<AccessCodeState>) -> Router {
 /// shared access-code links.
 pub fn access_code_public_routes(state: Arc<AccessCodeState>) -> Router {
     Router::new()
-        .route("/access/preview", get(preview_access_code_page))
+        .route(
+            "/access/preview",
+            get(preview_access_code_page).post(claim_access_code),
+        )
         .with_state(state)
 }
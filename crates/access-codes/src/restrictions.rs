@@ -0,0 +1,210 @@
+//! Passphrases, limits and allowlists of access codes.
+//!
+//! Validates the restriction fields of a create request, hashes passphrases
+//! with Argon2id and builds the claim cookie handed out on the landing page.
+//! Enforcement on item access lives in the access-control key layer
+//! (`access_control::restrictions`).
+
+use crate::CreateAccessCodeRequest;
+use access_control::restrictions::{claim_cookie_name, parse_ip_entry};
+use argon2::password_hash::{rand_core::OsRng, PasswordHash, SaltString};
+use argon2::{Argon2, PasswordHasher, PasswordVerifier};
+use rand::Rng;
+
+/// Maximum accepted passphrase length, in characters
+const MAX_PASSPHRASE_LENGTH: usize = 200;
+
+/// How long a claim cookie is kept by the browser
+const CLAIM_COOKIE_MAX_AGE_SECS: u64 = 30 * 24 * 60 * 60;
+
+/// Restriction fields of a create request, validated and normalized for storage.
+#[derive(Debug, Default)]
+pub(crate) struct ValidatedRestrictions {
+    pub passphrase: Option<String>,
+    pub max_uses: Option<i64>,
+    pub max_views: Option<i64>,
+    /// Newline-separated
+    pub allowed_ips: Option<String>,
+    /// Newline-separated
+    pub allowed_referrers: Option<String>,
+    pub burn_after_claim: bool,
+}
+
+/// Validate the optional restrictions of a create request.
+pub(crate) fn validate_restrictions(
+    request: &CreateAccessCodeRequest,
+) -> Result<ValidatedRestrictions, String> {
+    let passphrase = request
+        .passphrase
+        .as_deref()
+        .filter(|p| !p.is_empty())
+        .map(str::to_string);
+    if let Some(ref p) = passphrase {
+        if p.trim().is_empty() || p.chars().count() > MAX_PASSPHRASE_LENGTH {
+            return Err(format!(
+                "Passphrase must be 1-{} characters and not blank",
+                MAX_PASSPHRASE_LENGTH
+            ));
+        }
+    }
+
+    for (name, limit) in [
+        ("max_uses", request.max_uses),
+        ("max_views", request.max_views),
+    ] {
+        if limit.is_some_and(|l| l < 1) {
+            return Err(format!("{} must be at least 1", name));
+        }
+    }
+
+    let allowed_ips = normalize_list(&request.allowed_ips);
+    if let Some(bad) = allowed_ips.iter().find(|e| parse_ip_entry(e).is_none()) {
+        return Err(format!("Invalid IP address or CIDR range: {}", bad));
+    }
+
+    let allowed_referrers = normalize_list(&request.allowed_referrers);
+    if let Some(bad) = allowed_referrers.iter().find(|e| !is_valid_host_pattern(e)) {
+        return Err(format!("Invalid referrer host: {}", bad));
+    }
+
+    Ok(ValidatedRestrictions {
+        passphrase,
+        max_uses: request.max_uses,
+        max_views: request.max_views,
+        allowed_ips: join_list(allowed_ips),
+        allowed_referrers: join_list(allowed_referrers),
+        burn_after_claim: request.burn_after_claim,
+    })
+}
+
+fn normalize_list(entries: &[String]) -> Vec<String> {
+    entries
+        .iter()
+        .map(|e| e.trim().to_lowercase())
+        .filter(|e| !e.is_empty())
+        .collect()
+}
+
+fn join_list(entries: Vec<String>) -> Option<String> {
+    (!entries.is_empty()).then(|| entries.join("\n"))
+}
+
+/// A host name, optionally prefixed with `*.` to include subdomains.
+fn is_valid_host_pattern(entry: &str) -> bool {
+    let host = entry.strip_prefix("*.").unwrap_or(entry);
+    !host.is_empty()
+        && !host.starts_with(['.', '-'])
+        && !host.ends_with(['.', '-'])
+        && host
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '-')
+}
+
+/// Hash a passphrase with Argon2id. Argon2 is slow; runs off the async workers.
+pub(crate) async fn hash_passphrase(passphrase: String) -> Result<String, String> {
+    tokio::task::spawn_blocking(move || {
+        let salt = SaltString::generate(&mut OsRng);
+        Argon2::default()
+            .hash_password(passphrase.as_bytes(), &salt)
+            .map(|hash| hash.to_string())
+            .map_err(|e| format!("Failed to hash passphrase: {}", e))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Check a passphrase against a stored Argon2 hash.
+pub(crate) async fn verify_passphrase(passphrase: String, passphrase_hash: String) -> bool {
+    tokio::task::spawn_blocking(move || match PasswordHash::new(&passphrase_hash) {
+        Ok(parsed) => Argon2::default()
+            .verify_password(passphrase.as_bytes(), &parsed)
+            .is_ok(),
+        Err(_) => false,
+    })
+    .await
+    .unwrap_or(false)
+}
+
+/// Generate a random claim token.
+pub(crate) fn generate_claim_token() -> String {
+    const TOKEN_LENGTH: usize = 40;
+    const CHARSET: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789";
+
+    let mut rng = rand::thread_rng();
+    (0..TOKEN_LENGTH)
+        .map(|_| CHARSET[rng.gen_range(0..CHARSET.len())] as char)
+        .collect()
+}
+
+/// `Set-Cookie` value binding a claimed code to this browser.
+pub(crate) fn claim_cookie(access_code_id: i32, token: &str, secure: bool) -> String {
+    format!(
+        "{}={}; Path=/; Max-Age={}; HttpOnly; SameSite=Lax{}",
+        claim_cookie_name(access_code_id),
+        token,
+        CLAIM_COOKIE_MAX_AGE_SECS,
+        if secure { "; Secure" } else { "" }
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(json: &str) -> CreateAccessCodeRequest {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn test_validate_restrictions() {
+        let r = validate_restrictions(&request(
+            r#"{"code":"c","media_items":[],"passphrase":"open sesame","max_views":3,
+                "allowed_ips":[" 10.0.0.0/8 ","","203.0.113.7"],
+                "allowed_referrers":["Intranet.Example.com","*.partner.org"],
+                "burn_after_claim":true}"#,
+        ))
+        .unwrap();
+
+        assert_eq!(r.passphrase.as_deref(), Some("open sesame"));
+        assert_eq!(r.max_views, Some(3));
+        assert_eq!(r.allowed_ips.as_deref(), Some("10.0.0.0/8\n203.0.113.7"));
+        assert_eq!(
+            r.allowed_referrers.as_deref(),
+            Some("intranet.example.com\n*.partner.org")
+        );
+        assert!(r.burn_after_claim);
+
+        let r = validate_restrictions(&request(r#"{"code":"c","media_items":[],"passphrase":""}"#))
+            .unwrap();
+        assert!(r.passphrase.is_none());
+        assert!(r.allowed_ips.is_none());
+    }
+
+    #[test]
+    fn test_validate_restrictions_rejects_bad_input() {
+        for json in [
+            r#"{"code":"c","media_items":[],"max_uses":0}"#,
+            r#"{"code":"c","media_items":[],"passphrase":"   "}"#,
+            r#"{"code":"c","media_items":[],"allowed_ips":["10.0.0.0/40"]}"#,
+            r#"{"code":"c","media_items":[],"allowed_referrers":["https://example.com/"]}"#,
+        ] {
+            assert!(validate_restrictions(&request(json)).is_err(), "{}", json);
+        }
+    }
+
+    #[test]
+    fn test_claim_cookie() {
+        let cookie = claim_cookie(7, "tok", true);
+        assert!(cookie.starts_with("access_claim_7=tok; Path=/;"));
+        assert!(cookie.contains("HttpOnly"));
+        assert!(cookie.ends_with("; Secure"));
+        assert_eq!(generate_claim_token().len(), 40);
+    }
+
+    #[tokio::test]
+    async fn test_passphrase_round_trip() {
+        let hash = hash_passphrase("open sesame".to_string()).await.unwrap();
+        assert!(verify_passphrase("open sesame".to_string(), hash.clone()).await);
+        assert!(!verify_passphrase("wrong".to_string(), hash).await);
+    }
+}
@@ -141,6 +141,23 @@ impl AuditLogger {
         Ok(rows.into_iter().map(AuditLogEntry::from).collect())
     }
 
+    /// Get audit log entries recorded for an access key (most recent first).
+    pub async fn get_access_key_audit_log(
+        &self,
+        access_key: &str,
+        limit: i32,
+    ) -> Result<Vec<AuditLogEntry>, AccessError> {
+        let rows = self
+            .repo
+            .get_access_key_audit_log(access_key, limit)
+            .await
+            .map_err(|e| AccessError::Database {
+                message: e.to_string(),
+            })?;
+
+        Ok(rows.into_iter().map(AuditLogEntry::from).collect())
+    }
+
     /// Get all denied access attempts within a time window.
     pub async fn get_denied_attempts(
         &self,
@@ -31,7 +31,12 @@
 //! - Active status (is_active = true)
 //! - Expiration date (expires_at)
 //! - Download limits (max_downloads vs current_downloads)
-//! - Resource permissions (individual or group-wide)
+//! - View limits (max_views vs current_views; the last view is taken when a
+//!   page is counted, see `AccessControlService::record_key_view`)
+//! - Client IP allowlist
+//! - Claim cookie, for keys that must be claimed on the landing page
+//!   (passphrase, use limit, referrer allowlist or burn after first claim)
+//! - Resource permissions (individual, group-wide or folder-scoped)
 
 use crate::{
     AccessContext, AccessDecision, AccessError, AccessKeyData, AccessLayer, AccessRepository,
@@ -57,8 +62,10 @@ impl<'a> AccessKeyLayer<'a> {
     /// - Access key must be provided in context
     /// - Key must exist and be active in database
     /// - Key must not be expired
-    /// - Key must not exceed download limit
-    /// - Key must grant access to the specific resource (individual or group-wide)
+    /// - Key must not exceed download or view limit
+    /// - Client IP must be on the key's allowlist, if it has one
+    /// - Keys that require a claim must present a valid claim cookie
+    /// - Key must grant access to the specific resource (individual, group-wide or folder)
     /// - Permission granted = key's permission_level
     ///
     /// # Examples
@@ -133,6 +140,57 @@ impl<'a> AccessKeyLayer<'a> {
             .with_context(context.clone()));
         }
 
+        // Validate view limit not exceeded. Using up the last view is enforced
+        // where views are counted (`record_key_view`), so the page opened with
+        // it can still load its assets
+        if key_data.is_view_limit_exceeded() {
+            warn!("View limit exceeded for key: {}", key);
+            return Ok(AccessDecision::denied(
+                AccessLayer::AccessKey,
+                permission,
+                format!(
+                    "View limit of {} has been reached",
+                    key_data.restrictions.max_views.unwrap_or(0)
+                ),
+            )
+            .with_context(context.clone()));
+        }
+
+        // Validate client IP against the key's allowlist
+        if !key_data.allows_ip(context.ip_address.as_deref()) {
+            warn!(
+                "Access key {} used from IP {:?} outside its allowlist",
+                key, context.ip_address
+            );
+            return Ok(AccessDecision::denied(
+                AccessLayer::AccessKey,
+                permission,
+                "Access key is not valid from this IP address".to_string(),
+            )
+            .with_context(context.clone()));
+        }
+
+        // Restricted keys only work in the browser that claimed them
+        if key_data.requires_claim() {
+            let claimed = match context.claim_token(key_data.id) {
+                Some(token) => {
+                    self.repository
+                        .access_key_claim_exists(&key_data, token)
+                        .await?
+                }
+                None => false,
+            };
+
+            if !claimed {
+                return Ok(AccessDecision::denied(
+                    AccessLayer::AccessKey,
+                    permission,
+                    "Access key has not been claimed in this browser".to_string(),
+                )
+                .with_context(context.clone()));
+            }
+        }
+
         // Check if key grants access to this specific resource
         let grants_access = self
             .repository
@@ -222,7 +280,8 @@ mod tests {
                 is_public BOOLEAN NOT NULL DEFAULT 0,
                 media_type TEXT NOT NULL,
                 slug TEXT NOT NULL,
-                status TEXT NOT NULL DEFAULT 'active'
+                status TEXT NOT NULL DEFAULT 'active',
+                vault_id TEXT
             )",
         )
         .execute(&pool)
@@ -241,7 +300,16 @@ mod tests {
                 max_downloads INTEGER,
                 current_downloads INTEGER NOT NULL DEFAULT 0,
                 is_active BOOLEAN NOT NULL DEFAULT 1,
-                last_accessed_at TEXT
+                last_accessed_at TEXT,
+                vault_id TEXT,
+                passphrase_hash TEXT,
+                max_uses INTEGER,
+                current_uses INTEGER NOT NULL DEFAULT 0,
+                max_views INTEGER,
+                current_views INTEGER NOT NULL DEFAULT 0,
+                allowed_ips TEXT,
+                allowed_referrers TEXT,
+                burn_after_claim BOOLEAN NOT NULL DEFAULT 0
             )",
         )
         .execute(&pool)
@@ -260,6 +328,21 @@ mod tests {
         .await
         .unwrap();
 
+        sqlx::query(
+            "CREATE TABLE access_code_claims (
+                id INTEGER PRIMARY KEY,
+                access_code_id INTEGER NOT NULL,
+                token_hash TEXT NOT NULL UNIQUE,
+                ip_address TEXT,
+                user_agent TEXT,
+                referer TEXT,
+                created_at TEXT NOT NULL DEFAULT (datetime('now'))
+            )",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
         pool
     }
 
@@ -599,4 +682,138 @@ mod tests {
         let key_data = layer.get_key_data("inactive-key").await.unwrap();
         assert!(key_data.is_none()); // Inactive keys are not returned
     }
+
+    #[tokio::test]
+    async fn test_restricted_key_requires_claim() {
+        let pool = setup_test_db().await;
+        let repo = AccessRepository::new(Arc::new(db_sqlite::SqliteDatabase::new(pool.clone())));
+        let layer = AccessKeyLayer::new(&repo);
+
+        sqlx::query("INSERT INTO media_items (id, title, user_id, is_public, media_type, slug) VALUES (1, 'Video', 'user123', 0, 'video', 'test-video-1')")
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query(
+            "INSERT INTO access_codes (id, code, description, permission_level, burn_after_claim, allowed_ips)
+             VALUES (1, 'burn-key', 'Burn', 'read', 1, '10.0.0.0/8')",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        sqlx::query("INSERT INTO access_code_permissions (id, access_code_id, media_type, media_slug) VALUES (1, 1, 'video', 'test-video-1')")
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO access_code_claims (access_code_id, token_hash) VALUES (1, ?)")
+            .bind(crate::restrictions::hash_claim_token("claim-token"))
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let mut context = AccessContext::new(ResourceType::Video, 1)
+            .with_key("burn-key")
+            .with_ip("10.1.2.3");
+
+        // Not claimed in this browser
+        let decision = layer.check(&context, Permission::Read).await.unwrap();
+        assert!(!decision.granted);
+        assert!(decision.reason.contains("not been claimed"));
+
+        context.claim_tokens = vec![(1, "wrong-token".to_string())];
+        let decision = layer.check(&context, Permission::Read).await.unwrap();
+        assert!(!decision.granted);
+
+        context.claim_tokens = vec![(1, "claim-token".to_string())];
+        let decision = layer.check(&context, Permission::Read).await.unwrap();
+        assert!(decision.granted);
+
+        // Outside the IP allowlist
+        let context = context.with_ip("192.168.1.1");
+        let decision = layer.check(&context, Permission::Read).await.unwrap();
+        assert!(!decision.granted);
+        assert!(decision.reason.contains("IP address"));
+    }
+
+    #[tokio::test]
+    async fn test_view_limit_and_folder_scope() {
+        let pool = setup_test_db().await;
+        let repo = AccessRepository::new(Arc::new(db_sqlite::SqliteDatabase::new(pool.clone())));
+        let layer = AccessKeyLayer::new(&repo);
+
+        sqlx::query("INSERT INTO media_items (id, title, user_id, is_public, media_type, slug, vault_id) VALUES (1, 'In vault', 'user123', 0, 'video', 'in-vault', 'vault-a'), (2, 'Elsewhere', 'user123', 0, 'video', 'elsewhere', 'vault-b')")
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query(
+            "INSERT INTO access_codes (id, code, description, permission_level, vault_id, max_views)
+             VALUES (1, 'folder-key', 'Folder', 'read', 'vault-a', 1)",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let in_vault = AccessContext::new(ResourceType::Video, 1).with_key("folder-key");
+        let elsewhere = AccessContext::new(ResourceType::Video, 2).with_key("folder-key");
+
+        assert!(layer.check(&in_vault, Permission::Read).await.unwrap().granted);
+        assert!(!layer.check(&elsewhere, Permission::Read).await.unwrap().granted);
+
+        // The single allowed view is used up; the page it opened keeps loading
+        assert!(repo.increment_view_count("folder-key").await.unwrap());
+        assert!(!repo.increment_view_count("folder-key").await.unwrap());
+        assert!(layer.check(&in_vault, Permission::Read).await.unwrap().granted);
+
+        // Lowering the limit below the views already counted cuts access off
+        sqlx::query("UPDATE access_codes SET max_views = 0 WHERE code = 'folder-key'")
+            .execute(&pool)
+            .await
+            .unwrap();
+        let decision = layer.check(&in_vault, Permission::Read).await.unwrap();
+        assert!(!decision.granted);
+        assert!(decision.reason.contains("View limit"));
+    }
+
+    #[tokio::test]
+    async fn test_view_limit_page_then_assets() {
+        let pool = setup_test_db().await;
+        let database = Arc::new(db_sqlite::SqliteDatabase::new(pool.clone()));
+        let service = crate::AccessControlService::with_audit_enabled(
+            database.clone(),
+            database,
+            false,
+        );
+
+        sqlx::query("INSERT INTO media_items (id, title, user_id, is_public, media_type, slug) VALUES (1, 'Clip', 'user123', 0, 'video', 'clip')")
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query(
+            "INSERT INTO access_codes (id, code, description, permission_level, max_views)
+             VALUES (1, 'one-view', 'One view', 'read', 1)",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        sqlx::query("INSERT INTO access_code_permissions (id, access_code_id, media_type, media_slug) VALUES (1, 1, 'video', 'clip')")
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let context = AccessContext::new(ResourceType::Video, 1).with_key("one-view");
+
+        // Opening the player page takes the only view
+        let page = service.check_access(context.clone(), Permission::Read).await.unwrap();
+        assert!(page.granted);
+        assert!(service.record_key_view(&page, Some("one-view")).await);
+
+        // Playlists and segments of that page are still served
+        for _ in 0..3 {
+            let asset = service.check_access(context.clone(), Permission::Read).await.unwrap();
+            assert!(asset.granted, "{}", asset.reason);
+        }
+
+        // Opening the page again is refused
+        let page = service.check_access(context.clone(), Permission::Read).await.unwrap();
+        assert!(!service.record_key_view(&page, Some("one-view")).await);
+    }
 }
@@ -68,7 +68,8 @@
 //! # Security
 //!
 //! - All database queries use parameterized statements
-//! - Access keys validated for expiration and download limits
+//! - Access keys validated for expiration, download/view limits, IP allowlists and claims
+//! - Time-limited, HMAC-signed media URLs for delivery without a session
 //! - Complete audit log for compliance and security monitoring
 //! - Rate limiting support for failed access attempts
 //! - Privacy-conscious logging (no sensitive data in logs)
@@ -79,16 +80,19 @@ pub mod layers;
 pub mod models;
 pub mod permissions;
 pub mod repository;
+pub mod restrictions;
 pub mod service;
+pub mod signed_urls;
 
 // Re-export main types
 pub use audit::{AuditLogEntry, AuditLogger};
 pub use error::AccessError;
 pub use layers::{AccessKeyLayer, GroupLayer, OwnerLayer, PublicLayer};
-pub use models::{AccessContext, AccessDecision, AccessKeyData, AccessLayer};
+pub use models::{AccessContext, AccessDecision, AccessKeyData, AccessKeyRestrictions, AccessLayer};
 pub use permissions::Permission;
 pub use repository::AccessRepository;
 pub use service::AccessControlService;
+pub use signed_urls::{SignedUrlError, SignedUrlParams, UrlSigner};
 
 // Re-export for convenience
 pub use common::{GroupRole, ResourceType};
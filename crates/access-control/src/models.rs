@@ -5,8 +5,10 @@
 //! - `AccessContext` - Request context for access checks
 //! - `AccessLayer` - The 4 layers of access control
 //! - `AccessKeyData` - Access key information from database
+//! - `AccessKeyRestrictions` - Optional passphrase, limits and allowlists of a key
 
-use crate::Permission;
+use crate::{restrictions, Permission};
+use axum::http::{header, HeaderMap};
 use common::ResourceType;
 use serde::{Deserialize, Serialize};
 use std::fmt;
@@ -162,6 +164,9 @@ pub struct AccessContext {
     /// HTTP referer header
     pub referer: Option<String>,
 
+    /// Access key claim tokens from cookies, keyed by access key ID
+    pub claim_tokens: Vec<(i32, String)>,
+
     /// Timestamp of the access check
     pub timestamp: time::OffsetDateTime,
 }
@@ -176,6 +181,7 @@ impl Default for AccessContext {
             ip_address: None,
             user_agent: None,
             referer: None,
+            claim_tokens: Vec::new(),
             timestamp: time::OffsetDateTime::now_utc(),
         }
     }
@@ -192,6 +198,7 @@ impl AccessContext {
             ip_address: None,
             user_agent: None,
             referer: None,
+            claim_tokens: Vec::new(),
             timestamp: time::OffsetDateTime::now_utc(),
         }
     }
@@ -214,6 +221,29 @@ impl AccessContext {
         self
     }
 
+    /// Add client IP, user agent, referer and claim cookies from request headers
+    pub fn with_request_headers(mut self, headers: &HeaderMap) -> Self {
+        let header_str = |name| {
+            headers
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .map(str::to_string)
+        };
+        self.ip_address = restrictions::client_ip(headers);
+        self.user_agent = header_str(header::USER_AGENT);
+        self.referer = header_str(header::REFERER);
+        self.claim_tokens = restrictions::claim_tokens_from_headers(headers);
+        self
+    }
+
+    /// Claim token presented for an access key, if any
+    pub fn claim_token(&self, access_code_id: i32) -> Option<&str> {
+        self.claim_tokens
+            .iter()
+            .find(|(id, _)| *id == access_code_id)
+            .map(|(_, token)| token.as_str())
+    }
+
     /// Check if request is authenticated
     pub fn is_authenticated(&self) -> bool {
         self.user_id.is_some()
@@ -381,6 +411,40 @@ pub struct AccessKeyData {
 
     /// Whether the key is active
     pub is_active: bool,
+
+    /// Folder (vault) this key grants access to, instead of individual resources
+    pub vault_id: Option<String>,
+
+    /// Optional passphrase, limits and allowlists
+    pub restrictions: AccessKeyRestrictions,
+}
+
+/// Optional restrictions on an access key
+///
+/// See the [`restrictions`](crate::restrictions) module for how claims and
+/// allowlists are checked.
+#[derive(Debug, Clone, Default)]
+pub struct AccessKeyRestrictions {
+    /// The key must be unlocked with a passphrase when claimed
+    pub has_passphrase: bool,
+
+    /// Maximum number of claims
+    pub max_uses: Option<i32>,
+
+    /// Maximum number of item views
+    pub max_views: Option<i32>,
+
+    /// Current view count
+    pub current_views: i32,
+
+    /// Allowed client IPs or CIDR ranges (empty = any)
+    pub allowed_ips: Vec<String>,
+
+    /// Allowed referrer hosts for claims (empty = any)
+    pub allowed_referrers: Vec<String>,
+
+    /// The key can only be claimed once
+    pub burn_after_claim: bool,
 }
 
 impl AccessKeyData {
@@ -405,9 +469,51 @@ impl AccessKeyData {
         false
     }
 
+    /// Check if view limit has been reached
+    pub fn is_view_limit_reached(&self) -> bool {
+        if let Some(max) = self.restrictions.max_views {
+            return self.restrictions.current_views >= max;
+        }
+        false
+    }
+
+    /// Check if more views were counted than the limit allows.
+    ///
+    /// Views are counted atomically up to the limit when a page is opened,
+    /// so the assets of a page opened with the last view keep loading; this
+    /// only trips once the limit is lowered below the views already counted.
+    pub fn is_view_limit_exceeded(&self) -> bool {
+        if let Some(max) = self.restrictions.max_views {
+            return self.restrictions.current_views > max;
+        }
+        false
+    }
+
     /// Check if key is valid (active, not expired, not over limit)
     pub fn is_valid(&self) -> bool {
-        self.is_active && !self.is_expired() && !self.is_limit_exceeded()
+        self.is_active
+            && !self.is_expired()
+            && !self.is_limit_exceeded()
+            && !self.is_view_limit_reached()
+    }
+
+    /// Check if the key must be claimed before its resources can be opened
+    pub fn requires_claim(&self) -> bool {
+        let r = &self.restrictions;
+        r.has_passphrase
+            || r.burn_after_claim
+            || r.max_uses.is_some()
+            || !r.allowed_referrers.is_empty()
+    }
+
+    /// Check a client IP against the key's IP allowlist
+    pub fn allows_ip(&self, ip: Option<&str>) -> bool {
+        restrictions::ip_allowed(&self.restrictions.allowed_ips, ip)
+    }
+
+    /// Check a referer against the key's referrer allowlist
+    pub fn allows_referrer(&self, referer: Option<&str>) -> bool {
+        restrictions::referrer_allowed(&self.restrictions.allowed_referrers, referer)
     }
 
     /// Get remaining downloads (None if unlimited)
@@ -511,6 +617,8 @@ mod tests {
             max_downloads: Some(10),
             current_downloads: 5,
             is_active: true,
+            vault_id: None,
+            restrictions: AccessKeyRestrictions::default(),
         };
 
         assert!(key_data.is_valid());
@@ -545,6 +653,8 @@ mod tests {
             max_downloads: None,
             current_downloads: 0,
             is_active: true,
+            vault_id: None,
+            restrictions: AccessKeyRestrictions::default(),
         };
 
         assert!(expired_key.is_expired());
@@ -572,6 +682,8 @@ mod tests {
             max_downloads: None,
             current_downloads: 0,
             is_active: true,
+            vault_id: None,
+            restrictions: AccessKeyRestrictions::default(),
         };
 
         assert!(group_key.is_group_key());
@@ -646,6 +758,8 @@ mod tests {
             max_downloads: Some(10),
             current_downloads: 3,
             is_active: true,
+            vault_id: None,
+            restrictions: AccessKeyRestrictions::default(),
         };
 
         assert_eq!(key.remaining_downloads(), Some(7));
@@ -657,4 +771,52 @@ mod tests {
 
         assert_eq!(unlimited.remaining_downloads(), None);
     }
+
+    #[test]
+    fn test_access_key_restrictions() {
+        let mut key = AccessKeyData {
+            id: 1,
+            key: "restricted".to_string(),
+            description: "Restricted".to_string(),
+            permission_level: Permission::Read,
+            access_group_id: None,
+            share_all_group_resources: false,
+            expires_at: None,
+            max_downloads: None,
+            current_downloads: 0,
+            is_active: true,
+            vault_id: None,
+            restrictions: AccessKeyRestrictions {
+                max_views: Some(2),
+                current_views: 1,
+                allowed_ips: vec!["10.0.0.0/8".to_string()],
+                ..Default::default()
+            },
+        };
+
+        assert!(!key.requires_claim());
+        assert!(!key.is_view_limit_reached());
+        assert!(key.allows_ip(Some("10.1.1.1")));
+        assert!(!key.allows_ip(Some("192.168.1.1")));
+
+        key.restrictions.current_views = 2;
+        assert!(key.is_view_limit_reached());
+        assert!(!key.is_view_limit_exceeded());
+        assert!(!key.is_valid());
+
+        key.restrictions.max_views = Some(1);
+        assert!(key.is_view_limit_exceeded());
+
+        key.restrictions.burn_after_claim = true;
+        assert!(key.requires_claim());
+    }
+
+    #[test]
+    fn test_claim_token_lookup() {
+        let mut context = AccessContext::new(ResourceType::Video, 1);
+        context.claim_tokens = vec![(3, "abc".to_string())];
+
+        assert_eq!(context.claim_token(3), Some("abc"));
+        assert_eq!(context.claim_token(4), None);
+    }
 }
@@ -3,7 +3,7 @@
 //! This module provides a clean, type-safe interface to the database,
 //! delegating to the `db::access_control::AccessControlRepository` trait.
 
-use crate::{AccessError, AccessKeyData, Permission};
+use crate::{restrictions, AccessError, AccessKeyData, AccessKeyRestrictions, Permission};
 use common::{GroupRole, ResourceType};
 use db::access_control::AccessControlRepository;
 use std::str::FromStr;
@@ -14,6 +14,7 @@ fn resource_type_str(rt: ResourceType) -> &'static str {
     match rt {
         ResourceType::Video => "video",
         ResourceType::Image => "image",
+        ResourceType::Audio => "audio",
         ResourceType::File => "document",
         ResourceType::Folder => "folder",
     }
@@ -146,6 +147,18 @@ impl AccessRepository {
                     max_downloads: r.max_downloads,
                     current_downloads: r.current_downloads,
                     is_active: r.is_active,
+                    vault_id: r.vault_id,
+                    restrictions: AccessKeyRestrictions {
+                        has_passphrase: r.has_passphrase,
+                        max_uses: r.max_uses,
+                        max_views: r.max_views,
+                        current_views: r.current_views,
+                        allowed_ips: restrictions::parse_allowlist(r.allowed_ips.as_deref()),
+                        allowed_referrers: restrictions::parse_allowlist(
+                            r.allowed_referrers.as_deref(),
+                        ),
+                        burn_after_claim: r.burn_after_claim,
+                    },
                 }))
             }
             None => Ok(None),
@@ -169,6 +182,18 @@ impl AccessRepository {
 
         let rt_str = resource_type_str(resource_type);
 
+        // Folder-scoped key: the resource must be stored in the key's vault
+        if let Some(vault_id) = &key_data.vault_id {
+            let resource_vault = self
+                .repo
+                .get_resource_vault(rt_str, resource_id)
+                .await
+                .map_err(|e| AccessError::Database {
+                    message: e.to_string(),
+                })?;
+            return Ok(resource_vault.as_ref() == Some(vault_id));
+        }
+
         // Get slug for this resource
         let slug = self
             .repo
@@ -201,6 +226,30 @@ impl AccessRepository {
             })
     }
 
+    /// Count a view for an access key. Returns false if its view limit is reached.
+    pub async fn increment_view_count(&self, key: &str) -> Result<bool, AccessError> {
+        self.repo
+            .increment_view_count(key)
+            .await
+            .map_err(|e| AccessError::Database {
+                message: e.to_string(),
+            })
+    }
+
+    /// Check if a claim token was issued for an access key.
+    pub async fn access_key_claim_exists(
+        &self,
+        key_data: &AccessKeyData,
+        token: &str,
+    ) -> Result<bool, AccessError> {
+        self.repo
+            .access_code_claim_exists(key_data.id, &restrictions::hash_claim_token(token))
+            .await
+            .map_err(|e| AccessError::Database {
+                message: e.to_string(),
+            })
+    }
+
     /// Check if a resource exists.
     pub async fn resource_exists(
         &self,
@@ -0,0 +1,211 @@
+//! Access key restrictions
+//!
+//! Helpers for the optional restrictions an access key can carry:
+//!
+//! - **Claims** - keys with a passphrase, use limit, referrer allowlist or
+//!   burn-after-claim flag must be claimed on the landing page first. The
+//!   claim sets an `access_claim_{id}` cookie; only the SHA-256 of its token
+//!   is stored, and every item access via the key must present it.
+//! - **IP allowlists** - exact addresses or CIDR ranges, IPv4 and IPv6
+//! - **Referrer allowlists** - host names; `*.example.com` also matches
+//!   subdomains
+//!
+//! The client IP is taken from `X-Forwarded-For` / `X-Real-IP`, so IP
+//! allowlists are only meaningful behind a reverse proxy that sets them.
+
+use axum::http::{header, HeaderMap};
+use data_encoding::HEXLOWER;
+use sha2::{Digest, Sha256};
+use std::net::IpAddr;
+
+/// Prefix of the per-key claim cookie names.
+pub const CLAIM_COOKIE_PREFIX: &str = "access_claim_";
+
+/// Name of the claim cookie for an access key.
+pub fn claim_cookie_name(access_code_id: i32) -> String {
+    format!("{}{}", CLAIM_COOKIE_PREFIX, access_code_id)
+}
+
+/// Hash a claim token with SHA-256 (hex-encoded).
+pub fn hash_claim_token(token: &str) -> String {
+    HEXLOWER.encode(&Sha256::digest(token.as_bytes()))
+}
+
+/// Claim tokens sent as cookies, keyed by access key ID.
+pub fn claim_tokens_from_headers(headers: &HeaderMap) -> Vec<(i32, String)> {
+    headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(';'))
+        .filter_map(|pair| {
+            let (name, token) = pair.trim().split_once('=')?;
+            let id = name.strip_prefix(CLAIM_COOKIE_PREFIX)?.parse().ok()?;
+            Some((id, token.trim().to_string()))
+        })
+        .collect()
+}
+
+/// Client IP as reported by the reverse proxy.
+pub fn client_ip(headers: &HeaderMap) -> Option<String> {
+    let forwarded = headers
+        .get("x-forwarded-for")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.split(',').next());
+    let real_ip = || headers.get("x-real-ip").and_then(|v| v.to_str().ok());
+
+    forwarded
+        .or_else(real_ip)
+        .map(str::trim)
+        .filter(|ip| ip.parse::<IpAddr>().is_ok())
+        .map(str::to_string)
+}
+
+/// Split a stored allowlist (newline- or comma-separated) into entries.
+pub fn parse_allowlist(value: Option<&str>) -> Vec<String> {
+    value
+        .unwrap_or_default()
+        .split(['\n', ','])
+        .map(|entry| entry.trim().to_lowercase())
+        .filter(|entry| !entry.is_empty())
+        .collect()
+}
+
+/// Parse an IP allowlist entry: an address, or a CIDR range.
+pub fn parse_ip_entry(entry: &str) -> Option<(IpAddr, u8)> {
+    let (addr, prefix) = match entry.split_once('/') {
+        Some((addr, prefix)) => (addr.parse::<IpAddr>().ok()?, Some(prefix.parse().ok()?)),
+        None => (entry.parse::<IpAddr>().ok()?, None),
+    };
+    let max = if addr.is_ipv4() { 32 } else { 128 };
+    let prefix = prefix.unwrap_or(max);
+    (prefix <= max).then_some((addr.to_canonical(), prefix))
+}
+
+/// Check a client IP against an allowlist. An empty allowlist allows all.
+pub fn ip_allowed(allowlist: &[String], ip: Option<&str>) -> bool {
+    if allowlist.is_empty() {
+        return true;
+    }
+    let Some(ip) = ip.and_then(|ip| ip.parse::<IpAddr>().ok()) else {
+        return false;
+    };
+    let ip = ip.to_canonical();
+
+    allowlist
+        .iter()
+        .filter_map(|e| parse_ip_entry(e))
+        .any(|(network, prefix)| match (network, ip) {
+            (IpAddr::V4(net), IpAddr::V4(ip)) => {
+                let mask = u32::MAX.checked_shl(32 - prefix as u32).unwrap_or(0);
+                u32::from(net) & mask == u32::from(ip) & mask
+            }
+            (IpAddr::V6(net), IpAddr::V6(ip)) => {
+                let mask = u128::MAX.checked_shl(128 - prefix as u32).unwrap_or(0);
+                u128::from(net) & mask == u128::from(ip) & mask
+            }
+            _ => false,
+        })
+}
+
+/// Host part of a referer URL, lowercased and without port.
+fn referer_host(referer: &str) -> Option<String> {
+    let rest = referer.split_once("://").map(|(_, rest)| rest)?;
+    let authority = rest.split(['/', '?', '#']).next()?;
+    let host = authority.rsplit('@').next()?;
+    let host = match host.strip_prefix('[') {
+        Some(v6) => v6.split(']').next()?,
+        None => host.split(':').next()?,
+    };
+    (!host.is_empty()).then(|| host.to_lowercase())
+}
+
+/// Check a referer against a host allowlist. An empty allowlist allows all.
+pub fn referrer_allowed(allowlist: &[String], referer: Option<&str>) -> bool {
+    if allowlist.is_empty() {
+        return true;
+    }
+    let Some(host) = referer.and_then(referer_host) else {
+        return false;
+    };
+
+    allowlist
+        .iter()
+        .any(|entry| match entry.strip_prefix("*.") {
+            Some(domain) => host == domain || host.ends_with(&format!(".{}", domain)),
+            None => host == *entry,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    fn list(entries: &str) -> Vec<String> {
+        parse_allowlist(Some(entries))
+    }
+
+    #[test]
+    fn test_ip_allowlist() {
+        let allow = list("203.0.113.7\n10.0.0.0/8, 2001:db8::/32");
+
+        assert!(ip_allowed(&allow, Some("203.0.113.7")));
+        assert!(ip_allowed(&allow, Some("10.20.30.40")));
+        assert!(ip_allowed(&allow, Some("::ffff:10.1.2.3")));
+        assert!(ip_allowed(&allow, Some("2001:db8:1::1")));
+        assert!(!ip_allowed(&allow, Some("203.0.113.8")));
+        assert!(!ip_allowed(&allow, Some("2001:db9::1")));
+        assert!(!ip_allowed(&allow, None));
+        assert!(ip_allowed(&[], None));
+    }
+
+    #[test]
+    fn test_parse_ip_entry() {
+        assert!(parse_ip_entry("0.0.0.0/0").is_some());
+        assert!(parse_ip_entry("192.168.1.0/33").is_none());
+        assert!(parse_ip_entry("example.com").is_none());
+        assert_eq!(parse_ip_entry("192.168.1.1").map(|(_, p)| p), Some(32));
+    }
+
+    #[test]
+    fn test_referrer_allowlist() {
+        let allow = list("intranet.example.com\n*.partner.org");
+
+        assert!(referrer_allowed(
+            &allow,
+            Some("https://intranet.example.com/page?x=1")
+        ));
+        assert!(referrer_allowed(
+            &allow,
+            Some("http://INTRANET.example.com:8080/")
+        ));
+        assert!(referrer_allowed(&allow, Some("https://partner.org/")));
+        assert!(referrer_allowed(&allow, Some("https://docs.partner.org/a")));
+        assert!(!referrer_allowed(&allow, Some("https://evilpartner.org/")));
+        assert!(!referrer_allowed(&allow, Some("https://example.com/")));
+        assert!(!referrer_allowed(&allow, None));
+        assert!(referrer_allowed(&[], None));
+    }
+
+    #[test]
+    fn test_request_headers() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "x-forwarded-for",
+            HeaderValue::from_static("198.51.100.2, 10.0.0.1"),
+        );
+        headers.insert(
+            header::COOKIE,
+            HeaderValue::from_static("session=abc; access_claim_12=tok12; access_claim_x=bad"),
+        );
+
+        assert_eq!(client_ip(&headers).as_deref(), Some("198.51.100.2"));
+        assert_eq!(
+            claim_tokens_from_headers(&headers),
+            vec![(12, "tok12".to_string())]
+        );
+        assert_eq!(claim_cookie_name(12), "access_claim_12");
+        assert_eq!(hash_claim_token("tok12").len(), 64);
+    }
+}
@@ -7,7 +7,7 @@
 use crate::{
     layers::{AccessKeyLayer, GroupLayer, OwnerLayer, PublicLayer},
     AccessContext, AccessDecision, AccessError, AccessLayer, AccessRepository, AuditLogger,
-    Permission,
+    Permission, UrlSigner,
 };
 use common::ResourceType;
 use db::access_control::{AccessControlRepository, AuditRepository};
@@ -21,6 +21,7 @@ use tracing::{debug, info, warn};
 pub struct AccessControlService {
     repository: AccessRepository,
     audit_logger: AuditLogger,
+    url_signer: UrlSigner,
 }
 
 impl AccessControlService {
@@ -32,6 +33,7 @@ impl AccessControlService {
         Self {
             repository: AccessRepository::new(ac_repo),
             audit_logger: AuditLogger::new(audit_repo),
+            url_signer: UrlSigner::from_env(),
         }
     }
 
@@ -44,6 +46,7 @@ impl AccessControlService {
         Self {
             repository: AccessRepository::new(ac_repo),
             audit_logger: AuditLogger::with_enabled(audit_repo, audit_enabled),
+            url_signer: UrlSigner::from_env(),
         }
     }
 
@@ -239,11 +242,45 @@ impl AccessControlService {
         self.repository.increment_download_count(key).await
     }
 
+    /// Count a view of a resource opened with an access key.
+    ///
+    /// Returns false if the key's view limit was reached in the meantime.
+    pub async fn increment_view_count(&self, key: &str) -> Result<bool, AccessError> {
+        self.repository.increment_view_count(key).await
+    }
+
+    /// Count a page view if `decision` was granted through the access key layer.
+    ///
+    /// Handlers call this after opening a media page (not for every asset
+    /// request), so one view is one item opened. This is where view limits
+    /// are enforced: returns false if the key has no views left, and the page
+    /// must then be refused. Failures to count are only logged.
+    pub async fn record_key_view(&self, decision: &AccessDecision, key: Option<&str>) -> bool {
+        let Some(key) = key else {
+            return true;
+        };
+        if !decision.granted || decision.layer != AccessLayer::AccessKey {
+            return true;
+        }
+        match self.increment_view_count(key).await {
+            Ok(counted) => counted,
+            Err(e) => {
+                warn!(error = %e, "Failed to count access key view");
+                true
+            }
+        }
+    }
+
     /// Get audit logger for advanced audit operations.
     pub fn audit_logger(&self) -> &AuditLogger {
         &self.audit_logger
     }
 
+    /// Get the signer for signed media URLs.
+    pub fn url_signer(&self) -> &UrlSigner {
+        &self.url_signer
+    }
+
     /// Get repository for advanced queries.
     pub fn repository(&self) -> &AccessRepository {
         &self.repository
@@ -0,0 +1,301 @@
+//! Signed media URLs
+//!
+//! Time-limited URLs that deliver one media item without a session or an
+//! access code, e.g. for embedding in an LMS or an email. A signed URL
+//! carries three query parameters:
+//!
+//! - `expires` - unix timestamp (seconds) after which the URL stops working
+//! - `perm` - `view` ([`Permission::Read`]) or `download` ([`Permission::Download`])
+//! - `sig` - HMAC-SHA256 (hex) over the media item ID, permission and expiry
+//!
+//! The signature covers the item rather than a path, so one query string is
+//! valid for every delivery endpoint of the item, including HLS sub-playlists
+//! and segments. Endpoints that hand out the original file only accept
+//! `download` URLs, and only while the item still allows downloads.
+//!
+//! The key is read from `MEDIA_URL_SIGNING_KEY`. Without it a random key is
+//! generated at startup (see [`UrlSigner::is_ephemeral`]), and signed URLs stop
+//! working after a restart.
+
+use crate::Permission;
+use data_encoding::HEXLOWER;
+use hmac::{Hmac, Mac};
+use rand::RngCore;
+use serde::Deserialize;
+use sha2::Sha256;
+use std::fmt;
+use std::sync::Arc;
+use time::OffsetDateTime;
+use tracing::warn;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Environment variable holding the signing key
+pub const SIGNING_KEY_ENV: &str = "MEDIA_URL_SIGNING_KEY";
+
+/// Longest lifetime a signed URL may be minted with (7 days)
+pub const MAX_SIGNED_URL_TTL_SECS: i64 = 7 * 24 * 60 * 60;
+
+/// Shortest key accepted without a warning, in bytes
+const MIN_KEY_LENGTH: usize = 32;
+
+/// Signed URL query parameters, as sent by the client.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct SignedUrlParams {
+    pub expires: Option<i64>,
+    pub perm: Option<String>,
+    pub sig: Option<String>,
+}
+
+impl SignedUrlParams {
+    /// Whether the request carries a signature at all
+    pub fn is_present(&self) -> bool {
+        self.sig.is_some()
+    }
+
+    /// The parameters as a query string (without `?`), if complete
+    pub fn to_query(&self) -> Option<String> {
+        match (self.expires, &self.perm, &self.sig) {
+            (Some(expires), Some(perm), Some(sig)) => Some(format!(
+                "expires={}&perm={}&sig={}",
+                expires,
+                urlencoding::encode(perm),
+                urlencoding::encode(sig)
+            )),
+            _ => None,
+        }
+    }
+}
+
+/// Why a signed URL was rejected
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SignedUrlError {
+    /// `expires`, `perm` or `sig` is missing or not understood
+    Malformed,
+    /// The URL expired
+    Expired,
+    /// The signature does not match the item, permission and expiry
+    InvalidSignature,
+}
+
+impl fmt::Display for SignedUrlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SignedUrlError::Malformed => write!(f, "Malformed signed URL"),
+            SignedUrlError::Expired => write!(f, "Signed URL has expired"),
+            SignedUrlError::InvalidSignature => write!(f, "Invalid URL signature"),
+        }
+    }
+}
+
+impl std::error::Error for SignedUrlError {}
+
+/// Name of a signable permission in the `perm` parameter.
+pub fn permission_name(permission: Permission) -> Option<&'static str> {
+    match permission {
+        Permission::Read => Some("view"),
+        Permission::Download => Some("download"),
+        _ => None,
+    }
+}
+
+/// Parse the `perm` parameter (`view` or `download`).
+pub fn parse_permission(name: &str) -> Option<Permission> {
+    match name {
+        "view" => Some(Permission::Read),
+        "download" => Some(Permission::Download),
+        _ => None,
+    }
+}
+
+/// Mints and verifies signed media URLs.
+#[derive(Clone)]
+pub struct UrlSigner {
+    key: Arc<[u8]>,
+    ephemeral: bool,
+}
+
+impl fmt::Debug for UrlSigner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("UrlSigner").finish_non_exhaustive()
+    }
+}
+
+impl UrlSigner {
+    /// Create a signer with the given key.
+    pub fn new(key: &[u8]) -> Self {
+        Self {
+            key: key.into(),
+            ephemeral: false,
+        }
+    }
+
+    /// Create a signer with the key from `MEDIA_URL_SIGNING_KEY`, or a
+    /// random one if it is not set.
+    pub fn from_env() -> Self {
+        match std::env::var(SIGNING_KEY_ENV) {
+            Ok(key) if !key.is_empty() => {
+                if key.len() < MIN_KEY_LENGTH {
+                    warn!(
+                        "{} is shorter than {} bytes; signed URLs are easier to forge",
+                        SIGNING_KEY_ENV, MIN_KEY_LENGTH
+                    );
+                }
+                Self::new(key.as_bytes())
+            }
+            _ => {
+                warn!(
+                    "{} not set; using a random key, signed URLs will not survive a restart",
+                    SIGNING_KEY_ENV
+                );
+                let mut key = [0u8; MIN_KEY_LENGTH];
+                rand::thread_rng().fill_bytes(&mut key);
+                Self {
+                    ephemeral: true,
+                    ..Self::new(&key)
+                }
+            }
+        }
+    }
+
+    /// Whether the key was generated at startup because
+    /// `MEDIA_URL_SIGNING_KEY` is not set
+    pub fn is_ephemeral(&self) -> bool {
+        self.ephemeral
+    }
+
+    fn mac(&self, resource_id: i32, perm: &str, expires: i64) -> HmacSha256 {
+        let mut mac =
+            HmacSha256::new_from_slice(&self.key).expect("HMAC accepts keys of any length");
+        mac.update(format!("{}\n{}\n{}", resource_id, perm, expires).as_bytes());
+        mac
+    }
+
+    /// Sign access to a media item until `expires` (unix seconds).
+    ///
+    /// Returns `None` for permissions other than Read and Download.
+    pub fn sign(
+        &self,
+        resource_id: i32,
+        permission: Permission,
+        expires: i64,
+    ) -> Option<SignedUrlParams> {
+        let perm = permission_name(permission)?;
+        let sig = self.mac(resource_id, perm, expires).finalize().into_bytes();
+        Some(SignedUrlParams {
+            expires: Some(expires),
+            perm: Some(perm.to_string()),
+            sig: Some(HEXLOWER.encode(&sig)),
+        })
+    }
+
+    /// Verify signed URL parameters for a media item.
+    ///
+    /// Returns the permission the URL grants.
+    pub fn verify(
+        &self,
+        resource_id: i32,
+        params: &SignedUrlParams,
+    ) -> Result<Permission, SignedUrlError> {
+        self.verify_at(
+            resource_id,
+            params,
+            OffsetDateTime::now_utc().unix_timestamp(),
+        )
+    }
+
+    fn verify_at(
+        &self,
+        resource_id: i32,
+        params: &SignedUrlParams,
+        now: i64,
+    ) -> Result<Permission, SignedUrlError> {
+        let (Some(expires), Some(perm), Some(sig)) = (params.expires, &params.perm, &params.sig)
+        else {
+            return Err(SignedUrlError::Malformed);
+        };
+        let permission = parse_permission(perm).ok_or(SignedUrlError::Malformed)?;
+        let sig = HEXLOWER
+            .decode(sig.as_bytes())
+            .map_err(|_| SignedUrlError::Malformed)?;
+
+        // Check the signature first so expiry is only reported for real URLs
+        self.mac(resource_id, perm, expires)
+            .verify_slice(&sig)
+            .map_err(|_| SignedUrlError::InvalidSignature)?;
+        if expires <= now {
+            return Err(SignedUrlError::Expired);
+        }
+        Ok(permission)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sign_and_verify() {
+        let signer = UrlSigner::new(b"test-key");
+        let params = signer.sign(42, Permission::Download, 2_000).unwrap();
+
+        assert_eq!(signer.verify_at(42, &params, 1_000), Ok(Permission::Download));
+        assert_eq!(
+            signer.verify_at(42, &params, 2_000),
+            Err(SignedUrlError::Expired)
+        );
+        assert_eq!(
+            signer.verify_at(43, &params, 1_000),
+            Err(SignedUrlError::InvalidSignature)
+        );
+        assert_eq!(
+            UrlSigner::new(b"other-key").verify_at(42, &params, 1_000),
+            Err(SignedUrlError::InvalidSignature)
+        );
+        assert!(signer.sign(42, Permission::Edit, 2_000).is_none());
+    }
+
+    #[test]
+    fn test_tampered_params() {
+        let signer = UrlSigner::new(b"test-key");
+        let params = signer.sign(7, Permission::Read, 2_000).unwrap();
+
+        let upgraded = SignedUrlParams {
+            perm: Some("download".to_string()),
+            ..params.clone()
+        };
+        assert_eq!(
+            signer.verify_at(7, &upgraded, 1_000),
+            Err(SignedUrlError::InvalidSignature)
+        );
+
+        let extended = SignedUrlParams {
+            expires: Some(9_000),
+            ..params.clone()
+        };
+        assert_eq!(
+            signer.verify_at(7, &extended, 1_000),
+            Err(SignedUrlError::InvalidSignature)
+        );
+
+        let missing = SignedUrlParams {
+            sig: None,
+            ..params
+        };
+        assert_eq!(
+            signer.verify_at(7, &missing, 1_000),
+            Err(SignedUrlError::Malformed)
+        );
+    }
+
+    #[test]
+    fn test_to_query() {
+        let params = UrlSigner::new(b"k")
+            .sign(1, Permission::Read, 1_700_000_000)
+            .unwrap();
+        let query = params.to_query().unwrap();
+
+        assert!(query.starts_with("expires=1700000000&perm=view&sig="));
+        assert!(SignedUrlParams::default().to_query().is_none());
+    }
+}
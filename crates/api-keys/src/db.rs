@@ -12,9 +12,10 @@ use tracing::{error, info};
 pub async fn create_api_key(
     repo: &dyn ApiKeyRepository,
     user_id: &str,
-    request: CreateApiKeyRequest,
+    mut request: CreateApiKeyRequest,
 ) -> Result<ApiKeyResponse> {
     crate::validate_scopes(&request.scopes)?;
+    crate::normalize_bindings(&mut request)?;
 
     let key = generator::generate_live_key();
     let key_hash = generator::hash_api_key(&key);
@@ -3,10 +3,13 @@ pub mod generator;
 pub mod middleware;
 pub mod routes;
 
+use access_control::restrictions::parse_ip_entry;
 use anyhow::Result;
 
 // Re-export domain types from the db crate
-pub use ::db::api_keys::{ApiKey, ApiKeyResponse, CreateApiKeyRequest, UpdateApiKeyRequest};
+pub use ::db::api_keys::{
+    ApiKey, ApiKeyResponse, CreateApiKeyRequest, ResourceRef, UpdateApiKeyRequest,
+};
 
 // ============================================================================
 // Business logic (not DB-related)
@@ -63,3 +66,226 @@ pub fn validate_scopes(scopes: &[String]) -> Result<()> {
 
     Ok(())
 }
+
+/// Validate and normalize the bindings of a create request.
+///
+/// Blank values are dropped, the folder is stored without leading or trailing
+/// slashes and IP entries are lowercased.
+pub fn normalize_bindings(request: &mut CreateApiKeyRequest) -> Result<()> {
+    fn non_blank(value: &Option<String>) -> Option<String> {
+        value
+            .as_deref()
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+            .map(str::to_string)
+    }
+
+    request.workspace_id = non_blank(&request.workspace_id);
+    request.vault_id = non_blank(&request.vault_id);
+    if request.workspace_id.is_some() && request.vault_id.is_some() {
+        return Err(anyhow::anyhow!(
+            "A key can be bound to a workspace or a vault, not both"
+        ));
+    }
+
+    request.path_prefix = match non_blank(&request.path_prefix) {
+        Some(prefix) => {
+            let segments: Vec<&str> = prefix
+                .split('/')
+                .filter(|s| !s.is_empty() && *s != ".")
+                .collect();
+            if segments.contains(&"..") {
+                return Err(anyhow::anyhow!("Invalid folder: {}", prefix));
+            }
+            if request.workspace_id.is_none() {
+                return Err(anyhow::anyhow!(
+                    "A folder binding requires a workspace binding"
+                ));
+            }
+            (!segments.is_empty()).then(|| segments.join("/"))
+        }
+        None => None,
+    };
+
+    request.allowed_ips = request
+        .allowed_ips
+        .iter()
+        .map(|e| e.trim().to_lowercase())
+        .filter(|e| !e.is_empty())
+        .collect();
+    if let Some(bad) = request
+        .allowed_ips
+        .iter()
+        .find(|e| parse_ip_entry(e).is_none())
+    {
+        return Err(anyhow::anyhow!("Invalid IP address or CIDR range: {}", bad));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(json: &str) -> CreateApiKeyRequest {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn test_normalize_bindings() {
+        let mut r = request(
+            r#"{"name":"k","scopes":["read"],"workspace_id":" ws1 ","vault_id":"",
+                "path_prefix":"/clients//acme/","allowed_ips":[" 10.0.0.0/8 ",""]}"#,
+        );
+        normalize_bindings(&mut r).unwrap();
+
+        assert_eq!(r.workspace_id.as_deref(), Some("ws1"));
+        assert!(r.vault_id.is_none());
+        assert_eq!(r.path_prefix.as_deref(), Some("clients/acme"));
+        assert_eq!(r.allowed_ips, vec!["10.0.0.0/8"]);
+
+        for json in [
+            r#"{"name":"k","scopes":["read"],"workspace_id":"ws1","vault_id":"v1"}"#,
+            r#"{"name":"k","scopes":["read"],"path_prefix":"docs"}"#,
+            r#"{"name":"k","scopes":["read"],"workspace_id":"ws1","path_prefix":"a/../b"}"#,
+            r#"{"name":"k","scopes":["read"],"allowed_ips":["10.0.0.0/40"]}"#,
+        ] {
+            assert!(normalize_bindings(&mut request(json)).is_err(), "{}", json);
+        }
+    }
+
+    #[test]
+    fn test_allows_resource() {
+        let mut key: ApiKey = serde_json::from_value(serde_json::json!({
+            "id": 1, "user_id": "u", "key_prefix": "ak_live_abc1", "name": "k",
+            "description": null, "scopes": "[\"read\"]", "last_used_at": null,
+            "usage_count": 0, "expires_at": null, "created_at": "2026-01-01 00:00:00",
+            "is_active": true, "workspace_id": "ws1", "vault_id": null,
+            "path_prefix": "clients/acme", "allowed_ips": null
+        }))
+        .unwrap();
+        let in_ws = |path: &str| ResourceRef::workspace("ws1").with_path(path);
+
+        assert!(key.allows_resource(&in_ws("clients/acme")));
+        assert!(key.allows_resource(&in_ws("/clients/acme/notes.md")));
+        assert!(!key.allows_resource(&in_ws("clients/acme-other/notes.md")));
+        assert!(!key.allows_resource(&in_ws("clients/acme/../other/notes.md")));
+        assert!(!key.allows_resource(&ResourceRef::workspace("ws1")));
+        assert!(!key.allows_resource(&ResourceRef::workspace("ws2").with_path("clients/acme")));
+        assert!(!key.allows_resource(&ResourceRef::default()));
+
+        key.workspace_id = None;
+        key.path_prefix = None;
+        assert!(key.allows_resource(&ResourceRef::vault("v1")));
+    }
+
+    #[test]
+    fn test_resource_from_uri() {
+        use crate::middleware::resource_from_uri;
+
+        let uri = "/api/workspaces/ws1/files/serve?path=clients%2Facme%2Fa.md"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            resource_from_uri(&uri),
+            ResourceRef::workspace("ws1").with_path("clients/acme/a.md")
+        );
+        assert_eq!(
+            resource_from_uri(&"/api/user/vaults/v1".parse().unwrap()),
+            ResourceRef::vault("v1")
+        );
+        assert!(resource_from_uri(&"/vaults/new".parse().unwrap()).is_empty());
+        assert!(resource_from_uri(&"/api/media/clip/signed-url".parse().unwrap()).is_empty());
+    }
+
+    /// Repository holding a single unbound key owned by `u1`
+    struct OneKeyRepo(ApiKey);
+
+    #[async_trait::async_trait]
+    impl ::db::api_keys::ApiKeyRepository for OneKeyRepo {
+        async fn create_api_key(
+            &self,
+            _: &str,
+            _: &str,
+            _: &str,
+            _: &str,
+            _: &CreateApiKeyRequest,
+        ) -> Result<ApiKey, ::db::DbError> {
+            unimplemented!()
+        }
+        async fn get_api_key_by_hash(&self, _: &str) -> Result<Option<ApiKey>, ::db::DbError> {
+            Ok(Some(self.0.clone()))
+        }
+        async fn get_api_key_by_id(&self, _: i32, _: &str) -> Result<Option<ApiKey>, ::db::DbError> {
+            Ok(None)
+        }
+        async fn list_user_api_keys(&self, _: &str) -> Result<Vec<ApiKey>, ::db::DbError> {
+            Ok(Vec::new())
+        }
+        async fn revoke_api_key(&self, _: i32, _: &str) -> Result<bool, ::db::DbError> {
+            Ok(false)
+        }
+        async fn update_api_key(
+            &self,
+            _: i32,
+            _: &str,
+            _: &UpdateApiKeyRequest,
+        ) -> Result<Option<ApiKey>, ::db::DbError> {
+            Ok(None)
+        }
+        async fn update_last_used(&self, _: i32) -> Result<(), ::db::DbError> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_api_key_session_is_request_scoped() {
+        use axum::{body::Body, http::Request, middleware::from_fn_with_state, routing::get, Router};
+        use std::sync::Arc;
+        use tower::ServiceExt;
+        use tower_sessions::{MemoryStore, Session, SessionManagerLayer};
+
+        let key: ApiKey = serde_json::from_value(serde_json::json!({
+            "id": 1, "user_id": "u1", "key_prefix": "ak_live_abc1", "name": "k",
+            "description": null, "scopes": "[\"read\"]", "last_used_at": null,
+            "usage_count": 0, "expires_at": null, "created_at": "2026-01-01 00:00:00",
+            "is_active": true, "workspace_id": null, "vault_id": null,
+            "path_prefix": null, "allowed_ips": null
+        }))
+        .unwrap();
+        let repo: Arc<dyn ::db::api_keys::ApiKeyRepository> = Arc::new(OneKeyRepo(key));
+
+        let app = Router::new()
+            .route(
+                "/api/me",
+                get(|session: Session| async move {
+                    session
+                        .get::<String>("user_id")
+                        .await
+                        .unwrap()
+                        .unwrap_or_default()
+                }),
+            )
+            .layer(from_fn_with_state(
+                repo,
+                crate::middleware::api_key_or_session_auth,
+            ))
+            .layer(SessionManagerLayer::new(MemoryStore::default()));
+
+        let response = app
+            .oneshot(
+                Request::get("/api/me")
+                    .header("x-api-key", "ak_live_abc1_secret")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), axum::http::StatusCode::OK);
+        assert!(response.headers().get("set-cookie").is_none());
+        let body = axum::body::to_bytes(response.into_body(), 1024).await.unwrap();
+        assert_eq!(&body[..], b"u1");
+    }
+}
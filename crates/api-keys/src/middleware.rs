@@ -1,11 +1,14 @@
-use crate::{db, ApiKey};
+use crate::{db, ApiKey, ResourceRef};
+use access_control::restrictions::{client_ip, ip_allowed, parse_allowlist};
 use axum::{
-    extract::{Request, State},
-    http::{HeaderMap, StatusCode},
+    extract::{Query, Request, State},
+    http::{HeaderMap, StatusCode, Uri},
     middleware::Next,
     response::Response,
 };
 use ::db::api_keys::ApiKeyRepository;
+use std::collections::HashMap;
+use std::future::Future;
 use std::sync::Arc;
 use tower_sessions::Session;
 use tracing::{debug, warn};
@@ -16,6 +19,8 @@ pub struct AuthenticatedUser {
     pub user_id: String,
     pub auth_method: AuthMethod,
     pub api_key: Option<ApiKey>,
+    /// Workspace, vault and path named by the request URL
+    pub resource: ResourceRef,
 }
 
 #[derive(Clone, Debug)]
@@ -28,11 +33,17 @@ pub enum AuthMethod {
 ///
 /// This middleware:
 /// 1. First checks for an API key in headers (Authorization: Bearer or X-API-Key)
-/// 2. If no API key, falls back to session authentication
-/// 3. If authenticated, adds AuthenticatedUser to request extensions
-/// 4. Also sets session variables for backwards compatibility with existing handlers
-/// 5. If not authenticated but has `code` query parameter, allows through (for access code validation in handler)
-/// 6. If not authenticated, returns 401 Unauthorized
+/// 2. Rejects API keys used from an IP outside their allowlist, or on a URL naming a
+///    workspace, vault or path outside their bindings (403)
+/// 3. If no API key, falls back to session authentication
+/// 4. If authenticated, adds AuthenticatedUser to request extensions
+/// 5. Also sets session variables for backwards compatibility with existing handlers, for the
+///    duration of the request only (see [`with_request_session`]). A bound key only gets them
+///    where the URL names a resource it covers; elsewhere handlers must check it with
+///    [`require_scope_for`]
+/// 6. If not authenticated but has a `code` or `sig` query parameter, allows through (the handler
+///    validates the access code or signed URL)
+/// 7. If not authenticated, returns 401 Unauthorized
 pub async fn api_key_or_session_auth(
     State(repo): State<Arc<dyn ApiKeyRepository>>,
     session: Session,
@@ -46,6 +57,8 @@ pub async fn api_key_or_session_auth(
 
         match db::validate_api_key(repo.as_ref(), &api_key_str).await {
             Ok(Some(api_key)) => {
+                let resource = check_key_restrictions(&api_key, &headers, request.uri())?;
+
                 debug!(
                     event = "auth_success",
                     auth_method = "api_key",
@@ -54,17 +67,22 @@ pub async fn api_key_or_session_auth(
                     "Request authenticated via API key"
                 );
 
-                // Set session variables for backwards compatibility with existing handlers
-                let _ = session.insert("authenticated", true).await;
-                let _ = session.insert("user_id", api_key.user_id.clone()).await;
+                let user_id = api_key.user_id.clone();
+                let covered = api_key.allows_resource(&resource);
 
                 // Add authenticated user to request extensions
                 request.extensions_mut().insert(AuthenticatedUser {
-                    user_id: api_key.user_id.clone(),
+                    user_id: user_id.clone(),
                     auth_method: AuthMethod::ApiKey,
                     api_key: Some(api_key),
+                    resource,
                 });
 
+                // Sign the key's user into the session for this request only, for
+                // backwards compatibility with handlers that read it
+                if covered {
+                    return Ok(with_request_session(&session, &user_id, next.run(request)).await);
+                }
                 return Ok(next.run(request).await);
             }
             Ok(None) => {
@@ -114,28 +132,31 @@ pub async fn api_key_or_session_auth(
         );
 
         // Add authenticated user to request extensions
+        let resource = resource_from_uri(request.uri());
         request.extensions_mut().insert(AuthenticatedUser {
             user_id,
             auth_method: AuthMethod::Session,
             api_key: None,
+            resource,
         });
 
         return Ok(next.run(request).await);
     }
 
-    // Check if request has an access code query parameter
-    // If so, allow it through - the handler will validate the access code
+    // Check if request has an access code or URL signature query parameter
+    // If so, allow it through - the handler will validate it
     if let Some(query_str) = request.uri().query() {
-        // Check if query contains code= with a non-empty value
-        // Match patterns like: code=abc, ?code=abc, &code=abc
-        if query_str.split('&')
-            .any(|param| param.starts_with("code=") && param.len() > 5)
-        {
+        // Check if query contains code= or sig= with a non-empty value
+        // Match patterns like: code=abc, ?code=abc, &sig=abc
+        if query_str.split('&').any(|param| {
+            (param.starts_with("code=") && param.len() > 5)
+                || (param.starts_with("sig=") && param.len() > 4)
+        }) {
             debug!(
                 event = "auth_bypass",
-                reason = "access_code_present",
+                reason = "access_code_or_signature_present",
                 query = %query_str,
-                "Allowing request with access code through to handler for validation"
+                "Allowing request with access code or signature through to handler for validation"
             );
             return Ok(next.run(request).await);
         }
@@ -151,6 +172,37 @@ pub async fn api_key_or_session_auth(
     Err(StatusCode::UNAUTHORIZED)
 }
 
+/// Run `inner` with `user_id` signed into the session.
+///
+/// The previous session values are restored afterwards, so nothing derived from
+/// an API key is saved to the session store or handed out as a cookie: a
+/// session cookie must never carry a key's access past the key's own IP and
+/// binding checks.
+async fn with_request_session(
+    session: &Session,
+    user_id: &str,
+    inner: impl Future<Output = Response>,
+) -> Response {
+    const KEYS: [&str; 2] = ["authenticated", "user_id"];
+
+    let mut previous = Vec::with_capacity(KEYS.len());
+    for key in KEYS {
+        previous.push(session.get_value(key).await.ok().flatten());
+    }
+    let _ = session.insert("authenticated", true).await;
+    let _ = session.insert("user_id", user_id).await;
+
+    let response = inner.await;
+
+    for (key, value) in KEYS.into_iter().zip(previous) {
+        let _ = match value {
+            Some(value) => session.insert_value(key, value).await.map(|_| ()),
+            None => session.remove_value(key).await.map(|_| ()),
+        };
+    }
+    response
+}
+
 /// Middleware that ONLY accepts API key authentication (no session fallback)
 ///
 /// Use this for API-only endpoints that should not accept browser sessions
@@ -165,6 +217,8 @@ pub async fn api_key_only_auth(
 
     match db::validate_api_key(repo.as_ref(), &api_key_str).await {
         Ok(Some(api_key)) => {
+            let resource = check_key_restrictions(&api_key, &headers, request.uri())?;
+
             debug!(
                 event = "auth_success",
                 auth_method = "api_key",
@@ -177,6 +231,7 @@ pub async fn api_key_only_auth(
                 user_id: api_key.user_id.clone(),
                 auth_method: AuthMethod::ApiKey,
                 api_key: Some(api_key),
+                resource,
             });
 
             Ok(next.run(request).await)
@@ -202,6 +257,78 @@ pub async fn api_key_only_auth(
     }
 }
 
+/// Check an API key's IP allowlist and bindings against a request.
+///
+/// Returns the resource named by the URL. Fails with 403 if the client IP is
+/// not allowed, or the URL names a resource outside the key's bindings.
+fn check_key_restrictions(
+    api_key: &ApiKey,
+    headers: &HeaderMap,
+    uri: &Uri,
+) -> Result<ResourceRef, StatusCode> {
+    if !client_ip_allowed(api_key, headers) {
+        warn!(
+            event = "auth_failed",
+            auth_method = "api_key",
+            reason = "ip_not_allowed",
+            key_id = api_key.id,
+            "API key used from an IP outside its allowlist"
+        );
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    let resource = resource_from_uri(uri);
+    if !resource.is_empty() && !api_key.allows_resource(&resource) {
+        warn!(
+            event = "permission_denied",
+            key_id = api_key.id,
+            path = %uri.path(),
+            "API key is not bound to the requested resource"
+        );
+        return Err(StatusCode::FORBIDDEN);
+    }
+    Ok(resource)
+}
+
+/// Whether the client IP is in the key's allowlist (keys without one work from anywhere)
+pub fn client_ip_allowed(api_key: &ApiKey, headers: &HeaderMap) -> bool {
+    let allowlist = parse_allowlist(api_key.allowed_ips.as_deref());
+    ip_allowed(&allowlist, client_ip(headers).as_deref())
+}
+
+/// The workspace, vault and path a request URL names
+///
+/// Reads the segment after `workspaces` / `vaults` in `/api/...` paths, and the
+/// `workspace_id`, `vault_id` and `path` query parameters.
+pub fn resource_from_uri(uri: &Uri) -> ResourceRef {
+    let mut resource = ResourceRef::default();
+
+    if uri.path().starts_with("/api/") {
+        let segments: Vec<&str> = uri.path().split('/').collect();
+        for pair in segments.windows(2) {
+            match pair {
+                ["workspaces", id] if !id.is_empty() => {
+                    resource.workspace_id = Some(id.to_string())
+                }
+                ["vaults", id] if !id.is_empty() => resource.vault_id = Some(id.to_string()),
+                _ => {}
+            }
+        }
+    }
+
+    if let Ok(Query(mut params)) = Query::<HashMap<String, String>>::try_from_uri(uri) {
+        if let Some(id) = params.remove("workspace_id") {
+            resource.workspace_id.get_or_insert(id);
+        }
+        if let Some(id) = params.remove("vault_id") {
+            resource.vault_id.get_or_insert(id);
+        }
+        resource.path = params.remove("path");
+    }
+
+    resource
+}
+
 /// Extract API key from request headers
 ///
 /// Checks in order:
@@ -229,21 +356,43 @@ fn extract_api_key_from_headers(headers: &HeaderMap) -> Option<String> {
 
 /// Helper function to check if user has a specific scope
 /// Use this in route handlers to enforce permissions
+///
+/// Bound API keys must also cover the resource named by the request URL.
 pub fn require_scope(user: &AuthenticatedUser, scope: &str) -> Result<(), StatusCode> {
+    require_scope_for(user, scope, &user.resource)
+}
+
+/// Check a scope for a specific resource
+/// Use this in handlers that know which workspace, vault or path they act on
+pub fn require_scope_for(
+    user: &AuthenticatedUser,
+    scope: &str,
+    resource: &ResourceRef,
+) -> Result<(), StatusCode> {
     match &user.api_key {
         Some(api_key) => {
             // API key authentication - check scopes
-            if api_key.has_scope(scope) || api_key.has_scope("admin") {
-                Ok(())
-            } else {
+            if !api_key.has_scope(scope) && !api_key.has_scope("admin") {
                 warn!(
                     event = "permission_denied",
                     user_id = %user.user_id,
                     required_scope = scope,
                     "API key lacks required scope"
                 );
-                Err(StatusCode::FORBIDDEN)
+                return Err(StatusCode::FORBIDDEN);
             }
+            // ... and bindings
+            if !api_key.allows_resource(resource) {
+                warn!(
+                    event = "permission_denied",
+                    user_id = %user.user_id,
+                    key_id = api_key.id,
+                    required_scope = scope,
+                    "API key is not bound to the requested resource"
+                );
+                return Err(StatusCode::FORBIDDEN);
+            }
+            Ok(())
         }
         None => {
             // Session authentication - assume full permissions
@@ -145,6 +145,10 @@ struct CreateApiKeyFormData {
     scopes: Option<String>, // Comma-separated or checkboxes as "scope1,scope2"
     expiration: Option<String>, // "never", "30days", "90days", "1year", "custom"
     custom_expiration: Option<String>, // ISO 8601 date
+    workspace_id: Option<String>,
+    vault_id: Option<String>,
+    path_prefix: Option<String>,
+    allowed_ips: Option<String>, // One per line
 }
 
 /// Handle create API key form submission
@@ -197,6 +201,15 @@ async fn create_api_key_form_handler(
         description: form.description,
         scopes,
         expires_at,
+        workspace_id: form.workspace_id,
+        vault_id: form.vault_id,
+        path_prefix: form.path_prefix,
+        allowed_ips: form
+            .allowed_ips
+            .unwrap_or_default()
+            .lines()
+            .map(str::to_string)
+            .collect(),
     };
 
     match db::create_api_key(repo.as_ref(), &user_id, request).await {
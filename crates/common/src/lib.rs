@@ -2,6 +2,7 @@
 
 pub mod error;
 pub mod models;
+pub mod range;
 pub mod request_id;
 pub mod services;
 pub mod storage;
@@ -1,6 +1,6 @@
 //! Unified Media Item Model
 //!
-//! Single model for all media types (videos, images, documents)
+//! Single model for all media types (videos, images, audio, documents)
 //! Replaces separate Video, Image, and Document models
 
 use serde::{Deserialize, Serialize};
@@ -11,6 +11,7 @@ use serde::{Deserialize, Serialize};
 pub enum MediaType {
     Video,
     Image,
+    Audio,
     Document,
 }
 
@@ -19,6 +20,7 @@ impl std::fmt::Display for MediaType {
         match self {
             MediaType::Video => write!(f, "video"),
             MediaType::Image => write!(f, "image"),
+            MediaType::Audio => write!(f, "audio"),
             MediaType::Document => write!(f, "document"),
         }
     }
@@ -31,6 +33,7 @@ impl std::str::FromStr for MediaType {
         match s.to_lowercase().as_str() {
             "video" => Ok(MediaType::Video),
             "image" => Ok(MediaType::Image),
+            "audio" => Ok(MediaType::Audio),
             "document" => Ok(MediaType::Document),
             _ => Err(format!("Invalid media type: {}", s)),
         }
@@ -0,0 +1,465 @@
+//! HTTP byte-range file serving
+//!
+//! Shared by every handler that streams a file from disk (MP4 video, PDF,
+//! workspace files, federation content) so that browsers can seek, resume and
+//! play progressive media. Implements the parts of RFC 9110 that matter for
+//! static files:
+//!
+//! - `Range: bytes=…` with a single range → `206 Partial Content`
+//! - Multiple ranges or a range outside the file → `416 Range Not Satisfiable`
+//! - `If-Range` is honoured: a stale validator turns the request into a full `200`
+//! - `ETag` / `Last-Modified` on every response, `If-None-Match` /
+//!   `If-Modified-Since` → `304 Not Modified`
+//!
+//! Callers do their own access checks, then hand the resolved path to
+//! [`serve_file`] (or [`serve_stored_file`] for media that may have been
+//! offloaded to a remote storage backend) and add any extra headers (cache
+//! policy, CORS, disposition) to the returned response.
+
+use axum::{
+    body::Body,
+    http::{header, HeaderMap, StatusCode},
+    response::Response,
+};
+use std::io::SeekFrom;
+use std::path::Path;
+
+use crate::storage::UserStorageManager;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::fs::File;
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+use tokio_util::io::ReaderStream;
+
+/// Parsed `Range` header, resolved against the file length
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ByteRange {
+    /// No usable range — serve the whole file
+    Full,
+    /// Inclusive byte range `start..=end`
+    Partial { start: u64, end: u64 },
+    /// Syntactically valid but cannot be served (out of bounds or multi-range)
+    Unsatisfiable,
+}
+
+/// Parse a `Range` header value against a file of `len` bytes.
+///
+/// Headers in a unit other than `bytes` or with invalid syntax are ignored, as
+/// RFC 9110 requires. Multi-range requests are rejected rather than answered
+/// with `multipart/byteranges`.
+pub fn parse_range(value: &str, len: u64) -> ByteRange {
+    let Some(spec) = value.trim().strip_prefix("bytes=") else {
+        return ByteRange::Full;
+    };
+
+    if spec.contains(',') {
+        return ByteRange::Unsatisfiable;
+    }
+
+    let Some((start, end)) = spec.trim().split_once('-') else {
+        return ByteRange::Full;
+    };
+    let (start, end) = (start.trim(), end.trim());
+
+    if start.is_empty() {
+        // Suffix range: last N bytes
+        let Ok(suffix) = end.parse::<u64>() else {
+            return ByteRange::Full;
+        };
+        if suffix == 0 || len == 0 {
+            return ByteRange::Unsatisfiable;
+        }
+        return ByteRange::Partial {
+            start: len.saturating_sub(suffix),
+            end: len - 1,
+        };
+    }
+
+    let Ok(start) = start.parse::<u64>() else {
+        return ByteRange::Full;
+    };
+    let end = if end.is_empty() {
+        len.saturating_sub(1)
+    } else {
+        match end.parse::<u64>() {
+            Ok(end) if end >= start => end.min(len.saturating_sub(1)),
+            _ => return ByteRange::Full,
+        }
+    };
+
+    if start >= len {
+        return ByteRange::Unsatisfiable;
+    }
+
+    ByteRange::Partial { start, end }
+}
+
+/// Strong validator derived from file size and modification time
+fn make_etag(len: u64, modified: Option<SystemTime>) -> String {
+    let mtime = modified
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_nanos())
+        .unwrap_or(0);
+    format!("\"{:x}-{:x}\"", len, mtime)
+}
+
+/// `If-None-Match` matches when any listed tag (or `*`) equals ours
+fn etag_matches(header_value: &str, etag: &str) -> bool {
+    header_value
+        .split(',')
+        .map(|t| t.trim().trim_start_matches("W/"))
+        .any(|t| t == "*" || t == etag)
+}
+
+/// HTTP dates have one-second resolution, so compare at that granularity
+fn not_modified_since(modified: SystemTime, since: SystemTime) -> bool {
+    let secs = |t: SystemTime| {
+        t.duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0)
+    };
+    secs(modified) <= secs(since)
+}
+
+fn header_str(headers: &HeaderMap, name: header::HeaderName) -> Option<&str> {
+    headers.get(name).and_then(|v| v.to_str().ok())
+}
+
+/// Should the `Range` header be applied, given the request's `If-Range`?
+fn if_range_allows(headers: &HeaderMap, etag: &str, modified: Option<SystemTime>) -> bool {
+    let Some(value) = header_str(headers, header::IF_RANGE) else {
+        return true;
+    };
+    let value = value.trim();
+
+    if value.starts_with('"') {
+        // Strong comparison only — weak tags never match
+        return value == etag;
+    }
+
+    match (httpdate::parse_http_date(value), modified) {
+        (Ok(date), Some(modified)) => not_modified_since(modified, date),
+        _ => false,
+    }
+}
+
+/// Response builder carrying the validators sent with every answer
+fn base_response(etag: &str, modified: Option<SystemTime>) -> axum::http::response::Builder {
+    let mut builder = Response::builder()
+        .header(header::ACCEPT_RANGES, "bytes")
+        .header(header::ETAG, etag);
+    if let Some(modified) = modified {
+        builder = builder.header(header::LAST_MODIFIED, httpdate::fmt_http_date(modified));
+    }
+    builder
+}
+
+/// Evaluate the conditional and range headers of a request
+///
+/// Returns None when the client's copy is current (`304 Not Modified`),
+/// otherwise the range to serve.
+fn requested_range(
+    request_headers: &HeaderMap,
+    len: u64,
+    etag: &str,
+    modified: Option<SystemTime>,
+) -> Option<ByteRange> {
+    let not_modified = if let Some(inm) = header_str(request_headers, header::IF_NONE_MATCH) {
+        etag_matches(inm, etag)
+    } else if let (Some(ims), Some(modified)) = (
+        header_str(request_headers, header::IF_MODIFIED_SINCE),
+        modified,
+    ) {
+        httpdate::parse_http_date(ims)
+            .map(|since| not_modified_since(modified, since))
+            .unwrap_or(false)
+    } else {
+        false
+    };
+
+    if not_modified {
+        return None;
+    }
+
+    Some(match header_str(request_headers, header::RANGE) {
+        Some(value) if if_range_allows(request_headers, etag, modified) => parse_range(value, len),
+        _ => ByteRange::Full,
+    })
+}
+
+/// Finish a response for the resolved range with the given body
+fn range_response(
+    builder: axum::http::response::Builder,
+    range: ByteRange,
+    len: u64,
+    content_type: &str,
+    body: Body,
+) -> Response {
+    let response = match range {
+        ByteRange::Full => builder
+            .status(StatusCode::OK)
+            .header(header::CONTENT_TYPE, content_type)
+            .header(header::CONTENT_LENGTH, len)
+            .body(body),
+        ByteRange::Partial { start, end } => builder
+            .status(StatusCode::PARTIAL_CONTENT)
+            .header(header::CONTENT_TYPE, content_type)
+            .header(header::CONTENT_LENGTH, end - start + 1)
+            .header(
+                header::CONTENT_RANGE,
+                format!("bytes {}-{}/{}", start, end, len),
+            )
+            .body(body),
+        ByteRange::Unsatisfiable => builder
+            .status(StatusCode::RANGE_NOT_SATISFIABLE)
+            .header(header::CONTENT_RANGE, format!("bytes */{}", len))
+            .body(Body::empty()),
+    };
+    response.unwrap()
+}
+
+fn not_modified_response(builder: axum::http::response::Builder) -> Response {
+    builder
+        .status(StatusCode::NOT_MODIFIED)
+        .body(Body::empty())
+        .unwrap()
+}
+
+/// Stream a file from disk, honouring `Range` and conditional request headers.
+///
+/// Returns `Err` only for I/O failures (missing file, permission denied); the
+/// caller decides how to map those to a status code.
+pub async fn serve_file(
+    path: &Path,
+    content_type: &str,
+    request_headers: &HeaderMap,
+) -> std::io::Result<Response> {
+    let mut file = File::open(path).await?;
+    let metadata = file.metadata().await?;
+    let len = metadata.len();
+    let modified = metadata.modified().ok();
+    let etag = make_etag(len, modified);
+    let builder = base_response(&etag, modified);
+
+    let Some(range) = requested_range(request_headers, len, &etag, modified) else {
+        return Ok(not_modified_response(builder));
+    };
+
+    let body = match range {
+        ByteRange::Full => Body::from_stream(ReaderStream::new(file)),
+        ByteRange::Partial { start, end } => {
+            file.seek(SeekFrom::Start(start)).await?;
+            Body::from_stream(ReaderStream::new(file.take(end - start + 1)))
+        }
+        ByteRange::Unsatisfiable => Body::empty(),
+    };
+
+    Ok(range_response(builder, range, len, content_type, body))
+}
+
+/// Serve a stored file from local disk or the storage backend.
+///
+/// Files present in the local tree are served by [`serve_file`]; files that
+/// were offloaded to a remote backend are streamed from it with the same
+/// `Range` and conditional handling. A file found nowhere is reported as
+/// `NotFound`.
+pub async fn serve_stored_file(
+    storage: &UserStorageManager,
+    path: &Path,
+    content_type: &str,
+    request_headers: &HeaderMap,
+) -> std::io::Result<Response> {
+    if path.is_file() {
+        return serve_file(path, content_type, request_headers).await;
+    }
+
+    let not_found = || std::io::Error::from(std::io::ErrorKind::NotFound);
+    let key = storage.object_key(path).ok_or_else(not_found)?;
+    let backend = storage.backend();
+    if backend.local_path(&key).as_deref() == Some(path) {
+        return Err(not_found());
+    }
+
+    let meta = backend
+        .head(&key)
+        .await
+        .map_err(std::io::Error::other)?
+        .ok_or_else(not_found)?;
+    let etag = meta
+        .etag
+        .clone()
+        .unwrap_or_else(|| make_etag(meta.len, meta.modified));
+    let builder = base_response(&etag, meta.modified);
+
+    let Some(range) = requested_range(request_headers, meta.len, &etag, meta.modified) else {
+        return Ok(not_modified_response(builder));
+    };
+
+    let body = match range {
+        ByteRange::Full => backend.get_range(&key, None).await,
+        ByteRange::Partial { start, end } => backend.get_range(&key, Some((start, end))).await,
+        ByteRange::Unsatisfiable => {
+            return Ok(range_response(builder, range, meta.len, content_type, Body::empty()))
+        }
+    }
+    .map_err(|e| match e {
+        media_core::MediaError::FileNotFound { .. } => not_found(),
+        e => std::io::Error::other(e),
+    })?;
+
+    Ok(range_response(
+        builder,
+        range,
+        meta.len,
+        content_type,
+        Body::from_stream(body),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+    use std::io::Write;
+
+    #[test]
+    fn test_parse_range_forms() {
+        assert_eq!(
+            parse_range("bytes=0-99", 1000),
+            ByteRange::Partial { start: 0, end: 99 }
+        );
+        assert_eq!(
+            parse_range("bytes=500-", 1000),
+            ByteRange::Partial {
+                start: 500,
+                end: 999
+            }
+        );
+        assert_eq!(
+            parse_range("bytes=-100", 1000),
+            ByteRange::Partial {
+                start: 900,
+                end: 999
+            }
+        );
+        assert_eq!(
+            parse_range("bytes=-5000", 1000),
+            ByteRange::Partial { start: 0, end: 999 }
+        );
+        assert_eq!(
+            parse_range("bytes=900-5000", 1000),
+            ByteRange::Partial {
+                start: 900,
+                end: 999
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_range_rejections() {
+        assert_eq!(parse_range("bytes=1000-", 1000), ByteRange::Unsatisfiable);
+        assert_eq!(parse_range("bytes=0-1,5-9", 1000), ByteRange::Unsatisfiable);
+        assert_eq!(parse_range("bytes=-0", 1000), ByteRange::Unsatisfiable);
+        assert_eq!(parse_range("bytes=0-", 0), ByteRange::Unsatisfiable);
+    }
+
+    #[test]
+    fn test_parse_range_ignored() {
+        assert_eq!(parse_range("items=0-5", 1000), ByteRange::Full);
+        assert_eq!(parse_range("bytes=abc", 1000), ByteRange::Full);
+        assert_eq!(parse_range("bytes=9-3", 1000), ByteRange::Full);
+    }
+
+    #[test]
+    fn test_etag_matches() {
+        assert!(etag_matches("\"a-b\"", "\"a-b\""));
+        assert!(etag_matches("\"x\", W/\"a-b\"", "\"a-b\""));
+        assert!(etag_matches("*", "\"a-b\""));
+        assert!(!etag_matches("\"x\"", "\"a-b\""));
+    }
+
+    async fn serve(path: &Path, headers: &[(header::HeaderName, &str)]) -> Response {
+        let mut map = HeaderMap::new();
+        for (name, value) in headers {
+            map.insert(name.clone(), HeaderValue::from_str(value).unwrap());
+        }
+        serve_file(path, "text/plain", &map).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_serve_file() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(b"0123456789").unwrap();
+        let path = file.path();
+
+        let full = serve(path, &[]).await;
+        assert_eq!(full.status(), StatusCode::OK);
+        assert_eq!(full.headers()[header::CONTENT_LENGTH], "10");
+        assert_eq!(full.headers()[header::ACCEPT_RANGES], "bytes");
+        let etag = full.headers()[header::ETAG].to_str().unwrap().to_string();
+
+        let partial = serve(path, &[(header::RANGE, "bytes=2-5")]).await;
+        assert_eq!(partial.status(), StatusCode::PARTIAL_CONTENT);
+        assert_eq!(partial.headers()[header::CONTENT_RANGE], "bytes 2-5/10");
+        assert_eq!(partial.headers()[header::CONTENT_LENGTH], "4");
+
+        let unsat = serve(path, &[(header::RANGE, "bytes=20-")]).await;
+        assert_eq!(unsat.status(), StatusCode::RANGE_NOT_SATISFIABLE);
+        assert_eq!(unsat.headers()[header::CONTENT_RANGE], "bytes */10");
+
+        let stale = serve(
+            path,
+            &[
+                (header::RANGE, "bytes=2-5"),
+                (header::IF_RANGE, "\"stale\""),
+            ],
+        )
+        .await;
+        assert_eq!(stale.status(), StatusCode::OK);
+
+        let fresh = serve(
+            path,
+            &[(header::RANGE, "bytes=2-5"), (header::IF_RANGE, &etag)],
+        )
+        .await;
+        assert_eq!(fresh.status(), StatusCode::PARTIAL_CONTENT);
+
+        let cached = serve(path, &[(header::IF_NONE_MATCH, &etag)]).await;
+        assert_eq!(cached.status(), StatusCode::NOT_MODIFIED);
+    }
+
+    #[tokio::test]
+    async fn test_serve_stored_file_from_backend() {
+        use media_core::{Bytes, MemoryBackend, StorageBackend};
+        use std::sync::Arc;
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let backend = Arc::new(MemoryBackend::new());
+        backend
+            .put("vaults/v1/media/videos/clip/video.mp4", Bytes::from_static(b"0123456789"))
+            .await
+            .unwrap();
+        let storage = UserStorageManager::with_backend(temp_dir.path(), backend);
+        let path = temp_dir.path().join("vaults/v1/media/videos/clip/video.mp4");
+
+        let mut headers = HeaderMap::new();
+        headers.insert(header::RANGE, HeaderValue::from_static("bytes=2-5"));
+        let partial = serve_stored_file(&storage, &path, "video/mp4", &headers)
+            .await
+            .unwrap();
+        assert_eq!(partial.status(), StatusCode::PARTIAL_CONTENT);
+        assert_eq!(partial.headers()[header::CONTENT_RANGE], "bytes 2-5/10");
+        let body = axum::body::to_bytes(partial.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(&body[..], b"2345");
+
+        let missing = serve_stored_file(
+            &storage,
+            &temp_dir.path().join("vaults/v1/media/videos/other/video.mp4"),
+            "video/mp4",
+            &HeaderMap::new(),
+        )
+        .await;
+        assert_eq!(missing.unwrap_err().kind(), std::io::ErrorKind::NotFound);
+    }
+}
@@ -7,6 +7,7 @@
 //! - Backward compatibility with legacy paths (storage/{media_type}/)
 //! - Vault creation and management
 //! - Path validation and safety checks
+//! - Pluggable storage backends (local disk, S3-compatible object stores)
 //!
 //! ## Architecture
 //!
@@ -15,26 +16,48 @@
 //! - Vault IDs are meaningless without database lookup
 //! - User IDs never appear in filesystem paths
 //! - Database maps vault_id -> user_id
+//!
+//! ## Storage backends
+//!
+//! The directory tree under `base_dir` is always where files are written
+//! and processed. The configured [`StorageBackend`] is where they are kept:
+//! with the default local backend the tree *is* the store; with a remote
+//! backend (S3, MinIO) finished files are offloaded to the bucket and read
+//! back on demand — streamed directly when served over HTTP, or downloaded
+//! into the tree when a processing step needs a local file.
 
 use anyhow::{Context, Result};
+use media_core::backend::{key_from_relative_path, StorageBackend};
+use media_core::{MediaResult, S3Backend, StorageManager};
 use std::fs;
 use std::path::{Path, PathBuf};
-use tracing::{debug, info};
+use std::sync::Arc;
+use tracing::{debug, info, warn};
 
 /// Media type enum for storage organization
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum MediaType {
     Video,
     Image,
+    Audio,
     Document,
 }
 
 impl MediaType {
+    /// Every media type, in directory creation order
+    pub const ALL: [MediaType; 4] = [
+        MediaType::Video,
+        MediaType::Image,
+        MediaType::Audio,
+        MediaType::Document,
+    ];
+
     /// Get the directory name for this media type
     pub fn dir_name(&self) -> &'static str {
         match self {
             MediaType::Video => "videos",
             MediaType::Image => "images",
+            MediaType::Audio => "audio",
             MediaType::Document => "documents",
         }
     }
@@ -45,6 +68,8 @@ impl MediaType {
 pub struct UserStorageManager {
     /// Base storage directory (e.g., "storage")
     base_dir: PathBuf,
+    /// Where stored files are kept, keyed by their path relative to `base_dir`
+    backend: Arc<dyn StorageBackend>,
 }
 
 /// Generate a random vault ID
@@ -82,10 +107,35 @@ pub fn generate_workspace_id() -> String {
 }
 
 impl UserStorageManager {
-    /// Create a new UserStorageManager
+    /// Create a new UserStorageManager that keeps files on local disk
     pub fn new(base_dir: impl Into<PathBuf>) -> Self {
+        let base_dir = base_dir.into();
+        let backend = Arc::new(StorageManager::new(&base_dir));
+        Self { base_dir, backend }
+    }
+
+    /// Create a UserStorageManager that keeps files in the given backend
+    pub fn with_backend(base_dir: impl Into<PathBuf>, backend: Arc<dyn StorageBackend>) -> Self {
         Self {
             base_dir: base_dir.into(),
+            backend,
+        }
+    }
+
+    /// Create a UserStorageManager with the backend selected by `STORAGE_BACKEND`
+    ///
+    /// `local` (default) keeps files on disk; `s3` uses the S3-compatible
+    /// bucket configured by the `S3_*` variables (see `media_core::S3Config`).
+    pub fn from_env(base_dir: impl Into<PathBuf>) -> Result<Self> {
+        let base_dir = base_dir.into();
+        match std::env::var("STORAGE_BACKEND").as_deref() {
+            Ok("s3") => {
+                let backend = S3Backend::from_env().context("Invalid S3 storage configuration")?;
+                info!("Using S3 storage backend (bucket: {})", backend.bucket());
+                Ok(Self::with_backend(base_dir, Arc::new(backend)))
+            }
+            Ok("local") | Err(_) => Ok(Self::new(base_dir)),
+            Ok(other) => anyhow::bail!("Unknown STORAGE_BACKEND: {}", other),
         }
     }
 
@@ -94,6 +144,235 @@ impl UserStorageManager {
         &self.base_dir
     }
 
+    /// Get the storage backend
+    pub fn backend(&self) -> &Arc<dyn StorageBackend> {
+        &self.backend
+    }
+
+    /// Storage key of a path inside the base directory
+    pub fn object_key(&self, path: &Path) -> Option<String> {
+        key_from_relative_path(path.strip_prefix(&self.base_dir).ok()?)
+    }
+
+    /// Whether the backend keeps this file at its path in the local tree
+    fn stored_in_place(&self, key: &str, path: &Path) -> bool {
+        self.backend.local_path(key).as_deref() == Some(path)
+    }
+
+    /// Check whether a stored file exists, locally or in the backend
+    pub async fn stored_file_exists(&self, path: &Path) -> bool {
+        if path.is_file() {
+            return true;
+        }
+        match self.object_key(path) {
+            Some(key) if !self.stored_in_place(&key, path) => {
+                self.backend.exists(&key).await.unwrap_or_else(|e| {
+                    warn!("Failed to look up {} in storage backend: {}", key, e);
+                    false
+                })
+            }
+            _ => false,
+        }
+    }
+
+    /// Find media file in local storage or the backend, without downloading it
+    ///
+    /// For serving: pass the result to `range::serve_stored_file`.
+    pub async fn find_stored_media_file(
+        &self,
+        vault_id: &str,
+        media_type: MediaType,
+        filename: &str,
+    ) -> Option<PathBuf> {
+        let path = self.get_media_file_path(vault_id, media_type, filename);
+        self.stored_file_exists(&path).await.then_some(path)
+    }
+
+    /// Find thumbnail in local storage or the backend, without downloading it
+    pub async fn find_stored_thumbnail(
+        &self,
+        vault_id: &str,
+        media_type: MediaType,
+        slug: &str,
+    ) -> Option<PathBuf> {
+        let path = self.get_thumbnail_path(vault_id, media_type, slug);
+        self.stored_file_exists(&path).await.then_some(path)
+    }
+
+    /// Make a stored file available on local disk
+    ///
+    /// Files offloaded to a remote backend are downloaded back into the
+    /// local tree. Returns None if the file exists nowhere.
+    pub async fn fetch_local(&self, path: &Path) -> Option<PathBuf> {
+        if path.is_file() {
+            return Some(path.to_path_buf());
+        }
+        let key = self.object_key(path)?;
+        if self.stored_in_place(&key, path) {
+            return None;
+        }
+
+        match self.backend.download_to(&key, path).await {
+            Ok(_) => {
+                debug!("Fetched {} from storage backend", key);
+                Some(path.to_path_buf())
+            }
+            Err(media_core::MediaError::FileNotFound { .. }) => None,
+            Err(e) => {
+                warn!("Failed to fetch {} from storage backend: {}", key, e);
+                None
+            }
+        }
+    }
+
+    /// Find media file in local storage or the backend, downloading it if needed
+    pub async fn fetch_media_file(
+        &self,
+        vault_id: &str,
+        media_type: MediaType,
+        filename: &str,
+    ) -> Option<PathBuf> {
+        self.fetch_local(&self.get_media_file_path(vault_id, media_type, filename))
+            .await
+    }
+
+    /// Download the offloaded files under a directory that pass `filter`
+    ///
+    /// `filter` receives each file's path relative to `dir` ('/'-separated).
+    /// Files already present locally are kept. Returns the number of files
+    /// downloaded.
+    pub async fn fetch_local_dir(
+        &self,
+        dir: &Path,
+        filter: impl Fn(&str) -> bool,
+    ) -> MediaResult<usize> {
+        let Some(key) = self.object_key(dir) else {
+            return Ok(0);
+        };
+        if self.stored_in_place(&key, dir) {
+            return Ok(0);
+        }
+
+        let prefix = format!("{}/", key);
+        let mut fetched = 0;
+        for object in self.backend.list(&prefix).await? {
+            let relative = &object[prefix.len()..];
+            let path = dir.join(relative);
+            if filter(relative) && !path.exists() {
+                self.backend.download_to(&object, &path).await?;
+                fetched += 1;
+            }
+        }
+        Ok(fetched)
+    }
+
+    /// Hand a finished file or directory over to the storage backend
+    ///
+    /// With a remote backend every file is uploaded and the local copy
+    /// removed; with the local backend this does nothing. Returns the
+    /// number of files uploaded.
+    pub async fn offload(&self, path: &Path) -> MediaResult<usize> {
+        let mut pending = vec![path.to_path_buf()];
+        let mut dirs = Vec::new();
+        let mut uploaded = 0;
+
+        while let Some(path) = pending.pop() {
+            if path.is_dir() {
+                let mut entries = tokio::fs::read_dir(&path).await?;
+                while let Some(entry) = entries.next_entry().await? {
+                    pending.push(entry.path());
+                }
+                dirs.push(path);
+                continue;
+            }
+
+            let Some(key) = self.object_key(&path) else {
+                continue;
+            };
+            if self.stored_in_place(&key, &path) {
+                continue;
+            }
+            self.backend.put_file(&key, &path).await?;
+            tokio::fs::remove_file(&path).await?;
+            uploaded += 1;
+        }
+
+        // Remove the emptied directories, deepest first
+        for dir in dirs.iter().rev() {
+            let _ = tokio::fs::remove_dir(dir).await;
+        }
+
+        if uploaded > 0 {
+            debug!(
+                "Offloaded {} file(s) under {:?} to {} storage",
+                uploaded,
+                path,
+                self.backend.name()
+            );
+        }
+        Ok(uploaded)
+    }
+
+    /// Offload several files or directories, logging failures
+    ///
+    /// Files that fail to upload stay on local disk and are still served
+    /// from there.
+    pub async fn offload_paths(&self, paths: &[PathBuf]) {
+        for path in paths {
+            if let Err(e) = self.offload(path).await {
+                warn!("Failed to offload {:?} to {} storage: {}", path, self.backend.name(), e);
+            }
+        }
+    }
+
+    /// Delete a stored file or directory, locally and in the backend
+    pub async fn remove_stored(&self, path: &Path) -> MediaResult<()> {
+        if path.is_dir() {
+            tokio::fs::remove_dir_all(path).await?;
+        } else if path.is_file() {
+            tokio::fs::remove_file(path).await?;
+        }
+
+        if let Some(key) = self.object_key(path) {
+            if !self.stored_in_place(&key, path) {
+                self.backend.delete(&key).await?;
+                self.backend.delete_prefix(&format!("{}/", key)).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Move a stored file or directory to another path, locally and in the backend
+    ///
+    /// Returns whether anything was found to move.
+    pub async fn move_stored(&self, from: &Path, to: &Path) -> MediaResult<bool> {
+        let mut moved = false;
+        if from.exists() {
+            if let Some(parent) = to.parent() {
+                tokio::fs::create_dir_all(parent).await?;
+            }
+            tokio::fs::rename(from, to).await?;
+            moved = true;
+        }
+
+        if let (Some(from_key), Some(to_key)) = (self.object_key(from), self.object_key(to)) {
+            if !self.stored_in_place(&from_key, from) {
+                let mut objects = self.backend.list(&format!("{}/", from_key)).await?;
+                if self.backend.exists(&from_key).await? {
+                    objects.push(from_key.clone());
+                }
+                for object in objects {
+                    let target = format!("{}{}", to_key, &object[from_key.len()..]);
+                    let data = self.backend.get(&object).await?;
+                    self.backend.put(&target, data).await?;
+                    self.backend.delete(&object).await?;
+                    moved = true;
+                }
+            }
+        }
+        Ok(moved)
+    }
+
     /// Get the vaults root directory
     ///
     /// Returns: `storage/vaults/`
@@ -154,6 +433,54 @@ impl UserStorageManager {
             .join(media_type.dir_name())
     }
 
+    /// Get the trash directory of a vault
+    ///
+    /// Vault format: `storage/vaults/{vault_id}/.trash/`
+    pub fn vault_trash_dir(&self, vault_id: &str) -> PathBuf {
+        self.vault_storage_root(vault_id).join(".trash")
+    }
+
+    /// Where a file of a vault is kept while it is in the trash
+    ///
+    /// The layout below the vault root is preserved:
+    /// `vaults/{vault_id}/media/images/a.webp` is trashed to
+    /// `vaults/{vault_id}/.trash/media/images/a.webp`. Returns None for paths
+    /// outside the vault.
+    pub fn vault_trash_path(&self, vault_id: &str, path: &Path) -> Option<PathBuf> {
+        let relative = path.strip_prefix(self.vault_storage_root(vault_id)).ok()?;
+        Some(self.vault_trash_dir(vault_id).join(relative))
+    }
+
+    /// Get the directory holding the archived versions of a media item
+    ///
+    /// Vault format: `storage/vaults/{vault_id}/.versions/{slug}/`
+    pub fn vault_versions_dir(&self, vault_id: &str, slug: &str) -> PathBuf {
+        self.vault_storage_root(vault_id)
+            .join(".versions")
+            .join(slug)
+    }
+
+    /// Where a file of a vault is kept once archived as a version of a media item
+    ///
+    /// The layout below the vault root is preserved:
+    /// `vaults/{vault_id}/media/images/a.webp` is archived as version 2 of
+    /// `a` to `vaults/{vault_id}/.versions/a/v2/media/images/a.webp`. Returns
+    /// None for paths outside the vault.
+    pub fn vault_version_path(
+        &self,
+        vault_id: &str,
+        slug: &str,
+        version: i64,
+        path: &Path,
+    ) -> Option<PathBuf> {
+        let relative = path.strip_prefix(self.vault_storage_root(vault_id)).ok()?;
+        Some(
+            self.vault_versions_dir(vault_id, slug)
+                .join(format!("v{}", version))
+                .join(relative),
+        )
+    }
+
     /// Get the thumbnail directory for a specific user and media type (legacy)
     ///
     /// User format: `storage/users/{user_id}/thumbnails/{media_type}/`
@@ -169,6 +496,7 @@ impl UserStorageManager {
     /// - `storage/vaults/{vault_id}/`
     /// - `storage/vaults/{vault_id}/videos/`
     /// - `storage/vaults/{vault_id}/images/`
+    /// - `storage/vaults/{vault_id}/audio/`
     /// - `storage/vaults/{vault_id}/documents/`
     /// - `storage/vaults/{vault_id}/thumbnails/`
     pub fn ensure_vault_storage(&self, vault_id: &str) -> Result<()> {
@@ -178,7 +506,7 @@ impl UserStorageManager {
         ensure_dir_exists(&vault_root)?;
 
         // Create media type directories
-        for media_type in &[MediaType::Video, MediaType::Image, MediaType::Document] {
+        for media_type in &MediaType::ALL {
             let media_dir = self.vault_media_dir(vault_id, *media_type);
             ensure_dir_exists(&media_dir)?;
         }
@@ -187,7 +515,7 @@ impl UserStorageManager {
         let thumbnails_root = vault_root.join("thumbnails");
         ensure_dir_exists(&thumbnails_root)?;
 
-        for media_type in &[MediaType::Video, MediaType::Image, MediaType::Document] {
+        for media_type in &MediaType::ALL {
             let thumb_dir = self.vault_thumbnails_dir(vault_id, *media_type);
             ensure_dir_exists(&thumb_dir)?;
         }
@@ -202,6 +530,7 @@ impl UserStorageManager {
     /// - `storage/users/{user_id}/`
     /// - `storage/users/{user_id}/videos/`
     /// - `storage/users/{user_id}/images/`
+    /// - `storage/users/{user_id}/audio/`
     /// - `storage/users/{user_id}/documents/`
     /// - `storage/users/{user_id}/thumbnails/`
     pub fn ensure_user_storage(&self, user_id: &str) -> Result<()> {
@@ -211,7 +540,7 @@ impl UserStorageManager {
         ensure_dir_exists(&user_root)?;
 
         // Create media type directories
-        for media_type in &[MediaType::Video, MediaType::Image, MediaType::Document] {
+        for media_type in &MediaType::ALL {
             let media_dir = self.user_media_dir(user_id, *media_type);
             ensure_dir_exists(&media_dir)?;
         }
@@ -220,7 +549,7 @@ impl UserStorageManager {
         let thumbnails_root = user_root.join("thumbnails");
         ensure_dir_exists(&thumbnails_root)?;
 
-        for media_type in &[MediaType::Video, MediaType::Image, MediaType::Document] {
+        for media_type in &MediaType::ALL {
             let thumb_dir = self.thumbnails_dir(user_id, *media_type);
             ensure_dir_exists(&thumb_dir)?;
         }
@@ -404,6 +733,7 @@ impl UserStorageManager {
     /// - `storage/vaults/{vault_id}/media/`
     /// - `storage/vaults/{vault_id}/media/videos/`
     /// - `storage/vaults/{vault_id}/media/images/`
+    /// - `storage/vaults/{vault_id}/media/audio/`
     /// - `storage/vaults/{vault_id}/media/documents/`
     /// - `storage/vaults/{vault_id}/thumbnails/` (already exists from ensure_vault_storage)
     pub fn ensure_nested_vault_storage(&self, vault_id: &str) -> Result<()> {
@@ -417,7 +747,7 @@ impl UserStorageManager {
         ensure_dir_exists(&media_root)?;
 
         // Create media type directories under media/
-        for media_type in &[MediaType::Video, MediaType::Image, MediaType::Document] {
+        for media_type in &MediaType::ALL {
             let media_dir = self.vault_nested_media_dir(vault_id, *media_type);
             ensure_dir_exists(&media_dir)?;
         }
@@ -426,7 +756,7 @@ impl UserStorageManager {
         let thumbnails_root = vault_root.join("thumbnails");
         ensure_dir_exists(&thumbnails_root)?;
 
-        for media_type in &[MediaType::Video, MediaType::Image, MediaType::Document] {
+        for media_type in &MediaType::ALL {
             let thumb_dir = self.vault_thumbnails_dir(vault_id, *media_type);
             ensure_dir_exists(&thumb_dir)?;
         }
@@ -542,6 +872,121 @@ mod tests {
         assert!(validate_user_id(".hidden").is_err());
     }
 
+    #[tokio::test]
+    async fn test_offload_and_fetch_with_remote_backend() {
+        let temp_dir = TempDir::new().unwrap();
+        let backend = Arc::new(media_core::MemoryBackend::new());
+        let storage = UserStorageManager::with_backend(temp_dir.path(), backend.clone());
+
+        let item_dir = storage.get_media_file_path("vault-1", MediaType::Video, "clip");
+        fs::create_dir_all(item_dir.join("720p")).unwrap();
+        fs::write(item_dir.join("master.m3u8"), b"#EXTM3U").unwrap();
+        fs::write(item_dir.join("720p/segment_000.ts"), b"ts").unwrap();
+
+        assert_eq!(storage.offload(&item_dir).await.unwrap(), 2);
+        assert!(!item_dir.exists());
+        assert_eq!(backend.len(), 2);
+
+        let playlists = storage
+            .fetch_local_dir(&item_dir, |name| name.ends_with(".m3u8"))
+            .await
+            .unwrap();
+        assert_eq!(playlists, 1);
+        assert!(item_dir.join("master.m3u8").exists());
+        assert!(!item_dir.join("720p/segment_000.ts").exists());
+        fs::remove_file(item_dir.join("master.m3u8")).unwrap();
+
+        let playlist = item_dir.join("master.m3u8");
+        assert!(storage.stored_file_exists(&playlist).await);
+        let fetched = storage
+            .fetch_media_file("vault-1", MediaType::Video, "clip/master.m3u8")
+            .await
+            .unwrap();
+        assert_eq!(fs::read(fetched).unwrap(), b"#EXTM3U");
+        assert!(storage
+            .fetch_media_file("vault-1", MediaType::Video, "clip/missing.ts")
+            .await
+            .is_none());
+
+        storage.remove_stored(&item_dir).await.unwrap();
+        assert!(backend.is_empty());
+        assert!(!storage.stored_file_exists(&playlist).await);
+    }
+
+    #[tokio::test]
+    async fn test_move_stored_between_vaults() {
+        let temp_dir = TempDir::new().unwrap();
+        let backend = Arc::new(media_core::MemoryBackend::new());
+        let storage = UserStorageManager::with_backend(temp_dir.path(), backend.clone());
+
+        let from = storage.get_media_file_path("vault-1", MediaType::Video, "clip");
+        let to = storage.get_media_file_path("vault-2", MediaType::Video, "clip");
+        fs::create_dir_all(&from).unwrap();
+        fs::write(from.join("master.m3u8"), b"#EXTM3U").unwrap();
+        fs::write(from.join("segment_000.ts"), b"ts").unwrap();
+        storage.offload(&from.join("segment_000.ts")).await.unwrap();
+
+        assert!(storage.move_stored(&from, &to).await.unwrap());
+        assert!(!from.exists());
+        assert!(to.join("master.m3u8").exists());
+        assert!(!storage.stored_file_exists(&from.join("segment_000.ts")).await);
+        assert!(storage.stored_file_exists(&to.join("segment_000.ts")).await);
+        assert_eq!(backend.len(), 1);
+
+        assert!(!storage.move_stored(&from, &to).await.unwrap());
+    }
+
+    #[test]
+    fn test_vault_trash_path() {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = UserStorageManager::new(temp_dir.path());
+
+        let path = storage.get_media_file_path("vault-1", MediaType::Image, "a.webp");
+        assert_eq!(
+            storage.vault_trash_path("vault-1", &path).unwrap(),
+            temp_dir
+                .path()
+                .join("vaults/vault-1/.trash/media/images/a.webp")
+        );
+        assert!(storage.vault_trash_path("vault-2", &path).is_none());
+    }
+
+    #[test]
+    fn test_vault_version_path() {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = UserStorageManager::new(temp_dir.path());
+
+        let path = storage.get_media_file_path("vault-1", MediaType::Image, "a.webp");
+        assert_eq!(
+            storage
+                .vault_version_path("vault-1", "a", 2, &path)
+                .unwrap(),
+            temp_dir
+                .path()
+                .join("vaults/vault-1/.versions/a/v2/media/images/a.webp")
+        );
+        assert!(storage
+            .vault_version_path("vault-2", "a", 2, &path)
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_offload_is_noop_for_local_backend() {
+        let temp_dir = TempDir::new().unwrap();
+        let storage = UserStorageManager::new(temp_dir.path());
+
+        let path = storage.get_media_file_path("vault-1", MediaType::Image, "a.webp");
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, b"webp").unwrap();
+
+        assert_eq!(storage.offload(&path).await.unwrap(), 0);
+        assert!(path.exists());
+        assert_eq!(
+            storage.object_key(&path).as_deref(),
+            Some("vaults/vault-1/media/images/a.webp")
+        );
+    }
+
     #[test]
     fn test_thumbnails_dir() {
         let temp_dir = TempDir::new().unwrap();
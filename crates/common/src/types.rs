@@ -6,6 +6,7 @@ use serde::{Deserialize, Serialize};
 pub enum ResourceType {
     Video,
     Image,
+    Audio,
     File,
     Folder,
 }
@@ -15,6 +16,7 @@ impl std::fmt::Display for ResourceType {
         match self {
             ResourceType::Video => write!(f, "video"),
             ResourceType::Image => write!(f, "image"),
+            ResourceType::Audio => write!(f, "audio"),
             ResourceType::File => write!(f, "document"),
             ResourceType::Folder => write!(f, "folder"),
         }
@@ -28,6 +30,7 @@ impl std::str::FromStr for ResourceType {
         match s.to_lowercase().as_str() {
             "video" => Ok(ResourceType::Video),
             "image" => Ok(ResourceType::Image),
+            "audio" => Ok(ResourceType::Audio),
             "file" | "document" => Ok(ResourceType::File),
             "folder" => Ok(ResourceType::Folder),
             _ => Err(format!("Invalid resource type: {}", s)),
@@ -203,7 +203,7 @@ async fn extract_exif_data(file_path: &Path) -> Option<ExifData> {
 }
 
 /// Extract EXIF data from bytes
-async fn extract_exif_from_bytes(data: &[u8]) -> Option<ExifData> {
+pub async fn extract_exif_from_bytes(data: &[u8]) -> Option<ExifData> {
     let mut cursor = Cursor::new(data);
     match exif::Reader::new().read_from_container(&mut cursor) {
         Ok(exif_reader) => {
@@ -521,6 +521,237 @@ pub fn is_supported_format(format: &str) -> bool {
     )
 }
 
+// ============================================================================
+// EXIF Privacy Scrubbing
+// ============================================================================
+
+/// APP1 payload prefixes of metadata removed from JPEGs
+const JPEG_EXIF_PREFIX: &[u8] = b"Exif\0\0";
+const JPEG_XMP_PREFIXES: &[&[u8]] = &[
+    b"http://ns.adobe.com/xap/1.0/\0",
+    b"http://ns.adobe.com/xmp/extension/\0",
+];
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+/// VP8X feature flags
+const WEBP_FLAG_EXIF: u8 = 0x08;
+const WEBP_FLAG_XMP: u8 = 0x04;
+
+/// EXIF orientation (1-8) of an encoded image, if tagged
+pub fn exif_orientation(data: &[u8]) -> Option<u32> {
+    let exif = exif::Reader::new()
+        .read_from_container(&mut Cursor::new(data))
+        .ok()?;
+    exif.get_field(exif::Tag::Orientation, exif::In::PRIMARY)?
+        .value
+        .get_uint(0)
+        .filter(|o| (1..=8).contains(o))
+}
+
+/// Remove EXIF, XMP and IPTC metadata (GPS position, camera and lens, serial
+/// numbers, capture time …) from an encoded JPEG, PNG or WebP image.
+///
+/// A non-default orientation is kept in a minimal EXIF block so the image
+/// still displays upright. Returns `None` when the format isn't supported or
+/// there was nothing to remove; the original bytes can be used as they are.
+pub fn strip_private_metadata(data: &[u8]) -> Option<Vec<u8>> {
+    let orientation = exif_orientation(data).filter(|o| *o != 1);
+    let exif = orientation.and_then(orientation_only_exif);
+    let exif = exif.as_deref();
+
+    if data.starts_with(&[0xFF, 0xD8]) {
+        strip_jpeg(data, exif)
+    } else if data.starts_with(PNG_SIGNATURE) {
+        strip_png(data, exif)
+    } else if data.len() >= 12 && &data[0..4] == b"RIFF" && &data[8..12] == b"WEBP" {
+        strip_webp(data, exif)
+    } else {
+        None
+    }
+}
+
+/// A TIFF-structured EXIF block holding only the orientation tag
+fn orientation_only_exif(orientation: u32) -> Option<Vec<u8>> {
+    let field = exif::Field {
+        tag: exif::Tag::Orientation,
+        ifd_num: exif::In::PRIMARY,
+        value: exif::Value::Short(vec![orientation as u16]),
+    };
+    let mut writer = exif::experimental::Writer::new();
+    writer.push_field(&field);
+    let mut buf = Cursor::new(Vec::new());
+    writer.write(&mut buf, false).ok()?;
+    Some(buf.into_inner())
+}
+
+fn strip_jpeg(data: &[u8], mut exif: Option<&[u8]>) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(data.len());
+    out.extend_from_slice(&data[..2]);
+    let mut pos = 2;
+    let mut removed = false;
+
+    while pos + 4 <= data.len() {
+        if data[pos] != 0xFF {
+            return None;
+        }
+        let marker = data[pos + 1];
+        match marker {
+            // Fill byte
+            0xFF => {
+                out.push(0xFF);
+                pos += 1;
+                continue;
+            }
+            // Standalone markers
+            0x01 | 0xD0..=0xD8 => {
+                out.extend_from_slice(&data[pos..pos + 2]);
+                pos += 2;
+                continue;
+            }
+            // Start of scan / end of image: the rest is image data
+            0xDA | 0xD9 => break,
+            _ => {}
+        }
+
+        let len = u16::from_be_bytes([data[pos + 2], data[pos + 3]]) as usize;
+        if len < 2 || pos + 2 + len > data.len() {
+            return None;
+        }
+        let segment = &data[pos..pos + 2 + len];
+        let payload = &segment[4..];
+
+        let is_exif = marker == 0xE1 && payload.starts_with(JPEG_EXIF_PREFIX);
+        let is_xmp = marker == 0xE1 && JPEG_XMP_PREFIXES.iter().any(|p| payload.starts_with(p));
+        let is_iptc = marker == 0xED;
+        if is_exif || is_xmp || is_iptc {
+            removed = true;
+            if is_exif {
+                if let Some(tiff) = exif.take() {
+                    let len = (2 + JPEG_EXIF_PREFIX.len() + tiff.len()) as u16;
+                    out.extend_from_slice(&[0xFF, 0xE1]);
+                    out.extend_from_slice(&len.to_be_bytes());
+                    out.extend_from_slice(JPEG_EXIF_PREFIX);
+                    out.extend_from_slice(tiff);
+                }
+            }
+        } else {
+            out.extend_from_slice(segment);
+        }
+        pos += 2 + len;
+    }
+
+    if !removed {
+        return None;
+    }
+    out.extend_from_slice(&data[pos..]);
+    Some(out)
+}
+
+fn strip_png(data: &[u8], mut exif: Option<&[u8]>) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(data.len());
+    out.extend_from_slice(PNG_SIGNATURE);
+    let mut pos = PNG_SIGNATURE.len();
+    let mut removed = false;
+
+    while pos + 12 <= data.len() {
+        let len = u32::from_be_bytes(data[pos..pos + 4].try_into().ok()?) as usize;
+        let end = pos + 12 + len;
+        if end > data.len() {
+            return None;
+        }
+        let kind = &data[pos + 4..pos + 8];
+        let body = &data[pos + 8..pos + 8 + len];
+
+        // Text chunks start with a NUL-terminated keyword
+        let keyword = body.split(|b| *b == 0).next().unwrap_or_default();
+        let is_metadata_text = matches!(kind, b"tEXt" | b"zTXt" | b"iTXt")
+            && (keyword == b"XML:com.adobe.xmp" || keyword.starts_with(b"Raw profile type"));
+
+        if kind == b"eXIf" || is_metadata_text {
+            removed = true;
+            if kind == b"eXIf" {
+                if let Some(tiff) = exif.take() {
+                    let mut crc = crc32fast::Hasher::new();
+                    crc.update(b"eXIf");
+                    crc.update(tiff);
+                    out.extend_from_slice(&(tiff.len() as u32).to_be_bytes());
+                    out.extend_from_slice(b"eXIf");
+                    out.extend_from_slice(tiff);
+                    out.extend_from_slice(&crc.finalize().to_be_bytes());
+                }
+            }
+        } else {
+            out.extend_from_slice(&data[pos..end]);
+        }
+        pos = end;
+        if kind == b"IEND" {
+            break;
+        }
+    }
+
+    removed.then_some(out)
+}
+
+fn strip_webp(data: &[u8], mut exif: Option<&[u8]>) -> Option<Vec<u8>> {
+    let mut body = Vec::with_capacity(data.len());
+    let mut pos = 12;
+    let mut removed = false;
+    let mut kept_exif = false;
+    let mut vp8x_flags = None;
+
+    while pos + 8 <= data.len() {
+        let fourcc = &data[pos..pos + 4];
+        let size = u32::from_le_bytes(data[pos + 4..pos + 8].try_into().ok()?) as usize;
+        // Chunks are padded to an even size; tolerate a missing final pad byte
+        let end = (pos + 8 + size + (size & 1)).min(data.len());
+        if pos + 8 + size > data.len() {
+            return None;
+        }
+
+        match fourcc {
+            b"EXIF" | b"XMP " => {
+                removed = true;
+                if fourcc == b"EXIF" {
+                    if let Some(tiff) = exif.take() {
+                        body.extend_from_slice(b"EXIF");
+                        body.extend_from_slice(&(tiff.len() as u32).to_le_bytes());
+                        body.extend_from_slice(tiff);
+                        if tiff.len() % 2 == 1 {
+                            body.push(0);
+                        }
+                        kept_exif = true;
+                    }
+                }
+            }
+            _ => {
+                if fourcc == b"VP8X" && size > 0 {
+                    vp8x_flags = Some(body.len() + 8);
+                }
+                body.extend_from_slice(&data[pos..end]);
+            }
+        }
+        pos = end;
+    }
+
+    if !removed {
+        return None;
+    }
+    if let Some(flags) = vp8x_flags {
+        body[flags] &= !WEBP_FLAG_XMP;
+        if !kept_exif {
+            body[flags] &= !WEBP_FLAG_EXIF;
+        }
+    }
+
+    let mut out = Vec::with_capacity(body.len() + 12);
+    out.extend_from_slice(b"RIFF");
+    out.extend_from_slice(&((body.len() + 4) as u32).to_le_bytes());
+    out.extend_from_slice(b"WEBP");
+    out.extend_from_slice(&body);
+    Some(out)
+}
+
 // ============================================================================
 // Tests
 // ============================================================================
@@ -566,6 +797,116 @@ mod tests {
         assert!(!is_supported_format("pdf"));
     }
 
+    /// A small JPEG with an EXIF block holding GPS, camera and orientation
+    fn tagged_jpeg() -> Vec<u8> {
+        let img = DynamicImage::new_rgb8(8, 4);
+        let mut jpeg = Vec::new();
+        img.write_to(&mut Cursor::new(&mut jpeg), image::ImageOutputFormat::Jpeg(90))
+            .unwrap();
+
+        let fields = [
+            exif::Field {
+                tag: exif::Tag::Orientation,
+                ifd_num: exif::In::PRIMARY,
+                value: exif::Value::Short(vec![6]),
+            },
+            exif::Field {
+                tag: exif::Tag::Model,
+                ifd_num: exif::In::PRIMARY,
+                value: exif::Value::Ascii(vec![b"Pocket Cam".to_vec()]),
+            },
+            exif::Field {
+                tag: exif::Tag::GPSLatitudeRef,
+                ifd_num: exif::In::PRIMARY,
+                value: exif::Value::Ascii(vec![b"N".to_vec()]),
+            },
+            exif::Field {
+                tag: exif::Tag::GPSLatitude,
+                ifd_num: exif::In::PRIMARY,
+                value: exif::Value::Rational(vec![
+                    exif::Rational::from((52, 1)),
+                    exif::Rational::from((30, 1)),
+                    exif::Rational::from((0, 1)),
+                ]),
+            },
+        ];
+        let mut writer = exif::experimental::Writer::new();
+        for field in &fields {
+            writer.push_field(field);
+        }
+        let mut tiff = Cursor::new(Vec::new());
+        writer.write(&mut tiff, false).unwrap();
+        let tiff = tiff.into_inner();
+
+        let mut tagged = jpeg[..2].to_vec();
+        tagged.extend_from_slice(&[0xFF, 0xE1]);
+        tagged.extend_from_slice(&((2 + 6 + tiff.len()) as u16).to_be_bytes());
+        tagged.extend_from_slice(b"Exif\0\0");
+        tagged.extend_from_slice(&tiff);
+        tagged.extend_from_slice(&jpeg[2..]);
+        tagged
+    }
+
+    #[test]
+    fn test_strip_jpeg_keeps_orientation_only() {
+        let tagged = tagged_jpeg();
+        let exif = exif::Reader::new()
+            .read_from_container(&mut Cursor::new(&tagged))
+            .unwrap();
+        assert!(get_gps_coordinate(&exif, true).is_some());
+
+        let stripped = strip_private_metadata(&tagged).unwrap();
+        let exif = exif::Reader::new()
+            .read_from_container(&mut Cursor::new(&stripped))
+            .unwrap();
+        assert!(get_gps_coordinate(&exif, true).is_none());
+        assert!(exif.get_field(exif::Tag::Model, exif::In::PRIMARY).is_none());
+        assert_eq!(exif_orientation(&stripped), Some(6));
+        assert!(image::load_from_memory(&stripped).is_ok());
+    }
+
+    #[test]
+    fn test_strip_untagged_images_is_noop() {
+        let img = DynamicImage::new_rgba8(4, 4);
+        let mut png = Vec::new();
+        img.write_to(&mut Cursor::new(&mut png), image::ImageOutputFormat::Png)
+            .unwrap();
+        assert!(strip_private_metadata(&png).is_none());
+        assert!(strip_private_metadata(b"GIF89a").is_none());
+    }
+
+    #[test]
+    fn test_strip_png_and_webp_chunks() {
+        let tiff = orientation_only_exif(3).unwrap();
+
+        let mut png = PNG_SIGNATURE.to_vec();
+        for (kind, body) in [
+            (&b"IHDR"[..], &[0u8; 13][..]),
+            (b"eXIf", &tiff[..]),
+            (b"iTXt", b"XML:com.adobe.xmp\0\0\0\0\0<x/>"),
+            (b"IEND", b""),
+        ] {
+            let mut crc = crc32fast::Hasher::new();
+            crc.update(kind);
+            crc.update(body);
+            png.extend_from_slice(&(body.len() as u32).to_be_bytes());
+            png.extend_from_slice(kind);
+            png.extend_from_slice(body);
+            png.extend_from_slice(&crc.finalize().to_be_bytes());
+        }
+        let stripped = strip_png(&png, None).unwrap();
+        assert!(!stripped.windows(4).any(|w| w == b"eXIf" || w == b"iTXt"));
+        assert!(stripped.ends_with(&[0xAE, 0x42, 0x60, 0x82]));
+
+        let mut webp = b"RIFF\0\0\0\0WEBPVP8X\x0a\0\0\0".to_vec();
+        webp.extend_from_slice(&[WEBP_FLAG_EXIF | WEBP_FLAG_XMP, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+        webp.extend_from_slice(b"XMP \x03\0\0\0abc\0");
+        let stripped = strip_webp(&webp, None).unwrap();
+        assert_eq!(&stripped[4..8], &((stripped.len() - 8) as u32).to_le_bytes());
+        assert_eq!(stripped[20] & (WEBP_FLAG_EXIF | WEBP_FLAG_XMP), 0);
+        assert!(!stripped.windows(4).any(|w| w == b"XMP "));
+    }
+
     #[test]
     fn test_validation() {
         assert!(validate_dimensions(1920, 1080, 4000).is_ok());
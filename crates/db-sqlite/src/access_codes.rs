@@ -1,22 +1,61 @@
 use crate::SqliteDatabase;
-use db::access_codes::{AccessCode, AccessCodePermission, AccessCodeRepository};
+use db::access_codes::{
+    AccessCode, AccessCodePermission, AccessCodeRepository, AccessCodeRestrictions,
+    NewAccessCodeClaim,
+};
 use db::DbError;
 
 fn map_err(e: sqlx::Error) -> DbError {
     DbError::Internal(e.to_string())
 }
 
-fn row_to_access_code(row: (i32, String, Option<String>, Option<String>, String, String, Option<String>, i32, i64)) -> AccessCode {
-    AccessCode {
-        id: row.0,
-        code: row.1,
-        description: row.2,
-        expires_at: row.3,
-        created_at: row.4,
-        created_by: row.5,
-        vault_id: row.6,
-        is_active: row.7 != 0,
-        current_downloads: row.8,
+const ACCESS_CODE_COLUMNS: &str = "id, code, description, expires_at, created_at, created_by, vault_id, \
+     is_active, current_downloads, passphrase_hash IS NOT NULL AS has_passphrase, \
+     max_uses, current_uses, max_views, current_views, allowed_ips, allowed_referrers, \
+     burn_after_claim";
+
+#[derive(sqlx::FromRow)]
+struct AccessCodeSqlRow {
+    id: i32,
+    code: String,
+    description: Option<String>,
+    expires_at: Option<String>,
+    created_at: String,
+    created_by: String,
+    vault_id: Option<String>,
+    is_active: bool,
+    current_downloads: i64,
+    has_passphrase: bool,
+    max_uses: Option<i64>,
+    current_uses: i64,
+    max_views: Option<i64>,
+    current_views: i64,
+    allowed_ips: Option<String>,
+    allowed_referrers: Option<String>,
+    burn_after_claim: bool,
+}
+
+impl From<AccessCodeSqlRow> for AccessCode {
+    fn from(row: AccessCodeSqlRow) -> Self {
+        AccessCode {
+            id: row.id,
+            code: row.code,
+            description: row.description,
+            expires_at: row.expires_at,
+            created_at: row.created_at,
+            created_by: row.created_by,
+            vault_id: row.vault_id,
+            is_active: row.is_active,
+            current_downloads: row.current_downloads,
+            has_passphrase: row.has_passphrase,
+            max_uses: row.max_uses,
+            current_uses: row.current_uses,
+            max_views: row.max_views,
+            current_views: row.current_views,
+            allowed_ips: row.allowed_ips,
+            allowed_referrers: row.allowed_referrers,
+            burn_after_claim: row.burn_after_claim,
+        }
     }
 }
 
@@ -39,16 +78,24 @@ impl AccessCodeRepository for SqliteDatabase {
         expires_at: Option<&str>,
         created_by: &str,
         vault_id: Option<&str>,
+        restrictions: &AccessCodeRestrictions<'_>,
     ) -> Result<i32, DbError> {
         let id: i32 = sqlx::query_scalar(
-            "INSERT INTO access_codes (code, description, expires_at, created_by, vault_id) \
-             VALUES (?, ?, ?, ?, ?) RETURNING id",
+            "INSERT INTO access_codes (code, description, expires_at, created_by, vault_id, \
+             passphrase_hash, max_uses, max_views, allowed_ips, allowed_referrers, burn_after_claim) \
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?) RETURNING id",
         )
         .bind(code)
         .bind(description)
         .bind(expires_at)
         .bind(created_by)
         .bind(vault_id)
+        .bind(restrictions.passphrase_hash)
+        .bind(restrictions.max_uses)
+        .bind(restrictions.max_views)
+        .bind(restrictions.allowed_ips)
+        .bind(restrictions.allowed_referrers)
+        .bind(restrictions.burn_after_claim)
         .fetch_one(self.pool())
         .await
         .map_err(map_err)?;
@@ -60,40 +107,38 @@ impl AccessCodeRepository for SqliteDatabase {
         code: &str,
         user_id: &str,
     ) -> Result<Option<AccessCode>, DbError> {
-        let row: Option<(i32, String, Option<String>, Option<String>, String, String, Option<String>, i32, i64)> = sqlx::query_as(
-            "SELECT id, code, description, expires_at, created_at, created_by, vault_id, is_active, current_downloads \
-             FROM access_codes WHERE code = ? AND created_by = ?",
-        )
+        let row: Option<AccessCodeSqlRow> = sqlx::query_as(&format!(
+            "SELECT {ACCESS_CODE_COLUMNS} FROM access_codes WHERE code = ? AND created_by = ?"
+        ))
         .bind(code)
         .bind(user_id)
         .fetch_optional(self.pool())
         .await
         .map_err(map_err)?;
-        Ok(row.map(row_to_access_code))
+        Ok(row.map(AccessCode::from))
     }
 
     async fn get_active_code(&self, code: &str) -> Result<Option<AccessCode>, DbError> {
-        let row: Option<(i32, String, Option<String>, Option<String>, String, String, Option<String>, i32, i64)> = sqlx::query_as(
-            "SELECT id, code, description, expires_at, created_at, created_by, vault_id, is_active, current_downloads \
-             FROM access_codes WHERE code = ? AND is_active = 1",
-        )
+        let row: Option<AccessCodeSqlRow> = sqlx::query_as(&format!(
+            "SELECT {ACCESS_CODE_COLUMNS} FROM access_codes WHERE code = ? AND is_active = 1"
+        ))
         .bind(code)
         .fetch_optional(self.pool())
         .await
         .map_err(map_err)?;
-        Ok(row.map(row_to_access_code))
+        Ok(row.map(AccessCode::from))
     }
 
     async fn list_user_codes(&self, user_id: &str) -> Result<Vec<AccessCode>, DbError> {
-        let rows: Vec<(i32, String, Option<String>, Option<String>, String, String, Option<String>, i32, i64)> = sqlx::query_as(
-            "SELECT id, code, description, expires_at, created_at, created_by, vault_id, is_active, current_downloads \
-             FROM access_codes WHERE created_by = ? ORDER BY created_at DESC",
-        )
+        let rows: Vec<AccessCodeSqlRow> = sqlx::query_as(&format!(
+            "SELECT {ACCESS_CODE_COLUMNS} FROM access_codes \
+             WHERE created_by = ? ORDER BY created_at DESC"
+        ))
         .bind(user_id)
         .fetch_all(self.pool())
         .await
         .map_err(map_err)?;
-        Ok(rows.into_iter().map(row_to_access_code).collect())
+        Ok(rows.into_iter().map(AccessCode::from).collect())
     }
 
     async fn delete_code(&self, code: &str, user_id: &str) -> Result<bool, DbError> {
@@ -122,6 +167,57 @@ impl AccessCodeRepository for SqliteDatabase {
         Ok(id)
     }
 
+    // ── Claims ──────────────────────────────────────────────────────
+
+    async fn get_passphrase_hash(&self, code_id: i32) -> Result<Option<String>, DbError> {
+        let hash: Option<Option<String>> =
+            sqlx::query_scalar("SELECT passphrase_hash FROM access_codes WHERE id = ?")
+                .bind(code_id)
+                .fetch_optional(self.pool())
+                .await
+                .map_err(map_err)?;
+        Ok(hash.flatten())
+    }
+
+    async fn claim_code(
+        &self,
+        code_id: i32,
+        claim: &NewAccessCodeClaim<'_>,
+    ) -> Result<bool, DbError> {
+        let mut tx = self.pool().begin().await.map_err(map_err)?;
+
+        let counted = sqlx::query(
+            "UPDATE access_codes SET current_uses = current_uses + 1 \
+             WHERE id = ? AND is_active = 1 \
+               AND (max_uses IS NULL OR current_uses < max_uses) \
+               AND (burn_after_claim = 0 OR current_uses = 0)",
+        )
+        .bind(code_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(map_err)?;
+
+        if counted.rows_affected() == 0 {
+            return Ok(false);
+        }
+
+        sqlx::query(
+            "INSERT INTO access_code_claims (access_code_id, token_hash, ip_address, user_agent, referer) \
+             VALUES (?, ?, ?, ?, ?)",
+        )
+        .bind(code_id)
+        .bind(claim.token_hash)
+        .bind(claim.ip_address)
+        .bind(claim.user_agent)
+        .bind(claim.referer)
+        .execute(&mut *tx)
+        .await
+        .map_err(map_err)?;
+
+        tx.commit().await.map_err(map_err)?;
+        Ok(true)
+    }
+
     // ── Permissions ─────────────────────────────────────────────────
 
     async fn add_permission(
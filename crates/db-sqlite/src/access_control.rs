@@ -45,6 +45,15 @@ impl AccessControlRepository for SqliteDatabase {
                 .await
                 .map_err(map_err)?
             }
+            "audio" => {
+                sqlx::query_scalar(
+                    "SELECT is_public FROM media_items WHERE id = ? AND media_type = 'audio'",
+                )
+                .bind(resource_id)
+                .fetch_optional(self.pool())
+                .await
+                .map_err(map_err)?
+            }
             "folder" => {
                 sqlx::query_scalar("SELECT is_public FROM folders WHERE id = ?")
                     .bind(resource_id)
@@ -90,6 +99,15 @@ impl AccessControlRepository for SqliteDatabase {
                 .await
                 .map_err(map_err)
             }
+            "audio" => {
+                sqlx::query_scalar(
+                    "SELECT user_id FROM media_items WHERE id = ? AND media_type = 'audio'",
+                )
+                .bind(resource_id)
+                .fetch_optional(self.pool())
+                .await
+                .map_err(map_err)
+            }
             "folder" => {
                 sqlx::query_scalar("SELECT user_id FROM folders WHERE id = ?")
                     .bind(resource_id)
@@ -136,6 +154,15 @@ impl AccessControlRepository for SqliteDatabase {
                 .await
                 .map_err(map_err)
             }
+            "audio" => {
+                sqlx::query_scalar(
+                    "SELECT group_id FROM media_items WHERE id = ? AND media_type = 'audio'",
+                )
+                .bind(resource_id)
+                .fetch_optional(self.pool())
+                .await
+                .map_err(map_err)
+            }
             "folder" => {
                 sqlx::query_scalar("SELECT group_id FROM folders WHERE id = ?")
                     .bind(resource_id)
@@ -176,6 +203,14 @@ impl AccessControlRepository for SqliteDatabase {
             max_downloads: Option<i32>,
             current_downloads: i32,
             is_active: bool,
+            vault_id: Option<String>,
+            has_passphrase: bool,
+            max_uses: Option<i32>,
+            max_views: Option<i32>,
+            current_views: i32,
+            allowed_ips: Option<String>,
+            allowed_referrers: Option<String>,
+            burn_after_claim: bool,
         }
 
         let row: Option<Row> = sqlx::query_as(
@@ -189,7 +224,15 @@ impl AccessControlRepository for SqliteDatabase {
                 expires_at,
                 max_downloads,
                 current_downloads,
-                is_active
+                is_active,
+                vault_id,
+                passphrase_hash IS NOT NULL AS has_passphrase,
+                max_uses,
+                max_views,
+                current_views,
+                allowed_ips,
+                allowed_referrers,
+                burn_after_claim
              FROM access_codes
              WHERE code = ? AND is_active = 1",
         )
@@ -209,6 +252,14 @@ impl AccessControlRepository for SqliteDatabase {
             max_downloads: r.max_downloads,
             current_downloads: r.current_downloads,
             is_active: r.is_active,
+            vault_id: r.vault_id,
+            has_passphrase: r.has_passphrase,
+            max_uses: r.max_uses,
+            max_views: r.max_views,
+            current_views: r.current_views,
+            allowed_ips: r.allowed_ips,
+            allowed_referrers: r.allowed_referrers,
+            burn_after_claim: r.burn_after_claim,
         }))
     }
 
@@ -264,6 +315,58 @@ impl AccessControlRepository for SqliteDatabase {
         Ok(())
     }
 
+    async fn increment_view_count(&self, key: &str) -> Result<bool, DbError> {
+        let result = sqlx::query(
+            "UPDATE access_codes
+             SET current_views = current_views + 1,
+                 last_accessed_at = CURRENT_TIMESTAMP
+             WHERE code = ?
+               AND (max_views IS NULL OR current_views < max_views)",
+        )
+        .bind(key)
+        .execute(self.pool())
+        .await
+        .map_err(map_err)?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn access_code_claim_exists(
+        &self,
+        access_code_id: i32,
+        token_hash: &str,
+    ) -> Result<bool, DbError> {
+        let exists: bool = sqlx::query_scalar(
+            "SELECT EXISTS(
+                SELECT 1 FROM access_code_claims
+                WHERE access_code_id = ? AND token_hash = ?
+            )",
+        )
+        .bind(access_code_id)
+        .bind(token_hash)
+        .fetch_one(self.pool())
+        .await
+        .map_err(map_err)?;
+
+        Ok(exists)
+    }
+
+    async fn get_resource_vault(
+        &self,
+        resource_type: &str,
+        resource_id: i32,
+    ) -> Result<Option<String>, DbError> {
+        let vault_id: Option<Option<String>> =
+            sqlx::query_scalar("SELECT vault_id FROM media_items WHERE id = ? AND media_type = ?")
+                .bind(resource_id)
+                .bind(resource_type)
+                .fetch_optional(self.pool())
+                .await
+                .map_err(map_err)?;
+
+        Ok(vault_id.flatten())
+    }
+
     async fn resource_exists(
         &self,
         resource_type: &str,
@@ -297,6 +400,15 @@ impl AccessControlRepository for SqliteDatabase {
                 .await
                 .map_err(map_err)?
             }
+            "audio" => {
+                sqlx::query_scalar(
+                    "SELECT EXISTS(SELECT 1 FROM media_items WHERE id = ? AND media_type = 'audio')",
+                )
+                .bind(resource_id)
+                .fetch_one(self.pool())
+                .await
+                .map_err(map_err)?
+            }
             "folder" => {
                 sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM folders WHERE id = ?)")
                     .bind(resource_id)
@@ -342,6 +454,15 @@ impl AccessControlRepository for SqliteDatabase {
                 .await
                 .map_err(map_err)
             }
+            "audio" => {
+                sqlx::query_scalar(
+                    "SELECT title FROM media_items WHERE id = ? AND media_type = 'audio'",
+                )
+                .bind(resource_id)
+                .fetch_optional(self.pool())
+                .await
+                .map_err(map_err)
+            }
             "folder" => {
                 sqlx::query_scalar("SELECT name FROM folders WHERE id = ?")
                     .bind(resource_id)
@@ -411,6 +532,10 @@ impl AccessControlRepository for SqliteDatabase {
                 "SELECT id, is_public FROM media_items WHERE media_type = 'document' AND id IN ({})",
                 placeholders
             ),
+            "audio" => format!(
+                "SELECT id, is_public FROM media_items WHERE media_type = 'audio' AND id IN ({})",
+                placeholders
+            ),
             "folder" => format!(
                 "SELECT id, is_public FROM folders WHERE id IN ({})",
                 placeholders
@@ -459,6 +584,15 @@ impl AccessControlRepository for SqliteDatabase {
                 .await
                 .map_err(map_err)
             }
+            "audio" => {
+                sqlx::query_scalar(
+                    "SELECT status FROM media_items WHERE id = ? AND media_type = 'audio'",
+                )
+                .bind(resource_id)
+                .fetch_optional(self.pool())
+                .await
+                .map_err(map_err)
+            }
             "folder" => {
                 sqlx::query_scalar("SELECT visibility FROM folders WHERE id = ?")
                     .bind(resource_id)
@@ -555,6 +689,57 @@ impl AuditRepository for SqliteDatabase {
         }).collect())
     }
 
+    async fn get_access_key_audit_log(
+        &self,
+        access_key: &str,
+        limit: i32,
+    ) -> Result<Vec<AuditLogRow>, DbError> {
+        #[derive(sqlx::FromRow)]
+        struct Row {
+            id: i32,
+            user_id: Option<String>,
+            access_key: Option<String>,
+            ip_address: Option<String>,
+            user_agent: Option<String>,
+            resource_type: String,
+            resource_id: i32,
+            permission_requested: String,
+            permission_granted: Option<String>,
+            access_granted: bool,
+            access_layer: String,
+            reason: String,
+            created_at: String,
+        }
+
+        let rows: Vec<Row> = sqlx::query_as(
+            "SELECT * FROM access_audit_log
+             WHERE access_key = ?
+             ORDER BY created_at DESC
+             LIMIT ?",
+        )
+        .bind(access_key)
+        .bind(limit)
+        .fetch_all(self.pool())
+        .await
+        .map_err(map_err)?;
+
+        Ok(rows.into_iter().map(|r| AuditLogRow {
+            id: r.id,
+            user_id: r.user_id,
+            access_key: r.access_key,
+            ip_address: r.ip_address,
+            user_agent: r.user_agent,
+            resource_type: r.resource_type,
+            resource_id: r.resource_id,
+            permission_requested: r.permission_requested,
+            permission_granted: r.permission_granted,
+            access_granted: r.access_granted,
+            access_layer: r.access_layer,
+            reason: r.reason,
+            created_at: r.created_at,
+        }).collect())
+    }
+
     async fn get_denied_attempts(
         &self,
         since_iso: &str,
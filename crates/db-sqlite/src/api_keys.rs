@@ -22,6 +22,10 @@ struct ApiKeyRow {
     expires_at: Option<String>,
     created_at: String,
     is_active: bool,
+    workspace_id: Option<String>,
+    vault_id: Option<String>,
+    path_prefix: Option<String>,
+    allowed_ips: Option<String>,
 }
 
 impl From<ApiKeyRow> for ApiKey {
@@ -38,6 +42,10 @@ impl From<ApiKeyRow> for ApiKey {
             expires_at: r.expires_at,
             created_at: r.created_at,
             is_active: r.is_active,
+            workspace_id: r.workspace_id,
+            vault_id: r.vault_id,
+            path_prefix: r.path_prefix,
+            allowed_ips: r.allowed_ips,
         }
     }
 }
@@ -66,10 +74,13 @@ impl ApiKeyRepository for SqliteDatabase {
         request: &CreateApiKeyRequest,
     ) -> Result<ApiKey, DbError> {
         let scopes_json = serde_json::to_string(&request.scopes).unwrap_or_else(|_| "[]".into());
+        let allowed_ips =
+            (!request.allowed_ips.is_empty()).then(|| request.allowed_ips.join("\n"));
 
         let result = sqlx::query(
-            "INSERT INTO user_api_keys (user_id, key_hash, key_prefix, name, description, scopes, expires_at) \
-             VALUES (?, ?, ?, ?, ?, ?, ?)",
+            "INSERT INTO user_api_keys (user_id, key_hash, key_prefix, name, description, scopes, expires_at, \
+             workspace_id, vault_id, path_prefix, allowed_ips) \
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
         )
         .bind(user_id)
         .bind(key_hash)
@@ -78,6 +89,10 @@ impl ApiKeyRepository for SqliteDatabase {
         .bind(&request.description)
         .bind(&scopes_json)
         .bind(&request.expires_at)
+        .bind(&request.workspace_id)
+        .bind(&request.vault_id)
+        .bind(&request.path_prefix)
+        .bind(&allowed_ips)
         .execute(&self.pool)
         .await
         .map_err(map_err)?;
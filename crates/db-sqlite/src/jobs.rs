@@ -0,0 +1,342 @@
+//! SQLite implementation of [`db::jobs::JobRepository`].
+
+use db::jobs::{CreateTranscodeJob, JobListFilter, JobRepository, TranscodeJob};
+use db::DbError;
+
+use crate::SqliteDatabase;
+
+// ============================================================================
+// Internal row types
+// ============================================================================
+
+#[derive(sqlx::FromRow)]
+struct JobRow {
+    id: i64,
+    upload_id: String,
+    slug: String,
+    vault_id: String,
+    user_id: Option<String>,
+    source_path: String,
+    original_filename: String,
+    is_public: i32,
+    priority: i64,
+    status: String,
+    attempts: i64,
+    max_attempts: i64,
+    error: Option<String>,
+    created_at: String,
+    started_at: Option<String>,
+    finished_at: Option<String>,
+    updated_at: String,
+}
+
+impl From<JobRow> for TranscodeJob {
+    fn from(r: JobRow) -> Self {
+        Self {
+            id: r.id,
+            upload_id: r.upload_id,
+            slug: r.slug,
+            vault_id: r.vault_id,
+            user_id: r.user_id,
+            source_path: r.source_path,
+            original_filename: r.original_filename,
+            is_public: r.is_public != 0,
+            priority: r.priority,
+            status: r.status,
+            attempts: r.attempts,
+            max_attempts: r.max_attempts,
+            error: r.error,
+            created_at: r.created_at,
+            started_at: r.started_at,
+            finished_at: r.finished_at,
+            updated_at: r.updated_at,
+        }
+    }
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+fn map_sqlx_err(e: sqlx::Error) -> DbError {
+    match &e {
+        sqlx::Error::Database(db_err) if db_err.message().contains("UNIQUE") => {
+            DbError::UniqueViolation(db_err.message().to_string())
+        }
+        _ => DbError::Internal(e.to_string()),
+    }
+}
+
+// ============================================================================
+// Repository implementation
+// ============================================================================
+
+#[async_trait::async_trait]
+impl JobRepository for SqliteDatabase {
+    async fn enqueue_job(&self, job: &CreateTranscodeJob) -> Result<i64, DbError> {
+        let result = sqlx::query(
+            "INSERT INTO transcode_jobs (upload_id, slug, vault_id, user_id, source_path, original_filename, is_public, priority, max_attempts)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(&job.upload_id)
+        .bind(&job.slug)
+        .bind(&job.vault_id)
+        .bind(&job.user_id)
+        .bind(&job.source_path)
+        .bind(&job.original_filename)
+        .bind(job.is_public as i32)
+        .bind(job.priority)
+        .bind(job.max_attempts)
+        .execute(&self.pool)
+        .await
+        .map_err(map_sqlx_err)?;
+
+        Ok(result.last_insert_rowid())
+    }
+
+    async fn claim_next_job(&self) -> Result<Option<TranscodeJob>, DbError> {
+        // Single statement so two workers can never claim the same row.
+        sqlx::query_as::<_, JobRow>(
+            "UPDATE transcode_jobs
+             SET status = 'running', attempts = attempts + 1, error = NULL,
+                 started_at = datetime('now'), updated_at = datetime('now')
+             WHERE id = (
+                 SELECT id FROM transcode_jobs
+                 WHERE status = 'queued'
+                 ORDER BY priority DESC, id ASC
+                 LIMIT 1
+             )
+             RETURNING *",
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map(|opt| opt.map(Into::into))
+        .map_err(map_sqlx_err)
+    }
+
+    async fn get_job(&self, id: i64) -> Result<Option<TranscodeJob>, DbError> {
+        sqlx::query_as::<_, JobRow>("SELECT * FROM transcode_jobs WHERE id = ?")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await
+            .map(|opt| opt.map(Into::into))
+            .map_err(map_sqlx_err)
+    }
+
+    async fn get_latest_job_for_slug(&self, slug: &str) -> Result<Option<TranscodeJob>, DbError> {
+        sqlx::query_as::<_, JobRow>(
+            "SELECT * FROM transcode_jobs WHERE slug = ? ORDER BY id DESC LIMIT 1",
+        )
+        .bind(slug)
+        .fetch_optional(&self.pool)
+        .await
+        .map(|opt| opt.map(Into::into))
+        .map_err(map_sqlx_err)
+    }
+
+    async fn list_jobs(&self, filter: &JobListFilter) -> Result<Vec<TranscodeJob>, DbError> {
+        let limit = if filter.limit > 0 { filter.limit } else { 100 };
+        sqlx::query_as::<_, JobRow>(
+            "SELECT * FROM transcode_jobs
+             WHERE (? IS NULL OR user_id = ?)
+               AND (? IS NULL OR status = ?)
+             ORDER BY id DESC
+             LIMIT ?",
+        )
+        .bind(&filter.user_id)
+        .bind(&filter.user_id)
+        .bind(&filter.status)
+        .bind(&filter.status)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+        .map(|rows| rows.into_iter().map(Into::into).collect())
+        .map_err(map_sqlx_err)
+    }
+
+    async fn complete_job(&self, id: i64) -> Result<bool, DbError> {
+        let result = sqlx::query(
+            "UPDATE transcode_jobs
+             SET status = 'completed', error = NULL,
+                 finished_at = datetime('now'), updated_at = datetime('now')
+             WHERE id = ? AND status = 'running'",
+        )
+        .bind(id)
+        .execute(&self.pool)
+        .await
+        .map_err(map_sqlx_err)?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn fail_job(&self, id: i64, error: &str) -> Result<Option<String>, DbError> {
+        sqlx::query_scalar::<_, String>(
+            "UPDATE transcode_jobs
+             SET status = CASE WHEN attempts < max_attempts THEN 'queued' ELSE 'failed' END,
+                 error = ?,
+                 finished_at = CASE WHEN attempts < max_attempts THEN NULL ELSE datetime('now') END,
+                 updated_at = datetime('now')
+             WHERE id = ? AND status = 'running'
+             RETURNING status",
+        )
+        .bind(error)
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(map_sqlx_err)
+    }
+
+    async fn cancel_job(&self, id: i64, user_id: &str) -> Result<bool, DbError> {
+        let result = sqlx::query(
+            "UPDATE transcode_jobs
+             SET status = 'cancelled', finished_at = datetime('now'), updated_at = datetime('now')
+             WHERE id = ? AND user_id = ? AND status = 'queued'",
+        )
+        .bind(id)
+        .bind(user_id)
+        .execute(&self.pool)
+        .await
+        .map_err(map_sqlx_err)?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn requeue_interrupted_jobs(&self) -> Result<u64, DbError> {
+        // A job interrupted during its last attempt is failed rather than
+        // retried, like a job whose last attempt returned an error
+        let statuses = sqlx::query_scalar::<_, String>(
+            "UPDATE transcode_jobs
+             SET status = CASE WHEN attempts < max_attempts THEN 'queued' ELSE 'failed' END,
+                 error = CASE WHEN attempts < max_attempts THEN error
+                              ELSE 'Interrupted by a server restart during the last attempt' END,
+                 started_at = CASE WHEN attempts < max_attempts THEN NULL ELSE started_at END,
+                 finished_at = CASE WHEN attempts < max_attempts THEN NULL ELSE datetime('now') END,
+                 updated_at = datetime('now')
+             WHERE status = 'running'
+             RETURNING status",
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(map_sqlx_err)?;
+
+        Ok(statuses.iter().filter(|s| *s == "queued").count() as u64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use db::jobs::{JOB_STATUS_FAILED, JOB_STATUS_QUEUED, JOB_STATUS_RUNNING};
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    async fn setup_db() -> SqliteDatabase {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::raw_sql(include_str!(
+            "../../../migrations/20260401120000_transcode_jobs.sql"
+        ))
+        .execute(&pool)
+        .await
+        .unwrap();
+        SqliteDatabase::new(pool)
+    }
+
+    fn job(upload_id: &str, priority: i64, max_attempts: i64) -> CreateTranscodeJob {
+        CreateTranscodeJob {
+            upload_id: upload_id.to_string(),
+            slug: upload_id.to_string(),
+            vault_id: "vault".to_string(),
+            user_id: Some("user".to_string()),
+            source_path: format!("/tmp/{}.mp4", upload_id),
+            original_filename: format!("{}.mp4", upload_id),
+            is_public: false,
+            priority,
+            max_attempts,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_claim_is_exclusive() {
+        let db = setup_db().await;
+        let low = db.enqueue_job(&job("low", 0, 3)).await.unwrap();
+        let high = db.enqueue_job(&job("high", 5, 3)).await.unwrap();
+
+        let (a, b, c) = tokio::join!(
+            db.claim_next_job(),
+            db.claim_next_job(),
+            db.claim_next_job()
+        );
+        let mut claimed: Vec<i64> = [a, b, c]
+            .into_iter()
+            .filter_map(|r| r.unwrap())
+            .map(|j| {
+                assert_eq!(j.status, JOB_STATUS_RUNNING);
+                assert_eq!(j.attempts, 1);
+                j.id
+            })
+            .collect();
+        claimed.sort();
+        assert_eq!(claimed, vec![low, high]);
+        assert!(db.claim_next_job().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_fail_retries_up_to_max_attempts() {
+        let db = setup_db().await;
+        let id = db.enqueue_job(&job("flaky", 0, 2)).await.unwrap();
+
+        assert_eq!(db.claim_next_job().await.unwrap().unwrap().id, id);
+        assert_eq!(
+            db.fail_job(id, "ffmpeg crashed").await.unwrap().as_deref(),
+            Some(JOB_STATUS_QUEUED)
+        );
+
+        let retry = db.claim_next_job().await.unwrap().unwrap();
+        assert_eq!((retry.id, retry.attempts), (id, 2));
+        assert!(retry.error.is_none());
+        assert_eq!(
+            db.fail_job(id, "ffmpeg crashed again")
+                .await
+                .unwrap()
+                .as_deref(),
+            Some(JOB_STATUS_FAILED)
+        );
+
+        let failed = db.get_job(id).await.unwrap().unwrap();
+        assert_eq!(failed.error.as_deref(), Some("ffmpeg crashed again"));
+        assert!(failed.finished_at.is_some());
+        assert!(db.claim_next_job().await.unwrap().is_none());
+        // Only running jobs can fail
+        assert!(db.fail_job(id, "late").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_requeue_interrupted_jobs() {
+        let db = setup_db().await;
+        let retryable = db.enqueue_job(&job("retryable", 1, 3)).await.unwrap();
+        let exhausted = db.enqueue_job(&job("exhausted", 0, 1)).await.unwrap();
+        let waiting = db.enqueue_job(&job("waiting", 0, 3)).await.unwrap();
+        assert_eq!(db.claim_next_job().await.unwrap().unwrap().id, retryable);
+        assert_eq!(db.claim_next_job().await.unwrap().unwrap().id, exhausted);
+
+        assert_eq!(db.requeue_interrupted_jobs().await.unwrap(), 1);
+
+        let requeued = db.get_job(retryable).await.unwrap().unwrap();
+        assert_eq!(requeued.status, JOB_STATUS_QUEUED);
+        assert!(requeued.started_at.is_none());
+
+        let failed = db.get_job(exhausted).await.unwrap().unwrap();
+        assert_eq!(failed.status, JOB_STATUS_FAILED);
+        assert!(failed.error.is_some());
+        assert!(failed.finished_at.is_some());
+
+        assert_eq!(
+            db.get_job(waiting).await.unwrap().unwrap().status,
+            JOB_STATUS_QUEUED
+        );
+        assert_eq!(db.requeue_interrupted_jobs().await.unwrap(), 0);
+    }
+}
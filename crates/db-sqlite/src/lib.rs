@@ -10,6 +10,7 @@ pub mod agents;
 pub mod api_keys;
 pub mod federation;
 pub mod git_providers;
+pub mod jobs;
 pub mod llm_providers;
 pub mod media;
 pub mod processes;
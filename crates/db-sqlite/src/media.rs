@@ -146,6 +146,450 @@ impl From<FullMediaRow> for MediaItemRow {
     }
 }
 
+#[derive(sqlx::FromRow)]
+struct PublicCatalogSqlRow {
+    slug: String,
+    media_type: String,
+    title: String,
+    description: Option<String>,
+    filename: Option<String>,
+    mime_type: Option<String>,
+    file_size: Option<i64>,
+    created_at: String,
+    updated_at: Option<String>,
+    subtitle_languages: Option<String>,
+}
+
+impl From<PublicCatalogSqlRow> for PublicCatalogRow {
+    fn from(r: PublicCatalogSqlRow) -> Self {
+        Self {
+            slug: r.slug,
+            media_type: r.media_type,
+            title: r.title,
+            description: r.description,
+            filename: r.filename,
+            mime_type: r.mime_type,
+            file_size: r.file_size,
+            created_at: r.created_at,
+            updated_at: r.updated_at,
+            subtitle_languages: r
+                .subtitle_languages
+                .map(|langs| langs.split(',').map(str::to_string).collect())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// Column list for [`PublicCatalogSqlRow`]
+const PUBLIC_CATALOG_COLUMNS: &str =
+    "slug, media_type, title, description, filename, mime_type, file_size, \
+     created_at, updated_at, \
+     (SELECT group_concat(language) FROM media_subtitles \
+      WHERE media_subtitles.media_id = media_items.id) AS subtitle_languages";
+
+#[derive(sqlx::FromRow)]
+struct SubtitleSqlRow {
+    id: i64,
+    media_id: i64,
+    language: String,
+    label: String,
+    is_default: i32,
+    auto_generated: i32,
+    created_at: String,
+}
+
+impl From<SubtitleSqlRow> for SubtitleTrack {
+    fn from(r: SubtitleSqlRow) -> Self {
+        Self {
+            id: r.id,
+            media_id: r.media_id,
+            language: r.language,
+            label: r.label,
+            is_default: r.is_default != 0,
+            auto_generated: r.auto_generated != 0,
+            created_at: r.created_at,
+        }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct ChapterSqlRow {
+    id: i64,
+    media_id: i64,
+    start_time: f64,
+    end_time: Option<f64>,
+    title: String,
+}
+
+impl From<ChapterSqlRow> for VideoChapter {
+    fn from(r: ChapterSqlRow) -> Self {
+        Self {
+            id: r.id,
+            media_id: r.media_id,
+            start_time: r.start_time,
+            end_time: r.end_time,
+            title: r.title,
+        }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct TranscriptSqlRow {
+    media_id: i64,
+    status: String,
+    language: Option<String>,
+    backend: String,
+    error: Option<String>,
+    updated_at: String,
+}
+
+impl From<TranscriptSqlRow> for MediaTranscript {
+    fn from(r: TranscriptSqlRow) -> Self {
+        Self {
+            media_id: r.media_id,
+            status: r.status,
+            language: r.language,
+            backend: r.backend,
+            error: r.error,
+            updated_at: r.updated_at,
+        }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct LiveSessionSqlRow {
+    id: i64,
+    media_id: i64,
+    slug: String,
+    stream_path: String,
+    recording_path: String,
+    status: String,
+    started_at: String,
+    ended_at: Option<String>,
+    peak_viewers: i64,
+    total_viewers: i64,
+}
+
+impl From<LiveSessionSqlRow> for LiveSession {
+    fn from(r: LiveSessionSqlRow) -> Self {
+        Self {
+            id: r.id,
+            media_id: r.media_id,
+            slug: r.slug,
+            stream_path: r.stream_path,
+            recording_path: r.recording_path,
+            status: r.status,
+            started_at: r.started_at,
+            ended_at: r.ended_at,
+            peak_viewers: r.peak_viewers,
+            total_viewers: r.total_viewers,
+        }
+    }
+}
+
+/// Columns of [`LiveSessionSqlRow`], joined with the media item's slug
+const LIVE_SESSION_COLUMNS: &str = "s.id, s.media_id, m.slug, s.stream_path, s.recording_path, \
+     s.status, s.started_at, s.ended_at, s.peak_viewers, s.total_viewers \
+     FROM live_sessions s JOIN media_items m ON m.id = s.media_id";
+
+#[derive(sqlx::FromRow)]
+struct LiveStreamSqlRow {
+    id: i64,
+    user_id: String,
+    name: String,
+    stream_path: String,
+    key_hash: String,
+    key_prefix: String,
+    key_rotated_at: String,
+    expires_at: Option<String>,
+    expired: bool,
+    access: String,
+    access_code: Option<String>,
+    group_id: Option<i32>,
+    record: bool,
+    created_at: String,
+    updated_at: String,
+}
+
+impl From<LiveStreamSqlRow> for LiveStream {
+    fn from(r: LiveStreamSqlRow) -> Self {
+        Self {
+            id: r.id,
+            user_id: r.user_id,
+            name: r.name,
+            stream_path: r.stream_path,
+            key_hash: r.key_hash,
+            key_prefix: r.key_prefix,
+            key_rotated_at: r.key_rotated_at,
+            expires_at: r.expires_at,
+            expired: r.expired,
+            access: r.access,
+            access_code: r.access_code,
+            group_id: r.group_id,
+            record: r.record,
+            created_at: r.created_at,
+            updated_at: r.updated_at,
+        }
+    }
+}
+
+/// Columns of [`LiveStreamSqlRow`]
+const LIVE_STREAM_COLUMNS: &str = "id, user_id, name, stream_path, key_hash, key_prefix, \
+     key_rotated_at, expires_at, \
+     (expires_at IS NOT NULL AND expires_at <= datetime('now')) AS expired, \
+     access, access_code, group_id, record, created_at, updated_at \
+     FROM live_streams";
+
+#[derive(sqlx::FromRow)]
+struct AudioDetailsSqlRow {
+    media_id: i64,
+    duration: Option<f64>,
+    bitrate: Option<i64>,
+    channels: Option<i64>,
+    sample_rate: Option<i64>,
+    codec: Option<String>,
+}
+
+impl From<AudioDetailsSqlRow> for AudioDetails {
+    fn from(r: AudioDetailsSqlRow) -> Self {
+        Self {
+            media_id: r.media_id,
+            duration: r.duration,
+            bitrate: r.bitrate,
+            channels: r.channels,
+            sample_rate: r.sample_rate,
+            codec: r.codec,
+        }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct ImageServingSqlRow {
+    id: i32,
+    filename: String,
+    original_filename: Option<String>,
+    user_id: Option<String>,
+    vault_id: Option<String>,
+    is_public: i32,
+    mime_type: String,
+    file_hash: Option<String>,
+}
+
+impl From<ImageServingSqlRow> for ImageServingInfo {
+    fn from(r: ImageServingSqlRow) -> Self {
+        Self {
+            id: r.id,
+            filename: r.filename,
+            original_filename: r.original_filename,
+            user_id: r.user_id,
+            vault_id: r.vault_id,
+            is_public: r.is_public,
+            mime_type: r.mime_type,
+            file_hash: r.file_hash,
+        }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct PhotoDetailsSqlRow {
+    media_id: i64,
+    captured_at: Option<String>,
+    camera_make: Option<String>,
+    camera_model: Option<String>,
+    lens_model: Option<String>,
+    gps_latitude: Option<f64>,
+    gps_longitude: Option<f64>,
+}
+
+impl From<PhotoDetailsSqlRow> for PhotoDetails {
+    fn from(r: PhotoDetailsSqlRow) -> Self {
+        Self {
+            media_id: r.media_id,
+            captured_at: r.captured_at,
+            camera_make: r.camera_make,
+            camera_model: r.camera_model,
+            lens_model: r.lens_model,
+            gps_latitude: r.gps_latitude,
+            gps_longitude: r.gps_longitude,
+        }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct GeotaggedImageSqlRow {
+    slug: String,
+    title: String,
+    gps_latitude: f64,
+    gps_longitude: f64,
+    captured_at: Option<String>,
+    vault_id: Option<String>,
+}
+
+impl From<GeotaggedImageSqlRow> for GeotaggedImage {
+    fn from(r: GeotaggedImageSqlRow) -> Self {
+        Self {
+            slug: r.slug,
+            title: r.title,
+            latitude: r.gps_latitude,
+            longitude: r.gps_longitude,
+            captured_at: r.captured_at,
+            vault_id: r.vault_id,
+        }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct TrashedMediaSqlRow {
+    id: i32,
+    slug: String,
+    title: String,
+    media_type: String,
+    filename: String,
+    file_size: i64,
+    vault_id: Option<String>,
+    deleted_at: String,
+}
+
+impl From<TrashedMediaSqlRow> for TrashedMedia {
+    fn from(r: TrashedMediaSqlRow) -> Self {
+        Self {
+            id: r.id,
+            slug: r.slug,
+            title: r.title,
+            media_type: r.media_type,
+            filename: r.filename,
+            file_size: r.file_size,
+            vault_id: r.vault_id,
+            deleted_at: r.deleted_at,
+        }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct MediaVersionSqlRow {
+    media_id: i32,
+    version: i64,
+    filename: String,
+    original_filename: Option<String>,
+    mime_type: String,
+    file_size: i64,
+    video_type: Option<String>,
+    thumbnail_url: Option<String>,
+    content_hash: Option<String>,
+    file_hash: Option<String>,
+    current: bool,
+    archived_by: Option<String>,
+    comment: Option<String>,
+    archived_at: Option<String>,
+}
+
+impl From<MediaVersionSqlRow> for MediaVersion {
+    fn from(r: MediaVersionSqlRow) -> Self {
+        Self {
+            media_id: r.media_id,
+            version: r.version,
+            filename: r.filename,
+            original_filename: r.original_filename,
+            mime_type: r.mime_type,
+            file_size: r.file_size,
+            video_type: r.video_type,
+            thumbnail_url: r.thumbnail_url,
+            content_hash: r.content_hash,
+            file_hash: r.file_hash,
+            current: r.current,
+            archived_by: r.archived_by,
+            comment: r.comment,
+            archived_at: r.archived_at,
+        }
+    }
+}
+
+/// File columns shared by `media_items` and `media_versions`
+const MEDIA_FILE_COLUMNS: &str = "filename, original_filename, mime_type, file_size, \
+     video_type, thumbnail_url, content_hash, file_hash";
+
+#[derive(sqlx::FromRow)]
+struct BatchTargetSqlRow {
+    id: i32,
+    media_type: String,
+    filename: String,
+    vault_id: Option<String>,
+    is_public: i32,
+    group_id: Option<i32>,
+}
+
+#[derive(sqlx::FromRow)]
+struct SearchHitSqlRow {
+    #[sqlx(flatten)]
+    item: FullMediaRow,
+    snippet: Option<String>,
+    score: Option<f64>,
+}
+
+#[derive(sqlx::FromRow)]
+struct IntegrityIssueSqlRow {
+    id: i32,
+    slug: String,
+    title: String,
+    media_type: String,
+    vault_id: Option<String>,
+    file_hash: Option<String>,
+    integrity_status: String,
+    integrity_checked_at: Option<String>,
+}
+
+impl From<IntegrityIssueSqlRow> for IntegrityIssue {
+    fn from(r: IntegrityIssueSqlRow) -> Self {
+        Self {
+            id: r.id,
+            slug: r.slug,
+            title: r.title,
+            media_type: r.media_type,
+            vault_id: r.vault_id,
+            file_hash: r.file_hash,
+            status: r.integrity_status,
+            checked_at: r.integrity_checked_at,
+        }
+    }
+}
+
+impl From<SearchHitSqlRow> for MediaSearchHit {
+    fn from(r: SearchHitSqlRow) -> Self {
+        Self {
+            item: r.item.into(),
+            snippet: r.snippet,
+            score: r.score,
+        }
+    }
+}
+
+// ── Helper: full-text query ────────────────────────────────────────
+
+/// Column weights for `bm25()`: title, description, category, tags, body
+const FTS_WEIGHTS: &str = "10.0, 4.0, 2.0, 6.0, 1.0";
+
+/// Tokens around a match in search snippets
+const SNIPPET_TOKENS: i32 = 16;
+
+/// Turn free-text user input into an FTS5 MATCH expression.
+///
+/// Every word becomes a quoted prefix query and all words must match, so
+/// FTS5 operators and syntax characters in the input are taken literally.
+/// Returns `None` when the input contains nothing searchable.
+fn fts_match_query(search: &str) -> Option<String> {
+    let terms: Vec<String> = search
+        .split_whitespace()
+        .filter(|word| word.chars().any(char::is_alphanumeric))
+        .map(|word| format!("\"{}\"*", word.replace('"', "\"\"")))
+        .collect();
+
+    if terms.is_empty() {
+        None
+    } else {
+        Some(terms.join(" "))
+    }
+}
+
 // ── Helper: build WHERE clause from filter ─────────────────────────
 
 fn build_filter_clause(
@@ -155,6 +599,9 @@ fn build_filter_clause(
 ) -> String {
     let mut query = String::from(base);
 
+    // Trashed items only show up in the trash
+    query.push_str(" AND deleted_at IS NULL");
+
     if let Some(tenant_id) = &filter.tenant_id {
         query.push_str(" AND tenant_id = ?");
         bindings.push(tenant_id.clone());
@@ -165,16 +612,9 @@ fn build_filter_clause(
         bindings.push(media_type.clone());
     }
 
-    if let Some(search) = &filter.search {
-        query.push_str(
-            " AND (title LIKE ? OR description LIKE ? OR category LIKE ? \
-             OR id IN (SELECT media_id FROM media_tags WHERE tag LIKE ?))",
-        );
-        let pattern = format!("%{}%", search);
-        bindings.push(pattern.clone());
-        bindings.push(pattern.clone());
-        bindings.push(pattern.clone());
-        bindings.push(pattern);
+    if let Some(fts_query) = filter.search.as_deref().and_then(fts_match_query) {
+        query.push_str(" AND id IN (SELECT rowid FROM media_fts WHERE media_fts MATCH ?)");
+        bindings.push(fts_query);
     }
 
     if let Some(is_public) = filter.is_public {
@@ -202,6 +642,31 @@ fn build_filter_clause(
         bindings.push(group_id.clone());
     }
 
+    if let Some(camera) = &filter.camera {
+        query.push_str(
+            " AND id IN (SELECT media_id FROM media_photo \
+             WHERE camera_make LIKE ? OR camera_model LIKE ?)",
+        );
+        let pattern = format!("%{}%", camera);
+        bindings.push(pattern.clone());
+        bindings.push(pattern);
+    }
+
+    if let Some(after) = &filter.captured_after {
+        query.push_str(" AND id IN (SELECT media_id FROM media_photo WHERE captured_at >= ?)");
+        bindings.push(after.clone());
+    }
+
+    if let Some(before) = &filter.captured_before {
+        query.push_str(" AND id IN (SELECT media_id FROM media_photo WHERE captured_at <= ?)");
+        // A bare date includes the whole day
+        if before.len() == "YYYY-MM-DD".len() {
+            bindings.push(format!("{} 23:59:59", before));
+        } else {
+            bindings.push(before.clone());
+        }
+    }
+
     query
 }
 
@@ -214,13 +679,8 @@ impl MediaRepository for SqliteDatabase {
     async fn search_media(
         &self,
         filter: &MediaSearchFilter,
-    ) -> Result<Vec<MediaItemRow>, DbError> {
-        let mut bindings: Vec<String> = Vec::new();
-        let mut query = build_filter_clause(
-            "SELECT * FROM media_items WHERE 1=1",
-            filter,
-            &mut bindings,
-        );
+    ) -> Result<Vec<MediaSearchHit>, DbError> {
+        let fts_query = filter.search.as_deref().and_then(fts_match_query);
 
         let sort_field = if filter.sort_by.is_empty() {
             "created_at"
@@ -241,14 +701,60 @@ impl MediaRepository for SqliteDatabase {
             "asc" | "ASC" => "ASC",
             _ => "DESC",
         };
-        query.push_str(&format!(" ORDER BY {} {}", safe_sort, safe_order));
 
-        let mut sqlx_query = sqlx::query_as::<_, FullMediaRow>(&query);
-        for b in &bindings {
-            sqlx_query = sqlx_query.bind(b);
+        let mut bindings: Vec<String> = Vec::new();
+        let mut query = match &fts_query {
+            Some(fts_query) => {
+                // Join the index for ranking and snippets; the remaining
+                // filters are the same as for a plain listing
+                bindings.push(SNIPPET_MATCH_START.to_string());
+                bindings.push(SNIPPET_MATCH_END.to_string());
+                bindings.push(fts_query.clone());
+                let base = format!(
+                    "SELECT media_items.*, \
+                     snippet(media_fts, -1, ?, ?, '…', {}) AS snippet, \
+                     bm25(media_fts, {}) AS score \
+                     FROM media_fts JOIN media_items ON media_items.id = media_fts.rowid \
+                     WHERE media_fts MATCH ?",
+                    SNIPPET_TOKENS, FTS_WEIGHTS
+                );
+                let filter = MediaSearchFilter {
+                    search: None,
+                    ..filter.clone()
+                };
+                build_filter_clause(&base, &filter, &mut bindings)
+            }
+            None => build_filter_clause(
+                "SELECT media_items.*, NULL AS snippet, NULL AS score \
+                 FROM media_items WHERE 1=1",
+                filter,
+                &mut bindings,
+            ),
+        };
+
+        if sort_field == "relevance" && fts_query.is_some() {
+            query.push_str(" ORDER BY score ASC, media_items.created_at DESC");
+        } else {
+            query.push_str(&format!(
+                " ORDER BY media_items.{} {}",
+                safe_sort, safe_order
+            ));
         }
 
-        let rows = sqlx_query.fetch_all(self.pool()).await.map_err(map_err)?;
+        if let Some(limit) = filter.limit {
+            query.push_str(&format!(
+                " LIMIT {} OFFSET {}",
+                limit.max(0),
+                filter.offset.unwrap_or(0).max(0)
+            ));
+        }
+
+        let mut sqlx_query = sqlx::query_as::<_, SearchHitSqlRow>(&query);
+        for b in &bindings {
+            sqlx_query = sqlx_query.bind(b);
+        }
+
+        let rows = sqlx_query.fetch_all(self.pool()).await.map_err(map_err)?;
         Ok(rows.into_iter().map(Into::into).collect())
     }
 
@@ -275,7 +781,7 @@ impl MediaRepository for SqliteDatabase {
     // ── CRUD ──────────────────────────────────────────────────────
 
     async fn get_media_by_slug(&self, slug: &str) -> Result<Option<MediaItemRow>, DbError> {
-        let row = sqlx::query_as::<_, FullMediaRow>("SELECT * FROM media_items WHERE slug = ?")
+        let row = sqlx::query_as::<_, FullMediaRow>("SELECT * FROM media_items WHERE slug = ? AND deleted_at IS NULL")
             .bind(slug)
             .fetch_optional(self.pool())
             .await
@@ -290,7 +796,7 @@ impl MediaRepository for SqliteDatabase {
                 is_public, featured, status, category, thumbnail_url,
                 view_count, download_count, like_count, share_count, created_at
             FROM media_items
-            WHERE slug = ?"#,
+            WHERE slug = ? AND deleted_at IS NULL"#,
         )
         .bind(slug)
         .fetch_optional(self.pool())
@@ -315,8 +821,9 @@ impl MediaRepository for SqliteDatabase {
             r#"INSERT INTO media_items
             (slug, media_type, video_type, title, description, filename, original_filename,
              mime_type, file_size, is_public, user_id, group_id, vault_id, status, featured,
-             category, thumbnail_url, allow_download, allow_comments, mature_content, tenant_id)
-            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"#,
+             category, thumbnail_url, allow_download, allow_comments, mature_content, tenant_id,
+             content_hash, file_hash)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"#,
         )
         .bind(&item.slug)
         .bind(&item.media_type)
@@ -339,6 +846,8 @@ impl MediaRepository for SqliteDatabase {
         .bind(item.allow_comments)
         .bind(item.mature_content)
         .bind(&item.tenant_id)
+        .bind(&item.content_hash)
+        .bind(&item.file_hash)
         .execute(self.pool())
         .await
         .map_err(map_err)?;
@@ -383,7 +892,7 @@ impl MediaRepository for SqliteDatabase {
         user_id: &str,
     ) -> Result<Option<i32>, DbError> {
         let row: Option<i32> =
-            sqlx::query_scalar("SELECT id FROM media_items WHERE slug = ? AND user_id = ?")
+            sqlx::query_scalar("SELECT id FROM media_items WHERE slug = ? AND user_id = ? AND deleted_at IS NULL")
                 .bind(slug)
                 .bind(user_id)
                 .fetch_optional(self.pool())
@@ -398,7 +907,7 @@ impl MediaRepository for SqliteDatabase {
         user_id: &str,
     ) -> Result<Option<MediaDeletionInfo>, DbError> {
         let row: Option<(String, String, Option<String>)> = sqlx::query_as(
-            "SELECT media_type, filename, vault_id FROM media_items WHERE slug = ? AND user_id = ?",
+            "SELECT media_type, filename, vault_id FROM media_items WHERE slug = ? AND user_id = ? AND deleted_at IS NULL",
         )
         .bind(slug)
         .bind(user_id)
@@ -451,6 +960,18 @@ impl MediaRepository for SqliteDatabase {
         Ok(())
     }
 
+    // ── Downloads ─────────────────────────────────────────────────
+
+    async fn media_allows_download(&self, id: i32) -> Result<bool, DbError> {
+        let row: Option<(i32,)> =
+            sqlx::query_as("SELECT allow_download FROM media_items WHERE id = ?")
+                .bind(id)
+                .fetch_optional(self.pool())
+                .await
+                .map_err(map_err)?;
+        Ok(row.is_some_and(|(allow,)| allow != 0))
+    }
+
     // ── Status ────────────────────────────────────────────────────
 
     async fn get_media_status(&self, slug: &str) -> Result<Option<MediaStatusRow>, DbError> {
@@ -676,24 +1197,17 @@ impl MediaRepository for SqliteDatabase {
         &self,
         slug: &str,
     ) -> Result<Option<ImageServingInfo>, DbError> {
-        let row: Option<(i32, String, Option<String>, Option<String>, i32, String)> =
-            sqlx::query_as(
-                "SELECT id, filename, user_id, vault_id, is_public, mime_type \
-                 FROM media_items WHERE slug = ? AND media_type = 'image'",
-            )
-            .bind(slug)
-            .fetch_optional(self.pool())
-            .await
-            .map_err(map_err)?;
+        let row: Option<ImageServingSqlRow> = sqlx::query_as(
+            "SELECT id, filename, original_filename, user_id, vault_id, is_public, mime_type, \
+                    file_hash \
+             FROM media_items WHERE slug = ? AND media_type = 'image' AND deleted_at IS NULL",
+        )
+        .bind(slug)
+        .fetch_optional(self.pool())
+        .await
+        .map_err(map_err)?;
 
-        Ok(row.map(|r| ImageServingInfo {
-            id: r.0,
-            filename: r.1,
-            user_id: r.2,
-            vault_id: r.3,
-            is_public: r.4,
-            mime_type: r.5,
-        }))
+        Ok(row.map(Into::into))
     }
 
     async fn get_thumbnail_for_serving(
@@ -703,7 +1217,7 @@ impl MediaRepository for SqliteDatabase {
         let row: Option<(i32, Option<String>, Option<String>, i32, String, String)> =
             sqlx::query_as(
                 "SELECT id, user_id, vault_id, is_public, media_type, COALESCE(filename, '') \
-                 FROM media_items WHERE slug = ?",
+                 FROM media_items WHERE slug = ? AND deleted_at IS NULL",
             )
             .bind(slug)
             .fetch_optional(self.pool())
@@ -727,7 +1241,7 @@ impl MediaRepository for SqliteDatabase {
         let row: Option<(i32, Option<String>, Option<String>, Option<String>, i32)> =
             sqlx::query_as(
                 "SELECT id, user_id, vault_id, video_type, is_public \
-                 FROM media_items WHERE slug = ? AND media_type = 'video'",
+                 FROM media_items WHERE slug = ? AND media_type = 'video' AND deleted_at IS NULL",
             )
             .bind(slug)
             .fetch_optional(self.pool())
@@ -743,13 +1257,37 @@ impl MediaRepository for SqliteDatabase {
         }))
     }
 
+    async fn get_audio_for_serving(
+        &self,
+        slug: &str,
+    ) -> Result<Option<AudioServingInfo>, DbError> {
+        let row: Option<(i32, Option<String>, Option<String>, String, String, i32)> =
+            sqlx::query_as(
+                "SELECT id, user_id, vault_id, filename, mime_type, is_public \
+                 FROM media_items WHERE slug = ? AND media_type = 'audio' AND deleted_at IS NULL",
+            )
+            .bind(slug)
+            .fetch_optional(self.pool())
+            .await
+            .map_err(map_err)?;
+
+        Ok(row.map(|r| AudioServingInfo {
+            id: r.0,
+            user_id: r.1,
+            vault_id: r.2,
+            filename: r.3,
+            mime_type: r.4,
+            is_public: r.5,
+        }))
+    }
+
     async fn get_document_for_serving(
         &self,
         slug: &str,
     ) -> Result<Option<DocumentServingInfo>, DbError> {
         let row: Option<(i32, String, Option<String>)> = sqlx::query_as(
             "SELECT id, filename, vault_id FROM media_items \
-             WHERE slug = ? AND media_type = 'document'",
+             WHERE slug = ? AND media_type = 'document' AND deleted_at IS NULL",
         )
         .bind(slug)
         .fetch_optional(self.pool())
@@ -770,7 +1308,7 @@ impl MediaRepository for SqliteDatabase {
         let row = sqlx::query_as::<_, DocumentViewSqlRow>(
             r#"SELECT id, slug, title, filename, mime_type, user_id, vault_id, created_at, is_public
                FROM media_items
-               WHERE slug = ? AND media_type = 'document'"#,
+               WHERE slug = ? AND media_type = 'document' AND deleted_at IS NULL"#,
         )
         .bind(slug)
         .fetch_optional(self.pool())
@@ -853,7 +1391,7 @@ impl MediaRepository for SqliteDatabase {
             sqlx::query_as(
                 "SELECT slug, title, media_type, mime_type, file_size, thumbnail_url, created_at
                  FROM media_items
-                 WHERE vault_id = ? AND group_id = ? AND status = 'active'
+                 WHERE vault_id = ? AND group_id = ? AND status = 'active' AND deleted_at IS NULL
                  ORDER BY created_at DESC",
             )
             .bind(vault_id)
@@ -864,7 +1402,7 @@ impl MediaRepository for SqliteDatabase {
             sqlx::query_as(
                 "SELECT slug, title, media_type, mime_type, file_size, thumbnail_url, created_at
                  FROM media_items
-                 WHERE vault_id = ? AND status = 'active'
+                 WHERE vault_id = ? AND status = 'active' AND deleted_at IS NULL
                  ORDER BY created_at DESC",
             )
             .bind(vault_id)
@@ -897,7 +1435,10 @@ impl MediaRepository for SqliteDatabase {
         let exists: Option<i32> = sqlx::query_scalar(
             "SELECT 1 FROM access_codes
              WHERE code = ? AND vault_id = ? AND is_active = 1
-               AND (expires_at IS NULL OR expires_at > datetime('now'))",
+               AND (expires_at IS NULL OR expires_at > datetime('now'))
+               AND passphrase_hash IS NULL AND max_uses IS NULL AND max_views IS NULL
+               AND allowed_ips IS NULL AND allowed_referrers IS NULL
+               AND burn_after_claim = 0",
         )
         .bind(code)
         .bind(vault_id)
@@ -1069,7 +1610,7 @@ impl MediaRepository for SqliteDatabase {
     ) -> Result<Option<VideoPlayerInfo>, DbError> {
         let row: Option<(i32, String, i32)> = sqlx::query_as(
             "SELECT id, title, is_public FROM media_items \
-             WHERE media_type = 'video' AND slug = ?",
+             WHERE media_type = 'video' AND slug = ? AND deleted_at IS NULL",
         )
         .bind(slug)
         .fetch_optional(self.pool())
@@ -1086,7 +1627,7 @@ impl MediaRepository for SqliteDatabase {
     async fn get_video_for_hls(&self, slug: &str) -> Result<Option<VideoHlsInfo>, DbError> {
         let row: Option<(i32, Option<String>, Option<String>, i32)> = sqlx::query_as(
             "SELECT id, user_id, vault_id, is_public FROM media_items \
-             WHERE media_type = 'video' AND slug = ?",
+             WHERE media_type = 'video' AND slug = ? AND deleted_at IS NULL",
         )
         .bind(slug)
         .fetch_optional(self.pool())
@@ -1114,7 +1655,7 @@ impl MediaRepository for SqliteDatabase {
                 GROUP_CONCAT(mt.tag) as tags
              FROM media_items v
              LEFT JOIN media_tags mt ON v.id = mt.media_id
-             WHERE v.media_type = 'video' AND v.user_id = ? AND v.tenant_id = ?
+             WHERE v.media_type = 'video' AND v.user_id = ? AND v.tenant_id = ? AND v.deleted_at IS NULL
              GROUP BY v.id
              ORDER BY v.created_at DESC"#,
         )
@@ -1148,7 +1689,7 @@ impl MediaRepository for SqliteDatabase {
         let rows: Vec<(String, String, i32)> = if let Some(uid) = user_id {
             sqlx::query_as(
                 "SELECT slug, title, is_public FROM media_items \
-                 WHERE media_type = 'video' AND tenant_id = ? AND (is_public = 1 OR user_id = ?) \
+                 WHERE media_type = 'video' AND tenant_id = ? AND deleted_at IS NULL AND (is_public = 1 OR user_id = ?) \
                  ORDER BY is_public DESC, title",
             )
             .bind(tenant_id)
@@ -1158,7 +1699,7 @@ impl MediaRepository for SqliteDatabase {
         } else {
             sqlx::query_as(
                 "SELECT slug, title, is_public FROM media_items \
-                 WHERE media_type = 'video' AND tenant_id = ? AND is_public = 1 ORDER BY title",
+                 WHERE media_type = 'video' AND tenant_id = ? AND deleted_at IS NULL AND is_public = 1 ORDER BY title",
             )
             .bind(tenant_id)
             .fetch_all(self.pool())
@@ -1178,7 +1719,7 @@ impl MediaRepository for SqliteDatabase {
 
     async fn get_all_video_slugs(&self, tenant_id: &str) -> Result<Vec<String>, DbError> {
         let rows: Vec<(String,)> = sqlx::query_as(
-            "SELECT slug FROM media_items WHERE media_type = 'video' AND tenant_id = ?",
+            "SELECT slug FROM media_items WHERE media_type = 'video' AND tenant_id = ? AND deleted_at IS NULL",
         )
         .bind(tenant_id)
         .fetch_all(self.pool())
@@ -1258,95 +1799,971 @@ impl MediaRepository for SqliteDatabase {
         Ok(row.is_some())
     }
 
-    // ── Cross-domain helpers ──────────────────────────────────────
+    // ── Cross-domain helpers ──────────────────────────────────────
+
+    async fn get_media_title(
+        &self,
+        slug: &str,
+        media_type: &str,
+    ) -> Result<Option<String>, DbError> {
+        sqlx::query_scalar(
+            "SELECT title FROM media_items WHERE slug = ? AND media_type = ?",
+        )
+        .bind(slug)
+        .bind(media_type)
+        .fetch_optional(self.pool())
+        .await
+        .map_err(map_err)
+    }
+
+    async fn get_media_id_by_type(
+        &self,
+        media_type: &str,
+        slug: &str,
+    ) -> Result<Option<i64>, DbError> {
+        sqlx::query_scalar(
+            "SELECT id FROM media_items WHERE media_type = ? AND slug = ?",
+        )
+        .bind(media_type)
+        .bind(slug)
+        .fetch_optional(self.pool())
+        .await
+        .map_err(map_err)
+    }
+
+    async fn get_media_enrichment(
+        &self,
+        slug: &str,
+    ) -> Result<Option<db::media::MediaEnrichment>, DbError> {
+        let row: Option<(String, Option<String>, String)> = sqlx::query_as(
+            "SELECT COALESCE(filename, ?), thumbnail_url, COALESCE(title, ?) \
+             FROM media_items WHERE slug = ?",
+        )
+        .bind(slug)
+        .bind(slug)
+        .bind(slug)
+        .fetch_optional(self.pool())
+        .await
+        .map_err(map_err)?;
+        Ok(row.map(|(filename, thumbnail_url, title)| db::media::MediaEnrichment {
+            filename,
+            thumbnail_url,
+            title,
+        }))
+    }
+
+    async fn assign_media_group(&self, slug: &str, group_id: i32) -> Result<(), DbError> {
+        sqlx::query(
+            "UPDATE media_items SET group_id = ?, updated_at = datetime('now') WHERE slug = ?",
+        )
+        .bind(group_id)
+        .bind(slug)
+        .execute(self.pool())
+        .await
+        .map_err(map_err)?;
+        Ok(())
+    }
+
+    async fn unassign_media_group(&self, slug: &str, group_id: i32) -> Result<(), DbError> {
+        sqlx::query(
+            "UPDATE media_items SET group_id = NULL, updated_at = datetime('now') \
+             WHERE slug = ? AND group_id = ?",
+        )
+        .bind(slug)
+        .bind(group_id)
+        .execute(self.pool())
+        .await
+        .map_err(map_err)?;
+        Ok(())
+    }
+
+    async fn assign_media_group_for_user(
+        &self,
+        slug: &str,
+        user_id: &str,
+        group_id: i32,
+    ) -> Result<bool, DbError> {
+        let result = sqlx::query(
+            "UPDATE media_items SET group_id = ?, updated_at = datetime('now') \
+             WHERE slug = ? AND user_id = ?",
+        )
+        .bind(group_id)
+        .bind(slug)
+        .bind(user_id)
+        .execute(self.pool())
+        .await
+        .map_err(map_err)?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn check_media_in_group(
+        &self,
+        slug: &str,
+        user_id: &str,
+        group_id: i32,
+    ) -> Result<Option<i64>, DbError> {
+        sqlx::query_scalar(
+            "SELECT id FROM media_items WHERE slug = ? AND user_id = ? AND group_id = ?",
+        )
+        .bind(slug)
+        .bind(user_id)
+        .bind(group_id)
+        .fetch_optional(self.pool())
+        .await
+        .map_err(map_err)
+    }
+
+    async fn list_group_media(
+        &self,
+        group_id: i32,
+    ) -> Result<Vec<db::media::GroupMediaRow>, DbError> {
+        let rows: Vec<(String, String, String, String, Option<String>)> = sqlx::query_as(
+            "SELECT slug, title, media_type, filename, thumbnail_url \
+             FROM media_items WHERE group_id = ? AND deleted_at IS NULL ORDER BY media_type, created_at DESC",
+        )
+        .bind(group_id)
+        .fetch_all(self.pool())
+        .await
+        .map_err(map_err)?;
+        Ok(rows
+            .into_iter()
+            .map(|(slug, title, media_type, filename, thumbnail_url)| {
+                db::media::GroupMediaRow { slug, title, media_type, filename, thumbnail_url }
+            })
+            .collect())
+    }
+
+    async fn count_public_active(&self, tenant_id: &str) -> Result<i64, DbError> {
+        let count: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM media_items WHERE is_public = 1 AND status = 'active' AND deleted_at IS NULL AND tenant_id = ?",
+        )
+        .bind(tenant_id)
+        .fetch_one(self.pool())
+        .await
+        .map_err(map_err)?;
+        Ok(count)
+    }
+
+    async fn list_public_catalog(
+        &self,
+        tenant_id: &str,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<db::media::PublicCatalogRow>, DbError> {
+        let rows: Vec<PublicCatalogSqlRow> = sqlx::query_as(&format!(
+            "SELECT {} FROM media_items \
+             WHERE is_public = 1 AND status = 'active' AND deleted_at IS NULL AND tenant_id = ? \
+             ORDER BY created_at DESC LIMIT ? OFFSET ?",
+            PUBLIC_CATALOG_COLUMNS
+        ))
+        .bind(tenant_id)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(self.pool())
+        .await
+        .map_err(map_err)?;
+        Ok(rows.into_iter().map(Into::into).collect())
+    }
+
+    async fn get_public_metadata(
+        &self,
+        slug: &str,
+    ) -> Result<Option<db::media::PublicCatalogRow>, DbError> {
+        let row: Option<PublicCatalogSqlRow> = sqlx::query_as(&format!(
+            "SELECT {} FROM media_items WHERE slug = ? AND is_public = 1 AND status = 'active' AND deleted_at IS NULL",
+            PUBLIC_CATALOG_COLUMNS
+        ))
+        .bind(slug)
+        .fetch_optional(self.pool())
+        .await
+        .map_err(map_err)?;
+        Ok(row.map(Into::into))
+    }
+
+    async fn get_public_media_for_thumbnail(
+        &self,
+        slug: &str,
+    ) -> Result<Option<(String, Option<String>)>, DbError> {
+        sqlx::query_as(
+            "SELECT media_type, vault_id FROM media_items \
+             WHERE slug = ? AND is_public = 1 AND status = 'active' AND deleted_at IS NULL",
+        )
+        .bind(slug)
+        .fetch_optional(self.pool())
+        .await
+        .map_err(map_err)
+    }
+
+    async fn get_public_media_for_content(
+        &self,
+        slug: &str,
+    ) -> Result<Option<(String, String, Option<String>)>, DbError> {
+        sqlx::query_as(
+            "SELECT media_type, filename, vault_id FROM media_items \
+             WHERE slug = ? AND is_public = 1 AND status = 'active' AND deleted_at IS NULL",
+        )
+        .bind(slug)
+        .fetch_optional(self.pool())
+        .await
+        .map_err(map_err)
+    }
+
+    async fn get_vault_media_for_user(
+        &self,
+        vault_id: &str,
+        user_id: &str,
+    ) -> Result<Vec<db::media::FolderMediaRow>, DbError> {
+        type Row = (
+            Option<String>,
+            Option<String>,
+            Option<String>,
+            Option<String>,
+            Option<i64>,
+            Option<String>,
+            Option<String>,
+        );
+        let rows: Vec<Row> = sqlx::query_as(
+            "SELECT slug, title, media_type, mime_type, file_size, thumbnail_url, created_at \
+             FROM media_items WHERE vault_id = ? AND user_id = ? AND status = 'active' AND deleted_at IS NULL \
+             ORDER BY created_at DESC",
+        )
+        .bind(vault_id)
+        .bind(user_id)
+        .fetch_all(self.pool())
+        .await
+        .map_err(map_err)?;
+        Ok(rows
+            .into_iter()
+            .map(|(slug, title, media_type, mime_type, file_size, thumbnail_url, created_at)| {
+                db::media::FolderMediaRow {
+                    slug, title, media_type, mime_type, file_size, thumbnail_url, created_at,
+                }
+            })
+            .collect())
+    }
+
+    // ── Subtitles ─────────────────────────────────────────────────
+
+    async fn list_subtitles(&self, media_id: i64) -> Result<Vec<SubtitleTrack>, DbError> {
+        let rows: Vec<SubtitleSqlRow> = sqlx::query_as(
+            "SELECT id, media_id, language, label, is_default, auto_generated, created_at \
+             FROM media_subtitles WHERE media_id = ? \
+             ORDER BY is_default DESC, label",
+        )
+        .bind(media_id)
+        .fetch_all(self.pool())
+        .await
+        .map_err(map_err)?;
+        Ok(rows.into_iter().map(Into::into).collect())
+    }
+
+    async fn upsert_subtitle(
+        &self,
+        media_id: i64,
+        language: &str,
+        label: &str,
+        is_default: bool,
+        auto_generated: bool,
+    ) -> Result<SubtitleTrack, DbError> {
+        let mut tx = self.pool().begin().await.map_err(map_err)?;
+
+        if is_default {
+            sqlx::query("UPDATE media_subtitles SET is_default = 0 WHERE media_id = ?")
+                .bind(media_id)
+                .execute(&mut *tx)
+                .await
+                .map_err(map_err)?;
+        }
+
+        let row: SubtitleSqlRow = sqlx::query_as(
+            "INSERT INTO media_subtitles (media_id, language, label, is_default, auto_generated) \
+             VALUES (?, ?, ?, ?, ?) \
+             ON CONFLICT (media_id, language) DO UPDATE SET \
+             label = excluded.label, is_default = excluded.is_default, \
+             auto_generated = excluded.auto_generated, updated_at = datetime('now') \
+             RETURNING id, media_id, language, label, is_default, auto_generated, created_at",
+        )
+        .bind(media_id)
+        .bind(language)
+        .bind(label)
+        .bind(is_default as i32)
+        .bind(auto_generated as i32)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(map_err)?;
+
+        tx.commit().await.map_err(map_err)?;
+        Ok(row.into())
+    }
+
+    async fn delete_subtitle(&self, media_id: i64, language: &str) -> Result<bool, DbError> {
+        let result =
+            sqlx::query("DELETE FROM media_subtitles WHERE media_id = ? AND language = ?")
+                .bind(media_id)
+                .bind(language)
+                .execute(self.pool())
+                .await
+                .map_err(map_err)?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    // ── Chapters ──────────────────────────────────────────────────
+
+    async fn list_chapters(&self, media_id: i64) -> Result<Vec<VideoChapter>, DbError> {
+        let rows: Vec<ChapterSqlRow> = sqlx::query_as(
+            "SELECT id, media_id, start_time, end_time, title \
+             FROM media_chapters WHERE media_id = ? ORDER BY start_time",
+        )
+        .bind(media_id)
+        .fetch_all(self.pool())
+        .await
+        .map_err(map_err)?;
+        Ok(rows.into_iter().map(Into::into).collect())
+    }
+
+    async fn replace_chapters(
+        &self,
+        media_id: i64,
+        chapters: &[NewChapter],
+    ) -> Result<Vec<VideoChapter>, DbError> {
+        let mut tx = self.pool().begin().await.map_err(map_err)?;
+
+        sqlx::query("DELETE FROM media_chapters WHERE media_id = ?")
+            .bind(media_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(map_err)?;
+
+        let mut saved = Vec::with_capacity(chapters.len());
+        for chapter in chapters {
+            let row: ChapterSqlRow = sqlx::query_as(
+                "INSERT INTO media_chapters (media_id, start_time, end_time, title) \
+                 VALUES (?, ?, ?, ?) \
+                 RETURNING id, media_id, start_time, end_time, title",
+            )
+            .bind(media_id)
+            .bind(chapter.start_time)
+            .bind(chapter.end_time)
+            .bind(&chapter.title)
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(map_err)?;
+            saved.push(row.into());
+        }
+
+        tx.commit().await.map_err(map_err)?;
+        saved.sort_by(|a: &VideoChapter, b| a.start_time.total_cmp(&b.start_time));
+        Ok(saved)
+    }
+
+    // ── Transcripts ───────────────────────────────────────────────
+
+    async fn get_transcript(&self, media_id: i64) -> Result<Option<MediaTranscript>, DbError> {
+        let row: Option<TranscriptSqlRow> = sqlx::query_as(
+            "SELECT media_id, status, language, backend, error, updated_at \
+             FROM media_transcripts WHERE media_id = ?",
+        )
+        .bind(media_id)
+        .fetch_optional(self.pool())
+        .await
+        .map_err(map_err)?;
+        Ok(row.map(Into::into))
+    }
+
+    async fn list_transcript_segments(
+        &self,
+        media_id: i64,
+    ) -> Result<Vec<TranscriptSegment>, DbError> {
+        let rows: Vec<(f64, f64, String)> = sqlx::query_as(
+            "SELECT start_time, end_time, text FROM media_transcript_segments \
+             WHERE media_id = ? ORDER BY start_time, id",
+        )
+        .bind(media_id)
+        .fetch_all(self.pool())
+        .await
+        .map_err(map_err)?;
+        Ok(rows
+            .into_iter()
+            .map(|(start_time, end_time, text)| TranscriptSegment {
+                start_time,
+                end_time,
+                text,
+            })
+            .collect())
+    }
+
+    async fn set_transcript_status(
+        &self,
+        media_id: i64,
+        backend: &str,
+        status: &str,
+        error: Option<&str>,
+    ) -> Result<(), DbError> {
+        sqlx::query(
+            "INSERT INTO media_transcripts (media_id, status, backend, error) \
+             VALUES (?, ?, ?, ?) \
+             ON CONFLICT (media_id) DO UPDATE SET \
+             status = excluded.status, backend = excluded.backend, \
+             error = excluded.error, updated_at = datetime('now')",
+        )
+        .bind(media_id)
+        .bind(status)
+        .bind(backend)
+        .bind(error)
+        .execute(self.pool())
+        .await
+        .map_err(map_err)?;
+        Ok(())
+    }
+
+    async fn save_transcript(
+        &self,
+        media_id: i64,
+        backend: &str,
+        language: Option<&str>,
+        segments: &[TranscriptSegment],
+    ) -> Result<(), DbError> {
+        let mut tx = self.pool().begin().await.map_err(map_err)?;
+
+        sqlx::query("DELETE FROM media_transcript_segments WHERE media_id = ?")
+            .bind(media_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(map_err)?;
+
+        for segment in segments {
+            sqlx::query(
+                "INSERT INTO media_transcript_segments (media_id, start_time, end_time, text) \
+                 VALUES (?, ?, ?, ?)",
+            )
+            .bind(media_id)
+            .bind(segment.start_time)
+            .bind(segment.end_time)
+            .bind(&segment.text)
+            .execute(&mut *tx)
+            .await
+            .map_err(map_err)?;
+        }
+
+        sqlx::query(
+            "INSERT INTO media_transcripts (media_id, status, language, backend) \
+             VALUES (?, ?, ?, ?) \
+             ON CONFLICT (media_id) DO UPDATE SET \
+             status = excluded.status, language = excluded.language, \
+             backend = excluded.backend, error = NULL, updated_at = datetime('now')",
+        )
+        .bind(media_id)
+        .bind(TRANSCRIPT_COMPLETE)
+        .bind(language)
+        .bind(backend)
+        .execute(&mut *tx)
+        .await
+        .map_err(map_err)?;
+
+        tx.commit().await.map_err(map_err)?;
+        Ok(())
+    }
+
+    // ── Live sessions ─────────────────────────────────────────────
+
+    async fn start_live_session(
+        &self,
+        media_id: i64,
+        stream_path: &str,
+        recording_path: &str,
+    ) -> Result<i64, DbError> {
+        let result = sqlx::query(
+            "INSERT INTO live_sessions (media_id, stream_path, recording_path, status) \
+             VALUES (?, ?, ?, ?)",
+        )
+        .bind(media_id)
+        .bind(stream_path)
+        .bind(recording_path)
+        .bind(LIVE_SESSION_LIVE)
+        .execute(self.pool())
+        .await
+        .map_err(map_err)?;
+        Ok(result.last_insert_rowid())
+    }
+
+    async fn get_active_live_session(
+        &self,
+        stream_path: &str,
+    ) -> Result<Option<LiveSession>, DbError> {
+        let row: Option<LiveSessionSqlRow> = sqlx::query_as(&format!(
+            "SELECT {} WHERE s.stream_path = ? AND s.status = ? ORDER BY s.id DESC LIMIT 1",
+            LIVE_SESSION_COLUMNS
+        ))
+        .bind(stream_path)
+        .bind(LIVE_SESSION_LIVE)
+        .fetch_optional(self.pool())
+        .await
+        .map_err(map_err)?;
+        Ok(row.map(Into::into))
+    }
+
+    async fn get_live_session(&self, media_id: i64) -> Result<Option<LiveSession>, DbError> {
+        let row: Option<LiveSessionSqlRow> = sqlx::query_as(&format!(
+            "SELECT {} WHERE s.media_id = ? ORDER BY s.id DESC LIMIT 1",
+            LIVE_SESSION_COLUMNS
+        ))
+        .bind(media_id)
+        .fetch_optional(self.pool())
+        .await
+        .map_err(map_err)?;
+        Ok(row.map(Into::into))
+    }
+
+    async fn list_live_sessions(
+        &self,
+        user_id: &str,
+        limit: i64,
+    ) -> Result<Vec<LiveSession>, DbError> {
+        let rows: Vec<LiveSessionSqlRow> = sqlx::query_as(&format!(
+            "SELECT {} WHERE m.user_id = ? ORDER BY s.id DESC LIMIT ?",
+            LIVE_SESSION_COLUMNS
+        ))
+        .bind(user_id)
+        .bind(limit)
+        .fetch_all(self.pool())
+        .await
+        .map_err(map_err)?;
+        Ok(rows.into_iter().map(Into::into).collect())
+    }
+
+    async fn end_live_session(
+        &self,
+        id: i64,
+        status: &str,
+        peak_viewers: i64,
+        total_viewers: i64,
+    ) -> Result<(), DbError> {
+        let mut tx = self.pool().begin().await.map_err(map_err)?;
+
+        sqlx::query(
+            "UPDATE live_sessions SET status = ?, ended_at = datetime('now'), \
+             peak_viewers = MAX(peak_viewers, ?), total_viewers = MAX(total_viewers, ?) \
+             WHERE id = ?",
+        )
+        .bind(status)
+        .bind(peak_viewers)
+        .bind(total_viewers)
+        .bind(id)
+        .execute(&mut *tx)
+        .await
+        .map_err(map_err)?;
+
+        if status == LIVE_SESSION_ENDED {
+            sqlx::query(
+                "UPDATE media_items SET status = 'processing', updated_at = datetime('now') \
+                 WHERE id = (SELECT media_id FROM live_sessions WHERE id = ?) AND status = ?",
+            )
+            .bind(id)
+            .bind(MEDIA_STATUS_LIVE)
+            .execute(&mut *tx)
+            .await
+            .map_err(map_err)?;
+        }
+
+        tx.commit().await.map_err(map_err)?;
+        Ok(())
+    }
+
+    // ── Live streams ──────────────────────────────────────────────
+
+    async fn create_live_stream(
+        &self,
+        user_id: &str,
+        stream_path: &str,
+        key_hash: &str,
+        key_prefix: &str,
+        settings: &LiveStreamSettings,
+    ) -> Result<i64, DbError> {
+        let result = sqlx::query(
+            "INSERT INTO live_streams (user_id, name, stream_path, key_hash, key_prefix, \
+             expires_at, access, access_code, group_id, record) \
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(user_id)
+        .bind(&settings.name)
+        .bind(stream_path)
+        .bind(key_hash)
+        .bind(key_prefix)
+        .bind(&settings.expires_at)
+        .bind(&settings.access)
+        .bind(&settings.access_code)
+        .bind(settings.group_id)
+        .bind(settings.record)
+        .execute(self.pool())
+        .await
+        .map_err(map_err)?;
+        Ok(result.last_insert_rowid())
+    }
+
+    async fn get_live_stream(&self, id: i64) -> Result<Option<LiveStream>, DbError> {
+        let row: Option<LiveStreamSqlRow> =
+            sqlx::query_as(&format!("SELECT {} WHERE id = ?", LIVE_STREAM_COLUMNS))
+                .bind(id)
+                .fetch_optional(self.pool())
+                .await
+                .map_err(map_err)?;
+        Ok(row.map(Into::into))
+    }
+
+    async fn get_live_stream_by_path(
+        &self,
+        stream_path: &str,
+    ) -> Result<Option<LiveStream>, DbError> {
+        let row: Option<LiveStreamSqlRow> = sqlx::query_as(&format!(
+            "SELECT {} WHERE stream_path = ?",
+            LIVE_STREAM_COLUMNS
+        ))
+        .bind(stream_path)
+        .fetch_optional(self.pool())
+        .await
+        .map_err(map_err)?;
+        Ok(row.map(Into::into))
+    }
+
+    async fn list_live_streams(&self, user_id: &str) -> Result<Vec<LiveStream>, DbError> {
+        let rows: Vec<LiveStreamSqlRow> = sqlx::query_as(&format!(
+            "SELECT {} WHERE user_id = ? ORDER BY id",
+            LIVE_STREAM_COLUMNS
+        ))
+        .bind(user_id)
+        .fetch_all(self.pool())
+        .await
+        .map_err(map_err)?;
+        Ok(rows.into_iter().map(Into::into).collect())
+    }
+
+    async fn update_live_stream(
+        &self,
+        id: i64,
+        settings: &LiveStreamSettings,
+    ) -> Result<(), DbError> {
+        sqlx::query(
+            "UPDATE live_streams SET name = ?, expires_at = ?, access = ?, access_code = ?, \
+             group_id = ?, record = ?, updated_at = datetime('now') WHERE id = ?",
+        )
+        .bind(&settings.name)
+        .bind(&settings.expires_at)
+        .bind(&settings.access)
+        .bind(&settings.access_code)
+        .bind(settings.group_id)
+        .bind(settings.record)
+        .bind(id)
+        .execute(self.pool())
+        .await
+        .map_err(map_err)?;
+        Ok(())
+    }
+
+    async fn rotate_live_stream_key(
+        &self,
+        id: i64,
+        key_hash: &str,
+        key_prefix: &str,
+    ) -> Result<(), DbError> {
+        sqlx::query(
+            "UPDATE live_streams SET key_hash = ?, key_prefix = ?, \
+             key_rotated_at = datetime('now'), updated_at = datetime('now') WHERE id = ?",
+        )
+        .bind(key_hash)
+        .bind(key_prefix)
+        .bind(id)
+        .execute(self.pool())
+        .await
+        .map_err(map_err)?;
+        Ok(())
+    }
+
+    async fn delete_live_stream(&self, id: i64) -> Result<(), DbError> {
+        sqlx::query("DELETE FROM live_streams WHERE id = ?")
+            .bind(id)
+            .execute(self.pool())
+            .await
+            .map_err(map_err)?;
+        Ok(())
+    }
+
+    // ── Full-text index ───────────────────────────────────────────
+
+    async fn set_media_text(&self, media_id: i32, text: &str) -> Result<(), DbError> {
+        // media_fts is refreshed by the media_text triggers
+        sqlx::query(
+            "INSERT INTO media_text (media_id, body) VALUES (?, ?) \
+             ON CONFLICT (media_id) DO UPDATE SET \
+             body = excluded.body, updated_at = datetime('now')",
+        )
+        .bind(media_id)
+        .bind(text)
+        .execute(self.pool())
+        .await
+        .map_err(map_err)?;
+        Ok(())
+    }
+
+    async fn list_documents_without_text(
+        &self,
+        limit: i64,
+    ) -> Result<Vec<UnindexedDocument>, DbError> {
+        let rows: Vec<(i32, String, String, Option<String>)> = sqlx::query_as(
+            "SELECT id, filename, mime_type, vault_id FROM media_items \
+             WHERE media_type = 'document' AND deleted_at IS NULL \
+             AND id NOT IN (SELECT media_id FROM media_text) \
+             ORDER BY id LIMIT ?",
+        )
+        .bind(limit)
+        .fetch_all(self.pool())
+        .await
+        .map_err(map_err)?;
+
+        Ok(rows
+            .into_iter()
+            .map(|(id, filename, mime_type, vault_id)| UnindexedDocument {
+                id,
+                filename,
+                mime_type,
+                vault_id,
+            })
+            .collect())
+    }
+
+    // ── Integrity ─────────────────────────────────────────────────
+
+    async fn find_media_by_content_hash(
+        &self,
+        vault_id: &str,
+        media_type: &str,
+        content_hash: &str,
+    ) -> Result<Option<MediaItemRow>, DbError> {
+        let row = sqlx::query_as::<_, FullMediaRow>(
+            "SELECT * FROM media_items \
+             WHERE vault_id = ? AND media_type = ? AND content_hash = ? AND status != 'error' AND deleted_at IS NULL \
+             ORDER BY id LIMIT 1",
+        )
+        .bind(vault_id)
+        .bind(media_type)
+        .bind(content_hash)
+        .fetch_optional(self.pool())
+        .await
+        .map_err(map_err)?;
+        Ok(row.map(Into::into))
+    }
+
+    async fn list_media_for_verification(
+        &self,
+        after_id: i32,
+        limit: i64,
+    ) -> Result<Vec<IntegrityCandidate>, DbError> {
+        let rows: Vec<(i32, String, String, String, String, Option<String>)> = sqlx::query_as(
+            "SELECT id, slug, media_type, filename, vault_id, file_hash FROM media_items \
+             WHERE status = 'active' AND deleted_at IS NULL AND vault_id IS NOT NULL AND id > ? \
+             ORDER BY id LIMIT ?",
+        )
+        .bind(after_id)
+        .bind(limit)
+        .fetch_all(self.pool())
+        .await
+        .map_err(map_err)?;
+
+        Ok(rows
+            .into_iter()
+            .map(
+                |(id, slug, media_type, filename, vault_id, file_hash)| IntegrityCandidate {
+                    id,
+                    slug,
+                    media_type,
+                    filename,
+                    vault_id,
+                    file_hash,
+                },
+            )
+            .collect())
+    }
+
+    async fn record_integrity_check(
+        &self,
+        media_id: i32,
+        status: &str,
+        file_hash: Option<&str>,
+    ) -> Result<(), DbError> {
+        sqlx::query(
+            "UPDATE media_items SET integrity_status = ?, \
+             integrity_checked_at = datetime('now'), \
+             file_hash = COALESCE(file_hash, ?) \
+             WHERE id = ?",
+        )
+        .bind(status)
+        .bind(file_hash)
+        .bind(media_id)
+        .execute(self.pool())
+        .await
+        .map_err(map_err)?;
+        Ok(())
+    }
+
+    async fn get_integrity_summary(&self) -> Result<IntegritySummary, DbError> {
+        let (total, ok, mismatch, missing, unchecked, last_checked_at): (
+            i64,
+            i64,
+            i64,
+            i64,
+            i64,
+            Option<String>,
+        ) = sqlx::query_as(
+            "SELECT COUNT(*), \
+             COALESCE(SUM(integrity_status = 'ok'), 0), \
+             COALESCE(SUM(integrity_status = 'mismatch'), 0), \
+             COALESCE(SUM(integrity_status = 'missing'), 0), \
+             COALESCE(SUM(integrity_status IS NULL), 0), \
+             MAX(integrity_checked_at) \
+             FROM media_items WHERE status = 'active' AND deleted_at IS NULL",
+        )
+        .fetch_one(self.pool())
+        .await
+        .map_err(map_err)?;
+
+        Ok(IntegritySummary {
+            total,
+            ok,
+            mismatch,
+            missing,
+            unchecked,
+            last_checked_at,
+        })
+    }
+
+    async fn list_integrity_issues(&self, limit: i64) -> Result<Vec<IntegrityIssue>, DbError> {
+        let rows: Vec<IntegrityIssueSqlRow> = sqlx::query_as(
+            "SELECT id, slug, title, media_type, vault_id, file_hash, \
+             integrity_status, integrity_checked_at FROM media_items \
+             WHERE status = 'active' AND deleted_at IS NULL AND integrity_status IN ('mismatch', 'missing') \
+             ORDER BY integrity_checked_at DESC, id LIMIT ?",
+        )
+        .bind(limit)
+        .fetch_all(self.pool())
+        .await
+        .map_err(map_err)?;
+        Ok(rows.into_iter().map(Into::into).collect())
+    }
+
+    // ── Audio ─────────────────────────────────────────────────────
 
-    async fn get_media_title(
-        &self,
-        slug: &str,
-        media_type: &str,
-    ) -> Result<Option<String>, DbError> {
-        sqlx::query_scalar(
-            "SELECT title FROM media_items WHERE slug = ? AND media_type = ?",
+    async fn get_audio_details(&self, media_id: i64) -> Result<Option<AudioDetails>, DbError> {
+        let row: Option<AudioDetailsSqlRow> = sqlx::query_as(
+            "SELECT media_id, duration, bitrate, channels, sample_rate, codec \
+             FROM media_audio WHERE media_id = ?",
         )
-        .bind(slug)
-        .bind(media_type)
+        .bind(media_id)
         .fetch_optional(self.pool())
         .await
-        .map_err(map_err)
+        .map_err(map_err)?;
+        Ok(row.map(Into::into))
     }
 
-    async fn get_media_id_by_type(
-        &self,
-        media_type: &str,
-        slug: &str,
-    ) -> Result<Option<i64>, DbError> {
-        sqlx::query_scalar(
-            "SELECT id FROM media_items WHERE media_type = ? AND slug = ?",
+    async fn upsert_audio_details(&self, details: &AudioDetails) -> Result<(), DbError> {
+        sqlx::query(
+            "INSERT INTO media_audio (media_id, duration, bitrate, channels, sample_rate, codec) \
+             VALUES (?, ?, ?, ?, ?, ?) \
+             ON CONFLICT (media_id) DO UPDATE SET \
+             duration = excluded.duration, bitrate = excluded.bitrate, \
+             channels = excluded.channels, sample_rate = excluded.sample_rate, \
+             codec = excluded.codec, updated_at = datetime('now')",
         )
-        .bind(media_type)
-        .bind(slug)
-        .fetch_optional(self.pool())
+        .bind(details.media_id)
+        .bind(details.duration)
+        .bind(details.bitrate)
+        .bind(details.channels)
+        .bind(details.sample_rate)
+        .bind(&details.codec)
+        .execute(self.pool())
         .await
-        .map_err(map_err)
+        .map_err(map_err)?;
+        Ok(())
     }
+    // ── Photos ────────────────────────────────────────────────────
 
-    async fn get_media_enrichment(
-        &self,
-        slug: &str,
-    ) -> Result<Option<db::media::MediaEnrichment>, DbError> {
-        let row: Option<(String, Option<String>, String)> = sqlx::query_as(
-            "SELECT COALESCE(filename, ?), thumbnail_url, COALESCE(title, ?) \
-             FROM media_items WHERE slug = ?",
+    async fn get_photo_details(&self, media_id: i64) -> Result<Option<PhotoDetails>, DbError> {
+        let row: Option<PhotoDetailsSqlRow> = sqlx::query_as(
+            "SELECT media_id, captured_at, camera_make, camera_model, lens_model, \
+             gps_latitude, gps_longitude \
+             FROM media_photo WHERE media_id = ?",
         )
-        .bind(slug)
-        .bind(slug)
-        .bind(slug)
+        .bind(media_id)
         .fetch_optional(self.pool())
         .await
         .map_err(map_err)?;
-        Ok(row.map(|(filename, thumbnail_url, title)| db::media::MediaEnrichment {
-            filename,
-            thumbnail_url,
-            title,
-        }))
+        Ok(row.map(Into::into))
     }
 
-    async fn assign_media_group(&self, slug: &str, group_id: i32) -> Result<(), DbError> {
+    async fn upsert_photo_details(&self, details: &PhotoDetails) -> Result<(), DbError> {
         sqlx::query(
-            "UPDATE media_items SET group_id = ?, updated_at = datetime('now') WHERE slug = ?",
+            "INSERT INTO media_photo (media_id, captured_at, camera_make, camera_model, \
+             lens_model, gps_latitude, gps_longitude) \
+             VALUES (?, ?, ?, ?, ?, ?, ?) \
+             ON CONFLICT (media_id) DO UPDATE SET \
+             captured_at = excluded.captured_at, camera_make = excluded.camera_make, \
+             camera_model = excluded.camera_model, lens_model = excluded.lens_model, \
+             gps_latitude = excluded.gps_latitude, gps_longitude = excluded.gps_longitude, \
+             updated_at = datetime('now')",
         )
-        .bind(group_id)
-        .bind(slug)
+        .bind(details.media_id)
+        .bind(&details.captured_at)
+        .bind(&details.camera_make)
+        .bind(&details.camera_model)
+        .bind(&details.lens_model)
+        .bind(details.gps_latitude)
+        .bind(details.gps_longitude)
         .execute(self.pool())
         .await
         .map_err(map_err)?;
         Ok(())
     }
 
-    async fn unassign_media_group(&self, slug: &str, group_id: i32) -> Result<(), DbError> {
-        sqlx::query(
-            "UPDATE media_items SET group_id = NULL, updated_at = datetime('now') \
-             WHERE slug = ? AND group_id = ?",
+    async fn list_geotagged_images(
+        &self,
+        user_id: &str,
+        vault_id: Option<&str>,
+    ) -> Result<Vec<GeotaggedImage>, DbError> {
+        let rows: Vec<GeotaggedImageSqlRow> = sqlx::query_as(
+            "SELECT m.slug, m.title, p.gps_latitude, p.gps_longitude, p.captured_at, m.vault_id \
+             FROM media_photo p JOIN media_items m ON m.id = p.media_id \
+             WHERE m.user_id = ? AND m.media_type = 'image' AND m.deleted_at IS NULL \
+             AND p.gps_latitude IS NOT NULL AND p.gps_longitude IS NOT NULL \
+             AND (? IS NULL OR m.vault_id = ?) \
+             ORDER BY p.captured_at DESC, m.created_at DESC",
+        )
+        .bind(user_id)
+        .bind(vault_id)
+        .bind(vault_id)
+        .fetch_all(self.pool())
+        .await
+        .map_err(map_err)?;
+        Ok(rows.into_iter().map(Into::into).collect())
+    }
+
+    // ── Trash ─────────────────────────────────────────────────────
+
+    async fn trash_media(&self, slug: &str, user_id: &str) -> Result<bool, DbError> {
+        let result = sqlx::query(
+            "UPDATE media_items SET deleted_at = datetime('now') \
+             WHERE slug = ? AND user_id = ? AND deleted_at IS NULL",
         )
         .bind(slug)
-        .bind(group_id)
+        .bind(user_id)
         .execute(self.pool())
         .await
         .map_err(map_err)?;
-        Ok(())
+        Ok(result.rows_affected() > 0)
     }
 
-    async fn assign_media_group_for_user(
-        &self,
-        slug: &str,
-        user_id: &str,
-        group_id: i32,
-    ) -> Result<bool, DbError> {
+    async fn restore_media(&self, slug: &str, user_id: &str) -> Result<bool, DbError> {
         let result = sqlx::query(
-            "UPDATE media_items SET group_id = ?, updated_at = datetime('now') \
-             WHERE slug = ? AND user_id = ?",
+            "UPDATE media_items SET deleted_at = NULL \
+             WHERE slug = ? AND user_id = ? AND deleted_at IS NOT NULL",
         )
-        .bind(group_id)
         .bind(slug)
         .bind(user_id)
         .execute(self.pool())
@@ -1355,163 +2772,326 @@ impl MediaRepository for SqliteDatabase {
         Ok(result.rows_affected() > 0)
     }
 
-    async fn check_media_in_group(
+    async fn get_trashed_media(
         &self,
         slug: &str,
         user_id: &str,
-        group_id: i32,
-    ) -> Result<Option<i64>, DbError> {
-        sqlx::query_scalar(
-            "SELECT id FROM media_items WHERE slug = ? AND user_id = ? AND group_id = ?",
+    ) -> Result<Option<TrashedMedia>, DbError> {
+        let row: Option<TrashedMediaSqlRow> = sqlx::query_as(
+            "SELECT id, slug, title, media_type, filename, file_size, vault_id, deleted_at \
+             FROM media_items WHERE slug = ? AND user_id = ? AND deleted_at IS NOT NULL",
         )
         .bind(slug)
         .bind(user_id)
-        .bind(group_id)
         .fetch_optional(self.pool())
         .await
-        .map_err(map_err)
+        .map_err(map_err)?;
+        Ok(row.map(Into::into))
     }
 
-    async fn list_group_media(
-        &self,
-        group_id: i32,
-    ) -> Result<Vec<db::media::GroupMediaRow>, DbError> {
-        let rows: Vec<(String, String, String, String, Option<String>)> = sqlx::query_as(
-            "SELECT slug, title, media_type, filename, thumbnail_url \
-             FROM media_items WHERE group_id = ? ORDER BY media_type, created_at DESC",
+    async fn list_trashed_media(&self, user_id: &str) -> Result<Vec<TrashedMedia>, DbError> {
+        let rows: Vec<TrashedMediaSqlRow> = sqlx::query_as(
+            "SELECT id, slug, title, media_type, filename, file_size, vault_id, deleted_at \
+             FROM media_items WHERE user_id = ? AND deleted_at IS NOT NULL \
+             ORDER BY deleted_at DESC",
         )
-        .bind(group_id)
+        .bind(user_id)
         .fetch_all(self.pool())
         .await
         .map_err(map_err)?;
-        Ok(rows
-            .into_iter()
-            .map(|(slug, title, media_type, filename, thumbnail_url)| {
-                db::media::GroupMediaRow { slug, title, media_type, filename, thumbnail_url }
-            })
-            .collect())
+        Ok(rows.into_iter().map(Into::into).collect())
     }
 
-    async fn count_public_active(&self, tenant_id: &str) -> Result<i64, DbError> {
-        let count: i64 = sqlx::query_scalar(
-            "SELECT COUNT(*) FROM media_items WHERE is_public = 1 AND status = 'active' AND tenant_id = ?",
+    async fn list_expired_trashed_media(
+        &self,
+        retention_days: i64,
+    ) -> Result<Vec<TrashedMedia>, DbError> {
+        let rows: Vec<TrashedMediaSqlRow> = sqlx::query_as(
+            "SELECT id, slug, title, media_type, filename, file_size, vault_id, deleted_at \
+             FROM media_items \
+             WHERE deleted_at IS NOT NULL AND deleted_at < datetime('now', ?) \
+             ORDER BY deleted_at",
         )
-        .bind(tenant_id)
-        .fetch_one(self.pool())
+        .bind(format!("-{} days", retention_days.max(0)))
+        .fetch_all(self.pool())
         .await
         .map_err(map_err)?;
-        Ok(count)
+        Ok(rows.into_iter().map(Into::into).collect())
     }
 
-    async fn list_public_catalog(
+    // ── Versions ──────────────────────────────────────────────────
+
+    async fn get_current_media_version(
         &self,
-        tenant_id: &str,
-        limit: i64,
-        offset: i64,
-    ) -> Result<Vec<db::media::PublicCatalogRow>, DbError> {
-        let rows: Vec<(String, String, String, Option<String>, Option<String>, Option<String>, Option<i64>, String, Option<String>)> =
-            sqlx::query_as(
-                "SELECT slug, media_type, title, description, filename, mime_type, file_size, \
-                 created_at, updated_at \
-                 FROM media_items WHERE is_public = 1 AND status = 'active' AND tenant_id = ? \
-                 ORDER BY created_at DESC LIMIT ? OFFSET ?",
-            )
-            .bind(tenant_id)
-            .bind(limit)
-            .bind(offset)
-            .fetch_all(self.pool())
-            .await
-            .map_err(map_err)?;
-        Ok(rows
-            .into_iter()
-            .map(|(slug, media_type, title, description, filename, mime_type, file_size, created_at, updated_at)| {
-                db::media::PublicCatalogRow {
-                    slug, media_type, title, description, filename, mime_type, file_size, created_at, updated_at,
-                }
-            })
-            .collect())
+        media_id: i32,
+    ) -> Result<Option<MediaVersion>, DbError> {
+        let row: Option<MediaVersionSqlRow> = sqlx::query_as(&format!(
+            "SELECT id AS media_id, version, {}, 1 AS current, NULL AS archived_by, \
+             NULL AS comment, NULL AS archived_at FROM media_items WHERE id = ?",
+            MEDIA_FILE_COLUMNS
+        ))
+        .bind(media_id)
+        .fetch_optional(self.pool())
+        .await
+        .map_err(map_err)?;
+        Ok(row.map(Into::into))
     }
 
-    async fn get_public_metadata(
-        &self,
-        slug: &str,
-    ) -> Result<Option<db::media::PublicCatalogRow>, DbError> {
-        let row: Option<(String, String, String, Option<String>, Option<String>, Option<String>, Option<i64>, String, Option<String>)> =
-            sqlx::query_as(
-                "SELECT slug, media_type, title, description, filename, mime_type, file_size, \
-                 created_at, updated_at \
-                 FROM media_items WHERE slug = ? AND is_public = 1 AND status = 'active'",
-            )
-            .bind(slug)
-            .fetch_optional(self.pool())
-            .await
-            .map_err(map_err)?;
-        Ok(row.map(|(slug, media_type, title, description, filename, mime_type, file_size, created_at, updated_at)| {
-            db::media::PublicCatalogRow {
-                slug, media_type, title, description, filename, mime_type, file_size, created_at, updated_at,
-            }
-        }))
+    async fn list_media_versions(&self, media_id: i32) -> Result<Vec<MediaVersion>, DbError> {
+        let rows: Vec<MediaVersionSqlRow> = sqlx::query_as(&format!(
+            "SELECT media_id, version, {}, 0 AS current, archived_by, comment, archived_at \
+             FROM media_versions WHERE media_id = ? ORDER BY version DESC",
+            MEDIA_FILE_COLUMNS
+        ))
+        .bind(media_id)
+        .fetch_all(self.pool())
+        .await
+        .map_err(map_err)?;
+        Ok(rows.into_iter().map(Into::into).collect())
     }
 
-    async fn get_public_media_for_thumbnail(
+    async fn get_media_version(
         &self,
-        slug: &str,
-    ) -> Result<Option<(String, Option<String>)>, DbError> {
-        sqlx::query_as(
-            "SELECT media_type, vault_id FROM media_items \
-             WHERE slug = ? AND is_public = 1 AND status = 'active'",
-        )
-        .bind(slug)
+        media_id: i32,
+        version: i64,
+    ) -> Result<Option<MediaVersion>, DbError> {
+        let row: Option<MediaVersionSqlRow> = sqlx::query_as(&format!(
+            "SELECT media_id, version, {}, 0 AS current, archived_by, comment, archived_at \
+             FROM media_versions WHERE media_id = ? AND version = ?",
+            MEDIA_FILE_COLUMNS
+        ))
+        .bind(media_id)
+        .bind(version)
         .fetch_optional(self.pool())
         .await
-        .map_err(map_err)
+        .map_err(map_err)?;
+        Ok(row.map(Into::into))
     }
 
-    async fn get_public_media_for_content(
+    async fn archive_media_version(
         &self,
-        slug: &str,
-    ) -> Result<Option<(String, String, Option<String>)>, DbError> {
-        sqlx::query_as(
-            "SELECT media_type, filename, vault_id FROM media_items \
-             WHERE slug = ? AND is_public = 1 AND status = 'active'",
+        media_id: i32,
+        archived_by: &str,
+        comment: Option<&str>,
+    ) -> Result<i64, DbError> {
+        let version: Option<i64> = sqlx::query_scalar(&format!(
+            "INSERT INTO media_versions (media_id, version, {0}, archived_by, comment) \
+             SELECT id, version, {0}, ?, ? FROM media_items WHERE id = ? \
+             RETURNING version",
+            MEDIA_FILE_COLUMNS
+        ))
+        .bind(archived_by)
+        .bind(comment)
+        .bind(media_id)
+        .fetch_optional(self.pool())
+        .await
+        .map_err(map_err)?;
+        version.ok_or_else(|| DbError::internal(format!("media item {} not found", media_id)))
+    }
+
+    async fn replace_media_file(&self, media_id: i32, item: &MediaInsert) -> Result<i64, DbError> {
+        // Archived versions may be newer than the current one after a rollback
+        let version: Option<i64> = sqlx::query_scalar(
+            "UPDATE media_items SET filename = ?, original_filename = ?, mime_type = ?, \
+             file_size = ?, video_type = ?, thumbnail_url = ?, status = ?, \
+             content_hash = ?, file_hash = ?, integrity_status = NULL, \
+             integrity_checked_at = NULL, \
+             version = MAX(version, COALESCE( \
+                 (SELECT MAX(version) FROM media_versions WHERE media_id = ?), 0)) + 1, \
+             updated_at = datetime('now') \
+             WHERE id = ? RETURNING version",
         )
-        .bind(slug)
+        .bind(&item.filename)
+        .bind(&item.original_filename)
+        .bind(&item.mime_type)
+        .bind(item.file_size)
+        .bind(&item.video_type)
+        .bind(&item.thumbnail_url)
+        .bind(&item.status)
+        .bind(&item.content_hash)
+        .bind(&item.file_hash)
+        .bind(media_id)
+        .bind(media_id)
         .fetch_optional(self.pool())
         .await
-        .map_err(map_err)
+        .map_err(map_err)?;
+        version.ok_or_else(|| DbError::internal(format!("media item {} not found", media_id)))
     }
 
-    async fn get_vault_media_for_user(
-        &self,
-        vault_id: &str,
-        user_id: &str,
-    ) -> Result<Vec<db::media::FolderMediaRow>, DbError> {
-        type Row = (
-            Option<String>,
-            Option<String>,
-            Option<String>,
-            Option<String>,
-            Option<i64>,
-            Option<String>,
-            Option<String>,
-        );
-        let rows: Vec<Row> = sqlx::query_as(
-            "SELECT slug, title, media_type, mime_type, file_size, thumbnail_url, created_at \
-             FROM media_items WHERE vault_id = ? AND user_id = ? AND status = 'active' \
-             ORDER BY created_at DESC",
-        )
-        .bind(vault_id)
-        .bind(user_id)
-        .fetch_all(self.pool())
+    async fn restore_media_version(&self, media_id: i32, version: i64) -> Result<bool, DbError> {
+        let mut tx = self.pool().begin().await.map_err(map_err)?;
+
+        let result = sqlx::query(&format!(
+            "UPDATE media_items SET ({0}, version, status, integrity_status, \
+             integrity_checked_at, updated_at) = \
+             (SELECT {0}, version, 'active', NULL, NULL, datetime('now') \
+              FROM media_versions WHERE media_id = ? AND version = ?) \
+             WHERE id = ? AND EXISTS \
+             (SELECT 1 FROM media_versions WHERE media_id = ? AND version = ?)",
+            MEDIA_FILE_COLUMNS
+        ))
+        .bind(media_id)
+        .bind(version)
+        .bind(media_id)
+        .bind(media_id)
+        .bind(version)
+        .execute(&mut *tx)
         .await
         .map_err(map_err)?;
-        Ok(rows
-            .into_iter()
-            .map(|(slug, title, media_type, mime_type, file_size, thumbnail_url, created_at)| {
-                db::media::FolderMediaRow {
-                    slug, title, media_type, mime_type, file_size, thumbnail_url, created_at,
+        if result.rows_affected() == 0 {
+            return Ok(false);
+        }
+
+        sqlx::query("DELETE FROM media_versions WHERE media_id = ? AND version = ?")
+            .bind(media_id)
+            .bind(version)
+            .execute(&mut *tx)
+            .await
+            .map_err(map_err)?;
+
+        tx.commit().await.map_err(map_err)?;
+        Ok(true)
+    }
+
+    // ── Batch operations ──────────────────────────────────────────
+
+    async fn apply_media_batch(
+        &self,
+        user_id: &str,
+        slugs: &[String],
+        operation: &MediaBatchOperation,
+        dry_run: bool,
+    ) -> Result<Vec<MediaBatchItemResult>, DbError> {
+        let mut tx = self.pool().begin().await.map_err(map_err)?;
+        let mut seen = std::collections::HashSet::new();
+        let mut results = Vec::with_capacity(slugs.len());
+
+        for slug in slugs {
+            if !seen.insert(slug.as_str()) {
+                continue;
+            }
+            let target: Option<BatchTargetSqlRow> = sqlx::query_as(
+                "SELECT id, media_type, filename, vault_id, is_public, group_id \
+                 FROM media_items WHERE slug = ? AND user_id = ? AND deleted_at IS NULL",
+            )
+            .bind(slug)
+            .bind(user_id)
+            .fetch_optional(&mut *tx)
+            .await
+            .map_err(map_err)?;
+            let Some(target) = target else {
+                results.push(MediaBatchItemResult {
+                    slug: slug.clone(),
+                    status: MediaBatchStatus::NotFound,
+                    media_type: None,
+                    vault_id: None,
+                    filename: None,
+                    error: None,
+                });
+                continue;
+            };
+
+            let changed = match operation {
+                MediaBatchOperation::AddTags { tags } => {
+                    let mut added = 0;
+                    for tag in tags {
+                        added += sqlx::query(
+                            "INSERT OR IGNORE INTO media_tags (media_id, tag, created_at) \
+                             VALUES (?, ?, datetime('now'))",
+                        )
+                        .bind(target.id)
+                        .bind(tag)
+                        .execute(&mut *tx)
+                        .await
+                        .map_err(map_err)?
+                        .rows_affected();
+                    }
+                    added > 0
                 }
-            })
-            .collect())
+                MediaBatchOperation::RemoveTags { tags } => {
+                    let mut removed = 0;
+                    for tag in tags {
+                        removed += sqlx::query("DELETE FROM media_tags WHERE media_id = ? AND tag = ?")
+                            .bind(target.id)
+                            .bind(tag)
+                            .execute(&mut *tx)
+                            .await
+                            .map_err(map_err)?
+                            .rows_affected();
+                    }
+                    removed > 0
+                }
+                MediaBatchOperation::SetGroup { group_id } => {
+                    let changed = target.group_id != *group_id;
+                    if changed {
+                        sqlx::query(
+                            "UPDATE media_items SET group_id = ?, updated_at = datetime('now') WHERE id = ?",
+                        )
+                        .bind(group_id)
+                        .bind(target.id)
+                        .execute(&mut *tx)
+                        .await
+                        .map_err(map_err)?;
+                    }
+                    changed
+                }
+                MediaBatchOperation::SetVisibility { is_public } => {
+                    let changed = (target.is_public != 0) != *is_public;
+                    if changed {
+                        sqlx::query(
+                            "UPDATE media_items SET is_public = ?, updated_at = datetime('now') WHERE id = ?",
+                        )
+                        .bind(*is_public as i32)
+                        .bind(target.id)
+                        .execute(&mut *tx)
+                        .await
+                        .map_err(map_err)?;
+                    }
+                    changed
+                }
+                MediaBatchOperation::MoveToVault { vault_id } => {
+                    let changed = target.vault_id.as_deref() != Some(vault_id.as_str());
+                    if changed {
+                        sqlx::query(
+                            "UPDATE media_items SET vault_id = ?, updated_at = datetime('now') WHERE id = ?",
+                        )
+                        .bind(vault_id)
+                        .bind(target.id)
+                        .execute(&mut *tx)
+                        .await
+                        .map_err(map_err)?;
+                    }
+                    changed
+                }
+                MediaBatchOperation::Delete => {
+                    sqlx::query("UPDATE media_items SET deleted_at = datetime('now') WHERE id = ?")
+                        .bind(target.id)
+                        .execute(&mut *tx)
+                        .await
+                        .map_err(map_err)?;
+                    true
+                }
+            };
+
+            results.push(MediaBatchItemResult {
+                slug: slug.clone(),
+                status: if changed {
+                    MediaBatchStatus::Applied
+                } else {
+                    MediaBatchStatus::Unchanged
+                },
+                media_type: Some(target.media_type),
+                vault_id: target.vault_id,
+                filename: Some(target.filename),
+                error: None,
+            });
+        }
+
+        if dry_run {
+            tx.rollback().await.map_err(map_err)?;
+        } else {
+            tx.commit().await.map_err(map_err)?;
+        }
+        Ok(results)
     }
 }
@@ -1,11 +1,91 @@
 use crate::SqliteDatabase;
-use db::user_auth::{UpsertUserRequest, UserAuthRepository, UserLoginInfo};
+use db::user_auth::{
+    LocalAccount, LocalInvitation, NewLocalAccount, NewPasskeyCredential, PasskeyCredential,
+    UpsertUserRequest, UserAuthRepository, UserLoginInfo,
+};
 use db::DbError;
 
 fn map_err(e: sqlx::Error) -> DbError {
-    DbError::Internal(e.to_string())
+    match &e {
+        sqlx::Error::Database(db_err) if db_err.is_unique_violation() => {
+            DbError::UniqueViolation(db_err.message().to_string())
+        }
+        _ => DbError::Internal(e.to_string()),
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct LocalAccountSqlRow {
+    user_id: String,
+    email: String,
+    name: Option<String>,
+    password_hash: String,
+    password_changed_at: String,
+    failed_attempts: i64,
+    locked: bool,
+    locked_until: Option<String>,
+    totp_secret: Option<String>,
+    totp_enabled: bool,
+    created_at: String,
+}
+
+impl From<LocalAccountSqlRow> for LocalAccount {
+    fn from(r: LocalAccountSqlRow) -> Self {
+        LocalAccount {
+            user_id: r.user_id,
+            email: r.email,
+            name: r.name,
+            password_hash: r.password_hash,
+            password_changed_at: r.password_changed_at,
+            failed_attempts: r.failed_attempts,
+            locked: r.locked,
+            locked_until: r.locked_until,
+            totp_secret: r.totp_secret,
+            totp_enabled: r.totp_enabled,
+            created_at: r.created_at,
+        }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct PasskeySqlRow {
+    id: i64,
+    user_id: String,
+    credential_id: String,
+    public_key: Vec<u8>,
+    sign_count: i64,
+    name: String,
+    created_at: String,
+    last_used_at: Option<String>,
+}
+
+impl From<PasskeySqlRow> for PasskeyCredential {
+    fn from(r: PasskeySqlRow) -> Self {
+        PasskeyCredential {
+            id: r.id,
+            user_id: r.user_id,
+            credential_id: r.credential_id,
+            public_key: r.public_key,
+            sign_count: r.sign_count,
+            name: r.name,
+            created_at: r.created_at,
+            last_used_at: r.last_used_at,
+        }
+    }
 }
 
+const PASSKEY_COLUMNS: &str =
+    "id, user_id, credential_id, public_key, sign_count, name, created_at, last_used_at";
+
+const LOCAL_ACCOUNT_SELECT: &str = r#"
+    SELECT la.user_id, la.email, u.name, la.password_hash, la.password_changed_at,
+           la.failed_attempts,
+           (la.locked_until IS NOT NULL AND la.locked_until > datetime('now')) AS locked,
+           la.locked_until, la.totp_secret, la.totp_enabled, la.created_at
+    FROM local_accounts la
+    JOIN users u ON u.id = la.user_id
+"#;
+
 #[async_trait::async_trait]
 impl UserAuthRepository for SqliteDatabase {
     async fn upsert_user(&self, req: &UpsertUserRequest<'_>) -> Result<(), DbError> {
@@ -114,4 +194,438 @@ impl UserAuthRepository for SqliteDatabase {
                 .map_err(map_err)?;
         Ok(row.map(|(name,)| name))
     }
+
+    async fn get_user_avatar_url(&self, user_id: &str) -> Result<Option<String>, DbError> {
+        let avatar_url: Option<Option<String>> =
+            sqlx::query_scalar("SELECT avatar_url FROM users WHERE id = ?")
+                .bind(user_id)
+                .fetch_optional(self.pool())
+                .await
+                .map_err(map_err)?;
+        Ok(avatar_url.flatten())
+    }
+
+    async fn record_user_login(&self, user_id: &str) -> Result<(), DbError> {
+        sqlx::query("UPDATE users SET last_login_at = CURRENT_TIMESTAMP WHERE id = ?")
+            .bind(user_id)
+            .execute(self.pool())
+            .await
+            .map_err(map_err)?;
+        Ok(())
+    }
+
+    // ── Local accounts ────────────────────────────────────────────
+
+    async fn create_local_account(&self, account: &NewLocalAccount<'_>) -> Result<(), DbError> {
+        let mut tx = self.pool().begin().await.map_err(map_err)?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO users (id, email, name, provider, tenant_id)
+            VALUES (?, ?, ?, 'local', COALESCE(?, 'platform'))
+            "#,
+        )
+        .bind(account.user_id)
+        .bind(account.email)
+        .bind(account.name)
+        .bind(account.tenant_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(map_err)?;
+
+        sqlx::query("INSERT INTO local_accounts (user_id, email, password_hash) VALUES (?, ?, ?)")
+            .bind(account.user_id)
+            .bind(account.email)
+            .bind(account.password_hash)
+            .execute(&mut *tx)
+            .await
+            .map_err(map_err)?;
+
+        tx.commit().await.map_err(map_err)?;
+        Ok(())
+    }
+
+    async fn get_local_account(&self, user_id: &str) -> Result<Option<LocalAccount>, DbError> {
+        let sql = format!("{LOCAL_ACCOUNT_SELECT} WHERE la.user_id = ?");
+        let row: Option<LocalAccountSqlRow> = sqlx::query_as(&sql)
+            .bind(user_id)
+            .fetch_optional(self.pool())
+            .await
+            .map_err(map_err)?;
+        Ok(row.map(Into::into))
+    }
+
+    async fn get_local_account_by_email(
+        &self,
+        email: &str,
+    ) -> Result<Option<LocalAccount>, DbError> {
+        let sql = format!("{LOCAL_ACCOUNT_SELECT} WHERE la.email = ?");
+        let row: Option<LocalAccountSqlRow> = sqlx::query_as(&sql)
+            .bind(email)
+            .fetch_optional(self.pool())
+            .await
+            .map_err(map_err)?;
+        Ok(row.map(Into::into))
+    }
+
+    async fn list_local_accounts(&self) -> Result<Vec<LocalAccount>, DbError> {
+        let sql = format!("{LOCAL_ACCOUNT_SELECT} ORDER BY la.created_at");
+        let rows: Vec<LocalAccountSqlRow> = sqlx::query_as(&sql)
+            .fetch_all(self.pool())
+            .await
+            .map_err(map_err)?;
+        Ok(rows.into_iter().map(Into::into).collect())
+    }
+
+    async fn record_local_login_failure(
+        &self,
+        user_id: &str,
+        max_failures: u32,
+        lockout_minutes: u32,
+    ) -> Result<bool, DbError> {
+        // A failure after an expired lock starts a new count
+        let locked: Option<bool> = sqlx::query_scalar(
+            r#"
+            UPDATE local_accounts SET
+                failed_attempts = CASE
+                    WHEN locked_until IS NOT NULL AND locked_until <= datetime('now') THEN 1
+                    ELSE failed_attempts + 1
+                END,
+                locked_until = CASE
+                    WHEN locked_until IS NOT NULL AND locked_until > datetime('now') THEN locked_until
+                    WHEN (CASE
+                        WHEN locked_until IS NOT NULL AND locked_until <= datetime('now') THEN 1
+                        ELSE failed_attempts + 1
+                    END) >= ? THEN datetime('now', '+' || ? || ' minutes')
+                    ELSE NULL
+                END
+            WHERE user_id = ?
+            RETURNING locked_until IS NOT NULL
+            "#,
+        )
+        .bind(max_failures)
+        .bind(lockout_minutes)
+        .bind(user_id)
+        .fetch_optional(self.pool())
+        .await
+        .map_err(map_err)?;
+        Ok(locked.unwrap_or(false))
+    }
+
+    async fn record_local_login_success(&self, user_id: &str) -> Result<(), DbError> {
+        sqlx::query(
+            "UPDATE local_accounts SET failed_attempts = 0, locked_until = NULL WHERE user_id = ?",
+        )
+        .bind(user_id)
+        .execute(self.pool())
+        .await
+        .map_err(map_err)?;
+        self.record_user_login(user_id).await
+    }
+
+    async fn set_local_password(&self, user_id: &str, password_hash: &str) -> Result<(), DbError> {
+        sqlx::query(
+            r#"
+            UPDATE local_accounts SET
+                password_hash = ?,
+                password_changed_at = datetime('now'),
+                failed_attempts = 0,
+                locked_until = NULL
+            WHERE user_id = ?
+            "#,
+        )
+        .bind(password_hash)
+        .bind(user_id)
+        .execute(self.pool())
+        .await
+        .map_err(map_err)?;
+        Ok(())
+    }
+
+    async fn create_local_invitation(
+        &self,
+        token_hash: &str,
+        email: &str,
+        tenant_id: Option<&str>,
+        invited_by: &str,
+        valid_hours: i64,
+    ) -> Result<String, DbError> {
+        sqlx::query_scalar(
+            r#"
+            INSERT INTO local_signup_invitations (token_hash, email, tenant_id, invited_by, expires_at)
+            VALUES (?, ?, ?, ?, datetime('now', '+' || ? || ' hours'))
+            RETURNING expires_at
+            "#,
+        )
+        .bind(token_hash)
+        .bind(email)
+        .bind(tenant_id)
+        .bind(invited_by)
+        .bind(valid_hours)
+        .fetch_one(self.pool())
+        .await
+        .map_err(map_err)
+    }
+
+    async fn get_local_invitation(
+        &self,
+        token_hash: &str,
+    ) -> Result<Option<LocalInvitation>, DbError> {
+        let row: Option<(String, Option<String>, String)> = sqlx::query_as(
+            r#"
+            SELECT email, tenant_id, expires_at FROM local_signup_invitations
+            WHERE token_hash = ? AND expires_at > datetime('now')
+            "#,
+        )
+        .bind(token_hash)
+        .fetch_optional(self.pool())
+        .await
+        .map_err(map_err)?;
+        Ok(row.map(|(email, tenant_id, expires_at)| LocalInvitation {
+            email,
+            tenant_id,
+            expires_at,
+        }))
+    }
+
+    async fn delete_local_invitation(&self, token_hash: &str) -> Result<(), DbError> {
+        sqlx::query("DELETE FROM local_signup_invitations WHERE token_hash = ?")
+            .bind(token_hash)
+            .execute(self.pool())
+            .await
+            .map_err(map_err)?;
+        Ok(())
+    }
+
+    async fn create_local_password_reset(
+        &self,
+        token_hash: &str,
+        user_id: &str,
+        valid_hours: i64,
+    ) -> Result<String, DbError> {
+        sqlx::query_scalar(
+            r#"
+            INSERT INTO local_password_resets (token_hash, user_id, expires_at)
+            VALUES (?, ?, datetime('now', '+' || ? || ' hours'))
+            RETURNING expires_at
+            "#,
+        )
+        .bind(token_hash)
+        .bind(user_id)
+        .bind(valid_hours)
+        .fetch_one(self.pool())
+        .await
+        .map_err(map_err)
+    }
+
+    async fn consume_local_password_reset(
+        &self,
+        token_hash: &str,
+    ) -> Result<Option<String>, DbError> {
+        let user_id: Option<String> = sqlx::query_scalar(
+            r#"
+            DELETE FROM local_password_resets
+            WHERE token_hash = ? AND expires_at > datetime('now')
+            RETURNING user_id
+            "#,
+        )
+        .bind(token_hash)
+        .fetch_optional(self.pool())
+        .await
+        .map_err(map_err)?;
+
+        // Drop expired tokens while we're here
+        sqlx::query("DELETE FROM local_password_resets WHERE expires_at <= datetime('now')")
+            .execute(self.pool())
+            .await
+            .map_err(map_err)?;
+        Ok(user_id)
+    }
+
+    async fn set_local_totp_secret(&self, user_id: &str, secret: &str) -> Result<(), DbError> {
+        sqlx::query(
+            r#"
+            UPDATE local_accounts SET totp_secret = ?, totp_enabled = 0, totp_last_step = NULL
+            WHERE user_id = ?
+            "#,
+        )
+        .bind(secret)
+        .bind(user_id)
+        .execute(self.pool())
+        .await
+        .map_err(map_err)?;
+        Ok(())
+    }
+
+    async fn enable_local_totp(
+        &self,
+        user_id: &str,
+        recovery_code_hashes: &[String],
+    ) -> Result<(), DbError> {
+        sqlx::query(
+            "UPDATE local_accounts SET totp_enabled = 1 WHERE user_id = ? AND totp_secret IS NOT NULL",
+        )
+        .bind(user_id)
+        .execute(self.pool())
+        .await
+        .map_err(map_err)?;
+        self.replace_local_recovery_codes(user_id, recovery_code_hashes)
+            .await
+    }
+
+    async fn disable_local_totp(&self, user_id: &str) -> Result<(), DbError> {
+        sqlx::query(
+            r#"
+            UPDATE local_accounts SET totp_secret = NULL, totp_enabled = 0, totp_last_step = NULL
+            WHERE user_id = ?
+            "#,
+        )
+        .bind(user_id)
+        .execute(self.pool())
+        .await
+        .map_err(map_err)?;
+        self.replace_local_recovery_codes(user_id, &[]).await
+    }
+
+    async fn accept_local_totp_step(&self, user_id: &str, step: i64) -> Result<bool, DbError> {
+        let result = sqlx::query(
+            r#"
+            UPDATE local_accounts SET totp_last_step = ?
+            WHERE user_id = ? AND (totp_last_step IS NULL OR totp_last_step < ?)
+            "#,
+        )
+        .bind(step)
+        .bind(user_id)
+        .bind(step)
+        .execute(self.pool())
+        .await
+        .map_err(map_err)?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn replace_local_recovery_codes(
+        &self,
+        user_id: &str,
+        code_hashes: &[String],
+    ) -> Result<(), DbError> {
+        let mut tx = self.pool().begin().await.map_err(map_err)?;
+        sqlx::query("DELETE FROM local_recovery_codes WHERE user_id = ?")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(map_err)?;
+        for hash in code_hashes {
+            sqlx::query("INSERT INTO local_recovery_codes (user_id, code_hash) VALUES (?, ?)")
+                .bind(user_id)
+                .bind(hash)
+                .execute(&mut *tx)
+                .await
+                .map_err(map_err)?;
+        }
+        tx.commit().await.map_err(map_err)?;
+        Ok(())
+    }
+
+    async fn consume_local_recovery_code(
+        &self,
+        user_id: &str,
+        code_hash: &str,
+    ) -> Result<bool, DbError> {
+        let result =
+            sqlx::query("DELETE FROM local_recovery_codes WHERE user_id = ? AND code_hash = ?")
+                .bind(user_id)
+                .bind(code_hash)
+                .execute(self.pool())
+                .await
+                .map_err(map_err)?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn count_local_recovery_codes(&self, user_id: &str) -> Result<i64, DbError> {
+        sqlx::query_scalar("SELECT COUNT(*) FROM local_recovery_codes WHERE user_id = ?")
+            .bind(user_id)
+            .fetch_one(self.pool())
+            .await
+            .map_err(map_err)
+    }
+
+    // ── Passkeys ──────────────────────────────────────────────────
+
+    async fn add_passkey(&self, passkey: &NewPasskeyCredential<'_>) -> Result<i64, DbError> {
+        sqlx::query_scalar(
+            r#"
+            INSERT INTO passkey_credentials (user_id, credential_id, public_key, sign_count, name)
+            VALUES (?, ?, ?, ?, ?)
+            RETURNING id
+            "#,
+        )
+        .bind(passkey.user_id)
+        .bind(passkey.credential_id)
+        .bind(passkey.public_key)
+        .bind(passkey.sign_count)
+        .bind(passkey.name)
+        .fetch_one(self.pool())
+        .await
+        .map_err(map_err)
+    }
+
+    async fn list_passkeys(&self, user_id: &str) -> Result<Vec<PasskeyCredential>, DbError> {
+        let sql = format!(
+            "SELECT {PASSKEY_COLUMNS} FROM passkey_credentials WHERE user_id = ? ORDER BY id"
+        );
+        let rows: Vec<PasskeySqlRow> = sqlx::query_as(&sql)
+            .bind(user_id)
+            .fetch_all(self.pool())
+            .await
+            .map_err(map_err)?;
+        Ok(rows.into_iter().map(Into::into).collect())
+    }
+
+    async fn get_passkey_by_credential_id(
+        &self,
+        credential_id: &str,
+    ) -> Result<Option<PasskeyCredential>, DbError> {
+        let sql =
+            format!("SELECT {PASSKEY_COLUMNS} FROM passkey_credentials WHERE credential_id = ?");
+        let row: Option<PasskeySqlRow> = sqlx::query_as(&sql)
+            .bind(credential_id)
+            .fetch_optional(self.pool())
+            .await
+            .map_err(map_err)?;
+        Ok(row.map(Into::into))
+    }
+
+    async fn record_passkey_use(&self, id: i64, sign_count: i64) -> Result<(), DbError> {
+        sqlx::query(
+            "UPDATE passkey_credentials SET sign_count = ?, last_used_at = datetime('now') WHERE id = ?",
+        )
+        .bind(sign_count)
+        .bind(id)
+        .execute(self.pool())
+        .await
+        .map_err(map_err)?;
+        Ok(())
+    }
+
+    async fn rename_passkey(&self, user_id: &str, id: i64, name: &str) -> Result<bool, DbError> {
+        let result =
+            sqlx::query("UPDATE passkey_credentials SET name = ? WHERE id = ? AND user_id = ?")
+                .bind(name)
+                .bind(id)
+                .bind(user_id)
+                .execute(self.pool())
+                .await
+                .map_err(map_err)?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn delete_passkey(&self, user_id: &str, id: i64) -> Result<bool, DbError> {
+        let result = sqlx::query("DELETE FROM passkey_credentials WHERE id = ? AND user_id = ?")
+            .bind(id)
+            .bind(user_id)
+            .execute(self.pool())
+            .await
+            .map_err(map_err)?;
+        Ok(result.rows_affected() > 0)
+    }
 }
@@ -54,8 +54,8 @@ impl VaultRepository for SqliteDatabase {
     }
 
     async fn list_user_vaults(&self, user_id: &str) -> Result<Vec<StorageVault>, DbError> {
-        let rows: Vec<(String, String, String, i32, String)> = sqlx::query_as(
-            "SELECT vault_id, user_id, vault_name, is_default, created_at \
+        let rows: Vec<(String, String, String, i32, i32, String)> = sqlx::query_as(
+            "SELECT vault_id, user_id, vault_name, is_default, strip_exif, created_at \
              FROM storage_vaults WHERE user_id = ? \
              ORDER BY is_default DESC, created_at ASC",
         )
@@ -66,13 +66,16 @@ impl VaultRepository for SqliteDatabase {
 
         Ok(rows
             .into_iter()
-            .map(|(vault_id, user_id, vault_name, is_default, created_at)| StorageVault {
-                vault_id,
-                user_id,
-                vault_name,
-                is_default: is_default != 0,
-                created_at,
-            })
+            .map(
+                |(vault_id, user_id, vault_name, is_default, strip_exif, created_at)| StorageVault {
+                    vault_id,
+                    user_id,
+                    vault_name,
+                    is_default: is_default != 0,
+                    strip_exif: strip_exif != 0,
+                    created_at,
+                },
+            )
             .collect())
     }
 
@@ -108,6 +111,28 @@ impl VaultRepository for SqliteDatabase {
         Ok(())
     }
 
+    async fn get_vault_strip_exif(&self, vault_id: &str) -> Result<bool, DbError> {
+        let strip: Option<i32> =
+            sqlx::query_scalar("SELECT strip_exif FROM storage_vaults WHERE vault_id = ?")
+                .bind(vault_id)
+                .fetch_optional(self.pool())
+                .await
+                .map_err(map_err)?;
+        Ok(strip != Some(0))
+    }
+
+    async fn set_vault_strip_exif(&self, vault_id: &str, strip: bool) -> Result<(), DbError> {
+        sqlx::query(
+            "UPDATE storage_vaults SET strip_exif = ?, updated_at = datetime('now') WHERE vault_id = ?",
+        )
+        .bind(if strip { 1 } else { 0 })
+        .bind(vault_id)
+        .execute(self.pool())
+        .await
+        .map_err(map_err)?;
+        Ok(())
+    }
+
     async fn set_default_vault(&self, user_id: &str, vault_id: &str) -> Result<(), DbError> {
         let mut tx = self.pool().begin().await.map_err(map_err)?;
 
@@ -12,6 +12,38 @@ fn map_err(e: sqlx::Error) -> DbError {
     }
 }
 
+#[derive(sqlx::FromRow)]
+struct WorkspaceTrashSqlRow {
+    id: i64,
+    workspace_id: String,
+    original_path: String,
+    trash_name: String,
+    is_dir: bool,
+    size_bytes: i64,
+    folder_config: Option<String>,
+    deleted_by: Option<String>,
+    deleted_at: String,
+}
+
+impl From<WorkspaceTrashSqlRow> for WorkspaceTrashEntry {
+    fn from(r: WorkspaceTrashSqlRow) -> Self {
+        Self {
+            id: r.id,
+            workspace_id: r.workspace_id,
+            original_path: r.original_path,
+            trash_name: r.trash_name,
+            is_dir: r.is_dir,
+            size_bytes: r.size_bytes,
+            folder_config: r.folder_config,
+            deleted_by: r.deleted_by,
+            deleted_at: r.deleted_at,
+        }
+    }
+}
+
+const TRASH_COLUMNS: &str = "id, workspace_id, original_path, trash_name, is_dir, size_bytes, \
+     folder_config, deleted_by, deleted_at";
+
 #[async_trait::async_trait]
 impl WorkspaceRepository for SqliteDatabase {
     // ── Workspace CRUD ───────────────────────────────────────────
@@ -991,4 +1023,83 @@ impl WorkspaceRepository for SqliteDatabase {
         .map_err(map_err)?;
         Ok(code)
     }
+
+    // ── Workspace trash ──────────────────────────────────────────
+
+    async fn insert_trash_entry(&self, entry: &NewWorkspaceTrashEntry<'_>) -> Result<i64, DbError> {
+        let result = sqlx::query(
+            "INSERT INTO workspace_trash \
+             (workspace_id, original_path, trash_name, is_dir, size_bytes, folder_config, deleted_by) \
+             VALUES (?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(entry.workspace_id)
+        .bind(entry.original_path)
+        .bind(entry.trash_name)
+        .bind(entry.is_dir)
+        .bind(entry.size_bytes)
+        .bind(entry.folder_config)
+        .bind(entry.deleted_by)
+        .execute(self.pool())
+        .await
+        .map_err(map_err)?;
+        Ok(result.last_insert_rowid())
+    }
+
+    async fn list_trash_entries(
+        &self,
+        workspace_id: &str,
+    ) -> Result<Vec<WorkspaceTrashEntry>, DbError> {
+        let rows: Vec<WorkspaceTrashSqlRow> = sqlx::query_as(&format!(
+            "SELECT {} FROM workspace_trash WHERE workspace_id = ? \
+             ORDER BY deleted_at DESC, id DESC",
+            TRASH_COLUMNS
+        ))
+        .bind(workspace_id)
+        .fetch_all(self.pool())
+        .await
+        .map_err(map_err)?;
+        Ok(rows.into_iter().map(Into::into).collect())
+    }
+
+    async fn get_trash_entry(
+        &self,
+        workspace_id: &str,
+        id: i64,
+    ) -> Result<Option<WorkspaceTrashEntry>, DbError> {
+        let row: Option<WorkspaceTrashSqlRow> = sqlx::query_as(&format!(
+            "SELECT {} FROM workspace_trash WHERE workspace_id = ? AND id = ?",
+            TRASH_COLUMNS
+        ))
+        .bind(workspace_id)
+        .bind(id)
+        .fetch_optional(self.pool())
+        .await
+        .map_err(map_err)?;
+        Ok(row.map(Into::into))
+    }
+
+    async fn delete_trash_entry(&self, id: i64) -> Result<(), DbError> {
+        sqlx::query("DELETE FROM workspace_trash WHERE id = ?")
+            .bind(id)
+            .execute(self.pool())
+            .await
+            .map_err(map_err)?;
+        Ok(())
+    }
+
+    async fn list_expired_trash_entries(
+        &self,
+        retention_days: i64,
+    ) -> Result<Vec<WorkspaceTrashEntry>, DbError> {
+        let rows: Vec<WorkspaceTrashSqlRow> = sqlx::query_as(&format!(
+            "SELECT {} FROM workspace_trash WHERE deleted_at < datetime('now', ?) \
+             ORDER BY deleted_at",
+            TRASH_COLUMNS
+        ))
+        .bind(format!("-{} days", retention_days))
+        .fetch_all(self.pool())
+        .await
+        .map_err(map_err)?;
+        Ok(rows.into_iter().map(Into::into).collect())
+    }
 }
@@ -15,6 +15,47 @@ pub struct AccessCode {
     pub vault_id: Option<String>,
     pub is_active: bool,
     pub current_downloads: i64,
+    pub has_passphrase: bool,
+    pub max_uses: Option<i64>,
+    pub current_uses: i64,
+    pub max_views: Option<i64>,
+    pub current_views: i64,
+    /// Newline-separated IP addresses or CIDR ranges.
+    pub allowed_ips: Option<String>,
+    /// Newline-separated referrer host names.
+    pub allowed_referrers: Option<String>,
+    pub burn_after_claim: bool,
+}
+
+impl AccessCode {
+    /// Whether the code must be claimed on the landing page before its items
+    /// can be opened.
+    pub fn requires_claim(&self) -> bool {
+        self.has_passphrase
+            || self.burn_after_claim
+            || self.max_uses.is_some()
+            || self.allowed_referrers.is_some()
+    }
+}
+
+/// Optional restrictions set when creating an access code.
+#[derive(Debug, Clone, Default)]
+pub struct AccessCodeRestrictions<'a> {
+    pub passphrase_hash: Option<&'a str>,
+    pub max_uses: Option<i64>,
+    pub max_views: Option<i64>,
+    pub allowed_ips: Option<&'a str>,
+    pub allowed_referrers: Option<&'a str>,
+    pub burn_after_claim: bool,
+}
+
+/// Request metadata recorded when an access code is claimed.
+#[derive(Debug, Clone)]
+pub struct NewAccessCodeClaim<'a> {
+    pub token_hash: &'a str,
+    pub ip_address: Option<&'a str>,
+    pub user_agent: Option<&'a str>,
+    pub referer: Option<&'a str>,
 }
 
 /// A permission granted by an access code.
@@ -38,6 +79,7 @@ pub trait AccessCodeRepository: Send + Sync {
         expires_at: Option<&str>,
         created_by: &str,
         vault_id: Option<&str>,
+        restrictions: &AccessCodeRestrictions<'_>,
     ) -> Result<i32, DbError>;
 
     /// Get an access code by code string + owner.
@@ -59,6 +101,16 @@ pub trait AccessCodeRepository: Send + Sync {
     /// Get the code ID if it belongs to the user. Returns None if not found/not owned.
     async fn get_code_id_for_user(&self, code: &str, user_id: &str) -> Result<Option<i32>, DbError>;
 
+    // ── Claims ──────────────────────────────────────────────────────
+
+    /// Get the passphrase hash of a code, if it has one.
+    async fn get_passphrase_hash(&self, code_id: i32) -> Result<Option<String>, DbError>;
+
+    /// Claim a code: count a use and store the claim token, unless the use
+    /// limit is reached or the code already burned. Returns false in that case.
+    async fn claim_code(&self, code_id: i32, claim: &NewAccessCodeClaim<'_>)
+        -> Result<bool, DbError>;
+
     // ── Permissions ─────────────────────────────────────────────────
 
     /// Add a permission to an access code. Ignores duplicates.
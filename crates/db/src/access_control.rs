@@ -18,6 +18,14 @@ pub struct AccessKeyRow {
     pub max_downloads: Option<i32>,
     pub current_downloads: i32,
     pub is_active: bool,
+    pub vault_id: Option<String>,
+    pub has_passphrase: bool,
+    pub max_uses: Option<i32>,
+    pub max_views: Option<i32>,
+    pub current_views: i32,
+    pub allowed_ips: Option<String>,
+    pub allowed_referrers: Option<String>,
+    pub burn_after_claim: bool,
 }
 
 /// Audit log entry from the database.
@@ -122,6 +130,24 @@ pub trait AccessControlRepository: Send + Sync {
     /// Increment download count for an access key.
     async fn increment_download_count(&self, key: &str) -> Result<(), DbError>;
 
+    /// Increment the view count for an access key, unless its view limit is
+    /// reached. Returns false in that case.
+    async fn increment_view_count(&self, key: &str) -> Result<bool, DbError>;
+
+    /// Check if a claim token (SHA-256 hex) was issued for an access code.
+    async fn access_code_claim_exists(
+        &self,
+        access_code_id: i32,
+        token_hash: &str,
+    ) -> Result<bool, DbError>;
+
+    /// Get the vault a resource is stored in. Returns `None` if not found.
+    async fn get_resource_vault(
+        &self,
+        resource_type: &str,
+        resource_id: i32,
+    ) -> Result<Option<String>, DbError>;
+
     /// Check if a resource exists.
     async fn resource_exists(
         &self,
@@ -177,6 +203,13 @@ pub trait AuditRepository: Send + Sync {
         limit: i32,
     ) -> Result<Vec<AuditLogRow>, DbError>;
 
+    /// Get audit log entries recorded for an access key, ordered by created_at DESC.
+    async fn get_access_key_audit_log(
+        &self,
+        access_key: &str,
+        limit: i32,
+    ) -> Result<Vec<AuditLogRow>, DbError>;
+
     /// Get denied access attempts since a given ISO-8601 timestamp.
     async fn get_denied_attempts(
         &self,
@@ -22,6 +22,14 @@ pub struct ApiKey {
     pub expires_at: Option<String>,
     pub created_at: String,
     pub is_active: bool,
+    /// Workspace the key is bound to
+    pub workspace_id: Option<String>,
+    /// Vault the key is bound to
+    pub vault_id: Option<String>,
+    /// Folder within the bound workspace the key is limited to
+    pub path_prefix: Option<String>,
+    /// Newline-separated IP addresses or CIDR ranges the key works from
+    pub allowed_ips: Option<String>,
 }
 
 impl ApiKey {
@@ -45,6 +53,79 @@ impl ApiKey {
     pub fn is_valid(&self) -> bool {
         self.is_active && !self.is_expired()
     }
+
+    /// Whether the key is bound to a workspace, vault or folder
+    pub fn is_bound(&self) -> bool {
+        self.workspace_id.is_some() || self.vault_id.is_some() || self.path_prefix.is_some()
+    }
+
+    /// Whether the key's bindings cover a resource.
+    ///
+    /// Every binding must be matched by the resource; an unbound key covers
+    /// everything.
+    pub fn allows_resource(&self, resource: &ResourceRef) -> bool {
+        let matches = |binding: &Option<String>, value: &Option<String>| match binding {
+            Some(bound) => value.as_ref() == Some(bound),
+            None => true,
+        };
+        matches(&self.workspace_id, &resource.workspace_id)
+            && matches(&self.vault_id, &resource.vault_id)
+            && match &self.path_prefix {
+                Some(prefix) => resource
+                    .path
+                    .as_deref()
+                    .is_some_and(|path| path_within(path, prefix)),
+                None => true,
+            }
+    }
+}
+
+/// The resource a request acts on, as far as it is known.
+///
+/// Checked against the bindings of an API key with [`ApiKey::allows_resource`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ResourceRef {
+    pub workspace_id: Option<String>,
+    pub vault_id: Option<String>,
+    /// Workspace-relative path
+    pub path: Option<String>,
+}
+
+impl ResourceRef {
+    pub fn workspace(workspace_id: impl Into<String>) -> Self {
+        Self {
+            workspace_id: Some(workspace_id.into()),
+            ..Self::default()
+        }
+    }
+
+    pub fn vault(vault_id: impl Into<String>) -> Self {
+        Self {
+            vault_id: Some(vault_id.into()),
+            ..Self::default()
+        }
+    }
+
+    pub fn with_path(mut self, path: impl Into<String>) -> Self {
+        self.path = Some(path.into());
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.workspace_id.is_none() && self.vault_id.is_none() && self.path.is_none()
+    }
+}
+
+/// Whether a workspace-relative path lies within a folder (or is the folder).
+///
+/// Paths containing `..` are never within a folder.
+pub fn path_within(path: &str, folder: &str) -> bool {
+    let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty() && *s != ".").collect();
+    if segments.contains(&"..") {
+        return false;
+    }
+    let folder: Vec<&str> = folder.split('/').filter(|s| !s.is_empty()).collect();
+    segments.starts_with(&folder)
 }
 
 /// Request to create a new API key.
@@ -54,6 +135,15 @@ pub struct CreateApiKeyRequest {
     pub description: Option<String>,
     pub scopes: Vec<String>,
     pub expires_at: Option<String>,
+    /// Bind the key to a workspace
+    pub workspace_id: Option<String>,
+    /// Bind the key to a vault
+    pub vault_id: Option<String>,
+    /// Limit a workspace-bound key to a folder
+    pub path_prefix: Option<String>,
+    /// IP addresses or CIDR ranges the key works from
+    #[serde(default)]
+    pub allowed_ips: Vec<String>,
 }
 
 /// Response when creating an API key (includes full key — only shown once).
@@ -0,0 +1,118 @@
+//! Transcoding job queue — types and repository trait.
+//!
+//! Video uploads enqueue a job instead of spawning ffmpeg directly. A bounded
+//! pool of workers claims jobs in priority order, so a burst of uploads is
+//! processed a few at a time and survives a server restart.
+
+use serde::{Deserialize, Serialize};
+
+use crate::DbError;
+
+// ============================================================================
+// Domain types
+// ============================================================================
+
+/// Job is waiting for a free worker.
+pub const JOB_STATUS_QUEUED: &str = "queued";
+/// Job has been claimed by a worker.
+pub const JOB_STATUS_RUNNING: &str = "running";
+/// Job finished successfully.
+pub const JOB_STATUS_COMPLETED: &str = "completed";
+/// Job failed and will not be retried.
+pub const JOB_STATUS_FAILED: &str = "failed";
+/// Job was cancelled by its owner before a worker picked it up.
+pub const JOB_STATUS_CANCELLED: &str = "cancelled";
+
+/// A transcoding job stored in the `transcode_jobs` table.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranscodeJob {
+    pub id: i64,
+    /// Upload ID used as the progress-tracker key while the job runs.
+    pub upload_id: String,
+    pub slug: String,
+    pub vault_id: String,
+    pub user_id: Option<String>,
+    /// Absolute path of the uploaded source file awaiting processing.
+    pub source_path: String,
+    pub original_filename: String,
+    pub is_public: bool,
+    /// Higher values are claimed first; ties are broken by age.
+    pub priority: i64,
+    pub status: String,
+    pub attempts: i64,
+    pub max_attempts: i64,
+    pub error: Option<String>,
+    pub created_at: String,
+    pub started_at: Option<String>,
+    pub finished_at: Option<String>,
+    pub updated_at: String,
+}
+
+/// Request to enqueue a new transcoding job.
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreateTranscodeJob {
+    pub upload_id: String,
+    pub slug: String,
+    pub vault_id: String,
+    pub user_id: Option<String>,
+    pub source_path: String,
+    pub original_filename: String,
+    pub is_public: bool,
+    #[serde(default)]
+    pub priority: i64,
+    #[serde(default = "default_max_attempts")]
+    pub max_attempts: i64,
+}
+
+/// Filter for listing jobs.
+#[derive(Debug, Clone, Default)]
+pub struct JobListFilter {
+    /// Restrict to jobs owned by this user (`None` = all users).
+    pub user_id: Option<String>,
+    pub status: Option<String>,
+    pub limit: i64,
+}
+
+fn default_max_attempts() -> i64 {
+    3
+}
+
+// ============================================================================
+// Repository trait
+// ============================================================================
+
+/// Transcoding job queue repository.
+#[async_trait::async_trait]
+pub trait JobRepository: Send + Sync {
+    /// Enqueue a job, returning its row ID.
+    async fn enqueue_job(&self, job: &CreateTranscodeJob) -> Result<i64, DbError>;
+
+    /// Atomically claim the highest-priority queued job and mark it running.
+    /// Returns `None` when the queue is empty.
+    async fn claim_next_job(&self) -> Result<Option<TranscodeJob>, DbError>;
+
+    /// Get a job by ID.
+    async fn get_job(&self, id: i64) -> Result<Option<TranscodeJob>, DbError>;
+
+    /// Get the most recent job for a media slug.
+    async fn get_latest_job_for_slug(&self, slug: &str) -> Result<Option<TranscodeJob>, DbError>;
+
+    /// List jobs, newest first.
+    async fn list_jobs(&self, filter: &JobListFilter) -> Result<Vec<TranscodeJob>, DbError>;
+
+    /// Mark a running job as completed.
+    async fn complete_job(&self, id: i64) -> Result<bool, DbError>;
+
+    /// Record a failure. The job goes back to `queued` while attempts remain,
+    /// otherwise it is marked `failed`. Returns the resulting status.
+    async fn fail_job(&self, id: i64, error: &str) -> Result<Option<String>, DbError>;
+
+    /// Cancel a queued job owned by `user_id`. Returns false if the job is not
+    /// found, not owned by the user, or already picked up by a worker.
+    async fn cancel_job(&self, id: i64, user_id: &str) -> Result<bool, DbError>;
+
+    /// Put jobs left in `running` by a previous process back into the queue.
+    /// Jobs that were on their last attempt are marked failed instead.
+    /// Called once at startup; returns the number of jobs requeued.
+    async fn requeue_interrupted_jobs(&self) -> Result<u64, DbError>;
+}
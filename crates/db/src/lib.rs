@@ -12,6 +12,7 @@ pub mod api_keys;
 pub mod error;
 pub mod federation;
 pub mod git_providers;
+pub mod jobs;
 pub mod llm_providers;
 pub mod media;
 pub mod processes;
@@ -67,6 +67,10 @@ pub struct MediaInsert {
     pub allow_comments: i32,
     pub mature_content: i32,
     pub tenant_id: String,
+    /// SHA-256 of the uploaded bytes (deduplication key within a vault)
+    pub content_hash: Option<String>,
+    /// SHA-256 of the stored primary file (integrity baseline)
+    pub file_hash: Option<String>,
 }
 
 // ── Filter / search types ───────────────────────────────────────────
@@ -80,15 +84,47 @@ pub struct MediaSearchFilter {
     pub vault_id: Option<String>,
     pub tag: Option<String>,
     pub group_id: Option<String>,
+    /// Substring of the camera make or model (images)
+    pub camera: Option<String>,
+    /// Earliest capture time, `YYYY-MM-DD[ HH:MM:SS]` (images)
+    pub captured_after: Option<String>,
+    /// Latest capture time, `YYYY-MM-DD[ HH:MM:SS]` (images)
+    pub captured_before: Option<String>,
+    /// `created_at`, `title`, `file_size`, `view_count`, `updated_at` or
+    /// `relevance` (best full-text match first; newest first without `search`)
     pub sort_by: String,
     pub sort_order: String,
     pub tenant_id: Option<String>,
+    /// Page size; `None` returns all matching rows
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+/// Marks the start of a matched term in [`MediaSearchHit::snippet`].
+///
+/// Private-use characters rather than HTML, so snippets can be escaped
+/// before the markers are turned into highlighting.
+pub const SNIPPET_MATCH_START: char = '\u{E000}';
+
+/// Marks the end of a matched term in [`MediaSearchHit::snippet`].
+pub const SNIPPET_MATCH_END: char = '\u{E001}';
+
+/// A row returned by [`MediaRepository::search_media`].
+#[derive(Debug, Clone)]
+pub struct MediaSearchHit {
+    pub item: MediaItemRow,
+    /// Excerpt of the best matching field with matched terms wrapped in
+    /// [`SNIPPET_MATCH_START`]/[`SNIPPET_MATCH_END`]; only set for full-text searches
+    pub snippet: Option<String>,
+    /// Relevance score (lower is better); only set for full-text searches
+    pub score: Option<f64>,
 }
 
 #[derive(Debug, Clone, Default, Serialize)]
 pub struct MediaTypeCounts {
     pub videos: i64,
     pub images: i64,
+    pub audio: i64,
     pub documents: i64,
     pub total: i64,
 }
@@ -99,10 +135,14 @@ pub struct MediaTypeCounts {
 pub struct ImageServingInfo {
     pub id: i32,
     pub filename: String,
+    /// Name of the uploaded file (its extension names the kept original)
+    pub original_filename: Option<String>,
     pub user_id: Option<String>,
     pub vault_id: Option<String>,
     pub is_public: i32,
     pub mime_type: String,
+    /// SHA-256 of the stored file, changes when the file is replaced
+    pub file_hash: Option<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -124,6 +164,16 @@ pub struct VideoServingInfo {
     pub is_public: i32,
 }
 
+#[derive(Debug, Clone)]
+pub struct AudioServingInfo {
+    pub id: i32,
+    pub user_id: Option<String>,
+    pub vault_id: Option<String>,
+    pub filename: String,
+    pub mime_type: String,
+    pub is_public: i32,
+}
+
 /// For markdown/BPMN/PDF viewing pages.
 #[derive(Debug, Clone)]
 pub struct DocumentViewInfo {
@@ -290,6 +340,347 @@ pub struct PublicCatalogRow {
     pub file_size: Option<i64>,
     pub created_at: String,
     pub updated_at: Option<String>,
+    /// Languages of the subtitle tracks attached to the item
+    pub subtitle_languages: Vec<String>,
+}
+
+// ── Subtitles ───────────────────────────────────────────────────────
+
+/// A subtitle/caption track attached to a video.
+#[derive(Debug, Clone, Serialize)]
+pub struct SubtitleTrack {
+    pub id: i64,
+    pub media_id: i64,
+    /// BCP 47 language tag, also the file stem under `{slug}/subtitles/`
+    pub language: String,
+    /// Human-readable name shown in the player menu
+    pub label: String,
+    pub is_default: bool,
+    /// Generated from the video's transcript rather than uploaded
+    pub auto_generated: bool,
+    pub created_at: String,
+}
+
+// ── Chapters ────────────────────────────────────────────────────────
+
+/// A named chapter of a video.
+#[derive(Debug, Clone, Serialize)]
+pub struct VideoChapter {
+    pub id: i64,
+    pub media_id: i64,
+    /// Start offset in seconds
+    pub start_time: f64,
+    /// End offset in seconds; `None` runs until the next chapter (or the
+    /// end of the video)
+    pub end_time: Option<f64>,
+    pub title: String,
+}
+
+/// A chapter as submitted by the editor.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NewChapter {
+    pub start_time: f64,
+    #[serde(default)]
+    pub end_time: Option<f64>,
+    pub title: String,
+}
+
+// ── Transcripts ─────────────────────────────────────────────────────
+
+/// Transcript status while the ASR backend is running.
+pub const TRANSCRIPT_PENDING: &str = "pending";
+/// Transcript status once segments are stored.
+pub const TRANSCRIPT_COMPLETE: &str = "complete";
+/// Transcript status after the ASR backend failed.
+pub const TRANSCRIPT_FAILED: &str = "failed";
+
+/// Speech-to-text transcript state of a video.
+#[derive(Debug, Clone, Serialize)]
+pub struct MediaTranscript {
+    pub media_id: i64,
+    /// One of the `TRANSCRIPT_*` statuses
+    pub status: String,
+    /// BCP 47 tag of the spoken language, once known
+    pub language: Option<String>,
+    /// ASR backend that produced (or is producing) the transcript
+    pub backend: String,
+    pub error: Option<String>,
+    pub updated_at: String,
+}
+
+/// A timestamped piece of a transcript.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TranscriptSegment {
+    /// Start offset in seconds
+    pub start_time: f64,
+    /// End offset in seconds
+    pub end_time: f64,
+    pub text: String,
+}
+
+// ── Live sessions ───────────────────────────────────────────────────
+
+/// Live session status while the stream is being recorded.
+pub const LIVE_SESSION_LIVE: &str = "live";
+/// Live session status once the recording was handed to the transcoding queue.
+pub const LIVE_SESSION_ENDED: &str = "ended";
+/// Live session status when nothing usable was recorded.
+pub const LIVE_SESSION_FAILED: &str = "failed";
+
+/// Media item status while its live stream is on air.
+pub const MEDIA_STATUS_LIVE: &str = "live";
+
+/// A live stream recorded into a media item.
+#[derive(Debug, Clone, Serialize)]
+pub struct LiveSession {
+    pub id: i64,
+    pub media_id: i64,
+    pub slug: String,
+    /// MediaMTX path the stream was published to
+    pub stream_path: String,
+    #[serde(skip_serializing)]
+    pub recording_path: String,
+    /// One of the `LIVE_SESSION_*` statuses
+    pub status: String,
+    pub started_at: String,
+    pub ended_at: Option<String>,
+    /// Most viewers watching at the same time
+    pub peak_viewers: i64,
+    /// Distinct viewers over the whole session
+    pub total_viewers: i64,
+}
+
+// ── Live streams ────────────────────────────────────────────────────
+
+/// Only the owner may watch.
+pub const LIVE_ACCESS_PRIVATE: &str = "private";
+/// Anyone may watch.
+pub const LIVE_ACCESS_PUBLIC: &str = "public";
+/// Viewers need the stream's access code.
+pub const LIVE_ACCESS_CODE: &str = "code";
+/// Members of the stream's group may watch.
+pub const LIVE_ACCESS_GROUP: &str = "group";
+
+/// A stream definition with its own MediaMTX path and publish key.
+#[derive(Debug, Clone, Serialize)]
+pub struct LiveStream {
+    pub id: i64,
+    pub user_id: String,
+    pub name: String,
+    /// MediaMTX path, also the slug under `/hls/`
+    pub stream_path: String,
+    #[serde(skip_serializing)]
+    pub key_hash: String,
+    /// First characters of the publish key, for display
+    pub key_prefix: String,
+    pub key_rotated_at: String,
+    pub expires_at: Option<String>,
+    /// Whether `expires_at` has passed
+    pub expired: bool,
+    /// One of the `LIVE_ACCESS_*` values
+    pub access: String,
+    pub access_code: Option<String>,
+    pub group_id: Option<i32>,
+    /// Record sessions into the owner's media library
+    pub record: bool,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// Owner-editable settings of a stream definition.
+#[derive(Debug, Clone)]
+pub struct LiveStreamSettings {
+    pub name: String,
+    /// `YYYY-MM-DD HH:MM:SS` (UTC)
+    pub expires_at: Option<String>,
+    pub access: String,
+    pub access_code: Option<String>,
+    pub group_id: Option<i32>,
+    pub record: bool,
+}
+
+// ── Full-text index ─────────────────────────────────────────────────
+
+/// A document whose text has not been indexed yet.
+#[derive(Debug, Clone)]
+pub struct UnindexedDocument {
+    pub id: i32,
+    pub filename: String,
+    pub mime_type: String,
+    pub vault_id: Option<String>,
+}
+
+// ── Integrity ───────────────────────────────────────────────────────
+
+/// Verification status of an item whose stored bytes match its hash.
+pub const INTEGRITY_OK: &str = "ok";
+/// Verification status of an item whose stored bytes changed.
+pub const INTEGRITY_MISMATCH: &str = "mismatch";
+/// Verification status of an item whose stored file is gone.
+pub const INTEGRITY_MISSING: &str = "missing";
+
+/// The stored primary file of an active media item.
+#[derive(Debug, Clone)]
+pub struct IntegrityCandidate {
+    pub id: i32,
+    pub slug: String,
+    pub media_type: String,
+    pub filename: String,
+    pub vault_id: String,
+    /// Expected SHA-256; `None` for items stored before hashing existed
+    pub file_hash: Option<String>,
+}
+
+/// A media item whose last verification failed.
+#[derive(Debug, Clone, Serialize)]
+pub struct IntegrityIssue {
+    pub id: i32,
+    pub slug: String,
+    pub title: String,
+    pub media_type: String,
+    pub vault_id: Option<String>,
+    pub file_hash: Option<String>,
+    /// `mismatch` or `missing`
+    pub status: String,
+    pub checked_at: Option<String>,
+}
+
+/// Verification status counts over active media items.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct IntegritySummary {
+    pub total: i64,
+    pub ok: i64,
+    pub mismatch: i64,
+    pub missing: i64,
+    /// Items never verified
+    pub unchecked: i64,
+    pub last_checked_at: Option<String>,
+}
+
+// ── Audio ───────────────────────────────────────────────────────────
+
+/// Probed technical details of an audio item (`media_audio` row).
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct AudioDetails {
+    pub media_id: i64,
+    /// Duration in seconds
+    pub duration: Option<f64>,
+    /// Bitrate of the original in bits per second
+    pub bitrate: Option<i64>,
+    pub channels: Option<i64>,
+    /// Sample rate in Hz
+    pub sample_rate: Option<i64>,
+    pub codec: Option<String>,
+}
+
+// ── Photos ──────────────────────────────────────────────────────────
+
+/// Capture metadata of an image read from its EXIF (`media_photo` row).
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PhotoDetails {
+    pub media_id: i64,
+    /// `YYYY-MM-DD HH:MM:SS`, camera local time
+    pub captured_at: Option<String>,
+    pub camera_make: Option<String>,
+    pub camera_model: Option<String>,
+    pub lens_model: Option<String>,
+    pub gps_latitude: Option<f64>,
+    pub gps_longitude: Option<f64>,
+}
+
+/// An image with a recorded capture location, for map views.
+#[derive(Debug, Clone, Serialize)]
+pub struct GeotaggedImage {
+    pub slug: String,
+    pub title: String,
+    pub latitude: f64,
+    pub longitude: f64,
+    pub captured_at: Option<String>,
+    pub vault_id: Option<String>,
+}
+
+// ── Trash ───────────────────────────────────────────────────────────
+
+/// A media item in the trash (`media_items.deleted_at` set).
+#[derive(Debug, Clone, Serialize)]
+pub struct TrashedMedia {
+    pub id: i32,
+    pub slug: String,
+    pub title: String,
+    pub media_type: String,
+    pub filename: String,
+    pub file_size: i64,
+    pub vault_id: Option<String>,
+    pub deleted_at: String,
+}
+
+// ── Versions ────────────────────────────────────────────────────────
+
+/// One version of a media item's file: an archived one, or the one
+/// currently served.
+#[derive(Debug, Clone, Serialize)]
+pub struct MediaVersion {
+    pub media_id: i32,
+    pub version: i64,
+    pub filename: String,
+    pub original_filename: Option<String>,
+    pub mime_type: String,
+    pub file_size: i64,
+    pub video_type: Option<String>,
+    pub thumbnail_url: Option<String>,
+    pub content_hash: Option<String>,
+    pub file_hash: Option<String>,
+    /// Whether this is the version currently served
+    pub current: bool,
+    /// Who replaced the version (None for the current one)
+    pub archived_by: Option<String>,
+    pub comment: Option<String>,
+    /// When the version was replaced (None for the current one)
+    pub archived_at: Option<String>,
+}
+
+// ── Batch operations ────────────────────────────────────────────────
+
+/// Change applied to every selected item by [`MediaRepository::apply_media_batch`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum MediaBatchOperation {
+    AddTags { tags: Vec<String> },
+    RemoveTags { tags: Vec<String> },
+    /// `None` clears the access group
+    SetGroup { group_id: Option<i32> },
+    SetVisibility { is_public: bool },
+    /// Target vault; the caller must have checked that the user owns it
+    MoveToVault { vault_id: String },
+    /// Move to the trash
+    Delete,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MediaBatchStatus {
+    /// Changed (or would be, in a dry run)
+    Applied,
+    /// Already in the requested state
+    Unchanged,
+    /// No such slug owned by the user
+    NotFound,
+}
+
+/// Per-item outcome of a batch operation.
+#[derive(Debug, Clone, Serialize)]
+pub struct MediaBatchItemResult {
+    pub slug: String,
+    pub status: MediaBatchStatus,
+    pub media_type: Option<String>,
+    /// Vault the item was in before the batch
+    pub vault_id: Option<String>,
+    /// Stored filename, for moving or removing files after the batch
+    #[serde(skip)]
+    pub filename: Option<String>,
+    /// Set when the database change succeeded but a follow-up step failed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
 }
 
 // ── Repository trait ────────────────────────────────────────────────
@@ -298,11 +689,14 @@ pub struct PublicCatalogRow {
 pub trait MediaRepository: Send + Sync {
     // ── Search & list ─────────────────────────────────────────────
 
-    /// Search media_items with dynamic filters. Returns full rows.
+    /// Search media_items with dynamic filters, paginated by `limit`/`offset`.
+    ///
+    /// `filter.search` is matched against the full-text index of titles,
+    /// descriptions, categories, tags and document text.
     async fn search_media(
         &self,
         filter: &MediaSearchFilter,
-    ) -> Result<Vec<MediaItemRow>, DbError>;
+    ) -> Result<Vec<MediaSearchHit>, DbError>;
 
     /// Count media items of a specific type, applying the same filters as search.
     async fn count_media_by_type(
@@ -363,6 +757,12 @@ pub trait MediaRepository: Send + Sync {
 
     async fn increment_view_count(&self, id: i32) -> Result<(), DbError>;
 
+    // ── Downloads ─────────────────────────────────────────────────
+
+    /// Whether the owner allows downloading the item's original file.
+    /// False when the item does not exist.
+    async fn media_allows_download(&self, id: i32) -> Result<bool, DbError>;
+
     // ── Status ────────────────────────────────────────────────────
 
     /// Get processing status for a media item.
@@ -445,6 +845,12 @@ pub trait MediaRepository: Send + Sync {
         slug: &str,
     ) -> Result<Option<VideoServingInfo>, DbError>;
 
+    /// For audio rendition and waveform serving (minimal fields).
+    async fn get_audio_for_serving(
+        &self,
+        slug: &str,
+    ) -> Result<Option<AudioServingInfo>, DbError>;
+
     /// For PDF/BPMN file serving (minimal fields).
     async fn get_document_for_serving(
         &self,
@@ -481,6 +887,9 @@ pub trait MediaRepository: Send + Sync {
     // ── Access code checks (serving) ──────────────────────────────
 
     /// Check if a legacy access code grants access to a vault.
+    ///
+    /// Only unrestricted codes match; codes with a passphrase, limits or
+    /// allowlists are checked by the access-control key layer instead.
     async fn legacy_code_grants_vault_access(
         &self,
         code: &str,
@@ -655,4 +1064,289 @@ pub trait MediaRepository: Send + Sync {
         vault_id: &str,
         user_id: &str,
     ) -> Result<Vec<FolderMediaRow>, DbError>;
+
+    // ── Subtitles ─────────────────────────────────────────────────
+
+    /// List subtitle tracks for a media item, default track first.
+    async fn list_subtitles(&self, media_id: i64) -> Result<Vec<SubtitleTrack>, DbError>;
+
+    /// Insert or replace the track for a language. Setting `is_default`
+    /// clears the flag on the item's other tracks.
+    async fn upsert_subtitle(
+        &self,
+        media_id: i64,
+        language: &str,
+        label: &str,
+        is_default: bool,
+        auto_generated: bool,
+    ) -> Result<SubtitleTrack, DbError>;
+
+    /// Delete the track for a language. Returns true if a row was deleted.
+    async fn delete_subtitle(&self, media_id: i64, language: &str) -> Result<bool, DbError>;
+
+    // ── Chapters ──────────────────────────────────────────────────
+
+    /// List a video's chapters in start-time order.
+    async fn list_chapters(&self, media_id: i64) -> Result<Vec<VideoChapter>, DbError>;
+
+    /// Replace all chapters of a video with `chapters`.
+    async fn replace_chapters(
+        &self,
+        media_id: i64,
+        chapters: &[NewChapter],
+    ) -> Result<Vec<VideoChapter>, DbError>;
+
+    // ── Transcripts ───────────────────────────────────────────────
+
+    /// Get the transcript state of a video.
+    async fn get_transcript(&self, media_id: i64) -> Result<Option<MediaTranscript>, DbError>;
+
+    /// List a video's transcript segments in start-time order.
+    async fn list_transcript_segments(
+        &self,
+        media_id: i64,
+    ) -> Result<Vec<TranscriptSegment>, DbError>;
+
+    /// Record a pending or failed transcript run. Stored segments are kept.
+    async fn set_transcript_status(
+        &self,
+        media_id: i64,
+        backend: &str,
+        status: &str,
+        error: Option<&str>,
+    ) -> Result<(), DbError>;
+
+    /// Replace a video's transcript segments and mark it complete.
+    async fn save_transcript(
+        &self,
+        media_id: i64,
+        backend: &str,
+        language: Option<&str>,
+        segments: &[TranscriptSegment],
+    ) -> Result<(), DbError>;
+
+    // ── Live sessions ─────────────────────────────────────────────
+
+    /// Record that a stream went live into `media_id`. Returns the session ID.
+    async fn start_live_session(
+        &self,
+        media_id: i64,
+        stream_path: &str,
+        recording_path: &str,
+    ) -> Result<i64, DbError>;
+
+    /// The session currently recording `stream_path`, if any.
+    async fn get_active_live_session(
+        &self,
+        stream_path: &str,
+    ) -> Result<Option<LiveSession>, DbError>;
+
+    /// The session a media item was recorded from, if it was a live stream.
+    async fn get_live_session(&self, media_id: i64) -> Result<Option<LiveSession>, DbError>;
+
+    /// List a user's live sessions, newest first.
+    async fn list_live_sessions(
+        &self,
+        user_id: &str,
+        limit: i64,
+    ) -> Result<Vec<LiveSession>, DbError>;
+
+    /// Close a session with its final viewer counts. With
+    /// [`LIVE_SESSION_ENDED`] the media item moves on to `processing`.
+    async fn end_live_session(
+        &self,
+        id: i64,
+        status: &str,
+        peak_viewers: i64,
+        total_viewers: i64,
+    ) -> Result<(), DbError>;
+
+    // ── Live streams ──────────────────────────────────────────────
+
+    /// Create a stream definition. Returns its ID.
+    async fn create_live_stream(
+        &self,
+        user_id: &str,
+        stream_path: &str,
+        key_hash: &str,
+        key_prefix: &str,
+        settings: &LiveStreamSettings,
+    ) -> Result<i64, DbError>;
+
+    async fn get_live_stream(&self, id: i64) -> Result<Option<LiveStream>, DbError>;
+
+    /// The stream published to a MediaMTX path.
+    async fn get_live_stream_by_path(
+        &self,
+        stream_path: &str,
+    ) -> Result<Option<LiveStream>, DbError>;
+
+    /// List a user's stream definitions, oldest first.
+    async fn list_live_streams(&self, user_id: &str) -> Result<Vec<LiveStream>, DbError>;
+
+    async fn update_live_stream(
+        &self,
+        id: i64,
+        settings: &LiveStreamSettings,
+    ) -> Result<(), DbError>;
+
+    /// Replace a stream's publish key.
+    async fn rotate_live_stream_key(
+        &self,
+        id: i64,
+        key_hash: &str,
+        key_prefix: &str,
+    ) -> Result<(), DbError>;
+
+    async fn delete_live_stream(&self, id: i64) -> Result<(), DbError>;
+
+    // ── Full-text index ───────────────────────────────────────────
+    //
+    // Titles, descriptions, categories and tags are indexed automatically
+    // as items and tags are inserted, updated and deleted; document bodies
+    // are supplied by the caller.
+
+    /// Set the document text indexed for a media item (markdown/text body,
+    /// extracted PDF text), replacing any previous text.
+    async fn set_media_text(&self, media_id: i32, text: &str) -> Result<(), DbError>;
+
+    /// Documents that have no indexed text yet (uploaded before text
+    /// indexing existed), oldest first.
+    async fn list_documents_without_text(
+        &self,
+        limit: i64,
+    ) -> Result<Vec<UnindexedDocument>, DbError>;
+
+    // ── Integrity ─────────────────────────────────────────────────
+
+    /// Find an item in `vault_id` of the given type whose uploaded bytes had
+    /// the SHA-256 `content_hash`. Failed items are ignored.
+    async fn find_media_by_content_hash(
+        &self,
+        vault_id: &str,
+        media_type: &str,
+        content_hash: &str,
+    ) -> Result<Option<MediaItemRow>, DbError>;
+
+    /// Active items with a stored file and an id above `after_id`, by id.
+    async fn list_media_for_verification(
+        &self,
+        after_id: i32,
+        limit: i64,
+    ) -> Result<Vec<IntegrityCandidate>, DbError>;
+
+    /// Record the result of verifying an item. `file_hash` is stored only
+    /// if the item has no baseline hash yet.
+    async fn record_integrity_check(
+        &self,
+        media_id: i32,
+        status: &str,
+        file_hash: Option<&str>,
+    ) -> Result<(), DbError>;
+
+    /// Verification status counts over active items.
+    async fn get_integrity_summary(&self) -> Result<IntegritySummary, DbError>;
+
+    /// Active items whose last verification failed, most recent first.
+    async fn list_integrity_issues(&self, limit: i64) -> Result<Vec<IntegrityIssue>, DbError>;
+
+    // ── Audio ─────────────────────────────────────────────────────
+
+    /// Get the probed details of an audio item, if it has been processed.
+    async fn get_audio_details(&self, media_id: i64) -> Result<Option<AudioDetails>, DbError>;
+
+    /// Insert or replace the probed details of an audio item.
+    async fn upsert_audio_details(&self, details: &AudioDetails) -> Result<(), DbError>;
+
+    // ── Photos ────────────────────────────────────────────────────
+
+    /// Get the capture metadata of an image, if its upload carried EXIF.
+    async fn get_photo_details(&self, media_id: i64) -> Result<Option<PhotoDetails>, DbError>;
+
+    /// Insert or replace the capture metadata of an image.
+    async fn upsert_photo_details(&self, details: &PhotoDetails) -> Result<(), DbError>;
+
+    /// Geotagged images owned by `user_id`, optionally limited to one vault,
+    /// most recently captured first.
+    async fn list_geotagged_images(
+        &self,
+        user_id: &str,
+        vault_id: Option<&str>,
+    ) -> Result<Vec<GeotaggedImage>, DbError>;
+
+    // ── Trash ─────────────────────────────────────────────────────
+
+    /// Move a media item of `user_id` to the trash. Returns false if the
+    /// user has no such item outside the trash.
+    async fn trash_media(&self, slug: &str, user_id: &str) -> Result<bool, DbError>;
+
+    /// Take a media item of `user_id` out of the trash. Returns false if the
+    /// user has no such item in the trash.
+    async fn restore_media(&self, slug: &str, user_id: &str) -> Result<bool, DbError>;
+
+    /// Get a trashed media item of `user_id`.
+    async fn get_trashed_media(
+        &self,
+        slug: &str,
+        user_id: &str,
+    ) -> Result<Option<TrashedMedia>, DbError>;
+
+    /// Trashed media items of `user_id`, most recently deleted first.
+    async fn list_trashed_media(&self, user_id: &str) -> Result<Vec<TrashedMedia>, DbError>;
+
+    /// Media items of all users trashed more than `retention_days` days ago.
+    async fn list_expired_trashed_media(
+        &self,
+        retention_days: i64,
+    ) -> Result<Vec<TrashedMedia>, DbError>;
+
+    // ── Versions ──────────────────────────────────────────────────
+
+    /// The version of a media item's file currently served.
+    async fn get_current_media_version(
+        &self,
+        media_id: i32,
+    ) -> Result<Option<MediaVersion>, DbError>;
+
+    /// Archived versions of a media item, newest first.
+    async fn list_media_versions(&self, media_id: i32) -> Result<Vec<MediaVersion>, DbError>;
+
+    /// An archived version of a media item.
+    async fn get_media_version(
+        &self,
+        media_id: i32,
+        version: i64,
+    ) -> Result<Option<MediaVersion>, DbError>;
+
+    /// Archive the current file columns of a media item under its current
+    /// version number. Returns that number.
+    async fn archive_media_version(
+        &self,
+        media_id: i32,
+        archived_by: &str,
+        comment: Option<&str>,
+    ) -> Result<i64, DbError>;
+
+    /// Point a media item at a new file (the file columns, status and hashes
+    /// of `item`) under the next unused version number. Returns that number.
+    async fn replace_media_file(&self, media_id: i32, item: &MediaInsert) -> Result<i64, DbError>;
+
+    /// Make an archived version current again and drop it from the archive.
+    /// Returns false if there is no such version.
+    async fn restore_media_version(&self, media_id: i32, version: i64) -> Result<bool, DbError>;
+
+    // ── Batch operations ──────────────────────────────────────────
+
+    /// Apply one operation to many items of `user_id` in a single transaction.
+    ///
+    /// Returns one result per distinct slug, in request order; slugs the user
+    /// doesn't own are reported as `NotFound`. With `dry_run` the transaction
+    /// is rolled back, so the results show what would change.
+    async fn apply_media_batch(
+        &self,
+        user_id: &str,
+        slugs: &[String],
+        operation: &MediaBatchOperation,
+        dry_run: bool,
+    ) -> Result<Vec<MediaBatchItemResult>, DbError>;
 }
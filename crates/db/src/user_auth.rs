@@ -19,6 +19,71 @@ pub struct UpsertUserRequest<'a> {
     pub provider: &'a str,
 }
 
+/// Credentials of a built-in (local) account.
+#[derive(Debug, Clone)]
+pub struct LocalAccount {
+    pub user_id: String,
+    pub email: String,
+    pub name: Option<String>,
+    /// Argon2id PHC string
+    pub password_hash: String,
+    pub password_changed_at: String,
+    /// Consecutive failed logins
+    pub failed_attempts: i64,
+    /// Whether `locked_until` is still in the future
+    pub locked: bool,
+    pub locked_until: Option<String>,
+    /// Base32 TOTP secret, set from enrollment on
+    pub totp_secret: Option<String>,
+    /// Whether the TOTP secret was confirmed and is required at login
+    pub totp_enabled: bool,
+    pub created_at: String,
+}
+
+/// Parameters for creating a local account.
+#[derive(Debug)]
+pub struct NewLocalAccount<'a> {
+    pub user_id: &'a str,
+    pub email: &'a str,
+    pub name: &'a str,
+    pub password_hash: &'a str,
+    /// Tenant to join; None keeps the default
+    pub tenant_id: Option<&'a str>,
+}
+
+/// A pending invitation to sign up for a local account.
+#[derive(Debug, Clone)]
+pub struct LocalInvitation {
+    pub email: String,
+    pub tenant_id: Option<String>,
+    pub expires_at: String,
+}
+
+/// A registered passkey (WebAuthn credential).
+#[derive(Debug, Clone)]
+pub struct PasskeyCredential {
+    pub id: i64,
+    pub user_id: String,
+    /// base64url-encoded credential id
+    pub credential_id: String,
+    /// COSE_Key bytes
+    pub public_key: Vec<u8>,
+    pub sign_count: i64,
+    pub name: String,
+    pub created_at: String,
+    pub last_used_at: Option<String>,
+}
+
+/// Parameters for storing a newly registered passkey.
+#[derive(Debug)]
+pub struct NewPasskeyCredential<'a> {
+    pub user_id: &'a str,
+    pub credential_id: &'a str,
+    pub public_key: &'a [u8],
+    pub sign_count: i64,
+    pub name: &'a str,
+}
+
 #[async_trait::async_trait]
 pub trait UserAuthRepository: Send + Sync {
     /// Upsert a user record. On conflict updates email/name/avatar and last_login_at.
@@ -47,4 +112,131 @@ pub trait UserAuthRepository: Send + Sync {
 
     /// Get a user's display name by id. Returns None if user not found.
     async fn get_user_name(&self, user_id: &str) -> Result<Option<Option<String>>, DbError>;
+
+    /// Get a user's avatar URL by id. Returns None if user not found or unset.
+    async fn get_user_avatar_url(&self, user_id: &str) -> Result<Option<String>, DbError>;
+
+    /// Set `last_login_at` for logins that don't go through `upsert_user`.
+    async fn record_user_login(&self, user_id: &str) -> Result<(), DbError>;
+
+    // ── Local accounts ────────────────────────────────────────────
+
+    /// Create a user with provider `local` and its credentials. Fails with
+    /// `UniqueViolation` if the email is already used by a local account.
+    async fn create_local_account(&self, account: &NewLocalAccount<'_>) -> Result<(), DbError>;
+
+    async fn get_local_account(&self, user_id: &str) -> Result<Option<LocalAccount>, DbError>;
+
+    /// Look up a local account by email (case-insensitive).
+    async fn get_local_account_by_email(
+        &self,
+        email: &str,
+    ) -> Result<Option<LocalAccount>, DbError>;
+
+    /// All local accounts, oldest first.
+    async fn list_local_accounts(&self) -> Result<Vec<LocalAccount>, DbError>;
+
+    /// Count a failed login. Once `max_failures` consecutive failures are
+    /// reached the account locks for `lockout_minutes`. Returns whether the
+    /// account is locked now.
+    async fn record_local_login_failure(
+        &self,
+        user_id: &str,
+        max_failures: u32,
+        lockout_minutes: u32,
+    ) -> Result<bool, DbError>;
+
+    /// Clear failed logins and any lock, and update `last_login_at`.
+    async fn record_local_login_success(&self, user_id: &str) -> Result<(), DbError>;
+
+    /// Store a new password hash; also clears failed logins and any lock.
+    async fn set_local_password(&self, user_id: &str, password_hash: &str) -> Result<(), DbError>;
+
+    /// Store a signup invitation valid for `valid_hours`. Returns when it expires.
+    async fn create_local_invitation(
+        &self,
+        token_hash: &str,
+        email: &str,
+        tenant_id: Option<&str>,
+        invited_by: &str,
+        valid_hours: i64,
+    ) -> Result<String, DbError>;
+
+    /// An unexpired signup invitation.
+    async fn get_local_invitation(
+        &self,
+        token_hash: &str,
+    ) -> Result<Option<LocalInvitation>, DbError>;
+
+    async fn delete_local_invitation(&self, token_hash: &str) -> Result<(), DbError>;
+
+    /// Store a password reset token valid for `valid_hours`. Returns when it expires.
+    async fn create_local_password_reset(
+        &self,
+        token_hash: &str,
+        user_id: &str,
+        valid_hours: i64,
+    ) -> Result<String, DbError>;
+
+    /// Use up an unexpired password reset token. Returns its user.
+    async fn consume_local_password_reset(
+        &self,
+        token_hash: &str,
+    ) -> Result<Option<String>, DbError>;
+
+    /// Store an unconfirmed TOTP secret (TOTP stays off until enabled).
+    async fn set_local_totp_secret(&self, user_id: &str, secret: &str) -> Result<(), DbError>;
+
+    /// Require TOTP at login and replace the recovery codes.
+    async fn enable_local_totp(
+        &self,
+        user_id: &str,
+        recovery_code_hashes: &[String],
+    ) -> Result<(), DbError>;
+
+    /// Turn TOTP off, dropping the secret and the recovery codes.
+    async fn disable_local_totp(&self, user_id: &str) -> Result<(), DbError>;
+
+    /// Accept a TOTP time step if it is newer than the last accepted one,
+    /// so a code can't be replayed. Returns whether it was accepted.
+    async fn accept_local_totp_step(&self, user_id: &str, step: i64) -> Result<bool, DbError>;
+
+    async fn replace_local_recovery_codes(
+        &self,
+        user_id: &str,
+        code_hashes: &[String],
+    ) -> Result<(), DbError>;
+
+    /// Use up a recovery code. Returns false if the user has no such code.
+    async fn consume_local_recovery_code(
+        &self,
+        user_id: &str,
+        code_hash: &str,
+    ) -> Result<bool, DbError>;
+
+    /// Unused recovery codes of a user.
+    async fn count_local_recovery_codes(&self, user_id: &str) -> Result<i64, DbError>;
+
+    // ── Passkeys ──────────────────────────────────────────────────
+
+    /// Store a passkey. Fails with `UniqueViolation` if the credential id is
+    /// already registered.
+    async fn add_passkey(&self, passkey: &NewPasskeyCredential<'_>) -> Result<i64, DbError>;
+
+    /// Passkeys of a user, oldest first.
+    async fn list_passkeys(&self, user_id: &str) -> Result<Vec<PasskeyCredential>, DbError>;
+
+    async fn get_passkey_by_credential_id(
+        &self,
+        credential_id: &str,
+    ) -> Result<Option<PasskeyCredential>, DbError>;
+
+    /// Store the new signature counter and the time of use after a login.
+    async fn record_passkey_use(&self, id: i64, sign_count: i64) -> Result<(), DbError>;
+
+    /// Rename a passkey of the user. Returns false if not found.
+    async fn rename_passkey(&self, user_id: &str, id: i64, name: &str) -> Result<bool, DbError>;
+
+    /// Delete a passkey of the user. Returns false if not found.
+    async fn delete_passkey(&self, user_id: &str, id: i64) -> Result<bool, DbError>;
 }
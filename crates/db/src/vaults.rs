@@ -10,6 +10,8 @@ pub struct StorageVault {
     pub user_id: String,
     pub vault_name: String,
     pub is_default: bool,
+    /// Strip GPS and device EXIF from served image originals
+    pub strip_exif: bool,
     pub created_at: String,
 }
 
@@ -49,6 +51,13 @@ pub trait VaultRepository: Send + Sync {
     /// Update a vault's name.
     async fn update_vault_name(&self, vault_id: &str, name: &str) -> Result<(), DbError>;
 
+    /// Whether served image originals of a vault are stripped of private
+    /// EXIF. Unknown vaults get the default policy (strip).
+    async fn get_vault_strip_exif(&self, vault_id: &str) -> Result<bool, DbError>;
+
+    /// Set a vault's EXIF stripping policy.
+    async fn set_vault_strip_exif(&self, vault_id: &str, strip: bool) -> Result<(), DbError>;
+
     /// Set a vault as default for its owner (unsets all others in a transaction).
     async fn set_default_vault(&self, user_id: &str, vault_id: &str) -> Result<(), DbError>;
 
@@ -69,6 +69,38 @@ pub struct FolderGrant {
     pub group_id: Option<String>,
 }
 
+// ── Workspace trash types ────────────────────────────────────────────
+
+/// A file or folder moved to a workspace's `.trash` directory
+#[derive(Debug, Clone, Serialize)]
+pub struct WorkspaceTrashEntry {
+    pub id: i64,
+    pub workspace_id: String,
+    /// Path relative to the workspace root before deletion
+    pub original_path: String,
+    /// Entry name under `{workspace}/.trash/`
+    pub trash_name: String,
+    pub is_dir: bool,
+    pub size_bytes: i64,
+    /// workspace.yaml entry of a deleted folder (YAML)
+    #[serde(skip)]
+    pub folder_config: Option<String>,
+    pub deleted_by: Option<String>,
+    pub deleted_at: String,
+}
+
+/// Fields for recording a new trash entry
+#[derive(Debug, Clone)]
+pub struct NewWorkspaceTrashEntry<'a> {
+    pub workspace_id: &'a str,
+    pub original_path: &'a str,
+    pub trash_name: &'a str,
+    pub is_dir: bool,
+    pub size_bytes: i64,
+    pub folder_config: Option<&'a str>,
+    pub deleted_by: &'a str,
+}
+
 // ── Repository trait ─────────────────────────────────────────────────
 
 #[async_trait::async_trait]
@@ -341,4 +373,29 @@ pub trait WorkspaceRepository: Send + Sync {
         workspace_id: &str,
         folder_path: &str,
     ) -> Result<Option<String>, DbError>;
+
+    // ── Workspace trash ──────────────────────────────────────────
+
+    /// Record a file or folder moved to the trash. Returns the entry id.
+    async fn insert_trash_entry(&self, entry: &NewWorkspaceTrashEntry<'_>) -> Result<i64, DbError>;
+
+    /// Trash entries of a workspace, most recently deleted first.
+    async fn list_trash_entries(
+        &self,
+        workspace_id: &str,
+    ) -> Result<Vec<WorkspaceTrashEntry>, DbError>;
+
+    async fn get_trash_entry(
+        &self,
+        workspace_id: &str,
+        id: i64,
+    ) -> Result<Option<WorkspaceTrashEntry>, DbError>;
+
+    async fn delete_trash_entry(&self, id: i64) -> Result<(), DbError>;
+
+    /// Trash entries of all workspaces deleted more than `retention_days` ago.
+    async fn list_expired_trash_entries(
+        &self,
+        retention_days: i64,
+    ) -> Result<Vec<WorkspaceTrashEntry>, DbError>;
 }
@@ -33,6 +33,9 @@ pub struct CatalogItem {
     pub file_size: Option<i64>,
     pub created_at: String,
     pub updated_at: Option<String>,
+    /// Languages with a WebVTT track at `media/{slug}/subtitles/{lang}.vtt`
+    #[serde(default)]
+    pub subtitle_languages: Vec<String>,
 }
 
 /// Paginated catalog response from the origin server
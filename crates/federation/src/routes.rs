@@ -79,6 +79,7 @@ pub fn federation_server_routes() -> Router<Arc<FederationState>> {
         .route("/api/v1/federation/media/{slug}", get(crate::server::serve_media_metadata))
         .route("/api/v1/federation/media/{slug}/thumbnail", get(crate::server::serve_media_thumbnail))
         .route("/api/v1/federation/media/{slug}/content", get(crate::server::serve_media_content))
+        .route("/api/v1/federation/media/{slug}/subtitles/{file}", get(crate::server::serve_media_subtitle))
 }
 
 // ── Consumer-side routes (browse remote catalogs) ──────────
@@ -3,7 +3,7 @@
 use api_keys::middleware::AuthenticatedUser;
 use axum::{
     extract::{Path, Query, State},
-    http::StatusCode,
+    http::{HeaderMap, HeaderValue, StatusCode},
     response::{IntoResponse, Response},
     Extension, Json,
 };
@@ -70,6 +70,7 @@ pub async fn serve_catalog(
             file_size: row.file_size,
             created_at: row.created_at,
             updated_at: row.updated_at,
+            subtitle_languages: row.subtitle_languages,
         })
         .collect();
 
@@ -102,6 +103,7 @@ pub async fn serve_media_metadata(
                 file_size: row.file_size,
                 created_at: row.created_at,
                 updated_at: row.updated_at,
+                subtitle_languages: row.subtitle_languages,
             }).into_response()
         }
         Ok(None) => StatusCode::NOT_FOUND.into_response(),
@@ -144,42 +146,32 @@ pub async fn serve_media_thumbnail(
         _ => return StatusCode::NOT_FOUND.into_response(),
     };
 
-    // Try to find the thumbnail
-    let thumb_path = state.storage.find_thumbnail(&vault_id, media_type, &slug);
-    match thumb_path {
-        Some(path) => {
-            match tokio::fs::read(&path).await {
-                Ok(bytes) => (
-                    StatusCode::OK,
-                    [
-                        (axum::http::header::CONTENT_TYPE, "image/webp"),
-                        (axum::http::header::CACHE_CONTROL, "public, max-age=3600"),
-                    ],
-                    bytes,
-                ).into_response(),
-                Err(_) => StatusCode::NOT_FOUND.into_response(),
-            }
-        }
-        None => {
-            // For images, fall back to the image file itself
-            if media_type == common::storage::MediaType::Image {
-                let media_dir = state.storage.vault_nested_media_dir(&vault_id, media_type);
-                let webp_path = media_dir.join(format!("{}.webp", slug));
-                if webp_path.exists() {
-                    if let Ok(bytes) = tokio::fs::read(&webp_path).await {
-                        return (
-                            StatusCode::OK,
-                            [
-                                (axum::http::header::CONTENT_TYPE, "image/webp"),
-                                (axum::http::header::CACHE_CONTROL, "public, max-age=3600"),
-                            ],
-                            bytes,
-                        ).into_response();
-                    }
-                }
-            }
-            StatusCode::NOT_FOUND.into_response()
+    // Try to find the thumbnail; for images, fall back to the image file itself
+    let mut thumb_path = state
+        .storage
+        .find_stored_thumbnail(&vault_id, media_type, &slug)
+        .await;
+    if thumb_path.is_none() && media_type == common::storage::MediaType::Image {
+        thumb_path = state
+            .storage
+            .find_stored_media_file(&vault_id, media_type, &format!("{}.webp", slug))
+            .await;
+    }
+    let Some(path) = thumb_path else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+
+    match common::range::serve_stored_file(&state.storage, &path, "image/webp", &HeaderMap::new())
+        .await
+    {
+        Ok(mut response) => {
+            response.headers_mut().insert(
+                axum::http::header::CACHE_CONTROL,
+                HeaderValue::from_static("public, max-age=3600"),
+            );
+            response
         }
+        Err(_) => StatusCode::NOT_FOUND.into_response(),
     }
 }
 
@@ -189,6 +181,7 @@ pub async fn serve_media_content(
     Extension(user): Extension<AuthenticatedUser>,
     State(state): State<Arc<FederationState>>,
     Path(slug): Path<String>,
+    headers: HeaderMap,
 ) -> Response {
     if let Err(status) = require_federation_scope(&user) {
         return (status, "Forbidden").into_response();
@@ -215,25 +208,86 @@ pub async fn serve_media_content(
     };
 
     // Find the actual file
-    let file_path = state.storage.find_media_file(&vault_id, media_type, &filename);
-    match file_path {
-        Some(path) => {
-            match tokio::fs::read(&path).await {
-                Ok(bytes) => {
-                    let content_type = mime_from_filename(&filename);
-                    (
-                        StatusCode::OK,
-                        [
-                            (axum::http::header::CONTENT_TYPE, content_type),
-                            (axum::http::header::CACHE_CONTROL, "public, max-age=3600"),
-                        ],
-                        bytes,
-                    ).into_response()
-                }
-                Err(_) => StatusCode::NOT_FOUND.into_response(),
-            }
+    let file_path = state
+        .storage
+        .find_stored_media_file(&vault_id, media_type, &filename)
+        .await;
+    let Some(path) = file_path else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+
+    match common::range::serve_stored_file(&state.storage, &path, mime_from_filename(&filename), &headers)
+        .await
+    {
+        Ok(mut response) => {
+            response.headers_mut().insert(
+                axum::http::header::CACHE_CONTROL,
+                HeaderValue::from_static("public, max-age=3600"),
+            );
+            response
+        }
+        Err(_) => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+/// GET /api/v1/federation/media/{slug}/subtitles/{file}
+///
+/// Serves `{lang}.vtt` for a language listed in the item's `subtitle_languages`.
+pub async fn serve_media_subtitle(
+    Extension(user): Extension<AuthenticatedUser>,
+    State(state): State<Arc<FederationState>>,
+    Path((slug, file)): Path<(String, String)>,
+    headers: HeaderMap,
+) -> Response {
+    if let Err(status) = require_federation_scope(&user) {
+        return (status, "Forbidden").into_response();
+    }
+    let Some(language) = file.strip_suffix(".vtt") else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+
+    let row = match state.media_repo.get_public_metadata(&slug).await {
+        Ok(Some(row)) => row,
+        Ok(None) => return StatusCode::NOT_FOUND.into_response(),
+        Err(e) => {
+            warn!("Federation subtitle lookup failed: {}", e);
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+    // Only languages recorded in the database, never arbitrary path segments
+    if row.media_type != "video" || !row.subtitle_languages.iter().any(|l| l == language) {
+        return StatusCode::NOT_FOUND.into_response();
+    }
+
+    let vault_id = match state.media_repo.get_public_media_for_content(&slug).await {
+        Ok(Some((_, _, Some(vault_id)))) => vault_id,
+        Ok(_) => return StatusCode::NOT_FOUND.into_response(),
+        Err(e) => {
+            warn!("Federation subtitle lookup failed: {}", e);
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+
+    let relative = format!("{}/subtitles/{}.vtt", slug, language);
+    let Some(path) = state
+        .storage
+        .find_stored_media_file(&vault_id, common::storage::MediaType::Video, &relative)
+        .await
+    else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+
+    match common::range::serve_stored_file(&state.storage, &path, "text/vtt; charset=utf-8", &headers)
+        .await
+    {
+        Ok(mut response) => {
+            response.headers_mut().insert(
+                axum::http::header::CACHE_CONTROL,
+                HeaderValue::from_static("public, max-age=3600"),
+            );
+            response
         }
-        None => StatusCode::NOT_FOUND.into_response(),
+        Err(_) => StatusCode::NOT_FOUND.into_response(),
     }
 }
 
@@ -249,6 +303,7 @@ fn mime_from_filename(filename: &str) -> &'static str {
         Some("md") => "text/markdown",
         Some("m3u8") => "application/vnd.apple.mpegurl",
         Some("ts") => "video/mp2t",
+        Some("vtt") => "text/vtt",
         _ => "application/octet-stream",
     }
 }
@@ -0,0 +1,410 @@
+// Pluggable storage backends for media files
+// Phase 4: Media-Core Architecture
+// Created: April 2026
+//
+// Stored files are addressed by '/'-separated keys relative to the storage
+// root, e.g. `vaults/{vault_id}/media/videos/{slug}/video.mp4`. The local
+// filesystem (`StorageManager`), S3-compatible object stores (`S3Backend`)
+// and an in-memory store used by tests all implement `StorageBackend`, so
+// callers never need to know where the bytes live.
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures::stream::{self, Stream, StreamExt};
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+use std::path::{Component, Path, PathBuf};
+use std::pin::Pin;
+use std::sync::RwLock;
+use std::time::SystemTime;
+use tokio::fs;
+use tokio::io::AsyncWriteExt;
+use tracing::{debug, error};
+
+use crate::errors::{MediaError, MediaResult};
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// Stream of object bytes, as returned by [`StorageBackend::get_range`]
+pub type ByteStream = Pin<Box<dyn Stream<Item = std::io::Result<Bytes>> + Send>>;
+
+/// Size and validators of a stored object
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ObjectMeta {
+    /// Object size in bytes
+    pub len: u64,
+    /// Last modification time, if the backend reports one
+    pub modified: Option<SystemTime>,
+    /// Backend-provided entity tag (quoted), if any
+    pub etag: Option<String>,
+}
+
+// ============================================================================
+// Backend Trait
+// ============================================================================
+
+/// Object storage for media files
+///
+/// Keys are relative, '/'-separated and never contain `..` components (see
+/// [`validate_key`]). Reading a key that does not exist fails with
+/// [`MediaError::FileNotFound`].
+#[async_trait]
+pub trait StorageBackend: Send + Sync + Debug {
+    /// Short backend name for logs ("local", "s3", "memory")
+    fn name(&self) -> &'static str;
+
+    /// Where the object lives on the local filesystem, for backends that
+    /// store objects as plain files
+    fn local_path(&self, _key: &str) -> Option<PathBuf> {
+        None
+    }
+
+    /// Store an object from memory, replacing any existing object
+    async fn put(&self, key: &str, data: Bytes) -> MediaResult<()>;
+
+    /// Store an object from a local file, replacing any existing object
+    async fn put_file(&self, key: &str, path: &Path) -> MediaResult<()>;
+
+    /// Read a whole object into memory
+    async fn get(&self, key: &str) -> MediaResult<Bytes>;
+
+    /// Stream an object, or the inclusive byte range `start..=end` of it
+    async fn get_range(&self, key: &str, range: Option<(u64, u64)>) -> MediaResult<ByteStream>;
+
+    /// Size and validators of an object, or None if it does not exist
+    async fn head(&self, key: &str) -> MediaResult<Option<ObjectMeta>>;
+
+    /// Delete an object (deleting a missing object is not an error)
+    async fn delete(&self, key: &str) -> MediaResult<()>;
+
+    /// Keys of all objects starting with `prefix`, at any depth, sorted
+    async fn list(&self, prefix: &str) -> MediaResult<Vec<String>>;
+
+    /// Delete every object starting with `prefix` and return how many were deleted
+    ///
+    /// Pass a prefix ending in '/' to delete a "directory" without touching
+    /// siblings that share a name prefix.
+    async fn delete_prefix(&self, prefix: &str) -> MediaResult<usize> {
+        let keys = self.list(prefix).await?;
+        for key in &keys {
+            self.delete(key).await?;
+        }
+        Ok(keys.len())
+    }
+
+    /// Whether an object exists
+    async fn exists(&self, key: &str) -> MediaResult<bool> {
+        Ok(self.head(key).await?.is_some())
+    }
+
+    /// Copy an object to a local file and return the number of bytes written
+    ///
+    /// The file is written next to `path` and renamed into place, so readers
+    /// never see a partial download.
+    async fn download_to(&self, key: &str, path: &Path) -> MediaResult<u64> {
+        let mut body = self.get_range(key, None).await?;
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).await.map_err(|e| {
+                error!("Failed to create directory {:?}: {}", parent, e);
+                MediaError::storage(format!("Failed to create directory: {}", e))
+            })?;
+        }
+
+        let partial = partial_download_path(path);
+        let mut file = fs::File::create(&partial).await.map_err(|e| {
+            error!("Failed to create file {:?}: {}", partial, e);
+            MediaError::storage(format!("Failed to create file: {}", e))
+        })?;
+
+        let mut written = 0u64;
+        while let Some(chunk) = body.next().await {
+            let result = match chunk {
+                Ok(chunk) => file.write_all(&chunk).await.map(|_| chunk.len()),
+                Err(e) => Err(e),
+            };
+            match result {
+                Ok(len) => written += len as u64,
+                Err(e) => {
+                    let _ = fs::remove_file(&partial).await;
+                    error!("Failed to download {} to {:?}: {}", key, path, e);
+                    return Err(MediaError::storage(format!("Failed to download object: {}", e)));
+                }
+            }
+        }
+
+        file.sync_all().await?;
+        fs::rename(&partial, path).await.map_err(|e| {
+            error!("Failed to move download into place at {:?}: {}", path, e);
+            MediaError::storage(format!("Failed to move file: {}", e))
+        })?;
+
+        debug!("Downloaded {} ({} bytes) to {:?}", key, written, path);
+        Ok(written)
+    }
+}
+
+// ============================================================================
+// Utility Functions
+// ============================================================================
+
+/// Reject keys that are empty, absolute or escape the storage root
+pub fn validate_key(key: &str) -> MediaResult<()> {
+    let invalid = key.is_empty()
+        || key.starts_with('/')
+        || key.contains('\\')
+        || Path::new(key)
+            .components()
+            .any(|c| !matches!(c, Component::Normal(_)));
+
+    if invalid {
+        return Err(MediaError::InvalidFilename {
+            filename: key.to_string(),
+        });
+    }
+    Ok(())
+}
+
+/// Convert a path relative to the storage root into a storage key
+pub fn key_from_relative_path(path: &Path) -> Option<String> {
+    let parts: Option<Vec<&str>> = path
+        .components()
+        .map(|c| match c {
+            Component::Normal(part) => part.to_str(),
+            _ => None,
+        })
+        .collect();
+    let key = parts?.join("/");
+    (!key.is_empty()).then_some(key)
+}
+
+/// Temporary name a download is written to before being renamed into place
+fn partial_download_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".part");
+    path.with_file_name(name)
+}
+
+/// Inclusive byte range of `data`, clamped to its length
+fn slice_range(data: &Bytes, range: Option<(u64, u64)>) -> Bytes {
+    match range {
+        None => data.clone(),
+        Some((start, end)) => {
+            let len = data.len() as u64;
+            let start = start.min(len) as usize;
+            let end = end.saturating_add(1).min(len) as usize;
+            data.slice(start..end.max(start))
+        }
+    }
+}
+
+// ============================================================================
+// In-Memory Backend
+// ============================================================================
+
+/// Backend that keeps objects in memory
+///
+/// Stands in for a remote object store in tests; everything is lost when
+/// the process exits.
+#[derive(Debug, Default)]
+pub struct MemoryBackend {
+    objects: RwLock<BTreeMap<String, (Bytes, SystemTime)>>,
+}
+
+impl MemoryBackend {
+    /// Create an empty in-memory backend
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of stored objects
+    pub fn len(&self) -> usize {
+        self.objects.read().unwrap().len()
+    }
+
+    /// Whether no objects are stored
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn not_found(key: &str) -> MediaError {
+        MediaError::FileNotFound {
+            path: key.to_string(),
+        }
+    }
+}
+
+#[async_trait]
+impl StorageBackend for MemoryBackend {
+    fn name(&self) -> &'static str {
+        "memory"
+    }
+
+    async fn put(&self, key: &str, data: Bytes) -> MediaResult<()> {
+        validate_key(key)?;
+        self.objects
+            .write()
+            .unwrap()
+            .insert(key.to_string(), (data, SystemTime::now()));
+        Ok(())
+    }
+
+    async fn put_file(&self, key: &str, path: &Path) -> MediaResult<()> {
+        let data = fs::read(path).await.map_err(|e| {
+            if e.kind() == std::io::ErrorKind::NotFound {
+                return MediaError::FileNotFound {
+                    path: path.to_string_lossy().to_string(),
+                };
+            }
+            MediaError::storage(format!("Failed to read file: {}", e))
+        })?;
+        self.put(key, Bytes::from(data)).await
+    }
+
+    async fn get(&self, key: &str) -> MediaResult<Bytes> {
+        validate_key(key)?;
+        self.objects
+            .read()
+            .unwrap()
+            .get(key)
+            .map(|(data, _)| data.clone())
+            .ok_or_else(|| Self::not_found(key))
+    }
+
+    async fn get_range(&self, key: &str, range: Option<(u64, u64)>) -> MediaResult<ByteStream> {
+        let data = slice_range(&self.get(key).await?, range);
+        Ok(Box::pin(stream::once(async move { Ok(data) })))
+    }
+
+    async fn head(&self, key: &str) -> MediaResult<Option<ObjectMeta>> {
+        validate_key(key)?;
+        Ok(self
+            .objects
+            .read()
+            .unwrap()
+            .get(key)
+            .map(|(data, modified)| ObjectMeta {
+                len: data.len() as u64,
+                modified: Some(*modified),
+                etag: None,
+            }))
+    }
+
+    async fn delete(&self, key: &str) -> MediaResult<()> {
+        validate_key(key)?;
+        self.objects.write().unwrap().remove(key);
+        Ok(())
+    }
+
+    async fn list(&self, prefix: &str) -> MediaResult<Vec<String>> {
+        Ok(self
+            .objects
+            .read()
+            .unwrap()
+            .range(prefix.to_string()..)
+            .map(|(key, _)| key)
+            .take_while(|key| key.starts_with(prefix))
+            .cloned()
+            .collect())
+    }
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    async fn collect(stream: ByteStream) -> Vec<u8> {
+        let chunks: Vec<_> = stream.collect().await;
+        chunks
+            .into_iter()
+            .flat_map(|chunk| chunk.unwrap().to_vec())
+            .collect()
+    }
+
+    /// Behaviour every backend must share
+    pub(crate) async fn exercise_backend(backend: &dyn StorageBackend) {
+        let key = "vaults/v1/media/videos/clip/video.mp4";
+        backend.put(key, Bytes::from_static(b"0123456789")).await.unwrap();
+        backend
+            .put("vaults/v1/media/videos/clip/hls/master.m3u8", Bytes::from_static(b"#EXTM3U"))
+            .await
+            .unwrap();
+        backend
+            .put("vaults/v1/media/videos/clip-2/video.mp4", Bytes::from_static(b"other"))
+            .await
+            .unwrap();
+
+        assert_eq!(&backend.get(key).await.unwrap()[..], b"0123456789");
+        assert_eq!(backend.head(key).await.unwrap().unwrap().len, 10);
+        assert!(backend.exists(key).await.unwrap());
+        assert_eq!(
+            collect(backend.get_range(key, Some((2, 5))).await.unwrap()).await,
+            b"2345"
+        );
+        assert_eq!(
+            collect(backend.get_range(key, Some((8, 99))).await.unwrap()).await,
+            b"89"
+        );
+
+        let missing = backend.get("vaults/v1/missing.bin").await;
+        assert!(matches!(missing, Err(MediaError::FileNotFound { .. })));
+        assert!(backend.head("vaults/v1/missing.bin").await.unwrap().is_none());
+
+        assert_eq!(
+            backend.list("vaults/v1/media/videos/clip/").await.unwrap(),
+            vec![
+                "vaults/v1/media/videos/clip/hls/master.m3u8".to_string(),
+                key.to_string(),
+            ]
+        );
+        assert_eq!(backend.list("vaults/v1/media/videos/clip").await.unwrap().len(), 3);
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let local = temp_dir.path().join("nested/copy.mp4");
+        assert_eq!(backend.download_to(key, &local).await.unwrap(), 10);
+        assert_eq!(std::fs::read(&local).unwrap(), b"0123456789");
+
+        assert_eq!(backend.delete_prefix("vaults/v1/media/videos/clip/").await.unwrap(), 2);
+        assert!(!backend.exists(key).await.unwrap());
+        assert!(backend
+            .exists("vaults/v1/media/videos/clip-2/video.mp4")
+            .await
+            .unwrap());
+
+        backend.delete("vaults/v1/media/videos/clip-2/video.mp4").await.unwrap();
+        backend.delete("vaults/v1/media/videos/clip-2/video.mp4").await.unwrap();
+        assert!(backend.list("vaults/").await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_memory_backend() {
+        let backend = MemoryBackend::new();
+        exercise_backend(&backend).await;
+        assert!(backend.is_empty());
+    }
+
+    #[test]
+    fn test_validate_key() {
+        assert!(validate_key("vaults/v1/media/images/a.webp").is_ok());
+        assert!(validate_key("").is_err());
+        assert!(validate_key("/etc/passwd").is_err());
+        assert!(validate_key("vaults/../secret").is_err());
+        assert!(validate_key("vaults\\v1").is_err());
+    }
+
+    #[test]
+    fn test_key_from_relative_path() {
+        assert_eq!(
+            key_from_relative_path(Path::new("vaults/v1/media/a.webp")).as_deref(),
+            Some("vaults/v1/media/a.webp")
+        );
+        assert_eq!(key_from_relative_path(Path::new("")), None);
+        assert_eq!(key_from_relative_path(Path::new("../a")), None);
+    }
+}
@@ -0,0 +1,143 @@
+// Content hashing for deduplication and integrity checks
+// Phase 4: Media-Core Architecture
+// Created: April 2026
+
+use sha2::{Digest, Sha256};
+use std::path::Path;
+use tokio::fs;
+use tokio::io::AsyncReadExt;
+use tracing::error;
+
+use crate::errors::{MediaError, MediaResult};
+
+// ============================================================================
+// Constants
+// ============================================================================
+
+/// Read buffer size used when hashing files
+const HASH_BUFFER_SIZE: usize = 64 * 1024;
+
+// ============================================================================
+// Incremental Hasher
+// ============================================================================
+
+/// SHA-256 hasher fed chunk by chunk, e.g. while an upload streams in
+#[derive(Clone, Default)]
+pub struct ContentHasher {
+    hasher: Sha256,
+    len: u64,
+}
+
+impl ContentHasher {
+    /// Create a new hasher
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the next chunk of data
+    pub fn update(&mut self, chunk: &[u8]) {
+        self.hasher.update(chunk);
+        self.len += chunk.len() as u64;
+    }
+
+    /// Number of bytes hashed so far
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    /// Whether no data has been hashed yet
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Finish hashing and return the lowercase hex digest
+    pub fn finalize(self) -> String {
+        hex::encode(self.hasher.finalize())
+    }
+}
+
+// ============================================================================
+// Utility Functions
+// ============================================================================
+
+/// SHA-256 of a byte slice as lowercase hex
+pub fn sha256_hex(data: &[u8]) -> String {
+    hex::encode(Sha256::digest(data))
+}
+
+/// SHA-256 of a file as lowercase hex, read in fixed-size chunks
+pub async fn sha256_file<P: AsRef<Path>>(path: P) -> MediaResult<String> {
+    let path = path.as_ref();
+
+    let mut file = fs::File::open(path).await.map_err(|e| {
+        if e.kind() == std::io::ErrorKind::NotFound {
+            return MediaError::FileNotFound {
+                path: path.to_string_lossy().to_string(),
+            };
+        }
+        error!("Failed to open file {:?} for hashing: {}", path, e);
+        MediaError::storage(format!("Failed to open file: {}", e))
+    })?;
+
+    let mut hasher = ContentHasher::new();
+    let mut buffer = vec![0u8; HASH_BUFFER_SIZE];
+    loop {
+        let read = file.read(&mut buffer).await.map_err(|e| {
+            error!("Failed to read file {:?} for hashing: {}", path, e);
+            MediaError::storage(format!("Failed to read file: {}", e))
+        })?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+
+    Ok(hasher.finalize())
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    const HELLO_SHA256: &str = "185f8db32271fe25f561a6fc938b2e264306ec304eda518007d1764826381969";
+
+    #[test]
+    fn test_sha256_hex() {
+        assert_eq!(sha256_hex(b"Hello"), HELLO_SHA256);
+        assert_eq!(
+            sha256_hex(b""),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+    }
+
+    #[test]
+    fn test_incremental_matches_one_shot() {
+        let mut hasher = ContentHasher::new();
+        assert!(hasher.is_empty());
+        hasher.update(b"He");
+        hasher.update(b"llo");
+        assert_eq!(hasher.len(), 5);
+        assert_eq!(hasher.finalize(), HELLO_SHA256);
+    }
+
+    #[tokio::test]
+    async fn test_sha256_file() {
+        let temp_dir = tempdir().unwrap();
+        let path = temp_dir.path().join("hello.txt");
+        tokio::fs::write(&path, b"Hello").await.unwrap();
+
+        assert_eq!(sha256_file(&path).await.unwrap(), HELLO_SHA256);
+    }
+
+    #[tokio::test]
+    async fn test_sha256_file_not_found() {
+        let temp_dir = tempdir().unwrap();
+        let result = sha256_file(temp_dir.path().join("missing.bin")).await;
+        assert!(matches!(result, Err(MediaError::FileNotFound { .. })));
+    }
+}
@@ -20,15 +20,26 @@ pub use errors::{MediaError, MediaResult};
 // File validation
 pub mod validation;
 pub use validation::{
-    is_document_mime_type, is_image_mime_type, is_video_mime_type, sanitize_filename,
-    validate_extension_mime_match, validate_file_size, validate_file_size_for_type,
-    validate_filename, validate_mime_type, MAX_DOCUMENT_SIZE, MAX_IMAGE_SIZE, MAX_VIDEO_SIZE,
+    is_audio_mime_type, is_document_mime_type, is_image_mime_type, is_video_mime_type,
+    sanitize_filename, validate_extension_mime_match, validate_file_size,
+    validate_file_size_for_type, validate_filename, validate_mime_type, MAX_AUDIO_SIZE,
+    MAX_DOCUMENT_SIZE, MAX_IMAGE_SIZE, MAX_VIDEO_SIZE,
 };
 
 // Storage operations
 pub mod storage;
 pub use storage::{StorageManager, DEFAULT_STORAGE_ROOT};
 
+// Storage backends
+pub mod backend;
+pub mod s3;
+pub use backend::{ByteStream, MemoryBackend, ObjectMeta, StorageBackend};
+pub use s3::{S3Backend, S3Config};
+
+// Content hashing
+pub mod checksum;
+pub use checksum::{sha256_file, sha256_hex, ContentHasher};
+
 // Upload handling
 pub mod upload;
 pub use upload::{upload, upload_with_type, UploadConfig, UploadHandler, UploadResult};
@@ -36,8 +47,8 @@ pub use upload::{upload, upload_with_type, UploadConfig, UploadHandler, UploadRe
 // Metadata extraction
 pub mod metadata;
 pub use metadata::{
-    detect_mime_type, extract_metadata, generate_slug, generate_unique_slug, CommonMetadata,
-    DocumentMetadata, ImageMetadata, VideoMetadata,
+    detect_mime_type, extract_metadata, generate_slug, generate_unique_slug, AudioMetadata,
+    CommonMetadata, DocumentMetadata, ImageMetadata, VideoMetadata,
 };
 
 // ============================================================================
@@ -84,6 +95,11 @@ mod tests {
         assert!(image.is_image());
         assert!(!image.is_document());
 
+        let audio = MediaType::Audio;
+        assert!(audio.is_audio());
+        assert!(!audio.is_video());
+        assert_eq!(audio.as_str(), "audio");
+
         let doc = MediaType::Document(DocumentType::PDF);
         assert!(!doc.is_video());
         assert!(!doc.is_image());
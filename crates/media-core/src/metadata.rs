@@ -90,6 +90,28 @@ pub struct ImageMetadata {
     pub format: String,
 }
 
+/// Audio-specific metadata
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AudioMetadata {
+    /// Common metadata
+    pub common: CommonMetadata,
+
+    /// Duration in seconds
+    pub duration: Option<f64>,
+
+    /// Bitrate in bits per second
+    pub bitrate: Option<u64>,
+
+    /// Number of channels
+    pub channels: Option<u32>,
+
+    /// Sample rate in Hz
+    pub sample_rate: Option<u32>,
+
+    /// Audio codec
+    pub codec: Option<String>,
+}
+
 /// Document-specific metadata
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DocumentMetadata {
@@ -149,6 +171,8 @@ impl CommonMetadata {
             return MediaType::Video;
         } else if mime_type.starts_with("image/") {
             return MediaType::Image;
+        } else if mime_type.starts_with("audio/") {
+            return MediaType::Audio;
         } else if let Some(doc_type) = DocumentType::from_mime_type(mime_type) {
             return MediaType::Document(doc_type);
         }
@@ -160,6 +184,9 @@ impl CommonMetadata {
                 "jpg" | "jpeg" | "png" | "gif" | "webp" | "svg" | "bmp" | "tiff" => {
                     MediaType::Image
                 }
+                "mp3" | "m4a" | "aac" | "oga" | "opus" | "wav" | "flac" | "weba" => {
+                    MediaType::Audio
+                }
                 _ => {
                     if let Some(doc_type) = DocumentType::from_extension(ext) {
                         MediaType::Document(doc_type)
@@ -256,6 +283,40 @@ impl ImageMetadata {
     }
 }
 
+impl AudioMetadata {
+    /// Create basic audio metadata
+    pub fn new(common: CommonMetadata) -> Self {
+        Self {
+            common,
+            duration: None,
+            bitrate: None,
+            channels: None,
+            sample_rate: None,
+            codec: None,
+        }
+    }
+
+    /// Get duration as string (e.g., "1:02:03" or "4:05")
+    pub fn duration_string(&self) -> Option<String> {
+        let total = self.duration?.round() as u64;
+        let (hours, minutes, seconds) = (total / 3600, (total % 3600) / 60, total % 60);
+        Some(if hours > 0 {
+            format!("{}:{:02}:{:02}", hours, minutes, seconds)
+        } else {
+            format!("{}:{:02}", minutes, seconds)
+        })
+    }
+
+    /// Get channel layout name (e.g., "mono", "stereo")
+    pub fn channel_layout(&self) -> Option<String> {
+        self.channels.map(|channels| match channels {
+            1 => "mono".to_string(),
+            2 => "stereo".to_string(),
+            n => format!("{} channels", n),
+        })
+    }
+}
+
 impl DocumentMetadata {
     /// Create document metadata
     pub fn new(common: CommonMetadata, document_type: DocumentType) -> Self {
@@ -309,6 +370,37 @@ pub fn detect_mime_type(data: &[u8], filename: &str) -> String {
         if data[0..2] == [0xFF, 0xD8] {
             return "image/jpeg".to_string();
         }
+        // MP3 with ID3 tag
+        if data[0..3] == [0x49, 0x44, 0x33] {
+            return "audio/mpeg".to_string();
+        }
+        // MPEG audio frame sync: layer bits 00 mean AAC in an ADTS stream
+        if data[0] == 0xFF && data[1] & 0xE0 == 0xE0 {
+            if data[1] & 0x06 == 0 {
+                return "audio/aac".to_string();
+            }
+            return "audio/mpeg".to_string();
+        }
+        // FLAC
+        if data[0..4] == [0x66, 0x4C, 0x61, 0x43] {
+            return "audio/flac".to_string();
+        }
+        // Ogg (Vorbis or Opus)
+        if data[0..4] == [0x4F, 0x67, 0x67, 0x53] {
+            return "audio/ogg".to_string();
+        }
+        // WAV
+        if data.len() >= 12
+            && data[0..4] == [0x52, 0x49, 0x46, 0x46]
+            && data[8..12] == [0x57, 0x41, 0x56, 0x45]
+        {
+            return "audio/wav".to_string();
+        }
+        // M4A (MP4 container with an audio brand)
+        if data.len() >= 12 && data[4..8] == [0x66, 0x74, 0x79, 0x70] && data[8..11] == *b"M4A"
+        {
+            return "audio/mp4".to_string();
+        }
         // GIF
         if data[0..4] == [0x47, 0x49, 0x46, 0x38] {
             return "image/gif".to_string();
@@ -398,6 +490,35 @@ mod tests {
             "application/pdf".to_string(),
         );
         assert!(doc_metadata.media_type.is_document());
+
+        let audio_metadata =
+            CommonMetadata::from_bytes(b"data", "talk.mp3".to_string(), "audio/mpeg".to_string());
+        assert!(audio_metadata.media_type.is_audio());
+
+        let audio_by_extension = CommonMetadata::from_bytes(
+            b"data",
+            "talk.flac".to_string(),
+            "application/octet-stream".to_string(),
+        );
+        assert!(audio_by_extension.media_type.is_audio());
+    }
+
+    #[test]
+    fn test_audio_metadata() {
+        let common =
+            CommonMetadata::from_bytes(b"data", "talk.mp3".to_string(), "audio/mpeg".to_string());
+        let mut audio = AudioMetadata::new(common);
+        assert_eq!(audio.duration_string(), None);
+
+        audio.duration = Some(245.4);
+        audio.channels = Some(2);
+        assert_eq!(audio.duration_string(), Some("4:05".to_string()));
+        assert_eq!(audio.channel_layout(), Some("stereo".to_string()));
+
+        audio.duration = Some(3723.0);
+        audio.channels = Some(6);
+        assert_eq!(audio.duration_string(), Some("1:02:03".to_string()));
+        assert_eq!(audio.channel_layout(), Some("6 channels".to_string()));
     }
 
     #[test]
@@ -463,6 +584,35 @@ mod tests {
         assert_eq!(detect_mime_type(&pdf_data, "test.pdf"), "application/pdf");
     }
 
+    #[test]
+    fn test_detect_audio_mime_type_from_magic_numbers() {
+        // Unknown extension forces magic number detection
+        assert_eq!(detect_mime_type(b"ID3\x04\x00", "upload"), "audio/mpeg");
+        assert_eq!(
+            detect_mime_type(&[0xFF, 0xFB, 0x90, 0x64], "upload"),
+            "audio/mpeg"
+        );
+        assert_eq!(
+            detect_mime_type(&[0xFF, 0xF1, 0x50, 0x80], "upload"),
+            "audio/aac"
+        );
+        assert_eq!(detect_mime_type(b"fLaC\x00\x00", "upload"), "audio/flac");
+        assert_eq!(detect_mime_type(b"OggS\x00\x02", "upload"), "audio/ogg");
+        assert_eq!(
+            detect_mime_type(b"RIFF\x24\x00\x00\x00WAVEfmt ", "upload"),
+            "audio/wav"
+        );
+        assert_eq!(
+            detect_mime_type(b"\x00\x00\x00\x20ftypM4A \x00", "upload"),
+            "audio/mp4"
+        );
+        // JPEG must not be mistaken for an MPEG frame
+        assert_eq!(
+            detect_mime_type(&[0xFF, 0xD8, 0xFF, 0xE0], "upload"),
+            "image/jpeg"
+        );
+    }
+
     #[test]
     fn test_extra_metadata() {
         let mut metadata =
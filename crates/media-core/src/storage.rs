@@ -2,11 +2,16 @@
 // Phase 4: Media-Core Architecture
 // Created: February 2026
 
+use async_trait::async_trait;
+use bytes::Bytes;
+use std::io::SeekFrom;
 use std::path::{Path, PathBuf};
 use tokio::fs;
-use tokio::io::AsyncWriteExt;
+use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt};
+use tokio_util::io::ReaderStream;
 use tracing::{debug, error, info, warn};
 
+use crate::backend::{validate_key, ByteStream, ObjectMeta, StorageBackend};
 use crate::errors::{MediaError, MediaResult};
 
 // ============================================================================
@@ -21,6 +26,10 @@ pub const DEFAULT_STORAGE_ROOT: &str = "storage";
 // ============================================================================
 
 /// Storage manager for file operations
+///
+/// Also serves as the local-filesystem [`StorageBackend`], with keys
+/// resolved against the root directory.
+#[derive(Debug, Clone)]
 pub struct StorageManager {
     root: PathBuf,
 }
@@ -250,6 +259,11 @@ impl StorageManager {
         Ok(metadata.len())
     }
 
+    /// Get the SHA-256 checksum of a file as lowercase hex
+    pub async fn checksum<P: AsRef<Path>>(&self, relative_path: P) -> MediaResult<String> {
+        crate::checksum::sha256_file(self.absolute_path(&relative_path)).await
+    }
+
     /// List files in a directory
     pub async fn list_files<P: AsRef<Path>>(&self, relative_path: P) -> MediaResult<Vec<PathBuf>> {
         let path = self.absolute_path(&relative_path);
@@ -284,6 +298,158 @@ impl StorageManager {
     }
 }
 
+// ============================================================================
+// Local Filesystem Backend
+// ============================================================================
+
+#[async_trait]
+impl StorageBackend for StorageManager {
+    fn name(&self) -> &'static str {
+        "local"
+    }
+
+    fn local_path(&self, key: &str) -> Option<PathBuf> {
+        Some(self.absolute_path(key))
+    }
+
+    async fn put(&self, key: &str, data: Bytes) -> MediaResult<()> {
+        validate_key(key)?;
+        self.save_bytes(key, &data).await.map(|_| ())
+    }
+
+    async fn put_file(&self, key: &str, path: &Path) -> MediaResult<()> {
+        validate_key(key)?;
+        let target = self.absolute_path(key);
+        if target == path {
+            return Ok(());
+        }
+
+        if let Some(parent) = target.parent() {
+            self.ensure_directory(parent.strip_prefix(&self.root).unwrap_or(parent))
+                .await?;
+        }
+
+        fs::copy(path, &target).await.map_err(|e| {
+            if e.kind() == std::io::ErrorKind::NotFound {
+                return MediaError::FileNotFound {
+                    path: path.to_string_lossy().to_string(),
+                };
+            }
+            error!("Failed to copy {:?} to {:?}: {}", path, target, e);
+            MediaError::storage(format!("Failed to copy file: {}", e))
+        })?;
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> MediaResult<Bytes> {
+        validate_key(key)?;
+        self.read_bytes(key).await.map(Bytes::from)
+    }
+
+    async fn get_range(&self, key: &str, range: Option<(u64, u64)>) -> MediaResult<ByteStream> {
+        validate_key(key)?;
+        let path = self.absolute_path(key);
+
+        let mut file = fs::File::open(&path).await.map_err(|e| {
+            if e.kind() == std::io::ErrorKind::NotFound {
+                return MediaError::FileNotFound {
+                    path: path.to_string_lossy().to_string(),
+                };
+            }
+            error!("Failed to open file {:?}: {}", path, e);
+            MediaError::storage(format!("Failed to open file: {}", e))
+        })?;
+
+        match range {
+            None => Ok(Box::pin(ReaderStream::new(file))),
+            Some((start, end)) => {
+                file.seek(SeekFrom::Start(start)).await?;
+                let count = end.saturating_sub(start).saturating_add(1);
+                Ok(Box::pin(ReaderStream::new(file.take(count))))
+            }
+        }
+    }
+
+    async fn head(&self, key: &str) -> MediaResult<Option<ObjectMeta>> {
+        validate_key(key)?;
+        match fs::metadata(self.absolute_path(key)).await {
+            Ok(metadata) if metadata.is_file() => Ok(Some(ObjectMeta {
+                len: metadata.len(),
+                modified: metadata.modified().ok(),
+                etag: None,
+            })),
+            Ok(_) => Ok(None),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(MediaError::storage(format!(
+                "Failed to get file metadata: {}",
+                e
+            ))),
+        }
+    }
+
+    async fn delete(&self, key: &str) -> MediaResult<()> {
+        validate_key(key)?;
+        self.delete_file(key).await
+    }
+
+    async fn list(&self, prefix: &str) -> MediaResult<Vec<String>> {
+        // Walk the deepest directory that contains every matching key
+        let dir = match prefix.rfind('/') {
+            Some(pos) => &prefix[..pos],
+            None => "",
+        };
+        let mut pending = vec![self.absolute_path(dir)];
+        let mut keys = Vec::new();
+
+        while let Some(dir) = pending.pop() {
+            let mut entries = match fs::read_dir(&dir).await {
+                Ok(entries) => entries,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(e) => {
+                    error!("Failed to read directory {:?}: {}", dir, e);
+                    return Err(MediaError::storage(format!(
+                        "Failed to read directory: {}",
+                        e
+                    )));
+                }
+            };
+
+            while let Some(entry) = entries.next_entry().await? {
+                let path = entry.path();
+                if entry.file_type().await?.is_dir() {
+                    pending.push(path);
+                    continue;
+                }
+                let key = path
+                    .strip_prefix(&self.root)
+                    .ok()
+                    .and_then(crate::backend::key_from_relative_path);
+                if let Some(key) = key.filter(|key| key.starts_with(prefix)) {
+                    keys.push(key);
+                }
+            }
+        }
+
+        keys.sort();
+        Ok(keys)
+    }
+
+    async fn delete_prefix(&self, prefix: &str) -> MediaResult<usize> {
+        let keys = self.list(prefix).await?;
+        for key in &keys {
+            self.delete_file(key).await?;
+        }
+
+        // A directory prefix takes its (now empty) directory tree with it
+        if let Some(dir) = prefix.strip_suffix('/') {
+            if validate_key(dir).is_ok() {
+                self.delete_directory(dir).await?;
+            }
+        }
+        Ok(keys.len())
+    }
+}
+
 // ============================================================================
 // Utility Functions
 // ============================================================================
@@ -411,6 +577,34 @@ mod tests {
         assert_eq!(size, data.len() as u64);
     }
 
+    #[tokio::test]
+    async fn test_checksum() {
+        let temp_dir = tempdir().unwrap();
+        let storage = StorageManager::new(temp_dir.path());
+
+        storage.save_bytes("a.txt", b"same").await.unwrap();
+        storage.save_bytes("b.txt", b"same").await.unwrap();
+        storage.save_bytes("c.txt", b"other").await.unwrap();
+
+        let a = storage.checksum("a.txt").await.unwrap();
+        assert_eq!(a.len(), 64);
+        assert_eq!(a, storage.checksum("b.txt").await.unwrap());
+        assert_ne!(a, storage.checksum("c.txt").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_local_backend() {
+        let temp_dir = tempdir().unwrap();
+        let storage = StorageManager::new(temp_dir.path());
+        crate::backend::tests::exercise_backend(&storage).await;
+
+        assert_eq!(
+            storage.local_path("a/b.txt"),
+            Some(temp_dir.path().join("a/b.txt"))
+        );
+        assert!(!storage.directory_exists("vaults/v1/media/videos/clip").await);
+    }
+
     #[tokio::test]
     async fn test_file_not_found() {
         let temp_dir = tempdir().unwrap();
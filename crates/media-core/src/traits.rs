@@ -21,6 +21,9 @@ pub enum MediaType {
     /// Image media (JPEG, PNG, WebP, etc.)
     Image,
 
+    /// Audio media (MP3, M4A, FLAC, Opus, etc.)
+    Audio,
+
     /// Document media with specific document type
     Document(DocumentType),
 }
@@ -62,6 +65,7 @@ impl MediaType {
         match self {
             MediaType::Video => "video",
             MediaType::Image => "image",
+            MediaType::Audio => "audio",
             MediaType::Document(_) => "document",
         }
     }
@@ -76,6 +80,11 @@ impl MediaType {
         matches!(self, MediaType::Image)
     }
 
+    /// Check if this is an audio type
+    pub fn is_audio(&self) -> bool {
+        matches!(self, MediaType::Audio)
+    }
+
     /// Check if this is a document type
     pub fn is_document(&self) -> bool {
         matches!(self, MediaType::Document(_))
@@ -140,7 +149,7 @@ impl DocumentType {
 // MediaItem Trait
 // ============================================================================
 
-/// Common interface for all media items (videos, images, documents)
+/// Common interface for all media items (videos, images, audio, documents)
 ///
 /// This trait defines the common operations and properties that all media
 /// types must implement. Type-specific behavior is achieved through the
@@ -157,7 +166,7 @@ pub trait MediaItem: Send + Sync {
     /// Get the URL-friendly slug
     fn slug(&self) -> &str;
 
-    /// Get the media type (video, image, document)
+    /// Get the media type (video, image, audio, document)
     fn media_type(&self) -> MediaType;
 
     /// Get the human-readable title
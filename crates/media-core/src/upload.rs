@@ -231,6 +231,7 @@ impl UploadHandler {
         match media_type {
             MediaType::Video => PathBuf::from(format!("videos/{}/{}.{}", slug, slug, extension)),
             MediaType::Image => PathBuf::from(format!("images/{}.{}", slug, extension)),
+            MediaType::Audio => PathBuf::from(format!("audio/{}/{}.{}", slug, slug, extension)),
             MediaType::Document(_) => PathBuf::from(format!("documents/{}.{}", slug, extension)),
         }
     }
@@ -240,6 +241,7 @@ impl UploadHandler {
         match (actual, expected) {
             (MediaType::Video, MediaType::Video) => true,
             (MediaType::Image, MediaType::Image) => true,
+            (MediaType::Audio, MediaType::Audio) => true,
             (MediaType::Document(_), MediaType::Document(_)) => true,
             _ => false,
         }
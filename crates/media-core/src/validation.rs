@@ -18,6 +18,9 @@ pub const MAX_VIDEO_SIZE: u64 = 5 * 1024 * 1024 * 1024;
 /// Maximum file size for images: 50MB
 pub const MAX_IMAGE_SIZE: u64 = 50 * 1024 * 1024;
 
+/// Maximum file size for audio: 1GB
+pub const MAX_AUDIO_SIZE: u64 = 1024 * 1024 * 1024;
+
 /// Maximum file size for documents: 100MB
 pub const MAX_DOCUMENT_SIZE: u64 = 100 * 1024 * 1024;
 
@@ -38,6 +41,7 @@ pub fn validate_file_size_for_type(size: usize, media_type: &MediaType) -> Media
     let max_size = match media_type {
         MediaType::Video => MAX_VIDEO_SIZE,
         MediaType::Image => MAX_IMAGE_SIZE,
+        MediaType::Audio => MAX_AUDIO_SIZE,
         MediaType::Document(_) => MAX_DOCUMENT_SIZE,
     };
 
@@ -49,6 +53,7 @@ pub fn validate_mime_type(mime_type: &str, media_type: &MediaType) -> MediaResul
     let is_valid = match media_type {
         MediaType::Video => is_video_mime_type(mime_type),
         MediaType::Image => is_image_mime_type(mime_type),
+        MediaType::Audio => is_audio_mime_type(mime_type),
         MediaType::Document(_) => is_document_mime_type(mime_type),
     };
 
@@ -89,6 +94,27 @@ pub fn is_image_mime_type(mime_type: &str) -> bool {
     )
 }
 
+/// Check if MIME type is a valid audio type
+pub fn is_audio_mime_type(mime_type: &str) -> bool {
+    matches!(
+        mime_type,
+        "audio/mpeg"
+            | "audio/mp3"
+            | "audio/mp4"
+            | "audio/m4a"
+            | "audio/x-m4a"
+            | "audio/aac"
+            | "audio/ogg"
+            | "audio/opus"
+            | "audio/wav"
+            | "audio/x-wav"
+            | "audio/wave"
+            | "audio/flac"
+            | "audio/x-flac"
+            | "audio/webm"
+    )
+}
+
 /// Check if MIME type is a valid document type
 pub fn is_document_mime_type(mime_type: &str) -> bool {
     matches!(
@@ -166,6 +192,8 @@ pub fn validate_extension_mime_match(filename: &str, mime_type: &str) -> MediaRe
         "video"
     } else if is_image_mime_type(mime_type) {
         "image"
+    } else if is_audio_mime_type(mime_type) {
+        "audio"
     } else if is_document_mime_type(mime_type) {
         "document"
     } else {
@@ -202,6 +230,19 @@ pub fn validate_extension_mime_match(filename: &str, mime_type: &str) -> MediaRe
                 });
             }
         }
+        "audio" => {
+            if !matches!(
+                extension.to_lowercase().as_str(),
+                "mp3" | "m4a" | "aac" | "ogg" | "oga" | "opus" | "wav" | "flac" | "weba"
+            ) {
+                return Err(MediaError::ValidationError {
+                    message: format!(
+                        "File extension '{}' doesn't match audio MIME type '{}'",
+                        extension, mime_type
+                    ),
+                });
+            }
+        }
         "document" => {
             if !matches!(
                 extension.to_lowercase().as_str(),
@@ -253,6 +294,12 @@ mod tests {
         // Image: should allow up to 50MB
         assert!(validate_file_size_for_type(1000, &MediaType::Image).is_ok());
 
+        // Audio: should allow up to 1GB
+        assert!(validate_file_size_for_type(1000, &MediaType::Audio).is_ok());
+        assert!(
+            validate_file_size_for_type(MAX_AUDIO_SIZE as usize + 1, &MediaType::Audio).is_err()
+        );
+
         // Document: should allow up to 100MB
         assert!(validate_file_size_for_type(
             1000,
@@ -278,6 +325,18 @@ mod tests {
         assert!(!is_image_mime_type("application/pdf"));
     }
 
+    #[test]
+    fn test_is_audio_mime_type() {
+        assert!(is_audio_mime_type("audio/mpeg"));
+        assert!(is_audio_mime_type("audio/x-m4a"));
+        assert!(is_audio_mime_type("audio/flac"));
+        assert!(!is_audio_mime_type("video/mp4"));
+        assert!(!is_audio_mime_type("application/pdf"));
+        assert!(validate_mime_type("audio/ogg", &MediaType::Audio).is_ok());
+        assert!(validate_extension_mime_match("episode.mp3", "audio/mpeg").is_ok());
+        assert!(validate_extension_mime_match("episode.mp4", "audio/mpeg").is_err());
+    }
+
     #[test]
     fn test_is_document_mime_type() {
         assert!(is_document_mime_type("application/pdf"));
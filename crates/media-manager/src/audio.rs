@@ -0,0 +1,253 @@
+//! Audio items: background processing and rendition serving
+//!
+//! Uploads are stored as `media/audio/{slug}/original.{ext}` and inserted in
+//! `processing` state. [`spawn_audio_processing`] then runs the
+//! [`video_manager::audio`] pipeline (ffprobe, loudness-normalized AAC/Opus
+//! renditions, waveform), stores the probed details in `media_audio` and
+//! marks the item active with a waveform thumbnail.
+
+use access_control::{Permission, SignedUrlParams};
+use anyhow::{Context, Result};
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{Json, Response},
+};use db::media::{AudioDetails, MediaRepository};
+use serde_json::{json, Value};
+use std::path::PathBuf;
+use std::sync::Arc;
+use tower_sessions::Session;
+use tracing::{error, info};
+use video_manager::audio::{self as pipeline, LoudnessTarget};
+use video_manager::ffmpeg::FFmpegConfig;
+
+use common::storage::{MediaType, UserStorageManager};
+
+use crate::routes::MediaManagerState;
+use crate::serve::{
+    check_media_access, set_download_disposition, signed_url_grant, AccessQuery, AccessResource,
+};
+
+/// Context for processing an uploaded audio file
+#[derive(Clone)]
+pub struct AudioProcessingContext {
+    pub media_id: i32,
+    pub slug: String,
+    pub vault_id: String,
+    /// Path of `original.{ext}`; renditions are written next to it
+    pub original_path: PathBuf,
+    pub repo: Arc<dyn MediaRepository>,
+    pub user_storage: UserStorageManager,
+}
+
+/// Run the audio pipeline for an upload and activate the media item
+///
+/// A missing waveform only costs the thumbnail; failing to probe or encode
+/// the renditions marks the item as `error`.
+pub async fn process_audio_upload(context: AudioProcessingContext) -> Result<()> {
+    let output_dir = context
+        .original_path
+        .parent()
+        .context("Audio original has no parent directory")?;
+
+    let processed = pipeline::process_audio(
+        &FFmpegConfig::default(),
+        &context.original_path,
+        output_dir,
+        &LoudnessTarget::default(),
+    )
+    .await?;
+
+    let metadata = &processed.metadata;
+    context
+        .repo
+        .upsert_audio_details(&AudioDetails {
+            media_id: context.media_id as i64,
+            duration: Some(metadata.duration),
+            bitrate: metadata.bitrate.map(|b| b as i64),
+            channels: metadata.channels.map(i64::from),
+            sample_rate: metadata.sample_rate.map(i64::from),
+            codec: Some(metadata.codec.clone()),
+        })
+        .await
+        .context("Failed to store audio details")?;
+
+    let thumbnail_url = match &processed.waveform {
+        Some(waveform) => Some(write_waveform_thumbnail(&context, &waveform.peaks).await?),
+        None => None,
+    };
+
+    context
+        .repo
+        .update_media_status_active(&context.slug, "audio", thumbnail_url.as_deref())
+        .await
+        .context("Failed to activate audio item")?;
+
+    // Hand the finished item over to the storage backend
+    context
+        .user_storage
+        .offload_paths(&[
+            output_dir.to_path_buf(),
+            context
+                .user_storage
+                .get_thumbnail_path(&context.vault_id, MediaType::Audio, &context.slug),
+        ])
+        .await;
+
+    Ok(())
+}
+
+/// Save the waveform as the item's WebP thumbnail and return its URL
+async fn write_waveform_thumbnail(
+    context: &AudioProcessingContext,
+    peaks: &[f32],
+) -> Result<String> {
+    let webp_data = pipeline::waveform_thumbnail(peaks)?;
+
+    let thumb_path =
+        context
+            .user_storage
+            .get_thumbnail_path(&context.vault_id, MediaType::Audio, &context.slug);
+    if let Some(parent) = thumb_path.parent() {
+        tokio::fs::create_dir_all(parent)
+            .await
+            .context("Failed to create thumbnail directory")?;
+    }
+    tokio::fs::write(&thumb_path, &webp_data)
+        .await
+        .with_context(|| format!("Failed to save thumbnail to {:?}", thumb_path))?;
+
+    Ok(format!("/media/{}/thumbnail", context.slug))
+}
+
+/// Spawn async background task to process an audio upload
+///
+/// Returns immediately; the item stays in `processing` until the task
+/// finishes and is set to `error` if it fails.
+pub fn spawn_audio_processing(context: AudioProcessingContext) {
+    tokio::spawn(async move {
+        let slug = context.slug.clone();
+        let repo = context.repo.clone();
+        info!("Starting audio processing for: {}", slug);
+
+        match process_audio_upload(context).await {
+            Ok(()) => info!("Audio processing completed for: {}", slug),
+            Err(e) => {
+                error!("Audio processing failed for {}: {:?}", slug, e);
+                let _ = repo.update_media_status_error(&slug, "audio").await;
+            }
+        }
+    });
+}
+
+/// Probed details of an audio item
+/// GET /api/media/{slug}/audio
+pub async fn get_audio_details(
+    State(state): State<MediaManagerState>,
+    session: Session,
+    Path(slug): Path<String>,
+    Query(query): Query<AccessQuery>,
+    Query(signed): Query<SignedUrlParams>,
+    headers: HeaderMap,
+) -> Result<Json<Value>, StatusCode> {
+    let info = state
+        .repo
+        .get_audio_for_serving(&slug)
+        .await
+        .map_err(|e| {
+            error!("Database error: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    let vault_id = info.vault_id.clone().ok_or(StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let resource = AccessResource {
+        resource_type: access_control::ResourceType::Audio,
+        id: info.id,
+        owner_id: info.user_id.as_deref(),
+        is_public: info.is_public != 0,
+        signed_permission: Permission::Read,
+    };
+    check_media_access(&state, &session, &headers, resource, &vault_id, &signed, query.code).await?;
+
+    let details = state
+        .repo
+        .get_audio_details(info.id as i64)
+        .await
+        .map_err(|e| {
+            error!("Database error: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(Json(json!({
+        "slug": slug,
+        "details": details,
+        "renditions": {
+            "aac": format!("/media/{}/audio/{}", slug, pipeline::AAC_RENDITION),
+            "opus": format!("/media/{}/audio/{}", slug, pipeline::OPUS_RENDITION),
+        },
+        "waveform": format!("/media/{}/audio/{}", slug, pipeline::WAVEFORM_JSON),
+    })))
+}
+
+/// Serve an audio rendition or waveform file
+/// GET /media/{slug}/audio/{file}
+pub async fn serve_audio_file(
+    State(state): State<MediaManagerState>,
+    session: Session,
+    Path((slug, file)): Path<(String, String)>,
+    Query(query): Query<AccessQuery>,
+    Query(signed): Query<SignedUrlParams>,
+    headers: HeaderMap,
+) -> Result<Response, StatusCode> {
+    // Only the known output files are served; the original is not
+    let content_type = pipeline::served_content_type(&file).ok_or(StatusCode::NOT_FOUND)?;
+
+    let info = state
+        .repo
+        .get_audio_for_serving(&slug)
+        .await
+        .map_err(|e| {
+            error!("Database error: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    let vault_id = info.vault_id.clone().ok_or(StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let resource = AccessResource {
+        resource_type: access_control::ResourceType::Audio,
+        id: info.id,
+        owner_id: info.user_id.as_deref(),
+        is_public: info.is_public != 0,
+        signed_permission: Permission::Read,
+    };
+    check_media_access(&state, &session, &headers, resource, &vault_id, &signed, query.code).await?;
+
+    let path = state
+        .user_storage
+        .find_stored_media_file(&vault_id, MediaType::Audio, &format!("{}/{}", slug, file))
+        .await
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    let mut response =
+        common::range::serve_stored_file(&state.user_storage, &path, content_type, &headers)
+            .await
+            .map_err(|e| {
+                error!("Failed to open audio file: {}", e);
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?;
+
+    let response_headers = response.headers_mut();
+    response_headers.insert(
+        header::CACHE_CONTROL,
+        HeaderValue::from_static("public, max-age=31536000"),
+    );
+    response_headers.insert(
+        header::ACCESS_CONTROL_ALLOW_ORIGIN,
+        HeaderValue::from_static("*"),
+    );
+    let grant = signed_url_grant(&state, info.id, &signed).await;
+    set_download_disposition(&mut response, grant, &file);
+
+    Ok(response)
+}
@@ -0,0 +1,381 @@
+//! Bulk media operations
+//!
+//! `POST /api/media/batch` applies one operation (add/remove tags, set group,
+//! set visibility, move to another vault, move to the trash) to a selection of the
+//! user's media, given either as explicit slugs or as list filters. The
+//! database changes run in a single transaction; files are moved or removed
+//! afterwards. A dry run reports what would change without touching anything.
+//!
+//! ```json
+//! {
+//!   "slugs": ["beach-1", "beach-2"],
+//!   "operation": { "op": "add_tags", "tags": ["holiday"] },
+//!   "dry_run": true
+//! }
+//! ```
+
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Json, Response},
+};
+use common::storage::MediaType;
+use db::media::{MediaBatchItemResult, MediaBatchOperation, MediaBatchStatus};
+use serde::Deserialize;
+use tower_sessions::Session;
+use tracing::{error, info, warn};
+
+use crate::models::MediaFilterOptions;
+use crate::routes::MediaManagerState;
+use crate::search::MediaSearchService;
+
+/// Most items a single batch may touch
+pub const MAX_BATCH_SIZE: usize = 1000;
+
+/// Batch request body
+#[derive(Debug, Deserialize)]
+pub struct BatchRequest {
+    /// Explicit selection
+    #[serde(default)]
+    pub slugs: Vec<String>,
+    /// Selection by list filters (pagination is ignored)
+    #[serde(default)]
+    pub filter: Option<MediaFilterOptions>,
+    pub operation: MediaBatchOperation,
+    /// Report what would change without applying it
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+fn batch_error(status: StatusCode, message: &str) -> Response {
+    (
+        status,
+        Json(serde_json::json!({
+            "success": false,
+            "error": message
+        })),
+    )
+        .into_response()
+}
+
+/// Name of an operation as it appears in requests and reports
+fn operation_name(operation: &MediaBatchOperation) -> &'static str {
+    match operation {
+        MediaBatchOperation::AddTags { .. } => "add_tags",
+        MediaBatchOperation::RemoveTags { .. } => "remove_tags",
+        MediaBatchOperation::SetGroup { .. } => "set_group",
+        MediaBatchOperation::SetVisibility { .. } => "set_visibility",
+        MediaBatchOperation::MoveToVault { .. } => "move_to_vault",
+        MediaBatchOperation::Delete => "delete",
+    }
+}
+
+/// Trim tag names and drop empty ones and duplicates
+fn clean_tags(tags: &[String]) -> Vec<String> {
+    let mut cleaned: Vec<String> = Vec::with_capacity(tags.len());
+    for tag in tags {
+        let tag = tag.trim();
+        if !tag.is_empty() && !cleaned.iter().any(|t| t == tag) {
+            cleaned.push(tag.to_string());
+        }
+    }
+    cleaned
+}
+
+/// Apply an operation to many media items
+/// POST /api/media/batch
+pub async fn batch_media(
+    State(state): State<MediaManagerState>,
+    session: Session,
+    Json(request): Json<BatchRequest>,
+) -> impl IntoResponse {
+    let authenticated: bool = session
+        .get("authenticated")
+        .await
+        .ok()
+        .flatten()
+        .unwrap_or(false);
+    if !authenticated {
+        return batch_error(StatusCode::UNAUTHORIZED, "Authentication required");
+    }
+    let Some(user_id) = session.get::<String>("user_id").await.ok().flatten() else {
+        return batch_error(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "User ID not found in session",
+        );
+    };
+    let tenant_id: String = session
+        .get("tenant_id")
+        .await
+        .ok()
+        .flatten()
+        .unwrap_or_else(|| "platform".to_string());
+
+    // ── Validate the operation ──────────────────────────────────────
+    let mut operation = request.operation;
+    match &mut operation {
+        MediaBatchOperation::AddTags { tags } | MediaBatchOperation::RemoveTags { tags } => {
+            *tags = clean_tags(tags);
+            if tags.is_empty() {
+                return batch_error(StatusCode::BAD_REQUEST, "No tags given");
+            }
+        }
+        MediaBatchOperation::SetGroup {
+            group_id: Some(group_id),
+        } => match state.repo.get_user_groups(&user_id).await {
+            Ok(groups) if groups.iter().any(|(id, _)| id == group_id) => {}
+            Ok(_) => return batch_error(StatusCode::FORBIDDEN, "Group not found or access denied"),
+            Err(e) => {
+                error!("Failed to load groups: {}", e);
+                return batch_error(StatusCode::INTERNAL_SERVER_ERROR, "Failed to load groups");
+            }
+        },
+        MediaBatchOperation::MoveToVault { vault_id } => {
+            match state.vault_repo.get_vault_owner(vault_id).await {
+                Ok(Some(owner)) if owner == user_id => {}
+                Ok(_) => {
+                    return batch_error(StatusCode::FORBIDDEN, "Vault not found or access denied")
+                }
+                Err(e) => {
+                    error!("Failed to check vault owner: {}", e);
+                    return batch_error(StatusCode::INTERNAL_SERVER_ERROR, "Failed to check vault");
+                }
+            }
+        }
+        _ => {}
+    }
+
+    // ── Resolve the selection ───────────────────────────────────────
+    let slugs = match (request.slugs.is_empty(), request.filter) {
+        (false, None) => {
+            if request.slugs.len() > MAX_BATCH_SIZE {
+                return batch_error(
+                    StatusCode::BAD_REQUEST,
+                    &format!("At most {} items per batch", MAX_BATCH_SIZE),
+                );
+            }
+            request.slugs
+        }
+        (true, Some(mut filter)) => {
+            // Filters only ever select the user's own media
+            filter.user_id = Some(user_id.clone());
+            filter.tenant_id = Some(tenant_id);
+            let search_service = MediaSearchService::new(state.repo.clone());
+            match search_service
+                .matching_slugs(&filter, MAX_BATCH_SIZE as i64 + 1)
+                .await
+            {
+                Ok(slugs) if slugs.len() > MAX_BATCH_SIZE => {
+                    return batch_error(
+                        StatusCode::BAD_REQUEST,
+                        &format!(
+                            "Filter matches more than {} items; narrow it down",
+                            MAX_BATCH_SIZE
+                        ),
+                    );
+                }
+                Ok(slugs) => slugs,
+                Err(e) => {
+                    error!("Batch selection search failed: {}", e);
+                    return batch_error(StatusCode::INTERNAL_SERVER_ERROR, "Search failed");
+                }
+            }
+        }
+        _ => {
+            return batch_error(
+                StatusCode::BAD_REQUEST,
+                "Select items with either `slugs` or `filter`",
+            )
+        }
+    };
+
+    // ── Apply ───────────────────────────────────────────────────────
+    let mut items = match state
+        .repo
+        .apply_media_batch(&user_id, &slugs, &operation, request.dry_run)
+        .await
+    {
+        Ok(items) => items,
+        Err(e) => {
+            error!("Batch {} failed: {}", operation_name(&operation), e);
+            return batch_error(StatusCode::INTERNAL_SERVER_ERROR, "Batch operation failed");
+        }
+    };
+
+    if !request.dry_run {
+        for item in items
+            .iter_mut()
+            .filter(|item| item.status == MediaBatchStatus::Applied)
+        {
+            match &operation {
+                MediaBatchOperation::Delete => {
+                    if let (Some(media_type), Some(filename), Some(vault_id)) =
+                        (&item.media_type, &item.filename, &item.vault_id)
+                    {
+                        crate::trash::move_files_to_trash(
+                            &state, &item.slug, media_type, filename, vault_id,
+                        )
+                        .await;
+                    }
+                }
+                MediaBatchOperation::MoveToVault { vault_id } => {
+                    if let Err(e) = move_media_files(&state, item, vault_id).await {
+                        warn!("Failed to move files of {}: {}", item.slug, e);
+                        item.error = Some(e);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    let count = |status: MediaBatchStatus| items.iter().filter(|i| i.status == status).count();
+    let applied = count(MediaBatchStatus::Applied);
+    let unchanged = count(MediaBatchStatus::Unchanged);
+    let not_found = count(MediaBatchStatus::NotFound);
+    let failed = items.iter().filter(|i| i.error.is_some()).count();
+
+    info!(
+        "Batch {} by {}{}: {} applied, {} unchanged, {} not found",
+        operation_name(&operation),
+        user_id,
+        if request.dry_run { " (dry run)" } else { "" },
+        applied,
+        unchanged,
+        not_found
+    );
+
+    (
+        StatusCode::OK,
+        Json(serde_json::json!({
+            "success": true,
+            "dry_run": request.dry_run,
+            "operation": operation_name(&operation),
+            "summary": {
+                "selected": items.len(),
+                "applied": applied,
+                "unchanged": unchanged,
+                "not_found": not_found,
+                "failed": failed,
+            },
+            "items": items,
+        })),
+    )
+        .into_response()
+}
+
+/// Move the stored files of an item that changed vault
+///
+/// Mirrors `list::remove_media_files`: media file or directory, kept
+/// original, thumbnail and archived versions. The derivative cache of the
+/// old vault is dropped.
+async fn move_media_files(
+    state: &MediaManagerState,
+    item: &MediaBatchItemResult,
+    to_vault: &str,
+) -> Result<(), String> {
+    let (Some(media_type), Some(filename), Some(from_vault)) =
+        (&item.media_type, &item.filename, &item.vault_id)
+    else {
+        // Legacy items without a vault have no vault files to move
+        return Ok(());
+    };
+    let slug = item.slug.as_str();
+    let media_type_enum = match media_type.as_str() {
+        "video" => MediaType::Video,
+        "image" => MediaType::Image,
+        "audio" => MediaType::Audio,
+        _ => MediaType::Document,
+    };
+    let storage = &state.user_storage;
+
+    let mut moves = Vec::new();
+    if media_type == "video" || media_type == "audio" {
+        moves.push((
+            storage.vault_nested_media_path(from_vault, media_type_enum, slug),
+            storage.vault_nested_media_path(to_vault, media_type_enum, slug),
+        ));
+    } else {
+        moves.push((
+            storage.get_media_file_path(from_vault, media_type_enum, filename),
+            storage.get_media_file_path(to_vault, media_type_enum, filename),
+        ));
+        if media_type == "image" {
+            for ext in &["jpg", "jpeg", "png", "webp", "gif", "bmp"] {
+                let original_filename = format!("{}_original.{}", slug, ext);
+                if let Some(original_path) = storage
+                    .find_stored_media_file(from_vault, media_type_enum, &original_filename)
+                    .await
+                {
+                    moves.push((
+                        original_path,
+                        storage.get_media_file_path(to_vault, media_type_enum, &original_filename),
+                    ));
+                    break;
+                }
+            }
+        }
+    }
+    moves.push((
+        storage.get_thumbnail_path(from_vault, media_type_enum, slug),
+        storage.get_thumbnail_path(to_vault, media_type_enum, slug),
+    ));
+    moves.push((
+        storage.vault_versions_dir(from_vault, slug),
+        storage.vault_versions_dir(to_vault, slug),
+    ));
+
+    if media_type == "image" {
+        crate::derivatives::purge_cache(state, from_vault, slug).await;
+    }
+
+    let mut failures = Vec::new();
+    for (from, to) in moves {
+        if let Err(e) = storage.move_stored(&from, &to).await {
+            failures.push(format!("{:?}: {}", from.file_name().unwrap_or_default(), e));
+        }
+    }
+    if failures.is_empty() {
+        Ok(())
+    } else {
+        Err(format!("Failed to move {}", failures.join(", ")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clean_tags() {
+        let tags = vec![
+            " holiday ".to_string(),
+            "".to_string(),
+            "holiday".to_string(),
+            "beach".to_string(),
+        ];
+        assert_eq!(clean_tags(&tags), vec!["holiday", "beach"]);
+    }
+
+    #[test]
+    fn test_parse_batch_request() {
+        let request: BatchRequest = serde_json::from_str(
+            r#"{"filter": {"tag": "old"}, "operation": {"op": "set_group", "group_id": null}}"#,
+        )
+        .unwrap();
+        assert!(request.slugs.is_empty());
+        assert_eq!(request.filter.unwrap().tag.as_deref(), Some("old"));
+        assert_eq!(
+            request.operation,
+            MediaBatchOperation::SetGroup { group_id: None }
+        );
+        assert!(!request.dry_run);
+
+        let request: BatchRequest = serde_json::from_str(
+            r#"{"slugs": ["a"], "operation": {"op": "delete"}, "dry_run": true}"#,
+        )
+        .unwrap();
+        assert_eq!(operation_name(&request.operation), "delete");
+        assert!(request.dry_run);
+    }
+}
@@ -3,13 +3,13 @@
 use askama::Template;
 use axum::{
     extract::{Path, Query, State},
-    http::StatusCode,
+    http::{HeaderMap, StatusCode},
     response::Html,
     Json,
 };
 use serde::{Deserialize, Serialize};
 use tower_sessions::Session;
-use tracing::{error, info};
+use tracing::{error, info, warn};
 
 use crate::routes::MediaManagerState;
 
@@ -24,6 +24,7 @@ pub async fn view_bpmn_handler(
     State(state): State<MediaManagerState>,
     Path(slug): Path<String>,
     Query(query): Query<BpmnAccessQuery>,
+    headers: HeaderMap,
 ) -> Result<Html<String>, (StatusCode, String)> {
     let authenticated: bool = session
         .get("authenticated")
@@ -58,7 +59,8 @@ pub async fn view_bpmn_handler(
         ));
     }
 
-    let mut context = access_control::AccessContext::new(common::ResourceType::File, doc.id);
+    let mut context = access_control::AccessContext::new(common::ResourceType::File, doc.id)
+        .with_request_headers(&headers);
     if let Some(uid) = user_id.clone() {
         context = context.with_user(uid.clone());
     }
@@ -89,6 +91,13 @@ pub async fn view_bpmn_handler(
             "You don't have access to this document".to_string(),
         ));
     }
+    if !state
+        .access_control
+        .record_key_view(&decision, query.code.as_deref())
+        .await
+    {
+        return Err((StatusCode::GONE, "This access code has reached its view limit".to_string()));
+    }
 
     let vault_id = doc.vault_id.ok_or((
         StatusCode::INTERNAL_SERVER_ERROR,
@@ -98,9 +107,10 @@ pub async fn view_bpmn_handler(
     // Use multi-location fallback to find the file
     let file_path = state
         .user_storage
-        .find_media_file(&vault_id, common::storage::MediaType::Document,
+        .fetch_media_file(&vault_id, common::storage::MediaType::Document,
             &doc.filename,
         )
+        .await
         .ok_or_else(|| {
             error!("BPMN file not found: {} (vault: {})", doc.filename, vault_id);
             (StatusCode::NOT_FOUND, format!("File not found: {}", doc.filename))
@@ -231,9 +241,10 @@ pub async fn save_bpmn_handler(
     // Use multi-location fallback to find the file
     let file_path = state
         .user_storage
-        .find_media_file(&vault_id, common::storage::MediaType::Document,
+        .fetch_media_file(&vault_id, common::storage::MediaType::Document,
             &doc.filename,
         )
+        .await
         .ok_or_else(|| {
             error!("BPMN file not found: {} (vault: {})", doc.filename, vault_id);
             (
@@ -260,6 +271,10 @@ pub async fn save_bpmn_handler(
 
     info!("Saved BPMN file: {}", slug);
 
+    if let Err(e) = state.user_storage.offload(&file_path).await {
+        warn!("Failed to offload BPMN file {}: {}", slug, e);
+    }
+
     Ok(Json(SaveBpmnResponse {
         success: true,
         message: "BPMN saved successfully".to_string(),
@@ -0,0 +1,208 @@
+//! Document text for full-text search
+//!
+//! Markdown and other text documents are indexed with their file contents,
+//! PDFs with the text extracted by Ghostscript's `txtwrite` device (the same
+//! dependency used for PDF thumbnails). The text is stored through
+//! [`MediaRepository::set_media_text`], which refreshes the search index.
+
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::process::Command;
+use tracing::{error, info, warn};
+
+use common::storage::{MediaType, UserStorageManager};
+use db::media::{MediaRepository, UnindexedDocument};
+
+/// Upper bound on indexed text per document; the rest is not searchable
+pub const MAX_INDEXED_TEXT_BYTES: usize = 2 * 1024 * 1024;
+
+/// Documents fetched per backfill round
+const BACKFILL_BATCH: i64 = 50;
+
+/// Whether documents of this MIME type have text worth indexing
+pub fn is_indexable(mime_type: &str) -> bool {
+    mime_type == "application/pdf"
+        || mime_type.starts_with("text/")
+        || matches!(mime_type, "application/json" | "application/yaml")
+}
+
+/// Read the searchable text of a document
+pub async fn extract_text(path: &Path, mime_type: &str) -> Result<String> {
+    if mime_type == "application/pdf" {
+        return extract_pdf_text(path).await;
+    }
+
+    let bytes = tokio::fs::read(path)
+        .await
+        .with_context(|| format!("Failed to read {:?}", path))?;
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+/// Extract the text of all pages of a PDF with Ghostscript
+async fn extract_pdf_text(path: &Path) -> Result<String> {
+    let output = Command::new("gs")
+        .args(["-q", "-dNOPAUSE", "-dBATCH", "-dSAFER", "-sDEVICE=txtwrite"])
+        .arg("-sOutputFile=-")
+        .arg(path)
+        .output()
+        .await
+        .context("Failed to run Ghostscript (is it installed?)")?;
+
+    if !output.status.success() {
+        anyhow::bail!(
+            "Ghostscript text extraction failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Cut `text` to at most `max_bytes` on a character boundary
+fn truncate_text(text: &str, max_bytes: usize) -> &str {
+    if text.len() <= max_bytes {
+        return text;
+    }
+    let mut end = max_bytes;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    &text[..end]
+}
+
+/// Store `text` as the indexed body of a media item
+pub async fn set_document_text(
+    repo: &dyn MediaRepository,
+    media_id: i32,
+    text: &str,
+) -> Result<()> {
+    repo.set_media_text(media_id, truncate_text(text, MAX_INDEXED_TEXT_BYTES))
+        .await
+        .map_err(anyhow::Error::msg)
+        .context("Failed to store document text")
+}
+
+/// Extract and index the text of an uploaded document
+pub async fn index_document(
+    repo: &dyn MediaRepository,
+    media_id: i32,
+    path: &Path,
+    mime_type: &str,
+) -> Result<()> {
+    let text = extract_text(path, mime_type).await?;
+    set_document_text(repo, media_id, &text).await
+}
+
+/// Index a document's text, logging the outcome
+///
+/// Failures are logged only: the document stays searchable by its metadata.
+pub async fn index_document_logged(
+    repo: &dyn MediaRepository,
+    media_id: i32,
+    path: &Path,
+    mime_type: &str,
+) {
+    match index_document(repo, media_id, path, mime_type).await {
+        Ok(()) => info!("Indexed document text for media {}", media_id),
+        Err(e) => error!(
+            "Failed to index document text for media {}: {:?}",
+            media_id, e
+        ),
+    }
+}
+
+/// Spawn async background task to index a document's text
+///
+/// Failures are logged only: the document stays searchable by its metadata.
+pub fn spawn_document_indexing(
+    repo: Arc<dyn MediaRepository>,
+    media_id: i32,
+    path: PathBuf,
+    mime_type: String,
+) {
+    tokio::spawn(async move {
+        index_document_logged(repo.as_ref(), media_id, &path, &mime_type).await;
+    });
+}
+
+/// Index the text of documents uploaded before text indexing existed
+///
+/// Documents without readable text are stored with empty text so they are
+/// not picked up again. Returns the number of documents processed.
+pub async fn backfill_document_text(
+    repo: &dyn MediaRepository,
+    user_storage: &UserStorageManager,
+) -> Result<usize> {
+    let mut processed = 0;
+    loop {
+        let batch = repo
+            .list_documents_without_text(BACKFILL_BATCH)
+            .await
+            .map_err(anyhow::Error::msg)?;
+        if batch.is_empty() {
+            return Ok(processed);
+        }
+
+        for doc in batch {
+            let text = match document_text(user_storage, &doc).await {
+                Ok(text) => text,
+                Err(e) => {
+                    warn!("Cannot index text of document {}: {:?}", doc.id, e);
+                    String::new()
+                }
+            };
+            set_document_text(repo, doc.id, &text).await?;
+            processed += 1;
+        }
+    }
+}
+
+async fn document_text(
+    user_storage: &UserStorageManager,
+    doc: &UnindexedDocument,
+) -> Result<String> {
+    if !is_indexable(&doc.mime_type) {
+        return Ok(String::new());
+    }
+    let vault_id = doc.vault_id.as_deref().context("Document has no vault")?;
+    let path = user_storage
+        .fetch_media_file(vault_id, MediaType::Document, &doc.filename)
+        .await
+        .with_context(|| format!("File not found: {}", doc.filename))?;
+    extract_text(&path, &doc.mime_type).await
+}
+
+/// Spawn async background task running [`backfill_document_text`]
+pub fn spawn_text_backfill(repo: Arc<dyn MediaRepository>, user_storage: UserStorageManager) {
+    tokio::spawn(async move {
+        match backfill_document_text(repo.as_ref(), &user_storage).await {
+            Ok(0) => {}
+            Ok(count) => info!("Indexed text of {} existing document(s)", count),
+            Err(e) => error!("Document text backfill failed: {:?}", e),
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_indexable() {
+        assert!(is_indexable("application/pdf"));
+        assert!(is_indexable("text/markdown"));
+        assert!(is_indexable("text/csv"));
+        assert!(is_indexable("application/json"));
+        assert!(!is_indexable("application/xml"));
+        assert!(!is_indexable("image/png"));
+    }
+
+    #[test]
+    fn test_truncate_text_on_char_boundary() {
+        assert_eq!(truncate_text("hello", 10), "hello");
+        assert_eq!(truncate_text("hello", 3), "hel");
+        // "é" is two bytes; cutting inside it drops the whole character
+        assert_eq!(truncate_text("café", 4), "caf");
+    }
+}
@@ -0,0 +1,488 @@
+//! Responsive image derivatives
+//!
+//! Resized / re-encoded copies of an image, generated on first request and
+//! cached on local disk under the vault thumbnails directory:
+//! `thumbnails/images/derived/{slug}/{key}.{ext}`.
+//!
+//! Only allowlisted sizes and qualities are accepted, so the number of cached
+//! files per image is bounded and the cache can't be filled by varying the
+//! query string.
+
+use access_control::{Permission, SignedUrlParams};
+use anyhow::{Context, Result};
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::Response,
+};
+use common::storage::MediaType;
+use image::imageops::FilterType;
+use image::{DynamicImage, GenericImageView, ImageEncoder};
+use serde::Deserialize;
+use std::path::{Path as FsPath, PathBuf};
+use tower_sessions::Session;
+use tracing::{debug, error, info, warn};
+
+use crate::routes::MediaManagerState;
+use crate::serve::{check_media_access, AccessResource};
+
+/// Allowed values for `w` and `h`, in pixels
+pub const DERIVATIVE_SIZES: &[u32] = &[
+    64, 128, 160, 200, 240, 320, 400, 480, 640, 800, 960, 1280, 1600, 1920, 2560,
+];
+
+/// Widths listed in the `srcset` of image cards
+pub const SRCSET_WIDTHS: &[u32] = &[320, 640, 960, 1280];
+
+/// Allowed values for `q` (JPEG and AVIF; WebP derivatives are lossless)
+pub const DERIVATIVE_QUALITIES: &[u8] = &[40, 50, 60, 70, 75, 80, 85, 90, 95];
+
+/// Quality used when `q` is omitted
+pub const DEFAULT_QUALITY: u8 = 80;
+
+/// AVIF encoder speed (1 = slowest/smallest … 10 = fastest)
+const AVIF_SPEED: u8 = 8;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Fit {
+    /// Scale down to fit within the box, keeping the aspect ratio
+    #[default]
+    Contain,
+    /// Scale and crop to fill the box exactly
+    Cover,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Format {
+    #[default]
+    Webp,
+    Avif,
+    Jpeg,
+}
+
+impl Format {
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Webp => "webp",
+            Self::Avif => "avif",
+            Self::Jpeg => "jpg",
+        }
+    }
+
+    pub fn content_type(self) -> &'static str {
+        match self {
+            Self::Webp => "image/webp",
+            Self::Avif => "image/avif",
+            Self::Jpeg => "image/jpeg",
+        }
+    }
+}
+
+/// Query string of the derivative endpoint
+#[derive(Debug, Deserialize)]
+pub struct DerivativeQuery {
+    pub w: Option<u32>,
+    pub h: Option<u32>,
+    #[serde(default)]
+    pub fit: Fit,
+    #[serde(default)]
+    pub fmt: Format,
+    pub q: Option<u8>,
+    pub code: Option<String>,
+}
+
+/// A validated derivative request
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DerivativeSpec {
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub fit: Fit,
+    pub format: Format,
+    pub quality: u8,
+}
+
+impl DerivativeSpec {
+    /// Validate query parameters against the allowlists
+    pub fn from_query(query: &DerivativeQuery) -> Result<Self, String> {
+        for (name, value) in [("w", query.w), ("h", query.h)] {
+            if let Some(value) = value {
+                if !DERIVATIVE_SIZES.contains(&value) {
+                    return Err(format!(
+                        "{} must be one of {}",
+                        name,
+                        join_values(DERIVATIVE_SIZES)
+                    ));
+                }
+            }
+        }
+        let quality = query.q.unwrap_or(DEFAULT_QUALITY);
+        if !DERIVATIVE_QUALITIES.contains(&quality) {
+            return Err(format!(
+                "q must be one of {}",
+                join_values(DERIVATIVE_QUALITIES)
+            ));
+        }
+
+        Ok(Self {
+            width: query.w,
+            height: query.h,
+            fit: query.fit,
+            format: query.fmt,
+            // WebP is encoded losslessly; don't cache one copy per quality
+            quality: if query.fmt == Format::Webp {
+                DEFAULT_QUALITY
+            } else {
+                quality
+            },
+        })
+    }
+
+    /// Cache file name, unique per parameter set
+    pub fn cache_name(&self) -> String {
+        let dim = |v: Option<u32>| v.map_or("auto".to_string(), |v| v.to_string());
+        let fit = match self.fit {
+            Fit::Contain => "contain",
+            Fit::Cover => "cover",
+        };
+        let quality = match self.format {
+            Format::Webp => String::new(),
+            _ => format!("_q{}", self.quality),
+        };
+        format!(
+            "w{}_h{}_{}{}.{}",
+            dim(self.width),
+            dim(self.height),
+            fit,
+            quality,
+            self.format.extension()
+        )
+    }
+}
+
+fn join_values<T: ToString>(values: &[T]) -> String {
+    values
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// URL of a width-bounded WebP derivative
+pub fn derivative_url(slug: &str, width: u32) -> String {
+    format!("/media/{}/image?w={}", slug, width)
+}
+
+/// `srcset` value listing derivatives at [`SRCSET_WIDTHS`]
+pub fn srcset(slug: &str) -> String {
+    SRCSET_WIDTHS
+        .iter()
+        .map(|w| format!("{} {}w", derivative_url(slug, *w), w))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Directory holding the cached derivatives of one image
+pub fn cache_dir(state: &MediaManagerState, vault_id: &str, slug: &str) -> PathBuf {
+    state
+        .user_storage
+        .vault_thumbnails_dir(vault_id, MediaType::Image)
+        .join("derived")
+        .join(slug)
+}
+
+/// Remove all cached derivatives of an image
+pub async fn purge_cache(state: &MediaManagerState, vault_id: &str, slug: &str) {
+    let dir = cache_dir(state, vault_id, slug);
+    match tokio::fs::remove_dir_all(&dir).await {
+        Ok(()) => debug!("Purged derivative cache {:?}", dir),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => warn!("Failed to purge derivative cache {:?}: {}", dir, e),
+    }
+}
+
+/// Target size for `spec`, never larger than the source
+fn target_size(source: (u32, u32), spec: &DerivativeSpec) -> (u32, u32) {
+    let (sw, sh) = source;
+    match (spec.width, spec.height) {
+        (None, None) => (sw, sh),
+        (Some(w), None) => (w.min(sw), u32::MAX),
+        (None, Some(h)) => (u32::MAX, h.min(sh)),
+        (Some(w), Some(h)) if spec.fit == Fit::Cover => {
+            // Shrink the box, keeping its aspect ratio, until it fits the source
+            let scale = (sw as f64 / w as f64).min(sh as f64 / h as f64).min(1.0);
+            (
+                ((w as f64 * scale).round() as u32).max(1),
+                ((h as f64 * scale).round() as u32).max(1),
+            )
+        }
+        (Some(w), Some(h)) => (w.min(sw), h.min(sh)),
+    }
+}
+
+/// Resize and encode one derivative
+pub fn render(img: &DynamicImage, spec: &DerivativeSpec) -> Result<Vec<u8>> {
+    let (width, height) = target_size(img.dimensions(), spec);
+    let cover = spec.fit == Fit::Cover && spec.width.is_some() && spec.height.is_some();
+    let resized = if (width, height) == img.dimensions() {
+        img.clone()
+    } else if cover {
+        img.resize_to_fill(width, height, FilterType::Lanczos3)
+    } else {
+        img.resize(width, height, FilterType::Lanczos3)
+    };
+
+    let mut data = Vec::new();
+    match spec.format {
+        Format::Webp => {
+            let rgba = resized.to_rgba8();
+            image::codecs::webp::WebPEncoder::new_lossless(&mut data)
+                .write_image(
+                    &rgba,
+                    rgba.width(),
+                    rgba.height(),
+                    image::ExtendedColorType::Rgba8,
+                )
+                .context("WebP encoding failed")?;
+        }
+        Format::Avif => {
+            let rgba = resized.to_rgba8();
+            image::codecs::avif::AvifEncoder::new_with_speed_quality(
+                &mut data,
+                AVIF_SPEED,
+                spec.quality,
+            )
+            .write_image(
+                &rgba,
+                rgba.width(),
+                rgba.height(),
+                image::ExtendedColorType::Rgba8,
+            )
+            .context("AVIF encoding failed")?;
+        }
+        Format::Jpeg => {
+            // JPEG has no alpha channel
+            let rgb = resized.to_rgb8();
+            image::codecs::jpeg::JpegEncoder::new_with_quality(&mut data, spec.quality)
+                .write_image(
+                    &rgb,
+                    rgb.width(),
+                    rgb.height(),
+                    image::ExtendedColorType::Rgb8,
+                )
+                .context("JPEG encoding failed")?;
+        }
+    }
+    Ok(data)
+}
+
+/// Generate a derivative of `source` into `target`
+async fn generate(source: PathBuf, target: PathBuf, spec: DerivativeSpec) -> Result<()> {
+    let data = tokio::task::spawn_blocking(move || -> Result<Vec<u8>> {
+        let img = image::open(&source).with_context(|| format!("Failed to decode {:?}", source))?;
+        render(&img, &spec)
+    })
+    .await
+    .context("Derivative task panicked")??;
+
+    if let Some(parent) = target.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    // Write under a unique name and rename, so concurrent requests for the
+    // same derivative never serve a partial file
+    let temp = target.with_extension(format!("{}.tmp", uuid::Uuid::new_v4()));
+    tokio::fs::write(&temp, &data).await?;
+    if let Err(e) = tokio::fs::rename(&temp, &target).await {
+        let _ = tokio::fs::remove_file(&temp).await;
+        return Err(e.into());
+    }
+    Ok(())
+}
+
+/// Serve a resized / re-encoded image
+/// GET /media/{slug}/image?w=&h=&fit=cover|contain&fmt=webp|avif|jpeg&q=
+pub async fn serve_image_derivative(
+    State(state): State<MediaManagerState>,
+    session: Session,
+    Path(slug): Path<String>,
+    Query(query): Query<DerivativeQuery>,
+    Query(signed): Query<SignedUrlParams>,
+    headers: HeaderMap,
+) -> Result<Response, (StatusCode, String)> {
+    let spec = DerivativeSpec::from_query(&query).map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+    let status_only = |status: StatusCode| (status, String::new());
+
+    let info = state
+        .repo
+        .get_image_for_serving(&slug)
+        .await
+        .map_err(|e| {
+            error!("Database error: {}", e);
+            status_only(StatusCode::INTERNAL_SERVER_ERROR)
+        })?
+        .ok_or(status_only(StatusCode::NOT_FOUND))?;
+    let vault_id = info
+        .vault_id
+        .clone()
+        .ok_or(status_only(StatusCode::INTERNAL_SERVER_ERROR))?;
+
+    let resource = AccessResource {
+        resource_type: access_control::ResourceType::Image,
+        id: info.id,
+        owner_id: info.user_id.as_deref(),
+        is_public: info.is_public != 0,
+        signed_permission: Permission::Read,
+    };
+    check_media_access(&state, &session, &headers, resource, &vault_id, &signed, query.code.clone())
+        .await
+        .map_err(status_only)?;
+
+    let source = state
+        .user_storage
+        .find_stored_media_file(&vault_id, MediaType::Image, &info.filename)
+        .await
+        .ok_or(status_only(StatusCode::NOT_FOUND))?;
+
+    // Vector images scale by themselves
+    if info.mime_type == "image/svg+xml" {
+        return serve_cached(&state, &source, &info.mime_type, &headers)
+            .await
+            .map(|mut response| {
+                response.headers_mut().insert(
+                    "Content-Security-Policy",
+                    HeaderValue::from_static("default-src 'none'; style-src 'unsafe-inline'"),
+                );
+                response
+            });
+    }
+
+    let target = cache_dir(&state, &vault_id, &slug).join(spec.cache_name());
+    if !target.is_file() {
+        let local_source = state
+            .user_storage
+            .fetch_local(&source)
+            .await
+            .ok_or(status_only(StatusCode::NOT_FOUND))?;
+        generate(local_source, target.clone(), spec)
+            .await
+            .map_err(|e| {
+                error!("Failed to generate derivative of {}: {:#}", slug, e);
+                status_only(StatusCode::INTERNAL_SERVER_ERROR)
+            })?;
+        info!("Generated image derivative {}/{}", slug, spec.cache_name());
+    }
+
+    serve_cached(&state, &target, spec.format.content_type(), &headers).await
+}
+
+async fn serve_cached(
+    state: &MediaManagerState,
+    path: &FsPath,
+    content_type: &str,
+    headers: &HeaderMap,
+) -> Result<Response, (StatusCode, String)> {
+    let mut response =
+        common::range::serve_stored_file(&state.user_storage, path, content_type, headers)
+            .await
+            .map_err(|e| {
+                error!("Failed to open file: {}", e);
+                (StatusCode::INTERNAL_SERVER_ERROR, String::new())
+            })?;
+
+    let response_headers = response.headers_mut();
+    response_headers.insert(
+        header::CACHE_CONTROL,
+        HeaderValue::from_static("public, max-age=31536000"),
+    );
+    response_headers.insert(
+        header::ACCESS_CONTROL_ALLOW_ORIGIN,
+        HeaderValue::from_static("*"),
+    );
+    response_headers.insert(
+        "X-Content-Type-Options",
+        HeaderValue::from_static("nosniff"),
+    );
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn query(w: Option<u32>, h: Option<u32>, fmt: Format, q: Option<u8>) -> DerivativeQuery {
+        DerivativeQuery {
+            w,
+            h,
+            fit: Fit::Cover,
+            fmt,
+            q,
+            code: None,
+        }
+    }
+
+    #[test]
+    fn test_rejects_values_outside_allowlist() {
+        assert!(DerivativeSpec::from_query(&query(Some(333), None, Format::Webp, None)).is_err());
+        assert!(
+            DerivativeSpec::from_query(&query(None, Some(10_000), Format::Jpeg, None)).is_err()
+        );
+        assert!(
+            DerivativeSpec::from_query(&query(Some(320), None, Format::Jpeg, Some(81))).is_err()
+        );
+        assert!(
+            DerivativeSpec::from_query(&query(Some(320), Some(200), Format::Avif, Some(60)))
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_cache_name_per_parameter_set() {
+        let jpeg =
+            DerivativeSpec::from_query(&query(Some(640), None, Format::Jpeg, Some(70))).unwrap();
+        assert_eq!(jpeg.cache_name(), "w640_hauto_cover_q70.jpg");
+
+        // Quality doesn't apply to lossless WebP
+        let a =
+            DerivativeSpec::from_query(&query(Some(640), None, Format::Webp, Some(50))).unwrap();
+        let b =
+            DerivativeSpec::from_query(&query(Some(640), None, Format::Webp, Some(90))).unwrap();
+        assert_eq!(a.cache_name(), b.cache_name());
+    }
+
+    #[test]
+    fn test_render_never_upscales() {
+        let img = DynamicImage::new_rgb8(300, 200);
+        let spec = DerivativeSpec::from_query(&query(Some(640), None, Format::Jpeg, None)).unwrap();
+        let data = render(&img, &spec).unwrap();
+        let out = image::load_from_memory(&data).unwrap();
+        assert_eq!(out.dimensions(), (300, 200));
+
+        // A cover box larger than the source shrinks but keeps its shape
+        let spec =
+            DerivativeSpec::from_query(&query(Some(640), Some(200), Format::Jpeg, None)).unwrap();
+        let out = image::load_from_memory(&render(&img, &spec).unwrap()).unwrap();
+        assert_eq!(out.dimensions(), (300, 94));
+    }
+
+    #[test]
+    fn test_render_cover_and_contain() {
+        let img = DynamicImage::new_rgba8(800, 400);
+        let mut spec =
+            DerivativeSpec::from_query(&query(Some(200), Some(200), Format::Webp, None)).unwrap();
+        let out = image::load_from_memory(&render(&img, &spec).unwrap()).unwrap();
+        assert_eq!(out.dimensions(), (200, 200));
+
+        spec.fit = Fit::Contain;
+        let out = image::load_from_memory(&render(&img, &spec).unwrap()).unwrap();
+        assert_eq!(out.dimensions(), (200, 100));
+    }
+
+    #[test]
+    fn test_srcset() {
+        assert_eq!(
+            srcset("cat").split(", ").next(),
+            Some("/media/cat/image?w=320 320w")
+        );
+    }
+}
@@ -1,12 +1,13 @@
 //! Media detail page handler
-//! Unified detail view for images, videos, and documents
+//! Unified detail view for images, videos, audio, and documents
 
 use askama::Template;
 use axum::{
     extract::{Path, Query, State},
-    http::StatusCode,
+    http::{HeaderMap, StatusCode},
     response::{Html, IntoResponse, Redirect, Response},
 };
+use db::media::{AudioDetails, LiveSession, PhotoDetails, SubtitleTrack, VideoChapter};
 use serde::Deserialize;
 use tower_sessions::Session;
 use tracing::{error, info};
@@ -53,12 +54,114 @@ impl MediaDetail {
     }
 }
 
+/// Format seconds as "4:05" or "1:02:03"
+fn format_clock(secs: f64) -> String {
+    let total = secs.round() as u64;
+    let (h, m, s) = (total / 3600, (total % 3600) / 60, total % 60);
+    if h > 0 {
+        format!("{}:{:02}:{:02}", h, m, s)
+    } else {
+        format!("{}:{:02}", m, s)
+    }
+}
+
+/// A video chapter, formatted for display
+#[derive(Debug)]
+pub struct ChapterInfo {
+    pub start_time: f64,
+    /// e.g. "4:05" or "1:02:03"
+    pub start: String,
+    pub title: String,
+}
+
+impl From<VideoChapter> for ChapterInfo {
+    fn from(c: VideoChapter) -> Self {
+        Self {
+            start_time: c.start_time,
+            start: format_clock(c.start_time),
+            title: c.title,
+        }
+    }
+}
+
+/// Probed audio details, formatted for display
+#[derive(Debug, Default)]
+pub struct AudioInfo {
+    /// e.g. "4:05" or "1:02:03"
+    pub duration: Option<String>,
+    /// e.g. "mono", "stereo"
+    pub channels: Option<String>,
+    pub bitrate_kbps: Option<i64>,
+    pub sample_rate: Option<i64>,
+    pub codec: Option<String>,
+}
+
+impl From<AudioDetails> for AudioInfo {
+    fn from(d: AudioDetails) -> Self {
+        let duration = d.duration.map(format_clock);
+        let channels = d.channels.map(|n| match n {
+            1 => "mono".to_string(),
+            2 => "stereo".to_string(),
+            n => format!("{} channels", n),
+        });
+        Self {
+            duration,
+            channels,
+            bitrate_kbps: d.bitrate.map(|b| b / 1000),
+            sample_rate: d.sample_rate,
+            codec: d.codec,
+        }
+    }
+}
+
+/// The live session a video was recorded from, formatted for display
+#[derive(Debug)]
+pub struct LiveInfo {
+    /// MediaMTX path, played through `/hls/{path}/index.m3u8` while live
+    pub stream_path: String,
+    pub started_at: String,
+    pub ended_at: Option<String>,
+    pub peak_viewers: i64,
+    pub total_viewers: i64,
+}
+
+impl From<LiveSession> for LiveInfo {
+    fn from(s: LiveSession) -> Self {
+        Self {
+            stream_path: s.stream_path,
+            started_at: s.started_at,
+            ended_at: s.ended_at,
+            peak_viewers: s.peak_viewers,
+            total_viewers: s.total_viewers,
+        }
+    }
+}
+
 #[derive(Template)]
 #[template(path = "media/detail.html")]
 pub struct MediaDetailTemplate {
     pub authenticated: bool,
     pub media: MediaDetail,
     pub access_code: Option<String>,
+    pub is_owner: bool,
+    pub subtitles: Vec<SubtitleTrack>,
+    pub chapters: Vec<ChapterInfo>,
+    /// Set for processed audio items
+    pub audio: Option<AudioInfo>,
+    /// EXIF capture metadata of images, shown to the owner only
+    pub photo: Option<PhotoDetails>,
+    /// Set for videos recorded from a live stream
+    pub live: Option<LiveInfo>,
+}
+
+impl MediaDetailTemplate {
+    /// MediaMTX path to play while the video is still on air
+    pub fn live_stream_path(&self) -> Option<&str> {
+        self.live
+            .as_ref()
+            .filter(|_| self.media.status == db::media::MEDIA_STATUS_LIVE)
+            .map(|live| live.stream_path.as_str())
+    }
 }
 
 /// Media detail page handler
@@ -67,6 +170,7 @@ pub async fn media_detail_handler(
     State(state): State<MediaManagerState>,
     Path(slug): Path<String>,
     Query(query): Query<AccessCodeQuery>,
+    headers: HeaderMap,
 ) -> Result<Response, (StatusCode, String)> {
     let authenticated: bool = session
         .get("authenticated")
@@ -110,11 +214,13 @@ pub async fn media_detail_handler(
     let resource_type = match media_type.as_str() {
         "video" => common::ResourceType::Video,
         "image" => common::ResourceType::Image,
+        "audio" => common::ResourceType::Audio,
         "document" => common::ResourceType::File,
         _ => common::ResourceType::Image, // Default fallback
     };
 
-    let mut context = access_control::AccessContext::new(resource_type, media_id);
+    let mut context = access_control::AccessContext::new(resource_type, media_id)
+        .with_request_headers(&headers);
     if let Some(uid) = user_id.clone() {
         context = context.with_user(uid);
     }
@@ -192,6 +298,83 @@ pub async fn media_detail_handler(
         return Ok(Redirect::to(&redirect_url).into_response());
     }
 
+    // Documents are counted by the viewer page they redirect to
+    if !state
+        .access_control
+        .record_key_view(&decision, query.code.as_deref())
+        .await
+    {
+        return Err((StatusCode::GONE, "This access code has reached its view limit".to_string()));
+    }
+
+    let is_owner = match &user_id {
+        Some(uid) => matches!(
+            state.repo.get_media_id_by_slug_and_user(&slug, uid).await,
+            Ok(Some(_))
+        ),
+        None => false,
+    };
+
+    let subtitles = if media_type == "video" {
+        match state.repo.list_subtitles(media_id as i64).await {
+            Ok(tracks) => tracks,
+            Err(e) => {
+                error!("Error fetching subtitles: {}", e);
+                Vec::new()
+            }
+        }
+    } else {
+        Vec::new()
+    };
+
+    let chapters = if media_type == "video" {
+        match state.repo.list_chapters(media_id as i64).await {
+            Ok(chapters) => chapters.into_iter().map(ChapterInfo::from).collect(),
+            Err(e) => {
+                error!("Error fetching chapters: {}", e);
+                Vec::new()
+            }
+        }
+    } else {
+        Vec::new()
+    };
+
+    let live = if media_type == "video" {
+        match state.repo.get_live_session(media_id as i64).await {
+            Ok(session) => session.map(LiveInfo::from),
+            Err(e) => {
+                error!("Error fetching live session: {}", e);
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    let audio = if media_type == "audio" {
+        match state.repo.get_audio_details(media_id as i64).await {
+            Ok(details) => details.map(AudioInfo::from),
+            Err(e) => {
+                error!("Error fetching audio details: {}", e);
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    let photo = if media_type == "image" && is_owner {
+        match state.repo.get_photo_details(media_id as i64).await {
+            Ok(details) => details,
+            Err(e) => {
+                error!("Error fetching photo details: {}", e);
+                None
+            }
+        }
+    } else {
+        None
+    };
+
     let media = MediaDetail {
         id: media_id,
         slug: row.slug,
@@ -222,6 +405,12 @@ pub async fn media_detail_handler(
         authenticated,
         media,
         access_code: query.code.clone(),
+        is_owner,
+        subtitles,
+        chapters,
+        audio,
+        photo,
+        live,
     };
 
     match template.render() {
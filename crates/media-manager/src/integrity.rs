@@ -0,0 +1,407 @@
+//! Storage integrity verification
+//!
+//! Every upload records the SHA-256 of the stored primary file
+//! (`media_items.file_hash`). The verifier re-hashes the files on disk and
+//! flags items whose bytes no longer match (`mismatch`) or whose file is gone
+//! (`missing`), like a filesystem `fsck`. Items stored before hashing existed
+//! get their current hash recorded as the baseline on their first check.
+//! Files offloaded to a remote storage backend are hashed from the backend.
+//!
+//! A full pass runs once a day in the background; platform admins can view
+//! the report and start a pass through the admin API.
+
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::Json,
+};
+use chrono::{DateTime, Utc};
+use common::storage::{MediaType, UserStorageManager};
+use db::media::{
+    IntegrityCandidate, MediaRepository, INTEGRITY_MISMATCH, INTEGRITY_MISSING, INTEGRITY_OK,
+};
+use futures::StreamExt;
+use media_core::{sha256_file, ContentHasher, MediaError};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use tower_sessions::Session;
+use tracing::{error, info, warn};
+
+use crate::routes::MediaManagerState;
+
+/// Items loaded per verification batch
+const VERIFY_BATCH: i64 = 100;
+
+/// How often a full verification pass runs in the background
+const VERIFY_INTERVAL_SECS: u64 = 24 * 60 * 60;
+
+/// Default number of failed items listed in the report
+const DEFAULT_ISSUE_LIMIT: i64 = 100;
+
+/// Totals of one verification pass
+#[derive(Debug, Clone, Serialize)]
+pub struct VerificationRun {
+    pub started_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+    pub checked: u64,
+    pub ok: u64,
+    pub mismatch: u64,
+    pub missing: u64,
+    /// Items that could not be read (permissions, I/O errors)
+    pub failed: u64,
+}
+
+impl VerificationRun {
+    fn new() -> Self {
+        Self {
+            started_at: Utc::now(),
+            finished_at: None,
+            checked: 0,
+            ok: 0,
+            mismatch: 0,
+            missing: 0,
+            failed: 0,
+        }
+    }
+}
+
+/// Runs verification passes, one at a time, and keeps the last result
+#[derive(Default)]
+pub struct IntegrityVerifier {
+    running: AtomicBool,
+    last_run: Mutex<Option<VerificationRun>>,
+}
+
+impl IntegrityVerifier {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether a pass is in progress
+    pub fn is_running(&self) -> bool {
+        self.running.load(Ordering::SeqCst)
+    }
+
+    /// Totals of the last completed pass since startup
+    pub fn last_run(&self) -> Option<VerificationRun> {
+        self.last_run.lock().unwrap().clone()
+    }
+
+    /// Start a pass in the background unless one is already running
+    ///
+    /// Returns false if a pass is in progress.
+    pub fn spawn_run(
+        self: &Arc<Self>,
+        repo: Arc<dyn MediaRepository>,
+        user_storage: UserStorageManager,
+    ) -> bool {
+        if self.running.swap(true, Ordering::SeqCst) {
+            return false;
+        }
+
+        let verifier = self.clone();
+        tokio::spawn(async move {
+            match verify_all(repo.as_ref(), &user_storage).await {
+                Ok(run) => {
+                    info!(
+                        event = "integrity_check_complete",
+                        checked = run.checked,
+                        mismatch = run.mismatch,
+                        missing = run.missing,
+                        failed = run.failed,
+                        "Storage integrity check finished"
+                    );
+                    *verifier.last_run.lock().unwrap() = Some(run);
+                }
+                Err(e) => error!("Storage integrity check failed: {:?}", e),
+            }
+            verifier.running.store(false, Ordering::SeqCst);
+        });
+        true
+    }
+}
+
+/// Location of an item's primary file
+///
+/// Videos and audio live in a per-item directory (`{slug}/{filename}`),
+/// images and documents directly in the media type directory.
+pub fn stored_file_path(user_storage: &UserStorageManager, item: &IntegrityCandidate) -> PathBuf {
+    let (media_type, filename) = match item.media_type.as_str() {
+        "video" => (MediaType::Video, format!("{}/{}", item.slug, item.filename)),
+        "audio" => (MediaType::Audio, format!("{}/{}", item.slug, item.filename)),
+        "image" => (MediaType::Image, item.filename.clone()),
+        _ => (MediaType::Document, item.filename.clone()),
+    };
+    user_storage.get_media_file_path(&item.vault_id, media_type, &filename)
+}
+
+/// Hash a file that has been offloaded to the storage backend
+async fn sha256_stored(user_storage: &UserStorageManager, path: &Path) -> Result<String, MediaError> {
+    let not_found = || MediaError::FileNotFound {
+        path: path.to_string_lossy().to_string(),
+    };
+    let key = user_storage.object_key(path).ok_or_else(not_found)?;
+    if user_storage.backend().local_path(&key).as_deref() == Some(path) {
+        // Local backend: the file on disk is the stored object
+        return Err(not_found());
+    }
+
+    let mut stream = user_storage.backend().get_range(&key, None).await?;
+    let mut hasher = ContentHasher::new();
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk.map_err(|e| MediaError::storage(format!("Failed to read object: {}", e)))?;
+        hasher.update(&chunk);
+    }
+    Ok(hasher.finalize())
+}
+
+/// Compare an item's stored file against its recorded hash
+///
+/// Returns the status and the hash of the bytes on disk (if readable).
+pub async fn verify_item(
+    user_storage: &UserStorageManager,
+    item: &IntegrityCandidate,
+) -> Result<(&'static str, Option<String>), MediaError> {
+    let path = stored_file_path(user_storage, item);
+    let hashed = match sha256_file(&path).await {
+        Err(MediaError::FileNotFound { .. }) => sha256_stored(user_storage, &path).await,
+        result => result,
+    };
+    let actual = match hashed {
+        Ok(hash) => hash,
+        Err(MediaError::FileNotFound { .. }) => return Ok((INTEGRITY_MISSING, None)),
+        Err(e) => return Err(e),
+    };
+
+    let status = match &item.file_hash {
+        Some(expected) if *expected != actual => INTEGRITY_MISMATCH,
+        _ => INTEGRITY_OK,
+    };
+    Ok((status, Some(actual)))
+}
+
+/// Verify the stored files of all active media items
+pub async fn verify_all(
+    repo: &dyn MediaRepository,
+    user_storage: &UserStorageManager,
+) -> anyhow::Result<VerificationRun> {
+    let mut run = VerificationRun::new();
+    let mut after_id = 0;
+
+    loop {
+        let batch = repo
+            .list_media_for_verification(after_id, VERIFY_BATCH)
+            .await
+            .map_err(anyhow::Error::msg)?;
+        let Some(last) = batch.last() else {
+            break;
+        };
+        after_id = last.id;
+
+        for item in batch {
+            run.checked += 1;
+            let (status, actual) = match verify_item(user_storage, &item).await {
+                Ok(result) => result,
+                Err(e) => {
+                    warn!("Cannot verify media {} ({}): {}", item.id, item.slug, e);
+                    run.failed += 1;
+                    continue;
+                }
+            };
+
+            match status {
+                INTEGRITY_MISMATCH => {
+                    warn!(
+                        event = "integrity_mismatch",
+                        media_id = item.id,
+                        slug = %item.slug,
+                        expected = ?item.file_hash,
+                        actual = ?actual,
+                        "Stored file does not match its checksum"
+                    );
+                    run.mismatch += 1;
+                }
+                INTEGRITY_MISSING => {
+                    warn!(
+                        event = "integrity_missing",
+                        media_id = item.id,
+                        slug = %item.slug,
+                        "Stored file is missing"
+                    );
+                    run.missing += 1;
+                }
+                _ => run.ok += 1,
+            }
+
+            repo.record_integrity_check(item.id, status, actual.as_deref())
+                .await
+                .map_err(anyhow::Error::msg)?;
+        }
+    }
+
+    run.finished_at = Some(Utc::now());
+    Ok(run)
+}
+
+/// Spawn the daily verification pass
+///
+/// The first pass runs one interval after startup, so restarts do not
+/// re-read the whole library.
+pub fn start_verification_task(state: MediaManagerState) {
+    tokio::spawn(async move {
+        let period = std::time::Duration::from_secs(VERIFY_INTERVAL_SECS);
+        let mut interval = tokio::time::interval_at(tokio::time::Instant::now() + period, period);
+        loop {
+            interval.tick().await;
+            if !state
+                .integrity
+                .spawn_run(state.repo.clone(), state.user_storage.clone())
+            {
+                info!("Skipping scheduled integrity check: a check is already running");
+            }
+        }
+    });
+}
+
+// ── Admin API ───────────────────────────────────────────────────────
+
+#[derive(Debug, Deserialize)]
+pub struct IntegrityReportQuery {
+    pub limit: Option<i64>,
+}
+
+/// Only the platform admin may view and run integrity checks
+async fn require_platform_admin(session: &Session) -> Result<(), (StatusCode, Json<Value>)> {
+    workspace_core::auth::require_platform_admin(session)
+        .await
+        .map(|_| ())
+        .map_err(|status| {
+            let error = match status {
+                StatusCode::FORBIDDEN => "Admin access required",
+                _ => "Authentication required",
+            };
+            (status, Json(serde_json::json!({ "error": error })))
+        })
+}
+
+/// Storage integrity report
+/// GET /api/admin/media/integrity
+pub async fn get_integrity_report(
+    session: Session,
+    State(state): State<MediaManagerState>,
+    Query(query): Query<IntegrityReportQuery>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    require_platform_admin(&session).await?;
+
+    let db_error = |e: db::DbError| {
+        error!("Database error loading integrity report: {}", e);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({"error": "Database error"})),
+        )
+    };
+
+    let summary = state.repo.get_integrity_summary().await.map_err(db_error)?;
+    let limit = query.limit.unwrap_or(DEFAULT_ISSUE_LIMIT).clamp(1, 1000);
+    let issues = state
+        .repo
+        .list_integrity_issues(limit)
+        .await
+        .map_err(db_error)?;
+
+    Ok(Json(serde_json::json!({
+        "summary": summary,
+        "issues": issues,
+        "running": state.integrity.is_running(),
+        "last_run": state.integrity.last_run(),
+    })))
+}
+
+/// Start a verification pass
+/// POST /api/admin/media/integrity/verify
+pub async fn start_integrity_check(
+    session: Session,
+    State(state): State<MediaManagerState>,
+) -> Result<(StatusCode, Json<Value>), (StatusCode, Json<Value>)> {
+    require_platform_admin(&session).await?;
+
+    if !state
+        .integrity
+        .spawn_run(state.repo.clone(), state.user_storage.clone())
+    {
+        return Err((
+            StatusCode::CONFLICT,
+            Json(serde_json::json!({"error": "An integrity check is already running"})),
+        ));
+    }
+
+    info!("Storage integrity check started by admin");
+    Ok((
+        StatusCode::ACCEPTED,
+        Json(serde_json::json!({"success": true, "message": "Integrity check started"})),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidate(media_type: &str, filename: &str, file_hash: Option<&str>) -> IntegrityCandidate {
+        IntegrityCandidate {
+            id: 1,
+            slug: "item".to_string(),
+            media_type: media_type.to_string(),
+            filename: filename.to_string(),
+            vault_id: "vault-1".to_string(),
+            file_hash: file_hash.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_stored_file_path() {
+        let storage = UserStorageManager::new("/data");
+        let video = stored_file_path(&storage, &candidate("video", "video.mp4", None));
+        assert!(video.ends_with("item/video.mp4"));
+        let image = stored_file_path(&storage, &candidate("image", "item.webp", None));
+        assert!(image.ends_with("item.webp"));
+        assert!(!image.to_string_lossy().contains("item/"));
+    }
+
+    #[tokio::test]
+    async fn test_verify_item() {
+        let dir = std::env::temp_dir().join(format!("integrity-{}", uuid::Uuid::new_v4()));
+        let storage = UserStorageManager::new(&dir);
+        let item = candidate("document", "doc.txt", None);
+        let path = stored_file_path(&storage, &item);
+        tokio::fs::create_dir_all(path.parent().unwrap())
+            .await
+            .unwrap();
+        tokio::fs::write(&path, b"Hello").await.unwrap();
+        let hash = media_core::sha256_hex(b"Hello");
+
+        // No baseline yet: current bytes are accepted and returned as baseline
+        let (status, actual) = verify_item(&storage, &item).await.unwrap();
+        assert_eq!(status, INTEGRITY_OK);
+        assert_eq!(actual.as_deref(), Some(hash.as_str()));
+
+        let item = candidate("document", "doc.txt", Some(&hash));
+        assert_eq!(verify_item(&storage, &item).await.unwrap().0, INTEGRITY_OK);
+
+        tokio::fs::write(&path, b"Hellp").await.unwrap();
+        assert_eq!(
+            verify_item(&storage, &item).await.unwrap().0,
+            INTEGRITY_MISMATCH
+        );
+
+        tokio::fs::remove_file(&path).await.unwrap();
+        assert_eq!(
+            verify_item(&storage, &item).await.unwrap(),
+            (INTEGRITY_MISSING, None)
+        );
+
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+    }
+}
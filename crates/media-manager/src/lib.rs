@@ -1,29 +1,43 @@
 //! Unified Media Manager
 //!
 //! Handles upload, processing, serving, listing, search, and CRUD for all media types
-//! (videos, images, documents).
+//! (videos, images, audio, documents).
 //!
 //! This crate consolidates functionality that was previously split between
 //! media-hub (listing/search/CRUD UI) and media-manager (upload/detail/serving).
 
+pub mod audio;
+pub mod batch;
 pub mod bpmn_view;
+pub mod content_index;
+pub mod derivatives;
 pub mod detail;
 pub mod folder_access;
+pub mod integrity;
 pub mod list;
 pub mod markdown_view;
 pub mod models;
 pub mod pdf_thumbnail;
 pub mod pdf_view;
+pub mod photo;
+pub mod previews;
 pub mod progress;
 pub mod routes;
 pub mod search;
 pub mod serve;
+pub mod signed_urls;
+pub mod subtitles;
 pub mod templates;
+pub mod transcripts;
+pub mod trash;
+pub mod tus;
 pub mod upload;
+pub mod versions;
+pub mod video_edit;
 
 pub use routes::{
-    folder_access_routes, media_routes, media_serving_routes, media_upload_routes,
-    MediaManagerState,
+    folder_access_routes, media_routes, media_serving_routes, media_signing_routes, media_tus_routes,
+    media_upload_routes, MediaManagerState,
 };
 
 /// Version information
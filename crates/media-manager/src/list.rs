@@ -30,7 +30,7 @@ pub struct MediaListQuery {
     #[serde(default)]
     pub q: Option<String>,
 
-    /// Media type filter (video, image, document)
+    /// Media type filter (video, image, audio, document)
     #[serde(default)]
     pub type_filter: Option<String>,
 
@@ -50,7 +50,20 @@ pub struct MediaListQuery {
     #[serde(default)]
     pub group_id: Option<String>,
 
-    /// Sort field
+    /// Camera filter (substring of the EXIF make or model)
+    #[serde(default)]
+    pub camera: Option<String>,
+
+    /// Captured on or after (YYYY-MM-DD)
+    #[serde(default)]
+    pub captured_after: Option<String>,
+
+    /// Captured on or before (YYYY-MM-DD)
+    #[serde(default)]
+    pub captured_before: Option<String>,
+
+    /// Sort field; the default `relevance` ranks search results by match
+    /// quality and lists newest first when there is no search
     #[serde(default = "default_sort_by")]
     pub sort_by: String,
 
@@ -68,7 +81,7 @@ pub struct MediaListQuery {
 }
 
 fn default_sort_by() -> String {
-    "created_at".to_string()
+    "relevance".to_string()
 }
 
 fn default_sort_order() -> String {
@@ -167,6 +180,9 @@ pub async fn list_media_html(
         vault_id: noe(query.vault_id.clone()),
         tag: noe(query.tag.clone()),
         group_id: noe(query.group_id.clone()),
+        camera: noe(query.camera.clone()),
+        captured_after: noe(query.captured_after.clone()),
+        captured_before: noe(query.captured_before.clone()),
         sort_by: query.sort_by.clone(),
         sort_order: query.sort_order.clone(),
         page: query.page,
@@ -205,6 +221,7 @@ pub async fn list_media_html(
             };
 
             // Combine items with their metadata
+            let mut snippets = response.snippets;
             let items_with_metadata: Vec<MediaItemWithMetadata> = response
                 .items
                 .into_iter()
@@ -213,10 +230,12 @@ pub async fn list_media_html(
                     let group_name = item
                         .group_id()
                         .and_then(|gid| groups_map.get(&gid).cloned());
+                    let snippet = snippets.remove(&item.id());
                     MediaItemWithMetadata {
                         item,
                         tags,
                         group_name,
+                        snippet,
                     }
                 })
                 .collect();
@@ -273,6 +292,7 @@ pub async fn list_media_html(
                 sort_order: query.sort_order.clone(),
                 video_count: response.media_type_counts.videos,
                 image_count: response.media_type_counts.images,
+                audio_count: response.media_type_counts.audio,
                 document_count: response.media_type_counts.documents,
                 total_count: response.media_type_counts.total,
                 all_vaults,
@@ -355,6 +375,9 @@ pub async fn list_media_json(
         vault_id: noe(query.vault_id),
         tag: noe(query.tag),
         group_id: noe(query.group_id),
+        camera: noe(query.camera),
+        captured_after: noe(query.captured_after),
+        captured_before: noe(query.captured_before),
         sort_by: query.sort_by,
         sort_order: query.sort_order,
         page: query.page,
@@ -377,8 +400,9 @@ pub async fn list_media_json(
             };
 
             // Serialize then flatten the serde adjacently-tagged enum wrapper
-            // {"type":"MediaItem","data":{...}} → {..., "tags":[...]}
+            // {"type":"MediaItem","data":{...}} → {..., "tags":[...], "snippet":...}
             // This gives JS flat field access: item.slug, item.media_type, item.tags
+            // (snippet is highlighted HTML, set for full-text search results only)
             let mut json_val = serde_json::to_value(&response)
                 .unwrap_or_else(|_| serde_json::json!({"items": []}));
 
@@ -395,6 +419,9 @@ pub async fn list_media_json(
                     }
                     if let Some(map) = item.as_object_mut() {
                         map.insert("tags".to_string(), serde_json::json!(tags));
+                        if let Some(snippet) = response.snippets.get(&item_id) {
+                            map.insert("snippet".to_string(), serde_json::json!(snippet));
+                        }
                     }
                 }
             }
@@ -956,7 +983,9 @@ pub async fn list_user_groups(
     }
 }
 
-/// Delete a media item
+/// Move a media item to the trash
+///
+/// It can be restored from `/media/trash` until the retention period ends.
 pub async fn delete_media(
     State(state): State<MediaManagerState>,
     session: Session,
@@ -1034,116 +1063,26 @@ pub async fn delete_media(
     let filename = media_info.filename;
     let vault_id = media_info.vault_id;
 
-    // Delete tags from database
-    if let Err(e) = state.repo.delete_media_tags_by_slug(&slug).await {
-        warn!("Failed to delete tags for {}: {}", slug, e);
-    }
-
-    // Delete from database
-    match state.repo.delete_media_by_slug(&slug).await {
-        Ok(()) => {
-            // Try to delete physical file/directory if vault_id exists
+    // Move to the trash; files follow once the row is hidden
+    match state.repo.trash_media(&slug, &session_user_id).await {
+        Ok(_) => {
             if let Some(vault_id) = vault_id {
-                let media_type_enum = match media_type.as_str() {
-                    "video" => common::storage::MediaType::Video,
-                    "image" => common::storage::MediaType::Image,
-                    "document" => common::storage::MediaType::Document,
-                    _ => common::storage::MediaType::Document,
-                };
-
-                // For videos, delete the entire directory (contains HLS files, segments, etc.)
-                // For images/documents, delete the single file
-                if media_type == "video" {
-                    // Videos are in subdirectories: {slug}/
-                    let video_path =
-                        state
-                            .user_storage
-                            .find_media_file(&vault_id, media_type_enum, &slug);
-
-                    if let Some(video_dir) = video_path {
-                        if video_dir.is_dir() {
-                            if let Err(e) = tokio::fs::remove_dir_all(&video_dir).await {
-                                warn!(
-                                    "Failed to delete video directory {:?}: {}",
-                                    video_dir, e
-                                );
-                            } else {
-                                info!("Deleted video directory: {:?}", video_dir);
-                            }
-                        } else {
-                            warn!("Video path exists but is not a directory: {:?}", video_dir);
-                        }
-                    } else {
-                        warn!("Video directory not found for slug: {}", slug);
-                    }
-                } else {
-                    // Images and documents are flat files
-                    let file_path = state.user_storage.find_media_file(
-                        &vault_id,
-                        media_type_enum,
-                        &filename,
-                    );
-
-                    if let Some(path) = file_path {
-                        if let Err(e) = tokio::fs::remove_file(&path).await {
-                            warn!("Failed to delete file {:?}: {}", path, e);
-                        } else {
-                            info!("Deleted file: {:?}", path);
-                        }
-                    } else {
-                        warn!("Media file not found: {}", filename);
-                    }
-
-                    // For images, also try to delete original file if it exists
-                    if media_type == "image" {
-                        // Try common original filename patterns
-                        for ext in &["jpg", "jpeg", "png", "webp", "gif", "bmp"] {
-                            let original_filename = format!("{}_original.{}", slug, ext);
-                            if let Some(original_path) = state.user_storage.find_media_file(
-                                &vault_id,
-                                media_type_enum,
-                                &original_filename,
-                            ) {
-                                if let Err(e) = tokio::fs::remove_file(&original_path).await {
-                                    warn!(
-                                        "Failed to delete original file {:?}: {}",
-                                        original_path, e
-                                    );
-                                } else {
-                                    info!("Deleted original image: {:?}", original_path);
-                                }
-                                break; // Found and deleted, stop searching
-                            }
-                        }
-                    }
-                }
-
-                // Delete thumbnail using multi-location fallback
-                let thumb_path =
-                    state
-                        .user_storage
-                        .find_thumbnail(&vault_id, media_type_enum, &slug);
-
-                if let Some(thumb) = thumb_path {
-                    if let Err(e) = tokio::fs::remove_file(&thumb).await {
-                        warn!("Failed to delete thumbnail {:?}: {}", thumb, e);
-                    } else {
-                        info!("Deleted thumbnail: {:?}", thumb);
-                    }
-                }
+                crate::trash::move_files_to_trash(&state, &slug, &media_type, &filename, &vault_id)
+                    .await;
             }
 
-            info!("Deleted media: {}", slug);
+            info!("Moved media to trash: {}", slug);
             (
                 StatusCode::OK,
                 Json(serde_json::json!({
-                    "success": true
+                    "success": true,
+                    "trashed": true
                 })),
             )
                 .into_response()
         }
         Err(e) => {
-            error!("Failed to delete media from database: {}", e);
+            error!("Failed to move media to trash: {}", e);
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 Json(serde_json::json!({
@@ -1156,6 +1095,92 @@ pub async fn delete_media(
     }
 }
 
+/// Remove the stored files of a media item: media file or directory, kept
+/// original, derivative cache and thumbnail
+///
+/// Files are removed from local disk and from the storage backend; failures
+/// are logged.
+pub(crate) async fn remove_media_files(
+    state: &MediaManagerState,
+    slug: &str,
+    media_type: &str,
+    filename: &str,
+    vault_id: &str,
+) {
+    let media_type_enum = match media_type {
+        "video" => common::storage::MediaType::Video,
+        "image" => common::storage::MediaType::Image,
+        "audio" => common::storage::MediaType::Audio,
+        "document" => common::storage::MediaType::Document,
+        _ => common::storage::MediaType::Document,
+    };
+
+    // For videos and audio, delete the entire directory (HLS files,
+    // renditions, waveforms, etc.). For images/documents, delete the single file.
+    if media_type == "video" || media_type == "audio" {
+        // Videos and audio are in subdirectories: {slug}/
+        let video_dir = state
+            .user_storage
+            .vault_nested_media_path(vault_id, media_type_enum, slug);
+
+        if let Err(e) = state.user_storage.remove_stored(&video_dir).await {
+            warn!("Failed to delete video directory {:?}: {}", video_dir, e);
+        } else {
+            info!("Deleted video directory: {:?}", video_dir);
+        }
+    } else {
+        // Images and documents are flat files
+        let file_path = state
+            .user_storage
+            .get_media_file_path(vault_id, media_type_enum, filename);
+
+        if let Err(e) = state.user_storage.remove_stored(&file_path).await {
+            warn!("Failed to delete file {:?}: {}", file_path, e);
+        } else {
+            info!("Deleted file: {:?}", file_path);
+        }
+
+        // For images, also try to delete original file if it exists
+        if media_type == "image" {
+            // Try common original filename patterns
+            for ext in &["jpg", "jpeg", "png", "webp", "gif", "bmp"] {
+                let original_filename = format!("{}_original.{}", slug, ext);
+                if let Some(original_path) = state
+                    .user_storage
+                    .find_stored_media_file(vault_id, media_type_enum, &original_filename)
+                    .await
+                {
+                    if let Err(e) = state.user_storage.remove_stored(&original_path).await {
+                        warn!("Failed to delete original file {:?}: {}", original_path, e);
+                    } else {
+                        info!("Deleted original image: {:?}", original_path);
+                    }
+                    break; // Found and deleted, stop searching
+                }
+            }
+        }
+    }
+
+    if media_type == "image" {
+        crate::derivatives::purge_cache(state, vault_id, slug).await;
+    }
+
+    // Delete thumbnail
+    let thumb = state
+        .user_storage
+        .get_thumbnail_path(vault_id, media_type_enum, slug);
+
+    if let Err(e) = state.user_storage.remove_stored(&thumb).await {
+        warn!("Failed to delete thumbnail {:?}: {}", thumb, e);
+    }
+
+    // Archived versions
+    let versions = state.user_storage.vault_versions_dir(vault_id, slug);
+    if let Err(e) = state.user_storage.remove_stored(&versions).await {
+        warn!("Failed to delete versions {:?}: {}", versions, e);
+    }
+}
+
 // ============================================================================
 // Tag Autocomplete
 // ============================================================================
@@ -3,13 +3,13 @@
 use askama::Template;
 use axum::{
     extract::{Path, Query, State},
-    http::StatusCode,
+    http::{HeaderMap, StatusCode},
     response::Html,
     Json,
 };
 use serde::{Deserialize, Serialize};
 use tower_sessions::Session;
-use tracing::{error, info};
+use tracing::{error, info, warn};
 
 use crate::routes::MediaManagerState;
 
@@ -60,6 +60,7 @@ pub async fn view_markdown_handler(
     State(state): State<MediaManagerState>,
     Path(slug): Path<String>,
     Query(query): Query<MarkdownAccessQuery>,
+    headers: HeaderMap,
 ) -> Result<Html<String>, (StatusCode, String)> {
     let authenticated: bool = session
         .get("authenticated")
@@ -101,7 +102,8 @@ pub async fn view_markdown_handler(
 
     // Check access using AccessControlService (supports ownership, access codes,
     // group membership, and generates audit log entries)
-    let mut context = access_control::AccessContext::new(common::ResourceType::File, doc.id);
+    let mut context = access_control::AccessContext::new(common::ResourceType::File, doc.id)
+        .with_request_headers(&headers);
     if let Some(uid) = user_id.clone() {
         context = context.with_user(uid);
     }
@@ -132,6 +134,13 @@ pub async fn view_markdown_handler(
             "You don't have access to this document".to_string(),
         ));
     }
+    if !state
+        .access_control
+        .record_key_view(&decision, query.code.as_deref())
+        .await
+    {
+        return Err((StatusCode::GONE, "This access code has reached its view limit".to_string()));
+    }
 
     // Read the markdown file
     let vault_id = doc.vault_id.ok_or((
@@ -142,9 +151,10 @@ pub async fn view_markdown_handler(
     // Use multi-location fallback to find the file
     let file_path = state
         .user_storage
-        .find_media_file(&vault_id, common::storage::MediaType::Document,
+        .fetch_media_file(&vault_id, common::storage::MediaType::Document,
             &doc.filename,
         )
+        .await
         .ok_or_else(|| {
             error!("Markdown file not found: {} (vault: {})", doc.filename, vault_id);
             (StatusCode::NOT_FOUND, format!("File not found: {}", doc.filename))
@@ -254,9 +264,10 @@ pub async fn edit_markdown_handler(
     // Use multi-location fallback to find the file
     let file_path = state
         .user_storage
-        .find_media_file(&vault_id, common::storage::MediaType::Document,
+        .fetch_media_file(&vault_id, common::storage::MediaType::Document,
             &doc.filename,
         )
+        .await
         .ok_or_else(|| {
             error!("Markdown file not found: {} (vault: {})", doc.filename, vault_id);
             (StatusCode::NOT_FOUND, format!("File not found: {}", doc.filename))
@@ -391,9 +402,10 @@ pub async fn save_markdown_handler(
     // Use multi-location fallback to find the file
     let file_path = state
         .user_storage
-        .find_media_file(&vault_id, common::storage::MediaType::Document,
+        .fetch_media_file(&vault_id, common::storage::MediaType::Document,
             &doc.filename,
         )
+        .await
         .ok_or_else(|| {
             error!("Markdown file not found: {} (vault: {})", doc.filename, vault_id);
             (
@@ -420,6 +432,17 @@ pub async fn save_markdown_handler(
 
     info!("Saved markdown file: {}", slug);
 
+    if let Err(e) = state.user_storage.offload(&file_path).await {
+        warn!("Failed to offload markdown file {}: {}", slug, e);
+    }
+
+    // Keep the search index in step with the edited text
+    if let Err(e) =
+        crate::content_index::set_document_text(state.repo.as_ref(), doc.id, &payload.content).await
+    {
+        warn!("Failed to index markdown text for {}: {:?}", slug, e);
+    }
+
     Ok(Json(SaveMarkdownResponse {
         success: true,
         message: "Document saved successfully".to_string(),
@@ -1,11 +1,12 @@
 //! Unified media models for cross-type operations
 //!
 //! This module provides unified data structures that can represent any media type
-//! (video, image, document) for use in unified UI components and search results.
+//! (video, image, audio, document) for use in unified UI components and search results.
 
 use common::models::media_item::MediaItem;
 use media_core::traits::MediaType;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 /// Unified media item that can represent any media type
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -22,6 +23,7 @@ impl UnifiedMediaItem {
             Self::MediaItem(m) => match m.media_type.as_str() {
                 "video" => MediaType::Video,
                 "image" => MediaType::Image,
+                "audio" => MediaType::Audio,
                 _ => MediaType::Document(media_core::traits::DocumentType::Other(
                     m.media_type.clone(),
                 )),
@@ -94,6 +96,25 @@ impl UnifiedMediaItem {
         }
     }
 
+    /// Whether resized derivatives can be served (raster images)
+    pub fn has_derivatives(&self) -> bool {
+        match self {
+            Self::MediaItem(m) => m.media_type == "image" && m.mime_type != "image/svg+xml",
+        }
+    }
+
+    /// URL of a derivative bounded to `width` pixels (raster images only)
+    pub fn image_url(&self, width: u32) -> Option<String> {
+        self.has_derivatives()
+            .then(|| crate::derivatives::derivative_url(self.slug(), width))
+    }
+
+    /// `srcset` attribute value for responsive `<img>` tags (raster images only)
+    pub fn srcset(&self) -> Option<String> {
+        self.has_derivatives()
+            .then(|| crate::derivatives::srcset(self.slug()))
+    }
+
     /// Get the fallback icon URL based on media type
     pub fn fallback_icon(&self) -> String {
         match self {
@@ -105,6 +126,7 @@ impl UnifiedMediaItem {
                 match m.media_type.as_str() {
                     "video" => "/static/icons/document.svg".to_string(), // Video icon
                     "image" => "/static/icons/default.svg".to_string(),
+                    "audio" => "/static/icons/audio-icon.svg".to_string(),
                     "document" => {
                         // Check for specific document types
                         if let Some(cat) = &m.category {
@@ -264,6 +286,7 @@ impl UnifiedMediaItem {
                 match m.media_type.as_str() {
                     "video" => "Video",
                     "image" => "Image",
+                    "audio" => "Audio",
                     _ => "Media",
                 }
             }
@@ -310,6 +333,7 @@ impl UnifiedMediaItem {
             Self::MediaItem(m) => match m.media_type.as_str() {
                 "video" => "media-video",
                 "image" => "media-image",
+                "audio" => "media-audio",
                 "document" => "media-document",
                 _ => "media-unknown",
             },
@@ -380,12 +404,13 @@ impl From<common::models::media_item::MediaItem> for UnifiedMediaItem {
 
 /// Filter options for unified media queries
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default)]
 pub struct MediaFilterOptions {
-    /// Search query (searches title, description, category, tags)
+    /// Full-text search query (title, description, category, tags, document text)
     pub search: Option<String>,
 
     /// Filter by media type
-    pub media_type: Option<String>, // "video", "image", "document"
+    pub media_type: Option<String>, // "video", "image", "audio", "document"
 
     /// Filter by visibility
     pub is_public: Option<bool>,
@@ -402,8 +427,17 @@ pub struct MediaFilterOptions {
     /// Filter by access group id (as string for uniform binding)
     pub group_id: Option<String>,
 
+    /// Filter images by camera (substring of the EXIF make or model)
+    pub camera: Option<String>,
+
+    /// Filter images captured on or after this date (YYYY-MM-DD)
+    pub captured_after: Option<String>,
+
+    /// Filter images captured on or before this date (YYYY-MM-DD)
+    pub captured_before: Option<String>,
+
     /// Sort field
-    pub sort_by: String, // "created_at", "title", "file_size"
+    pub sort_by: String, // "relevance", "created_at", "title", "file_size"
 
     /// Sort order
     pub sort_order: String, // "asc", "desc"
@@ -427,6 +461,9 @@ pub struct MediaListResponse {
     pub page_size: i32,
     pub total_pages: i32,
     pub media_type_counts: MediaTypeCounts,
+    /// Highlighted match excerpts (HTML) by media id, for full-text searches
+    #[serde(skip)]
+    pub snippets: HashMap<i32, String>,
 }
 
 /// Counts of each media type
@@ -434,6 +471,7 @@ pub struct MediaListResponse {
 pub struct MediaTypeCounts {
     pub videos: i64,
     pub images: i64,
+    pub audio: i64,
     pub documents: i64,
     pub total: i64,
 }
@@ -524,6 +562,49 @@ mod tests {
         assert!(formatted.contains("MB"));
     }
 
+    #[test]
+    fn test_audio_item() {
+        let media_item = MediaItem {
+            id: 2,
+            slug: "episode-1".to_string(),
+            title: "Episode 1".to_string(),
+            description: None,
+            media_type: "audio".to_string(),
+            filename: "original.mp3".to_string(),
+            original_filename: Some("episode-1.mp3".to_string()),
+            file_size: 1024 * 1024 * 40,
+            mime_type: "audio/mpeg".to_string(),
+            is_public: 0,
+            user_id: Some("user1".to_string()),
+            group_id: None,
+            vault_id: None,
+            status: "active".to_string(),
+            featured: 0,
+            category: None,
+            thumbnail_url: None,
+            view_count: 0,
+            download_count: 0,
+            like_count: 0,
+            share_count: 0,
+            allow_download: 1,
+            allow_comments: 1,
+            mature_content: 0,
+            seo_title: None,
+            seo_description: None,
+            seo_keywords: None,
+            created_at: "2024-01-01 00:00:00".to_string(),
+            updated_at: None,
+            published_at: None,
+        };
+
+        let unified: UnifiedMediaItem = media_item.into();
+        assert!(unified.media_type().is_audio());
+        assert_eq!(unified.type_label(), "Audio");
+        assert_eq!(unified.type_class(), "media-audio");
+        assert_eq!(unified.fallback_icon(), "/static/icons/audio-icon.svg");
+        assert_eq!(unified.view_url(), "/media/episode-1");
+    }
+
     #[test]
     fn test_media_filter_options_default() {
         let filter = MediaFilterOptions::default();
@@ -172,26 +172,30 @@ pub async fn generate_pdf_thumbnail(context: PdfThumbnailContext) -> Result<()>
     Ok(())
 }
 
-/// Spawn async background task to generate PDF thumbnail
+/// Generate a PDF thumbnail, logging the outcome
 ///
-/// This is a non-blocking operation that returns immediately.
 /// Failures are logged but do not affect the document upload.
-pub fn spawn_thumbnail_generation(context: PdfThumbnailContext) {
+pub async fn generate_pdf_thumbnail_logged(context: PdfThumbnailContext) {
     let slug = context.slug.clone();
+    info!("Starting PDF thumbnail generation for: {}", slug);
 
-    tokio::spawn(async move {
-        info!("Starting PDF thumbnail generation for: {}", slug);
-
-        match generate_pdf_thumbnail(context).await {
-            Ok(_) => {
-                info!("PDF thumbnail generation completed for: {}", slug);
-            }
-            Err(e) => {
-                error!(
-                    "PDF thumbnail generation failed for {} (non-fatal): {:?}",
-                    slug, e
-                );
-            }
+    match generate_pdf_thumbnail(context).await {
+        Ok(_) => {
+            info!("PDF thumbnail generation completed for: {}", slug);
         }
-    });
+        Err(e) => {
+            error!(
+                "PDF thumbnail generation failed for {} (non-fatal): {:?}",
+                slug, e
+            );
+        }
+    }
+}
+
+/// Spawn async background task to generate PDF thumbnail
+///
+/// This is a non-blocking operation that returns immediately.
+/// Failures are logged but do not affect the document upload.
+pub fn spawn_thumbnail_generation(context: PdfThumbnailContext) {
+    tokio::spawn(generate_pdf_thumbnail_logged(context));
 }
@@ -1,10 +1,10 @@
 //! PDF viewing and file-serving handlers for media manager
 
+use access_control::SignedUrlParams;
 use askama::Template;
 use axum::{
-    body::Body,
     extract::{Path, Query, State},
-    http::{header, StatusCode},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
     response::{Html, Response},
 };
 use serde::Deserialize;
@@ -12,6 +12,7 @@ use tower_sessions::Session;
 use tracing::{error, info};
 
 use crate::routes::MediaManagerState;
+use crate::serve::{set_download_disposition, signed_url_grant};
 
 #[derive(Debug, Deserialize)]
 pub struct PdfAccessQuery {
@@ -24,6 +25,7 @@ pub async fn view_pdf_handler(
     State(state): State<MediaManagerState>,
     Path(slug): Path<String>,
     Query(query): Query<PdfAccessQuery>,
+    headers: HeaderMap,
 ) -> Result<Html<String>, (StatusCode, String)> {
     let authenticated: bool = session
         .get("authenticated")
@@ -53,7 +55,8 @@ pub async fn view_pdf_handler(
     }
 
     // Access control
-    let mut context = access_control::AccessContext::new(common::ResourceType::File, doc.id);
+    let mut context = access_control::AccessContext::new(common::ResourceType::File, doc.id)
+        .with_request_headers(&headers);
     if let Some(uid) = user_id.clone() {
         context = context.with_user(uid);
     }
@@ -74,6 +77,13 @@ pub async fn view_pdf_handler(
         info!(media_slug = %slug, reason = %decision.reason, "Access denied to PDF");
         return Err((StatusCode::FORBIDDEN, "You don't have access to this document".to_string()));
     }
+    if !state
+        .access_control
+        .record_key_view(&decision, query.code.as_deref())
+        .await
+    {
+        return Err((StatusCode::GONE, "This access code has reached its view limit".to_string()));
+    }
 
     info!("Serving PDF viewer for: {}", slug);
 
@@ -99,6 +109,8 @@ pub async fn serve_pdf_handler(
     State(state): State<MediaManagerState>,
     Path(slug): Path<String>,
     Query(query): Query<PdfAccessQuery>,
+    Query(signed): Query<SignedUrlParams>,
+    headers: HeaderMap,
 ) -> Result<Response, (StatusCode, String)> {
     let authenticated: bool = session
         .get("authenticated")
@@ -127,27 +139,31 @@ pub async fn serve_pdf_handler(
         return Err((StatusCode::BAD_REQUEST, "Not a PDF document".to_string()));
     }
 
-    // Access control
-    let mut context = access_control::AccessContext::new(common::ResourceType::File, doc.id);
-    if let Some(uid) = user_id {
-        context = context.with_user(uid);
-    }
-    if let Some(key) = query.code {
-        context = context.with_key(key);
-    }
-
-    let decision = state
-        .access_control
-        .check_access(context, access_control::Permission::Read)
-        .await
-        .map_err(|e| {
-            error!("Access control error: {}", e);
-            (StatusCode::INTERNAL_SERVER_ERROR, "Access control error".to_string())
-        })?;
-
-    if !decision.granted {
-        info!(media_slug = %slug, reason = %decision.reason, "Access denied to PDF serve");
-        return Err((StatusCode::FORBIDDEN, "Access denied".to_string()));
+    // Access control: a signed URL, or the regular access checks
+    let grant = signed_url_grant(&state, doc.id, &signed).await;
+    if grant.is_none() {
+        let mut context = access_control::AccessContext::new(common::ResourceType::File, doc.id)
+            .with_request_headers(&headers);
+        if let Some(uid) = user_id {
+            context = context.with_user(uid);
+        }
+        if let Some(key) = query.code {
+            context = context.with_key(key);
+        }
+
+        let decision = state
+            .access_control
+            .check_access(context, access_control::Permission::Read)
+            .await
+            .map_err(|e| {
+                error!("Access control error: {}", e);
+                (StatusCode::INTERNAL_SERVER_ERROR, "Access control error".to_string())
+            })?;
+
+        if !decision.granted {
+            info!(media_slug = %slug, reason = %decision.reason, "Access denied to PDF serve");
+            return Err((StatusCode::FORBIDDEN, "Access denied".to_string()));
+        }
     }
 
     let vault_id = doc.vault_id.ok_or((
@@ -158,28 +174,35 @@ pub async fn serve_pdf_handler(
     // Find the file
     let file_path = state
         .user_storage
-        .find_media_file(
+        .find_stored_media_file(
             &vault_id,
             common::storage::MediaType::Document,
             &doc.filename,
         )
+        .await
         .ok_or_else(|| {
             error!("PDF file not found: {} (vault: {})", doc.filename, vault_id);
             (StatusCode::NOT_FOUND, format!("PDF file not found: {}", doc.filename))
         })?;
 
-    let bytes = tokio::fs::read(&file_path).await.map_err(|e| {
+    let mut response = common::range::serve_stored_file(
+        &state.user_storage,
+        &file_path,
+        "application/pdf",
+        &headers,
+    )
+    .await
+    .map_err(|e| {
         error!("Failed to read PDF file {:?}: {}", file_path, e);
         (StatusCode::INTERNAL_SERVER_ERROR, "Failed to read file".to_string())
     })?;
 
     info!("Serving PDF bytes for: {}", slug);
 
-    Ok(Response::builder()
-        .status(StatusCode::OK)
-        .header(header::CONTENT_TYPE, "application/pdf")
-        .header(header::CONTENT_DISPOSITION, "inline")
-        .header(header::CONTENT_LENGTH, bytes.len().to_string())
-        .body(Body::from(bytes))
-        .unwrap())
+    response
+        .headers_mut()
+        .insert(header::CONTENT_DISPOSITION, HeaderValue::from_static("inline"));
+    set_download_disposition(&mut response, grant, &doc.filename);
+
+    Ok(response)
 }
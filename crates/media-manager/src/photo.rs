@@ -0,0 +1,354 @@
+//! Photo metadata and EXIF privacy
+//!
+//! Capture metadata (time, camera, lens, GPS position) is read from the EXIF
+//! of uploaded images into `media_photo`, where it backs the camera / date
+//! filters of the media list and the owner-only map view.
+//!
+//! Served originals are scrubbed of GPS and device EXIF unless the owning
+//! vault's policy (`storage_vaults.strip_exif`) says otherwise. The scrubbed
+//! copy is cached on disk, so it is served with Range and ETag support. The WebP
+//! derivatives never carry EXIF; their pixels are rotated upright at upload.
+
+use access_control::{Permission, SignedUrlParams};
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{Json, Response},
+};
+use common::storage::MediaType;
+use common::utils::image_metadata;
+use db::media::{ImageServingInfo, PhotoDetails};
+use image::{DynamicImage, ImageDecoder, ImageReader};
+use serde::Deserialize;
+use serde_json::json;
+use std::io::Cursor;
+use std::path::PathBuf;
+use tower_sessions::Session;
+use tracing::{debug, error, warn};
+
+use crate::routes::MediaManagerState;
+use crate::serve::{
+    check_media_access, set_download_disposition, signed_url_grant, AccessQuery, AccessResource,
+};
+
+/// Decode an image with its EXIF orientation applied to the pixels
+pub fn decode_upright(data: &[u8]) -> image::ImageResult<DynamicImage> {
+    let mut decoder = ImageReader::new(Cursor::new(data))
+        .with_guessed_format()?
+        .into_decoder()?;
+    let orientation = decoder.orientation()?;
+    let mut img = DynamicImage::from_decoder(decoder)?;
+    img.apply_orientation(orientation);
+    Ok(img)
+}
+
+/// Capture metadata from the EXIF of an uploaded image
+///
+/// Returns `None` when the image has no EXIF or none of the fields we keep.
+pub async fn read_photo_details(media_id: i64, data: &[u8]) -> Option<PhotoDetails> {
+    let exif = image_metadata::extract_exif_from_bytes(data).await?;
+
+    // ASCII values are displayed quoted; unset dates as "unknown"
+    let clean = |value: Option<String>| {
+        value
+            .map(|v| v.trim_matches('"').trim().to_string())
+            .filter(|v| !v.is_empty() && v != "unknown")
+    };
+    let details = PhotoDetails {
+        media_id,
+        captured_at: clean(exif.taken_at),
+        camera_make: clean(exif.camera_make),
+        camera_model: clean(exif.camera_model),
+        lens_model: clean(exif.lens_model),
+        gps_latitude: exif.gps_latitude.filter(|v| v.is_finite()),
+        gps_longitude: exif.gps_longitude.filter(|v| v.is_finite()),
+    };
+
+    let empty = details.captured_at.is_none()
+        && details.camera_make.is_none()
+        && details.camera_model.is_none()
+        && details.lens_model.is_none()
+        && details.gps_latitude.is_none();
+    (!empty).then_some(details)
+}
+
+/// Read and store the capture metadata of a newly uploaded image
+pub async fn record_photo_details(state: &MediaManagerState, media_id: i64, data: &[u8]) {
+    let Some(details) = read_photo_details(media_id, data).await else {
+        return;
+    };
+    if let Err(e) = state.repo.upsert_photo_details(&details).await {
+        warn!("Failed to save photo metadata of media {}: {}", media_id, e);
+    }
+}
+
+/// Serve the uploaded original of an image
+/// GET /media/{slug}/original
+///
+/// Falls back to the WebP when the original wasn't kept. GPS and device EXIF
+/// are stripped according to the vault's policy.
+pub async fn serve_image_original(
+    State(state): State<MediaManagerState>,
+    session: Session,
+    Path(slug): Path<String>,
+    Query(query): Query<AccessQuery>,
+    Query(signed): Query<SignedUrlParams>,
+    headers: HeaderMap,
+) -> Result<Response, StatusCode> {
+    let info = state
+        .repo
+        .get_image_for_serving(&slug)
+        .await
+        .map_err(|e| {
+            error!("Database error: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    let vault_id = info
+        .vault_id
+        .clone()
+        .ok_or(StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let resource = AccessResource {
+        resource_type: access_control::ResourceType::Image,
+        id: info.id,
+        owner_id: info.user_id.as_deref(),
+        is_public: info.is_public != 0,
+        signed_permission: Permission::Download,
+    };
+    check_media_access(&state, &session, &headers, resource, &vault_id, &signed, query.code).await?;
+
+    // Kept originals are stored as {slug}_original.{ext}
+    let original_name = info
+        .original_filename
+        .as_deref()
+        .and_then(|name| std::path::Path::new(name).extension())
+        .and_then(|ext| ext.to_str())
+        .map(|ext| format!("{}_original.{}", slug, ext));
+    let mut found = None;
+    if let Some(name) = &original_name {
+        found = state
+            .user_storage
+            .find_stored_media_file(&vault_id, MediaType::Image, name)
+            .await
+            .map(|path| (path, name.clone()));
+    }
+    if found.is_none() {
+        found = state
+            .user_storage
+            .find_stored_media_file(&vault_id, MediaType::Image, &info.filename)
+            .await
+            .map(|path| (path, info.filename.clone()));
+    }
+    let (path, name) = found.ok_or(StatusCode::NOT_FOUND)?;
+    let content_type = mime_guess::from_path(&name)
+        .first_or_octet_stream()
+        .to_string();
+
+    let strip = state
+        .vault_repo
+        .get_vault_strip_exif(&vault_id)
+        .await
+        .unwrap_or(true);
+
+    let mut response = if strip && content_type != "image/svg+xml" {
+        let scrubbed = scrubbed_original(&state, &vault_id, &slug, &info, &path, &name).await?;
+        debug!("Serving scrubbed original of {}", slug);
+        common::range::serve_file(&scrubbed, &content_type, &headers)
+            .await
+            .map_err(|e| {
+                error!("Failed to open {:?}: {}", scrubbed, e);
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?
+    } else {
+        common::range::serve_stored_file(&state.user_storage, &path, &content_type, &headers)
+            .await
+            .map_err(|e| {
+                error!("Failed to open file: {}", e);
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?
+    };
+
+    let response_headers = response.headers_mut();
+    response_headers.insert(
+        header::CACHE_CONTROL,
+        HeaderValue::from_static("private, max-age=3600"),
+    );
+    response_headers.insert(
+        "X-Content-Type-Options",
+        HeaderValue::from_static("nosniff"),
+    );
+    if content_type == "image/svg+xml" {
+        response_headers.insert(
+            "Content-Security-Policy",
+            HeaderValue::from_static("default-src 'none'; style-src 'unsafe-inline'"),
+        );
+    }
+    let grant = signed_url_grant(&state, info.id, &signed).await;
+    set_download_disposition(
+        &mut response,
+        grant,
+        info.original_filename.as_deref().unwrap_or(&name),
+    );
+    Ok(response)
+}
+
+/// Copy of an original with its private metadata removed
+///
+/// Written once next to the image's derivatives (so it is purged with them)
+/// and named after the file hash, so a replaced file gets a fresh copy.
+async fn scrubbed_original(
+    state: &MediaManagerState,
+    vault_id: &str,
+    slug: &str,
+    info: &ImageServingInfo,
+    source: &std::path::Path,
+    name: &str,
+) -> Result<PathBuf, StatusCode> {
+    let local = state
+        .user_storage
+        .fetch_local(source)
+        .await
+        .ok_or(StatusCode::NOT_FOUND)?;
+    let hash = match &info.file_hash {
+        Some(hash) => hash.clone(),
+        None => media_core::sha256_file(&local).await.map_err(|e| {
+            error!("Failed to hash {:?}: {}", local, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?,
+    };
+    let ext = std::path::Path::new(name)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or("bin");
+    let target = crate::derivatives::cache_dir(state, vault_id, slug)
+        .join(format!("scrubbed_{}.{}", hash, ext));
+    if target.is_file() {
+        return Ok(target);
+    }
+
+    let data = tokio::fs::read(&local).await.map_err(|e| {
+        error!("Failed to read {:?}: {}", local, e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    let data = tokio::task::spawn_blocking(move || {
+        image_metadata::strip_private_metadata(&data).unwrap_or(data)
+    })
+    .await
+    .map_err(|e| {
+        error!("EXIF scrubbing task failed: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    // Write under a unique name and rename, so concurrent requests never
+    // serve a partial file
+    let write = async {
+        if let Some(parent) = target.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        let temp = target.with_extension(format!("{}.tmp", uuid::Uuid::new_v4()));
+        tokio::fs::write(&temp, &data).await?;
+        if let Err(e) = tokio::fs::rename(&temp, &target).await {
+            let _ = tokio::fs::remove_file(&temp).await;
+            return Err(e);
+        }
+        Ok(())
+    };
+    write.await.map_err(|e: std::io::Error| {
+        error!("Failed to write scrubbed original {:?}: {}", target, e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    debug!("Cached scrubbed original of {}", slug);
+    Ok(target)
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PhotoLocationsQuery {
+    pub vault_id: Option<String>,
+}
+
+/// Geotagged images of the signed-in user, for a map view
+/// GET /api/media/photos/locations?vault_id=
+pub async fn list_photo_locations(
+    State(state): State<MediaManagerState>,
+    session: Session,
+    Query(query): Query<PhotoLocationsQuery>,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    let authenticated: bool = session
+        .get("authenticated")
+        .await
+        .ok()
+        .flatten()
+        .unwrap_or(false);
+    if !authenticated {
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+    let user_id: String = session
+        .get("user_id")
+        .await
+        .ok()
+        .flatten()
+        .ok_or(StatusCode::UNAUTHORIZED)?;
+
+    let vault_id = query.vault_id.filter(|v| !v.is_empty());
+    if let Some(vault_id) = &vault_id {
+        let owner = state
+            .vault_repo
+            .get_vault_owner(vault_id)
+            .await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        if owner.as_deref() != Some(user_id.as_str()) {
+            return Err(StatusCode::FORBIDDEN);
+        }
+    }
+
+    let images = state
+        .repo
+        .list_geotagged_images(&user_id, vault_id.as_deref())
+        .await
+        .map_err(|e| {
+            error!("Failed to list geotagged images: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    let features: Vec<_> = images
+        .into_iter()
+        .map(|image| {
+            json!({
+                "slug": image.slug,
+                "title": image.title,
+                "latitude": image.latitude,
+                "longitude": image.longitude,
+                "captured_at": image.captured_at,
+                "url": format!("/media/{}", image.slug),
+                "thumbnail_url": format!("/media/{}/image?w=200&h=200&fit=cover", image.slug),
+            })
+        })
+        .collect();
+
+    Ok(Json(json!({ "count": features.len(), "images": features })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_upright_without_exif() {
+        let img = DynamicImage::new_rgb8(6, 3);
+        let mut png = Vec::new();
+        img.write_to(&mut Cursor::new(&mut png), image::ImageFormat::Png)
+            .unwrap();
+        let decoded = decode_upright(&png).unwrap();
+        assert_eq!((decoded.width(), decoded.height()), (6, 3));
+    }
+
+    #[tokio::test]
+    async fn test_read_photo_details_without_exif() {
+        let img = DynamicImage::new_rgb8(2, 2);
+        let mut png = Vec::new();
+        img.write_to(&mut Cursor::new(&mut png), image::ImageFormat::Png)
+            .unwrap();
+        assert!(read_photo_details(1, &png).await.is_none());
+    }
+}
@@ -0,0 +1,252 @@
+//! Poster frames and seek previews for videos
+//!
+//! The processing pipeline picks a representative poster frame and writes
+//! sprite sheets with a WebVTT thumbnail track
+//! ([`video_manager::previews`]). Owners can replace the poster with the
+//! frame at a given timestamp or with an uploaded image.
+
+use access_control::SignedUrlParams;
+use axum::{
+    body::Body,
+    extract::{Multipart, Path, Query, State},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{Json, Response},
+};
+use common::storage::MediaType;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::path::Path as FsPath;
+use tower_sessions::Session;
+use tracing::{error, info};
+use video_manager::ffmpeg::{self, FFmpegConfig};
+use video_manager::previews::{self, SPRITES_DIR, SPRITES_VTT};
+
+use crate::routes::MediaManagerState;
+use crate::serve::{check_video_access, AccessQuery};
+use crate::subtitles::{api_error, internal_error, owned_video, ApiError};
+
+/// Upper bound on an uploaded poster image
+const MAX_POSTER_SIZE: usize = 10 * 1024 * 1024;
+
+/// Serve the thumbnail track or a sprite sheet
+/// GET /media/{slug}/sprites/{file}
+///
+/// Cues reference the sheets relatively; an access code is carried over
+/// into those references so private videos shared by code keep working.
+pub async fn serve_sprite(
+    State(state): State<MediaManagerState>,
+    session: Session,
+    Path((slug, file)): Path<(String, String)>,
+    Query(query): Query<AccessQuery>,
+    Query(signed): Query<SignedUrlParams>,
+    headers: HeaderMap,
+) -> Result<Response, StatusCode> {
+    if !previews::is_sprite_file(&file) {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    let info = state
+        .repo
+        .get_video_for_serving(&slug)
+        .await
+        .map_err(|e| {
+            error!("Database error: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    let vault_id = info
+        .vault_id
+        .clone()
+        .ok_or(StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    check_video_access(&state, &session, &headers, &info, &vault_id, &signed, query.code.clone()).await?;
+
+    let path = state
+        .user_storage
+        .find_stored_media_file(
+            &vault_id,
+            MediaType::Video,
+            &format!("{}/{}/{}", slug, SPRITES_DIR, file),
+        )
+        .await
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    if file == SPRITES_VTT {
+        let local = state
+            .user_storage
+            .fetch_local(&path)
+            .await
+            .ok_or(StatusCode::NOT_FOUND)?;
+        let mut vtt = tokio::fs::read_to_string(&local).await.map_err(|e| {
+            error!("Failed to read thumbnail track: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+        if let Some(code) = query.code.filter(|c| !c.is_empty()) {
+            let code = urlencoding::encode(&code);
+            vtt = vtt.replace(".jpg#xywh=", &format!(".jpg?code={}#xywh=", code));
+        }
+        return Response::builder()
+            .header(header::CONTENT_TYPE, "text/vtt; charset=utf-8")
+            .header(header::CACHE_CONTROL, "no-cache")
+            .body(Body::from(vtt))
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    let mut response =
+        common::range::serve_stored_file(&state.user_storage, &path, "image/jpeg", &headers)
+            .await
+            .map_err(|e| {
+                error!("Failed to open sprite sheet: {}", e);
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?;
+    response
+        .headers_mut()
+        .insert(header::CACHE_CONTROL, HeaderValue::from_static("no-cache"));
+    Ok(response)
+}
+
+/// Poster frame request body
+#[derive(Debug, Deserialize)]
+pub struct PosterFrameRequest {
+    /// Offset into the video in seconds
+    pub timestamp: f64,
+}
+
+/// Write poster and thumbnail from `source` and hand them to storage
+async fn replace_poster(
+    state: &MediaManagerState,
+    vault_id: &str,
+    slug: &str,
+    video_dir: &FsPath,
+    source: &FsPath,
+    timestamp: f64,
+) -> Result<(), ApiError> {
+    let storage = &state.user_storage;
+    let poster_path = video_dir.join("poster.jpg");
+    let thumbnail_path = storage
+        .vault_thumbnails_dir(vault_id, MediaType::Video)
+        .join(format!("{}_thumb.jpg", slug));
+
+    previews::write_poster_images(
+        &FFmpegConfig::default(),
+        source,
+        timestamp,
+        &poster_path,
+        &thumbnail_path,
+    )
+    .await
+    .map_err(|e| internal_error("Failed to create poster", format!("{:#}", e)))?;
+
+    storage
+        .offload_paths(&[
+            poster_path,
+            storage.get_thumbnail_path(vault_id, MediaType::Video, slug),
+        ])
+        .await;
+    Ok(())
+}
+
+/// Use the frame at a timestamp as poster
+/// PUT /api/media/{slug}/poster (JSON: {timestamp})
+pub async fn set_poster_frame(
+    State(state): State<MediaManagerState>,
+    session: Session,
+    Path(slug): Path<String>,
+    Json(request): Json<PosterFrameRequest>,
+) -> Result<Json<Value>, ApiError> {
+    let (info, video_dir) = owned_video(&state, &session, &slug).await?;
+    let vault_id = info.vault_id.clone().unwrap_or_default();
+    let item = state
+        .repo
+        .get_media_by_slug(&slug)
+        .await
+        .map_err(|e| internal_error("Failed to load video", e))?
+        .ok_or_else(|| api_error(StatusCode::NOT_FOUND, "Video not found"))?;
+
+    let source = state
+        .user_storage
+        .fetch_local(&video_dir.join(&item.filename))
+        .await
+        .ok_or_else(|| {
+            api_error(
+                StatusCode::NOT_FOUND,
+                "The original video file is not available",
+            )
+        })?;
+    let duration = ffmpeg::extract_metadata(&FFmpegConfig::default(), &source)
+        .await
+        .map_err(|e| internal_error("Failed to read video", format!("{:#}", e)))?
+        .duration;
+
+    let timestamp = request.timestamp;
+    if !timestamp.is_finite() || timestamp < 0.0 || timestamp >= duration {
+        return Err(api_error(
+            StatusCode::BAD_REQUEST,
+            "The timestamp must lie within the video",
+        ));
+    }
+
+    replace_poster(&state, &vault_id, &slug, &video_dir, &source, timestamp).await?;
+
+    info!("Poster of {} set to the frame at {:.3}s", slug, timestamp);
+    Ok(Json(json!({ "success": true, "timestamp": timestamp })))
+}
+
+/// Upload a custom poster image
+/// POST /api/media/{slug}/poster (multipart: file)
+pub async fn upload_poster(
+    State(state): State<MediaManagerState>,
+    session: Session,
+    Path(slug): Path<String>,
+    mut multipart: Multipart,
+) -> Result<Json<Value>, ApiError> {
+    let (info, video_dir) = owned_video(&state, &session, &slug).await?;
+    let vault_id = info.vault_id.clone().unwrap_or_default();
+
+    let mut data: Option<Vec<u8>> = None;
+    while let Some(field) = multipart.next_field().await.map_err(|e| {
+        error!("Multipart error: {}", e);
+        api_error(StatusCode::BAD_REQUEST, "Invalid form data")
+    })? {
+        if field.name() == Some("file") {
+            let bytes = field
+                .bytes()
+                .await
+                .map_err(|_| api_error(StatusCode::BAD_REQUEST, "Invalid file field"))?;
+            data = Some(bytes.to_vec());
+        }
+    }
+    let data = data.ok_or_else(|| api_error(StatusCode::BAD_REQUEST, "No image provided"))?;
+    if data.len() > MAX_POSTER_SIZE {
+        return Err(api_error(
+            StatusCode::PAYLOAD_TOO_LARGE,
+            "Poster image is too large",
+        ));
+    }
+    let extension = match image::guess_format(&data) {
+        Ok(image::ImageFormat::Jpeg) => "jpg",
+        Ok(image::ImageFormat::Png) => "png",
+        Ok(image::ImageFormat::WebP) => "webp",
+        _ => {
+            return Err(api_error(
+                StatusCode::BAD_REQUEST,
+                "Poster must be a JPEG, PNG or WebP image",
+            ))
+        }
+    };
+
+    let temp_dir = state.user_storage.temp_dir();
+    tokio::fs::create_dir_all(&temp_dir)
+        .await
+        .map_err(|e| internal_error("Failed to store poster image", e))?;
+    let upload_path = temp_dir.join(format!("poster-{}.{}", uuid::Uuid::new_v4(), extension));
+    tokio::fs::write(&upload_path, &data)
+        .await
+        .map_err(|e| internal_error("Failed to store poster image", e))?;
+
+    let result = replace_poster(&state, &vault_id, &slug, &video_dir, &upload_path, 0.0).await;
+    let _ = tokio::fs::remove_file(&upload_path).await;
+    result?;
+
+    info!("Custom poster uploaded for {}", slug);
+    Ok(Json(json!({ "success": true })))
+}
@@ -3,14 +3,23 @@
 //! Consolidates all media endpoints:
 //! - Listing and search (HTML + JSON API)
 //! - Upload form and upload handler
+//! - Resumable (tus) uploads
 //! - Detail pages (HTML)
 //! - Markdown view/edit/save
-//! - Image serving (original, WebP, thumbnail)
+//! - Image serving (original, WebP, thumbnail, resized derivatives)
+//! - Photo locations (map view)
 //! - CRUD operations (get, update, delete, toggle visibility)
+//! - Bulk operations (tags, group, visibility, move, delete)
+//! - Trash (list, restore, delete forever)
+//! - Subtitle tracks and transcripts for videos
+//! - Chapters and clips for videos
+//! - Poster frames and seek-preview sprites for videos
+//! - Audio renditions and waveforms
+//! - Storage integrity report (admin)
 //! - Vault management
 
 use axum::{
-    routing::{get, post},
+    routing::{delete, get, head, options, post, put},
     Router,
 };
 use common::storage::UserStorageManager;
@@ -30,8 +39,16 @@ pub struct MediaManagerState {
     pub video_progress_tracker: Option<video_manager::progress::ProgressTracker>,
     pub video_metrics_store: Option<video_manager::metrics::MetricsStore>,
     pub video_audit_logger: Option<video_manager::metrics::AuditLogger>,
+    // Transcoding job queue (HLS uploads are processed by its workers)
+    pub transcode_queue: Option<video_manager::queue::TranscodeQueue>,
     // HLS transcoding progress tracker (for WebSocket updates)
     pub hls_progress: Arc<crate::progress::ProgressTracker>,
+    // Resumable (tus) uploads currently receiving a chunk
+    pub tus_active: Arc<dashmap::DashSet<String>>,
+    // Storage integrity checks (checksum verification of stored files)
+    pub integrity: Arc<crate::integrity::IntegrityVerifier>,
+    // Speech-to-text for videos (re-running transcripts on demand)
+    pub transcriber: Option<video_manager::transcripts::Transcriber>,
 }
 
 impl MediaManagerState {
@@ -51,7 +68,11 @@ impl MediaManagerState {
             video_progress_tracker: None,
             video_metrics_store: None,
             video_audit_logger: None,
+            transcode_queue: None,
             hls_progress: Arc::new(crate::progress::ProgressTracker::new()),
+            tus_active: Arc::new(dashmap::DashSet::new()),
+            integrity: Arc::new(crate::integrity::IntegrityVerifier::new()),
+            transcriber: None,
         }
     }
 
@@ -65,6 +86,7 @@ impl MediaManagerState {
         progress_tracker: video_manager::progress::ProgressTracker,
         metrics_store: video_manager::metrics::MetricsStore,
         audit_logger: video_manager::metrics::AuditLogger,
+        transcode_queue: video_manager::queue::TranscodeQueue,
     ) -> Self {
         Self {
             repo,
@@ -75,9 +97,19 @@ impl MediaManagerState {
             video_progress_tracker: Some(progress_tracker),
             video_metrics_store: Some(metrics_store),
             video_audit_logger: Some(audit_logger),
+            transcode_queue: Some(transcode_queue),
             hls_progress: Arc::new(crate::progress::ProgressTracker::new()),
+            tus_active: Arc::new(dashmap::DashSet::new()),
+            integrity: Arc::new(crate::integrity::IntegrityVerifier::new()),
+            transcriber: None,
         }
     }
+
+    /// Enable on-demand transcripts with the video pipeline's transcriber
+    pub fn with_transcriber(mut self, transcriber: video_manager::transcripts::Transcriber) -> Self {
+        self.transcriber = Some(transcriber);
+        self
+    }
 }
 
 /// Create all media routes (unified)
@@ -126,12 +158,81 @@ pub fn media_routes() -> Router<MediaManagerState> {
             "/api/media/{slug}/save-bpmn",
             post(crate::bpmn_view::save_bpmn_handler),
         )
+        .route("/api/media/batch", post(crate::batch::batch_media))
+        // ── Trash ───────────────────────────────────────────────────
+        .route("/media/trash", get(crate::trash::trash_page))
+        .route("/api/media/trash", get(crate::trash::list_trash))
+        .route(
+            "/api/media/trash/{slug}/restore",
+            post(crate::trash::restore_from_trash),
+        )
+        .route(
+            "/api/media/trash/{slug}",
+            delete(crate::trash::delete_from_trash),
+        )
         .route(
             "/api/media/{slug}",
             get(crate::list::get_media_item)
                 .put(crate::list::update_media_item)
                 .delete(crate::list::delete_media),
         )
+        // ── Versions ────────────────────────────────────────────────
+        .route(
+            "/api/media/{slug}/versions",
+            get(crate::versions::list_versions),
+        )
+        .route(
+            "/api/media/{slug}/versions/diff",
+            get(crate::versions::diff_versions_handler),
+        )
+        .route(
+            "/api/media/{slug}/versions/{version}/rollback",
+            post(crate::versions::rollback_version),
+        )
+        // ── Subtitle tracks (videos) ────────────────────────────────
+        .route(
+            "/api/media/{slug}/subtitles",
+            get(crate::subtitles::list_subtitles).post(crate::subtitles::upload_subtitle),
+        )
+        .route(
+            "/api/media/{slug}/subtitles/{language}",
+            delete(crate::subtitles::delete_subtitle),
+        )
+        // ── Transcripts (videos) ────────────────────────────────────
+        .route(
+            "/api/media/{slug}/transcript",
+            get(crate::transcripts::get_transcript)
+                .post(crate::transcripts::regenerate_transcript),
+        )
+        // ── Chapters (videos) ───────────────────────────────────────
+        .route(
+            "/api/media/{slug}/chapters",
+            get(crate::video_edit::list_chapters).put(crate::video_edit::save_chapters),
+        )
+        // ── Poster frame (videos) ───────────────────────────────────
+        .route(
+            "/api/media/{slug}/poster",
+            put(crate::previews::set_poster_frame).post(crate::previews::upload_poster),
+        )
+        // ── Photo locations (owner map view) ────────────────────────
+        .route(
+            "/api/media/photos/locations",
+            get(crate::photo::list_photo_locations),
+        )
+        // ── Audio details ───────────────────────────────────────────
+        .route(
+            "/api/media/{slug}/audio",
+            get(crate::audio::get_audio_details),
+        )
+        // ── Storage integrity (admin) ───────────────────────────────
+        .route(
+            "/api/admin/media/integrity",
+            get(crate::integrity::get_integrity_report),
+        )
+        .route(
+            "/api/admin/media/integrity/verify",
+            post(crate::integrity::start_integrity_check),
+        )
         // ── Video progress tracking ─────────────────────────────────
         .route(
             "/api/media/{slug}/progress",
@@ -152,6 +253,46 @@ pub fn media_upload_routes() -> Router<MediaManagerState> {
         // ── Upload ──────────────────────────────────────────────────
         .route("/media/upload", get(crate::list::show_upload_form))
         .route("/api/media/upload", post(crate::upload::upload_media))
+        // ── Clips (cut and re-transcoded like an upload) ────────────
+        .route(
+            "/api/media/{slug}/clips",
+            post(crate::video_edit::create_clip),
+        )
+        // ── File replacement (processed like an upload) ─────────────
+        .route(
+            "/api/media/{slug}/replace",
+            post(crate::versions::replace_file),
+        )
+}
+
+/// Create resumable upload routes (tus 1.0)
+///
+/// Kept apart from [`media_upload_routes`]: a large file is sent as many
+/// PATCH requests, which the strict upload rate limit would throttle. Chunk
+/// bodies are streamed to disk, so no body limit applies either.
+pub fn media_tus_routes() -> Router<MediaManagerState> {
+    Router::new()
+        .route(
+            "/api/media/tus",
+            options(crate::tus::tus_options).post(crate::tus::create_upload),
+        )
+        .route(
+            "/api/media/tus/{id}",
+            head(crate::tus::upload_offset)
+                .patch(crate::tus::append_chunk)
+                .delete(crate::tus::terminate_upload),
+        )
+}
+
+/// Create signed URL routes (behind API key or session auth)
+///
+/// Minting is gated by the `read` scope of API keys, so an LMS integration can
+/// request short-lived delivery URLs without a browser session.
+pub fn media_signing_routes() -> Router<MediaManagerState> {
+    Router::new().route(
+        "/api/media/{slug}/signed-url",
+        post(crate::signed_urls::create_signed_url),
+    )
 }
 
 /// Create folder access code routes (public, no auth — validated by code)
@@ -164,7 +305,7 @@ pub fn folder_access_routes() -> Router<MediaManagerState> {
 
 /// Create media serving routes (lenient rate limiting)
 ///
-/// These routes serve media files (images, PDFs, videos) and need high rate limits
+/// These routes serve media files (images, PDFs, videos, audio) and need high rate limits
 /// (300+ RPM) to support galleries loading many assets concurrently. Access is
 /// controlled by access codes, not rate limiting.
 pub fn media_serving_routes() -> Router<MediaManagerState> {
@@ -179,10 +320,43 @@ pub fn media_serving_routes() -> Router<MediaManagerState> {
             "/media/{slug}/image.webp",
             get(crate::serve::serve_image_webp),
         )
+        .route(
+            "/media/{slug}/image",
+            get(crate::derivatives::serve_image_derivative),
+        )
+        .route(
+            "/media/{slug}/original",
+            get(crate::photo::serve_image_original),
+        )
         // ── Thumbnail serving (all media types) ──────────────────────
         .route(
             "/media/{slug}/thumbnail",
             get(crate::serve::serve_thumbnail),
         )
+        // ── Progressive MP4 serving (supports Range requests) ──────────
+        .route(
+            "/media/{slug}/video.mp4",
+            get(crate::serve::serve_video_mp4),
+        )
+        // ── Subtitle tracks (WebVTT, also used by <track>) ─────────────
+        .route(
+            "/media/{slug}/subtitles/{file}",
+            get(crate::subtitles::serve_subtitle),
+        )
+        // ── Chapters track (WebVTT) ─────────────────────────────────────
+        .route(
+            "/media/{slug}/chapters.vtt",
+            get(crate::video_edit::serve_chapters),
+        )
+        // ── Seek-preview sprites and thumbnail track (WebVTT) ──────────
+        .route(
+            "/media/{slug}/sprites/{file}",
+            get(crate::previews::serve_sprite),
+        )
+        // ── Audio renditions and waveform (supports Range requests) ────
+        .route(
+            "/media/{slug}/audio/{file}",
+            get(crate::audio::serve_audio_file),
+        )
     // NOTE: HLS video serving (/hls/{slug}/{*path}) is handled by video-manager crate
 }
@@ -1,12 +1,17 @@
 //! Unified search across all media types
 //!
 //! Provides cross-media search functionality that queries videos, images,
-//! and documents simultaneously and returns unified results.
+//! audio and documents simultaneously and returns unified results. Search
+//! terms are matched against the full-text index (titles, descriptions,
+//! categories, tags and document text) with ranked, highlighted results.
 
 use crate::models::{MediaFilterOptions, MediaListResponse, MediaTypeCounts, UnifiedMediaItem};
 use anyhow::Result;
 use common::models::media_item::MediaItem;
-use db::media::{MediaItemRow, MediaRepository, MediaSearchFilter};
+use db::media::{
+    MediaItemRow, MediaRepository, MediaSearchFilter, SNIPPET_MATCH_END, SNIPPET_MATCH_START,
+};
+use std::collections::HashMap;
 use std::sync::Arc;
 use tracing::{debug, info};
 
@@ -22,10 +27,10 @@ impl MediaSearchService {
     }
 
     /// Search across all media types
+    ///
+    /// Filtering, ranking and pagination happen in the database; only the
+    /// requested page is loaded.
     pub async fn search(&self, options: MediaFilterOptions) -> Result<MediaListResponse> {
-        let offset = options.page * options.page_size;
-        let limit = options.page_size;
-
         debug!(
             "Searching media with options: search={:?}, type={:?}, page={}, size={}",
             options.search, options.media_type, options.page, options.page_size
@@ -33,27 +38,26 @@ impl MediaSearchService {
 
         let filter = to_search_filter(&options);
 
-        // Get counts for each media type
+        // Get counts for each media type (their sum is the filtered total)
         let counts = self.get_media_counts(&filter).await?;
 
-        // Collect items from media_items table (unified query)
-        let rows = self.repo.search_media(&filter).await.map_err(anyhow::Error::msg)?;
-        let mut all_items: Vec<UnifiedMediaItem> = rows
+        let hits = self
+            .repo
+            .search_media(&filter)
+            .await
+            .map_err(anyhow::Error::msg)?;
+        let mut snippets = HashMap::new();
+        let items: Vec<UnifiedMediaItem> = hits
             .into_iter()
-            .map(|row| UnifiedMediaItem::from(media_item_from_row(row)))
-            .collect();
-
-        // Sort results (already sorted by database, but keep for flexibility)
-        self.sort_items(&mut all_items, &options);
-
-        // Apply pagination
-        let total = all_items.len() as i64;
-        let paginated: Vec<UnifiedMediaItem> = all_items
-            .into_iter()
-            .skip(offset as usize)
-            .take(limit as usize)
+            .map(|hit| {
+                if let Some(snippet) = &hit.snippet {
+                    snippets.insert(hit.item.id, highlight_snippet(snippet));
+                }
+                UnifiedMediaItem::from(media_item_from_row(hit.item))
+            })
             .collect();
 
+        let total = counts.total;
         let total_pages = if options.page_size > 0 {
             ((total as f64) / (options.page_size as f64)).ceil() as i32
         } else {
@@ -66,61 +70,51 @@ impl MediaSearchService {
         );
 
         Ok(MediaListResponse {
-            items: paginated,
+            items,
             total,
             page: options.page,
             page_size: options.page_size,
             total_pages,
             media_type_counts: counts,
+            snippets,
         })
     }
 
+    /// Slugs of the items matching the filters, ignoring pagination
+    ///
+    /// Returns at most `limit` slugs, in the filter's sort order.
+    pub async fn matching_slugs(
+        &self,
+        options: &MediaFilterOptions,
+        limit: i64,
+    ) -> Result<Vec<String>> {
+        let mut filter = to_search_filter(options);
+        filter.limit = Some(limit);
+        filter.offset = None;
+
+        let hits = self
+            .repo
+            .search_media(&filter)
+            .await
+            .map_err(anyhow::Error::msg)?;
+        Ok(hits.into_iter().map(|hit| hit.item.slug).collect())
+    }
+
     /// Get counts for each media type
     async fn get_media_counts(&self, filter: &MediaSearchFilter) -> Result<MediaTypeCounts> {
         let video_count = self.repo.count_media_by_type("video", filter).await.map_err(anyhow::Error::msg)?;
         let image_count = self.repo.count_media_by_type("image", filter).await.map_err(anyhow::Error::msg)?;
+        let audio_count = self.repo.count_media_by_type("audio", filter).await.map_err(anyhow::Error::msg)?;
         let document_count = self.repo.count_media_by_type("document", filter).await.map_err(anyhow::Error::msg)?;
 
         Ok(MediaTypeCounts {
             videos: video_count,
             images: image_count,
+            audio: audio_count,
             documents: document_count,
-            total: video_count + image_count + document_count,
+            total: video_count + image_count + audio_count + document_count,
         })
     }
-
-    /// Sort unified media items
-    fn sort_items(&self, items: &mut [UnifiedMediaItem], options: &MediaFilterOptions) {
-        match options.sort_by.as_str() {
-            "title" => {
-                items.sort_by(|a, b| {
-                    if options.sort_order == "desc" {
-                        b.title().cmp(a.title())
-                    } else {
-                        a.title().cmp(b.title())
-                    }
-                });
-            }
-            "file_size" => {
-                items.sort_by(|a, b| {
-                    if options.sort_order == "desc" {
-                        b.file_size().cmp(&a.file_size())
-                    } else {
-                        a.file_size().cmp(&b.file_size())
-                    }
-                });
-            }
-            "created_at" | _ => {
-                items.sort_by(|a, b| {
-                    if options.sort_order == "desc" {
-                        b.created_at().cmp(&a.created_at())
-                    } else {
-                        a.created_at().cmp(&b.created_at())
-                    }
-                });
-            }
-        }
-    }
 }
 
 /// Convert `MediaFilterOptions` (crate-local) to `MediaSearchFilter` (db crate).
@@ -133,6 +127,9 @@ fn to_search_filter(opts: &MediaFilterOptions) -> MediaSearchFilter {
         vault_id: opts.vault_id.clone(),
         tag: opts.tag.clone(),
         group_id: opts.group_id.clone(),
+        camera: opts.camera.clone(),
+        captured_after: opts.captured_after.clone(),
+        captured_before: opts.captured_before.clone(),
         sort_by: if opts.sort_by.is_empty() {
             "created_at".to_string()
         } else {
@@ -144,7 +141,30 @@ fn to_search_filter(opts: &MediaFilterOptions) -> MediaSearchFilter {
             opts.sort_order.clone()
         },
         tenant_id: opts.tenant_id.clone(),
+        limit: (opts.page_size > 0).then_some(opts.page_size as i64),
+        offset: (opts.page_size > 0).then_some(opts.page.max(0) as i64 * opts.page_size as i64),
+    }
+}
+
+/// Render a search snippet as HTML with matched terms in `<mark>`
+///
+/// The snippet is document text, so it is escaped before the match markers
+/// are replaced.
+pub(crate) fn highlight_snippet(snippet: &str) -> String {
+    let mut html = String::with_capacity(snippet.len() + 16);
+    for c in snippet.chars() {
+        match c {
+            SNIPPET_MATCH_START => html.push_str("<mark>"),
+            SNIPPET_MATCH_END => html.push_str("</mark>"),
+            '&' => html.push_str("&amp;"),
+            '<' => html.push_str("&lt;"),
+            '>' => html.push_str("&gt;"),
+            '"' => html.push_str("&quot;"),
+            '\'' => html.push_str("&#x27;"),
+            c => html.push(c),
+        }
     }
+    html
 }
 
 /// Convert a `MediaItemRow` (db crate) to a `MediaItem` (common crate).
@@ -213,6 +233,9 @@ mod tests {
             vault_id: Some("vault1".to_string()),
             tag: Some("rust".to_string()),
             group_id: Some("5".to_string()),
+            camera: Some("Pocket".to_string()),
+            captured_after: Some("2024-01-01".to_string()),
+            captured_before: None,
             sort_by: "title".to_string(),
             sort_order: "asc".to_string(),
             page: 2,
@@ -223,7 +246,35 @@ mod tests {
         assert_eq!(filter.search, Some("test".to_string()));
         assert_eq!(filter.media_type, Some("video".to_string()));
         assert_eq!(filter.is_public, Some(true));
+        assert_eq!(filter.camera, Some("Pocket".to_string()));
+        assert_eq!(filter.captured_after, Some("2024-01-01".to_string()));
         assert_eq!(filter.sort_by, "title");
         assert_eq!(filter.sort_order, "asc");
+        assert_eq!(filter.limit, Some(10));
+        assert_eq!(filter.offset, Some(20));
+    }
+
+    #[test]
+    fn test_to_search_filter_unpaginated() {
+        let opts = MediaFilterOptions {
+            page: 3,
+            page_size: 0,
+            ..Default::default()
+        };
+        let filter = to_search_filter(&opts);
+        assert_eq!(filter.limit, None);
+        assert_eq!(filter.offset, None);
+    }
+
+    #[test]
+    fn test_highlight_snippet() {
+        let snippet = format!(
+            "a <b> & {}rust{} tips",
+            SNIPPET_MATCH_START, SNIPPET_MATCH_END
+        );
+        assert_eq!(
+            highlight_snippet(&snippet),
+            "a &lt;b&gt; &amp; <mark>rust</mark> tips"
+        );
     }
 }
@@ -1,16 +1,15 @@
 //! Media serving endpoints
 //! Handles serving images (original, WebP, thumbnails), videos, and documents
 
+use access_control::{Permission, SignedUrlParams};
 use axum::{
-    body::Body,
     extract::{Path, Query, State},
-    http::{header, StatusCode},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
     response::Response,
 };
 use db::error::DbError;
+use db::media::VideoServingInfo;
 use serde::Deserialize;
-use tokio::fs::File;
-use tokio_util::io::ReaderStream;
 use tower_sessions::Session;
 use tracing::{debug, error};
 
@@ -34,8 +33,10 @@ pub async fn serve_image_webp(
     session: Session,
     Path(slug): Path<String>,
     Query(query): Query<AccessQuery>,
+    Query(signed): Query<SignedUrlParams>,
+    headers: HeaderMap,
 ) -> Result<Response, StatusCode> {
-    serve_image_variant(state, session, slug, ImageVariant::WebP, query).await
+    serve_image_variant(state, session, slug, ImageVariant::WebP, query, signed, headers).await
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -49,6 +50,8 @@ async fn serve_image_variant(
     slug: String,
     variant: ImageVariant,
     query: AccessQuery,
+    signed: SignedUrlParams,
+    headers: HeaderMap,
 ) -> Result<Response, StatusCode> {
     debug!("Serving image: {} variant: {:?}", slug, variant);
 
@@ -79,12 +82,13 @@ async fn serve_image_variant(
 
     // Check access control
     if info.is_public == 0 {
-        // Private image - check ownership or access code
+        // Private image - check ownership, signed URL or access code
         let has_access = if let Some(ref uid) = user_id {
             info.user_id.as_ref() == Some(uid)
         } else {
             false
         };
+        let has_access = has_access || signed_url_grant(&state, info.id, &signed).await.is_some();
 
         if !has_access {
             // Check access code if provided
@@ -97,7 +101,8 @@ async fn serve_image_variant(
                             access_control::ResourceType::Image,
                             info.id,
                         )
-                        .with_key(code.clone()),
+                        .with_key(code.clone())
+                        .with_request_headers(&headers),
                         access_control::Permission::Read,
                     )
                     .await
@@ -134,21 +139,19 @@ async fn serve_image_variant(
         ImageVariant::WebP => {
             // Serve WebP version - try WebP first, then fall back to original
             let webp_filename = format!("{}.webp", slug);
-            let webp_path = state.user_storage.find_media_file(
-                &vault_id,
-                common::storage::MediaType::Image,
-                &webp_filename,
-            );
+            let webp_path = state
+                .user_storage
+                .find_stored_media_file(&vault_id, common::storage::MediaType::Image, &webp_filename)
+                .await;
 
             if let Some(path) = webp_path {
                 (path, "image/webp".to_string())
             } else {
                 // WebP doesn't exist, try original
-                let original_path = state.user_storage.find_media_file(
-                    &vault_id,
-                    common::storage::MediaType::Image,
-                    &info.filename,
-                );
+                let original_path = state
+                    .user_storage
+                    .find_stored_media_file(&vault_id, common::storage::MediaType::Image, &info.filename)
+                    .await;
 
                 match original_path {
                     Some(path) => {
@@ -157,61 +160,66 @@ async fn serve_image_variant(
                             .to_string();
                         (path, mime)
                     }
-                    None => return Err(StatusCode::NOT_FOUND),
+                    None => {
+                        error!("Image file not found: {}", slug);
+                        return Err(StatusCode::NOT_FOUND);
+                    }
                 }
             }
         }
     };
 
-    // Check if file exists
-    if !file_path.exists() {
-        error!("Image file not found: {:?}", file_path);
-        return Err(StatusCode::NOT_FOUND);
-    }
-
     // Increment view count
     let _ = state.repo.increment_view_count(info.id).await;
 
-    // Open and stream file
-    let file = File::open(&file_path).await.map_err(|e| {
-        error!("Failed to open file: {}", e);
-        StatusCode::INTERNAL_SERVER_ERROR
-    })?;
-
-    let stream = ReaderStream::new(file);
-    let body = Body::from_stream(stream);
-
-    let mut builder = Response::builder()
-        .status(StatusCode::OK)
-        .header(header::CONTENT_TYPE, &content_type)
-        .header(header::CACHE_CONTROL, "public, max-age=31536000") // Cache for 1 year
-        .header(header::ACCESS_CONTROL_ALLOW_ORIGIN, "*") // Allow embedding
-        .header("X-Content-Type-Options", "nosniff"); // Prevent MIME-sniffing
+    // Stream file from local disk or the storage backend
+    let mut response =
+        common::range::serve_stored_file(&state.user_storage, &file_path, &content_type, &headers)
+            .await
+            .map_err(|e| {
+                error!("Failed to open file: {}", e);
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?;
+
+    let response_headers = response.headers_mut();
+    response_headers.insert(
+        header::CACHE_CONTROL,
+        HeaderValue::from_static("public, max-age=31536000"), // Cache for 1 year
+    );
+    response_headers.insert(
+        header::ACCESS_CONTROL_ALLOW_ORIGIN,
+        HeaderValue::from_static("*"), // Allow embedding
+    );
+    response_headers.insert(
+        "X-Content-Type-Options",
+        HeaderValue::from_static("nosniff"), // Prevent MIME-sniffing
+    );
 
     // SVG files can contain embedded JavaScript — lock them down with CSP
     if content_type == "image/svg+xml" {
-        builder = builder
-            .header(
-                "Content-Security-Policy",
-                "default-src 'none'; style-src 'unsafe-inline'",
-            )
-            .header(
-                header::CONTENT_DISPOSITION,
-                "inline; filename=\"image.svg\"",
-            );
+        response_headers.insert(
+            "Content-Security-Policy",
+            HeaderValue::from_static("default-src 'none'; style-src 'unsafe-inline'"),
+        );
+        response_headers.insert(
+            header::CONTENT_DISPOSITION,
+            HeaderValue::from_static("inline; filename=\"image.svg\""),
+        );
     }
 
-    Ok(builder.body(body).unwrap())
+    Ok(response)
 }
 
 /// Serve media thumbnail (WebP)
 /// GET /media/{slug}/thumbnail
-/// Works for all media types: image, video, document
+/// Works for all media types: image, video, audio, document
 pub async fn serve_thumbnail(
     State(state): State<MediaManagerState>,
     session: Session,
     Path(slug): Path<String>,
     Query(query): Query<AccessQuery>,
+    Query(signed): Query<SignedUrlParams>,
+    headers: HeaderMap,
 ) -> Result<Response, StatusCode> {
     debug!("Serving thumbnail: {}", slug);
 
@@ -241,20 +249,23 @@ pub async fn serve_thumbnail(
         let has_access = user_id
             .as_ref()
             .map(|uid| info.user_id.as_ref() == Some(uid))
-            .unwrap_or(false);
+            .unwrap_or(false)
+            || signed_url_grant(&state, info.id, &signed).await.is_some();
 
         if !has_access {
             if let Some(code) = query.code {
                 let resource_type = match info.media_type.as_str() {
                     "video" => access_control::ResourceType::Video,
                     "image" => access_control::ResourceType::Image,
+                    "audio" => access_control::ResourceType::Audio,
                     _ => access_control::ResourceType::File,
                 };
                 let decision = state
                     .access_control
                     .check_access(
                         access_control::AccessContext::new(resource_type, info.id)
-                            .with_key(code.clone()),
+                            .with_key(code.clone())
+                            .with_request_headers(&headers),
                         access_control::Permission::Read,
                     )
                     .await
@@ -288,12 +299,14 @@ pub async fn serve_thumbnail(
     let media_type_enum = match info.media_type.as_str() {
         "video" => common::storage::MediaType::Video,
         "image" => common::storage::MediaType::Image,
+        "audio" => common::storage::MediaType::Audio,
         _ => common::storage::MediaType::Document,
     };
 
     let thumb_path = state
         .user_storage
-        .find_thumbnail(&vault_id, media_type_enum, &slug);
+        .find_stored_thumbnail(&vault_id, media_type_enum, &slug)
+        .await;
 
     // Resolve file path and content type
     let (file_path, content_type) = if let Some(p) = thumb_path {
@@ -302,14 +315,16 @@ pub async fn serve_thumbnail(
         // Try {slug}.webp (regular images converted to WebP on upload)
         let webp_path = state
             .user_storage
-            .find_media_file(&vault_id, common::storage::MediaType::Image, &format!("{}.webp", slug));
+            .find_stored_media_file(&vault_id, common::storage::MediaType::Image, &format!("{}.webp", slug))
+            .await;
         if let Some(p) = webp_path {
             (p, "image/webp".to_string())
         } else {
             // Fall back to original file (e.g. SVG stored as {filename})
             let orig_path = state
                 .user_storage
-                .find_media_file(&vault_id, common::storage::MediaType::Image, &info.filename);
+                .find_stored_media_file(&vault_id, common::storage::MediaType::Image, &info.filename)
+                .await;
             match orig_path {
                 Some(p) => {
                     let mime = mime_guess::from_path(&info.filename)
@@ -324,29 +339,197 @@ pub async fn serve_thumbnail(
         return Err(StatusCode::NOT_FOUND);
     };
 
-    let file = File::open(&file_path).await.map_err(|e| {
-        error!("Failed to open thumbnail: {}", e);
-        StatusCode::INTERNAL_SERVER_ERROR
-    })?;
-
-    let stream = ReaderStream::new(file);
-    let body = Body::from_stream(stream);
-
-    let mut builder = Response::builder()
-        .status(StatusCode::OK)
-        .header(header::CONTENT_TYPE, &content_type)
-        .header(header::CACHE_CONTROL, "public, max-age=31536000")
-        .header(header::ACCESS_CONTROL_ALLOW_ORIGIN, "*");
+    let mut response =
+        common::range::serve_stored_file(&state.user_storage, &file_path, &content_type, &headers)
+            .await
+            .map_err(|e| {
+                error!("Failed to open thumbnail: {}", e);
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?;
+
+    let response_headers = response.headers_mut();
+    // Video owners can replace the poster, so those thumbnails are revalidated
+    let cache_control = if info.media_type == "video" {
+        "no-cache"
+    } else {
+        "public, max-age=31536000"
+    };
+    response_headers.insert(header::CACHE_CONTROL, HeaderValue::from_static(cache_control));
+    response_headers.insert(header::ACCESS_CONTROL_ALLOW_ORIGIN, HeaderValue::from_static("*"));
 
     // SVG files can contain embedded JavaScript — add CSP when serving as thumbnail
     if content_type == "image/svg+xml" {
-        builder = builder.header(
+        response_headers.insert(
             "Content-Security-Policy",
-            "default-src 'none'; style-src 'unsafe-inline'",
+            HeaderValue::from_static("default-src 'none'; style-src 'unsafe-inline'"),
         );
     }
 
-    Ok(builder.body(body).unwrap())
+    Ok(response)
+}
+
+/// Read access to a video: public, owned by the session user, granted by a
+/// signed URL, or by an access code for the item or its vault.
+pub(crate) async fn check_video_access(
+    state: &MediaManagerState,
+    session: &Session,
+    headers: &HeaderMap,
+    info: &VideoServingInfo,
+    vault_id: &str,
+    signed: &SignedUrlParams,
+    code: Option<String>,
+) -> Result<(), StatusCode> {
+    let resource = AccessResource {
+        resource_type: access_control::ResourceType::Video,
+        id: info.id,
+        owner_id: info.user_id.as_deref(),
+        is_public: info.is_public != 0,
+        signed_permission: Permission::Read,
+    };
+    check_media_access(state, session, headers, resource, vault_id, signed, code).await
+}
+
+/// Permission granted by a signed URL for a media item, if the request
+/// carries a valid one.
+///
+/// A download grant is re-checked against the item's current
+/// `allow_download` and is downgraded to view access once the owner has
+/// disabled downloads.
+pub(crate) async fn signed_url_grant(
+    state: &MediaManagerState,
+    media_id: i32,
+    signed: &SignedUrlParams,
+) -> Option<Permission> {
+    if !signed.is_present() {
+        return None;
+    }
+    match state.access_control.url_signer().verify(media_id, signed) {
+        Ok(Permission::Download) => match state.repo.media_allows_download(media_id).await {
+            Ok(true) => Some(Permission::Download),
+            Ok(false) => {
+                debug!(media_id, "Download-signed URL used while downloads are disabled");
+                Some(Permission::Read)
+            }
+            Err(e) => {
+                error!("Database error: {}", e);
+                Some(Permission::Read)
+            }
+        },
+        Ok(permission) => Some(permission),
+        Err(e) => {
+            debug!(media_id, error = %e, "Signed URL rejected");
+            None
+        }
+    }
+}
+
+/// Serve the response as a download when it was requested through a
+/// download-signed URL.
+pub(crate) fn set_download_disposition(
+    response: &mut Response,
+    grant: Option<Permission>,
+    filename: &str,
+) {
+    if grant != Some(Permission::Download) {
+        return;
+    }
+    let filename = filename.replace(['"', '\\'], "_");
+    let value = HeaderValue::from_str(&format!("attachment; filename=\"{}\"", filename))
+        .unwrap_or_else(|_| HeaderValue::from_static("attachment"));
+    response.headers_mut().insert(header::CONTENT_DISPOSITION, value);
+}
+
+/// The item an access check is about
+pub(crate) struct AccessResource<'a> {
+    pub resource_type: access_control::ResourceType,
+    pub id: i32,
+    pub owner_id: Option<&'a str>,
+    pub is_public: bool,
+    /// Permission a signed URL must grant: `Download` for endpoints that
+    /// hand out the original file
+    pub signed_permission: Permission,
+}
+
+/// Read access to a media item: public, owned by the session user, granted
+/// by a signed URL carrying `resource.signed_permission`, or by an access
+/// code for the item or its vault.
+pub(crate) async fn check_media_access(
+    state: &MediaManagerState,
+    session: &Session,
+    headers: &HeaderMap,
+    resource: AccessResource<'_>,
+    vault_id: &str,
+    signed: &SignedUrlParams,
+    code: Option<String>,
+) -> Result<(), StatusCode> {
+    if resource.is_public {
+        return Ok(());
+    }
+    if let Some(grant) = signed_url_grant(state, resource.id, signed).await {
+        if grant >= resource.signed_permission {
+            return Ok(());
+        }
+        debug!(media_id = resource.id, ?grant, "Signed URL does not grant this endpoint");
+    }
+
+    let authenticated: bool = session
+        .get("authenticated")
+        .await
+        .ok()
+        .flatten()
+        .unwrap_or(false);
+    let user_id: Option<String> = if authenticated {
+        session.get("user_id").await.ok().flatten()
+    } else {
+        None
+    };
+
+    let has_access = user_id
+        .as_deref()
+        .map(|uid| resource.owner_id == Some(uid))
+        .unwrap_or(false);
+    if has_access {
+        return Ok(());
+    }
+
+    let Some(code) = code else {
+        return Err(StatusCode::UNAUTHORIZED);
+    };
+
+    let item_decision = state
+        .access_control
+        .check_access(
+            access_control::AccessContext::new(resource.resource_type, resource.id)
+                .with_key(code.clone())
+                .with_request_headers(headers),
+            access_control::Permission::Read,
+        )
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    if item_decision.granted {
+        return Ok(());
+    }
+
+    let folder_ok = state
+        .repo
+        .legacy_code_grants_vault_access(&code, vault_id)
+        .await
+        .unwrap_or(false)
+        || state
+            .repo
+            .workspace_code_grants_vault_access(&code, vault_id)
+            .await
+            .unwrap_or(false)
+        || state
+            .repo
+            .workspace_folder_code_grants_vault_via_owner(&code, vault_id)
+            .await
+            .unwrap_or(false);
+    if !folder_ok {
+        return Err(StatusCode::FORBIDDEN);
+    }
+    Ok(())
 }
 
 /// Serve MP4 video file directly
@@ -356,6 +539,8 @@ pub async fn serve_video_mp4(
     session: Session,
     Path(slug): Path<String>,
     Query(query): Query<AccessQuery>,
+    Query(signed): Query<SignedUrlParams>,
+    headers: HeaderMap,
 ) -> Result<Response, StatusCode> {
     use common::storage::MediaType;
 
@@ -376,76 +561,26 @@ pub async fn serve_video_mp4(
     }
 
     // All media should have vault_id now
-    let vault_id = info.vault_id.ok_or(StatusCode::INTERNAL_SERVER_ERROR)?;
-
-    // Access control (same pattern as image/thumbnail handlers)
-    if info.is_public == 0 {
-        let authenticated: bool = session
-            .get("authenticated")
-            .await
-            .ok()
-            .flatten()
-            .unwrap_or(false);
-        let user_id: Option<String> = if authenticated {
-            session.get("user_id").await.ok().flatten()
-        } else {
-            None
-        };
-
-        let has_access = user_id
-            .as_ref()
-            .map(|uid| info.user_id.as_ref() == Some(uid))
-            .unwrap_or(false);
-
-        if !has_access {
-            if let Some(code) = query.code {
-                let item_decision = state
-                    .access_control
-                    .check_access(
-                        access_control::AccessContext::new(
-                            access_control::ResourceType::Video,
-                            info.id,
-                        )
-                        .with_key(code.clone()),
-                        access_control::Permission::Read,
-                    )
-                    .await
-                    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-
-                if !item_decision.granted {
-                    let folder_ok = state
-                        .repo
-                        .legacy_code_grants_vault_access(&code, &vault_id)
-                        .await
-                        .unwrap_or(false)
-                        || state
-                            .repo
-                            .workspace_code_grants_vault_access(&code, &vault_id)
-                            .await
-                            .unwrap_or(false)
-                        || state
-                            .repo
-                            .workspace_folder_code_grants_vault_via_owner(&code, &vault_id)
-                            .await
-                            .unwrap_or(false);
-                    if !folder_ok {
-                        return Err(StatusCode::FORBIDDEN);
-                    }
-                }
-            } else {
-                return Err(StatusCode::UNAUTHORIZED);
-            }
-        }
-    }
+    let vault_id = info.vault_id.clone().ok_or(StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    // Access control (same pattern as image/thumbnail handlers). This is the
+    // original upload, so a signed URL needs the download permission
+    let resource = AccessResource {
+        resource_type: access_control::ResourceType::Video,
+        id: info.id,
+        owner_id: info.user_id.as_deref(),
+        is_public: info.is_public != 0,
+        signed_permission: Permission::Download,
+    };
+    check_media_access(&state, &session, &headers, resource, &vault_id, &signed, query.code).await?;
 
     // Find video file using vault-based storage
     // Videos are stored in subdirectories: {slug}/video.mp4
     let video_filename = format!("{}/video.mp4", slug);
-    let video_path = state.user_storage.find_media_file(
-        &vault_id,
-        MediaType::Video,
-        &video_filename,
-    );
+    let video_path = state
+        .user_storage
+        .find_stored_media_file(&vault_id, MediaType::Video, &video_filename)
+        .await;
 
     let video_path = match video_path {
         Some(path) => {
@@ -458,20 +593,26 @@ pub async fn serve_video_mp4(
         }
     };
 
-    // Open and stream file
-    let file = File::open(&video_path).await.map_err(|e| {
-        error!("Failed to open video: {}", e);
-        StatusCode::INTERNAL_SERVER_ERROR
-    })?;
-
-    let stream = ReaderStream::new(file);
-    let body = Body::from_stream(stream);
-
-    Ok(Response::builder()
-        .status(StatusCode::OK)
-        .header(header::CONTENT_TYPE, "video/mp4")
-        .header(header::CACHE_CONTROL, "public, max-age=31536000") // Cache for 1 year
-        .header(header::ACCESS_CONTROL_ALLOW_ORIGIN, "*") // Allow embedding
-        .body(body)
-        .unwrap())
+    // Stream file, honouring Range requests so players can seek
+    let mut response =
+        common::range::serve_stored_file(&state.user_storage, &video_path, "video/mp4", &headers)
+            .await
+            .map_err(|e| {
+                error!("Failed to open video: {}", e);
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?;
+
+    let response_headers = response.headers_mut();
+    response_headers.insert(
+        header::CACHE_CONTROL,
+        HeaderValue::from_static("public, max-age=31536000"), // Cache for 1 year
+    );
+    response_headers.insert(
+        header::ACCESS_CONTROL_ALLOW_ORIGIN,
+        HeaderValue::from_static("*"), // Allow embedding
+    );
+    let grant = signed_url_grant(&state, info.id, &signed).await;
+    set_download_disposition(&mut response, grant, &format!("{}.mp4", slug));
+
+    Ok(response)
 }
@@ -0,0 +1,175 @@
+//! Minting signed media URLs
+//!
+//! `POST /api/media/{slug}/signed-url` returns a time-limited URL for one
+//! media item that works without a session or access code, for embedding in
+//! an LMS or an email. Only the owner can mint URLs; API keys need the `read`
+//! scope, and vault-bound keys only work for items in their vault.
+//! Verification happens in the serving handlers and the HLS proxy (see
+//! `access_control::signed_urls`); `allow_download` is checked both here and
+//! each time a download URL is used.
+
+use access_control::signed_urls::{parse_permission, MAX_SIGNED_URL_TTL_SECS};
+use access_control::Permission;
+use api_keys::middleware::{require_scope_for, AuthenticatedUser};
+use api_keys::ResourceRef;
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    response::Json,
+    Extension,
+};
+use db::media::MediaItemRow;
+use serde::{Deserialize, Serialize};
+use tower_sessions::Session;
+use tracing::info;
+use video_manager::audio::AAC_RENDITION;
+
+use crate::routes::MediaManagerState;
+use crate::subtitles::{api_error, internal_error, ApiError};
+
+/// Lifetime of a signed URL when none is requested (1 hour)
+const DEFAULT_TTL_SECS: i64 = 60 * 60;
+
+/// Shortest lifetime a signed URL may be minted with
+const MIN_TTL_SECS: i64 = 60;
+
+#[derive(Debug, Deserialize)]
+pub struct SignedUrlRequest {
+    /// `view` (default) or `download`
+    pub permission: Option<String>,
+    /// Lifetime in seconds (default 1 hour, at most 7 days)
+    pub expires_in: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SignedUrlResponse {
+    pub slug: String,
+    pub permission: String,
+    /// Unix timestamp (seconds)
+    pub expires_at: i64,
+    /// Query string that signs any delivery URL of the item
+    pub query: String,
+    /// Delivery URL of the item's main file, if it has one for this permission
+    pub url: Option<String>,
+}
+
+/// Mint a signed URL for a media item owned by the caller.
+/// POST /api/media/{slug}/signed-url
+pub async fn create_signed_url(
+    State(state): State<MediaManagerState>,
+    session: Session,
+    user: Option<Extension<AuthenticatedUser>>,
+    Path(slug): Path<String>,
+    headers: HeaderMap,
+    Json(request): Json<SignedUrlRequest>,
+) -> Result<Json<SignedUrlResponse>, ApiError> {
+    let user_id: String = match &user {
+        Some(Extension(user)) => Some(user.user_id.clone()),
+        None => session.get("user_id").await.ok().flatten(),
+    }
+    .ok_or_else(|| api_error(StatusCode::UNAUTHORIZED, "Authentication required"))?;
+
+    let item = state
+        .repo
+        .get_media_by_slug(&slug)
+        .await
+        .map_err(|e| internal_error("Failed to load media", e))?
+        .ok_or_else(|| api_error(StatusCode::NOT_FOUND, "Media not found"))?;
+    if item.user_id.as_deref() != Some(user_id.as_str()) {
+        return Err(api_error(
+            StatusCode::FORBIDDEN,
+            "Only the owner can create signed URLs for this media",
+        ));
+    }
+    if let Some(Extension(user)) = &user {
+        let resource = item
+            .vault_id
+            .clone()
+            .map(ResourceRef::vault)
+            .unwrap_or_default();
+        require_scope_for(user, "read", &resource).map_err(|status| {
+            api_error(status, "API key does not grant read access to this media")
+        })?;
+    }
+
+    let perm = request.permission.as_deref().unwrap_or("view");
+    let permission = parse_permission(perm).ok_or_else(|| {
+        api_error(
+            StatusCode::BAD_REQUEST,
+            "permission must be \"view\" or \"download\"",
+        )
+    })?;
+    if permission == Permission::Download && item.allow_download == 0 {
+        return Err(api_error(
+            StatusCode::FORBIDDEN,
+            "Downloads are disabled for this media",
+        ));
+    }
+
+    let ttl = request.expires_in.unwrap_or(DEFAULT_TTL_SECS);
+    if !(MIN_TTL_SECS..=MAX_SIGNED_URL_TTL_SECS).contains(&ttl) {
+        return Err(api_error(
+            StatusCode::BAD_REQUEST,
+            &format!(
+                "expires_in must be between {} and {} seconds",
+                MIN_TTL_SECS, MAX_SIGNED_URL_TTL_SECS
+            ),
+        ));
+    }
+    let expires_at = chrono::Utc::now().timestamp() + ttl;
+
+    let query = state
+        .access_control
+        .url_signer()
+        .sign(item.id, permission, expires_at)
+        .and_then(|params| params.to_query())
+        .ok_or_else(|| api_error(StatusCode::INTERNAL_SERVER_ERROR, "Failed to sign URL"))?;
+    let url = delivery_path(&item, permission)
+        .map(|path| format!("{}{}?{}", base_url(&headers), path, query));
+
+    info!(
+        media_slug = %slug,
+        user_id = %user_id,
+        permission = %perm,
+        expires_at,
+        "Signed media URL created"
+    );
+
+    Ok(Json(SignedUrlResponse {
+        slug,
+        permission: perm.to_string(),
+        expires_at,
+        query,
+        url,
+    }))
+}
+
+/// Path of the endpoint that delivers the item's main file
+fn delivery_path(item: &MediaItemRow, permission: Permission) -> Option<String> {
+    let slug = &item.slug;
+    match item.media_type.as_str() {
+        // The MP4 is the original upload, so it is only reachable for downloads
+        "video" if item.video_type.as_deref() == Some("mp4") => {
+            (permission == Permission::Download).then(|| format!("/media/{}/video.mp4", slug))
+        }
+        "video" => Some(format!("/hls/{}/master.m3u8", slug)),
+        "image" if permission == Permission::Download => Some(format!("/media/{}/original", slug)),
+        "image" => Some(format!("/media/{}/image.webp", slug)),
+        "audio" => Some(format!("/media/{}/audio/{}", slug, AAC_RENDITION)),
+        "document" if item.filename.ends_with(".pdf") => Some(format!("/media/{}/serve", slug)),
+        _ => None,
+    }
+}
+
+/// Scheme and host the request was made to
+fn base_url(headers: &HeaderMap) -> String {
+    let host = headers
+        .get("host")
+        .and_then(|h| h.to_str().ok())
+        .unwrap_or("localhost:3000");
+    let scheme = headers
+        .get("x-forwarded-proto")
+        .and_then(|h| h.to_str().ok())
+        .unwrap_or("http");
+    format!("{}://{}", scheme, host)
+}
@@ -0,0 +1,324 @@
+//! Subtitle track management for videos
+//!
+//! Owners upload `.srt` or `.vtt` files per language; they are stored as
+//! WebVTT next to the video and packaged into the HLS master playlist by
+//! [`video_manager::subtitles`]. MP4 videos use the full `.vtt` files through
+//! `<track>` elements on the detail page.
+
+use access_control::SignedUrlParams;
+use axum::{
+    extract::{Multipart, Path, Query, State},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{Json, Response},
+};
+use common::storage::MediaType;
+use db::media::VideoServingInfo;
+use serde_json::{json, Value};
+use std::path::PathBuf;
+use tower_sessions::Session;
+use tracing::{error, info, warn};
+use video_manager::subtitles::{self as subs, MAX_SUBTITLE_SIZE};
+
+use crate::routes::MediaManagerState;
+use crate::serve::{check_video_access, AccessQuery};
+
+pub(crate) type ApiError = (StatusCode, Json<Value>);
+
+pub(crate) fn api_error(status: StatusCode, message: &str) -> ApiError {
+    (status, Json(json!({ "success": false, "error": message })))
+}
+
+pub(crate) fn internal_error(context: &str, e: impl std::fmt::Display) -> ApiError {
+    error!("{}: {}", context, e);
+    api_error(StatusCode::INTERNAL_SERVER_ERROR, context)
+}
+
+/// Resolve a video owned by the session user, with its storage directory
+pub(crate) async fn owned_video(
+    state: &MediaManagerState,
+    session: &Session,
+    slug: &str,
+) -> Result<(VideoServingInfo, PathBuf), ApiError> {
+    let authenticated: bool = session
+        .get("authenticated")
+        .await
+        .ok()
+        .flatten()
+        .unwrap_or(false);
+    if !authenticated {
+        return Err(api_error(StatusCode::UNAUTHORIZED, "Authentication required"));
+    }
+    let user_id: String = session
+        .get("user_id")
+        .await
+        .ok()
+        .flatten()
+        .ok_or_else(|| {
+            api_error(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "User ID not found in session",
+            )
+        })?;
+
+    let info = state
+        .repo
+        .get_video_for_serving(slug)
+        .await
+        .map_err(|e| internal_error("Failed to load video", e))?
+        .ok_or_else(|| api_error(StatusCode::NOT_FOUND, "Video not found"))?;
+
+    if info.user_id.as_deref() != Some(user_id.as_str()) {
+        return Err(api_error(
+            StatusCode::FORBIDDEN,
+            "Only the owner can edit this video",
+        ));
+    }
+
+    let vault_id = info
+        .vault_id
+        .as_deref()
+        .ok_or_else(|| api_error(StatusCode::INTERNAL_SERVER_ERROR, "Video has no vault"))?;
+    let video_dir = state
+        .user_storage
+        .vault_nested_media_path(vault_id, MediaType::Video, slug);
+
+    Ok((info, video_dir))
+}
+
+/// Files packaging reads: playlists, subtitle tracks and the first video
+/// segment (for the MPEG-TS timestamp offset)
+pub(crate) fn needed_for_packaging(name: &str) -> bool {
+    name.ends_with(".m3u8") || name.ends_with(".vtt") || name.ends_with("segment_000.ts")
+}
+
+/// Rebuild the HLS subtitle renditions after a change
+async fn repackage(state: &MediaManagerState, media_id: i64, video_dir: &std::path::Path) {
+    let tracks = match state.repo.list_subtitles(media_id).await {
+        Ok(tracks) => tracks,
+        Err(e) => {
+            warn!("Failed to list subtitles for packaging: {}", e);
+            return;
+        }
+    };
+
+    // Offloaded videos are packaged from a local working copy
+    if let Err(e) = state
+        .user_storage
+        .fetch_local_dir(video_dir, needed_for_packaging)
+        .await
+    {
+        warn!("Failed to fetch video files for packaging: {}", e);
+        return;
+    }
+    if let Err(e) = subs::package_subtitles(video_dir, &tracks).await {
+        warn!("Failed to package subtitles: {:#}", e);
+    }
+    if let Err(e) = state.user_storage.offload(video_dir).await {
+        warn!("Failed to offload packaged subtitles: {}", e);
+    }
+}
+
+/// List subtitle tracks
+/// GET /api/media/{slug}/subtitles
+pub async fn list_subtitles(
+    State(state): State<MediaManagerState>,
+    session: Session,
+    Path(slug): Path<String>,
+    Query(query): Query<AccessQuery>,
+    Query(signed): Query<SignedUrlParams>,
+    headers: HeaderMap,
+) -> Result<Json<Value>, ApiError> {
+    let info = state
+        .repo
+        .get_video_for_serving(&slug)
+        .await
+        .map_err(|e| internal_error("Failed to load video", e))?
+        .ok_or_else(|| api_error(StatusCode::NOT_FOUND, "Video not found"))?;
+    let vault_id = info.vault_id.clone().unwrap_or_default();
+
+    check_video_access(&state, &session, &headers, &info, &vault_id, &signed, query.code)
+        .await
+        .map_err(|status| api_error(status, "Access denied"))?;
+
+    let tracks = state
+        .repo
+        .list_subtitles(info.id as i64)
+        .await
+        .map_err(|e| internal_error("Failed to list subtitles", e))?;
+
+    Ok(Json(json!({ "success": true, "subtitles": tracks })))
+}
+
+/// Upload or replace the subtitle track for a language
+/// POST /api/media/{slug}/subtitles (multipart: file, language, label, default)
+pub async fn upload_subtitle(
+    State(state): State<MediaManagerState>,
+    session: Session,
+    Path(slug): Path<String>,
+    mut multipart: Multipart,
+) -> Result<Json<Value>, ApiError> {
+    let (info, video_dir) = owned_video(&state, &session, &slug).await?;
+
+    let mut language: Option<String> = None;
+    let mut label: Option<String> = None;
+    let mut is_default = false;
+    let mut file: Option<(String, Vec<u8>)> = None;
+
+    while let Some(field) = multipart.next_field().await.map_err(|e| {
+        error!("Multipart error: {}", e);
+        api_error(StatusCode::BAD_REQUEST, "Invalid form data")
+    })? {
+        let name = field.name().unwrap_or("").to_string();
+        match name.as_str() {
+            "file" => {
+                let filename = field.file_name().unwrap_or("").to_string();
+                let data = field
+                    .bytes()
+                    .await
+                    .map_err(|_| api_error(StatusCode::BAD_REQUEST, "Invalid file field"))?;
+                file = Some((filename, data.to_vec()));
+            }
+            "language" | "label" | "default" => {
+                let value = field
+                    .text()
+                    .await
+                    .map_err(|_| api_error(StatusCode::BAD_REQUEST, "Invalid form field"))?;
+                let value = value.trim().to_string();
+                match name.as_str() {
+                    "language" => language = Some(value),
+                    "label" if !value.is_empty() => label = Some(value),
+                    "default" => is_default = matches!(value.as_str(), "1" | "true" | "on"),
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let language = language
+        .as_deref()
+        .and_then(subs::normalize_language)
+        .ok_or_else(|| api_error(StatusCode::BAD_REQUEST, "A valid language code is required"))?;
+    let (filename, data) =
+        file.ok_or_else(|| api_error(StatusCode::BAD_REQUEST, "No subtitle file provided"))?;
+    if data.len() > MAX_SUBTITLE_SIZE {
+        return Err(api_error(
+            StatusCode::PAYLOAD_TOO_LARGE,
+            "Subtitle file is too large",
+        ));
+    }
+    let label: String = label.unwrap_or_else(|| language.clone()).chars().take(64).collect();
+
+    let vtt = subs::to_webvtt(&filename, &data)
+        .map_err(|e| api_error(StatusCode::BAD_REQUEST, &format!("{:#}", e)))?;
+
+    subs::write_track(&video_dir, &language, &vtt)
+        .await
+        .map_err(|e| internal_error("Failed to store subtitle file", format!("{:#}", e)))?;
+
+    let track = state
+        .repo
+        .upsert_subtitle(info.id as i64, &language, &label, is_default, false)
+        .await
+        .map_err(|e| internal_error("Failed to save subtitle track", e))?;
+
+    repackage(&state, info.id as i64, &video_dir).await;
+
+    info!("Subtitle track {} saved for {}", language, slug);
+    Ok(Json(json!({ "success": true, "subtitle": track })))
+}
+
+/// Remove the subtitle track for a language
+/// DELETE /api/media/{slug}/subtitles/{language}
+pub async fn delete_subtitle(
+    State(state): State<MediaManagerState>,
+    session: Session,
+    Path((slug, language)): Path<(String, String)>,
+) -> Result<Json<Value>, ApiError> {
+    let (info, video_dir) = owned_video(&state, &session, &slug).await?;
+
+    let language = subs::normalize_language(&language)
+        .ok_or_else(|| api_error(StatusCode::BAD_REQUEST, "Invalid language code"))?;
+
+    let deleted = state
+        .repo
+        .delete_subtitle(info.id as i64, &language)
+        .await
+        .map_err(|e| internal_error("Failed to delete subtitle track", e))?;
+    if !deleted {
+        return Err(api_error(StatusCode::NOT_FOUND, "Subtitle track not found"));
+    }
+
+    if let Err(e) = subs::remove_track(&video_dir, &language).await {
+        warn!("Failed to remove subtitle files: {:#}", e);
+    }
+    for path in [
+        subs::track_path(&video_dir, &language),
+        video_dir.join(subs::SUBTITLES_DIR).join(&language),
+    ] {
+        if let Err(e) = state.user_storage.remove_stored(&path).await {
+            warn!("Failed to remove stored subtitle files: {}", e);
+        }
+    }
+    repackage(&state, info.id as i64, &video_dir).await;
+
+    info!("Subtitle track {} removed from {}", language, slug);
+    Ok(Json(json!({ "success": true })))
+}
+
+/// Serve the full WebVTT file for a language
+/// GET /media/{slug}/subtitles/{language}.vtt
+pub async fn serve_subtitle(
+    State(state): State<MediaManagerState>,
+    session: Session,
+    Path((slug, file)): Path<(String, String)>,
+    Query(query): Query<AccessQuery>,
+    Query(signed): Query<SignedUrlParams>,
+    headers: HeaderMap,
+) -> Result<Response, StatusCode> {
+    // Only canonical language tags map to files; anything else is not a track
+    let language = file
+        .strip_suffix(".vtt")
+        .filter(|lang| subs::normalize_language(lang).as_deref() == Some(*lang))
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    let info = state
+        .repo
+        .get_video_for_serving(&slug)
+        .await
+        .map_err(|e| {
+            error!("Database error: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    let vault_id = info.vault_id.clone().ok_or(StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    check_video_access(&state, &session, &headers, &info, &vault_id, &signed, query.code).await?;
+
+    let path = state
+        .user_storage
+        .find_stored_media_file(
+            &vault_id,
+            MediaType::Video,
+            &format!("{}/{}/{}.vtt", slug, subs::SUBTITLES_DIR, language),
+        )
+        .await
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    let mut response = common::range::serve_stored_file(
+        &state.user_storage,
+        &path,
+        "text/vtt; charset=utf-8",
+        &headers,
+    )
+    .await
+    .map_err(|e| {
+        error!("Failed to open subtitle file: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    response
+        .headers_mut()
+        .insert(header::CACHE_CONTROL, HeaderValue::from_static("no-cache"));
+
+    Ok(response)
+}
@@ -17,6 +17,9 @@ pub struct MediaItemWithMetadata {
 
     /// Group name (if any)
     pub group_name: Option<String>,
+
+    /// Highlighted search match excerpt (HTML, escaped), for full-text searches
+    pub snippet: Option<String>,
 }
 
 /// Template for the unified media list page
@@ -59,6 +62,9 @@ pub struct MediaListTemplate {
     /// Count of images
     pub image_count: i64,
 
+    /// Count of audio items
+    pub audio_count: i64,
+
     /// Count of documents
     pub document_count: i64,
 
@@ -179,6 +185,32 @@ impl Default for MediaUploadTemplate {
     }
 }
 
+/// Trashed media item for the trash page
+pub struct TrashItemDisplay {
+    pub slug: String,
+    pub title: String,
+    pub media_type: String,
+    /// Human-readable file size
+    pub size: String,
+    pub deleted_at: String,
+    /// When the item is purged for good
+    pub purge_after: String,
+}
+
+/// Template for the media trash page
+#[derive(Template)]
+#[template(path = "media/trash.html")]
+pub struct MediaTrashTemplate {
+    /// Whether the current user is authenticated (required by base-tailwind user-menu)
+    pub authenticated: bool,
+
+    /// Trashed items, most recently deleted first
+    pub items: Vec<TrashItemDisplay>,
+
+    /// Days trashed items are kept before they are purged
+    pub retention_days: u64,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -198,6 +230,7 @@ mod tests {
             sort_order: "desc".to_string(),
             video_count: 0,
             image_count: 0,
+            audio_count: 0,
             document_count: 0,
             total_count: 0,
             all_vaults: vec![],
@@ -229,6 +262,7 @@ mod tests {
             sort_order: "desc".to_string(),
             video_count: 0,
             image_count: 0,
+            audio_count: 0,
             document_count: 0,
             total_count: 0,
             all_vaults: vec![],
@@ -259,6 +293,7 @@ mod tests {
             sort_order: "desc".to_string(),
             video_count: 0,
             image_count: 0,
+            audio_count: 0,
             document_count: 0,
             total_count: 0,
             all_vaults: vec![],
@@ -289,6 +324,7 @@ mod tests {
             sort_order: "desc".to_string(),
             video_count: 0,
             image_count: 0,
+            audio_count: 0,
             document_count: 0,
             total_count: 0,
             all_vaults: vec![],
@@ -0,0 +1,158 @@
+//! Speech-to-text transcripts of videos
+//!
+//! Transcripts are produced by the processing pipeline when an ASR backend is
+//! configured ([`video_manager::transcripts`]). Viewers read the timestamped
+//! segments; owners can run the transcription again, e.g. after switching
+//! backends or for videos uploaded before transcripts existed.
+
+use access_control::SignedUrlParams;
+use axum::{
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    response::Json,
+};
+use db::media::{TRANSCRIPT_COMPLETE, TRANSCRIPT_PENDING};
+use serde_json::{json, Value};
+use tower_sessions::Session;
+use tracing::{error, info, warn};
+
+use crate::routes::MediaManagerState;
+use crate::serve::{check_video_access, AccessQuery};
+use crate::subtitles::{api_error, internal_error, needed_for_packaging, owned_video, ApiError};
+
+/// Get a video's transcript
+/// GET /api/media/{slug}/transcript
+pub async fn get_transcript(
+    State(state): State<MediaManagerState>,
+    session: Session,
+    Path(slug): Path<String>,
+    Query(query): Query<AccessQuery>,
+    Query(signed): Query<SignedUrlParams>,
+    headers: HeaderMap,
+) -> Result<Json<Value>, ApiError> {
+    let info = state
+        .repo
+        .get_video_for_serving(&slug)
+        .await
+        .map_err(|e| internal_error("Failed to load video", e))?
+        .ok_or_else(|| api_error(StatusCode::NOT_FOUND, "Video not found"))?;
+    let vault_id = info.vault_id.clone().unwrap_or_default();
+
+    check_video_access(&state, &session, &headers, &info, &vault_id, &signed, query.code)
+        .await
+        .map_err(|status| api_error(status, "Access denied"))?;
+
+    let transcript = state
+        .repo
+        .get_transcript(info.id as i64)
+        .await
+        .map_err(|e| internal_error("Failed to load transcript", e))?
+        .ok_or_else(|| api_error(StatusCode::NOT_FOUND, "No transcript for this video"))?;
+    let segments = if transcript.status == TRANSCRIPT_COMPLETE {
+        state
+            .repo
+            .list_transcript_segments(info.id as i64)
+            .await
+            .map_err(|e| internal_error("Failed to load transcript", e))?
+    } else {
+        Vec::new()
+    };
+
+    Ok(Json(json!({
+        "success": true,
+        "transcript": transcript,
+        "segments": segments,
+    })))
+}
+
+/// Transcribe a video (again)
+/// POST /api/media/{slug}/transcript
+///
+/// Runs in the background; poll `GET` until the status leaves `pending`.
+pub async fn regenerate_transcript(
+    State(state): State<MediaManagerState>,
+    session: Session,
+    Path(slug): Path<String>,
+) -> Result<(StatusCode, Json<Value>), ApiError> {
+    let (info, video_dir) = owned_video(&state, &session, &slug).await?;
+    let transcriber = state
+        .transcriber
+        .clone()
+        .filter(|t| t.is_enabled())
+        .ok_or_else(|| {
+            api_error(
+                StatusCode::SERVICE_UNAVAILABLE,
+                "Speech-to-text is not configured",
+            )
+        })?;
+
+    let item = state
+        .repo
+        .get_media_by_slug(&slug)
+        .await
+        .map_err(|e| internal_error("Failed to load video", e))?
+        .ok_or_else(|| api_error(StatusCode::NOT_FOUND, "Video not found"))?;
+    if item.status != "active" {
+        return Err(api_error(
+            StatusCode::CONFLICT,
+            "The video is still processing",
+        ));
+    }
+    let media_id = info.id as i64;
+
+    let source = state
+        .user_storage
+        .fetch_local(&video_dir.join(&item.filename))
+        .await
+        .ok_or_else(|| {
+            api_error(
+                StatusCode::NOT_FOUND,
+                "The original video file is not available",
+            )
+        })?;
+
+    state
+        .repo
+        .set_transcript_status(
+            media_id,
+            transcriber.backend_name(),
+            TRANSCRIPT_PENDING,
+            None,
+        )
+        .await
+        .map_err(|e| internal_error("Failed to start transcription", e))?;
+
+    let task_state = state.clone();
+    let user_id = info.user_id.clone();
+    tokio::spawn(async move {
+        // Captions are packaged into the playlists, which may be offloaded
+        if let Err(e) = task_state
+            .user_storage
+            .fetch_local_dir(&video_dir, needed_for_packaging)
+            .await
+        {
+            warn!("Failed to fetch video files for packaging: {}", e);
+        }
+        let result = transcriber
+            .transcribe_video(
+                task_state.repo.as_ref(),
+                media_id,
+                &source,
+                &video_dir,
+                user_id.as_deref(),
+            )
+            .await;
+        if let Err(e) = result {
+            error!("Transcription of media {} failed: {:#}", media_id, e);
+        }
+        if let Err(e) = task_state.user_storage.offload(&video_dir).await {
+            warn!("Failed to offload transcript captions: {}", e);
+        }
+    });
+
+    info!("Transcription of {} started", slug);
+    Ok((
+        StatusCode::ACCEPTED,
+        Json(json!({ "success": true, "status": TRANSCRIPT_PENDING })),
+    ))
+}
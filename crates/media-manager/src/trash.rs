@@ -0,0 +1,405 @@
+//! Media trash
+//!
+//! Deleting a media item moves it to the trash: `media_items.deleted_at` is
+//! set and its files are moved to the vault's `.trash` area, keeping their
+//! layout below the vault root. Trashed items are hidden from listings,
+//! search and serving, can be restored until the retention period
+//! (`TRASH_RETENTION_DAYS`) runs out, and are then purged by the scheduled
+//! task in [`start_purge_task`].
+
+use askama::Template;
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{Html, IntoResponse, Json, Redirect, Response},
+};
+use chrono::{Duration, NaiveDateTime};
+use common::storage::{MediaType, UserStorageManager};
+use db::media::TrashedMedia;
+use serde_json::json;
+use std::path::PathBuf;
+use tower_sessions::Session;
+use tracing::{error, info, warn};
+use video_manager::cleanup;
+
+use crate::routes::MediaManagerState;
+use crate::templates::{MediaTrashTemplate, TrashItemDisplay};
+
+/// Stored paths that make up a media item in a vault
+///
+/// The media file (images, documents) or directory (videos, audio), every
+/// possible kept original of an image, and the thumbnail. Paths are not
+/// checked for existence.
+pub(crate) fn stored_paths(
+    storage: &UserStorageManager,
+    slug: &str,
+    media_type: &str,
+    filename: &str,
+    vault_id: &str,
+) -> Vec<PathBuf> {
+    let media_type_enum = match media_type {
+        "video" => MediaType::Video,
+        "image" => MediaType::Image,
+        "audio" => MediaType::Audio,
+        _ => MediaType::Document,
+    };
+
+    let mut paths = Vec::new();
+    if media_type == "video" || media_type == "audio" {
+        paths.push(storage.vault_nested_media_path(vault_id, media_type_enum, slug));
+    } else {
+        paths.push(storage.get_media_file_path(vault_id, media_type_enum, filename));
+        if media_type == "image" {
+            for ext in &["jpg", "jpeg", "png", "webp", "gif", "bmp"] {
+                let original_filename = format!("{}_original.{}", slug, ext);
+                paths.push(storage.get_media_file_path(
+                    vault_id,
+                    media_type_enum,
+                    &original_filename,
+                ));
+            }
+        }
+    }
+    paths.push(storage.get_thumbnail_path(vault_id, media_type_enum, slug));
+    paths
+}
+
+/// Move the files of a media item into the vault's trash
+///
+/// Resized image derivatives are dropped rather than kept.
+pub(crate) async fn move_files_to_trash(
+    state: &MediaManagerState,
+    slug: &str,
+    media_type: &str,
+    filename: &str,
+    vault_id: &str,
+) {
+    let storage = &state.user_storage;
+    for path in stored_paths(storage, slug, media_type, filename, vault_id) {
+        let Some(trash_path) = storage.vault_trash_path(vault_id, &path) else {
+            continue;
+        };
+        if let Err(e) = storage.move_stored(&path, &trash_path).await {
+            warn!("Failed to move {:?} to the trash: {}", path, e);
+        }
+    }
+    if media_type == "image" {
+        crate::derivatives::purge_cache(state, vault_id, slug).await;
+    }
+}
+
+/// Move the files of a media item back from the vault's trash
+async fn move_files_from_trash(state: &MediaManagerState, item: &TrashedMedia, vault_id: &str) {
+    let storage = &state.user_storage;
+    for path in stored_paths(
+        storage,
+        &item.slug,
+        &item.media_type,
+        &item.filename,
+        vault_id,
+    ) {
+        let Some(trash_path) = storage.vault_trash_path(vault_id, &path) else {
+            continue;
+        };
+        if let Err(e) = storage.move_stored(&trash_path, &path).await {
+            warn!("Failed to restore {:?} from the trash: {}", path, e);
+        }
+    }
+}
+
+/// Delete a trashed media item for good: database row and files
+///
+/// Files are removed from the trash and, in case moving them there failed,
+/// from their original location.
+pub async fn purge_media(
+    state: &MediaManagerState,
+    item: &TrashedMedia,
+) -> Result<(), db::DbError> {
+    if let Err(e) = state.repo.delete_media_tags_by_slug(&item.slug).await {
+        warn!("Failed to delete tags for {}: {}", item.slug, e);
+    }
+    state.repo.delete_media_by_slug(&item.slug).await?;
+
+    if let Some(vault_id) = &item.vault_id {
+        let storage = &state.user_storage;
+        for path in stored_paths(
+            storage,
+            &item.slug,
+            &item.media_type,
+            &item.filename,
+            vault_id,
+        ) {
+            if let Some(trash_path) = storage.vault_trash_path(vault_id, &path) {
+                if let Err(e) = storage.remove_stored(&trash_path).await {
+                    warn!("Failed to delete trashed file {:?}: {}", trash_path, e);
+                }
+            }
+        }
+        crate::list::remove_media_files(
+            state,
+            &item.slug,
+            &item.media_type,
+            &item.filename,
+            vault_id,
+        )
+        .await;
+    }
+    Ok(())
+}
+
+/// Purge media items trashed more than `retention_days` days ago
+///
+/// Returns the number of items purged.
+pub async fn purge_expired(state: &MediaManagerState, retention_days: u64) -> usize {
+    let expired = match state
+        .repo
+        .list_expired_trashed_media(retention_days as i64)
+        .await
+    {
+        Ok(expired) => expired,
+        Err(e) => {
+            error!("Failed to list expired trash: {}", e);
+            return 0;
+        }
+    };
+
+    let mut purged = 0;
+    for item in &expired {
+        match purge_media(state, item).await {
+            Ok(()) => purged += 1,
+            Err(e) => error!("Failed to purge trashed media {}: {}", item.slug, e),
+        }
+    }
+    purged
+}
+
+/// Spawn the scheduled purge of expired media trash
+pub fn start_purge_task(state: MediaManagerState) {
+    cleanup::spawn_trash_purge("media", move |retention_days| {
+        let state = state.clone();
+        async move { purge_expired(&state, retention_days).await }
+    });
+}
+
+/// When a trashed item is purged (`deleted_at` plus the retention period)
+fn purge_after(deleted_at: &str, retention_days: u64) -> Option<String> {
+    let deleted = NaiveDateTime::parse_from_str(deleted_at, "%Y-%m-%d %H:%M:%S").ok()?;
+    let purge = deleted + Duration::days(retention_days as i64);
+    Some(purge.format("%Y-%m-%d %H:%M").to_string())
+}
+
+fn format_size(size: i64) -> String {
+    if size < 1024 {
+        format!("{} B", size)
+    } else if size < 1024 * 1024 {
+        format!("{:.1} KB", size as f64 / 1024.0)
+    } else if size < 1024 * 1024 * 1024 {
+        format!("{:.1} MB", size as f64 / (1024.0 * 1024.0))
+    } else {
+        format!("{:.1} GB", size as f64 / (1024.0 * 1024.0 * 1024.0))
+    }
+}
+
+async fn session_user(session: &Session) -> Option<String> {
+    let authenticated: bool = session
+        .get("authenticated")
+        .await
+        .ok()
+        .flatten()
+        .unwrap_or(false);
+    if !authenticated {
+        return None;
+    }
+    session.get("user_id").await.ok().flatten()
+}
+
+// ── Handlers ────────────────────────────────────────────────────────
+
+/// Trash page
+/// GET /media/trash
+pub async fn trash_page(State(state): State<MediaManagerState>, session: Session) -> Response {
+    let Some(user_id) = session_user(&session).await else {
+        return Redirect::to("/login").into_response();
+    };
+
+    let items = match state.repo.list_trashed_media(&user_id).await {
+        Ok(items) => items,
+        Err(e) => {
+            error!("Failed to list trash: {}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Failed to load the trash",
+            )
+                .into_response();
+        }
+    };
+
+    let retention_days = cleanup::trash_retention_days();
+    let template = MediaTrashTemplate {
+        authenticated: true,
+        retention_days,
+        items: items
+            .into_iter()
+            .map(|item| TrashItemDisplay {
+                purge_after: purge_after(&item.deleted_at, retention_days).unwrap_or_default(),
+                size: format_size(item.file_size),
+                slug: item.slug,
+                title: item.title,
+                media_type: item.media_type,
+                deleted_at: item.deleted_at,
+            })
+            .collect(),
+    };
+
+    match template.render() {
+        Ok(html) => Html(html).into_response(),
+        Err(e) => {
+            error!("Template rendering error: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Template error: {}", e),
+            )
+                .into_response()
+        }
+    }
+}
+
+/// Trashed media of the signed-in user
+/// GET /api/media/trash
+pub async fn list_trash(
+    State(state): State<MediaManagerState>,
+    session: Session,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    let user_id = session_user(&session)
+        .await
+        .ok_or(StatusCode::UNAUTHORIZED)?;
+    let items = state.repo.list_trashed_media(&user_id).await.map_err(|e| {
+        error!("Failed to list trash: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let retention_days = cleanup::trash_retention_days();
+    let items: Vec<_> = items
+        .into_iter()
+        .map(|item| {
+            json!({
+                "slug": item.slug,
+                "title": item.title,
+                "media_type": item.media_type,
+                "file_size": item.file_size,
+                "vault_id": item.vault_id,
+                "deleted_at": item.deleted_at,
+                "purge_after": purge_after(&item.deleted_at, retention_days),
+            })
+        })
+        .collect();
+
+    Ok(Json(json!({
+        "retention_days": retention_days,
+        "count": items.len(),
+        "items": items,
+    })))
+}
+
+/// Restore a trashed media item
+/// POST /api/media/trash/{slug}/restore
+pub async fn restore_from_trash(
+    State(state): State<MediaManagerState>,
+    session: Session,
+    Path(slug): Path<String>,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    let user_id = session_user(&session)
+        .await
+        .ok_or(StatusCode::UNAUTHORIZED)?;
+    let item = state
+        .repo
+        .get_trashed_media(&slug, &user_id)
+        .await
+        .map_err(|e| {
+            error!("Database error: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    // Files first, so a restored item is never served without them
+    if let Some(vault_id) = &item.vault_id {
+        move_files_from_trash(&state, &item, vault_id).await;
+    }
+    let restored = state
+        .repo
+        .restore_media(&slug, &user_id)
+        .await
+        .map_err(|e| {
+            error!("Failed to restore {}: {}", slug, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    if !restored {
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    info!("Restored media from trash: {}", slug);
+    Ok(Json(json!({ "success": true, "slug": slug })))
+}
+
+/// Delete a trashed media item for good
+/// DELETE /api/media/trash/{slug}
+pub async fn delete_from_trash(
+    State(state): State<MediaManagerState>,
+    session: Session,
+    Path(slug): Path<String>,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    let user_id = session_user(&session)
+        .await
+        .ok_or(StatusCode::UNAUTHORIZED)?;
+    let item = state
+        .repo
+        .get_trashed_media(&slug, &user_id)
+        .await
+        .map_err(|e| {
+            error!("Database error: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    purge_media(&state, &item).await.map_err(|e| {
+        error!("Failed to purge {}: {}", slug, e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    info!("Deleted media from trash: {}", slug);
+    Ok(Json(json!({ "success": true })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_purge_after() {
+        assert_eq!(
+            purge_after("2026-01-30 12:00:00", 30).as_deref(),
+            Some("2026-03-01 12:00")
+        );
+        assert!(purge_after("yesterday", 30).is_none());
+    }
+
+    #[test]
+    fn test_stored_paths_of_video_and_image() {
+        let storage = UserStorageManager::new("/srv/storage");
+
+        let video = stored_paths(&storage, "clip", "video", "clip/master.m3u8", "vault-1");
+        assert_eq!(
+            video,
+            vec![
+                PathBuf::from("/srv/storage/vaults/vault-1/media/videos/clip"),
+                PathBuf::from("/srv/storage/vaults/vault-1/thumbnails/videos/clip_thumb.webp"),
+            ]
+        );
+
+        let image = stored_paths(&storage, "beach", "image", "beach.webp", "vault-1");
+        assert_eq!(image.len(), 8);
+        assert!(image.contains(&PathBuf::from(
+            "/srv/storage/vaults/vault-1/media/images/beach_original.jpg"
+        )));
+    }
+}
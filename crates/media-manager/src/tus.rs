@@ -0,0 +1,789 @@
+//! Resumable uploads (tus 1.0)
+//!
+//! Implements the tus core protocol with the `creation`, `termination` and
+//! `expiration` extensions so large media can be uploaded in chunks and
+//! resumed after a dropped connection.
+//!
+//! Chunks are appended to `{temp}/tus/{id}.bin`; the upload's length, form
+//! fields (sent as `Upload-Metadata`) and expiry live next to it in
+//! `{id}.json`. The current offset is simply the size of the `.bin` file.
+//! Once the last byte arrives the assembled file is handed to the regular
+//! upload pipeline ([`crate::upload::process_upload`]), so validation and
+//! per-type processing are identical to multipart uploads.
+//!
+//! Progress is tracked under the upload id in the shared
+//! [`crate::progress::ProgressTracker`]; clients can follow it on
+//! `/api/media/{id}/progress/ws` like any transcoding job.
+
+use axum::{
+    body::Body,
+    extract::{Path, State},
+    http::{header, HeaderMap, HeaderName, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use base64::Engine;
+use chrono::{DateTime, Duration, Utc};
+use common::models::MediaType;
+use common::storage::UserStorageManager;
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::PathBuf;
+use tokio::io::AsyncWriteExt;
+use tower_sessions::Session;
+use tracing::{error, info, warn};
+
+use crate::routes::MediaManagerState;
+use crate::upload::{max_size_for, process_upload, UploadData, UploadForm};
+
+/// Protocol version implemented by this server
+pub const TUS_VERSION: &str = "1.0.0";
+
+/// Supported protocol extensions
+pub const TUS_EXTENSIONS: &str = "creation,termination,expiration";
+
+/// How long an upload may sit idle before it expires; every PATCH renews it
+pub const UPLOAD_EXPIRY_HOURS: i64 = 24;
+
+/// How often expired uploads are swept from the staging directory
+const CLEANUP_INTERVAL_SECS: u64 = 60 * 60;
+
+const TUS_RESUMABLE: HeaderName = HeaderName::from_static("tus-resumable");
+const TUS_VERSION_HEADER: HeaderName = HeaderName::from_static("tus-version");
+const TUS_EXTENSION: HeaderName = HeaderName::from_static("tus-extension");
+const TUS_MAX_SIZE: HeaderName = HeaderName::from_static("tus-max-size");
+const UPLOAD_LENGTH: HeaderName = HeaderName::from_static("upload-length");
+const UPLOAD_OFFSET: HeaderName = HeaderName::from_static("upload-offset");
+const UPLOAD_METADATA: HeaderName = HeaderName::from_static("upload-metadata");
+const UPLOAD_EXPIRES: HeaderName = HeaderName::from_static("upload-expires");
+const MEDIA_SLUG: HeaderName = HeaderName::from_static("x-media-slug");
+const MEDIA_ID: HeaderName = HeaderName::from_static("x-media-id");
+
+/// State of a resumable upload, persisted as `{id}.json`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct TusUpload {
+    id: String,
+    user_id: String,
+    tenant_id: String,
+    /// Total size announced in `Upload-Length`
+    length: u64,
+    form: UploadForm,
+    expires_at: DateTime<Utc>,
+    /// Response of the upload pipeline once the upload has completed
+    result: Option<Value>,
+}
+
+impl TusUpload {
+    fn is_expired(&self) -> bool {
+        self.expires_at <= Utc::now()
+    }
+}
+
+/// Error response carrying the `Tus-Resumable` header
+#[derive(Debug)]
+pub struct TusError {
+    status: StatusCode,
+    body: Option<Value>,
+    headers: Vec<(HeaderName, String)>,
+}
+
+impl TusError {
+    fn new(status: StatusCode, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            body: Some(serde_json::json!({ "error": message.into() })),
+            headers: Vec::new(),
+        }
+    }
+
+    /// Error without a body, described by protocol headers only
+    fn with_headers(status: StatusCode, headers: Vec<(HeaderName, String)>) -> Self {
+        Self {
+            status,
+            body: None,
+            headers,
+        }
+    }
+
+    fn message(&self) -> Option<&str> {
+        self.body.as_ref()?.get("error")?.as_str()
+    }
+}
+
+impl From<(StatusCode, Json<Value>)> for TusError {
+    fn from((status, Json(body)): (StatusCode, Json<Value>)) -> Self {
+        Self {
+            status,
+            body: Some(body),
+            headers: Vec::new(),
+        }
+    }
+}
+
+impl IntoResponse for TusError {
+    fn into_response(self) -> Response {
+        let response = match self.body {
+            Some(body) => (self.status, Json(body)).into_response(),
+            None => self.status.into_response(),
+        };
+        with_tus_headers(response, self.headers)
+    }
+}
+
+/// Empty response with the `Tus-Resumable` header and the given headers
+fn tus_response(status: StatusCode, headers: Vec<(HeaderName, String)>) -> Response {
+    with_tus_headers(status.into_response(), headers)
+}
+
+fn with_tus_headers(mut response: Response, headers: Vec<(HeaderName, String)>) -> Response {
+    let response_headers = response.headers_mut();
+    response_headers.insert(TUS_RESUMABLE, HeaderValue::from_static(TUS_VERSION));
+    for (name, value) in headers {
+        if let Ok(value) = HeaderValue::from_str(&value) {
+            response_headers.insert(name, value);
+        }
+    }
+    response
+}
+
+// ── Staging storage ─────────────────────────────────────────────────
+
+fn staging_dir(storage: &UserStorageManager) -> PathBuf {
+    storage.temp_dir().join("tus")
+}
+
+fn data_path(storage: &UserStorageManager, id: &str) -> PathBuf {
+    staging_dir(storage).join(format!("{}.bin", id))
+}
+
+fn info_path(storage: &UserStorageManager, id: &str) -> PathBuf {
+    staging_dir(storage).join(format!("{}.json", id))
+}
+
+async fn save_upload(storage: &UserStorageManager, upload: &TusUpload) -> Result<(), TusError> {
+    let json = serde_json::to_vec(upload).map_err(|e| {
+        error!("Failed to serialize upload {}: {}", upload.id, e);
+        TusError::new(StatusCode::INTERNAL_SERVER_ERROR, "Failed to store upload")
+    })?;
+    tokio::fs::write(info_path(storage, &upload.id), json)
+        .await
+        .map_err(|e| {
+            error!("Failed to write upload info {}: {}", upload.id, e);
+            TusError::new(StatusCode::INTERNAL_SERVER_ERROR, "Failed to store upload")
+        })
+}
+
+/// Load an upload owned by `user_id`
+///
+/// Unknown ids and uploads of other users are both reported as 404;
+/// expired uploads are removed and reported as 410.
+async fn load_upload(
+    state: &MediaManagerState,
+    id: &str,
+    user_id: &str,
+) -> Result<TusUpload, TusError> {
+    let not_found = || TusError::new(StatusCode::NOT_FOUND, "Upload not found");
+
+    // Ids are generated UUIDs; anything else must not reach the filesystem
+    if uuid::Uuid::parse_str(id).is_err() {
+        return Err(not_found());
+    }
+
+    let json = tokio::fs::read(info_path(&state.user_storage, id))
+        .await
+        .map_err(|_| not_found())?;
+    let upload: TusUpload = serde_json::from_slice(&json).map_err(|e| {
+        error!("Corrupt upload info {}: {}", id, e);
+        TusError::new(StatusCode::INTERNAL_SERVER_ERROR, "Corrupt upload state")
+    })?;
+
+    if upload.user_id != user_id {
+        return Err(not_found());
+    }
+    if upload.is_expired() {
+        remove_upload(state, id).await;
+        return Err(TusError::new(StatusCode::GONE, "Upload expired"));
+    }
+
+    Ok(upload)
+}
+
+/// Bytes received so far
+async fn current_offset(storage: &UserStorageManager, upload: &TusUpload) -> u64 {
+    if upload.result.is_some() {
+        return upload.length;
+    }
+    tokio::fs::metadata(data_path(storage, &upload.id))
+        .await
+        .map(|m| m.len())
+        .unwrap_or(0)
+}
+
+/// Delete the staged data, the upload info and its progress entry
+async fn remove_upload(state: &MediaManagerState, id: &str) {
+    let _ = tokio::fs::remove_file(data_path(&state.user_storage, id)).await;
+    let _ = tokio::fs::remove_file(info_path(&state.user_storage, id)).await;
+    state.hls_progress.remove(id);
+}
+
+/// Remove expired uploads from the staging directory
+///
+/// Returns the ids of the removed uploads.
+pub async fn cleanup_expired_uploads(state: &MediaManagerState) -> Vec<String> {
+    let mut removed = Vec::new();
+    let Ok(mut entries) = tokio::fs::read_dir(staging_dir(&state.user_storage)).await else {
+        return removed;
+    };
+
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        let path = entry.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("json") {
+            continue;
+        }
+        let Some(id) = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .map(str::to_string)
+        else {
+            continue;
+        };
+
+        let expired = match tokio::fs::read(&path).await {
+            Ok(json) => serde_json::from_slice::<TusUpload>(&json)
+                .map(|upload| upload.is_expired())
+                .unwrap_or(true),
+            Err(_) => false,
+        };
+        if expired {
+            remove_upload(state, &id).await;
+            removed.push(id);
+        }
+    }
+
+    removed
+}
+
+/// Spawn a background task that periodically removes expired uploads
+pub fn start_expiration_task(state: MediaManagerState) {
+    tokio::spawn(async move {
+        let mut interval =
+            tokio::time::interval(std::time::Duration::from_secs(CLEANUP_INTERVAL_SECS));
+        loop {
+            interval.tick().await;
+            let removed = cleanup_expired_uploads(&state).await;
+            if !removed.is_empty() {
+                info!("Removed {} expired resumable upload(s)", removed.len());
+            }
+        }
+    });
+}
+
+// ── Header helpers ──────────────────────────────────────────────────
+
+/// Format a timestamp as an RFC 7231 HTTP date (used by `Upload-Expires`)
+fn http_date(time: DateTime<Utc>) -> String {
+    time.format("%a, %d %b %Y %H:%M:%S GMT").to_string()
+}
+
+/// Parse `Upload-Metadata` (`key base64value,key2 base64value2`) into form fields
+///
+/// Keys without a value set an empty string; unknown keys are ignored.
+fn parse_metadata(header: &str) -> Result<UploadForm, String> {
+    let mut form = UploadForm::default();
+
+    for pair in header.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+        let mut parts = pair.splitn(2, ' ');
+        let key = parts.next().unwrap_or_default();
+        let value = match parts.next().map(str::trim) {
+            Some(encoded) if !encoded.is_empty() => {
+                let bytes = base64::engine::general_purpose::STANDARD
+                    .decode(encoded)
+                    .map_err(|_| format!("Invalid base64 value for '{}'", key))?;
+                String::from_utf8(bytes)
+                    .map_err(|_| format!("Invalid UTF-8 value for '{}'", key))?
+            }
+            _ => String::new(),
+        };
+        form.set_field(key, value);
+    }
+
+    Ok(form)
+}
+
+fn header_u64(headers: &HeaderMap, name: &HeaderName) -> Option<u64> {
+    headers.get(name)?.to_str().ok()?.trim().parse().ok()
+}
+
+/// Reject requests speaking another protocol version (412 per the tus spec)
+fn check_resumable(headers: &HeaderMap) -> Result<(), TusError> {
+    match headers.get(&TUS_RESUMABLE).and_then(|v| v.to_str().ok()) {
+        Some(TUS_VERSION) => Ok(()),
+        _ => Err(TusError::with_headers(
+            StatusCode::PRECONDITION_FAILED,
+            vec![(TUS_VERSION_HEADER, TUS_VERSION.to_string())],
+        )),
+    }
+}
+
+/// The authenticated user's id and tenant
+async fn session_user(session: &Session) -> Result<(String, String), TusError> {
+    let authenticated: bool = session
+        .get("authenticated")
+        .await
+        .ok()
+        .flatten()
+        .unwrap_or(false);
+    if !authenticated {
+        return Err(TusError::new(
+            StatusCode::UNAUTHORIZED,
+            "You must be logged in to upload media.",
+        ));
+    }
+
+    let user_id: String = session.get("user_id").await.ok().flatten().ok_or_else(|| {
+        TusError::new(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "User ID not found in session. Please log in again.",
+        )
+    })?;
+    let tenant_id: String = session
+        .get("tenant_id")
+        .await
+        .ok()
+        .flatten()
+        .unwrap_or_else(|| "platform".to_string());
+
+    Ok((user_id, tenant_id))
+}
+
+/// Headers identifying the media item created by a completed upload
+fn result_headers(upload: &TusUpload) -> Vec<(HeaderName, String)> {
+    let Some(result) = &upload.result else {
+        return Vec::new();
+    };
+    let mut headers = Vec::new();
+    if let Some(slug) = result.get("slug").and_then(Value::as_str) {
+        headers.push((MEDIA_SLUG, slug.to_string()));
+    }
+    if let Some(id) = result.get("id").and_then(Value::as_i64) {
+        headers.push((MEDIA_ID, id.to_string()));
+    }
+    headers
+}
+
+/// Upload progress as a percentage, kept below 100 until processing is done
+fn upload_percent(offset: u64, length: u64) -> u8 {
+    if length == 0 {
+        return 99;
+    }
+    ((offset.saturating_mul(100) / length).min(99)) as u8
+}
+
+// ── Handlers ────────────────────────────────────────────────────────
+
+/// Advertise server capabilities
+/// OPTIONS /api/media/tus
+pub async fn tus_options() -> Response {
+    tus_response(
+        StatusCode::NO_CONTENT,
+        vec![
+            (TUS_VERSION_HEADER, TUS_VERSION.to_string()),
+            (TUS_EXTENSION, TUS_EXTENSIONS.to_string()),
+            (TUS_MAX_SIZE, max_size_for(&MediaType::Video).to_string()),
+        ],
+    )
+}
+
+/// Create a resumable upload
+/// POST /api/media/tus
+///
+/// Requires `Upload-Length`; the upload form fields (`media_type`, `title`,
+/// `is_public`, `filename`, ...) are passed in `Upload-Metadata`.
+pub async fn create_upload(
+    session: Session,
+    State(state): State<MediaManagerState>,
+    headers: HeaderMap,
+) -> Result<Response, TusError> {
+    check_resumable(&headers)?;
+    let (user_id, tenant_id) = session_user(&session).await?;
+
+    let length = header_u64(&headers, &UPLOAD_LENGTH)
+        .ok_or_else(|| TusError::new(StatusCode::BAD_REQUEST, "Upload-Length is required"))?;
+
+    let metadata = headers
+        .get(&UPLOAD_METADATA)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default();
+    let form = parse_metadata(metadata).map_err(|e| TusError::new(StatusCode::BAD_REQUEST, e))?;
+
+    // Fail fast on what the pipeline would reject after the whole transfer
+    let media_type: MediaType = form
+        .media_type
+        .as_deref()
+        .ok_or_else(|| TusError::new(StatusCode::BAD_REQUEST, "media_type is required"))
+        .and_then(|m| {
+            m.parse().map_err(|e: String| {
+                TusError::new(
+                    StatusCode::BAD_REQUEST,
+                    format!("Invalid media_type: {}", e),
+                )
+            })
+        })?;
+    for (field, present) in [
+        ("title", form.title.is_some()),
+        ("is_public", form.is_public.is_some()),
+        ("filename", form.filename.is_some()),
+    ] {
+        if !present {
+            return Err(TusError::new(
+                StatusCode::BAD_REQUEST,
+                format!("{} is required", field),
+            ));
+        }
+    }
+
+    let max_size = max_size_for(&media_type);
+    if length > max_size {
+        warn!(
+            event = "upload_rejected",
+            reason = "file_too_large",
+            size = length,
+            max = max_size,
+            "Resumable upload exceeds size limit"
+        );
+        return Err(TusError::new(
+            StatusCode::PAYLOAD_TOO_LARGE,
+            format!("File too large: maximum is {} bytes", max_size),
+        ));
+    }
+
+    let id = uuid::Uuid::new_v4().to_string();
+    let upload = TusUpload {
+        id: id.clone(),
+        user_id,
+        tenant_id,
+        length,
+        form,
+        expires_at: Utc::now() + Duration::hours(UPLOAD_EXPIRY_HOURS),
+        result: None,
+    };
+
+    let storage_error = |e: std::io::Error| {
+        error!("Failed to create resumable upload: {}", e);
+        TusError::new(StatusCode::INTERNAL_SERVER_ERROR, "Failed to create upload")
+    };
+    tokio::fs::create_dir_all(staging_dir(&state.user_storage))
+        .await
+        .map_err(storage_error)?;
+    tokio::fs::File::create(data_path(&state.user_storage, &id))
+        .await
+        .map_err(storage_error)?;
+    save_upload(&state.user_storage, &upload).await?;
+
+    state.hls_progress.start(&id);
+    state.hls_progress.update(&id, "Uploading".to_string(), 0);
+
+    info!(
+        event = "tus_upload_created",
+        upload_id = %id,
+        length = length,
+        "Resumable upload created"
+    );
+
+    Ok(tus_response(
+        StatusCode::CREATED,
+        vec![
+            (header::LOCATION, format!("/api/media/tus/{}", id)),
+            (UPLOAD_EXPIRES, http_date(upload.expires_at)),
+        ],
+    ))
+}
+
+/// Report how many bytes of an upload have been received
+/// HEAD /api/media/tus/{id}
+pub async fn upload_offset(
+    session: Session,
+    State(state): State<MediaManagerState>,
+    Path(id): Path<String>,
+    headers: HeaderMap,
+) -> Result<Response, TusError> {
+    check_resumable(&headers)?;
+    let (user_id, _) = session_user(&session).await?;
+    let upload = load_upload(&state, &id, &user_id).await?;
+
+    let offset = current_offset(&state.user_storage, &upload).await;
+    let mut response_headers = vec![
+        (UPLOAD_OFFSET, offset.to_string()),
+        (UPLOAD_LENGTH, upload.length.to_string()),
+        (header::CACHE_CONTROL, "no-store".to_string()),
+    ];
+    if upload.result.is_none() {
+        response_headers.push((UPLOAD_EXPIRES, http_date(upload.expires_at)));
+    }
+    response_headers.extend(result_headers(&upload));
+
+    Ok(tus_response(StatusCode::OK, response_headers))
+}
+
+/// Append a chunk to an upload
+/// PATCH /api/media/tus/{id}
+///
+/// The final chunk hands the assembled file to the upload pipeline; the
+/// response then carries `X-Media-Slug` / `X-Media-Id` of the new item.
+pub async fn append_chunk(
+    session: Session,
+    State(state): State<MediaManagerState>,
+    Path(id): Path<String>,
+    headers: HeaderMap,
+    body: Body,
+) -> Result<Response, TusError> {
+    check_resumable(&headers)?;
+    let (user_id, _) = session_user(&session).await?;
+
+    let content_type = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default();
+    if content_type != "application/offset+octet-stream" {
+        return Err(TusError::new(
+            StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            "Content-Type must be application/offset+octet-stream",
+        ));
+    }
+    let client_offset = header_u64(&headers, &UPLOAD_OFFSET)
+        .ok_or_else(|| TusError::new(StatusCode::BAD_REQUEST, "Upload-Offset is required"))?;
+
+    let mut upload = load_upload(&state, &id, &user_id).await?;
+
+    // One writer per upload; a concurrent PATCH would corrupt the offset
+    let _guard = PatchGuard::acquire(&state, &id)
+        .ok_or_else(|| TusError::new(StatusCode::CONFLICT, "Upload is already receiving data"))?;
+
+    let offset = current_offset(&state.user_storage, &upload).await;
+    if client_offset != offset {
+        return Err(TusError::with_headers(
+            StatusCode::CONFLICT,
+            vec![(UPLOAD_OFFSET, offset.to_string())],
+        ));
+    }
+    if upload.result.is_some() {
+        // Already complete (e.g. a retried final PATCH)
+        let mut response_headers = vec![(UPLOAD_OFFSET, offset.to_string())];
+        response_headers.extend(result_headers(&upload));
+        return Ok(tus_response(StatusCode::NO_CONTENT, response_headers));
+    }
+
+    let (new_offset, overflow) = write_chunk(&state, &upload, offset, body).await?;
+
+    upload.expires_at = Utc::now() + Duration::hours(UPLOAD_EXPIRY_HOURS);
+    save_upload(&state.user_storage, &upload).await?;
+
+    if overflow {
+        return Err(TusError::new(
+            StatusCode::PAYLOAD_TOO_LARGE,
+            "Chunk exceeds Upload-Length",
+        ));
+    }
+
+    if new_offset < upload.length {
+        return Ok(tus_response(
+            StatusCode::NO_CONTENT,
+            vec![
+                (UPLOAD_OFFSET, new_offset.to_string()),
+                (UPLOAD_EXPIRES, http_date(upload.expires_at)),
+            ],
+        ));
+    }
+
+    // ── Upload complete: hand off to the upload pipeline ────────────
+    info!(
+        event = "tus_upload_complete",
+        upload_id = %id,
+        length = upload.length,
+        "Resumable upload received, processing"
+    );
+    state.hls_progress.update(&id, "Processing".to_string(), 99);
+
+    let file_data = UploadData::Staged {
+        path: data_path(&state.user_storage, &id),
+        len: upload.length,
+    };
+    match process_upload(
+        &state,
+        upload.user_id.clone(),
+        upload.tenant_id.clone(),
+        upload.form.clone(),
+        Some(file_data),
+        None,
+    )
+    .await
+    {
+        Ok(Json(result)) => {
+            upload.result = Some(result);
+            // Keep the info until it expires so HEAD can still report the item
+            save_upload(&state.user_storage, &upload).await?;
+            let _ = tokio::fs::remove_file(data_path(&state.user_storage, &id)).await;
+            state.hls_progress.complete(&id);
+
+            let mut response_headers = vec![(UPLOAD_OFFSET, new_offset.to_string())];
+            response_headers.extend(result_headers(&upload));
+            Ok(tus_response(StatusCode::NO_CONTENT, response_headers))
+        }
+        Err(e) => {
+            let error = TusError::from(e);
+            let message = error
+                .message()
+                .unwrap_or("Upload processing failed")
+                .to_string();
+            warn!("Resumable upload {} rejected: {}", id, message);
+            state.hls_progress.fail(&id, message);
+
+            let _ = tokio::fs::remove_file(data_path(&state.user_storage, &id)).await;
+            let _ = tokio::fs::remove_file(info_path(&state.user_storage, &id)).await;
+            Err(error)
+        }
+    }
+}
+
+/// Append the request body at `offset` without exceeding the upload length
+///
+/// Returns the new offset and whether the body was longer than allowed.
+/// Data received before a dropped connection is kept so the client can resume.
+async fn write_chunk(
+    state: &MediaManagerState,
+    upload: &TusUpload,
+    offset: u64,
+    body: Body,
+) -> Result<(u64, bool), TusError> {
+    let io_error = |e: std::io::Error| {
+        error!("Failed to write chunk for upload {}: {}", upload.id, e);
+        TusError::new(StatusCode::INTERNAL_SERVER_ERROR, "Failed to store chunk")
+    };
+
+    let mut file = tokio::fs::OpenOptions::new()
+        .append(true)
+        .open(data_path(&state.user_storage, &upload.id))
+        .await
+        .map_err(io_error)?;
+
+    let mut written = offset;
+    let mut overflow = false;
+    let mut stream = body.into_data_stream();
+    let mut last_reported = upload_percent(offset, upload.length);
+
+    while let Some(chunk) = stream.next().await {
+        let chunk = match chunk {
+            Ok(chunk) => chunk,
+            Err(e) => {
+                warn!(
+                    "Upload {} interrupted at {} bytes: {}",
+                    upload.id, written, e
+                );
+                break;
+            }
+        };
+
+        let remaining = upload.length - written;
+        let take = (chunk.len() as u64).min(remaining) as usize;
+        file.write_all(&chunk[..take]).await.map_err(io_error)?;
+        written += take as u64;
+
+        let percent = upload_percent(written, upload.length);
+        if percent != last_reported {
+            state
+                .hls_progress
+                .update(&upload.id, "Uploading".to_string(), percent);
+            last_reported = percent;
+        }
+
+        if take < chunk.len() {
+            overflow = true;
+            break;
+        }
+    }
+
+    file.flush().await.map_err(io_error)?;
+    Ok((written, overflow))
+}
+
+/// Abort an upload and delete its staged data
+/// DELETE /api/media/tus/{id}
+pub async fn terminate_upload(
+    session: Session,
+    State(state): State<MediaManagerState>,
+    Path(id): Path<String>,
+    headers: HeaderMap,
+) -> Result<Response, TusError> {
+    check_resumable(&headers)?;
+    let (user_id, _) = session_user(&session).await?;
+    load_upload(&state, &id, &user_id).await?;
+
+    let _guard = PatchGuard::acquire(&state, &id)
+        .ok_or_else(|| TusError::new(StatusCode::CONFLICT, "Upload is receiving data"))?;
+    remove_upload(&state, &id).await;
+    info!(event = "tus_upload_terminated", upload_id = %id, "Resumable upload terminated");
+
+    Ok(tus_response(StatusCode::NO_CONTENT, Vec::new()))
+}
+
+/// Marks an upload as busy for the lifetime of a request
+struct PatchGuard<'a> {
+    state: &'a MediaManagerState,
+    id: String,
+}
+
+impl<'a> PatchGuard<'a> {
+    fn acquire(state: &'a MediaManagerState, id: &str) -> Option<Self> {
+        state.tus_active.insert(id.to_string()).then(|| Self {
+            state,
+            id: id.to_string(),
+        })
+    }
+}
+
+impl Drop for PatchGuard<'_> {
+    fn drop(&mut self) {
+        self.state.tus_active.remove(&self.id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_parse_metadata() {
+        // "video", "My clip", "1", "clip.mp4"
+        let header =
+            "media_type dmlkZW8=,title TXkgY2xpcA==,is_public MQ==,filename Y2xpcC5tcDQ=,tags";
+        let form = parse_metadata(header).unwrap();
+
+        assert_eq!(form.media_type.as_deref(), Some("video"));
+        assert_eq!(form.title.as_deref(), Some("My clip"));
+        assert_eq!(form.is_public, Some(1));
+        assert_eq!(form.filename.as_deref(), Some("clip.mp4"));
+        assert!(form.tags.is_none());
+    }
+
+    #[test]
+    fn test_parse_metadata_invalid_base64() {
+        assert!(parse_metadata("title !!!").is_err());
+        assert!(parse_metadata("").unwrap().title.is_none());
+    }
+
+    #[test]
+    fn test_http_date() {
+        let time = Utc.with_ymd_and_hms(2026, 4, 3, 9, 5, 0).unwrap();
+        assert_eq!(http_date(time), "Fri, 03 Apr 2026 09:05:00 GMT");
+    }
+
+    #[test]
+    fn test_upload_percent() {
+        assert_eq!(upload_percent(0, 200), 0);
+        assert_eq!(upload_percent(100, 200), 50);
+        assert_eq!(upload_percent(200, 200), 99);
+        assert_eq!(upload_percent(0, 0), 99);
+    }
+}
@@ -6,20 +6,23 @@ use axum::{
     response::Json,
 };
 use common::models::MediaType;
+use common::storage::UserStorageManager;
 use db::media::{MediaInsert, MediaRepository};
 use serde_json::Value;
+use std::path::PathBuf;
 use std::sync::Arc;
 use tower_sessions::Session;
 use tracing::{error, info, warn};
-use std::path::PathBuf;
 
-use crate::pdf_thumbnail::{spawn_thumbnail_generation, PdfThumbnailContext};
+use crate::audio::{spawn_audio_processing, AudioProcessingContext};
+use crate::pdf_thumbnail::{generate_pdf_thumbnail_logged, PdfThumbnailContext};
 use crate::routes::MediaManagerState;
 
 // ── media-core validation re-exports ────────────────────────────────────
 use media_core::{
-    detect_mime_type, sanitize_filename, validate_extension_mime_match, validate_file_size,
-    validate_filename, validate_mime_type, MAX_DOCUMENT_SIZE, MAX_IMAGE_SIZE, MAX_VIDEO_SIZE,
+    detect_mime_type, sanitize_filename, sha256_file, sha256_hex, validate_extension_mime_match,
+    validate_file_size, validate_filename, validate_mime_type, ContentHasher, MAX_AUDIO_SIZE,
+    MAX_DOCUMENT_SIZE, MAX_IMAGE_SIZE, MAX_VIDEO_SIZE,
 };
 
 /// Convert the simple `common::models::MediaType` plus a detected MIME into
@@ -28,6 +31,7 @@ fn to_core_media_type(simple: &MediaType, detected_mime: &str) -> media_core::tr
     match simple {
         MediaType::Video => media_core::traits::MediaType::Video,
         MediaType::Image => media_core::traits::MediaType::Image,
+        MediaType::Audio => media_core::traits::MediaType::Audio,
         MediaType::Document => {
             let doc_type = media_core::traits::DocumentType::from_mime_type(detected_mime)
                 .unwrap_or(media_core::traits::DocumentType::Other(
@@ -38,8 +42,137 @@ fn to_core_media_type(simple: &MediaType, detected_mime: &str) -> media_core::tr
     }
 }
 
+/// Per-type upload size limit in bytes
+pub(crate) fn max_size_for(media_type: &MediaType) -> u64 {
+    match media_type {
+        MediaType::Image => MAX_IMAGE_SIZE,
+        MediaType::Video => MAX_VIDEO_SIZE,
+        MediaType::Audio => MAX_AUDIO_SIZE,
+        MediaType::Document => MAX_DOCUMENT_SIZE,
+    }
+}
+
+/// Form fields of an upload, shared by the multipart and resumable (tus) endpoints
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct UploadForm {
+    pub media_type: Option<String>,
+    pub slug: Option<String>,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub is_public: Option<i32>,
+    pub transcode_for_streaming: Option<i32>,
+    /// For images: keep original file (default: 1)
+    pub keep_original: Option<i32>,
+    pub group_id: Option<i32>,
+    pub vault_id: Option<String>,
+    pub category: Option<String>,
+    pub tags: Option<Vec<String>>,
+    pub filename: Option<String>,
+}
+
+impl UploadForm {
+    /// Set a field from its form value; unknown names are ignored
+    pub fn set_field(&mut self, name: &str, value: String) {
+        match name {
+            "media_type" => self.media_type = Some(value),
+            "slug" if !value.is_empty() => self.slug = Some(value),
+            "title" => self.title = Some(value),
+            "description" => {
+                self.description = if value.is_empty() { None } else { Some(value) };
+            }
+            "is_public" => self.is_public = Some(value.parse().unwrap_or(0)),
+            "transcode_for_streaming" => {
+                self.transcode_for_streaming = Some(value.parse().unwrap_or(1));
+            }
+            "keep_original" => self.keep_original = Some(value.parse().unwrap_or(1)),
+            "group_id" if !value.is_empty() => self.group_id = value.parse().ok(),
+            "vault_id" if !value.is_empty() => self.vault_id = Some(value),
+            "category" => {
+                self.category = if value.is_empty() { None } else { Some(value) };
+            }
+            "tags" if !value.is_empty() => {
+                self.tags = Some(value.split(',').map(|s| s.trim().to_string()).collect())
+            }
+            "filename" => self.filename = Some(value),
+            _ => {}
+        }
+    }
+}
+
+/// Content of an uploaded file
+pub(crate) enum UploadData {
+    /// Buffered multipart body, hashed while it was received
+    Memory { data: Vec<u8>, sha256: String },
+    /// Assembled on disk by a resumable upload
+    Staged { path: PathBuf, len: u64 },
+}
+
+impl UploadData {
+    /// Bytes read for content-based MIME detection
+    const HEAD_LEN: usize = 8192;
+
+    fn len(&self) -> usize {
+        match self {
+            Self::Memory { data, .. } => data.len(),
+            Self::Staged { len, .. } => *len as usize,
+        }
+    }
+
+    /// The first bytes of the file
+    async fn head(&self) -> std::io::Result<Vec<u8>> {
+        match self {
+            Self::Memory { data, .. } => Ok(data[..data.len().min(Self::HEAD_LEN)].to_vec()),
+            Self::Staged { path, .. } => {
+                use tokio::io::AsyncReadExt;
+
+                let file = tokio::fs::File::open(path).await?;
+                let mut head = Vec::with_capacity(Self::HEAD_LEN);
+                file.take(Self::HEAD_LEN as u64)
+                    .read_to_end(&mut head)
+                    .await?;
+                Ok(head)
+            }
+        }
+    }
+
+    /// SHA-256 of the file as lowercase hex
+    async fn content_hash(&self) -> media_core::MediaResult<String> {
+        match self {
+            Self::Memory { sha256, .. } => Ok(sha256.clone()),
+            Self::Staged { path, .. } => sha256_file(path).await,
+        }
+    }
+
+    /// The whole file in memory
+    async fn into_bytes(self) -> std::io::Result<Vec<u8>> {
+        match self {
+            Self::Memory { data, .. } => Ok(data),
+            Self::Staged { path, .. } => {
+                let data = tokio::fs::read(&path).await?;
+                let _ = tokio::fs::remove_file(&path).await;
+                Ok(data)
+            }
+        }
+    }
+
+    /// Store the file at `dest`; staged files are moved rather than copied
+    async fn save_to(self, dest: &std::path::Path) -> std::io::Result<()> {
+        match self {
+            Self::Memory { data, .. } => tokio::fs::write(dest, &data).await,
+            Self::Staged { path, .. } => {
+                if tokio::fs::rename(&path, dest).await.is_err() {
+                    // Staging and storage may live on different filesystems
+                    tokio::fs::copy(&path, dest).await?;
+                    let _ = tokio::fs::remove_file(&path).await;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
 /// Unified media upload handler
-/// Handles videos, images, and documents with type-specific processing
+/// Handles videos, images, audio, and documents with type-specific processing
 pub async fn upload_media(
     session: Session,
     State(state): State<MediaManagerState>,
@@ -85,21 +218,10 @@ pub async fn upload_media(
         .unwrap_or_else(|| "platform".to_string());
 
     // Parse multipart form data
-    let mut media_type: Option<String> = None;
-    let mut slug: Option<String> = None;
-    let mut title: Option<String> = None;
-    let mut description: Option<String> = None;
-    let mut is_public: Option<i32> = None;
-    let mut transcode_for_streaming: Option<i32> = None;
-    let mut keep_original: Option<i32> = None; // For images: keep original file (default: 1)
-    let mut group_id: Option<i32> = None;
-    let mut vault_id: Option<String> = None;
-    let mut category: Option<String> = None;
-    let mut tags: Option<Vec<String>> = None;
-    let mut file_data: Option<Vec<u8>> = None;
-    let mut filename: Option<String> = None;
-
-    while let Some(field) = multipart.next_field().await.map_err(|e| {
+    let mut form = UploadForm::default();
+    let mut file_data: Option<UploadData> = None;
+
+    while let Some(mut field) = multipart.next_field().await.map_err(|e| {
         error!("Multipart error: {}", e);
         (
             StatusCode::BAD_REQUEST,
@@ -108,131 +230,67 @@ pub async fn upload_media(
     })? {
         let name = field.name().unwrap_or("").to_string();
 
-        match name.as_str() {
-            "media_type" => {
-                media_type = Some(field.text().await.map_err(|_| {
-                    (
-                        StatusCode::BAD_REQUEST,
-                        Json(serde_json::json!({"error": "Invalid media_type field"})),
-                    )
-                })?);
-            }
-            "slug" => {
-                let slug_text = field.text().await.map_err(|_| {
-                    (
-                        StatusCode::BAD_REQUEST,
-                        Json(serde_json::json!({"error": "Invalid slug field"})),
-                    )
-                })?;
-                if !slug_text.is_empty() {
-                    slug = Some(slug_text);
-                }
-            }
-            "title" => {
-                title = Some(field.text().await.map_err(|_| {
-                    (
-                        StatusCode::BAD_REQUEST,
-                        Json(serde_json::json!({"error": "Invalid title field"})),
-                    )
-                })?);
-            }
-            "description" => {
-                let desc = field.text().await.map_err(|_| {
-                    (
-                        StatusCode::BAD_REQUEST,
-                        Json(serde_json::json!({"error": "Invalid description field"})),
-                    )
-                })?;
-                description = if desc.is_empty() { None } else { Some(desc) };
-            }
-            "is_public" => {
-                let value = field.text().await.map_err(|_| {
-                    (
-                        StatusCode::BAD_REQUEST,
-                        Json(serde_json::json!({"error": "Invalid is_public field"})),
-                    )
-                })?;
-                is_public = Some(value.parse().unwrap_or(0));
-            }
-            "transcode_for_streaming" => {
-                let value = field.text().await.map_err(|_| {
-                    (
-                        StatusCode::BAD_REQUEST,
-                        Json(serde_json::json!({"error": "Invalid transcode_for_streaming field"})),
-                    )
-                })?;
-                transcode_for_streaming = Some(value.parse().unwrap_or(1));
-            }
-            "keep_original" => {
-                let value = field.text().await.map_err(|_| {
-                    (
-                        StatusCode::BAD_REQUEST,
-                        Json(serde_json::json!({"error": "Invalid keep_original field"})),
-                    )
-                })?;
-                keep_original = Some(value.parse().unwrap_or(1)); // Default: keep original
-            }
-            "group_id" => {
-                let value = field.text().await.map_err(|_| {
-                    (
-                        StatusCode::BAD_REQUEST,
-                        Json(serde_json::json!({"error": "Invalid group_id field"})),
-                    )
-                })?;
-                if !value.is_empty() {
-                    group_id = value.parse().ok();
-                }
-            }
-            "vault_id" => {
-                let value = field.text().await.map_err(|_| {
-                    (
-                        StatusCode::BAD_REQUEST,
-                        Json(serde_json::json!({"error": "Invalid vault_id field"})),
-                    )
-                })?;
-                if !value.is_empty() {
-                    vault_id = Some(value);
-                }
-            }
-            "category" => {
-                let cat = field.text().await.map_err(|_| {
-                    (
-                        StatusCode::BAD_REQUEST,
-                        Json(serde_json::json!({"error": "Invalid category field"})),
-                    )
-                })?;
-                category = if cat.is_empty() { None } else { Some(cat) };
-            }
-            "tags" => {
-                let tags_str = field.text().await.map_err(|_| {
-                    (
-                        StatusCode::BAD_REQUEST,
-                        Json(serde_json::json!({"error": "Invalid tags field"})),
-                    )
-                })?;
-                if !tags_str.is_empty() {
-                    tags = Some(tags_str.split(',').map(|s| s.trim().to_string()).collect());
-                }
-            }
-            "file" => {
-                filename = field.file_name().map(|s| s.to_string());
-                file_data = Some(
-                    field
-                        .bytes()
-                        .await
-                        .map_err(|_| {
-                            (
-                                StatusCode::BAD_REQUEST,
-                                Json(serde_json::json!({"error": "Invalid file data"})),
-                            )
-                        })?
-                        .to_vec(),
-                );
+        if name == "file" {
+            form.filename = field.file_name().map(|s| s.to_string());
+
+            // Hash the file as it streams in, for deduplication and integrity checks
+            let mut hasher = ContentHasher::new();
+            let mut data = Vec::new();
+            while let Some(chunk) = field.chunk().await.map_err(|_| {
+                (
+                    StatusCode::BAD_REQUEST,
+                    Json(serde_json::json!({"error": "Invalid file data"})),
+                )
+            })? {
+                hasher.update(&chunk);
+                data.extend_from_slice(&chunk);
             }
-            _ => {}
+            file_data = Some(UploadData::Memory {
+                data,
+                sha256: hasher.finalize(),
+            });
+        } else {
+            let value = field.text().await.map_err(|_| {
+                (
+                    StatusCode::BAD_REQUEST,
+                    Json(serde_json::json!({"error": format!("Invalid {} field", name)})),
+                )
+            })?;
+            form.set_field(&name, value);
         }
     }
 
+    process_upload(&state, user_id, tenant_id, form, file_data, None).await
+}
+
+/// Validate an upload and hand it to the type-specific processing
+///
+/// Shared by the multipart endpoint, completed resumable (tus) uploads and
+/// file replacement. With `replace_id` the processed file becomes the new
+/// version of that media item instead of a new item.
+pub(crate) async fn process_upload(
+    state: &MediaManagerState,
+    user_id: String,
+    tenant_id: String,
+    form: UploadForm,
+    file_data: Option<UploadData>,
+    replace_id: Option<i32>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    let UploadForm {
+        media_type,
+        slug,
+        title,
+        description,
+        is_public,
+        transcode_for_streaming,
+        keep_original,
+        group_id,
+        vault_id,
+        category,
+        tags,
+        filename,
+    } = form;
+
     // Validate required fields
     let media_type_str = media_type.ok_or((
         StatusCode::BAD_REQUEST,
@@ -278,7 +336,14 @@ pub async fn upload_media(
     })?;
 
     // 2. Content-based MIME detection (magic numbers + extension)
-    let detected_mime = detect_mime_type(&file_data, &original_filename);
+    let head = file_data.head().await.map_err(|e| {
+        error!("Failed to read upload: {}", e);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({"error": "Failed to read uploaded file"})),
+        )
+    })?;
+    let detected_mime = detect_mime_type(&head, &original_filename);
 
     // 3. Convert to media-core's rich MediaType for validation
     let core_media_type = to_core_media_type(&media_type_enum, &detected_mime);
@@ -293,11 +358,7 @@ pub async fn upload_media(
     })?;
 
     // 5. Validate file size against per-type limits
-    let max_size = match media_type_enum {
-        MediaType::Image => MAX_IMAGE_SIZE,
-        MediaType::Video => MAX_VIDEO_SIZE,
-        MediaType::Document => MAX_DOCUMENT_SIZE,
-    };
+    let max_size = max_size_for(&media_type_enum);
     validate_file_size(file_data.len(), max_size).map_err(|e| {
         warn!(
             event = "upload_rejected",
@@ -351,61 +412,52 @@ pub async fn upload_media(
         })?
     };
 
-    // Auto-generate slug if not provided, ensuring global uniqueness
-    // Note: Database has UNIQUE constraint on slug column (not vault-scoped)
-    let slug = if let Some(s) = slug {
-        s
-    } else {
-        // Generate base slug from title
-        let base_slug = media_core::metadata::generate_slug(&title);
+    // Identical bytes already stored in this vault: reuse the existing item
+    let content_hash = file_data.content_hash().await.map_err(|e| {
+        error!("Failed to hash upload: {}", e);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({"error": "Failed to read uploaded file"})),
+        )
+    })?;
 
-        // Check if base slug exists globally
-        let existing = state.repo.slug_exists(&base_slug).await.map_err(|e| {
-            error!("Database error checking slug: {}", e);
+    let existing = state
+        .repo
+        .find_media_by_content_hash(&vault_id, &media_type_enum.to_string(), &content_hash)
+        .await
+        .map_err(|e| {
+            error!("Database error checking for duplicate upload: {}", e);
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 Json(serde_json::json!({"error": "Database error"})),
             )
         })?;
 
-        if existing.is_some() {
-            // Find next available suffix (_2, _3, etc.) globally
-            let mut counter = 2;
-            let mut unique_slug = format!("{}_{}", base_slug, counter);
-
-            loop {
-                let exists = state.repo.slug_exists(&unique_slug).await.map_err(|e| {
-                    error!("Database error checking slug: {}", e);
-                    (
-                        StatusCode::INTERNAL_SERVER_ERROR,
-                        Json(serde_json::json!({"error": "Database error"})),
-                    )
-                })?;
-
-                if exists.is_none() {
-                    break;
-                }
-
-                counter += 1;
-                unique_slug = format!("{}_{}", base_slug, counter);
-
-                // Safety check to prevent infinite loop
-                if counter > 1000 {
-                    return Err((
-                        StatusCode::INTERNAL_SERVER_ERROR,
-                        Json(serde_json::json!({"error": "Could not generate unique slug"})),
-                    ));
-                }
-            }
+    // A replacement may match another item; the new version is stored anyway
+    if let Some(existing) = existing.filter(|_| replace_id.is_none()) {
+        info!(
+            event = "upload_deduplicated",
+            slug = %existing.slug,
+            vault_id = %vault_id,
+            sha256 = %content_hash,
+            "Upload matches an existing item, not storing a second copy"
+        );
+        return Ok(Json(serde_json::json!({
+            "success": true,
+            "duplicate": true,
+            "message": "An identical file already exists in this vault",
+            "media_type": existing.media_type,
+            "slug": existing.slug,
+            "id": existing.id,
+            "title": existing.title,
+            "sha256": content_hash
+        })));
+    }
 
-            info!(
-                "Slug '{}' already exists globally, using '{}' instead",
-                base_slug, unique_slug
-            );
-            unique_slug
-        } else {
-            base_slug
-        }
+    // Auto-generate slug if not provided, ensuring global uniqueness
+    let slug = match slug {
+        Some(s) => s,
+        None => unique_slug(state, &title).await?,
     };
 
     // Process based on media type
@@ -413,7 +465,7 @@ pub async fn upload_media(
         MediaType::Image => {
             let keep_original_bool = keep_original.unwrap_or(1) == 1; // Default: true (keep original)
             process_image_upload(
-                &state,
+                state,
                 slug,
                 title,
                 description,
@@ -427,6 +479,8 @@ pub async fn upload_media(
                 original_filename,
                 keep_original_bool,
                 tenant_id,
+                content_hash,
+                replace_id,
             )
             .await
         }
@@ -438,7 +492,7 @@ pub async fn upload_media(
 
                 // Delegate to video-manager HLS processing pipeline
                 return process_video_hls_upload(
-                    &state,
+                    state,
                     slug,
                     title,
                     description,
@@ -449,13 +503,15 @@ pub async fn upload_media(
                     file_data,
                     original_filename,
                     tenant_id,
+                    content_hash,
+                    replace_id,
                 )
                 .await;
             }
 
             // Standard MP4 upload (no transcoding)
             process_video_upload(
-                &state,
+                state,
                 slug,
                 title,
                 description,
@@ -468,12 +524,34 @@ pub async fn upload_media(
                 file_data,
                 original_filename,
                 tenant_id,
+                content_hash,
+                replace_id,
+            )
+            .await
+        }
+        MediaType::Audio => {
+            process_audio_upload(
+                state,
+                slug,
+                title,
+                description,
+                is_public,
+                user_id,
+                group_id,
+                vault_id,
+                category,
+                tags,
+                file_data,
+                original_filename,
+                tenant_id,
+                content_hash,
+                replace_id,
             )
             .await
         }
         MediaType::Document => {
             process_document_upload(
-                &state,
+                state,
                 slug,
                 title,
                 description,
@@ -486,12 +564,73 @@ pub async fn upload_media(
                 file_data,
                 original_filename,
                 tenant_id,
+                content_hash,
+                replace_id,
             )
             .await
         }
     }
 }
 
+/// Generate a slug from a title that no other media item uses
+///
+/// Note: Database has UNIQUE constraint on slug column (not vault-scoped)
+pub(crate) async fn unique_slug(
+    state: &MediaManagerState,
+    title: &str,
+) -> Result<String, (StatusCode, Json<Value>)> {
+    // Generate base slug from title
+    let base_slug = media_core::metadata::generate_slug(title);
+
+    // Check if base slug exists globally
+    let existing = state.repo.slug_exists(&base_slug).await.map_err(|e| {
+        error!("Database error checking slug: {}", e);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({"error": "Database error"})),
+        )
+    })?;
+
+    if existing.is_none() {
+        return Ok(base_slug);
+    }
+
+    // Find next available suffix (_2, _3, etc.) globally
+    let mut counter = 2;
+    let mut unique_slug = format!("{}_{}", base_slug, counter);
+
+    loop {
+        let exists = state.repo.slug_exists(&unique_slug).await.map_err(|e| {
+            error!("Database error checking slug: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({"error": "Database error"})),
+            )
+        })?;
+
+        if exists.is_none() {
+            break;
+        }
+
+        counter += 1;
+        unique_slug = format!("{}_{}", base_slug, counter);
+
+        // Safety check to prevent infinite loop
+        if counter > 1000 {
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({"error": "Could not generate unique slug"})),
+            ));
+        }
+    }
+
+    info!(
+        "Slug '{}' already exists globally, using '{}' instead",
+        base_slug, unique_slug
+    );
+    Ok(unique_slug)
+}
+
 /// Process image upload
 async fn process_image_upload(
     state: &MediaManagerState,
@@ -504,11 +643,22 @@ async fn process_image_upload(
     vault_id: String,
     category: Option<String>,
     tags: Option<Vec<String>>,
-    file_data: Vec<u8>,
+    file_data: UploadData,
     original_filename: String,
     keep_original: bool, // Whether to keep the original file alongside WebP
     tenant_id: String,
+    content_hash: String,
+    replace_id: Option<i32>,
 ) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    // Images are decoded in memory; they are small enough (see MAX_IMAGE_SIZE)
+    let file_data = file_data.into_bytes().await.map_err(|e| {
+        error!("Failed to read image upload: {}", e);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({"error": "Failed to read uploaded file"})),
+        )
+    })?;
+
     // Determine MIME type
     let mime_type = mime_guess::from_path(&original_filename)
         .first_or_octet_stream()
@@ -517,7 +667,11 @@ async fn process_image_upload(
     // Check if SVG (preserve as-is)
     let is_svg = original_filename.to_lowercase().ends_with(".svg");
 
-    let (final_filename, webp_filename, file_size) = if is_svg {
+    // Files to hand over to the storage backend once the item is saved
+    let mut stored_paths = Vec::new();
+
+    // file_hash: checksum of the stored file (the WebP differs from the upload)
+    let (final_filename, webp_filename, file_size, file_hash) = if is_svg {
         // Store SVG as-is (using nested structure)
         let svg_filename = format!("{}.svg", slug);
         let svg_path = state
@@ -543,14 +697,16 @@ async fn process_image_upload(
             )
         })?;
 
+        stored_paths.push(svg_path);
+
         let file_size = file_data.len() as i64;
-        (svg_filename.clone(), None, file_size)
+        (svg_filename.clone(), None, file_size, content_hash.clone())
     } else {
         // Transcode to WebP (always) + optionally keep original
         let webp_filename = format!("{}.webp", slug);
 
-        // Load and convert to WebP
-        let img = image::load_from_memory(&file_data).map_err(|e| {
+        // Load (rotated upright per EXIF orientation) and convert to WebP
+        let img = crate::photo::decode_upright(&file_data).map_err(|e| {
             error!("Failed to load image: {}", e);
             (
                 StatusCode::BAD_REQUEST,
@@ -594,6 +750,8 @@ async fn process_image_upload(
                 )
             })?;
 
+        stored_paths.push(webp_path);
+
         info!(
             "Saved WebP image: {}, keep_original={}",
             webp_filename, keep_original
@@ -622,6 +780,7 @@ async fn process_image_upload(
                     )
                 })?;
 
+            stored_paths.push(original_path);
             info!("Saved original image: {}", original_stored_filename);
         } else {
             info!("Skipped saving original image (user preference)");
@@ -632,12 +791,13 @@ async fn process_image_upload(
             webp_filename.clone(),
             Some(webp_filename.clone()),
             file_size,
+            sha256_hex(&webp_data),
         )
     };
 
     // Generate thumbnail (400x400)
     let thumbnail_url = if !is_svg {
-        let img = image::load_from_memory(&file_data).ok();
+        let img = crate::photo::decode_upright(&file_data).ok();
         if let Some(img) = img {
             let thumb =
                 image::imageops::resize(&img, 400, 400, image::imageops::FilterType::Lanczos3);
@@ -657,6 +817,7 @@ async fn process_image_upload(
             }
 
             let _ = tokio::fs::write(&thumb_path, &thumb_data).await;
+            stored_paths.push(thumb_path);
 
             Some(format!("/media/{}/thumbnail", slug))
         } else {
@@ -690,15 +851,19 @@ async fn process_image_upload(
         allow_comments: 1,
         mature_content: 0,
         tenant_id: tenant_id.clone(),
+        content_hash: Some(content_hash),
+        file_hash: Some(file_hash),
     };
 
-    let media_id = state.repo.insert_media_item(&insert).await.map_err(|e| {
-        error!("Database error: {}", e);
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(serde_json::json!({"error": "Failed to save media metadata"})),
-        )
-    })? as i32;
+    let media_id = save_media_item(state, &insert, replace_id)
+        .await
+        .map_err(|e| {
+            error!("Database error: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({"error": "Failed to save media metadata"})),
+            )
+        })? as i32;
 
     // Add tags if provided
     if let Some(tag_list) = tags {
@@ -707,6 +872,12 @@ async fn process_image_upload(
         }
     }
 
+    if !is_svg {
+        crate::photo::record_photo_details(state, media_id as i64, &file_data).await;
+    }
+
+    spawn_offload(&state.user_storage, stored_paths);
+
     info!("Image uploaded successfully: {} by user {}", slug, user_id);
 
     Ok(Json(serde_json::json!({
@@ -732,9 +903,11 @@ async fn process_video_upload(
     vault_id: String,
     category: Option<String>,
     tags: Option<Vec<String>>,
-    file_data: Vec<u8>,
+    file_data: UploadData,
     original_filename: String,
     tenant_id: String,
+    content_hash: String,
+    replace_id: Option<i32>,
 ) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
     use std::path::Path;
 
@@ -811,15 +984,13 @@ async fn process_video_upload(
     }
 
     // Write video file
-    tokio::fs::write(&video_path, &file_data)
-        .await
-        .map_err(|e| {
-            error!("Failed to save video file: {}", e);
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(serde_json::json!({"error": "Failed to save video file"})),
-            )
-        })?;
+    file_data.save_to(&video_path).await.map_err(|e| {
+        error!("Failed to save video file: {}", e);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({"error": "Failed to save video file"})),
+        )
+    })?;
 
     info!(
         "Saved video file for user {} vault {}: {}",
@@ -850,7 +1021,7 @@ async fn process_video_upload(
         is_public,
         user_id: Some(user_id.clone()),
         group_id,
-        vault_id: Some(vault_id),
+        vault_id: Some(vault_id.clone()),
         status: "active".to_string(),
         featured: 0,
         category,
@@ -859,15 +1030,19 @@ async fn process_video_upload(
         allow_comments: 1,
         mature_content: 0,
         tenant_id: tenant_id.clone(),
+        content_hash: Some(content_hash.clone()),
+        file_hash: Some(content_hash),
     };
 
-    let media_id = state.repo.insert_media_item(&insert).await.map_err(|e| {
-        error!("Database error: {}", e);
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(serde_json::json!({"error": "Failed to save video metadata"})),
-        )
-    })? as i32;
+    let media_id = save_media_item(state, &insert, replace_id)
+        .await
+        .map_err(|e| {
+            error!("Database error: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({"error": "Failed to save video metadata"})),
+            )
+        })? as i32;
 
     // Add tags if provided
     if let Some(tag_list) = tags {
@@ -876,6 +1051,18 @@ async fn process_video_upload(
         }
     }
 
+    spawn_offload(
+        &state.user_storage,
+        vec![
+            video_path.parent().unwrap_or(&video_path).to_path_buf(),
+            state.user_storage.get_thumbnail_path(
+                &vault_id,
+                common::storage::MediaType::Video,
+                &slug,
+            ),
+        ],
+    );
+
     info!(
         "Video uploaded successfully as MP4: {} (ID: {})",
         slug, media_id
@@ -894,7 +1081,10 @@ async fn process_video_upload(
 }
 
 /// Process video upload with HLS transcoding
-/// Simplified version that works with media_items table and vault storage
+///
+/// Stores the upload in the temp directory, creates the `media_items` row in
+/// `processing` state and queues a transcoding job. The job queue workers run
+/// the full video-manager pipeline (metadata, thumbnail, poster, HLS ladder).
 async fn process_video_hls_upload(
     state: &MediaManagerState,
     slug: String,
@@ -904,9 +1094,11 @@ async fn process_video_hls_upload(
     user_id: String,
     group_id: Option<i32>,
     vault_id: String,
-    file_data: Vec<u8>,
+    file_data: UploadData,
     original_filename: String,
     tenant_id: String,
+    content_hash: String,
+    replace_id: Option<i32>,
 ) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
     info!(
         "Processing HLS video upload: slug={}, title={}, file={}, size={} bytes",
@@ -916,36 +1108,44 @@ async fn process_video_hls_upload(
         file_data.len()
     );
 
-    let file_size = file_data.len() as i64;
+    let Some(queue) = state.transcode_queue.as_ref() else {
+        error!("HLS upload requested but no transcoding queue is configured");
+        return Err((
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(serde_json::json!({"error": "HLS transcoding is not available"})),
+        ));
+    };
 
-    // Save file to nested vault storage
-    let video_dir = state
-        .user_storage
-        .vault_nested_media_dir(&vault_id, common::storage::MediaType::Video)
-        .join(&slug);
+    let file_size = file_data.len() as i64;
+    let upload_id = uuid::Uuid::new_v4().to_string();
 
-    tokio::fs::create_dir_all(&video_dir).await.map_err(|e| {
-        error!("Failed to create video directory: {}", e);
+    // Stage the upload in the temp directory; the pipeline moves it into the
+    // vault video directory as original.{ext} once transcoding succeeds.
+    let temp_dir = state.user_storage.temp_dir();
+    tokio::fs::create_dir_all(&temp_dir).await.map_err(|e| {
+        error!("Failed to create temp directory: {}", e);
         (
             StatusCode::INTERNAL_SERVER_ERROR,
             Json(serde_json::json!({"error": "Failed to create storage directory"})),
         )
     })?;
 
-    // Save original file
-    let source_video_path = video_dir.join(&original_filename);
-    tokio::fs::write(&source_video_path, &file_data)
-        .await
-        .map_err(|e| {
-            error!("Failed to write video file: {}", e);
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(serde_json::json!({"error": "Failed to save video"})),
-            )
-        })?;
+    let source_video_path = temp_dir.join(format!("{}.tmp", upload_id));
+    file_data.save_to(&source_video_path).await.map_err(|e| {
+        error!("Failed to write video file: {}", e);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({"error": "Failed to save video"})),
+        )
+    })?;
 
     info!("Saved source video to: {:?}", source_video_path);
 
+    let extension = std::path::Path::new(&original_filename)
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("mp4");
+
     // Insert initial media_items record with status='processing'
     let insert = MediaInsert {
         slug: slug.clone(),
@@ -953,7 +1153,7 @@ async fn process_video_hls_upload(
         video_type: Some("hls".to_string()),
         title: title.clone(),
         description: description.clone(),
-        filename: original_filename.clone(),
+        filename: format!("original.{}", extension),
         original_filename: Some(original_filename.clone()),
         mime_type: "video/mp4".to_string(),
         file_size,
@@ -969,91 +1169,62 @@ async fn process_video_hls_upload(
         allow_comments: 1,
         mature_content: 0,
         tenant_id: tenant_id.clone(),
+        content_hash: Some(content_hash.clone()),
+        file_hash: Some(content_hash),
     };
 
-    let media_id = state.repo.insert_media_item(&insert).await.map_err(|e| {
-        error!("Failed to create media_items record: {}", e);
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(serde_json::json!({"error": "Failed to create database record"})),
-        )
-    })?;
-
-    // Start progress tracking
-    state.hls_progress.start(&slug);
-    state.hls_progress.update(&slug, "Uploading".to_string(), 10);
-
-    // Clone data for background task
-    let repo = state.repo.clone();
-    let video_dir_clone = video_dir.clone();
-    let slug_clone = slug.clone();
-    let source_path_clone = source_video_path.clone();
-    let progress_tracker = state.hls_progress.clone();
-    let user_storage_clone = state.user_storage.clone();
-    let vault_id_clone = vault_id.clone();
-
-    // Spawn background HLS transcoding task
-    tokio::spawn(async move {
-        info!("Starting HLS transcoding for: {}", slug_clone);
-
-        // Stage 1: Validation (15%)
-        progress_tracker.update(&slug_clone, "Validating video".to_string(), 15);
-
-        // Stage 2: Extract metadata (20%)
-        progress_tracker.update(&slug_clone, "Extracting metadata".to_string(), 20);
-
-        // Stage 3: Transcode to HLS (25-85%)
-        progress_tracker.update(&slug_clone, "Transcoding to HLS".to_string(), 25);
-
-        // Run FFmpeg to create HLS playlist
-        match transcode_to_hls(&source_path_clone, &video_dir_clone).await {
-            Ok(_) => {
-                info!("HLS transcoding complete for: {}", slug_clone);
-
-                // Stage 4: Generate thumbnail (85%)
-                progress_tracker.update(&slug_clone, "Generating thumbnail".to_string(), 85);
-
-                // Generate thumbnail (save to centralized location, not video dir)
-                let thumbnail_url = match generate_hls_thumbnail(&source_path_clone, &slug_clone, &vault_id_clone, &user_storage_clone).await {
-                    Ok(url) => Some(url),
-                    Err(e) => {
-                        warn!("Failed to generate thumbnail: {}", e);
-                        None
-                    }
-                };
+    let media_id = save_media_item(state, &insert, replace_id)
+        .await
+        .map_err(|e| {
+            error!("Failed to create media_items record: {}", e);
+            let _ = std::fs::remove_file(&source_video_path);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({"error": "Failed to create database record"})),
+            )
+        })?;
 
-                // Update media_items to mark as complete
-                let update_result = repo.update_media_status_active(
-                    &slug_clone,
-                    "video",
-                    thumbnail_url.as_deref(),
-                ).await;
-
-                match update_result {
-                    Ok(_) => {
-                        info!("Video {} marked as active", slug_clone);
-                        // Mark progress as complete (100%)
-                        progress_tracker.complete(&slug_clone);
-                    }
-                    Err(e) => {
-                        error!("Failed to update video status: {}", e);
-                        progress_tracker.fail(&slug_clone, format!("Database update failed: {}", e));
-                    }
-                }
-            }
-            Err(e) => {
-                error!("HLS transcoding failed for {}: {}", slug_clone, e);
+    if let Some(tracker) = &state.video_progress_tracker {
+        tracker.init_upload(
+            upload_id.clone(),
+            slug.clone(),
+            Some(original_filename.clone()),
+            Some(file_size as u64),
+        );
+        tracker.update(
+            &upload_id,
+            video_manager::progress::ProgressStatus::Processing,
+            20,
+            "Queued for processing".to_string(),
+        );
+    }
 
-                // Mark progress as failed
-                progress_tracker.fail(&slug_clone, e.clone());
+    let job = db::jobs::CreateTranscodeJob {
+        upload_id: upload_id.clone(),
+        slug: slug.clone(),
+        vault_id: vault_id.clone(),
+        user_id: Some(user_id.clone()),
+        source_path: source_video_path.to_string_lossy().to_string(),
+        original_filename: original_filename.clone(),
+        is_public: is_public == 1,
+        priority: 0,
+        max_attempts: 3,
+    };
 
-                // Update media_items to mark as error
-                let _ = repo.update_media_status_error(&slug_clone, "video").await;
-            }
+    let job_id = match queue.enqueue(&job).await {
+        Ok(id) => id,
+        Err(e) => {
+            error!("Failed to queue HLS transcoding for {}: {:#}", slug, e);
+            let _ = state.repo.update_media_status_error(&slug, "video").await;
+            let _ = tokio::fs::remove_file(&source_video_path).await;
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({"error": "Failed to queue video for transcoding"})),
+            ));
         }
-    });
+    };
 
-    info!("Video upload accepted for HLS processing: {}", slug);
+    info!("Video upload accepted for HLS processing: {} (job {})", slug, job_id);
 
     // Return 202 ACCEPTED with job tracking info
     Ok(Json(serde_json::json!({
@@ -1064,115 +1235,38 @@ async fn process_video_hls_upload(
         "video_type": "hls",
         "slug": slug,
         "id": media_id,
+        "job_id": job_id,
         "progress_url": format!("/api/media/{}/progress", slug),
-        "note": "HLS transcoding in progress - check progress_url for status"
+        "note": "HLS transcoding queued - check progress_url for status"
     })))
 }
 
-/// Transcode video to HLS format with multiple quality levels
-/// Uses video-manager's proper multi-quality HLS transcoding
-async fn transcode_to_hls(
-    source_path: &PathBuf,
-    output_dir: &PathBuf,
-) -> Result<(), String> {
-    info!("Transcoding to HLS (multi-quality): {:?} -> {:?}", source_path, output_dir);
-
-    // Extract video metadata to determine appropriate quality levels
-    let ffmpeg_config = video_manager::ffmpeg::FFmpegConfig {
-        ffmpeg_path: PathBuf::from("ffmpeg"),
-        ffprobe_path: PathBuf::from("ffprobe"),
-        threads: 0,
-    };
-
-    let metadata = video_manager::ffmpeg::extract_metadata(&ffmpeg_config, source_path)
-        .await
-        .map_err(|e| format!("Failed to extract metadata: {}", e))?;
-
-    info!(
-        "Source video: {}x{}, duration: {:.1}s",
-        metadata.width, metadata.height, metadata.duration
-    );
-
-    // Build HLS config
-    let hls_config = video_manager::hls::HlsConfig {
-        segment_duration: 6,
-        auto_quality_selection: true,
-        delete_original: false,
-    };
-
-    // Transcode to HLS with multiple qualities
-    let qualities = video_manager::hls::transcode_to_hls(
-        &ffmpeg_config,
-        &hls_config,
-        source_path,
-        output_dir,
-        &metadata,
-    )
-    .await
-    .map_err(|e| format!("HLS transcoding failed: {}", e))?;
-
-    info!("HLS transcoding successful: {} quality levels created: {:?}", qualities.len(), qualities);
-    Ok(())
-}
-
-/// Generate thumbnail from video
-async fn generate_hls_thumbnail(
-    source_path: &PathBuf,
-    slug: &str,
-    vault_id: &str,
-    user_storage: &common::storage::UserStorageManager,
-) -> Result<String, String> {
-    use tokio::process::Command;
-
-    // Use centralized thumbnails directory (not video directory)
-    let thumb_dir = user_storage.vault_thumbnails_dir(vault_id, common::storage::MediaType::Video);
-    tokio::fs::create_dir_all(&thumb_dir)
-        .await
-        .map_err(|e| format!("Failed to create thumbnails directory: {}", e))?;
-
-    let temp_thumbnail_path = thumb_dir.join(format!("{}_thumb_temp.jpg", slug));
-
-    info!("Generating HLS thumbnail: {:?}", temp_thumbnail_path);
-
-    // Extract frame at 1 second using FFmpeg
-    let output = Command::new("ffmpeg")
-        .args(&[
-            "-i", source_path.to_str().unwrap(),
-            "-ss", "00:00:01",
-            "-vframes", "1",
-            "-vf", "scale=400:400:force_original_aspect_ratio=decrease",
-            "-y",
-            temp_thumbnail_path.to_str().unwrap(),
-        ])
-        .output()
-        .await
-        .map_err(|e| format!("Failed to run FFmpeg: {}", e))?;
-
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(format!("Thumbnail generation failed: {}", stderr));
-    }
-
-    // Convert to WebP and save to centralized location
-    let final_thumb_path = thumb_dir.join(format!("{}_thumb.webp", slug));
-    if let Ok(img_data) = tokio::fs::read(&temp_thumbnail_path).await {
-        if let Ok(img) = image::load_from_memory(&img_data) {
-            let mut webp_data = Vec::new();
-            let encoder = image::codecs::webp::WebPEncoder::new_lossless(&mut webp_data);
-            if img.write_with_encoder(encoder).is_ok() {
-                let _ = tokio::fs::write(&final_thumb_path, &webp_data).await;
-                let _ = tokio::fs::remove_file(&temp_thumbnail_path).await;
-
-                info!("HLS thumbnail saved to centralized location: {:?}", final_thumb_path);
-                return Ok(format!("/media/{}/thumbnail", slug));
-            }
+/// Save a processed upload: a new media item, or the new version of the item
+/// whose file is being replaced. Returns the item ID.
+async fn save_media_item(
+    state: &MediaManagerState,
+    insert: &MediaInsert,
+    replace_id: Option<i32>,
+) -> Result<i64, db::DbError> {
+    match replace_id {
+        Some(media_id) => {
+            let version = state.repo.replace_media_file(media_id, insert).await?;
+            info!("Stored version {} of {}", version, insert.slug);
+            Ok(media_id as i64)
         }
+        None => state.repo.insert_media_item(insert).await,
     }
+}
 
-    // Cleanup temp file even on error
-    let _ = tokio::fs::remove_file(&temp_thumbnail_path).await;
-
-    Err("Failed to convert thumbnail to WebP".to_string())
+/// Hand an item's stored files over to the storage backend in background
+///
+/// A no-op with the local backend; the files are served from local disk
+/// until the upload finishes.
+fn spawn_offload(user_storage: &UserStorageManager, paths: Vec<PathBuf>) {
+    let user_storage = user_storage.clone();
+    tokio::spawn(async move {
+        user_storage.offload_paths(&paths).await;
+    });
 }
 
 /// Generate video thumbnail using FFmpeg
@@ -1252,6 +1346,174 @@ async fn generate_video_thumbnail(
     Ok(thumbnail_url)
 }
 
+/// Process audio upload
+///
+/// Stores the original as `media/audio/{slug}/original.{ext}`, inserts the item
+/// in `processing` state and hands it to the background audio pipeline
+/// (metadata, normalized renditions, waveform thumbnail).
+async fn process_audio_upload(
+    state: &MediaManagerState,
+    slug: String,
+    title: String,
+    description: Option<String>,
+    is_public: i32,
+    user_id: String,
+    group_id: Option<i32>,
+    vault_id: String,
+    category: Option<String>,
+    tags: Option<Vec<String>>,
+    file_data: UploadData,
+    original_filename: String,
+    tenant_id: String,
+    content_hash: String,
+    replace_id: Option<i32>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    use std::path::Path;
+
+    info!(
+        "Processing audio upload: slug={}, title={}, file={}, size={} bytes, vault={}",
+        slug,
+        title,
+        original_filename,
+        file_data.len(),
+        vault_id
+    );
+
+    // Determine MIME type from extension
+    let extension = Path::new(&original_filename)
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_lowercase();
+
+    let mime_type = match extension.as_str() {
+        "mp3" => "audio/mpeg",
+        "m4a" => "audio/mp4",
+        "aac" => "audio/aac",
+        "ogg" | "oga" => "audio/ogg",
+        "opus" => "audio/opus",
+        "wav" => "audio/wav",
+        "flac" => "audio/flac",
+        "weba" => "audio/webm",
+        _ => {
+            warn!(
+                event = "upload_rejected",
+                reason = "unsupported_audio_type",
+                extension = %extension,
+                filename = %original_filename,
+                "Unsupported audio extension"
+            );
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(serde_json::json!({
+                    "error": format!(
+                        "Unsupported audio type: .{}. Allowed: mp3, m4a, aac, ogg, oga, opus, wav, flac, weba",
+                        extension
+                    )
+                })),
+            ));
+        }
+    };
+
+    let file_size = file_data.len() as i64;
+    let filename = format!("original.{}", extension);
+
+    // Audio items get a directory: the renditions and waveform live next to the original
+    let audio_path = state
+        .user_storage
+        .vault_nested_media_dir(&vault_id, common::storage::MediaType::Audio)
+        .join(&slug)
+        .join(&filename);
+
+    if let Some(parent) = audio_path.parent() {
+        tokio::fs::create_dir_all(parent).await.map_err(|e| {
+            error!("Failed to create audio directory: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({"error": "Failed to create storage directory"})),
+            )
+        })?;
+    }
+
+    file_data.save_to(&audio_path).await.map_err(|e| {
+        error!("Failed to save audio file: {}", e);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({"error": "Failed to save audio file"})),
+        )
+    })?;
+
+    info!(
+        "Saved audio file for user {} vault {}: {:?}",
+        user_id, vault_id, audio_path
+    );
+
+    let insert = MediaInsert {
+        slug: slug.clone(),
+        media_type: "audio".to_string(),
+        video_type: None,
+        title: title.clone(),
+        description: description.clone(),
+        filename,
+        original_filename: Some(original_filename),
+        mime_type: mime_type.to_string(),
+        file_size,
+        is_public,
+        user_id: Some(user_id.clone()),
+        group_id,
+        vault_id: Some(vault_id.clone()),
+        status: "processing".to_string(),
+        featured: 0,
+        category,
+        thumbnail_url: None,
+        allow_download: 1,
+        allow_comments: 1,
+        mature_content: 0,
+        tenant_id: tenant_id.clone(),
+        content_hash: Some(content_hash.clone()),
+        file_hash: Some(content_hash),
+    };
+
+    let media_id = save_media_item(state, &insert, replace_id)
+        .await
+        .map_err(|e| {
+            error!("Database error inserting audio: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({"error": "Failed to save to database"})),
+            )
+        })? as i32;
+
+    // Add tags if provided
+    if let Some(tag_list) = tags {
+        for tag in tag_list {
+            let _ = state.repo.insert_media_tag(media_id, &tag).await;
+        }
+    }
+
+    spawn_audio_processing(AudioProcessingContext {
+        media_id,
+        slug: slug.clone(),
+        vault_id,
+        original_path: audio_path,
+        repo: state.repo.clone(),
+        user_storage: state.user_storage.clone(),
+    });
+
+    info!("Audio upload accepted for processing: {} (ID: {})", slug, media_id);
+
+    Ok(Json(serde_json::json!({
+        "success": true,
+        "status": "processing",
+        "message": "Audio uploaded and queued for processing",
+        "media_type": "audio",
+        "slug": slug,
+        "id": media_id,
+        "mime_type": mime_type,
+        "file_size": file_size
+    })))
+}
+
 /// Process document upload
 async fn process_document_upload(
     state: &MediaManagerState,
@@ -1264,9 +1526,11 @@ async fn process_document_upload(
     vault_id: String,
     category: Option<String>,
     tags: Option<Vec<String>>,
-    file_data: Vec<u8>,
+    file_data: UploadData,
     original_filename: String,
     tenant_id: String,
+    content_hash: String,
+    replace_id: Option<i32>,
 ) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
     use std::path::Path;
 
@@ -1342,15 +1606,13 @@ async fn process_document_upload(
     }
 
     // Write document file
-    tokio::fs::write(&document_path, &file_data)
-        .await
-        .map_err(|e| {
-            error!("Failed to save document file: {}", e);
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(serde_json::json!({"error": "Failed to save file"})),
-            )
-        })?;
+    file_data.save_to(&document_path).await.map_err(|e| {
+        error!("Failed to save document file: {}", e);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({"error": "Failed to save file"})),
+        )
+    })?;
 
     info!(
         "Saved document file for user {} vault {}: {}",
@@ -1380,15 +1642,19 @@ async fn process_document_upload(
         allow_comments: 1,
         mature_content: 0,
         tenant_id: tenant_id.clone(),
+        content_hash: Some(content_hash.clone()),
+        file_hash: Some(content_hash),
     };
 
-    let media_id = state.repo.insert_media_item(&insert).await.map_err(|e| {
-        error!("Database error inserting document: {}", e);
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(serde_json::json!({"error": "Failed to save to database"})),
-        )
-    })? as i32;
+    let media_id = save_media_item(state, &insert, replace_id)
+        .await
+        .map_err(|e| {
+            error!("Database error inserting document: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({"error": "Failed to save to database"})),
+            )
+        })? as i32;
 
     // Add tags if provided
     if let Some(tag_list) = tags {
@@ -1397,19 +1663,41 @@ async fn process_document_upload(
         }
     }
 
-    // Generate PDF thumbnail asynchronously in background
-    if mime_type == "application/pdf" {
-        let thumb_context = PdfThumbnailContext {
-            media_id,
-            slug: slug.clone(),
-            vault_id: vault_id.clone(),
-            pdf_path: document_path.clone(),
-            repo: state.repo.clone(),
-            user_storage: state.user_storage.clone(),
-        };
-
-        spawn_thumbnail_generation(thumb_context);
-    }
+    // Generate the PDF thumbnail and index the text for full-text search in
+    // background, then hand the stored files over to the storage backend
+    let thumb_context = (mime_type == "application/pdf").then(|| PdfThumbnailContext {
+        media_id,
+        slug: slug.clone(),
+        vault_id: vault_id.clone(),
+        pdf_path: document_path.clone(),
+        repo: state.repo.clone(),
+        user_storage: state.user_storage.clone(),
+    });
+    let index_mime_type =
+        crate::content_index::is_indexable(mime_type).then(|| mime_type.to_string());
+    let stored_paths = vec![
+        document_path.clone(),
+        state
+            .user_storage
+            .get_thumbnail_path(&vault_id, common::storage::MediaType::Document, &slug),
+    ];
+    let repo = state.repo.clone();
+    let user_storage = state.user_storage.clone();
+    tokio::spawn(async move {
+        if let Some(context) = thumb_context {
+            generate_pdf_thumbnail_logged(context).await;
+        }
+        if let Some(mime_type) = index_mime_type {
+            crate::content_index::index_document_logged(
+                repo.as_ref(),
+                media_id,
+                &document_path,
+                &mime_type,
+            )
+            .await;
+        }
+        user_storage.offload_paths(&stored_paths).await;
+    });
 
     info!(
         "Document uploaded successfully: {} (ID: {})",
@@ -1483,15 +1771,11 @@ pub async fn get_upload_progress(
                     "error": "Processing failed"
                 })
             } else if is_processing {
-                // For ongoing processing, we could query the progress_tracker
-                // if it's available in state
-                let progress = if let Some(ref _tracker) = state.video_progress_tracker {
-                    // Try to get progress from tracker
-                    // Note: We'd need the upload_id to query the tracker
-                    // For now, return generic processing status
-                    50
-                } else {
-                    50
+                // Live progress comes from the queue worker's tracker, keyed
+                // by the upload ID of the latest job for this slug
+                let (progress, stage) = match queued_job_progress(&state, &slug).await {
+                    Some(p) => (p.progress, p.stage),
+                    None => (50, "Transcoding to HLS".to_string()),
                 };
 
                 serde_json::json!({
@@ -1499,7 +1783,7 @@ pub async fn get_upload_progress(
                     "status": "processing",
                     "video_type": video_type,
                     "progress": progress,
-                    "stage": "Transcoding to HLS",
+                    "stage": stage,
                     "complete": false,
                     "message": "Video is being processed for HLS streaming"
                 })
@@ -1581,6 +1865,23 @@ async fn handle_progress_socket(
                         let _ = sender.send(Message::Close(None)).await;
                         break;
                     }
+                } else if let Some(progress) = queued_job_progress(&state, &slug).await {
+                    let message = serde_json::json!({
+                        "slug": slug,
+                        "stage": progress.stage,
+                        "percent": progress.progress,
+                        "error": progress.error,
+                    })
+                    .to_string();
+
+                    if sender.send(Message::Text(message.into())).await.is_err() {
+                        break; // Client disconnected
+                    }
+
+                    if progress.progress >= 100 || progress.error.is_some() {
+                        let _ = sender.send(Message::Close(None)).await;
+                        break;
+                    }
                 } else {
                     // No progress found - check database status via repository
                     let status = get_video_status_json(&state.repo, &slug).await;
@@ -1615,6 +1916,17 @@ async fn handle_progress_socket(
     info!("WebSocket connection closed for: {}", slug);
 }
 
+/// Look up live progress for the most recent transcoding job of a slug
+async fn queued_job_progress(
+    state: &MediaManagerState,
+    slug: &str,
+) -> Option<video_manager::progress::UploadProgress> {
+    let queue = state.transcode_queue.as_ref()?;
+    let tracker = state.video_progress_tracker.as_ref()?;
+    let job = queue.repo().get_latest_job_for_slug(slug).await.ok()??;
+    tracker.get(&job.upload_id)
+}
+
 /// Helper to get video status from database via repository
 async fn get_video_status_json(repo: &Arc<dyn MediaRepository>, slug: &str) -> Option<String> {
     let result = repo.get_video_status(slug).await.ok()?;
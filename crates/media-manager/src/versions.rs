@@ -0,0 +1,508 @@
+//! Media item versions
+//!
+//! Replacing the file of a media item keeps its slug, so access codes,
+//! publications and links keep working. The previous file columns are
+//! archived as a numbered version in `media_versions` and its stored files
+//! (media file or directory, kept original, thumbnail) are moved to the
+//! vault's `.versions/{slug}/v{n}/` area, keeping their layout below the
+//! vault root. The new file then runs through the regular upload processing.
+//!
+//! Rolling back archives the current version the same way and moves the
+//! chosen one back into place. Subtitle tracks belong to the item rather than
+//! to a version and stay in place.
+
+use axum::{
+    extract::{Multipart, Path, Query, State},
+    http::StatusCode,
+    response::Json,
+};
+use db::media::{MediaItemRow, MediaVersion};
+use media_core::ContentHasher;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::path::PathBuf;
+use tower_sessions::Session;
+use tracing::{info, warn};
+use video_manager::subtitles::SUBTITLES_DIR;
+
+use crate::routes::MediaManagerState;
+use crate::subtitles::{api_error, internal_error, ApiError};
+use crate::trash::stored_paths;
+use crate::upload::{process_upload, UploadData, UploadForm};
+
+/// Longest accepted version comment, in characters
+const MAX_COMMENT_LEN: usize = 500;
+
+/// Resolve a media item owned by the session user, with the user's ID
+async fn owned_item(
+    state: &MediaManagerState,
+    session: &Session,
+    slug: &str,
+) -> Result<(MediaItemRow, String), ApiError> {
+    let authenticated: bool = session
+        .get("authenticated")
+        .await
+        .ok()
+        .flatten()
+        .unwrap_or(false);
+    if !authenticated {
+        return Err(api_error(
+            StatusCode::UNAUTHORIZED,
+            "Authentication required",
+        ));
+    }
+    let user_id: String = session.get("user_id").await.ok().flatten().ok_or_else(|| {
+        api_error(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "User ID not found in session",
+        )
+    })?;
+
+    let item = state
+        .repo
+        .get_media_by_slug(slug)
+        .await
+        .map_err(|e| internal_error("Failed to load media", e))?
+        .ok_or_else(|| api_error(StatusCode::NOT_FOUND, "Media not found"))?;
+    if item.user_id.as_deref() != Some(user_id.as_str()) {
+        return Err(api_error(
+            StatusCode::FORBIDDEN,
+            "Only the owner can manage versions of this media",
+        ));
+    }
+    Ok((item, user_id))
+}
+
+/// The vault of an item whose file may be replaced right now
+fn replaceable_vault(item: &MediaItemRow) -> Result<&str, ApiError> {
+    if item.status != "active" {
+        return Err(api_error(
+            StatusCode::CONFLICT,
+            "The media is still processing",
+        ));
+    }
+    item.vault_id
+        .as_deref()
+        .ok_or_else(|| api_error(StatusCode::INTERNAL_SERVER_ERROR, "Media has no vault"))
+}
+
+/// Subtitle directory of a video, which stays with the item across versions
+fn subtitles_dir(
+    state: &MediaManagerState,
+    item: &MediaItemRow,
+    vault_id: &str,
+) -> Option<PathBuf> {
+    (item.media_type == "video").then(|| {
+        state
+            .user_storage
+            .vault_nested_media_path(vault_id, common::storage::MediaType::Video, &item.slug)
+            .join(SUBTITLES_DIR)
+    })
+}
+
+/// Move the stored files of an item's current file into the archive of `version`
+async fn archive_files(
+    state: &MediaManagerState,
+    item: &MediaItemRow,
+    filename: &str,
+    vault_id: &str,
+    version: i64,
+) {
+    let storage = &state.user_storage;
+    for path in stored_paths(storage, &item.slug, &item.media_type, filename, vault_id) {
+        let Some(version_path) = storage.vault_version_path(vault_id, &item.slug, version, &path)
+        else {
+            continue;
+        };
+        if let Err(e) = storage.move_stored(&path, &version_path).await {
+            warn!("Failed to archive {:?}: {}", path, e);
+        }
+    }
+    if let Some(subtitles) = subtitles_dir(state, item, vault_id) {
+        if let Some(archived) =
+            storage.vault_version_path(vault_id, &item.slug, version, &subtitles)
+        {
+            if let Err(e) = storage.move_stored(&archived, &subtitles).await {
+                warn!("Failed to keep subtitles of {}: {}", item.slug, e);
+            }
+        }
+    }
+    if item.media_type == "image" {
+        crate::derivatives::purge_cache(state, vault_id, &item.slug).await;
+    }
+}
+
+/// Move the stored files of archived `version` back into place
+///
+/// Whatever is stored in place (other than subtitles) is deleted first.
+async fn unarchive_files(
+    state: &MediaManagerState,
+    item: &MediaItemRow,
+    version: &MediaVersion,
+    vault_id: &str,
+) {
+    let storage = &state.user_storage;
+    if let Some(subtitles) = subtitles_dir(state, item, vault_id) {
+        if let Some(archived) =
+            storage.vault_version_path(vault_id, &item.slug, version.version, &subtitles)
+        {
+            if let Err(e) = storage.move_stored(&subtitles, &archived).await {
+                warn!("Failed to keep subtitles of {}: {}", item.slug, e);
+            }
+        }
+    }
+    for path in stored_paths(
+        storage,
+        &item.slug,
+        &item.media_type,
+        &version.filename,
+        vault_id,
+    ) {
+        let Some(version_path) =
+            storage.vault_version_path(vault_id, &item.slug, version.version, &path)
+        else {
+            continue;
+        };
+        if let Err(e) = storage.remove_stored(&path).await {
+            warn!("Failed to delete {:?}: {}", path, e);
+        }
+        if let Err(e) = storage.move_stored(&version_path, &path).await {
+            warn!("Failed to restore {:?}: {}", path, e);
+        }
+    }
+    let archive = storage
+        .vault_versions_dir(vault_id, &item.slug)
+        .join(format!("v{}", version.version));
+    if let Err(e) = storage.remove_stored(&archive).await {
+        warn!("Failed to delete archive {:?}: {}", archive, e);
+    }
+    if item.media_type == "image" {
+        crate::derivatives::purge_cache(state, vault_id, &item.slug).await;
+    }
+}
+
+/// Metadata fields that differ between two versions
+fn diff_versions(from: &MediaVersion, to: &MediaVersion) -> Vec<Value> {
+    let fields = [
+        ("filename", json!(from.filename), json!(to.filename)),
+        (
+            "original_filename",
+            json!(from.original_filename),
+            json!(to.original_filename),
+        ),
+        ("mime_type", json!(from.mime_type), json!(to.mime_type)),
+        ("file_size", json!(from.file_size), json!(to.file_size)),
+        ("video_type", json!(from.video_type), json!(to.video_type)),
+        (
+            "thumbnail_url",
+            json!(from.thumbnail_url),
+            json!(to.thumbnail_url),
+        ),
+        (
+            "content_hash",
+            json!(from.content_hash),
+            json!(to.content_hash),
+        ),
+        ("file_hash", json!(from.file_hash), json!(to.file_hash)),
+    ];
+    fields
+        .into_iter()
+        .filter(|(_, a, b)| a != b)
+        .map(|(field, a, b)| json!({ "field": field, "from": a, "to": b }))
+        .collect()
+}
+
+/// The current version of an item, or one of its archived versions
+async fn load_version(
+    state: &MediaManagerState,
+    current: &MediaVersion,
+    version: i64,
+) -> Result<MediaVersion, ApiError> {
+    if version == current.version {
+        return Ok(current.clone());
+    }
+    state
+        .repo
+        .get_media_version(current.media_id, version)
+        .await
+        .map_err(|e| internal_error("Failed to load version", e))?
+        .ok_or_else(|| api_error(StatusCode::NOT_FOUND, "Version not found"))
+}
+
+async fn current_version(
+    state: &MediaManagerState,
+    item: &MediaItemRow,
+) -> Result<MediaVersion, ApiError> {
+    state
+        .repo
+        .get_current_media_version(item.id)
+        .await
+        .map_err(|e| internal_error("Failed to load version", e))?
+        .ok_or_else(|| api_error(StatusCode::NOT_FOUND, "Media not found"))
+}
+
+// ── Handlers ────────────────────────────────────────────────────────
+
+/// Versions of a media item, current one first
+/// GET /api/media/{slug}/versions
+pub async fn list_versions(
+    State(state): State<MediaManagerState>,
+    session: Session,
+    Path(slug): Path<String>,
+) -> Result<Json<Value>, ApiError> {
+    let (item, _) = owned_item(&state, &session, &slug).await?;
+    let current = current_version(&state, &item).await?;
+    let archived = state
+        .repo
+        .list_media_versions(item.id)
+        .await
+        .map_err(|e| internal_error("Failed to list versions", e))?;
+
+    let mut versions = vec![current];
+    versions.extend(archived);
+    Ok(Json(json!({
+        "slug": slug,
+        "current": versions[0].version,
+        "count": versions.len(),
+        "versions": versions,
+    })))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DiffQuery {
+    /// Defaults to the newest archived version
+    pub from: Option<i64>,
+    /// Defaults to the current version
+    pub to: Option<i64>,
+}
+
+/// Metadata differences between two versions
+/// GET /api/media/{slug}/versions/diff?from=&to=
+pub async fn diff_versions_handler(
+    State(state): State<MediaManagerState>,
+    session: Session,
+    Path(slug): Path<String>,
+    Query(query): Query<DiffQuery>,
+) -> Result<Json<Value>, ApiError> {
+    let (item, _) = owned_item(&state, &session, &slug).await?;
+    let current = current_version(&state, &item).await?;
+
+    let from = match query.from {
+        Some(version) => load_version(&state, &current, version).await?,
+        None => state
+            .repo
+            .list_media_versions(item.id)
+            .await
+            .map_err(|e| internal_error("Failed to list versions", e))?
+            .into_iter()
+            .next()
+            .ok_or_else(|| api_error(StatusCode::NOT_FOUND, "There are no earlier versions"))?,
+    };
+    let to = match query.to {
+        Some(version) => load_version(&state, &current, version).await?,
+        None => current.clone(),
+    };
+
+    Ok(Json(json!({
+        "slug": slug,
+        "from": from.version,
+        "to": to.version,
+        "changes": diff_versions(&from, &to),
+    })))
+}
+
+/// Replace the file of a media item, archiving the current one
+/// POST /api/media/{slug}/replace (multipart: file, comment?)
+pub async fn replace_file(
+    State(state): State<MediaManagerState>,
+    session: Session,
+    Path(slug): Path<String>,
+    mut multipart: Multipart,
+) -> Result<Json<Value>, ApiError> {
+    let (item, user_id) = owned_item(&state, &session, &slug).await?;
+    let vault_id = replaceable_vault(&item)?.to_string();
+
+    let mut comment: Option<String> = None;
+    let mut file: Option<(String, Vec<u8>, String)> = None;
+    while let Some(mut field) = multipart.next_field().await.map_err(|e| {
+        warn!("Multipart error: {}", e);
+        api_error(StatusCode::BAD_REQUEST, "Invalid form data")
+    })? {
+        match field.name().unwrap_or("") {
+            "file" => {
+                let filename = field.file_name().unwrap_or("").to_string();
+                let mut hasher = ContentHasher::new();
+                let mut data = Vec::new();
+                while let Some(chunk) = field
+                    .chunk()
+                    .await
+                    .map_err(|_| api_error(StatusCode::BAD_REQUEST, "Invalid file data"))?
+                {
+                    hasher.update(&chunk);
+                    data.extend_from_slice(&chunk);
+                }
+                file = Some((filename, data, hasher.finalize()));
+            }
+            "comment" => {
+                let value = field
+                    .text()
+                    .await
+                    .map_err(|_| api_error(StatusCode::BAD_REQUEST, "Invalid comment"))?;
+                let value: String = value.trim().chars().take(MAX_COMMENT_LEN).collect();
+                comment = Some(value).filter(|c| !c.is_empty());
+            }
+            _ => {}
+        }
+    }
+
+    let (filename, data, sha256) =
+        file.ok_or_else(|| api_error(StatusCode::BAD_REQUEST, "file is required"))?;
+    let current = current_version(&state, &item).await?;
+    if current.content_hash.as_deref() == Some(sha256.as_str()) {
+        return Err(api_error(
+            StatusCode::CONFLICT,
+            "The file is identical to the current version",
+        ));
+    }
+
+    let tenant_id: String = session
+        .get("tenant_id")
+        .await
+        .ok()
+        .flatten()
+        .unwrap_or_else(|| "platform".to_string());
+
+    let archived = state
+        .repo
+        .archive_media_version(item.id, &user_id, comment.as_deref())
+        .await
+        .map_err(|e| internal_error("Failed to archive the current version", e))?;
+    archive_files(&state, &item, &item.filename, &vault_id, archived).await;
+
+    let form = UploadForm {
+        media_type: Some(item.media_type.clone()),
+        slug: Some(slug.clone()),
+        title: Some(item.title.clone()),
+        description: item.description.clone(),
+        is_public: Some(item.is_public),
+        transcode_for_streaming: Some(i32::from(item.video_type.as_deref() == Some("hls"))),
+        keep_original: None,
+        group_id: item.group_id,
+        vault_id: Some(vault_id.clone()),
+        category: item.category.clone(),
+        tags: None,
+        filename: Some(filename),
+    };
+    let result = process_upload(
+        &state,
+        user_id,
+        tenant_id,
+        form,
+        Some(UploadData::Memory { data, sha256 }),
+        Some(item.id),
+    )
+    .await;
+
+    let Json(mut result) = match result {
+        Ok(result) => result,
+        Err(e) => {
+            // Put the archived version back in place
+            if let Ok(Some(version)) = state.repo.get_media_version(item.id, archived).await {
+                unarchive_files(&state, &item, &version, &vault_id).await;
+            }
+            if let Err(e) = state.repo.restore_media_version(item.id, archived).await {
+                warn!("Failed to restore version {} of {}: {}", archived, slug, e);
+            }
+            return Err(e);
+        }
+    };
+
+    let version = current_version(&state, &item).await?.version;
+    info!("Replaced file of {}: version {} archived", slug, archived);
+    result["version"] = json!(version);
+    result["archived_version"] = json!(archived);
+    Ok(Json(result))
+}
+
+/// Make an archived version current again, archiving the current one
+/// POST /api/media/{slug}/versions/{version}/rollback
+pub async fn rollback_version(
+    State(state): State<MediaManagerState>,
+    session: Session,
+    Path((slug, version)): Path<(String, i64)>,
+) -> Result<Json<Value>, ApiError> {
+    let (item, user_id) = owned_item(&state, &session, &slug).await?;
+    let vault_id = replaceable_vault(&item)?;
+    let target = state
+        .repo
+        .get_media_version(item.id, version)
+        .await
+        .map_err(|e| internal_error("Failed to load version", e))?
+        .ok_or_else(|| api_error(StatusCode::NOT_FOUND, "Version not found"))?;
+
+    let comment = format!("Rolled back to version {}", version);
+    let archived = state
+        .repo
+        .archive_media_version(item.id, &user_id, Some(&comment))
+        .await
+        .map_err(|e| internal_error("Failed to archive the current version", e))?;
+    archive_files(&state, &item, &item.filename, vault_id, archived).await;
+    unarchive_files(&state, &item, &target, vault_id).await;
+
+    let restored = state
+        .repo
+        .restore_media_version(item.id, version)
+        .await
+        .map_err(|e| internal_error("Failed to restore version", e))?;
+    if !restored {
+        return Err(api_error(StatusCode::NOT_FOUND, "Version not found"));
+    }
+
+    info!("Rolled back {} to version {}", slug, version);
+    Ok(Json(json!({
+        "success": true,
+        "slug": slug,
+        "version": version,
+        "archived_version": archived,
+    })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn version(version: i64, filename: &str, file_size: i64) -> MediaVersion {
+        MediaVersion {
+            media_id: 1,
+            version,
+            filename: filename.to_string(),
+            original_filename: Some("report.pdf".to_string()),
+            mime_type: "application/pdf".to_string(),
+            file_size,
+            video_type: None,
+            thumbnail_url: None,
+            content_hash: Some(format!("hash-{}", version)),
+            file_hash: None,
+            current: false,
+            archived_by: None,
+            comment: None,
+            archived_at: None,
+        }
+    }
+
+    #[test]
+    fn test_diff_versions() {
+        let from = version(1, "report.pdf", 100);
+        let to = version(2, "report.pdf", 120);
+
+        let changes = diff_versions(&from, &to);
+        assert_eq!(
+            changes,
+            vec![
+                json!({ "field": "file_size", "from": 100, "to": 120 }),
+                json!({ "field": "content_hash", "from": "hash-1", "to": "hash-2" }),
+            ]
+        );
+        assert!(diff_versions(&from, &from).is_empty());
+    }
+}
@@ -0,0 +1,488 @@
+//! Video editing: chapters and clips
+//!
+//! Owners define named chapters per video; [`video_manager::chapters`]
+//! publishes them as a WebVTT chapters track and as `EXT-X-DATERANGE` tags in
+//! the HLS playlists. A clip cuts the span between two points out of a video
+//! ([`video_manager::ffmpeg::cut_clip`]) into a new media item, which then
+//! goes through the regular HLS transcoding queue. Chapters inside the clip
+//! are carried over.
+
+use access_control::SignedUrlParams;
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{Json, Response},
+};
+use common::storage::MediaType;
+use db::media::{MediaInsert, MediaItemRow, NewChapter};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::path::{Path as FsPath, PathBuf};
+use tower_sessions::Session;
+use tracing::{error, info, warn};
+use video_manager::chapters;
+use video_manager::ffmpeg::{self, FFmpegConfig};
+
+use crate::routes::MediaManagerState;
+use crate::serve::{check_video_access, AccessQuery};
+use crate::subtitles::{api_error, internal_error, owned_video, ApiError};
+use crate::upload::unique_slug;
+
+/// Shortest clip that can be cut, in seconds
+const MIN_CLIP_SECONDS: f64 = 1.0;
+
+#[derive(Debug, Deserialize)]
+pub struct ChaptersRequest {
+    pub chapters: Vec<NewChapter>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ClipRequest {
+    /// In point in seconds
+    pub start: f64,
+    /// Out point in seconds
+    pub end: f64,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    /// Defaults to the source video's visibility
+    pub is_public: Option<bool>,
+}
+
+/// Duration of a video: from its HLS playlists, else by probing the file
+async fn video_duration(
+    state: &MediaManagerState,
+    video_dir: &FsPath,
+    filename: &str,
+) -> Option<f64> {
+    if let Err(e) = state
+        .user_storage
+        .fetch_local_dir(video_dir, |name| name.ends_with(".m3u8"))
+        .await
+    {
+        warn!("Failed to fetch playlists of {:?}: {}", video_dir, e);
+    }
+    if let Some(duration) = chapters::hls_duration(video_dir).await {
+        return Some(duration);
+    }
+
+    let path = state
+        .user_storage
+        .fetch_local(&video_dir.join(filename))
+        .await?;
+    match ffmpeg::extract_metadata(&FFmpegConfig::default(), &path).await {
+        Ok(metadata) => Some(metadata.duration),
+        Err(e) => {
+            warn!("Failed to probe {:?}: {:#}", path, e);
+            None
+        }
+    }
+}
+
+/// Rebuild the chapters track and playlist date ranges after a change
+///
+/// Videos still transcoding have no known duration yet; they are packaged
+/// once transcoding finishes.
+async fn repackage(
+    state: &MediaManagerState,
+    media_id: i64,
+    video_dir: &FsPath,
+    duration: Option<f64>,
+) {
+    let Some(duration) = duration else {
+        return;
+    };
+    let chapters = match state.repo.list_chapters(media_id).await {
+        Ok(chapters) => chapters,
+        Err(e) => {
+            warn!("Failed to list chapters for packaging: {}", e);
+            return;
+        }
+    };
+
+    if let Err(e) = chapters::package_chapters(video_dir, &chapters, duration).await {
+        warn!("Failed to package chapters: {:#}", e);
+    }
+    if let Err(e) = state.user_storage.offload(video_dir).await {
+        warn!("Failed to offload packaged chapters: {}", e);
+    }
+    if chapters.is_empty() {
+        if let Err(e) = state
+            .user_storage
+            .remove_stored(&chapters::chapters_path(video_dir))
+            .await
+        {
+            warn!("Failed to remove stored chapters track: {}", e);
+        }
+    }
+}
+
+/// Load the full media row of a video
+async fn load_item(state: &MediaManagerState, slug: &str) -> Result<MediaItemRow, ApiError> {
+    state
+        .repo
+        .get_media_by_slug(slug)
+        .await
+        .map_err(|e| internal_error("Failed to load video", e))?
+        .ok_or_else(|| api_error(StatusCode::NOT_FOUND, "Video not found"))
+}
+
+// ── Chapters ────────────────────────────────────────────────────────
+
+/// List chapters
+/// GET /api/media/{slug}/chapters
+pub async fn list_chapters(
+    State(state): State<MediaManagerState>,
+    session: Session,
+    Path(slug): Path<String>,
+    Query(query): Query<AccessQuery>,
+    Query(signed): Query<SignedUrlParams>,
+    headers: HeaderMap,
+) -> Result<Json<Value>, ApiError> {
+    let info = state
+        .repo
+        .get_video_for_serving(&slug)
+        .await
+        .map_err(|e| internal_error("Failed to load video", e))?
+        .ok_or_else(|| api_error(StatusCode::NOT_FOUND, "Video not found"))?;
+    let vault_id = info.vault_id.clone().unwrap_or_default();
+
+    check_video_access(&state, &session, &headers, &info, &vault_id, &signed, query.code)
+        .await
+        .map_err(|status| api_error(status, "Access denied"))?;
+
+    let chapters = state
+        .repo
+        .list_chapters(info.id as i64)
+        .await
+        .map_err(|e| internal_error("Failed to list chapters", e))?;
+
+    Ok(Json(json!({ "success": true, "chapters": chapters })))
+}
+
+/// Replace all chapters of a video
+/// PUT /api/media/{slug}/chapters (JSON: {"chapters": [{start_time, end_time?, title}]})
+pub async fn save_chapters(
+    State(state): State<MediaManagerState>,
+    session: Session,
+    Path(slug): Path<String>,
+    Json(request): Json<ChaptersRequest>,
+) -> Result<Json<Value>, ApiError> {
+    let (info, video_dir) = owned_video(&state, &session, &slug).await?;
+    let item = load_item(&state, &slug).await?;
+
+    let duration = video_duration(&state, &video_dir, &item.filename).await;
+    let chapters = chapters::normalize_chapters(request.chapters, duration)
+        .map_err(|e| api_error(StatusCode::BAD_REQUEST, &format!("{:#}", e)))?;
+
+    let saved = state
+        .repo
+        .replace_chapters(info.id as i64, &chapters)
+        .await
+        .map_err(|e| internal_error("Failed to save chapters", e))?;
+
+    repackage(&state, info.id as i64, &video_dir, duration).await;
+
+    info!("Saved {} chapter(s) for {}", saved.len(), slug);
+    Ok(Json(json!({ "success": true, "chapters": saved })))
+}
+
+/// Serve the WebVTT chapters track
+/// GET /media/{slug}/chapters.vtt
+pub async fn serve_chapters(
+    State(state): State<MediaManagerState>,
+    session: Session,
+    Path(slug): Path<String>,
+    Query(query): Query<AccessQuery>,
+    Query(signed): Query<SignedUrlParams>,
+    headers: HeaderMap,
+) -> Result<Response, StatusCode> {
+    let info = state
+        .repo
+        .get_video_for_serving(&slug)
+        .await
+        .map_err(|e| {
+            error!("Database error: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    let vault_id = info
+        .vault_id
+        .clone()
+        .ok_or(StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    check_video_access(&state, &session, &headers, &info, &vault_id, &signed, query.code).await?;
+
+    let path = state
+        .user_storage
+        .find_stored_media_file(
+            &vault_id,
+            MediaType::Video,
+            &format!("{}/{}", slug, chapters::CHAPTERS_FILE),
+        )
+        .await
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    let mut response = common::range::serve_stored_file(
+        &state.user_storage,
+        &path,
+        "text/vtt; charset=utf-8",
+        &headers,
+    )
+    .await
+    .map_err(|e| {
+        error!("Failed to open chapters track: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    response
+        .headers_mut()
+        .insert(header::CACHE_CONTROL, HeaderValue::from_static("no-cache"));
+
+    Ok(response)
+}
+
+// ── Clips ───────────────────────────────────────────────────────────
+
+/// Cut a clip out of a video into a new media item
+/// POST /api/media/{slug}/clips (JSON: {start, end, title?, description?, is_public?})
+///
+/// The clip is cut in the background and then queued for HLS transcoding;
+/// progress is reported like for an upload.
+pub async fn create_clip(
+    State(state): State<MediaManagerState>,
+    session: Session,
+    Path(slug): Path<String>,
+    Json(request): Json<ClipRequest>,
+) -> Result<(StatusCode, Json<Value>), ApiError> {
+    let (info, video_dir) = owned_video(&state, &session, &slug).await?;
+    if state.transcode_queue.is_none() {
+        return Err(api_error(
+            StatusCode::SERVICE_UNAVAILABLE,
+            "HLS transcoding is not available",
+        ));
+    }
+    let item = load_item(&state, &slug).await?;
+    if item.status != "active" {
+        return Err(api_error(
+            StatusCode::CONFLICT,
+            "The video is still processing",
+        ));
+    }
+
+    let source = state
+        .user_storage
+        .fetch_local(&video_dir.join(&item.filename))
+        .await
+        .ok_or_else(|| {
+            api_error(
+                StatusCode::NOT_FOUND,
+                "The original video file is not available",
+            )
+        })?;
+    let duration = ffmpeg::extract_metadata(&FFmpegConfig::default(), &source)
+        .await
+        .map_err(|e| internal_error("Failed to read video", format!("{:#}", e)))?
+        .duration;
+
+    let start = request.start;
+    let end = request.end.min(duration);
+    if !start.is_finite() || !end.is_finite() || start < 0.0 || end - start < MIN_CLIP_SECONDS {
+        return Err(api_error(
+            StatusCode::BAD_REQUEST,
+            &format!(
+                "The clip must lie within the video and be at least {} second long",
+                MIN_CLIP_SECONDS
+            ),
+        ));
+    }
+
+    let user_id = info.user_id.clone().unwrap_or_default();
+    let tenant_id: String = session
+        .get("tenant_id")
+        .await
+        .ok()
+        .flatten()
+        .unwrap_or_else(|| "platform".to_string());
+    let vault_id = item
+        .vault_id
+        .clone()
+        .ok_or_else(|| api_error(StatusCode::INTERNAL_SERVER_ERROR, "Video has no vault"))?;
+
+    let title: String = request
+        .title
+        .map(|t| t.trim().to_string())
+        .filter(|t| !t.is_empty())
+        .unwrap_or_else(|| format!("{} (clip)", item.title))
+        .chars()
+        .take(200)
+        .collect();
+    let clip_slug = unique_slug(&state, &title).await?;
+    let is_public = request.is_public.map_or(item.is_public, i32::from);
+
+    let insert = MediaInsert {
+        slug: clip_slug.clone(),
+        media_type: "video".to_string(),
+        video_type: Some("hls".to_string()),
+        title: title.clone(),
+        description: request.description.or_else(|| item.description.clone()),
+        filename: "original.mp4".to_string(),
+        original_filename: Some(format!("{}.mp4", clip_slug)),
+        mime_type: "video/mp4".to_string(),
+        file_size: 0,
+        is_public,
+        user_id: Some(user_id.clone()),
+        group_id: item.group_id,
+        vault_id: Some(vault_id.clone()),
+        status: "processing".to_string(),
+        featured: 0,
+        category: item.category.clone(),
+        thumbnail_url: None,
+        allow_download: item.allow_download,
+        allow_comments: item.allow_comments,
+        mature_content: item.mature_content,
+        tenant_id,
+        content_hash: None,
+        file_hash: None,
+    };
+    let clip_id = state
+        .repo
+        .insert_media_item(&insert)
+        .await
+        .map_err(|e| internal_error("Failed to create clip record", e))?;
+
+    // Carry over the chapters inside the clip
+    match state.repo.list_chapters(info.id as i64).await {
+        Ok(source_chapters) => {
+            let clip_chapters = chapters::chapters_for_clip(&source_chapters, duration, start, end);
+            if !clip_chapters.is_empty() {
+                if let Err(e) = state.repo.replace_chapters(clip_id, &clip_chapters).await {
+                    warn!("Failed to copy chapters to clip {}: {}", clip_slug, e);
+                }
+            }
+        }
+        Err(e) => warn!("Failed to list chapters of {}: {}", slug, e),
+    }
+
+    let upload_id = uuid::Uuid::new_v4().to_string();
+    if let Some(tracker) = &state.video_progress_tracker {
+        tracker.init_upload(
+            upload_id.clone(),
+            clip_slug.clone(),
+            Some(format!("{}.mp4", clip_slug)),
+            None,
+        );
+        tracker.update(
+            &upload_id,
+            video_manager::progress::ProgressStatus::Processing,
+            10,
+            "Cutting clip".to_string(),
+        );
+    }
+
+    let job = ClipJob {
+        upload_id,
+        clip_slug: clip_slug.clone(),
+        vault_id,
+        user_id,
+        source,
+        start,
+        end,
+        is_public: is_public == 1,
+    };
+    let task_state = state.clone();
+    tokio::spawn(async move {
+        let clip_slug = job.clip_slug.clone();
+        if let Err(e) = cut_and_queue(&task_state, job).await {
+            error!("Failed to create clip {}: {:#}", clip_slug, e);
+            let _ = task_state
+                .repo
+                .update_media_status_error(&clip_slug, "video")
+                .await;
+        }
+    });
+
+    info!(
+        "Clip {} of {} ({:.3}s - {:.3}s) accepted",
+        clip_slug, slug, start, end
+    );
+    Ok((
+        StatusCode::ACCEPTED,
+        Json(json!({
+            "success": true,
+            "status": "processing",
+            "slug": clip_slug,
+            "id": clip_id,
+            "start": start,
+            "end": end,
+            "progress_url": format!("/api/media/{}/progress", clip_slug),
+        })),
+    ))
+}
+
+/// A clip waiting to be cut and queued for transcoding
+struct ClipJob {
+    upload_id: String,
+    clip_slug: String,
+    vault_id: String,
+    user_id: String,
+    source: PathBuf,
+    start: f64,
+    end: f64,
+    is_public: bool,
+}
+
+/// Cut the clip into the temp directory and queue it for HLS transcoding
+async fn cut_and_queue(state: &MediaManagerState, job: ClipJob) -> anyhow::Result<()> {
+    let queue = state
+        .transcode_queue
+        .as_ref()
+        .ok_or_else(|| anyhow::anyhow!("No transcoding queue configured"))?;
+
+    // The pipeline picks the source up from the temp directory, like an upload
+    let clip_path = state
+        .user_storage
+        .temp_dir()
+        .join(format!("{}.tmp", job.upload_id));
+    let result = ffmpeg::cut_clip(
+        &FFmpegConfig::default(),
+        &job.source,
+        &clip_path,
+        job.start,
+        job.end,
+    )
+    .await;
+    if let Err(e) = result {
+        if let Some(tracker) = &state.video_progress_tracker {
+            tracker.set_error(&job.upload_id, format!("Clip cutting failed: {}", e));
+        }
+        let _ = tokio::fs::remove_file(&clip_path).await;
+        return Err(e);
+    }
+
+    let enqueued = queue
+        .enqueue(&db::jobs::CreateTranscodeJob {
+            upload_id: job.upload_id.clone(),
+            slug: job.clip_slug.clone(),
+            vault_id: job.vault_id,
+            user_id: Some(job.user_id),
+            source_path: clip_path.to_string_lossy().to_string(),
+            original_filename: format!("{}.mp4", job.clip_slug),
+            is_public: job.is_public,
+            priority: 0,
+            max_attempts: 3,
+        })
+        .await;
+    if let Err(e) = enqueued {
+        let _ = tokio::fs::remove_file(&clip_path).await;
+        return Err(e);
+    }
+
+    if let Some(tracker) = &state.video_progress_tracker {
+        tracker.update(
+            &job.upload_id,
+            video_manager::progress::ProgressStatus::Processing,
+            20,
+            "Queued for processing".to_string(),
+        );
+    }
+    Ok(())
+}
@@ -25,8 +25,15 @@
 //! RATE_LIMIT_GENERAL_RPM=120
 //! RATE_LIMIT_GENERAL_BURST=30
 //! RATE_LIMIT_ENABLED=true         # master switch (default: true)
+//! RATE_LIMIT_AUTH_LOCKOUT_ATTEMPTS=5   # failed logins before an account locks
+//! RATE_LIMIT_AUTH_LOCKOUT_MINUTES=15   # how long it stays locked
 //! ```
 //!
+//! The per-IP limits can't stop an attacker spreading guesses for one account
+//! over many addresses, so the Auth class also carries an [`AccountLockout`]
+//! policy, applied by the local account login. It is independent of the
+//! master switch.
+//!
 //! # Usage
 //!
 //! ```rust,ignore
@@ -103,6 +110,15 @@ pub struct ClassLimit {
     pub burst: u32,
 }
 
+/// Per-account lockout after repeated failed logins (Auth class).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AccountLockout {
+    /// Consecutive failed logins that lock the account.
+    pub max_failures: u32,
+    /// How long a locked account stays locked.
+    pub lockout_minutes: u32,
+}
+
 // ---------------------------------------------------------------------------
 // Configuration
 // ---------------------------------------------------------------------------
@@ -118,6 +134,8 @@ pub struct RateLimitConfig {
     pub validation: ClassLimit,
     pub api_mutate: ClassLimit,
     pub general: ClassLimit,
+    /// Account lockout for the Auth class.
+    pub auth_lockout: AccountLockout,
 }
 
 impl Default for RateLimitConfig {
@@ -148,6 +166,10 @@ impl Default for RateLimitConfig {
                 requests_per_minute: 120,
                 burst: 30,
             },
+            auth_lockout: AccountLockout {
+                max_failures: 5,
+                lockout_minutes: 15,
+            },
         }
     }
 }
@@ -173,6 +195,16 @@ impl RateLimitConfig {
                 config.auth.burst = n;
             }
         }
+        if let Ok(v) = std::env::var("RATE_LIMIT_AUTH_LOCKOUT_ATTEMPTS") {
+            if let Ok(n) = v.parse() {
+                config.auth_lockout.max_failures = n;
+            }
+        }
+        if let Ok(v) = std::env::var("RATE_LIMIT_AUTH_LOCKOUT_MINUTES") {
+            if let Ok(n) = v.parse() {
+                config.auth_lockout.lockout_minutes = n;
+            }
+        }
 
         // Upload
         if let Ok(v) = std::env::var("RATE_LIMIT_UPLOAD_RPM") {
@@ -293,12 +325,19 @@ impl RateLimitConfig {
     pub fn print_summary(&self) {
         if !self.enabled {
             println!("⚡ Rate Limiting: DISABLED (RATE_LIMIT_ENABLED=false)");
+            println!(
+                "   - Account lockout: {} failures, {} min",
+                self.auth_lockout.max_failures, self.auth_lockout.lockout_minutes
+            );
             return;
         }
         println!("⚡ Rate Limiting: ENABLED");
         println!(
-            "   - Auth:       {} rpm, burst {}",
-            self.auth.requests_per_minute, self.auth.burst
+            "   - Auth:       {} rpm, burst {}, lockout after {} failures for {} min",
+            self.auth.requests_per_minute,
+            self.auth.burst,
+            self.auth_lockout.max_failures,
+            self.auth_lockout.lockout_minutes
         );
         println!(
             "   - Upload:     {} rpm, burst {}",
@@ -380,6 +419,8 @@ mod tests {
         assert_eq!(config.media_serving.requests_per_minute, 300);
         assert_eq!(config.media_serving.burst, 100);
         assert_eq!(config.general.requests_per_minute, 120);
+        assert_eq!(config.auth_lockout.max_failures, 5);
+        assert_eq!(config.auth_lockout.lockout_minutes, 15);
     }
 
     #[test]
@@ -450,15 +491,18 @@ mod tests {
         std::env::set_var("RATE_LIMIT_AUTH_RPM", "42");
         std::env::set_var("RATE_LIMIT_AUTH_BURST", "7");
         std::env::set_var("RATE_LIMIT_ENABLED", "true");
+        std::env::set_var("RATE_LIMIT_AUTH_LOCKOUT_ATTEMPTS", "3");
 
         let config = RateLimitConfig::from_env();
         assert_eq!(config.auth.requests_per_minute, 42);
         assert_eq!(config.auth.burst, 7);
+        assert_eq!(config.auth_lockout.max_failures, 3);
         assert!(config.enabled);
 
         // Clean up
         std::env::remove_var("RATE_LIMIT_AUTH_RPM");
         std::env::remove_var("RATE_LIMIT_AUTH_BURST");
+        std::env::remove_var("RATE_LIMIT_AUTH_LOCKOUT_ATTEMPTS");
         std::env::remove_var("RATE_LIMIT_ENABLED");
     }
 
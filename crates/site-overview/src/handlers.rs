@@ -1,6 +1,7 @@
-use workspace_core::auth::{check_scope, require_auth, verify_workspace_ownership};
+use workspace_core::auth::{check_scope_for, require_auth, verify_workspace_ownership};
 use workspace_core::WorkspaceConfig;
 use api_keys::middleware::AuthenticatedUser;
+use api_keys::ResourceRef;
 use axum::{
     extract::{Path, Query, State},
     http::StatusCode,
@@ -71,7 +72,11 @@ pub async fn generate_site_handler(
     Json(request): Json<GenerateSiteRequest>,
 ) -> Result<Json<GenerateSiteResponse>, (StatusCode, Json<serde_json::Value>)> {
     let je = |s: StatusCode| (s, Json(serde_json::json!({})));
-    check_scope(&user, "write").map_err(je)?;
+    check_scope_for(
+        &user,
+        "write",
+        &ResourceRef::workspace(&workspace_id).with_path(&request.folder_path),
+    ).map_err(je)?;
     let user_id = require_auth(&session).await.map_err(je)?;
     verify_workspace_ownership(state.repo.as_ref(), &workspace_id, &user_id).await.map_err(je)?;
 
@@ -323,7 +328,11 @@ pub async fn delete_site_build_handler(
     Json(request): Json<DeleteSiteBuildRequest>,
 ) -> Result<Json<serde_json::Value>, (StatusCode, Json<serde_json::Value>)> {
     let je = |s: StatusCode| (s, Json(serde_json::json!({})));
-    check_scope(&user, "write").map_err(je)?;
+    check_scope_for(
+        &user,
+        "write",
+        &ResourceRef::workspace(&workspace_id).with_path(&request.folder_path),
+    ).map_err(je)?;
     let user_id = require_auth(&session).await.map_err(je)?;
     verify_workspace_ownership(state.repo.as_ref(), &workspace_id, &user_id).await.map_err(je)?;
 
@@ -434,7 +443,11 @@ pub async fn vitepress_add_page_handler(
         )
     };
 
-    check_scope(&user, "write").map_err(|_| err("unauthorized"))?;
+    check_scope_for(
+        &user,
+        "write",
+        &ResourceRef::workspace(&workspace_id).with_path(&request.folder_path),
+    ).map_err(|_| err("unauthorized"))?;
     let user_id = require_auth(&session).await.map_err(|_| err("unauthorized"))?;
     verify_workspace_ownership(state.repo.as_ref(), &workspace_id, &user_id)
         .await
@@ -568,7 +581,11 @@ pub async fn site_editor_page(
     State(state): State<Arc<SiteHandlerState>>,
     Query(query): Query<SiteEditorQuery>,
 ) -> Result<Response, StatusCode> {
-    check_scope(&user, "read")?;
+    check_scope_for(
+        &user,
+        "read",
+        &ResourceRef::workspace(&workspace_id).with_path(query.path.as_deref().unwrap_or_default()),
+    )?;
     let user_id = require_auth(&session).await?;
     let (workspace_name, _) = verify_workspace_ownership(state.repo.as_ref(), &workspace_id, &user_id).await?;
 
@@ -616,7 +633,11 @@ pub async fn site_collection_page(
     State(state): State<Arc<SiteHandlerState>>,
     Query(query): Query<SiteCollectionQuery>,
 ) -> Result<Response, StatusCode> {
-    check_scope(&user, "read")?;
+    check_scope_for(
+        &user,
+        "read",
+        &ResourceRef::workspace(&workspace_id).with_path(query.path.as_deref().unwrap_or_default()),
+    )?;
     let user_id = require_auth(&session).await?;
     let (workspace_name, _) = verify_workspace_ownership(state.repo.as_ref(), &workspace_id, &user_id).await?;
 
@@ -656,7 +677,11 @@ pub async fn site_entry_editor_page(
     State(state): State<Arc<SiteHandlerState>>,
     Query(query): Query<SiteEntryQuery>,
 ) -> Result<Response, StatusCode> {
-    check_scope(&user, "read")?;
+    check_scope_for(
+        &user,
+        "read",
+        &ResourceRef::workspace(&workspace_id).with_path(query.path.as_deref().unwrap_or_default()),
+    )?;
     let user_id = require_auth(&session).await?;
     let (workspace_name, _) = verify_workspace_ownership(state.repo.as_ref(), &workspace_id, &user_id).await?;
 
@@ -700,7 +725,11 @@ pub async fn create_collection_entry(
     State(state): State<Arc<SiteHandlerState>>,
     Json(req): Json<CreateEntryRequest>,
 ) -> Result<Json<serde_json::Value>, StatusCode> {
-    check_scope(&user, "write")?;
+    check_scope_for(
+        &user,
+        "write",
+        &ResourceRef::workspace(&workspace_id).with_path(&req.folder_path),
+    )?;
     let user_id = require_auth(&session).await?;
     verify_workspace_ownership(state.repo.as_ref(), &workspace_id, &user_id).await?;
 
@@ -755,7 +784,11 @@ pub async fn save_collection_entry(
     State(state): State<Arc<SiteHandlerState>>,
     Json(req): Json<SaveEntryRequest>,
 ) -> Result<Json<serde_json::Value>, StatusCode> {
-    check_scope(&user, "write")?;
+    check_scope_for(
+        &user,
+        "write",
+        &ResourceRef::workspace(&workspace_id).with_path(&req.folder_path),
+    )?;
     let user_id = require_auth(&session).await?;
     verify_workspace_ownership(state.repo.as_ref(), &workspace_id, &user_id).await?;
 
@@ -791,7 +824,11 @@ pub async fn delete_collection_entry(
     State(state): State<Arc<SiteHandlerState>>,
     Query(req): Query<DeleteEntryQuery>,
 ) -> Result<Json<serde_json::Value>, StatusCode> {
-    check_scope(&user, "write")?;
+    check_scope_for(
+        &user,
+        "write",
+        &ResourceRef::workspace(&workspace_id).with_path(&req.folder_path),
+    )?;
     let user_id = require_auth(&session).await?;
     verify_workspace_ownership(state.repo.as_ref(), &workspace_id, &user_id).await?;
 
@@ -824,7 +861,11 @@ pub async fn create_site_page(
     State(state): State<Arc<SiteHandlerState>>,
     Json(req): Json<CreatePageRequest>,
 ) -> Result<Json<serde_json::Value>, StatusCode> {
-    check_scope(&user, "write")?;
+    check_scope_for(
+        &user,
+        "write",
+        &ResourceRef::workspace(&workspace_id).with_path(&req.folder_path),
+    )?;
     let user_id = require_auth(&session).await?;
     verify_workspace_ownership(state.repo.as_ref(), &workspace_id, &user_id).await?;
 
@@ -916,7 +957,11 @@ pub async fn create_site_collection(
     State(state): State<Arc<SiteHandlerState>>,
     Json(req): Json<CreateCollectionRequest>,
 ) -> Result<Json<serde_json::Value>, StatusCode> {
-    check_scope(&user, "write")?;
+    check_scope_for(
+        &user,
+        "write",
+        &ResourceRef::workspace(&workspace_id).with_path(&req.folder_path),
+    )?;
     let user_id = require_auth(&session).await?;
     verify_workspace_ownership(state.repo.as_ref(), &workspace_id, &user_id).await?;
 
@@ -1007,7 +1052,11 @@ pub async fn site_status_handler(
     State(state): State<Arc<SiteHandlerState>>,
     Query(req): Query<SiteFolderQuery>,
 ) -> Result<Json<serde_json::Value>, StatusCode> {
-    check_scope(&user, "read")?;
+    check_scope_for(
+        &user,
+        "read",
+        &ResourceRef::workspace(&workspace_id).with_path(&req.folder_path),
+    )?;
     let user_id = require_auth(&session).await?;
     verify_workspace_ownership(state.repo.as_ref(), &workspace_id, &user_id).await?;
 
@@ -1044,7 +1093,11 @@ pub async fn list_site_pages_handler(
     State(state): State<Arc<SiteHandlerState>>,
     Query(req): Query<SiteFolderQuery>,
 ) -> Result<Json<serde_json::Value>, StatusCode> {
-    check_scope(&user, "read")?;
+    check_scope_for(
+        &user,
+        "read",
+        &ResourceRef::workspace(&workspace_id).with_path(&req.folder_path),
+    )?;
     let user_id = require_auth(&session).await?;
     verify_workspace_ownership(state.repo.as_ref(), &workspace_id, &user_id).await?;
 
@@ -1076,7 +1129,11 @@ pub async fn remove_site_page_handler(
     State(state): State<Arc<SiteHandlerState>>,
     Query(req): Query<RemovePageQuery>,
 ) -> Result<Json<serde_json::Value>, StatusCode> {
-    check_scope(&user, "write")?;
+    check_scope_for(
+        &user,
+        "write",
+        &ResourceRef::workspace(&workspace_id).with_path(&req.folder_path),
+    )?;
     let user_id = require_auth(&session).await?;
     verify_workspace_ownership(state.repo.as_ref(), &workspace_id, &user_id).await?;
 
@@ -1124,7 +1181,11 @@ pub async fn list_site_collections_handler(
     State(state): State<Arc<SiteHandlerState>>,
     Query(req): Query<SiteFolderQuery>,
 ) -> Result<Json<serde_json::Value>, StatusCode> {
-    check_scope(&user, "read")?;
+    check_scope_for(
+        &user,
+        "read",
+        &ResourceRef::workspace(&workspace_id).with_path(&req.folder_path),
+    )?;
     let user_id = require_auth(&session).await?;
     verify_workspace_ownership(state.repo.as_ref(), &workspace_id, &user_id).await?;
 
@@ -1155,7 +1216,11 @@ pub async fn remove_site_collection_handler(
     State(state): State<Arc<SiteHandlerState>>,
     Query(req): Query<RemoveCollectionQuery>,
 ) -> Result<Json<serde_json::Value>, StatusCode> {
-    check_scope(&user, "write")?;
+    check_scope_for(
+        &user,
+        "write",
+        &ResourceRef::workspace(&workspace_id).with_path(&req.folder_path),
+    )?;
     let user_id = require_auth(&session).await?;
     verify_workspace_ownership(state.repo.as_ref(), &workspace_id, &user_id).await?;
 
@@ -1210,7 +1275,11 @@ pub async fn list_collection_entries_handler(
     State(state): State<Arc<SiteHandlerState>>,
     Query(req): Query<ListEntriesQuery>,
 ) -> Result<Json<serde_json::Value>, StatusCode> {
-    check_scope(&user, "read")?;
+    check_scope_for(
+        &user,
+        "read",
+        &ResourceRef::workspace(&workspace_id).with_path(&req.folder_path),
+    )?;
     let user_id = require_auth(&session).await?;
     verify_workspace_ownership(state.repo.as_ref(), &workspace_id, &user_id).await?;
 
@@ -1277,7 +1346,11 @@ pub async fn site_validate_handler(
     State(state): State<Arc<SiteHandlerState>>,
     Query(req): Query<SiteFolderQuery>,
 ) -> Result<Json<serde_json::Value>, StatusCode> {
-    check_scope(&user, "read")?;
+    check_scope_for(
+        &user,
+        "read",
+        &ResourceRef::workspace(&workspace_id).with_path(&req.folder_path),
+    )?;
     let user_id = require_auth(&session).await?;
     verify_workspace_ownership(state.repo.as_ref(), &workspace_id, &user_id).await?;
 
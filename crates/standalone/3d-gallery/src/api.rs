@@ -154,7 +154,8 @@ async fn fetch_media_for_access_code(
         items.push(MediaItem3D {
             id: id as i32,
             media_type: MediaType::Image,
-            url: format!("/media/{}/image.webp?code={}", slug, access_code_str),
+            // Bounded derivative: the scene never shows images above 1920 px
+            url: format!("/media/{}/image?w=1920&code={}", slug, access_code_str),
             thumbnail_url: final_thumbnail,
             title,
             description,
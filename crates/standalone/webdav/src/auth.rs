@@ -1,6 +1,8 @@
+use api_keys::middleware::client_ip_allowed;
+use api_keys::ApiKey;
 use base64::Engine;
 use db::api_keys::ApiKeyRepository;
-use http::HeaderMap;
+use http::{HeaderMap, Method};
 use tracing::warn;
 
 pub struct AuthConfig {
@@ -15,10 +17,15 @@ impl Default for AuthConfig {
     }
 }
 
+/// Authenticate a request by the API key sent as the Basic auth password.
+///
+/// Keys with an IP allowlist are rejected (403) from other addresses; scopes
+/// and workspace/folder bindings are checked per request with [`required_scope`]
+/// and [`ApiKey::allows_resource`].
 pub async fn verify_basic_auth(
     repo: &dyn ApiKeyRepository,
     headers: &HeaderMap,
-) -> Result<String, http::StatusCode> {
+) -> Result<ApiKey, http::StatusCode> {
     let auth_header = headers
         .get(http::header::AUTHORIZATION)
         .and_then(|v| v.to_str().ok())
@@ -37,7 +44,11 @@ pub async fn verify_basic_auth(
         .ok_or(http::StatusCode::UNAUTHORIZED)?;
 
     match api_keys::db::validate_api_key(repo, password).await {
-        Ok(Some(key)) => Ok(key.user_id),
+        Ok(Some(key)) if !client_ip_allowed(&key, headers) => {
+            warn!("WebDAV auth failed: API key {} used from a disallowed IP", key.id);
+            Err(http::StatusCode::FORBIDDEN)
+        }
+        Ok(Some(key)) => Ok(key),
         Ok(None) => {
             warn!("WebDAV auth failed: invalid or expired API key");
             Err(http::StatusCode::UNAUTHORIZED)
@@ -48,3 +59,12 @@ pub async fn verify_basic_auth(
         }
     }
 }
+
+/// API key scope a WebDAV method needs.
+pub fn required_scope(method: &Method) -> &'static str {
+    match method.as_str() {
+        "GET" | "HEAD" | "PROPFIND" | "OPTIONS" => "read",
+        "DELETE" => "delete",
+        _ => "write",
+    }
+}
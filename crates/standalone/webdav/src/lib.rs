@@ -7,8 +7,9 @@ use axum::{
     Router,
     extract::DefaultBodyLimit,
 };
+use api_keys::{ApiKey, ResourceRef};
 use bytes::Bytes;
-use db::api_keys::ApiKeyRepository;
+use db::api_keys::{path_within, ApiKeyRepository};
 use http_body_util::BodyExt;
 use std::{path::PathBuf, sync::Arc};
 use tracing::warn;
@@ -153,34 +154,50 @@ fn slugify(s: &str) -> String {
     result.trim_end_matches('-').to_string()
 }
 
-/// Returns (user_id, resolved_workspace_id).
+/// Returns (user_id, resolved_workspace_id, visible_child).
 /// Accepts workspace_id (exact) or a slug derived from the workspace name.
+///
+/// The API key must have the scope the method needs, and cover `path` (and the
+/// `Destination` of MOVE/COPY) if it is bound to a workspace or folder.
+/// `visible_child` is set when a folder-bound key browses a folder above its
+/// own (see [`bound_folder_child`]); listings then show only that child.
 async fn verify_workspace_access(
     state: &WebdavState,
     identifier: &str,
+    path: &str,
+    method: &Method,
     headers: &HeaderMap,
-) -> Result<(String, String), StatusCode> {
-    let user_id = auth::verify_basic_auth(&*state.api_key_repo, headers).await?;
+) -> Result<(String, String, Option<String>), StatusCode> {
+    let key = auth::verify_basic_auth(&*state.api_key_repo, headers).await?;
+    let workspace_id = resolve_workspace(state, identifier, &key.user_id).await?;
+    let visible_child = check_key_access(&key, &workspace_id, identifier, path, method, headers)?;
+    Ok((key.user_id, workspace_id, visible_child))
+}
 
+async fn resolve_workspace(
+    state: &WebdavState,
+    identifier: &str,
+    user_id: &str,
+) -> Result<String, StatusCode> {
     // 1. Try exact workspace_id match
     let row: Option<(String,)> = sqlx::query_as(
         "SELECT workspace_id FROM workspaces WHERE workspace_id = ? AND user_id = ?",
     )
     .bind(identifier)
-    .bind(&user_id)
+    .bind(user_id)
     .fetch_optional(&state.pool)
     .await
     .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
 
     if let Some((workspace_id,)) = row {
-        return Ok((user_id, workspace_id));
+        return Ok(workspace_id);
     }
 
     // 2. Slug-match against workspace names
     let rows: Vec<(String, String)> = sqlx::query_as(
         "SELECT workspace_id, name FROM workspaces WHERE user_id = ?",
     )
-    .bind(&user_id)
+    .bind(user_id)
     .fetch_all(&state.pool)
     .await
     .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
@@ -188,7 +205,7 @@ async fn verify_workspace_access(
     let identifier_slug = slugify(identifier);
     for (workspace_id, name) in rows {
         if slugify(&name) == identifier_slug {
-            return Ok((user_id, workspace_id));
+            return Ok(workspace_id);
         }
     }
 
@@ -196,6 +213,82 @@ async fn verify_workspace_access(
     Err(StatusCode::FORBIDDEN)
 }
 
+/// Checks the key's scope for the method and its workspace/folder bindings.
+///
+/// Returns the child `path` may list for a PROPFIND above the key's folder.
+fn check_key_access(
+    key: &ApiKey,
+    workspace_id: &str,
+    identifier: &str,
+    path: &str,
+    method: &Method,
+    headers: &HeaderMap,
+) -> Result<Option<String>, StatusCode> {
+    let scope = auth::required_scope(method);
+    if !key.has_scope(scope) && !key.has_scope("admin") {
+        warn!("API key {} lacks the {} scope for {}", key.id, scope, method);
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    let mut paths = vec![path.to_string()];
+    if matches!(method.as_str(), "MOVE" | "COPY") {
+        if let Some(dest) = headers
+            .get("Destination")
+            .and_then(|v| v.to_str().ok())
+            .and_then(|d| destination_path(d, identifier))
+        {
+            paths.push(dest);
+        }
+    }
+    for path in paths {
+        if !key.allows_resource(&ResourceRef::workspace(workspace_id).with_path(path.as_str())) {
+            if let Some(child) = bound_folder_child(key, workspace_id, &path, method, headers) {
+                return Ok(Some(child));
+            }
+            warn!("API key {} is not bound to {}/{}", key.id, workspace_id, path);
+            return Err(StatusCode::FORBIDDEN);
+        }
+    }
+    Ok(None)
+}
+
+/// Lets a folder-bound key browse down to its folder, so clients that start
+/// at the workspace root (e.g. Finder) can mount it.
+///
+/// For a PROPFIND with Depth 0 or 1 on a folder above the key's folder,
+/// returns the child of `path` on the way down to it. Nothing else in those
+/// folders is visible.
+fn bound_folder_child(
+    key: &ApiKey,
+    workspace_id: &str,
+    path: &str,
+    method: &Method,
+    headers: &HeaderMap,
+) -> Option<String> {
+    if method.as_str() != "PROPFIND" {
+        return None;
+    }
+    let depth = headers
+        .get("Depth")
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("1");
+    if depth != "0" && depth != "1" {
+        return None;
+    }
+    let prefix = key.path_prefix.as_deref()?;
+    if !key.allows_resource(&ResourceRef::workspace(workspace_id).with_path(prefix))
+        || !path_within(prefix, path)
+    {
+        return None;
+    }
+    let path_depth = path.split('/').filter(|s| !s.is_empty()).count();
+    prefix
+        .split('/')
+        .filter(|s| !s.is_empty())
+        .nth(path_depth)
+        .map(str::to_string)
+}
+
 async fn handle_get(
     state: &WebdavState,
     workspace_id: &str,
@@ -212,7 +305,7 @@ async fn handle_get(
     }
 
     if full_path.is_dir() {
-        return handle_propfind(state, workspace_id, identifier, path, headers).await;
+        return handle_propfind(state, workspace_id, identifier, path, headers, None).await;
     }
 
     match tokio::fs::read(&full_path).await {
@@ -277,6 +370,7 @@ async fn handle_propfind(
     identifier: &str,    // original URL segment, for hrefs
     path: &str,
     headers: &HeaderMap,
+    visible_child: Option<&str>, // only child to list, for folder-bound keys
 ) -> Response {
     let workspace_root = state.workspace_root(workspace_id);
     let path_trimmed = path.trim_start_matches('/');
@@ -326,6 +420,9 @@ async fn handle_propfind(
                     if name.starts_with('.') {
                         continue;
                     }
+                    if visible_child.is_some_and(|child| child != name) {
+                        continue;
+                    }
                     let is_dir = entry_path.is_dir();
                     let child_href = format!("{}/{}", base_href, name);
                     xml.push_str(&dav_xml::propfind_response(&child_href, &entry_path, is_dir));
@@ -661,11 +758,11 @@ pub fn webdav_routes(state: WebdavState) -> Router {
                         // be consumed before the next request (e.g. PUT) arrives.
                         let _ = body.collect().await;
 
-                        match verify_workspace_access(&state, &workspace_id, &headers).await {
-                            Ok((_, resolved)) => match method.as_str() {
+                        match verify_workspace_access(&state, &workspace_id, "", &method, &headers).await {
+                            Ok((_, resolved, visible_child)) => match method.as_str() {
                                 "HEAD" => handle_head(&state, &resolved, "").await,
                                 "PROPFIND" => {
-                                    handle_propfind(&state, &resolved, &workspace_id, "", &headers).await
+                                    handle_propfind(&state, &resolved, &workspace_id, "", &headers, visible_child.as_deref()).await
                                 }
                                 "MKCOL" => handle_mkcol(&state, &resolved, "").await,
                                 "LOCK" => handle_lock(&workspace_id, ""),
@@ -694,11 +791,11 @@ pub fn webdav_routes(state: WebdavState) -> Router {
                         // Drain the request body — see comment above.
                         let _ = body.collect().await;
 
-                        match verify_workspace_access(&state, &workspace_id, &headers).await {
-                            Ok((_, resolved)) => match method.as_str() {
+                        match verify_workspace_access(&state, &workspace_id, "", &method, &headers).await {
+                            Ok((_, resolved, visible_child)) => match method.as_str() {
                                 "HEAD" => handle_head(&state, &resolved, "").await,
                                 "PROPFIND" => {
-                                    handle_propfind(&state, &resolved, &workspace_id, "", &headers).await
+                                    handle_propfind(&state, &resolved, &workspace_id, "", &headers, visible_child.as_deref()).await
                                 }
                                 "MKCOL" => handle_mkcol(&state, &resolved, "").await,
                                 "LOCK" => handle_lock(&workspace_id, ""),
@@ -724,11 +821,11 @@ pub fn webdav_routes(state: WebdavState) -> Router {
                         // Drain the request body — see comment above.
                         let _ = body.collect().await;
 
-                        match verify_workspace_access(&state, &workspace_id, &headers).await {
-                            Ok((_, resolved)) => match method.as_str() {
+                        match verify_workspace_access(&state, &workspace_id, &path, &method, &headers).await {
+                            Ok((_, resolved, visible_child)) => match method.as_str() {
                                 "HEAD" => handle_head(&state, &resolved, &path).await,
                                 "PROPFIND" => {
-                                    handle_propfind(&state, &resolved, &workspace_id, &path, &headers).await
+                                    handle_propfind(&state, &resolved, &workspace_id, &path, &headers, visible_child.as_deref()).await
                                 }
                                 "MKCOL" => handle_mkcol(&state, &resolved, &path).await,
                                 "MOVE" => {
@@ -753,8 +850,8 @@ pub fn webdav_routes(state: WebdavState) -> Router {
                       body: Body| {
                     let state = state.clone();
                     async move {
-                        match verify_workspace_access(&state, &workspace_id, &headers).await {
-                            Ok((_, resolved)) => handle_put(&state, &resolved, &path, body).await,
+                        match verify_workspace_access(&state, &workspace_id, &path, &Method::PUT, &headers).await {
+                            Ok((_, resolved, _)) => handle_put(&state, &resolved, &path, body).await,
                             Err(status) => auth_error_response(status),
                         }
                     }
@@ -765,8 +862,8 @@ pub fn webdav_routes(state: WebdavState) -> Router {
                 move |Path((workspace_id, path)): Path<(String, String)>, headers: HeaderMap| {
                     let state = state.clone();
                     async move {
-                        match verify_workspace_access(&state, &workspace_id, &headers).await {
-                            Ok((_, resolved)) => handle_delete(&state, &resolved, &path).await,
+                        match verify_workspace_access(&state, &workspace_id, &path, &Method::DELETE, &headers).await {
+                            Ok((_, resolved, _)) => handle_delete(&state, &resolved, &path).await,
                             Err(status) => auth_error_response(status),
                         }
                     }
@@ -13,12 +13,19 @@ use openidconnect::{
     PkceCodeVerifier, RedirectUrl, Scope, TokenResponse,
 };
 use db::user_auth::{UpsertUserRequest, UserAuthRepository};
+use rate_limiter::{AccountLockout, RateLimitConfig};
 use serde::Deserialize;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use tower_sessions::Session;
 use tracing::{self, error, info, warn};
 
+pub mod local;
+pub mod passkeys;
+pub mod password;
+pub mod totp;
+pub mod webauthn;
+
 // -------------------------------
 // Configuration
 // -------------------------------
@@ -31,6 +38,7 @@ pub struct OidcConfig {
     pub enable_emergency_login: bool,
     pub su_user: String,
     pub su_pwd: String,
+    pub enable_local_accounts: bool,
 }
 
 impl OidcConfig {
@@ -50,6 +58,10 @@ impl OidcConfig {
                 .unwrap_or(false),
             su_user: std::env::var("SU_USER").unwrap_or_else(|_| "admin".to_string()),
             su_pwd: std::env::var("SU_PWD").unwrap_or_else(|_| "".to_string()),
+            enable_local_accounts: std::env::var("ENABLE_LOCAL_ACCOUNTS")
+                .unwrap_or_else(|_| "false".to_string())
+                .parse()
+                .unwrap_or(false),
         }
     }
 }
@@ -62,6 +74,10 @@ pub struct AuthState {
     pub oidc_client: Arc<RwLock<Option<CoreClient>>>,
     pub config: OidcConfig,
     pub repo: Arc<dyn UserAuthRepository>,
+    /// Lockout policy for local account logins
+    pub lockout: AccountLockout,
+    /// Site passkeys are registered for
+    pub relying_party: webauthn::RelyingParty,
 }
 
 impl AuthState {
@@ -73,19 +89,29 @@ impl AuthState {
 
         Ok(Self {
             oidc_client: Arc::new(RwLock::new(oidc_client)),
+            relying_party: webauthn::RelyingParty::from_env(&config.redirect_uri),
             config,
             repo,
+            lockout: RateLimitConfig::default().auth_lockout,
         })
     }
 
     pub fn new_without_oidc(config: OidcConfig, repo: Arc<dyn UserAuthRepository>) -> Self {
         Self {
             oidc_client: Arc::new(RwLock::new(None)),
+            relying_party: webauthn::RelyingParty::from_env(&config.redirect_uri),
             config,
             repo,
+            lockout: RateLimitConfig::default().auth_lockout,
         }
     }
 
+    /// Use the lockout policy of the rate limit configuration.
+    pub fn with_lockout(mut self, lockout: AccountLockout) -> Self {
+        self.lockout = lockout;
+        self
+    }
+
     /// Attempt OIDC provider discovery. Returns None if provider is unreachable.
     async fn discover_oidc_client(config: &OidcConfig) -> Option<CoreClient> {
         println!("🔍 Discovering OIDC provider: {}", config.issuer_url);
@@ -142,7 +168,8 @@ pub fn auth_routes(state: Arc<AuthState>) -> Router {
         .route("/profile", get(user_profile_handler))
         .route("/oidc/authorize", get(oidc_authorize_handler))
         .route("/oidc/callback", get(oidc_callback_handler))
-        .route("/auth/error", get(auth_error_handler));
+        .route("/auth/error", get(auth_error_handler))
+        .merge(passkeys::passkey_routes());
 
     // Only register emergency login routes if enabled
     if state.config.enable_emergency_login {
@@ -157,6 +184,11 @@ pub fn auth_routes(state: Arc<AuthState>) -> Router {
         println!("🔒 Emergency login is DISABLED");
     }
 
+    if state.config.enable_local_accounts {
+        println!("👤 Local accounts are ENABLED");
+        router = router.merge(local::local_account_routes());
+    }
+
     router.with_state(state)
 }
 
@@ -170,6 +202,7 @@ struct LoginTemplate {
     authenticated: bool,
     oidc_available: bool,
     emergency_enabled: bool,
+    local_enabled: bool,
 }
 
 #[derive(Template)]
@@ -316,11 +349,13 @@ pub async fn login_page_handler(
 
     let oidc_available = state.get_oidc_client().await.is_some();
     let emergency_enabled = state.config.enable_emergency_login;
+    let local_enabled = state.config.enable_local_accounts;
 
     let template = LoginTemplate {
         authenticated: false,
         oidc_available,
         emergency_enabled,
+        local_enabled,
     };
     Ok(Html(template.render().unwrap()))
 }
@@ -843,6 +878,91 @@ pub async fn logout_handler(session: Session) -> Result<Redirect, StatusCode> {
     Ok(Redirect::to("/"))
 }
 
+// -------------------------------
+// Session Setup
+// -------------------------------
+
+/// Store a successful login in the session, with the same tenant invitation,
+/// tenant and branding resolution as the OIDC callback. Used by the passkey
+/// and local account logins; the session id is renewed first.
+pub(crate) async fn start_user_session(
+    state: &AuthState,
+    session: &Session,
+    user_id: &str,
+    email: &str,
+    name: &str,
+    avatar_url: &str,
+) -> Result<(), StatusCode> {
+    if let Ok(Some(invited_tenant)) = state.repo.get_tenant_invitation_by_email(email).await {
+        let _ = state.repo.set_user_tenant(user_id, &invited_tenant).await;
+        let _ = state.repo.delete_tenant_invitation(email).await;
+        info!(
+            event = "invitation_accepted",
+            user_id = %user_id,
+            tenant_id = %invited_tenant,
+            "User assigned to tenant via invitation"
+        );
+    }
+
+    let tenant_id: String = state
+        .repo
+        .get_user_tenant_id(user_id)
+        .await
+        .unwrap_or(None)
+        .unwrap_or_else(|| "platform".to_string());
+
+    let brand_name: String = state
+        .repo
+        .get_tenant_branding_json(&tenant_id)
+        .await
+        .unwrap_or(None)
+        .and_then(|j| serde_json::from_str::<serde_json::Value>(&j).ok())
+        .and_then(|v| v.get("name").and_then(|n| n.as_str()).map(String::from))
+        .unwrap_or_default();
+
+    // Prevent session fixation
+    session
+        .cycle_id()
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    session
+        .insert("authenticated", true)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let values = [
+        ("user_id", user_id.to_string()),
+        ("tenant_id", tenant_id),
+        ("brand_name", brand_name),
+        ("email", email.to_string()),
+        ("name", name.to_string()),
+        ("avatar_url", avatar_url.to_string()),
+    ];
+    for (key, value) in values {
+        session
+            .insert(key, value)
+            .await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    }
+    Ok(())
+}
+
+/// Only same-site paths are accepted as post-login redirect.
+///
+/// The path must start with a single `/` followed by a character other than
+/// `/`; backslashes and control characters are rejected, since browsers treat
+/// `/\host` or `/\t/host` like `//host`.
+pub(crate) fn safe_return_to(return_to: Option<String>) -> String {
+    return_to
+        .filter(|r| {
+            let mut chars = r.chars();
+            chars.next() == Some('/')
+                && chars.next().is_some_and(|c| c != '/')
+                && !r.chars().any(|c| c == '\\' || c.is_control())
+        })
+        .unwrap_or_else(|| "/".to_string())
+}
+
 // -------------------------------
 // Helper Functions
 // -------------------------------
@@ -861,3 +981,21 @@ pub async fn is_authenticated(session: &Session) -> bool {
 pub async fn get_user_id(session: &Session) -> Option<String> {
     session.get("user_id").await.ok().flatten()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_safe_return_to() {
+        assert_eq!(safe_return_to(None), "/");
+        assert_eq!(safe_return_to(Some("/media".into())), "/media");
+        assert_eq!(safe_return_to(Some("//evil.example".into())), "/");
+        assert_eq!(safe_return_to(Some("https://evil.example".into())), "/");
+        assert_eq!(safe_return_to(Some("/\\evil.example".into())), "/");
+        assert_eq!(safe_return_to(Some("/\t/evil.example".into())), "/");
+        assert_eq!(safe_return_to(Some("/media\r\nX: y".into())), "/");
+        assert_eq!(safe_return_to(Some("".into())), "/");
+        assert_eq!(safe_return_to(Some("/media?q=a/b".into())), "/media?q=a/b");
+    }
+}
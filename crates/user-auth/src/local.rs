@@ -0,0 +1,1062 @@
+//! Built-in (local) accounts for instances without an identity provider.
+//!
+//! Accounts sign in with email and password, plus a TOTP code once they have
+//! enrolled. There is no open registration: the platform admin issues signup
+//! invitations and password reset links, which are single-use tokens stored
+//! as SHA-256 only.
+//!
+//! Failed logins (password, TOTP code or recovery code) count towards the
+//! Auth-class [`AccountLockout`](rate_limiter::AccountLockout) of
+//! [`AuthState`]; the per-IP Auth rate limit applies on top since all routes
+//! here are part of [`auth_routes`](crate::auth_routes).
+
+use crate::password::{
+    generate_token, hash_password, hash_token, validate_password, verify_dummy_password,
+    verify_password,
+};
+use crate::{
+    is_authenticated, safe_return_to, start_user_session, totp, AlreadyLoggedInTemplate, AuthState,
+};
+use askama::Template;
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{Html, IntoResponse, Redirect, Response},
+    routing::{get, post},
+    Form, Json, Router,
+};
+use db::user_auth::{LocalAccount, NewLocalAccount};
+use db::DbError;
+use serde::Deserialize;
+use serde_json::json;
+use std::sync::Arc;
+use tower_sessions::Session;
+use tracing::{error, info, warn};
+
+/// Session key of a user who passed the password step but still owes a TOTP code
+const PENDING_TOTP_KEY: &str = "local_pending_user_id";
+
+const DEFAULT_INVITATION_HOURS: i64 = 72;
+const MAX_INVITATION_HOURS: i64 = 720;
+const PASSWORD_RESET_HOURS: i64 = 24;
+
+type ApiError = (StatusCode, Json<serde_json::Value>);
+
+fn api_error(status: StatusCode, msg: &str) -> ApiError {
+    (status, Json(json!({ "error": msg })))
+}
+
+fn internal_error(e: DbError) -> ApiError {
+    error!(error = %e, "Local account database error");
+    api_error(StatusCode::INTERNAL_SERVER_ERROR, "Database error")
+}
+
+fn error_redirect(reason: &str, detail: &str) -> Redirect {
+    Redirect::to(&format!(
+        "/auth/error?reason={}&detail={}",
+        reason,
+        urlencoding::encode(detail)
+    ))
+}
+
+// -------------------------------
+// Router Setup
+// -------------------------------
+
+/// Routes for local accounts, merged into `auth_routes` when
+/// `ENABLE_LOCAL_ACCOUNTS=true`.
+pub fn local_account_routes() -> Router<Arc<AuthState>> {
+    Router::new()
+        .route("/login/local", get(login_form_handler).post(login_handler))
+        .route(
+            "/login/local/totp",
+            get(totp_form_handler).post(totp_handler),
+        )
+        .route("/signup", get(signup_form_handler).post(signup_handler))
+        .route(
+            "/password/reset",
+            get(password_reset_form_handler).post(password_reset_handler),
+        )
+        .route("/profile/security", get(security_page_handler))
+        // Self-service
+        .route("/api/account/password", post(change_password))
+        .route("/api/account/totp", get(totp_status))
+        .route("/api/account/totp/enroll", post(totp_enroll))
+        .route("/api/account/totp/confirm", post(totp_confirm))
+        .route("/api/account/totp/disable", post(totp_disable))
+        .route(
+            "/api/account/totp/recovery-codes",
+            post(regenerate_recovery_codes),
+        )
+        // Platform admin
+        .route("/api/admin/local-accounts", get(list_accounts))
+        .route(
+            "/api/admin/local-accounts/invitations",
+            post(create_invitation),
+        )
+        .route(
+            "/api/admin/local-accounts/{user_id}/password-reset",
+            post(create_password_reset),
+        )
+}
+
+// -------------------------------
+// Templates
+// -------------------------------
+
+#[derive(Template)]
+#[template(path = "auth/local_login.html")]
+struct LocalLoginTemplate {
+    authenticated: bool,
+    error: Option<String>,
+    notice: Option<String>,
+}
+
+#[derive(Template)]
+#[template(path = "auth/local_totp.html")]
+struct LocalTotpTemplate {
+    authenticated: bool,
+    error: Option<String>,
+}
+
+#[derive(Template)]
+#[template(path = "auth/local_signup.html")]
+struct LocalSignupTemplate {
+    authenticated: bool,
+    token: String,
+    email: String,
+    name: String,
+    error: Option<String>,
+}
+
+#[derive(Template)]
+#[template(path = "auth/local_password_reset.html")]
+struct LocalPasswordResetTemplate {
+    authenticated: bool,
+    token: String,
+    error: Option<String>,
+}
+
+#[derive(Template)]
+#[template(path = "auth/local_security.html")]
+struct LocalSecurityTemplate {
+    authenticated: bool,
+    email: String,
+    totp_enabled: bool,
+    recovery_codes_left: i64,
+    min_password_length: usize,
+}
+
+fn render(template: impl Template) -> Html<String> {
+    Html(template.render().unwrap())
+}
+
+fn login_page(error: Option<String>, notice: Option<String>) -> Html<String> {
+    render(LocalLoginTemplate {
+        authenticated: false,
+        error,
+        notice,
+    })
+}
+
+// -------------------------------
+// Helpers
+// -------------------------------
+
+/// Argon2 is deliberately slow; keep it off the async workers.
+async fn check_password(password: String, password_hash: String) -> bool {
+    tokio::task::spawn_blocking(move || verify_password(&password, &password_hash))
+        .await
+        .unwrap_or(false)
+}
+
+async fn check_dummy_password(password: String) {
+    let _ = tokio::task::spawn_blocking(move || verify_dummy_password(&password)).await;
+}
+
+async fn new_password_hash(password: String) -> Result<String, String> {
+    tokio::task::spawn_blocking(move || hash_password(&password))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// Generic failure message; doesn't reveal whether the account exists or is locked.
+fn failed_login_message(state: &AuthState) -> String {
+    format!(
+        "Invalid credentials. After {} failed attempts the account is locked for {} minutes.",
+        state.lockout.max_failures, state.lockout.lockout_minutes
+    )
+}
+
+/// Count a failed login against the lockout policy.
+async fn record_failure(state: &AuthState, account: &LocalAccount, reason: &str) {
+    warn!(
+        event = "auth_failed",
+        auth_type = "local",
+        user_id = %account.user_id,
+        reason = reason,
+        "Failed local login attempt"
+    );
+    match state
+        .repo
+        .record_local_login_failure(
+            &account.user_id,
+            state.lockout.max_failures,
+            state.lockout.lockout_minutes,
+        )
+        .await
+    {
+        Ok(true) => warn!(
+            event = "account_locked",
+            auth_type = "local",
+            user_id = %account.user_id,
+            lockout_minutes = state.lockout.lockout_minutes,
+            "Local account locked after repeated failed logins"
+        ),
+        Ok(false) => {}
+        Err(e) => error!(error = %e, "Failed to record failed login"),
+    }
+}
+
+/// Log the account in and send it where it was going.
+async fn start_session(
+    state: &AuthState,
+    session: &Session,
+    account: &LocalAccount,
+) -> Result<Redirect, StatusCode> {
+    if let Err(e) = state
+        .repo
+        .record_local_login_success(&account.user_id)
+        .await
+    {
+        error!(error = %e, "Failed to record local login");
+    }
+
+    let return_to: Option<String> = session.get("return_to").await.ok().flatten();
+    let _ = session.remove::<String>("return_to").await;
+    let _ = session.remove::<String>(PENDING_TOTP_KEY).await;
+
+    start_user_session(
+        state,
+        session,
+        &account.user_id,
+        &account.email,
+        account.name.as_deref().unwrap_or_default(),
+        "",
+    )
+    .await?;
+
+    info!(
+        event = "auth_success",
+        auth_type = "local",
+        user_id = %account.user_id,
+        email = %account.email,
+        "User authenticated via local account"
+    );
+
+    Ok(Redirect::to(&safe_return_to(return_to)))
+}
+
+/// The local account of the logged-in user.
+async fn session_account(state: &AuthState, session: &Session) -> Result<LocalAccount, ApiError> {
+    if !is_authenticated(session).await {
+        return Err(api_error(StatusCode::UNAUTHORIZED, "Not authenticated"));
+    }
+    let user_id: String = session
+        .get("user_id")
+        .await
+        .ok()
+        .flatten()
+        .ok_or_else(|| api_error(StatusCode::UNAUTHORIZED, "Not authenticated"))?;
+    state
+        .repo
+        .get_local_account(&user_id)
+        .await
+        .map_err(internal_error)?
+        .ok_or_else(|| api_error(StatusCode::NOT_FOUND, "Not a local account"))
+}
+
+/// Re-check the password before sensitive self-service changes.
+async fn confirm_password(
+    state: &AuthState,
+    account: &LocalAccount,
+    password: &str,
+) -> Result<(), ApiError> {
+    if account.locked {
+        return Err(api_error(
+            StatusCode::FORBIDDEN,
+            "Account is temporarily locked after too many failed attempts",
+        ));
+    }
+    if check_password(password.to_string(), account.password_hash.clone()).await {
+        Ok(())
+    } else {
+        record_failure(state, account, "invalid_password").await;
+        Err(api_error(StatusCode::FORBIDDEN, "Incorrect password"))
+    }
+}
+
+/// [`workspace_core::auth::require_platform_admin`] with a JSON error body
+async fn require_platform_admin(session: &Session) -> Result<String, ApiError> {
+    workspace_core::auth::require_platform_admin(session)
+        .await
+        .map_err(|status| match status {
+            StatusCode::FORBIDDEN => api_error(status, "Platform admin only"),
+            _ => api_error(status, "Not authenticated"),
+        })
+}
+
+fn new_recovery_codes() -> (Vec<String>, Vec<String>) {
+    let codes = totp::generate_recovery_codes();
+    let hashes = codes.iter().map(|c| hash_token(c)).collect();
+    (codes, hashes)
+}
+
+// -------------------------------
+// Login
+// -------------------------------
+
+#[derive(Debug, Deserialize)]
+pub struct LocalLoginQuery {
+    #[serde(default)]
+    pub return_to: Option<String>,
+}
+
+#[tracing::instrument(skip(session, query))]
+pub async fn login_form_handler(
+    session: Session,
+    Query(query): Query<LocalLoginQuery>,
+) -> Result<Html<String>, StatusCode> {
+    if is_authenticated(&session).await {
+        return Ok(render(AlreadyLoggedInTemplate {
+            authenticated: true,
+        }));
+    }
+    if let Some(return_to) = query.return_to {
+        session
+            .insert("return_to", return_to)
+            .await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    }
+    Ok(login_page(None, None))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LocalLoginForm {
+    email: String,
+    password: String,
+}
+
+#[tracing::instrument(skip(state, session, form))]
+pub async fn login_handler(
+    State(state): State<Arc<AuthState>>,
+    session: Session,
+    Form(form): Form<LocalLoginForm>,
+) -> Result<Response, StatusCode> {
+    let account = state
+        .repo
+        .get_local_account_by_email(form.email.trim())
+        .await
+        .map_err(|e| {
+            error!(error = %e, "Failed to look up local account");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    let account = match account {
+        Some(account) if !account.locked => account,
+        Some(account) => {
+            // Locked: don't even check the password, but take as long as if we did
+            check_dummy_password(form.password).await;
+            warn!(
+                event = "auth_failed",
+                auth_type = "local",
+                user_id = %account.user_id,
+                reason = "account_locked",
+                "Login attempt on locked local account"
+            );
+            return Ok(login_page(Some(failed_login_message(&state)), None).into_response());
+        }
+        None => {
+            check_dummy_password(form.password).await;
+            warn!(
+                event = "auth_failed",
+                auth_type = "local",
+                reason = "unknown_email",
+                "Failed local login attempt"
+            );
+            return Ok(login_page(Some(failed_login_message(&state)), None).into_response());
+        }
+    };
+
+    if !check_password(form.password, account.password_hash.clone()).await {
+        record_failure(&state, &account, "invalid_password").await;
+        return Ok(login_page(Some(failed_login_message(&state)), None).into_response());
+    }
+
+    if account.totp_enabled {
+        session
+            .insert(PENDING_TOTP_KEY, account.user_id.clone())
+            .await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        return Ok(Redirect::to("/login/local/totp").into_response());
+    }
+
+    Ok(start_session(&state, &session, &account)
+        .await?
+        .into_response())
+}
+
+#[tracing::instrument(skip(session))]
+pub async fn totp_form_handler(session: Session) -> Response {
+    let pending: Option<String> = session.get(PENDING_TOTP_KEY).await.ok().flatten();
+    if pending.is_none() {
+        return Redirect::to("/login/local").into_response();
+    }
+    render(LocalTotpTemplate {
+        authenticated: false,
+        error: None,
+    })
+    .into_response()
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LocalTotpForm {
+    code: String,
+}
+
+/// Second login step: a TOTP code, or one of the recovery codes.
+#[tracing::instrument(skip(state, session, form))]
+pub async fn totp_handler(
+    State(state): State<Arc<AuthState>>,
+    session: Session,
+    Form(form): Form<LocalTotpForm>,
+) -> Result<Response, StatusCode> {
+    let pending: Option<String> = session.get(PENDING_TOTP_KEY).await.ok().flatten();
+    let Some(user_id) = pending else {
+        return Ok(Redirect::to("/login/local").into_response());
+    };
+
+    let account = state
+        .repo
+        .get_local_account(&user_id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let account = match account {
+        Some(account) if !account.locked => account,
+        _ => {
+            let _ = session.remove::<String>(PENDING_TOTP_KEY).await;
+            return Ok(login_page(Some(failed_login_message(&state)), None).into_response());
+        }
+    };
+
+    let code = form.code.trim();
+    let accepted = if code
+        .chars()
+        .all(|c| c.is_ascii_digit() || c.is_whitespace())
+    {
+        match account
+            .totp_secret
+            .as_deref()
+            .and_then(|secret| totp::verify(secret, code))
+        {
+            Some(step) => state
+                .repo
+                .accept_local_totp_step(&account.user_id, step)
+                .await
+                .unwrap_or(false),
+            None => false,
+        }
+    } else {
+        let code_hash = hash_token(&totp::normalize_recovery_code(code));
+        let used = state
+            .repo
+            .consume_local_recovery_code(&account.user_id, &code_hash)
+            .await
+            .unwrap_or(false);
+        if used {
+            info!(
+                event = "recovery_code_used",
+                user_id = %account.user_id,
+                "Local account signed in with a recovery code"
+            );
+        }
+        used
+    };
+
+    if !accepted {
+        record_failure(&state, &account, "invalid_totp_code").await;
+        return Ok(render(LocalTotpTemplate {
+            authenticated: false,
+            error: Some("Invalid code. Please try again.".to_string()),
+        })
+        .into_response());
+    }
+
+    Ok(start_session(&state, &session, &account)
+        .await?
+        .into_response())
+}
+
+// -------------------------------
+// Signup by invitation
+// -------------------------------
+
+#[derive(Debug, Deserialize)]
+pub struct TokenQuery {
+    #[serde(default)]
+    pub token: String,
+}
+
+#[tracing::instrument(skip(state, query))]
+pub async fn signup_form_handler(
+    State(state): State<Arc<AuthState>>,
+    Query(query): Query<TokenQuery>,
+) -> Response {
+    match state
+        .repo
+        .get_local_invitation(&hash_token(&query.token))
+        .await
+    {
+        Ok(Some(invitation)) => render(LocalSignupTemplate {
+            authenticated: false,
+            token: query.token,
+            email: invitation.email,
+            name: String::new(),
+            error: None,
+        })
+        .into_response(),
+        _ => error_redirect(
+            "invalid_invitation",
+            "This invitation link is invalid or has expired. Please ask for a new one.",
+        )
+        .into_response(),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SignupForm {
+    token: String,
+    name: String,
+    password: String,
+    password_confirm: String,
+}
+
+#[tracing::instrument(skip(state, session, form))]
+pub async fn signup_handler(
+    State(state): State<Arc<AuthState>>,
+    session: Session,
+    Form(form): Form<SignupForm>,
+) -> Result<Response, StatusCode> {
+    let token_hash = hash_token(&form.token);
+    let invitation = match state.repo.get_local_invitation(&token_hash).await {
+        Ok(Some(invitation)) => invitation,
+        _ => {
+            return Ok(error_redirect(
+                "invalid_invitation",
+                "This invitation link is invalid or has expired. Please ask for a new one.",
+            )
+            .into_response())
+        }
+    };
+
+    let form_error = |message: String| {
+        render(LocalSignupTemplate {
+            authenticated: false,
+            token: form.token.clone(),
+            email: invitation.email.clone(),
+            name: form.name.clone(),
+            error: Some(message),
+        })
+        .into_response()
+    };
+
+    let name = form.name.trim();
+    if name.is_empty() {
+        return Ok(form_error("Please enter your name".to_string()));
+    }
+    if let Err(message) = validate_password(&form.password) {
+        return Ok(form_error(message));
+    }
+    if form.password != form.password_confirm {
+        return Ok(form_error("Passwords do not match".to_string()));
+    }
+
+    let password_hash = new_password_hash(form.password.clone())
+        .await
+        .map_err(|e| {
+            error!(error = %e, "Failed to hash password");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    let user_id = uuid::Uuid::new_v4().to_string();
+    let created = state
+        .repo
+        .create_local_account(&NewLocalAccount {
+            user_id: &user_id,
+            email: &invitation.email,
+            name,
+            password_hash: &password_hash,
+            tenant_id: invitation.tenant_id.as_deref(),
+        })
+        .await;
+    match created {
+        Ok(()) => {}
+        Err(DbError::UniqueViolation(_)) => {
+            return Ok(form_error(
+                "An account with this email already exists".to_string(),
+            ))
+        }
+        Err(e) => {
+            error!(error = %e, "Failed to create local account");
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    }
+    let _ = state.repo.delete_local_invitation(&token_hash).await;
+
+    info!(
+        event = "local_account_created",
+        user_id = %user_id,
+        email = %invitation.email,
+        tenant_id = invitation.tenant_id.as_deref().unwrap_or("platform"),
+        "Local account created from invitation"
+    );
+
+    let account = state
+        .repo
+        .get_local_account(&user_id)
+        .await
+        .ok()
+        .flatten()
+        .ok_or(StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(start_session(&state, &session, &account)
+        .await?
+        .into_response())
+}
+
+// -------------------------------
+// Password reset
+// -------------------------------
+
+#[tracing::instrument(skip(query))]
+pub async fn password_reset_form_handler(Query(query): Query<TokenQuery>) -> Html<String> {
+    render(LocalPasswordResetTemplate {
+        authenticated: false,
+        token: query.token,
+        error: None,
+    })
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PasswordResetForm {
+    token: String,
+    password: String,
+    password_confirm: String,
+}
+
+#[tracing::instrument(skip(state, form))]
+pub async fn password_reset_handler(
+    State(state): State<Arc<AuthState>>,
+    Form(form): Form<PasswordResetForm>,
+) -> Result<Response, StatusCode> {
+    // Check the new password first so a typo doesn't use up the token
+    let problem = validate_password(&form.password).err().or_else(|| {
+        (form.password != form.password_confirm).then(|| "Passwords do not match".to_string())
+    });
+    if let Some(message) = problem {
+        return Ok(render(LocalPasswordResetTemplate {
+            authenticated: false,
+            token: form.token,
+            error: Some(message),
+        })
+        .into_response());
+    }
+
+    let user_id = match state
+        .repo
+        .consume_local_password_reset(&hash_token(&form.token))
+        .await
+    {
+        Ok(Some(user_id)) => user_id,
+        _ => {
+            return Ok(error_redirect(
+                "invalid_reset_link",
+                "This password reset link is invalid or has expired. Please ask for a new one.",
+            )
+            .into_response())
+        }
+    };
+
+    let password_hash = new_password_hash(form.password).await.map_err(|e| {
+        error!(error = %e, "Failed to hash password");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    state
+        .repo
+        .set_local_password(&user_id, &password_hash)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    info!(
+        event = "password_reset",
+        auth_type = "local",
+        user_id = %user_id,
+        "Local account password reset"
+    );
+
+    Ok(login_page(
+        None,
+        Some("Your password has been changed. Please log in.".to_string()),
+    )
+    .into_response())
+}
+
+// -------------------------------
+// Self-service
+// -------------------------------
+
+#[tracing::instrument(skip(state, session))]
+pub async fn security_page_handler(
+    State(state): State<Arc<AuthState>>,
+    session: Session,
+) -> Result<Html<String>, StatusCode> {
+    let account = session_account(&state, &session)
+        .await
+        .map_err(|(status, _)| status)?;
+    let recovery_codes_left = state
+        .repo
+        .count_local_recovery_codes(&account.user_id)
+        .await
+        .unwrap_or(0);
+
+    Ok(render(LocalSecurityTemplate {
+        authenticated: true,
+        email: account.email,
+        totp_enabled: account.totp_enabled,
+        recovery_codes_left,
+        min_password_length: crate::password::MIN_PASSWORD_LENGTH,
+    }))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ChangePasswordRequest {
+    current_password: String,
+    new_password: String,
+}
+
+/// POST /api/account/password
+pub async fn change_password(
+    State(state): State<Arc<AuthState>>,
+    session: Session,
+    Json(req): Json<ChangePasswordRequest>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let account = session_account(&state, &session).await?;
+    confirm_password(&state, &account, &req.current_password).await?;
+    validate_password(&req.new_password)
+        .map_err(|message| api_error(StatusCode::BAD_REQUEST, &message))?;
+
+    let password_hash = new_password_hash(req.new_password)
+        .await
+        .map_err(|_| api_error(StatusCode::INTERNAL_SERVER_ERROR, "Failed to hash password"))?;
+    state
+        .repo
+        .set_local_password(&account.user_id, &password_hash)
+        .await
+        .map_err(internal_error)?;
+
+    info!(
+        event = "password_changed",
+        auth_type = "local",
+        user_id = %account.user_id,
+        "Local account password changed"
+    );
+    Ok(Json(json!({ "success": true })))
+}
+
+/// GET /api/account/totp
+pub async fn totp_status(
+    State(state): State<Arc<AuthState>>,
+    session: Session,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let account = session_account(&state, &session).await?;
+    let recovery_codes_left = state
+        .repo
+        .count_local_recovery_codes(&account.user_id)
+        .await
+        .map_err(internal_error)?;
+    Ok(Json(json!({
+        "enabled": account.totp_enabled,
+        "recovery_codes_left": recovery_codes_left,
+    })))
+}
+
+/// POST /api/account/totp/enroll — new secret, active once confirmed
+pub async fn totp_enroll(
+    State(state): State<Arc<AuthState>>,
+    session: Session,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let account = session_account(&state, &session).await?;
+    if account.totp_enabled {
+        return Err(api_error(
+            StatusCode::CONFLICT,
+            "Two-factor authentication is already enabled",
+        ));
+    }
+
+    let secret = totp::generate_secret();
+    state
+        .repo
+        .set_local_totp_secret(&account.user_id, &secret)
+        .await
+        .map_err(internal_error)?;
+
+    let brand_name: String = session
+        .get("brand_name")
+        .await
+        .ok()
+        .flatten()
+        .filter(|n: &String| !n.is_empty())
+        .unwrap_or_else(|| "Media Server".to_string());
+
+    Ok(Json(json!({
+        "secret": secret,
+        "otpauth_uri": totp::provisioning_uri(&secret, &brand_name, &account.email),
+    })))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TotpConfirmRequest {
+    code: String,
+}
+
+/// POST /api/account/totp/confirm — enable TOTP; returns the recovery codes
+pub async fn totp_confirm(
+    State(state): State<Arc<AuthState>>,
+    session: Session,
+    Json(req): Json<TotpConfirmRequest>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let account = session_account(&state, &session).await?;
+    if account.totp_enabled {
+        return Err(api_error(
+            StatusCode::CONFLICT,
+            "Two-factor authentication is already enabled",
+        ));
+    }
+    let secret = account
+        .totp_secret
+        .as_deref()
+        .ok_or_else(|| api_error(StatusCode::BAD_REQUEST, "Start enrollment first"))?;
+
+    let step = totp::verify(secret, &req.code)
+        .ok_or_else(|| api_error(StatusCode::BAD_REQUEST, "Invalid code"))?;
+    state
+        .repo
+        .accept_local_totp_step(&account.user_id, step)
+        .await
+        .map_err(internal_error)?;
+
+    let (codes, hashes) = new_recovery_codes();
+    state
+        .repo
+        .enable_local_totp(&account.user_id, &hashes)
+        .await
+        .map_err(internal_error)?;
+
+    info!(
+        event = "totp_enabled",
+        user_id = %account.user_id,
+        "Two-factor authentication enabled for local account"
+    );
+    Ok(Json(json!({ "recovery_codes": codes })))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PasswordConfirmRequest {
+    password: String,
+}
+
+/// POST /api/account/totp/disable
+pub async fn totp_disable(
+    State(state): State<Arc<AuthState>>,
+    session: Session,
+    Json(req): Json<PasswordConfirmRequest>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let account = session_account(&state, &session).await?;
+    confirm_password(&state, &account, &req.password).await?;
+    state
+        .repo
+        .disable_local_totp(&account.user_id)
+        .await
+        .map_err(internal_error)?;
+
+    info!(
+        event = "totp_disabled",
+        user_id = %account.user_id,
+        "Two-factor authentication disabled for local account"
+    );
+    Ok(Json(json!({ "success": true })))
+}
+
+/// POST /api/account/totp/recovery-codes — replace all recovery codes
+pub async fn regenerate_recovery_codes(
+    State(state): State<Arc<AuthState>>,
+    session: Session,
+    Json(req): Json<PasswordConfirmRequest>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let account = session_account(&state, &session).await?;
+    if !account.totp_enabled {
+        return Err(api_error(
+            StatusCode::BAD_REQUEST,
+            "Two-factor authentication is not enabled",
+        ));
+    }
+    confirm_password(&state, &account, &req.password).await?;
+
+    let (codes, hashes) = new_recovery_codes();
+    state
+        .repo
+        .replace_local_recovery_codes(&account.user_id, &hashes)
+        .await
+        .map_err(internal_error)?;
+    Ok(Json(json!({ "recovery_codes": codes })))
+}
+
+// -------------------------------
+// Platform admin
+// -------------------------------
+
+/// GET /api/admin/local-accounts
+pub async fn list_accounts(
+    State(state): State<Arc<AuthState>>,
+    session: Session,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    require_platform_admin(&session).await?;
+    let accounts = state
+        .repo
+        .list_local_accounts()
+        .await
+        .map_err(internal_error)?;
+
+    let accounts: Vec<serde_json::Value> = accounts
+        .into_iter()
+        .map(|a| {
+            json!({
+                "user_id": a.user_id,
+                "email": a.email,
+                "name": a.name,
+                "totp_enabled": a.totp_enabled,
+                "failed_attempts": a.failed_attempts,
+                "locked": a.locked,
+                "locked_until": a.locked_until,
+                "password_changed_at": a.password_changed_at,
+                "created_at": a.created_at,
+            })
+        })
+        .collect();
+    Ok(Json(json!({ "accounts": accounts })))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateInvitationRequest {
+    email: String,
+    #[serde(default)]
+    tenant_id: Option<String>,
+    #[serde(default)]
+    valid_hours: Option<i64>,
+}
+
+/// POST /api/admin/local-accounts/invitations
+///
+/// Returns the signup link; it is shown only once.
+pub async fn create_invitation(
+    State(state): State<Arc<AuthState>>,
+    session: Session,
+    Json(req): Json<CreateInvitationRequest>,
+) -> Result<(StatusCode, Json<serde_json::Value>), ApiError> {
+    let admin_id = require_platform_admin(&session).await?;
+
+    let email = req.email.trim();
+    if email.is_empty() || !email.contains('@') {
+        return Err(api_error(StatusCode::BAD_REQUEST, "Invalid email address"));
+    }
+    if state
+        .repo
+        .get_local_account_by_email(email)
+        .await
+        .map_err(internal_error)?
+        .is_some()
+    {
+        return Err(api_error(
+            StatusCode::CONFLICT,
+            "A local account with this email already exists",
+        ));
+    }
+    let tenant_id = req.tenant_id.as_deref().filter(|t| !t.is_empty());
+    let valid_hours = req
+        .valid_hours
+        .unwrap_or(DEFAULT_INVITATION_HOURS)
+        .clamp(1, MAX_INVITATION_HOURS);
+
+    let token = generate_token();
+    let expires_at = state
+        .repo
+        .create_local_invitation(
+            &hash_token(&token),
+            email,
+            tenant_id,
+            &admin_id,
+            valid_hours,
+        )
+        .await
+        .map_err(internal_error)?;
+
+    info!(
+        event = "local_invitation_created",
+        email = %email,
+        tenant_id = tenant_id.unwrap_or("platform"),
+        "Local account signup invitation created"
+    );
+    Ok((
+        StatusCode::CREATED,
+        Json(json!({
+            "email": email,
+            "tenant_id": tenant_id,
+            "signup_url": format!("/signup?token={}", token),
+            "expires_at": expires_at,
+        })),
+    ))
+}
+
+/// POST /api/admin/local-accounts/{user_id}/password-reset
+///
+/// Returns the reset link; it is shown only once.
+pub async fn create_password_reset(
+    State(state): State<Arc<AuthState>>,
+    session: Session,
+    Path(user_id): Path<String>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    require_platform_admin(&session).await?;
+    let account = state
+        .repo
+        .get_local_account(&user_id)
+        .await
+        .map_err(internal_error)?
+        .ok_or_else(|| api_error(StatusCode::NOT_FOUND, "Local account not found"))?;
+
+    let token = generate_token();
+    let expires_at = state
+        .repo
+        .create_local_password_reset(&hash_token(&token), &account.user_id, PASSWORD_RESET_HOURS)
+        .await
+        .map_err(internal_error)?;
+
+    info!(
+        event = "password_reset_created",
+        user_id = %account.user_id,
+        "Local account password reset link created"
+    );
+    Ok(Json(json!({
+        "user_id": account.user_id,
+        "reset_url": format!("/password/reset?token={}", token),
+        "expires_at": expires_at,
+    })))
+}
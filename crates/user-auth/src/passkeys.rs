@@ -0,0 +1,439 @@
+//! Passkey (WebAuthn) registration, login and management.
+//!
+//! Passkeys are discoverable credentials: the login ceremony asks the browser
+//! for any passkey of this site, and the credential it returns identifies the
+//! user. Challenges live in the session and are single-use. A successful
+//! login sets up the session exactly like the OIDC callback.
+
+use crate::webauthn::{self, ES256, RS256};
+use crate::{is_authenticated, safe_return_to, start_user_session, AuthState};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    routing::{get, post, put},
+    Json, Router,
+};
+use db::user_auth::NewPasskeyCredential;
+use db::DbError;
+use serde::Deserialize;
+use serde_json::json;
+use std::sync::Arc;
+use tower_sessions::Session;
+use tracing::{error, info, warn};
+
+const REGISTER_CHALLENGE_KEY: &str = "passkey_register_challenge";
+const LOGIN_CHALLENGE_KEY: &str = "passkey_login_challenge";
+
+/// Browser-side timeout of a ceremony, in milliseconds
+const CEREMONY_TIMEOUT_MS: u32 = 120_000;
+/// WebAuthn limits user handles to 64 bytes
+const MAX_USER_HANDLE_LEN: usize = 64;
+const MAX_NAME_LEN: usize = 100;
+
+type ApiError = (StatusCode, Json<serde_json::Value>);
+
+fn api_error(status: StatusCode, msg: &str) -> ApiError {
+    (status, Json(json!({ "error": msg })))
+}
+
+fn internal_error(e: DbError) -> ApiError {
+    error!(error = %e, "Passkey database error");
+    api_error(StatusCode::INTERNAL_SERVER_ERROR, "Database error")
+}
+
+fn decode(value: &str) -> Result<Vec<u8>, ApiError> {
+    webauthn::decode(value).map_err(|e| api_error(StatusCode::BAD_REQUEST, &e))
+}
+
+/// Routes for passkeys; part of `auth_routes`, so they share the Auth rate limit.
+pub fn passkey_routes() -> Router<Arc<AuthState>> {
+    Router::new()
+        .route("/api/passkeys", get(list_passkeys))
+        .route("/api/passkeys/register/options", post(registration_options))
+        .route("/api/passkeys/register", post(register_passkey))
+        .route(
+            "/api/passkeys/{id}",
+            put(rename_passkey).delete(delete_passkey),
+        )
+        .route("/api/passkeys/login/options", post(login_options))
+        .route("/api/passkeys/login", post(login))
+}
+
+async fn require_user(session: &Session) -> Result<String, ApiError> {
+    if !is_authenticated(session).await {
+        return Err(api_error(StatusCode::UNAUTHORIZED, "Not authenticated"));
+    }
+    session
+        .get("user_id")
+        .await
+        .ok()
+        .flatten()
+        .ok_or_else(|| api_error(StatusCode::UNAUTHORIZED, "Not authenticated"))
+}
+
+/// Take a challenge out of the session; each one is good for one attempt.
+async fn take_challenge(session: &Session, key: &str) -> Result<String, ApiError> {
+    session
+        .remove::<String>(key)
+        .await
+        .ok()
+        .flatten()
+        .ok_or_else(|| {
+            api_error(
+                StatusCode::BAD_REQUEST,
+                "No passkey request in progress. Please try again.",
+            )
+        })
+}
+
+// -------------------------------
+// Management
+// -------------------------------
+
+/// GET /api/passkeys
+pub async fn list_passkeys(
+    State(state): State<Arc<AuthState>>,
+    session: Session,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let user_id = require_user(&session).await?;
+    let passkeys = state
+        .repo
+        .list_passkeys(&user_id)
+        .await
+        .map_err(internal_error)?;
+
+    let passkeys: Vec<serde_json::Value> = passkeys
+        .into_iter()
+        .map(|p| {
+            json!({
+                "id": p.id,
+                "name": p.name,
+                "created_at": p.created_at,
+                "last_used_at": p.last_used_at,
+            })
+        })
+        .collect();
+    Ok(Json(json!({ "passkeys": passkeys })))
+}
+
+/// POST /api/passkeys/register/options — `PublicKeyCredentialCreationOptions`
+pub async fn registration_options(
+    State(state): State<Arc<AuthState>>,
+    session: Session,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let user_id = require_user(&session).await?;
+    if user_id.len() > MAX_USER_HANDLE_LEN {
+        return Err(api_error(
+            StatusCode::BAD_REQUEST,
+            "Passkeys are not available for this account",
+        ));
+    }
+    let email: String = session
+        .get("email")
+        .await
+        .ok()
+        .flatten()
+        .unwrap_or_default();
+    let name: String = session
+        .get("name")
+        .await
+        .ok()
+        .flatten()
+        .unwrap_or_else(|| email.clone());
+
+    // Don't register the same authenticator twice
+    let exclude: Vec<serde_json::Value> = state
+        .repo
+        .list_passkeys(&user_id)
+        .await
+        .map_err(internal_error)?
+        .into_iter()
+        .map(|p| json!({ "type": "public-key", "id": p.credential_id }))
+        .collect();
+
+    let challenge = webauthn::generate_challenge();
+    session
+        .insert(REGISTER_CHALLENGE_KEY, challenge.clone())
+        .await
+        .map_err(|_| api_error(StatusCode::INTERNAL_SERVER_ERROR, "Session error"))?;
+
+    let rp = &state.relying_party;
+    Ok(Json(json!({
+        "publicKey": {
+            "challenge": challenge,
+            "rp": { "id": rp.id, "name": rp.name },
+            "user": {
+                "id": webauthn::encode(user_id.as_bytes()),
+                "name": email,
+                "displayName": name,
+            },
+            "pubKeyCredParams": [
+                { "type": "public-key", "alg": ES256 },
+                { "type": "public-key", "alg": RS256 },
+            ],
+            "timeout": CEREMONY_TIMEOUT_MS,
+            "attestation": "none",
+            "authenticatorSelection": {
+                "residentKey": "required",
+                "requireResidentKey": true,
+                "userVerification": "required",
+            },
+            "excludeCredentials": exclude,
+        }
+    })))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RegisterPasskeyRequest {
+    #[serde(default)]
+    name: Option<String>,
+    /// `rawId`, base64url
+    credential_id: String,
+    client_data_json: String,
+    attestation_object: String,
+}
+
+/// POST /api/passkeys/register
+pub async fn register_passkey(
+    State(state): State<Arc<AuthState>>,
+    session: Session,
+    Json(req): Json<RegisterPasskeyRequest>,
+) -> Result<(StatusCode, Json<serde_json::Value>), ApiError> {
+    let user_id = require_user(&session).await?;
+    let challenge = take_challenge(&session, REGISTER_CHALLENGE_KEY).await?;
+
+    let credential = webauthn::verify_registration(
+        &state.relying_party,
+        &challenge,
+        &decode(&req.client_data_json)?,
+        &decode(&req.attestation_object)?,
+    )
+    .map_err(|e| {
+        warn!(
+            event = "passkey_registration_failed",
+            user_id = %user_id,
+            reason = %e,
+            "Passkey registration rejected"
+        );
+        api_error(StatusCode::BAD_REQUEST, &e)
+    })?;
+    if credential.credential_id != decode(&req.credential_id)? {
+        return Err(api_error(StatusCode::BAD_REQUEST, "Credential id mismatch"));
+    }
+
+    let name = req
+        .name
+        .as_deref()
+        .map(str::trim)
+        .filter(|n| !n.is_empty())
+        .unwrap_or("Passkey");
+    let name: String = name.chars().take(MAX_NAME_LEN).collect();
+
+    let credential_id = webauthn::encode(&credential.credential_id);
+    let id = state
+        .repo
+        .add_passkey(&NewPasskeyCredential {
+            user_id: &user_id,
+            credential_id: &credential_id,
+            public_key: &credential.public_key,
+            sign_count: credential.sign_count as i64,
+            name: &name,
+        })
+        .await
+        .map_err(|e| match e {
+            DbError::UniqueViolation(_) => {
+                api_error(StatusCode::CONFLICT, "This passkey is already registered")
+            }
+            e => internal_error(e),
+        })?;
+
+    info!(
+        event = "passkey_registered",
+        user_id = %user_id,
+        passkey_id = id,
+        "Passkey registered"
+    );
+    Ok((StatusCode::CREATED, Json(json!({ "id": id, "name": name }))))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RenamePasskeyRequest {
+    name: String,
+}
+
+/// PUT /api/passkeys/{id}
+pub async fn rename_passkey(
+    State(state): State<Arc<AuthState>>,
+    session: Session,
+    Path(id): Path<i64>,
+    Json(req): Json<RenamePasskeyRequest>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let user_id = require_user(&session).await?;
+    let name = req.name.trim();
+    if name.is_empty() || name.chars().count() > MAX_NAME_LEN {
+        return Err(api_error(
+            StatusCode::BAD_REQUEST,
+            "Name must be 1-100 characters",
+        ));
+    }
+    if !state
+        .repo
+        .rename_passkey(&user_id, id, name)
+        .await
+        .map_err(internal_error)?
+    {
+        return Err(api_error(StatusCode::NOT_FOUND, "Passkey not found"));
+    }
+    Ok(Json(json!({ "id": id, "name": name })))
+}
+
+/// DELETE /api/passkeys/{id}
+pub async fn delete_passkey(
+    State(state): State<Arc<AuthState>>,
+    session: Session,
+    Path(id): Path<i64>,
+) -> Result<StatusCode, ApiError> {
+    let user_id = require_user(&session).await?;
+    if !state
+        .repo
+        .delete_passkey(&user_id, id)
+        .await
+        .map_err(internal_error)?
+    {
+        return Err(api_error(StatusCode::NOT_FOUND, "Passkey not found"));
+    }
+    info!(
+        event = "passkey_deleted",
+        user_id = %user_id,
+        passkey_id = id,
+        "Passkey deleted"
+    );
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// -------------------------------
+// Login
+// -------------------------------
+
+/// POST /api/passkeys/login/options — `PublicKeyCredentialRequestOptions`
+pub async fn login_options(
+    State(state): State<Arc<AuthState>>,
+    session: Session,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let challenge = webauthn::generate_challenge();
+    session
+        .insert(LOGIN_CHALLENGE_KEY, challenge.clone())
+        .await
+        .map_err(|_| api_error(StatusCode::INTERNAL_SERVER_ERROR, "Session error"))?;
+
+    // No allowCredentials: the browser offers every passkey of this site
+    Ok(Json(json!({
+        "publicKey": {
+            "challenge": challenge,
+            "rpId": state.relying_party.id,
+            "timeout": CEREMONY_TIMEOUT_MS,
+            "userVerification": "required",
+        }
+    })))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PasskeyLoginRequest {
+    /// `rawId`, base64url
+    credential_id: String,
+    client_data_json: String,
+    authenticator_data: String,
+    signature: String,
+    #[serde(default)]
+    user_handle: Option<String>,
+    #[serde(default)]
+    return_to: Option<String>,
+}
+
+/// POST /api/passkeys/login — returns where to go next
+pub async fn login(
+    State(state): State<Arc<AuthState>>,
+    session: Session,
+    Json(req): Json<PasskeyLoginRequest>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let challenge = take_challenge(&session, LOGIN_CHALLENGE_KEY).await?;
+    let rejected = |reason: &str| {
+        warn!(
+            event = "auth_failed",
+            auth_type = "passkey",
+            reason = reason,
+            "Passkey login rejected"
+        );
+        api_error(StatusCode::UNAUTHORIZED, "Passkey not accepted")
+    };
+
+    let credential_id = webauthn::encode(&decode(&req.credential_id)?);
+    let passkey = state
+        .repo
+        .get_passkey_by_credential_id(&credential_id)
+        .await
+        .map_err(internal_error)?
+        .ok_or_else(|| rejected("unknown_credential"))?;
+
+    if let Some(handle) = req.user_handle.as_deref().filter(|h| !h.is_empty()) {
+        if decode(handle)? != passkey.user_id.as_bytes() {
+            return Err(rejected("user_handle_mismatch"));
+        }
+    }
+
+    let sign_count = webauthn::verify_authentication(
+        &state.relying_party,
+        &challenge,
+        &passkey.public_key,
+        passkey.sign_count as u32,
+        &decode(&req.client_data_json)?,
+        &decode(&req.authenticator_data)?,
+        &decode(&req.signature)?,
+    )
+    .map_err(|e| rejected(&e))?;
+
+    if let Err(e) = state
+        .repo
+        .record_passkey_use(passkey.id, sign_count as i64)
+        .await
+    {
+        error!(error = %e, "Failed to record passkey use");
+    }
+    let _ = state.repo.record_user_login(&passkey.user_id).await;
+
+    let user_id = passkey.user_id;
+    let email = state
+        .repo
+        .get_user_email(&user_id)
+        .await
+        .map_err(internal_error)?
+        .unwrap_or_else(|| "unknown".to_string());
+    let name = state
+        .repo
+        .get_user_name(&user_id)
+        .await
+        .map_err(internal_error)?
+        .flatten()
+        .unwrap_or_else(|| "Unknown User".to_string());
+    let avatar_url = state
+        .repo
+        .get_user_avatar_url(&user_id)
+        .await
+        .map_err(internal_error)?
+        .unwrap_or_default();
+
+    start_user_session(&state, &session, &user_id, &email, &name, &avatar_url)
+        .await
+        .map_err(|status| api_error(status, "Failed to start session"))?;
+
+    info!(
+        event = "auth_success",
+        auth_type = "passkey",
+        user_id = %user_id,
+        email = %email,
+        passkey_id = passkey.id,
+        "User authenticated via passkey"
+    );
+
+    Ok(Json(json!({ "redirect": safe_return_to(req.return_to) })))
+}
@@ -0,0 +1,121 @@
+//! Password hashing and one-time tokens for local accounts.
+//!
+//! Passwords are hashed with Argon2id (PHC string format, random salt).
+//! Signup invitations, password reset links and recovery codes are random
+//! tokens of which only the SHA-256 is stored.
+
+use argon2::password_hash::{rand_core::OsRng, PasswordHash, SaltString};
+use argon2::{Argon2, PasswordHasher, PasswordVerifier};
+use data_encoding::HEXLOWER;
+use rand::Rng;
+use sha2::{Digest, Sha256};
+use std::sync::OnceLock;
+
+/// Minimum accepted password length, in characters
+pub const MIN_PASSWORD_LENGTH: usize = 10;
+
+/// Hash a password with Argon2id.
+pub fn hash_password(password: &str) -> Result<String, String> {
+    let salt = SaltString::generate(&mut OsRng);
+    Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .map(|hash| hash.to_string())
+        .map_err(|e| format!("Failed to hash password: {}", e))
+}
+
+/// Check a password against a stored Argon2 hash.
+pub fn verify_password(password: &str, password_hash: &str) -> bool {
+    match PasswordHash::new(password_hash) {
+        Ok(parsed) => Argon2::default()
+            .verify_password(password.as_bytes(), &parsed)
+            .is_ok(),
+        Err(_) => false,
+    }
+}
+
+/// Spend the same time as a real verification, so unknown emails can't be
+/// told apart from wrong passwords by response time.
+pub fn verify_dummy_password(password: &str) {
+    static DUMMY_HASH: OnceLock<String> = OnceLock::new();
+    let hash = DUMMY_HASH.get_or_init(|| hash_password("dummy password").unwrap_or_default());
+    let _ = verify_password(password, hash);
+}
+
+/// Check a new password against the password policy.
+pub fn validate_password(password: &str) -> Result<(), String> {
+    if password.chars().count() < MIN_PASSWORD_LENGTH {
+        return Err(format!(
+            "Password must be at least {} characters long",
+            MIN_PASSWORD_LENGTH
+        ));
+    }
+    if password.trim().is_empty() {
+        return Err("Password must not be blank".to_string());
+    }
+    Ok(())
+}
+
+/// Generate a random URL-safe token (signup invitations, password resets).
+pub fn generate_token() -> String {
+    const TOKEN_LENGTH: usize = 40;
+    const CHARSET: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789";
+
+    let mut rng = rand::thread_rng();
+    (0..TOKEN_LENGTH)
+        .map(|_| CHARSET[rng.gen_range(0..CHARSET.len())] as char)
+        .collect()
+}
+
+/// Hash a token or recovery code with SHA-256 (hex-encoded).
+pub fn hash_token(token: &str) -> String {
+    HEXLOWER.encode(&Sha256::digest(token.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hash_and_verify_password() {
+        let hash = hash_password("correct horse battery").unwrap();
+        assert!(hash.starts_with("$argon2id$"));
+        assert!(verify_password("correct horse battery", &hash));
+        assert!(!verify_password("wrong horse battery", &hash));
+    }
+
+    #[test]
+    fn test_hashes_are_salted() {
+        let a = hash_password("same password").unwrap();
+        let b = hash_password("same password").unwrap();
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn test_verify_rejects_malformed_hash() {
+        assert!(!verify_password("anything", "not-a-phc-string"));
+    }
+
+    #[test]
+    fn test_validate_password() {
+        assert!(validate_password("short").is_err());
+        assert!(validate_password("          ").is_err());
+        assert!(validate_password("long enough pw").is_ok());
+    }
+
+    #[test]
+    fn test_generate_token() {
+        let a = generate_token();
+        let b = generate_token();
+        assert_eq!(a.len(), 40);
+        assert!(a.chars().all(|c| c.is_ascii_alphanumeric()));
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn test_hash_token() {
+        assert_eq!(
+            hash_token("abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+}
@@ -0,0 +1,573 @@
+//! WebAuthn relying party checks for passkey login.
+//!
+//! Only what passkeys need: `none` attestation (the attestation statement is
+//! not verified), required user verification, and ES256 or RS256 credential
+//! keys. The browser side is plain `navigator.credentials`; binary fields are
+//! exchanged base64url-encoded.
+
+use ciborium::value::Value;
+use data_encoding::BASE64URL_NOPAD;
+use p256::ecdsa::signature::Verifier;
+use rand::Rng;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+
+/// COSE algorithm ids offered at registration, in order of preference
+pub const ES256: i64 = -7;
+pub const RS256: i64 = -257;
+
+/// COSE key types (`kty`) and the P-256 curve (`crv`)
+const KTY_EC2: i64 = 2;
+const KTY_RSA: i64 = 3;
+const CRV_P256: i64 = 1;
+
+/// Smallest RSA modulus accepted for RS256 credentials
+const MIN_RSA_BITS: usize = 2048;
+
+const FLAG_USER_PRESENT: u8 = 0x01;
+const FLAG_USER_VERIFIED: u8 = 0x04;
+const FLAG_ATTESTED_CREDENTIAL: u8 = 0x40;
+
+/// The site passkeys are bound to.
+#[derive(Clone, Debug)]
+pub struct RelyingParty {
+    /// Registrable domain, e.g. `media.example.com`
+    pub id: String,
+    /// Exact origin the browser reports, e.g. `https://media.example.com`
+    pub origin: String,
+    /// Name shown by the authenticator
+    pub name: String,
+}
+
+impl RelyingParty {
+    /// `WEBAUTHN_RP_ID`, `WEBAUTHN_ORIGIN` and `WEBAUTHN_RP_NAME`; the origin
+    /// defaults to the scheme and host of the OIDC redirect URI.
+    pub fn from_env(redirect_uri: &str) -> Self {
+        let origin = std::env::var("WEBAUTHN_ORIGIN")
+            .unwrap_or_else(|_| origin_of(redirect_uri))
+            .trim_end_matches('/')
+            .to_string();
+        let id = std::env::var("WEBAUTHN_RP_ID").unwrap_or_else(|_| host_of(&origin));
+        let name = std::env::var("WEBAUTHN_RP_NAME").unwrap_or_else(|_| "Media Server".to_string());
+        Self { id, origin, name }
+    }
+}
+
+/// `scheme://host[:port]` of a URL.
+fn origin_of(url: &str) -> String {
+    match url.find("://") {
+        Some(i) => {
+            let rest = &url[i + 3..];
+            let end = rest.find('/').unwrap_or(rest.len());
+            format!("{}{}", &url[..i + 3], &rest[..end])
+        }
+        None => url.to_string(),
+    }
+}
+
+/// Host of an origin, without port.
+fn host_of(origin: &str) -> String {
+    let host = origin.split("://").nth(1).unwrap_or(origin);
+    host.split(':').next().unwrap_or(host).to_string()
+}
+
+/// A random challenge, base64url-encoded.
+pub fn generate_challenge() -> String {
+    let bytes: [u8; 32] = rand::thread_rng().gen();
+    BASE64URL_NOPAD.encode(&bytes)
+}
+
+pub fn encode(bytes: &[u8]) -> String {
+    BASE64URL_NOPAD.encode(bytes)
+}
+
+pub fn decode(value: &str) -> Result<Vec<u8>, String> {
+    BASE64URL_NOPAD
+        .decode(value.trim_end_matches('=').as_bytes())
+        .map_err(|_| "Invalid base64url encoding".to_string())
+}
+
+/// A credential created by `navigator.credentials.create()`.
+#[derive(Debug)]
+pub struct RegisteredCredential {
+    pub credential_id: Vec<u8>,
+    /// COSE_Key bytes, as stored
+    pub public_key: Vec<u8>,
+    pub sign_count: u32,
+}
+
+#[derive(Deserialize)]
+struct ClientData {
+    #[serde(rename = "type")]
+    kind: String,
+    challenge: String,
+    origin: String,
+}
+
+fn check_client_data(
+    rp: &RelyingParty,
+    client_data_json: &[u8],
+    kind: &str,
+    challenge: &str,
+) -> Result<(), String> {
+    let client_data: ClientData =
+        serde_json::from_slice(client_data_json).map_err(|_| "Invalid client data".to_string())?;
+    if client_data.kind != kind {
+        return Err(format!("Unexpected client data type: {}", client_data.kind));
+    }
+    if client_data.challenge.trim_end_matches('=') != challenge {
+        return Err("Challenge mismatch".to_string());
+    }
+    if client_data.origin != rp.origin {
+        return Err(format!("Unexpected origin: {}", client_data.origin));
+    }
+    Ok(())
+}
+
+struct AuthenticatorData<'a> {
+    flags: u8,
+    sign_count: u32,
+    /// Attested credential data and extensions, if any
+    rest: &'a [u8],
+}
+
+fn parse_authenticator_data<'a>(
+    rp: &RelyingParty,
+    data: &'a [u8],
+) -> Result<AuthenticatorData<'a>, String> {
+    if data.len() < 37 {
+        return Err("Authenticator data too short".to_string());
+    }
+    if data[..32] != Sha256::digest(rp.id.as_bytes())[..] {
+        return Err("Credential belongs to a different site".to_string());
+    }
+    let flags = data[32];
+    if flags & FLAG_USER_PRESENT == 0 {
+        return Err("User presence is required".to_string());
+    }
+    if flags & FLAG_USER_VERIFIED == 0 {
+        return Err("User verification is required".to_string());
+    }
+    Ok(AuthenticatorData {
+        flags,
+        sign_count: u32::from_be_bytes([data[33], data[34], data[35], data[36]]),
+        rest: &data[37..],
+    })
+}
+
+fn map_get(map: &[(Value, Value)], key: i64) -> Option<&Value> {
+    map.iter()
+        .find(|(k, _)| k.as_integer().map(i128::from) == Some(key as i128))
+        .map(|(_, v)| v)
+}
+
+/// A credential public key we can verify signatures with.
+enum PublicKey {
+    Es256(p256::ecdsa::VerifyingKey),
+    Rs256(rsa::pkcs1v15::VerifyingKey<Sha256>),
+}
+
+impl PublicKey {
+    /// Parse a COSE_Key (RFC 9053); only ES256 and RS256 are supported.
+    fn from_cose(cose_key: &[u8]) -> Result<Self, String> {
+        let key: Value = ciborium::de::from_reader(cose_key)
+            .map_err(|_| "Invalid credential key".to_string())?;
+        let map = key.as_map().ok_or("Invalid credential key")?;
+        let alg = map_get(map, 3)
+            .and_then(|v| v.as_integer())
+            .map(i128::from)
+            .ok_or("Credential key has no algorithm")?;
+
+        let bytes = |label: i64| -> Result<&Vec<u8>, String> {
+            map_get(map, label)
+                .and_then(|v| v.as_bytes())
+                .ok_or_else(|| "Incomplete credential key".to_string())
+        };
+        let int = |label: i64| {
+            map_get(map, label)
+                .and_then(|v| v.as_integer())
+                .map(i128::from)
+        };
+        let kty = int(1);
+
+        match alg {
+            a if a == ES256 as i128 => {
+                if kty != Some(KTY_EC2 as i128) || int(-1) != Some(CRV_P256 as i128) {
+                    return Err("ES256 credential key is not a P-256 EC2 key".to_string());
+                }
+                let (x, y) = (bytes(-2)?, bytes(-3)?);
+                let mut point = Vec::with_capacity(65);
+                point.push(0x04);
+                point.extend_from_slice(x);
+                point.extend_from_slice(y);
+                p256::ecdsa::VerifyingKey::from_sec1_bytes(&point)
+                    .map(PublicKey::Es256)
+                    .map_err(|_| "Invalid ES256 key".to_string())
+            }
+            a if a == RS256 as i128 => {
+                if kty != Some(KTY_RSA as i128) {
+                    return Err("RS256 credential key is not an RSA key".to_string());
+                }
+                let (n, e) = (bytes(-1)?, bytes(-2)?);
+                let n = rsa::BigUint::from_bytes_be(n);
+                if n.bits() < MIN_RSA_BITS {
+                    return Err(format!(
+                        "RS256 credential key is shorter than {} bits",
+                        MIN_RSA_BITS
+                    ));
+                }
+                rsa::RsaPublicKey::new(n, rsa::BigUint::from_bytes_be(e))
+                    .map(|key| PublicKey::Rs256(rsa::pkcs1v15::VerifyingKey::new(key)))
+                    .map_err(|_| "Invalid RS256 key".to_string())
+            }
+            other => Err(format!("Unsupported credential algorithm {}", other)),
+        }
+    }
+
+    fn verify(&self, message: &[u8], signature: &[u8]) -> Result<(), String> {
+        let valid = match self {
+            PublicKey::Es256(key) => p256::ecdsa::Signature::from_der(signature)
+                .map(|sig| key.verify(message, &sig).is_ok()),
+            PublicKey::Rs256(key) => rsa::pkcs1v15::Signature::try_from(signature)
+                .map(|sig| key.verify(message, &sig).is_ok()),
+        }
+        .map_err(|_| "Invalid signature encoding".to_string())?;
+        if valid {
+            Ok(())
+        } else {
+            Err("Invalid signature".to_string())
+        }
+    }
+}
+
+/// Verify the response of `navigator.credentials.create()`.
+pub fn verify_registration(
+    rp: &RelyingParty,
+    challenge: &str,
+    client_data_json: &[u8],
+    attestation_object: &[u8],
+) -> Result<RegisteredCredential, String> {
+    check_client_data(rp, client_data_json, "webauthn.create", challenge)?;
+
+    let attestation: Value = ciborium::de::from_reader(attestation_object)
+        .map_err(|_| "Invalid attestation object".to_string())?;
+    let auth_data = attestation
+        .as_map()
+        .and_then(|m| {
+            m.iter()
+                .find(|(k, _)| k.as_text() == Some("authData"))
+                .and_then(|(_, v)| v.as_bytes())
+        })
+        .ok_or("Attestation object has no authenticator data")?;
+
+    let data = parse_authenticator_data(rp, auth_data)?;
+    if data.flags & FLAG_ATTESTED_CREDENTIAL == 0 {
+        return Err("No credential in authenticator data".to_string());
+    }
+
+    // AAGUID (16) | credential id length (2) | credential id | COSE_Key | extensions
+    let rest = data.rest;
+    if rest.len() < 18 {
+        return Err("Attested credential data too short".to_string());
+    }
+    let id_len = u16::from_be_bytes([rest[16], rest[17]]) as usize;
+    let rest = &rest[18..];
+    if rest.len() < id_len {
+        return Err("Attested credential data too short".to_string());
+    }
+    let (credential_id, rest) = rest.split_at(id_len);
+
+    // The key is a single CBOR item; whatever follows is extension data
+    let mut reader = rest;
+    let _: Value =
+        ciborium::de::from_reader(&mut reader).map_err(|_| "Invalid credential key".to_string())?;
+    let public_key = rest[..rest.len() - reader.len()].to_vec();
+
+    // Reject keys we won't be able to verify later
+    PublicKey::from_cose(&public_key)?;
+
+    Ok(RegisteredCredential {
+        credential_id: credential_id.to_vec(),
+        public_key,
+        sign_count: data.sign_count,
+    })
+}
+
+/// Verify the response of `navigator.credentials.get()`. Returns the new
+/// signature counter.
+pub fn verify_authentication(
+    rp: &RelyingParty,
+    challenge: &str,
+    public_key: &[u8],
+    stored_sign_count: u32,
+    client_data_json: &[u8],
+    authenticator_data: &[u8],
+    signature: &[u8],
+) -> Result<u32, String> {
+    check_client_data(rp, client_data_json, "webauthn.get", challenge)?;
+    let data = parse_authenticator_data(rp, authenticator_data)?;
+
+    let mut message = authenticator_data.to_vec();
+    message.extend_from_slice(&Sha256::digest(client_data_json));
+    PublicKey::from_cose(public_key)?.verify(&message, signature)?;
+
+    // A counter that doesn't move forward hints at a cloned authenticator;
+    // synced passkeys always report 0.
+    if (data.sign_count != 0 || stored_sign_count != 0) && data.sign_count <= stored_sign_count {
+        return Err("Signature counter did not increase".to_string());
+    }
+    Ok(data.sign_count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use p256::ecdsa::{signature::Signer, SigningKey};
+
+    fn rp() -> RelyingParty {
+        RelyingParty {
+            id: "media.example.com".to_string(),
+            origin: "https://media.example.com".to_string(),
+            name: "Media Server".to_string(),
+        }
+    }
+
+    fn signing_key() -> SigningKey {
+        SigningKey::from_slice(&[7u8; 32]).unwrap()
+    }
+
+    fn cose_key(key: &SigningKey) -> Vec<u8> {
+        let point = key.verifying_key().to_encoded_point(false);
+        let value = Value::Map(vec![
+            (Value::from(1), Value::from(2)),
+            (Value::from(3), Value::from(ES256)),
+            (Value::from(-1), Value::from(1)),
+            (Value::from(-2), Value::Bytes(point.x().unwrap().to_vec())),
+            (Value::from(-3), Value::Bytes(point.y().unwrap().to_vec())),
+        ]);
+        let mut out = Vec::new();
+        ciborium::ser::into_writer(&value, &mut out).unwrap();
+        out
+    }
+
+    fn client_data(kind: &str, challenge: &str, origin: &str) -> Vec<u8> {
+        serde_json::to_vec(&serde_json::json!({
+            "type": kind,
+            "challenge": challenge,
+            "origin": origin,
+        }))
+        .unwrap()
+    }
+
+    fn auth_data(rp_id: &str, flags: u8, sign_count: u32, attested: &[u8]) -> Vec<u8> {
+        let mut data = Sha256::digest(rp_id.as_bytes()).to_vec();
+        data.push(flags);
+        data.extend_from_slice(&sign_count.to_be_bytes());
+        data.extend_from_slice(attested);
+        data
+    }
+
+    fn attestation_object(key: &SigningKey, credential_id: &[u8]) -> Vec<u8> {
+        let mut attested = vec![0u8; 16];
+        attested.extend_from_slice(&(credential_id.len() as u16).to_be_bytes());
+        attested.extend_from_slice(credential_id);
+        attested.extend_from_slice(&cose_key(key));
+        let value = Value::Map(vec![
+            (Value::from("fmt"), Value::from("none")),
+            (Value::from("attStmt"), Value::Map(vec![])),
+            (
+                Value::from("authData"),
+                Value::Bytes(auth_data("media.example.com", 0x45, 0, &attested)),
+            ),
+        ]);
+        let mut out = Vec::new();
+        ciborium::ser::into_writer(&value, &mut out).unwrap();
+        out
+    }
+
+    fn assertion(
+        key: &SigningKey,
+        challenge: &str,
+        sign_count: u32,
+    ) -> (Vec<u8>, Vec<u8>, Vec<u8>) {
+        let client_data_json = client_data("webauthn.get", challenge, "https://media.example.com");
+        let authenticator_data = auth_data("media.example.com", 0x05, sign_count, &[]);
+        let mut message = authenticator_data.clone();
+        message.extend_from_slice(&Sha256::digest(&client_data_json));
+        let signature: p256::ecdsa::Signature = key.sign(&message);
+        (
+            client_data_json,
+            authenticator_data,
+            signature.to_der().as_bytes().to_vec(),
+        )
+    }
+
+    #[test]
+    fn test_relying_party_defaults() {
+        assert_eq!(
+            origin_of("https://media.example.com/oidc/callback"),
+            "https://media.example.com"
+        );
+        assert_eq!(host_of("http://localhost:3000"), "localhost");
+    }
+
+    #[test]
+    fn test_registration() {
+        let key = signing_key();
+        let challenge = generate_challenge();
+        let client_data_json =
+            client_data("webauthn.create", &challenge, "https://media.example.com");
+
+        let credential = verify_registration(
+            &rp(),
+            &challenge,
+            &client_data_json,
+            &attestation_object(&key, b"cred-1"),
+        )
+        .unwrap();
+        assert_eq!(credential.credential_id, b"cred-1");
+        assert_eq!(credential.public_key, cose_key(&key));
+        assert_eq!(credential.sign_count, 0);
+    }
+
+    #[test]
+    fn test_registration_rejects_wrong_challenge_and_origin() {
+        let key = signing_key();
+        let attestation = attestation_object(&key, b"cred-1");
+
+        let wrong_challenge = client_data("webauthn.create", "other", "https://media.example.com");
+        assert!(verify_registration(&rp(), "expected", &wrong_challenge, &attestation).is_err());
+
+        let wrong_origin = client_data("webauthn.create", "expected", "https://evil.example");
+        assert!(verify_registration(&rp(), "expected", &wrong_origin, &attestation).is_err());
+
+        let wrong_type = client_data("webauthn.get", "expected", "https://media.example.com");
+        assert!(verify_registration(&rp(), "expected", &wrong_type, &attestation).is_err());
+    }
+
+    #[test]
+    fn test_authentication() {
+        let key = signing_key();
+        let public_key = cose_key(&key);
+        let (client_data_json, authenticator_data, signature) = assertion(&key, "chal", 0);
+
+        let count = verify_authentication(
+            &rp(),
+            "chal",
+            &public_key,
+            0,
+            &client_data_json,
+            &authenticator_data,
+            &signature,
+        )
+        .unwrap();
+        assert_eq!(count, 0);
+
+        // Wrong challenge
+        assert!(verify_authentication(
+            &rp(),
+            "other",
+            &public_key,
+            0,
+            &client_data_json,
+            &authenticator_data,
+            &signature,
+        )
+        .is_err());
+
+        // Signature by another key
+        let other = SigningKey::from_slice(&[9u8; 32]).unwrap();
+        assert!(verify_authentication(
+            &rp(),
+            "chal",
+            &cose_key(&other),
+            0,
+            &client_data_json,
+            &authenticator_data,
+            &signature,
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_authentication_sign_count() {
+        let key = signing_key();
+        let public_key = cose_key(&key);
+
+        let (client_data_json, authenticator_data, signature) = assertion(&key, "chal", 5);
+        let verify = |stored| {
+            verify_authentication(
+                &rp(),
+                "chal",
+                &public_key,
+                stored,
+                &client_data_json,
+                &authenticator_data,
+                &signature,
+            )
+        };
+        assert_eq!(verify(4).unwrap(), 5);
+        assert!(verify(5).is_err());
+    }
+
+    #[test]
+    fn test_authentication_requires_user_verification() {
+        let key = signing_key();
+        let client_data_json = client_data("webauthn.get", "chal", "https://media.example.com");
+        let authenticator_data = auth_data("media.example.com", FLAG_USER_PRESENT, 0, &[]);
+        let mut message = authenticator_data.clone();
+        message.extend_from_slice(&Sha256::digest(&client_data_json));
+        let signature: p256::ecdsa::Signature = key.sign(&message);
+
+        let result = verify_authentication(
+            &rp(),
+            "chal",
+            &cose_key(&key),
+            0,
+            &client_data_json,
+            &authenticator_data,
+            signature.to_der().as_bytes(),
+        );
+        assert_eq!(result.unwrap_err(), "User verification is required");
+    }
+
+    #[test]
+    fn test_credential_key_types() {
+        let cbor = |entries: Vec<(i64, Value)>| {
+            let value = Value::Map(
+                entries
+                    .into_iter()
+                    .map(|(k, v)| (Value::from(k), v))
+                    .collect(),
+            );
+            let mut out = Vec::new();
+            ciborium::ser::into_writer(&value, &mut out).unwrap();
+            out
+        };
+        let point = signing_key().verifying_key().to_encoded_point(false);
+        let ec2 = |kty: i64, crv: i64| {
+            cbor(vec![
+                (1, Value::from(kty)),
+                (3, Value::from(ES256)),
+                (-1, Value::from(crv)),
+                (-2, Value::Bytes(point.x().unwrap().to_vec())),
+                (-3, Value::Bytes(point.y().unwrap().to_vec())),
+            ])
+        };
+        assert!(PublicKey::from_cose(&ec2(KTY_EC2, CRV_P256)).is_ok());
+        assert!(PublicKey::from_cose(&ec2(KTY_RSA, CRV_P256)).is_err());
+        assert!(PublicKey::from_cose(&ec2(KTY_EC2, 2)).is_err());
+
+        let rsa = |kty: i64, modulus_bytes: usize| {
+            cbor(vec![
+                (1, Value::from(kty)),
+                (3, Value::from(RS256)),
+                (-1, Value::Bytes(vec![0xff; modulus_bytes])),
+                (-2, Value::Bytes(vec![0x01, 0x00, 0x01])),
+            ])
+        };
+        assert!(PublicKey::from_cose(&rsa(KTY_RSA, 256)).is_ok());
+        assert!(PublicKey::from_cose(&rsa(KTY_EC2, 256)).is_err());
+        assert!(PublicKey::from_cose(&rsa(KTY_RSA, 128)).is_err());
+    }
+}
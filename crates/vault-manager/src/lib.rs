@@ -39,6 +39,8 @@ pub struct CreateVaultRequest {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UpdateVaultRequest {
     pub name: Option<String>,
+    /// Strip GPS and device EXIF from served image originals
+    pub strip_exif: Option<bool>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -47,6 +49,7 @@ pub struct VaultResponse {
     pub vault_code: Option<String>,
     pub name: String,
     pub is_default: bool,
+    pub strip_exif: bool,
     pub created_at: String,
     pub media_count: i64,
 }
@@ -79,6 +82,7 @@ pub struct VaultDisplay {
     pub vault_code: String,
     pub name: String,
     pub is_default: bool,
+    pub strip_exif: bool,
     pub created_at: String,
     pub created_at_human: String,
     pub media_count: i64,
@@ -222,6 +226,8 @@ pub async fn create_vault(
         vault_code: Some(vault_code),
         name: request.name,
         is_default,
+        // New vaults get the column default
+        strip_exif: true,
         created_at,
         media_count: 0,
     }))
@@ -275,6 +281,7 @@ pub async fn list_vaults(
             vault_code: Some(vault.vault_id),
             name: vault.vault_name,
             is_default: vault.is_default,
+            strip_exif: vault.strip_exif,
             created_at: vault.created_at,
             media_count,
         });
@@ -285,7 +292,7 @@ pub async fn list_vaults(
     }))
 }
 
-/// Update a vault's name
+/// Update a vault's name and privacy settings
 #[tracing::instrument(skip(session, state, request))]
 pub async fn update_vault(
     Path(vault_id): Path<String>,
@@ -339,6 +346,16 @@ pub async fn update_vault(
         info!("Updated vault {} name to '{}'", vault_id, name);
     }
 
+    if let Some(strip_exif) = request.strip_exif {
+        state
+            .repo
+            .set_vault_strip_exif(&vault_id, strip_exif)
+            .await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+        info!("Set vault {} EXIF stripping to {}", vault_id, strip_exif);
+    }
+
     Ok(StatusCode::OK)
 }
 
@@ -510,6 +527,7 @@ pub async fn list_vaults_page(
             vault_code: vault.vault_id,
             name: vault.vault_name,
             is_default: vault.is_default,
+            strip_exif: vault.strip_exif,
             created_at: vault.created_at.clone(),
             created_at_human: format_human_date(&vault.created_at),
             media_count,
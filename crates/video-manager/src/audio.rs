@@ -0,0 +1,519 @@
+//! Audio processing: loudness-normalized renditions and waveform previews
+//!
+//! Audio uploads are stored under `media/audio/{slug}/` as `original.{ext}`.
+//! [`process_audio`] adds the files the player needs next to it:
+//!
+//! - `audio.m4a` — AAC, plays everywhere
+//! - `audio.webm` — Opus, smaller at the same quality where supported
+//! - `waveform.json` — peak amplitudes for drawing a seekable waveform
+//! - `waveform.png` — the same peaks rendered as an image
+//!
+//! Both renditions go through ffmpeg's `loudnorm` filter (EBU R128) so that
+//! episodes recorded at different levels play back at the same loudness.
+
+use crate::ffmpeg::{extract_audio_metadata, AudioMetadata, FFmpegConfig};
+use anyhow::{Context, Result};
+use image::{Rgba, RgbaImage};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use tokio::io::AsyncReadExt;
+use tokio::process::Command;
+use tracing::{info, warn};
+
+/// AAC rendition file name
+pub const AAC_RENDITION: &str = "audio.m4a";
+
+/// Opus rendition file name
+pub const OPUS_RENDITION: &str = "audio.webm";
+
+/// Waveform peaks file name
+pub const WAVEFORM_JSON: &str = "waveform.json";
+
+/// Rendered waveform file name
+pub const WAVEFORM_PNG: &str = "waveform.png";
+
+/// Sample rate the waveform is computed at; plenty for peak detection
+const WAVEFORM_SAMPLE_RATE: u32 = 8000;
+
+/// Number of peaks stored in `waveform.json`
+pub const WAVEFORM_PEAKS: usize = 1000;
+
+/// Content type of a file that may be served from an audio item's directory.
+/// Anything else (the original, temp files) is not served by name.
+pub fn served_content_type(file_name: &str) -> Option<&'static str> {
+    match file_name {
+        AAC_RENDITION => Some("audio/mp4"),
+        OPUS_RENDITION => Some("audio/webm"),
+        WAVEFORM_JSON => Some("application/json"),
+        WAVEFORM_PNG => Some("image/png"),
+        _ => None,
+    }
+}
+
+// ── Loudness normalization ──────────────────────────────────────────
+
+/// Loudness target for the `loudnorm` filter
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct LoudnessTarget {
+    /// Integrated loudness in LUFS
+    pub integrated: f64,
+    /// Maximum true peak in dBTP
+    pub true_peak: f64,
+    /// Loudness range in LU
+    pub range: f64,
+}
+
+impl Default for LoudnessTarget {
+    /// -16 LUFS / -1.5 dBTP, the usual target for podcasts and spoken word
+    fn default() -> Self {
+        Self {
+            integrated: -16.0,
+            true_peak: -1.5,
+            range: 11.0,
+        }
+    }
+}
+
+impl LoudnessTarget {
+    /// ffmpeg audio filter applying this target
+    pub fn filter(&self) -> String {
+        format!(
+            "loudnorm=I={}:TP={}:LRA={}",
+            self.integrated, self.true_peak, self.range
+        )
+    }
+}
+
+// ── Renditions ──────────────────────────────────────────────────────
+
+/// A normalized audio rendition produced from the original
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AudioRendition {
+    pub file_name: &'static str,
+    /// ffmpeg encoder name
+    pub encoder: &'static str,
+    /// Bitrate in kbps
+    pub bitrate: u32,
+    /// Output sample rate in Hz (`loudnorm` upsamples internally)
+    pub sample_rate: u32,
+}
+
+/// Renditions produced for every audio upload, most compatible first
+pub const AUDIO_RENDITIONS: [AudioRendition; 2] = [
+    AudioRendition {
+        file_name: AAC_RENDITION,
+        encoder: "aac",
+        bitrate: 128,
+        sample_rate: 44100,
+    },
+    AudioRendition {
+        file_name: OPUS_RENDITION,
+        encoder: "libopus",
+        bitrate: 96,
+        sample_rate: 48000,
+    },
+];
+
+/// ffmpeg arguments for encoding one rendition
+pub fn rendition_args(
+    rendition: &AudioRendition,
+    input: &Path,
+    output: &Path,
+    target: &LoudnessTarget,
+) -> Vec<String> {
+    let mut args = vec![
+        "-hide_banner".to_string(),
+        "-v".to_string(),
+        "error".to_string(),
+        "-i".to_string(),
+        input.to_string_lossy().to_string(),
+        // Drop cover art and any other non-audio streams
+        "-map".to_string(),
+        "0:a:0".to_string(),
+        "-af".to_string(),
+        target.filter(),
+        "-c:a".to_string(),
+        rendition.encoder.to_string(),
+        "-b:a".to_string(),
+        format!("{}k", rendition.bitrate),
+        "-ar".to_string(),
+        rendition.sample_rate.to_string(),
+    ];
+    if rendition.file_name.ends_with(".m4a") {
+        // Put the index up front so playback can start before the download ends
+        args.push("-movflags".to_string());
+        args.push("+faststart".to_string());
+    }
+    args.push("-y".to_string());
+    args.push(output.to_string_lossy().to_string());
+    args
+}
+
+/// Encode every rendition of `input` into `output_dir`
+pub async fn transcode_renditions(
+    config: &FFmpegConfig,
+    input: &Path,
+    output_dir: &Path,
+    target: &LoudnessTarget,
+) -> Result<Vec<PathBuf>> {
+    let mut outputs = Vec::with_capacity(AUDIO_RENDITIONS.len());
+
+    for rendition in &AUDIO_RENDITIONS {
+        let output = output_dir.join(rendition.file_name);
+        let result = Command::new(&config.ffmpeg_path)
+            .args(rendition_args(rendition, input, &output, target))
+            .stdout(Stdio::null())
+            .output()
+            .await
+            .context("Failed to execute ffmpeg")?;
+
+        if !result.status.success() {
+            let _ = tokio::fs::remove_file(&output).await;
+            anyhow::bail!(
+                "Encoding {} failed: {}",
+                rendition.file_name,
+                String::from_utf8_lossy(&result.stderr).trim()
+            );
+        }
+
+        info!("Encoded audio rendition {:?}", output);
+        outputs.push(output);
+    }
+
+    Ok(outputs)
+}
+
+// ── Waveform ────────────────────────────────────────────────────────
+
+/// Peak amplitudes of an audio file, normalized to `0.0..=1.0`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Waveform {
+    /// Duration of the decoded audio in seconds
+    pub duration: f64,
+    pub peaks: Vec<f32>,
+}
+
+/// Collects the absolute peak of fixed-size blocks of 16-bit PCM samples
+/// while the decoder output streams in, so long recordings never have to be
+/// held in memory.
+#[derive(Debug)]
+pub struct PeakAccumulator {
+    block_size: usize,
+    in_block: usize,
+    current: u16,
+    /// Odd trailing byte of the previous chunk
+    carry: Option<u8>,
+    samples: u64,
+    peaks: Vec<u16>,
+}
+
+impl PeakAccumulator {
+    pub fn new(block_size: usize) -> Self {
+        Self {
+            block_size: block_size.max(1),
+            in_block: 0,
+            current: 0,
+            carry: None,
+            samples: 0,
+            peaks: Vec::new(),
+        }
+    }
+
+    /// Feed signed 16-bit little-endian samples; chunks may split a sample
+    pub fn push_bytes(&mut self, mut bytes: &[u8]) {
+        if let Some(low) = self.carry.take() {
+            let Some((&high, rest)) = bytes.split_first() else {
+                self.carry = Some(low);
+                return;
+            };
+            self.push_sample(i16::from_le_bytes([low, high]));
+            bytes = rest;
+        }
+
+        let mut pairs = bytes.chunks_exact(2);
+        for pair in &mut pairs {
+            self.push_sample(i16::from_le_bytes([pair[0], pair[1]]));
+        }
+        self.carry = pairs.remainder().first().copied();
+    }
+
+    fn push_sample(&mut self, sample: i16) {
+        self.current = self.current.max(sample.unsigned_abs());
+        self.in_block += 1;
+        self.samples += 1;
+        if self.in_block == self.block_size {
+            self.peaks.push(self.current);
+            self.current = 0;
+            self.in_block = 0;
+        }
+    }
+
+    /// Number of samples seen so far
+    pub fn samples(&self) -> u64 {
+        self.samples
+    }
+
+    /// Block peaks, including a final partial block
+    pub fn finish(mut self) -> Vec<u16> {
+        if self.in_block > 0 {
+            self.peaks.push(self.current);
+        }
+        self.peaks
+    }
+}
+
+/// Reduce block peaks to `count` normalized peaks (max of each span).
+/// Shorter inputs are returned as-is rather than stretched.
+pub fn downsample_peaks(peaks: &[u16], count: usize) -> Vec<f32> {
+    if peaks.is_empty() || count == 0 {
+        return Vec::new();
+    }
+
+    let scale = i16::MAX as f32;
+    let normalize = |p: u16| (p as f32 / scale).min(1.0);
+    if peaks.len() <= count {
+        return peaks.iter().copied().map(normalize).collect();
+    }
+
+    (0..count)
+        .map(|i| {
+            let start = i * peaks.len() / count;
+            let end = ((i + 1) * peaks.len() / count).max(start + 1);
+            normalize(peaks[start..end].iter().copied().max().unwrap_or(0))
+        })
+        .collect()
+}
+
+/// Decode `input` to mono PCM with ffmpeg and compute its waveform
+pub async fn extract_waveform(
+    config: &FFmpegConfig,
+    input: &Path,
+    count: usize,
+) -> Result<Waveform> {
+    let mut child = Command::new(&config.ffmpeg_path)
+        .args(["-hide_banner", "-v", "error", "-i"])
+        .arg(input)
+        .args(["-map", "0:a:0", "-ac", "1", "-ar"])
+        .arg(WAVEFORM_SAMPLE_RATE.to_string())
+        .args(["-f", "s16le", "-acodec", "pcm_s16le", "pipe:1"])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .context("Failed to execute ffmpeg")?;
+
+    let mut stdout = child
+        .stdout
+        .take()
+        .context("ffmpeg stdout was not captured")?;
+
+    // 10ms blocks keep enough detail for any reasonable display width
+    let mut accumulator = PeakAccumulator::new((WAVEFORM_SAMPLE_RATE / 100) as usize);
+    let mut buf = vec![0u8; 64 * 1024];
+    loop {
+        let n = stdout
+            .read(&mut buf)
+            .await
+            .context("Failed to read decoded audio")?;
+        if n == 0 {
+            break;
+        }
+        accumulator.push_bytes(&buf[..n]);
+    }
+
+    let status = child.wait().await.context("Failed to wait for ffmpeg")?;
+    if !status.success() {
+        anyhow::bail!("ffmpeg failed to decode {:?}", input);
+    }
+
+    let duration = accumulator.samples() as f64 / WAVEFORM_SAMPLE_RATE as f64;
+    Ok(Waveform {
+        duration,
+        peaks: downsample_peaks(&accumulator.finish(), count),
+    })
+}
+
+/// Render peaks as vertically centred bars on a transparent background
+pub fn render_waveform(peaks: &[f32], width: u32, height: u32, color: Rgba<u8>) -> RgbaImage {
+    let mut img = RgbaImage::new(width, height);
+    if peaks.is_empty() || width == 0 || height == 0 {
+        return img;
+    }
+
+    let mid = height as f32 / 2.0;
+    for x in 0..width {
+        let index = (x as usize * peaks.len() / width as usize).min(peaks.len() - 1);
+        // Always draw at least a 1px centre line so silence is visible
+        let half = (peaks[index].clamp(0.0, 1.0) * mid).max(0.5);
+        let top = (mid - half).floor().max(0.0) as u32;
+        let bottom = ((mid + half).ceil() as u32).min(height);
+        for y in top..bottom {
+            img.put_pixel(x, y, color);
+        }
+    }
+    img
+}
+
+/// Compute the waveform of `input` and write `waveform.json` and
+/// `waveform.png` into `output_dir`
+pub async fn generate_waveform(
+    config: &FFmpegConfig,
+    input: &Path,
+    output_dir: &Path,
+) -> Result<Waveform> {
+    let waveform = extract_waveform(config, input, WAVEFORM_PEAKS).await?;
+
+    let json = serde_json::to_vec(&waveform).context("Failed to serialize waveform")?;
+    tokio::fs::write(output_dir.join(WAVEFORM_JSON), json)
+        .await
+        .context("Failed to write waveform JSON")?;
+
+    let img = render_waveform(&waveform.peaks, 1200, 200, Rgba([59, 130, 246, 255]));
+    let png_path = output_dir.join(WAVEFORM_PNG);
+    tokio::task::spawn_blocking(move || img.save_with_format(&png_path, image::ImageFormat::Png))
+        .await
+        .context("Waveform render task panicked")?
+        .context("Failed to write waveform PNG")?;
+
+    Ok(waveform)
+}
+
+/// Render a waveform thumbnail (WebP) for media listings
+pub fn waveform_thumbnail(peaks: &[f32]) -> Result<Vec<u8>> {
+    let (width, height) = (400, 225);
+    let mut thumb = RgbaImage::from_pixel(width, height, Rgba([30, 41, 59, 255]));
+    let bars = render_waveform(peaks, width - 40, height - 80, Rgba([96, 165, 250, 255]));
+    image::imageops::overlay(&mut thumb, &bars, 20, 40);
+
+    let mut webp = Vec::new();
+    let encoder = image::codecs::webp::WebPEncoder::new_lossless(&mut webp);
+    thumb
+        .write_with_encoder(encoder)
+        .context("Failed to encode WebP thumbnail")?;
+    Ok(webp)
+}
+
+// ── Pipeline ────────────────────────────────────────────────────────
+
+/// Result of processing an audio upload
+#[derive(Debug, Clone)]
+pub struct ProcessedAudio {
+    pub metadata: AudioMetadata,
+    /// `None` if the waveform could not be generated; playback still works
+    pub waveform: Option<Waveform>,
+}
+
+/// Probe the original, encode the normalized renditions and generate the
+/// waveform, writing everything into `output_dir`
+pub async fn process_audio(
+    config: &FFmpegConfig,
+    input: &Path,
+    output_dir: &Path,
+    target: &LoudnessTarget,
+) -> Result<ProcessedAudio> {
+    let metadata = extract_audio_metadata(config, input).await?;
+
+    transcode_renditions(config, input, output_dir, target).await?;
+
+    let waveform = match generate_waveform(config, input, output_dir).await {
+        Ok(waveform) => Some(waveform),
+        Err(e) => {
+            warn!("Waveform generation failed for {:?}: {:#}", input, e);
+            None
+        }
+    };
+
+    Ok(ProcessedAudio { metadata, waveform })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_loudness_filter() {
+        assert_eq!(
+            LoudnessTarget::default().filter(),
+            "loudnorm=I=-16:TP=-1.5:LRA=11"
+        );
+    }
+
+    #[test]
+    fn test_rendition_args() {
+        let target = LoudnessTarget::default();
+        let aac = rendition_args(
+            &AUDIO_RENDITIONS[0],
+            Path::new("/in/original.mp3"),
+            Path::new("/out/audio.m4a"),
+            &target,
+        );
+        assert!(aac.windows(2).any(|w| w[0] == "-af" && w[1] == target.filter()));
+        assert!(aac.windows(2).any(|w| w[0] == "-c:a" && w[1] == "aac"));
+        assert!(aac.contains(&"+faststart".to_string()));
+        assert_eq!(aac.last().unwrap(), "/out/audio.m4a");
+
+        let opus = rendition_args(
+            &AUDIO_RENDITIONS[1],
+            Path::new("/in/original.mp3"),
+            Path::new("/out/audio.webm"),
+            &target,
+        );
+        assert!(opus.windows(2).any(|w| w[0] == "-c:a" && w[1] == "libopus"));
+        assert!(!opus.contains(&"+faststart".to_string()));
+    }
+
+    #[test]
+    fn test_served_content_type() {
+        assert_eq!(served_content_type("audio.m4a"), Some("audio/mp4"));
+        assert_eq!(served_content_type("waveform.json"), Some("application/json"));
+        assert_eq!(served_content_type("original.mp3"), None);
+        assert_eq!(served_content_type("../audio.m4a"), None);
+    }
+
+    #[test]
+    fn test_peak_accumulator_handles_split_samples() {
+        let samples: [i16; 5] = [100, -3000, 20, 7, -32768];
+        let bytes: Vec<u8> = samples.iter().flat_map(|s| s.to_le_bytes()).collect();
+
+        let mut acc = PeakAccumulator::new(2);
+        // Split in the middle of the second sample
+        acc.push_bytes(&bytes[..3]);
+        acc.push_bytes(&bytes[3..]);
+        assert_eq!(acc.samples(), 5);
+        assert_eq!(acc.finish(), vec![3000, 20, 32768]);
+    }
+
+    #[test]
+    fn test_downsample_peaks() {
+        let peaks = [0u16, 32767, 100, 200, 16384, 0];
+        let down = downsample_peaks(&peaks, 3);
+        assert_eq!(down.len(), 3);
+        assert_eq!(down[0], 1.0);
+        assert!((down[1] - 200.0 / 32767.0).abs() < 1e-6);
+        assert!((down[2] - 0.5).abs() < 0.001);
+
+        // Never stretched, never above 1.0
+        assert_eq!(downsample_peaks(&[32768], 10), vec![1.0]);
+        assert!(downsample_peaks(&[], 10).is_empty());
+    }
+
+    #[test]
+    fn test_render_waveform() {
+        let color = Rgba([255, 0, 0, 255]);
+        let img = render_waveform(&[0.0, 1.0], 4, 10, color);
+        assert_eq!(img.dimensions(), (4, 10));
+        // Silent half: only the centre line
+        assert_eq!(*img.get_pixel(0, 5), color);
+        assert_eq!(img.get_pixel(0, 0)[3], 0);
+        // Loud half: full height
+        assert_eq!(*img.get_pixel(3, 0), color);
+        assert_eq!(*img.get_pixel(3, 9), color);
+    }
+
+    #[test]
+    fn test_waveform_thumbnail_is_webp() {
+        let webp = waveform_thumbnail(&[0.2, 0.8, 0.5]).unwrap();
+        assert_eq!(&webp[0..4], b"RIFF");
+        assert_eq!(&webp[8..12], b"WEBP");
+    }
+}
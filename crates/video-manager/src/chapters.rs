@@ -0,0 +1,340 @@
+//! Video chapters
+//!
+//! Chapters are stored per video (`media_chapters`) and published in two
+//! forms:
+//!
+//! ```text
+//! {video_dir}/chapters.vtt          WebVTT chapters track (<track kind="chapters">)
+//! {video_dir}/{variant}/index.m3u8  #EXT-X-DATERANGE tags with CLASS="chapter"
+//! ```
+//!
+//! [`package_chapters`] rewrites both. Like subtitle packaging it runs after
+//! each chapter change and after (re)transcoding, since transcoding writes
+//! the media playlists from scratch.
+
+use crate::hls::{rewrite_dateranges, DateRange};
+use crate::subtitles::{parse_media_playlist, render_vtt, Cue};
+use anyhow::{Context, Result};
+use db::media::{NewChapter, VideoChapter};
+use std::path::{Path, PathBuf};
+use tokio::fs;
+use tracing::info;
+
+/// File name of the WebVTT chapters track in the video directory
+pub const CHAPTERS_FILE: &str = "chapters.vtt";
+
+/// `CLASS` of the chapter date ranges in HLS playlists
+pub const CHAPTER_CLASS: &str = "chapter";
+
+/// Upper bound on the number of chapters per video
+pub const MAX_CHAPTERS: usize = 500;
+
+/// Upper bound on a chapter title, in characters
+pub const MAX_TITLE_LENGTH: usize = 200;
+
+/// Validate chapters submitted by the editor and sort them by start time
+///
+/// Titles are trimmed and flattened to one line. With a known `duration`,
+/// chapters must start inside the video and explicit ends are clamped to it.
+pub fn normalize_chapters(
+    chapters: Vec<NewChapter>,
+    duration: Option<f64>,
+) -> Result<Vec<NewChapter>> {
+    if chapters.len() > MAX_CHAPTERS {
+        anyhow::bail!("A video can have at most {} chapters", MAX_CHAPTERS);
+    }
+
+    let mut normalized = Vec::with_capacity(chapters.len());
+    for chapter in chapters {
+        let title: String = chapter
+            .title
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .replace("-->", "->")
+            .chars()
+            .take(MAX_TITLE_LENGTH)
+            .collect();
+        if title.is_empty() {
+            anyhow::bail!("Every chapter needs a title");
+        }
+
+        let start = chapter.start_time;
+        if !start.is_finite() || start < 0.0 {
+            anyhow::bail!("Chapter \"{}\" has an invalid start time", title);
+        }
+        if duration.is_some_and(|d| start >= d) {
+            anyhow::bail!("Chapter \"{}\" starts after the end of the video", title);
+        }
+
+        let end = match chapter.end_time {
+            Some(end) if !end.is_finite() || end <= start => {
+                anyhow::bail!("Chapter \"{}\" must end after it starts", title)
+            }
+            Some(end) => Some(duration.map_or(end, |d| end.min(d))),
+            None => None,
+        };
+
+        normalized.push(NewChapter {
+            start_time: start,
+            end_time: end,
+            title,
+        });
+    }
+
+    normalized.sort_by(|a, b| a.start_time.total_cmp(&b.start_time));
+    if let Some(pair) = normalized
+        .windows(2)
+        .find(|pair| pair[0].start_time == pair[1].start_time)
+    {
+        anyhow::bail!(
+            "Chapters \"{}\" and \"{}\" start at the same time",
+            pair[0].title,
+            pair[1].title
+        );
+    }
+
+    Ok(normalized)
+}
+
+/// Resolve chapters into cues: a chapter without an explicit end runs until
+/// the next chapter starts, the last one until the end of the video
+pub fn chapter_cues(chapters: &[VideoChapter], duration: f64) -> Vec<Cue> {
+    chapters
+        .iter()
+        .enumerate()
+        .filter_map(|(i, chapter)| {
+            let next_start = chapters.get(i + 1).map_or(duration, |next| next.start_time);
+            let end = chapter.end_time.unwrap_or(next_start).min(duration);
+            (end > chapter.start_time).then(|| Cue {
+                start: chapter.start_time,
+                end,
+                settings: String::new(),
+                text: vec![chapter.title.clone()],
+            })
+        })
+        .collect()
+}
+
+/// Chapters as HLS date ranges (`ID="chapter-N"`, `X-TITLE`)
+pub fn chapter_dateranges(cues: &[Cue]) -> Vec<DateRange> {
+    cues.iter()
+        .enumerate()
+        .map(|(i, cue)| DateRange {
+            id: format!("chapter-{}", i + 1),
+            start: cue.start,
+            duration: cue.end - cue.start,
+            attributes: vec![("X-TITLE".to_string(), cue.text.join(" "))],
+        })
+        .collect()
+}
+
+/// Chapters overlapping the clip `start..end`, shifted to the clip's timeline
+pub fn chapters_for_clip(
+    chapters: &[VideoChapter],
+    duration: f64,
+    start: f64,
+    end: f64,
+) -> Vec<NewChapter> {
+    chapter_cues(chapters, duration)
+        .into_iter()
+        .filter(|cue| cue.start < end && cue.end > start)
+        .map(|cue| NewChapter {
+            start_time: (cue.start - start).max(0.0),
+            end_time: Some(cue.end.min(end) - start),
+            title: cue.text.join(" "),
+        })
+        .collect()
+}
+
+/// Path of the WebVTT chapters track
+pub fn chapters_path(video_dir: &Path) -> PathBuf {
+    video_dir.join(CHAPTERS_FILE)
+}
+
+/// Media playlists referenced by `master.m3u8`
+fn variant_playlists(master: &str) -> Vec<&str> {
+    master
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && !l.starts_with('#'))
+        .collect()
+}
+
+/// Duration of a transcoded video: the segment durations of its first
+/// variant. `None` for videos without an HLS rendition.
+pub async fn hls_duration(video_dir: &Path) -> Option<f64> {
+    let master = fs::read_to_string(video_dir.join("master.m3u8"))
+        .await
+        .ok()?;
+    let variant = variant_playlists(&master).into_iter().next()?;
+    let playlist = fs::read_to_string(video_dir.join(variant)).await.ok()?;
+    let (segments, _) = parse_media_playlist(&playlist);
+    (!segments.is_empty()).then(|| segments.iter().sum())
+}
+
+/// Write the chapters track and the chapter date ranges of every variant.
+///
+/// Without chapters the track and the date ranges are removed. Videos
+/// without a master playlist (MP4, or HLS still transcoding) only get the
+/// WebVTT track.
+pub async fn package_chapters(
+    video_dir: &Path,
+    chapters: &[VideoChapter],
+    duration: f64,
+) -> Result<()> {
+    let cues = chapter_cues(chapters, duration);
+
+    let track = chapters_path(video_dir);
+    if cues.is_empty() {
+        if track.exists() {
+            fs::remove_file(&track)
+                .await
+                .context("Failed to remove chapters track")?;
+        }
+    } else {
+        fs::write(&track, render_vtt(&cues, None))
+            .await
+            .context("Failed to write chapters track")?;
+    }
+
+    let Ok(master) = fs::read_to_string(video_dir.join("master.m3u8")).await else {
+        return Ok(());
+    };
+
+    let ranges = chapter_dateranges(&cues);
+    for variant in variant_playlists(&master) {
+        let path = video_dir.join(variant);
+        let playlist = fs::read_to_string(&path)
+            .await
+            .with_context(|| format!("Failed to read playlist {:?}", path))?;
+        let rewritten = rewrite_dateranges(&playlist, CHAPTER_CLASS, &ranges)?;
+        fs::write(&path, rewritten)
+            .await
+            .with_context(|| format!("Failed to update playlist {:?}", path))?;
+    }
+
+    info!("Packaged {} chapter(s) into {:?}", cues.len(), video_dir);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chapter(start: f64, end: Option<f64>, title: &str) -> VideoChapter {
+        VideoChapter {
+            id: 1,
+            media_id: 1,
+            start_time: start,
+            end_time: end,
+            title: title.to_string(),
+        }
+    }
+
+    fn new_chapter(start: f64, end: Option<f64>, title: &str) -> NewChapter {
+        NewChapter {
+            start_time: start,
+            end_time: end,
+            title: title.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_normalize_chapters() {
+        let chapters = normalize_chapters(
+            vec![
+                new_chapter(60.0, Some(500.0), "  Part\n two "),
+                new_chapter(0.0, None, "Intro"),
+            ],
+            Some(120.0),
+        )
+        .unwrap();
+        assert_eq!(chapters[0], new_chapter(0.0, None, "Intro"));
+        assert_eq!(chapters[1], new_chapter(60.0, Some(120.0), "Part two"));
+
+        assert!(normalize_chapters(vec![new_chapter(0.0, None, " ")], None).is_err());
+        assert!(normalize_chapters(vec![new_chapter(-1.0, None, "A")], None).is_err());
+        assert!(normalize_chapters(vec![new_chapter(130.0, None, "A")], Some(120.0)).is_err());
+        assert!(normalize_chapters(vec![new_chapter(5.0, Some(5.0), "A")], None).is_err());
+        assert!(normalize_chapters(
+            vec![new_chapter(5.0, None, "A"), new_chapter(5.0, None, "B")],
+            None
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_chapter_cues() {
+        let chapters = [
+            chapter(0.0, None, "Intro"),
+            chapter(30.0, Some(45.0), "Demo"),
+            chapter(90.0, None, "Q&A"),
+        ];
+        let cues = chapter_cues(&chapters, 120.0);
+        let spans: Vec<(f64, f64)> = cues.iter().map(|c| (c.start, c.end)).collect();
+        assert_eq!(spans, vec![(0.0, 30.0), (30.0, 45.0), (90.0, 120.0)]);
+
+        let vtt = render_vtt(&cues, None);
+        assert!(vtt.contains("00:00:30.000 --> 00:00:45.000\nDemo\n"));
+
+        let ranges = chapter_dateranges(&cues);
+        assert_eq!(ranges[2].id, "chapter-3");
+        assert_eq!(ranges[2].duration, 30.0);
+        assert_eq!(ranges[2].attributes[0].1, "Q&A");
+    }
+
+    #[test]
+    fn test_chapters_for_clip() {
+        let chapters = [
+            chapter(0.0, None, "Intro"),
+            chapter(60.0, None, "Workshop"),
+            chapter(240.0, None, "Wrap-up"),
+        ];
+        let clip = chapters_for_clip(&chapters, 300.0, 50.0, 230.0);
+        assert_eq!(
+            clip,
+            vec![
+                new_chapter(0.0, Some(10.0), "Intro"),
+                new_chapter(10.0, Some(180.0), "Workshop"),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_package_chapters() {
+        let dir = tempfile::tempdir().unwrap();
+        let video_dir = dir.path();
+        fs::create_dir_all(video_dir.join("720p")).await.unwrap();
+        fs::write(
+            video_dir.join("master.m3u8"),
+            "#EXTM3U\n#EXT-X-VERSION:3\n#EXT-X-STREAM-INF:BANDWIDTH=1,RESOLUTION=2x2\n720p/index.m3u8\n",
+        )
+        .await
+        .unwrap();
+        let playlist = "#EXTM3U\n#EXT-X-TARGETDURATION:4\n#EXTINF:4.000,\nsegment_000.ts\n#EXTINF:4.000,\nsegment_001.ts\n#EXT-X-ENDLIST\n";
+        fs::write(video_dir.join("720p/index.m3u8"), playlist)
+            .await
+            .unwrap();
+
+        assert_eq!(hls_duration(video_dir).await, Some(8.0));
+
+        let chapters = [chapter(0.0, None, "One"), chapter(4.0, None, "Two")];
+        package_chapters(video_dir, &chapters, 8.0).await.unwrap();
+
+        let vtt = fs::read_to_string(chapters_path(video_dir)).await.unwrap();
+        assert!(vtt.contains("00:00:04.000 --> 00:00:08.000\nTwo\n"));
+        let variant = fs::read_to_string(video_dir.join("720p/index.m3u8"))
+            .await
+            .unwrap();
+        assert_eq!(variant.matches("CLASS=\"chapter\"").count(), 2);
+        assert!(variant.contains("START-DATE=\"1970-01-01T00:00:04Z\""));
+
+        package_chapters(video_dir, &[], 8.0).await.unwrap();
+        assert!(!chapters_path(video_dir).exists());
+        let variant = fs::read_to_string(video_dir.join("720p/index.m3u8"))
+            .await
+            .unwrap();
+        assert_eq!(variant, playlist);
+    }
+}
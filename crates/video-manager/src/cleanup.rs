@@ -8,8 +8,11 @@
 //! - Manual cleanup operations
 //! - Cleanup tracking and logging
 //! - Safe cleanup (errors during cleanup don't panic)
+//! - Scheduled purge of trashed media and workspace files
 
+use std::future::Future;
 use std::path::{Path, PathBuf};
+use std::time::Duration;
 use tokio::fs;
 use tracing::{debug, error, info, warn};
 
@@ -306,6 +309,56 @@ pub async fn cleanup_old_temp_files(
     Ok(cleaned_count)
 }
 
+/// Days trashed items are kept when `TRASH_RETENTION_DAYS` is not set
+pub const DEFAULT_TRASH_RETENTION_DAYS: u64 = 30;
+
+/// How often the scheduled trash purge runs
+pub const TRASH_PURGE_INTERVAL_SECS: u64 = 3600;
+
+/// Days trashed media items and workspace files are kept before they are
+/// purged (`TRASH_RETENTION_DAYS`)
+pub fn trash_retention_days() -> u64 {
+    std::env::var("TRASH_RETENTION_DAYS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_TRASH_RETENTION_DAYS)
+}
+
+/// Delete a trashed file or directory
+pub async fn cleanup_trash_entry(path: &Path) -> Result<(), std::io::Error> {
+    if path.is_dir() {
+        cleanup_directory(path).await
+    } else {
+        cleanup_file(path).await
+    }
+}
+
+/// Spawn a scheduled trash purge
+///
+/// Every [`TRASH_PURGE_INTERVAL_SECS`], starting right away, `purge` is
+/// called with the retention period in days and returns the number of items
+/// it removed. `name` labels the trash in the logs.
+pub fn spawn_trash_purge<F, Fut>(name: &'static str, purge: F)
+where
+    F: Fn(u64) -> Fut + Send + 'static,
+    Fut: Future<Output = usize> + Send + 'static,
+{
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(TRASH_PURGE_INTERVAL_SECS));
+        loop {
+            interval.tick().await;
+            let retention_days = trash_retention_days();
+            let purged = purge(retention_days).await;
+            if purged > 0 {
+                info!(
+                    "Cleanup: Purged {} item(s) older than {} days from the {} trash",
+                    purged, retention_days, name
+                );
+            }
+        }
+    });
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -357,6 +410,23 @@ mod tests {
         cleanup_directory(&temp_dir).await.unwrap();
     }
 
+    #[tokio::test]
+    async fn test_cleanup_trash_entry() {
+        let trash_dir = std::env::temp_dir().join("test_cleanup_trash");
+        let folder = trash_dir.join("20260101-folder");
+        let file = trash_dir.join("20260101-notes.md");
+        fs::create_dir_all(&folder).await.unwrap();
+        File::create(folder.join("a.txt")).await.unwrap();
+        File::create(&file).await.unwrap();
+
+        cleanup_trash_entry(&folder).await.unwrap();
+        cleanup_trash_entry(&file).await.unwrap();
+        assert!(!folder.exists());
+        assert!(!file.exists());
+
+        cleanup_directory(&trash_dir).await.unwrap();
+    }
+
     #[tokio::test]
     async fn test_cleanup_manager() {
         let temp_file1 = std::env::temp_dir().join("test_manager_1.tmp");
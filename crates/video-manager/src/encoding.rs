@@ -0,0 +1,332 @@
+//! Encoding profiles for HLS transcoding
+//!
+//! A profile describes the rendition ladder and encoder settings used by
+//! [`transcode_to_hls`](crate::hls::transcode_to_hls). Without any
+//! configuration the built-in [`QUALITY_PRESETS`] ladder is used.
+//!
+//! A vault can override this with an `encoding.yaml` file in its storage root
+//! (`storage/vaults/{vault_id}/encoding.yaml`). Media-server workspace folders
+//! each own a vault, so this also gives per-folder profiles:
+//!
+//! ```yaml
+//! name: course
+//! rate_control:
+//!   mode: crf
+//!   crf: 23
+//! align_keyframes: true
+//! segment_duration: 4
+//! renditions:
+//!   - { name: 720p, width: 1280, height: 720, video_bitrate: 2500 }
+//!   - { name: 480p, width: 854, height: 480, video_bitrate: 1200, audio_bitrate: 96 }
+//! audio_only:
+//!   name: audio
+//!   bitrate: 64
+//! loudness:
+//!   integrated: -16
+//! ```
+//!
+//! Every profile gets an audio-only rendition for low-bandwidth viewers unless
+//! it sets `audio_only: null`. Loudness normalisation is off unless `loudness`
+//! is set (see [`crate::loudness`]).
+
+use crate::ffmpeg::VideoMetadata;
+use crate::hls::{QualityPreset, ALTERNATE_AUDIO_PREFIX, QUALITY_PRESETS};
+use crate::loudness::LoudnessTarget;
+use anyhow::{Context, Result};
+use common::storage::UserStorageManager;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::Path;
+use tracing::info;
+
+/// File name of the per-vault profile
+pub const ENCODING_PROFILE_FILE: &str = "encoding.yaml";
+
+/// How the video bitrate is controlled
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "lowercase")]
+pub enum RateControl {
+    /// Constant bitrate: `video_bitrate` is the target, `max_bitrate`/`buffer_size` cap it
+    #[default]
+    Cbr,
+    /// Constant rate factor: quality-targeted, `max_bitrate`/`buffer_size` still cap peaks
+    Crf { crf: u8 },
+}
+
+/// Audio-only rendition advertised in the master playlist
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AudioOnlyRendition {
+    /// Output directory name
+    #[serde(default = "default_audio_name")]
+    pub name: String,
+    /// AAC bitrate in kbps
+    #[serde(default = "default_audio_bitrate")]
+    pub bitrate: u32,
+}
+
+impl AudioOnlyRendition {
+    /// Bandwidth in bits per second for the master playlist
+    pub fn bandwidth(&self) -> u32 {
+        self.bitrate * 1000
+    }
+}
+
+impl Default for AudioOnlyRendition {
+    fn default() -> Self {
+        Self {
+            name: default_audio_name(),
+            bitrate: default_audio_bitrate(),
+        }
+    }
+}
+
+fn default_audio_name() -> String {
+    "audio".to_string()
+}
+
+fn default_audio_bitrate() -> u32 {
+    64
+}
+
+fn default_encoder_preset() -> String {
+    "medium".to_string()
+}
+
+/// Rendition ladder and encoder settings for HLS transcoding
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EncodingProfile {
+    /// Profile name, used in logs
+    #[serde(default = "default_profile_name")]
+    pub name: String,
+    /// Video renditions, highest quality first
+    #[serde(default = "default_renditions")]
+    pub renditions: Vec<QualityPreset>,
+    #[serde(default)]
+    pub rate_control: RateControl,
+    /// Audio-only rendition for low-bandwidth viewers (`null` disables it)
+    #[serde(default = "default_audio_only")]
+    pub audio_only: Option<AudioOnlyRendition>,
+    /// Force keyframes on segment boundaries so every rendition switches cleanly
+    #[serde(default)]
+    pub align_keyframes: bool,
+    /// x264 speed/quality preset (`ultrafast` … `veryslow`)
+    #[serde(default = "default_encoder_preset")]
+    pub encoder_preset: String,
+    /// Overrides [`HlsConfig::segment_duration`](crate::hls::HlsConfig) when set
+    #[serde(default)]
+    pub segment_duration: Option<u32>,
+    /// Normalise every audio stream to these loudness targets
+    #[serde(default)]
+    pub loudness: Option<LoudnessTarget>,
+}
+
+fn default_profile_name() -> String {
+    "default".to_string()
+}
+
+fn default_renditions() -> Vec<QualityPreset> {
+    QUALITY_PRESETS.to_vec()
+}
+
+fn default_audio_only() -> Option<AudioOnlyRendition> {
+    Some(AudioOnlyRendition::default())
+}
+
+impl Default for EncodingProfile {
+    fn default() -> Self {
+        Self {
+            name: default_profile_name(),
+            renditions: default_renditions(),
+            rate_control: RateControl::default(),
+            audio_only: default_audio_only(),
+            align_keyframes: false,
+            encoder_preset: default_encoder_preset(),
+            segment_duration: None,
+            loudness: None,
+        }
+    }
+}
+
+impl EncodingProfile {
+    /// Parse and validate a profile from YAML
+    pub fn from_yaml(content: &str) -> Result<Self> {
+        let profile: Self =
+            serde_yaml::from_str(content).context("Failed to parse encoding profile")?;
+        profile.validate()?;
+        Ok(profile)
+    }
+
+    /// Load a profile file, falling back to the default when it does not exist
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read encoding profile {:?}", path))?;
+        let profile = Self::from_yaml(&content)
+            .with_context(|| format!("Invalid encoding profile {:?}", path))?;
+
+        info!("Loaded encoding profile '{}' from {:?}", profile.name, path);
+        Ok(profile)
+    }
+
+    /// Load the profile configured for a vault
+    pub fn load_for_vault(user_storage: &UserStorageManager, vault_id: &str) -> Result<Self> {
+        Self::load(
+            &user_storage
+                .vault_storage_root(vault_id)
+                .join(ENCODING_PROFILE_FILE),
+        )
+    }
+
+    /// Check the profile for settings ffmpeg or HLS players would reject
+    pub fn validate(&self) -> Result<()> {
+        if self.renditions.is_empty() {
+            anyhow::bail!("Encoding profile '{}' has no renditions", self.name);
+        }
+
+        let mut names = HashSet::new();
+        let audio_name = self.audio_only.as_ref().map(|a| a.name.as_str());
+        for name in self
+            .renditions
+            .iter()
+            .map(|r| r.name.as_ref())
+            .chain(audio_name)
+        {
+            if name.is_empty()
+                || !name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+            {
+                anyhow::bail!("Invalid rendition name '{}'", name);
+            }
+            if !names.insert(name) {
+                anyhow::bail!("Duplicate rendition name '{}'", name);
+            }
+            if name.starts_with(ALTERNATE_AUDIO_PREFIX) {
+                anyhow::bail!("Rendition name '{}' is reserved for alternate audio", name);
+            }
+        }
+
+        for r in &self.renditions {
+            if r.width == 0 || r.height == 0 || r.width % 2 != 0 || r.height % 2 != 0 {
+                anyhow::bail!(
+                    "Rendition '{}' must have non-zero, even dimensions (got {}x{})",
+                    r.name,
+                    r.width,
+                    r.height
+                );
+            }
+            if r.video_bitrate == 0 {
+                anyhow::bail!("Rendition '{}' has no video bitrate", r.name);
+            }
+        }
+
+        if let RateControl::Crf { crf } = self.rate_control {
+            if crf > 51 {
+                anyhow::bail!("CRF must be between 0 and 51 (got {})", crf);
+            }
+        }
+
+        if self.segment_duration == Some(0) {
+            anyhow::bail!("Segment duration must be at least 1 second");
+        }
+
+        if let Some(loudness) = &self.loudness {
+            loudness.validate()?;
+        }
+
+        Ok(())
+    }
+
+    /// Renditions that do not exceed the source resolution
+    pub fn renditions_for_source(&self, metadata: &VideoMetadata) -> Vec<&QualityPreset> {
+        self.renditions
+            .iter()
+            .filter(|r| r.width <= metadata.width && r.height <= metadata.height)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_profile_uses_builtin_ladder() {
+        let profile = EncodingProfile::default();
+        assert_eq!(profile.renditions.len(), QUALITY_PRESETS.len());
+        assert_eq!(profile.rate_control, RateControl::Cbr);
+        assert_eq!(profile.audio_only.as_ref().unwrap().name, "audio");
+        assert!(profile.loudness.is_none());
+        assert!(profile.validate().is_ok());
+    }
+
+    #[test]
+    fn test_parse_custom_profile() {
+        let yaml = r#"
+name: course
+rate_control:
+  mode: crf
+  crf: 23
+align_keyframes: true
+renditions:
+  - { name: 720p, width: 1280, height: 720, video_bitrate: 2500 }
+  - { name: 480p, width: 854, height: 480, video_bitrate: 1200, audio_bitrate: 96 }
+audio_only:
+  bitrate: 48
+loudness:
+  integrated: -16
+"#;
+        let profile = EncodingProfile::from_yaml(yaml).unwrap();
+        assert_eq!(profile.name, "course");
+        assert_eq!(profile.rate_control, RateControl::Crf { crf: 23 });
+        assert!(profile.align_keyframes);
+        assert_eq!(profile.renditions.len(), 2);
+        assert_eq!(profile.renditions[0].maxrate(), 2500);
+        assert_eq!(profile.renditions[0].bufsize(), 5000);
+        assert_eq!(profile.renditions[1].audio_bitrate, 96);
+        let audio = profile.audio_only.unwrap();
+        assert_eq!(audio.name, "audio");
+        assert_eq!(audio.bandwidth(), 48000);
+        let loudness = profile.loudness.unwrap();
+        assert_eq!(loudness.integrated, -16.0);
+        assert_eq!(loudness.true_peak, -1.0);
+    }
+
+    #[test]
+    fn test_audio_only_can_be_disabled() {
+        let profile = EncodingProfile::from_yaml("name: video-only\naudio_only: null").unwrap();
+        assert!(profile.audio_only.is_none());
+    }
+
+    #[test]
+    fn test_invalid_profiles_rejected() {
+        assert!(EncodingProfile::from_yaml("renditions: []").is_err());
+        assert!(EncodingProfile::from_yaml(
+            "renditions:\n  - { name: ../x, width: 640, height: 360, video_bitrate: 800 }"
+        )
+        .is_err());
+        assert!(EncodingProfile::from_yaml(
+            "renditions:\n  - { name: a, width: 641, height: 360, video_bitrate: 800 }"
+        )
+        .is_err());
+        assert!(EncodingProfile::from_yaml(
+            "renditions:\n  - { name: a, width: 640, height: 360, video_bitrate: 800 }\n  - { name: a, width: 320, height: 180, video_bitrate: 300 }"
+        )
+        .is_err());
+        assert!(EncodingProfile::from_yaml("rate_control: { mode: crf, crf: 60 }").is_err());
+        assert!(EncodingProfile::from_yaml(
+            "renditions:\n  - { name: audio_0, width: 640, height: 360, video_bitrate: 800 }"
+        )
+        .is_err());
+        assert!(EncodingProfile::from_yaml("loudness: { integrated: 3 }").is_err());
+    }
+
+    #[test]
+    fn test_missing_file_falls_back_to_default() {
+        let profile = EncodingProfile::load(Path::new("/nonexistent/encoding.yaml")).unwrap();
+        assert_eq!(profile.name, "default");
+    }
+}
@@ -3,6 +3,7 @@
 //! This module provides utilities for:
 //! - Video metadata extraction using FFprobe
 //! - Thumbnail and poster generation
+//! - Cutting clips out of a video
 //! - Video validation and integrity checks
 //! - Command execution with error handling
 
@@ -94,6 +95,26 @@ pub struct VideoMetadata {
     pub file_size: u64,
     /// Format name (e.g., "mp4", "mov")
     pub format: String,
+    /// Audio streams in source order
+    #[serde(default)]
+    pub audio_streams: Vec<AudioStreamInfo>,
+}
+
+/// One audio stream of a video, as reported by FFprobe
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AudioStreamInfo {
+    /// Position among the audio streams (`-map 0:a:{index}`)
+    pub index: usize,
+    /// Language tag from the container (`eng`, `de`), if set
+    pub language: Option<String>,
+    /// Stream title from the container, if set
+    pub title: Option<String>,
+    /// Audio codec (e.g., "aac", "opus")
+    pub codec: Option<String>,
+    /// Number of channels
+    pub channels: Option<u32>,
+    /// Marked as the default track
+    pub is_default: bool,
 }
 
 /// FFprobe JSON output structures
@@ -123,15 +144,29 @@ struct FFprobeStream {
     height: Option<u32>,
     r_frame_rate: Option<String>,
     avg_frame_rate: Option<String>,
-    #[allow(dead_code)]
     bit_rate: Option<String>,
+    channels: Option<u32>,
+    sample_rate: Option<String>,
+    #[serde(default)]
+    tags: FFprobeTags,
+    #[serde(default)]
+    disposition: FFprobeDisposition,
 }
 
-/// Extract metadata from a video file using FFprobe
-pub async fn extract_metadata(config: &FFmpegConfig, video_path: &Path) -> Result<VideoMetadata> {
-    info!("Extracting metadata from: {:?}", video_path);
+#[derive(Debug, Default, Deserialize)]
+struct FFprobeTags {
+    language: Option<String>,
+    title: Option<String>,
+}
 
-    // Run ffprobe with JSON output
+#[derive(Debug, Default, Deserialize)]
+struct FFprobeDisposition {
+    #[serde(default)]
+    default: u8,
+}
+
+/// Run FFprobe and parse its JSON description of the file's format and streams
+async fn probe(config: &FFmpegConfig, path: &Path) -> Result<FFprobeOutput> {
     let output = Command::new(&config.ffprobe_path)
         .args([
             "-v",
@@ -141,7 +176,7 @@ pub async fn extract_metadata(config: &FFmpegConfig, video_path: &Path) -> Resul
             "-show_format",
             "-show_streams",
         ])
-        .arg(video_path)
+        .arg(path)
         .output()
         .await
         .context("Failed to execute ffprobe")?;
@@ -153,8 +188,36 @@ pub async fn extract_metadata(config: &FFmpegConfig, video_path: &Path) -> Resul
 
     // Parse JSON output
     let stdout = String::from_utf8_lossy(&output.stdout);
-    let probe_data: FFprobeOutput =
-        serde_json::from_str(&stdout).context("Failed to parse FFprobe JSON output")?;
+    serde_json::from_str(&stdout).context("Failed to parse FFprobe JSON output")
+}
+
+/// Describe the audio streams among FFprobe's streams
+fn audio_streams(streams: &[FFprobeStream]) -> Vec<AudioStreamInfo> {
+    streams
+        .iter()
+        .filter(|s| s.codec_type == "audio")
+        .enumerate()
+        .map(|(index, s)| AudioStreamInfo {
+            index,
+            // "und" is ffmpeg's placeholder for an unset language
+            language: s
+                .tags
+                .language
+                .clone()
+                .filter(|l| !l.is_empty() && l != "und"),
+            title: s.tags.title.clone().filter(|t| !t.trim().is_empty()),
+            codec: s.codec_name.clone(),
+            channels: s.channels,
+            is_default: s.disposition.default == 1,
+        })
+        .collect()
+}
+
+/// Extract metadata from a video file using FFprobe
+pub async fn extract_metadata(config: &FFmpegConfig, video_path: &Path) -> Result<VideoMetadata> {
+    info!("Extracting metadata from: {:?}", video_path);
+
+    let probe_data = probe(config, video_path).await?;
 
     // Extract video stream info
     let video_stream = probe_data
@@ -205,6 +268,7 @@ pub async fn extract_metadata(config: &FFmpegConfig, video_path: &Path) -> Resul
         .clone()
         .unwrap_or_else(|| "unknown".to_string());
     let audio_codec = audio_stream.and_then(|s| s.codec_name.clone());
+    let audio_streams = audio_streams(&probe_data.streams);
 
     // Parse bitrate
     let bitrate = probe_data
@@ -229,6 +293,7 @@ pub async fn extract_metadata(config: &FFmpegConfig, video_path: &Path) -> Resul
         bitrate,
         file_size,
         format,
+        audio_streams,
     };
 
     info!(
@@ -239,6 +304,91 @@ pub async fn extract_metadata(config: &FFmpegConfig, video_path: &Path) -> Resul
     Ok(metadata)
 }
 
+/// Audio metadata extracted from FFprobe
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AudioMetadata {
+    /// Duration in seconds
+    pub duration: f64,
+    /// Audio codec (e.g., "mp3", "aac", "flac")
+    pub codec: String,
+    /// Bitrate in bits per second
+    pub bitrate: Option<u64>,
+    /// Number of channels
+    pub channels: Option<u32>,
+    /// Sample rate in Hz
+    pub sample_rate: Option<u32>,
+    /// File size in bytes
+    pub file_size: u64,
+    /// Format name (e.g., "mp3", "flac")
+    pub format: String,
+}
+
+/// Extract metadata from an audio file using FFprobe
+///
+/// Uses the first audio stream; cover art in MP3/M4A files shows up as a
+/// video stream and is ignored.
+pub async fn extract_audio_metadata(
+    config: &FFmpegConfig,
+    audio_path: &Path,
+) -> Result<AudioMetadata> {
+    info!("Extracting audio metadata from: {:?}", audio_path);
+
+    let probe_data = probe(config, audio_path).await?;
+
+    let audio_stream = probe_data
+        .streams
+        .iter()
+        .find(|s| s.codec_type == "audio")
+        .ok_or_else(|| anyhow::anyhow!("No audio stream found"))?;
+
+    let duration = probe_data
+        .format
+        .duration
+        .as_ref()
+        .and_then(|d| d.parse::<f64>().ok())
+        .ok_or_else(|| anyhow::anyhow!("Could not parse audio duration"))?;
+
+    let file_size = probe_data
+        .format
+        .size
+        .as_ref()
+        .and_then(|s| s.parse::<u64>().ok())
+        .unwrap_or_else(|| std::fs::metadata(audio_path).map(|m| m.len()).unwrap_or(0));
+
+    // Prefer the stream bitrate; VBR files often only report it on the container
+    let bitrate = audio_stream
+        .bit_rate
+        .as_ref()
+        .or(probe_data.format.bit_rate.as_ref())
+        .and_then(|b| b.parse::<u64>().ok());
+
+    let metadata = AudioMetadata {
+        duration,
+        codec: audio_stream
+            .codec_name
+            .clone()
+            .unwrap_or_else(|| "unknown".to_string()),
+        bitrate,
+        channels: audio_stream.channels,
+        sample_rate: audio_stream
+            .sample_rate
+            .as_ref()
+            .and_then(|r| r.parse::<u32>().ok()),
+        file_size,
+        format: probe_data
+            .format
+            .format_name
+            .unwrap_or_else(|| "unknown".to_string()),
+    };
+
+    info!(
+        "Audio metadata extracted: {:.2}s, codec: {}, channels: {:?}, bitrate: {:?}",
+        metadata.duration, metadata.codec, metadata.channels, metadata.bitrate
+    );
+
+    Ok(metadata)
+}
+
 /// Parse frame rate from FFprobe format (e.g., "30000/1001")
 fn parse_frame_rate(rate_str: Option<&String>) -> Option<f64> {
     rate_str.and_then(|s| {
@@ -389,6 +539,68 @@ pub async fn generate_poster(
     Ok(())
 }
 
+/// Cut the span between `start` and `end` (seconds) out of a video
+///
+/// The clip is re-encoded (H.264/AAC, always written as MP4 whatever the
+/// output file's extension) so it starts exactly at `start`
+/// rather than at the preceding keyframe. Clips are transcoded again by the
+/// HLS pipeline, so a fast preset at high quality is used.
+pub async fn cut_clip(
+    config: &FFmpegConfig,
+    video_path: &Path,
+    output_path: &Path,
+    start: f64,
+    end: f64,
+) -> Result<()> {
+    if !(start >= 0.0 && end > start) {
+        anyhow::bail!("Invalid clip range {:.3}s - {:.3}s", start, end);
+    }
+    info!(
+        "Cutting clip {:.3}s - {:.3}s: {:?} -> {:?}",
+        start, end, video_path, output_path
+    );
+
+    if let Some(parent) = output_path.parent() {
+        tokio::fs::create_dir_all(parent)
+            .await
+            .context("Failed to create output directory")?;
+    }
+
+    // Seeking before -i is frame-accurate when re-encoding
+    let output = Command::new(&config.ffmpeg_path)
+        .args(["-v", "error", "-ss", &format!("{:.3}", start), "-i"])
+        .arg(video_path)
+        .args(["-t", &format!("{:.3}", end - start)])
+        .args(["-map", "0:v:0", "-map", "0:a:0?"])
+        .args(["-c:v", "libx264", "-preset", "veryfast", "-crf", "18"])
+        .args(["-c:a", "aac", "-b:a", "192k"])
+        .args(["-f", "mp4", "-movflags", "+faststart"])
+        .args(["-threads", &config.threads.to_string()])
+        .arg("-y")
+        .arg(output_path)
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .output()
+        .await
+        .context("Failed to execute ffmpeg for clip cutting")?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!(
+            "FFmpeg clip cutting failed with status: {}\nError: {}",
+            output.status,
+            stderr.lines().take(10).collect::<Vec<_>>().join("\n")
+        );
+    }
+
+    if !output_path.exists() {
+        anyhow::bail!("Clip file was not created: {:?}", output_path);
+    }
+
+    info!("Clip created successfully: {:?}", output_path);
+    Ok(())
+}
+
 /// Validate video file integrity
 ///
 /// Performs a quick check to ensure the video file is readable and not corrupted
@@ -458,6 +670,32 @@ mod tests {
         assert_eq!(parse_frame_rate(Some(&"invalid".to_string())), None);
     }
 
+    #[test]
+    fn test_audio_streams() {
+        let streams: Vec<FFprobeStream> = serde_json::from_str(
+            r#"[
+                {"codec_type": "video", "codec_name": "h264", "width": 1920, "height": 1080},
+                {"codec_type": "audio", "codec_name": "aac", "channels": 2,
+                 "tags": {"language": "eng", "title": "English"}, "disposition": {"default": 1}},
+                {"codec_type": "audio", "codec_name": "ac3", "channels": 6,
+                 "tags": {"language": "und"}, "disposition": {"default": 0}},
+                {"codec_type": "audio", "codec_name": "opus"}
+            ]"#,
+        )
+        .unwrap();
+        let audio = audio_streams(&streams);
+        assert_eq!(audio.len(), 3);
+        assert_eq!(audio[0].index, 0);
+        assert_eq!(audio[0].language.as_deref(), Some("eng"));
+        assert_eq!(audio[0].title.as_deref(), Some("English"));
+        assert!(audio[0].is_default);
+        assert_eq!(audio[1].language, None);
+        assert_eq!(audio[1].channels, Some(6));
+        assert!(!audio[1].is_default);
+        assert_eq!(audio[2].index, 2);
+        assert_eq!(audio[2].codec.as_deref(), Some("opus"));
+    }
+
     #[test]
     fn test_is_codec_supported() {
         assert!(is_codec_supported("h264"));
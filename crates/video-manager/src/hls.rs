@@ -6,16 +6,32 @@
 //! - Creating quality-specific playlists (index.m3u8)
 //! - Generating master playlists for adaptive bitrate streaming
 //! - Smart quality selection based on source resolution
+//! - Per-vault rendition ladders via [`EncodingProfile`]
+//! - Alternate audio renditions (`EXT-X-MEDIA:TYPE=AUDIO`) for sources with
+//!   several audio streams, and an audio-only variant
+//! - Optional loudness normalisation ([`crate::loudness`])
+//! - Timed metadata (`EXT-X-DATERANGE`) in media playlists
 
-use crate::ffmpeg::{FFmpegConfig, VideoMetadata};
+use crate::encoding::{AudioOnlyRendition, EncodingProfile, RateControl};
+use crate::ffmpeg::{AudioStreamInfo, FFmpegConfig, VideoMetadata};
+use crate::loudness::{loudnorm_filter, measure_loudness};
+use crate::subtitles::normalize_language;
 use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::fmt::Write as _;
 use std::path::Path;
 use std::process::Stdio;
 use tokio::fs;
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
 use tokio::process::Command;
 use tracing::{error, info, warn};
 
 /// HLS configuration
+///
+/// Server-wide defaults; an [`EncodingProfile`] may override the ladder and
+/// segment duration per vault.
 #[derive(Debug, Clone)]
 pub struct HlsConfig {
     /// Segment duration in seconds
@@ -37,29 +53,67 @@ impl Default for HlsConfig {
 }
 
 /// Quality preset for HLS transcoding
-#[derive(Debug, Clone)]
+///
+/// Also the rendition entry of an [`EncodingProfile`]; optional fields fall
+/// back to sensible values when omitted from YAML.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct QualityPreset {
     /// Quality name (e.g., "1080p", "720p")
-    pub name: &'static str,
+    pub name: Cow<'static, str>,
     /// Target width in pixels
     pub width: u32,
     /// Target height in pixels
     pub height: u32,
     /// Video bitrate in kbps
     pub video_bitrate: u32,
-    /// Max video bitrate in kbps
+    /// Max video bitrate in kbps (0 = same as `video_bitrate`)
+    #[serde(default)]
     pub max_bitrate: u32,
-    /// Buffer size in kbps
+    /// Buffer size in kbps (0 = twice the max bitrate)
+    #[serde(default)]
     pub buffer_size: u32,
     /// Audio bitrate in kbps
+    #[serde(default = "default_audio_bitrate")]
     pub audio_bitrate: u32,
     /// H.264 profile
-    pub profile: &'static str,
+    #[serde(default = "default_h264_profile")]
+    pub profile: Cow<'static, str>,
     /// H.264 level
-    pub level: &'static str,
+    #[serde(default = "default_h264_level")]
+    pub level: Cow<'static, str>,
+}
+
+fn default_audio_bitrate() -> u32 {
+    128
+}
+
+fn default_h264_profile() -> Cow<'static, str> {
+    Cow::Borrowed("high")
+}
+
+fn default_h264_level() -> Cow<'static, str> {
+    Cow::Borrowed("4.1")
 }
 
 impl QualityPreset {
+    /// Effective max bitrate in kbps
+    pub fn maxrate(&self) -> u32 {
+        if self.max_bitrate > 0 {
+            self.max_bitrate
+        } else {
+            self.video_bitrate
+        }
+    }
+
+    /// Effective VBV buffer size in kbps
+    pub fn bufsize(&self) -> u32 {
+        if self.buffer_size > 0 {
+            self.buffer_size
+        } else {
+            self.maxrate() * 2
+        }
+    }
+
     /// Get total bandwidth in bits per second for HLS playlist
     pub fn bandwidth(&self) -> u32 {
         (self.video_bitrate + self.audio_bitrate) * 1000
@@ -74,48 +128,48 @@ impl QualityPreset {
 /// Standard quality presets for HLS transcoding
 pub const QUALITY_PRESETS: &[QualityPreset] = &[
     QualityPreset {
-        name: "1080p",
+        name: Cow::Borrowed("1080p"),
         width: 1920,
         height: 1080,
         video_bitrate: 5000,
         max_bitrate: 5000,
         buffer_size: 10000,
         audio_bitrate: 128,
-        profile: "high",
-        level: "4.0",
+        profile: Cow::Borrowed("high"),
+        level: Cow::Borrowed("4.0"),
     },
     QualityPreset {
-        name: "720p",
+        name: Cow::Borrowed("720p"),
         width: 1280,
         height: 720,
         video_bitrate: 2800,
         max_bitrate: 2800,
         buffer_size: 5600,
         audio_bitrate: 128,
-        profile: "high",
-        level: "3.1",
+        profile: Cow::Borrowed("high"),
+        level: Cow::Borrowed("3.1"),
     },
     QualityPreset {
-        name: "480p",
+        name: Cow::Borrowed("480p"),
         width: 854,
         height: 480,
         video_bitrate: 1400,
         max_bitrate: 1400,
         buffer_size: 2800,
         audio_bitrate: 96,
-        profile: "main",
-        level: "3.0",
+        profile: Cow::Borrowed("main"),
+        level: Cow::Borrowed("3.0"),
     },
     QualityPreset {
-        name: "360p",
+        name: Cow::Borrowed("360p"),
         width: 640,
         height: 360,
         video_bitrate: 800,
         max_bitrate: 800,
         buffer_size: 1600,
         audio_bitrate: 96,
-        profile: "baseline",
-        level: "3.0",
+        profile: Cow::Borrowed("baseline"),
+        level: Cow::Borrowed("3.0"),
     },
 ];
 
@@ -136,15 +190,130 @@ pub fn select_qualities_for_source(metadata: &VideoMetadata) -> Vec<&'static Qua
         .collect()
 }
 
+/// Segment duration after applying the profile override
+fn segment_duration(hls_config: &HlsConfig, profile: &EncodingProfile) -> u32 {
+    profile
+        .segment_duration
+        .unwrap_or(hls_config.segment_duration)
+}
+
+/// FFmpeg arguments forcing a keyframe at every segment boundary
+///
+/// Without this, x264 places keyframes on scene cuts and each rendition's
+/// segments start at slightly different times, which makes players stall or
+/// glitch when switching quality.
+fn keyframe_alignment_args(segment_duration: u32, fps: f64) -> Vec<String> {
+    let gop = ((fps.max(1.0) * segment_duration as f64).round() as u32).max(1);
+    vec![
+        "-g".to_string(),
+        gop.to_string(),
+        "-keyint_min".to_string(),
+        gop.to_string(),
+        "-sc_threshold".to_string(),
+        "0".to_string(),
+        "-force_key_frames".to_string(),
+        format!("expr:gte(t,n_forced*{})", segment_duration),
+    ]
+}
+
+/// FFmpeg arguments for the profile's rate control mode
+fn rate_control_args(rate_control: &RateControl, preset: &QualityPreset) -> Vec<String> {
+    let mut args = match rate_control {
+        RateControl::Cbr => vec!["-b:v".to_string(), format!("{}k", preset.video_bitrate)],
+        RateControl::Crf { crf } => vec!["-crf".to_string(), crf.to_string()],
+    };
+    args.extend([
+        "-maxrate".to_string(),
+        format!("{}k", preset.maxrate()),
+        "-bufsize".to_string(),
+        format!("{}k", preset.bufsize()),
+    ]);
+    args
+}
+
+/// Rendition group referenced by video variants when audio is carried separately
+const AUDIO_GROUP_ID: &str = "aud";
+
+/// Directory name prefix of alternate audio renditions (`audio_0`, `audio_1`, …)
+pub const ALTERNATE_AUDIO_PREFIX: &str = "audio_";
+
+/// Which source audio stream to encode, and how
+#[derive(Debug, Clone, Default)]
+pub struct AudioInput {
+    /// Position among the source's audio streams; `None` lets ffmpeg choose
+    pub stream: Option<usize>,
+    /// `-af` filter chain, e.g. loudness normalisation
+    pub filter: Option<String>,
+}
+
+impl AudioInput {
+    /// FFmpeg arguments selecting and filtering the stream
+    fn args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        if let Some(stream) = self.stream {
+            args.extend(["-map".to_string(), format!("0:a:{}", stream)]);
+        }
+        if let Some(filter) = &self.filter {
+            args.extend(["-af".to_string(), filter.clone()]);
+        }
+        args
+    }
+}
+
+/// Alternate audio rendition advertised as `#EXT-X-MEDIA:TYPE=AUDIO`
+#[derive(Debug, Clone)]
+pub struct AudioTrackRendition {
+    /// Output directory name
+    pub name: String,
+    /// Language tag, if the source stream has one
+    pub language: Option<String>,
+    /// Name shown in the player's audio menu
+    pub label: String,
+    /// Selected when the viewer has no language preference
+    pub is_default: bool,
+    /// AAC bitrate in kbps
+    pub bitrate: u32,
+}
+
+impl AudioTrackRendition {
+    /// Describe the rendition for a source audio stream
+    pub fn for_stream(stream: &AudioStreamInfo, is_default: bool, bitrate: u32) -> Self {
+        let language = stream.language.as_deref().and_then(normalize_language);
+        let label = stream
+            .title
+            .clone()
+            .or_else(|| language.clone())
+            .unwrap_or_else(|| format!("Track {}", stream.index + 1));
+        Self {
+            name: format!("{}{}", ALTERNATE_AUDIO_PREFIX, stream.index),
+            language,
+            label,
+            is_default,
+            bitrate,
+        }
+    }
+}
+
+/// Index of the stream players should start with: the one flagged default, else the first
+fn default_audio_stream(streams: &[AudioStreamInfo]) -> usize {
+    streams.iter().position(|s| s.is_default).unwrap_or(0)
+}
+
 /// Transcode video to a specific quality variant
 ///
-/// Generates HLS segments and playlist for one quality level
+/// Generates HLS segments and playlist for one quality level. With
+/// `audio: None` the variant is video-only and audio comes from alternate
+/// renditions.
+#[allow(clippy::too_many_arguments)]
 pub async fn transcode_quality_variant(
     ffmpeg_config: &FFmpegConfig,
     hls_config: &HlsConfig,
+    profile: &EncodingProfile,
     input_path: &Path,
     output_dir: &Path,
     preset: &QualityPreset,
+    metadata: &VideoMetadata,
+    audio: Option<&AudioInput>,
 ) -> Result<()> {
     info!(
         "Transcoding to {} ({}x{}, {}k video, {}k audio)",
@@ -152,7 +321,7 @@ pub async fn transcode_quality_variant(
     );
 
     // Create quality directory
-    let quality_dir = output_dir.join(preset.name);
+    let quality_dir = output_dir.join(preset.name.as_ref());
     fs::create_dir_all(&quality_dir)
         .await
         .context("Failed to create quality directory")?;
@@ -161,15 +330,33 @@ pub async fn transcode_quality_variant(
     let playlist_path = quality_dir.join("index.m3u8");
     let segment_pattern = quality_dir.join("segment_%03d.ts");
 
+    let segment_duration = segment_duration(hls_config, profile);
+    let keyframe_args = if profile.align_keyframes {
+        keyframe_alignment_args(segment_duration, metadata.fps)
+    } else {
+        Vec::new()
+    };
+
+    let audio_args = match audio {
+        Some(audio) if audio.stream.is_some() => {
+            let mut args = vec!["-map".to_string(), "0:v:0".to_string()];
+            args.extend(audio.args());
+            args
+        }
+        Some(audio) => audio.args(),
+        None => vec!["-map".to_string(), "0:v:0".to_string(), "-an".to_string()],
+    };
+
     // Build FFmpeg command for HLS transcoding
     let output = Command::new(&ffmpeg_config.ffmpeg_path)
         .arg("-i")
         .arg(input_path)
+        .args(&audio_args)
         // Video encoding
         .args(["-c:v", "libx264"])
-        .args(["-preset", "medium"])
-        .args(["-profile:v", preset.profile])
-        .args(["-level", preset.level])
+        .args(["-preset", profile.encoder_preset.as_str()])
+        .args(["-profile:v", preset.profile.as_ref()])
+        .args(["-level", preset.level.as_ref()])
         .args([
             "-vf",
             &format!(
@@ -177,9 +364,8 @@ pub async fn transcode_quality_variant(
                 preset.width, preset.height, preset.width, preset.height
             ),
         ])
-        .args(["-b:v", &format!("{}k", preset.video_bitrate)])
-        .args(["-maxrate", &format!("{}k", preset.max_bitrate)])
-        .args(["-bufsize", &format!("{}k", preset.buffer_size)])
+        .args(rate_control_args(&profile.rate_control, preset))
+        .args(&keyframe_args)
         // Audio encoding
         .args(["-c:a", "aac"])
         .args(["-b:a", &format!("{}k", preset.audio_bitrate)])
@@ -187,7 +373,7 @@ pub async fn transcode_quality_variant(
         .args(["-ac", "2"])
         // HLS settings
         .args(["-f", "hls"])
-        .args(["-hls_time", &hls_config.segment_duration.to_string()])
+        .args(["-hls_time", &segment_duration.to_string()])
         .args(["-hls_playlist_type", "vod"])
         .args(["-hls_segment_type", "mpegts"])
         .args([
@@ -235,6 +421,66 @@ pub async fn transcode_quality_variant(
     Ok(())
 }
 
+/// Transcode an audio stream to an audio-only HLS rendition
+///
+/// Used for the audio-only variant as well as for alternate audio renditions.
+pub async fn transcode_audio_rendition(
+    ffmpeg_config: &FFmpegConfig,
+    segment_duration: u32,
+    input_path: &Path,
+    output_dir: &Path,
+    name: &str,
+    bitrate: u32,
+    audio: &AudioInput,
+) -> Result<()> {
+    info!("Transcoding audio rendition {} ({}k)", name, bitrate);
+
+    let rendition_dir = output_dir.join(name);
+    fs::create_dir_all(&rendition_dir)
+        .await
+        .context("Failed to create audio rendition directory")?;
+
+    let playlist_path = rendition_dir.join("index.m3u8");
+    let segment_pattern = rendition_dir.join("segment_%03d.ts");
+
+    let output = Command::new(&ffmpeg_config.ffmpeg_path)
+        .arg("-i")
+        .arg(input_path)
+        .arg("-vn")
+        .args(audio.args())
+        .args(["-c:a", "aac"])
+        .args(["-b:a", &format!("{}k", bitrate)])
+        .args(["-ar", "44100"])
+        .args(["-ac", "2"])
+        .args(["-f", "hls"])
+        .args(["-hls_time", &segment_duration.to_string()])
+        .args(["-hls_playlist_type", "vod"])
+        .args(["-hls_segment_type", "mpegts"])
+        .args([
+            "-hls_segment_filename",
+            segment_pattern.to_string_lossy().as_ref(),
+        ])
+        .arg("-y")
+        .arg(&playlist_path)
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .output()
+        .await
+        .context("Failed to execute FFmpeg for audio rendition")?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        error!("FFmpeg stderr for {}: {}", name, stderr);
+        anyhow::bail!(
+            "FFmpeg audio rendition failed with status: {}\nError: {}",
+            output.status,
+            stderr.lines().take(10).collect::<Vec<_>>().join("\n")
+        );
+    }
+
+    Ok(())
+}
+
 /// Count the number of segment files in a directory
 async fn count_segments(dir: &Path) -> Result<usize> {
     let mut count = 0;
@@ -256,29 +502,83 @@ async fn count_segments(dir: &Path) -> Result<usize> {
     Ok(count)
 }
 
-/// Generate master playlist for adaptive bitrate streaming
+/// Render the master playlist for adaptive bitrate streaming
 ///
-/// The master playlist references all quality variants and allows
-/// the HLS player to switch between them based on network conditions
-pub async fn generate_master_playlist(output_dir: &Path, presets: &[&QualityPreset]) -> Result<()> {
-    info!("Generating master playlist with {} variants", presets.len());
-
-    let master_path = output_dir.join("master.m3u8");
-
-    // Build playlist content
+/// With `audio_tracks`, video variants reference the alternate audio group
+/// instead of carrying audio themselves.
+pub fn render_master_playlist(
+    presets: &[&QualityPreset],
+    audio_tracks: &[AudioTrackRendition],
+    audio_only: Option<&AudioOnlyRendition>,
+) -> String {
     let mut content = String::from("#EXTM3U\n");
     content.push_str("#EXT-X-VERSION:3\n");
 
+    for track in audio_tracks {
+        let language = track
+            .language
+            .as_ref()
+            .map(|l| format!(",LANGUAGE=\"{}\"", l))
+            .unwrap_or_default();
+        let _ = writeln!(
+            content,
+            "#EXT-X-MEDIA:TYPE=AUDIO,GROUP-ID=\"{}\",NAME=\"{}\"{},DEFAULT={},AUTOSELECT=YES,URI=\"{}/index.m3u8\"",
+            AUDIO_GROUP_ID,
+            quote_attribute(&track.label),
+            language,
+            if track.is_default { "YES" } else { "NO" },
+            track.name
+        );
+    }
+    let audio_group = if audio_tracks.is_empty() {
+        String::new()
+    } else {
+        format!(",AUDIO=\"{}\"", AUDIO_GROUP_ID)
+    };
+
     for preset in presets {
         // Add stream info
-        content.push_str(&format!(
-            "#EXT-X-STREAM-INF:BANDWIDTH={},RESOLUTION={}\n",
+        let _ = writeln!(
+            content,
+            "#EXT-X-STREAM-INF:BANDWIDTH={},RESOLUTION={}{}",
             preset.bandwidth(),
-            preset.resolution()
-        ));
-        content.push_str(&format!("{}/index.m3u8\n", preset.name));
+            preset.resolution(),
+            audio_group
+        );
+        let _ = writeln!(content, "{}/index.m3u8", preset.name);
+    }
+
+    if let Some(audio) = audio_only {
+        let _ = writeln!(
+            content,
+            "#EXT-X-STREAM-INF:BANDWIDTH={},CODECS=\"mp4a.40.2\"",
+            audio.bandwidth()
+        );
+        let _ = writeln!(content, "{}/index.m3u8", audio.name);
     }
 
+    content
+}
+
+/// Generate master playlist for adaptive bitrate streaming
+///
+/// The master playlist references all quality variants and allows
+/// the HLS player to switch between them based on network conditions
+pub async fn generate_master_playlist(
+    output_dir: &Path,
+    presets: &[&QualityPreset],
+    audio_tracks: &[AudioTrackRendition],
+    audio_only: Option<&AudioOnlyRendition>,
+) -> Result<()> {
+    info!(
+        "Generating master playlist with {} variants and {} audio renditions",
+        presets.len(),
+        audio_tracks.len()
+    );
+
+    let master_path = output_dir.join("master.m3u8");
+    let content = render_master_playlist(presets, audio_tracks, audio_only);
+
     // Write master playlist
     fs::write(&master_path, content)
         .await
@@ -288,32 +588,66 @@ pub async fn generate_master_playlist(output_dir: &Path, presets: &[&QualityPres
     Ok(())
 }
 
+/// Measure each audio stream and build its normalisation filter
+///
+/// Streams that cannot be measured, or are silent, are left untouched.
+async fn loudness_filters(
+    ffmpeg_config: &FFmpegConfig,
+    profile: &EncodingProfile,
+    input_path: &Path,
+    streams: &[AudioStreamInfo],
+) -> Vec<Option<String>> {
+    let Some(target) = &profile.loudness else {
+        return vec![None; streams.len()];
+    };
+
+    let mut filters = Vec::with_capacity(streams.len());
+    for stream in streams {
+        let filter = match measure_loudness(ffmpeg_config, input_path, stream.index, target).await {
+            Ok(measured) => measured.map(|m| loudnorm_filter(target, &m)),
+            Err(e) => {
+                warn!(
+                    "Loudness measurement of audio stream {} failed, leaving it as is: {}",
+                    stream.index, e
+                );
+                None
+            }
+        };
+        filters.push(filter);
+    }
+    filters
+}
+
 /// Transcode video to HLS with multiple quality variants
 ///
 /// This is the main entry point for HLS transcoding. It:
-/// 1. Selects appropriate qualities based on source resolution
-/// 2. Transcodes to each quality in sequence
-/// 3. Generates master playlist
-/// 4. Optionally deletes original file
+/// 1. Selects the profile's renditions that fit the source resolution
+/// 2. Measures loudness, if the profile normalises it
+/// 3. Transcodes to each quality in sequence; with several audio streams the
+///    variants are video-only and each stream gets an alternate rendition
+/// 4. Adds the audio-only rendition, if the profile has one
+/// 5. Generates master playlist
+/// 6. Optionally deletes original file
 ///
-/// Returns the list of quality names that were generated
+/// Returns the list of video quality names that were generated
 pub async fn transcode_to_hls(
     ffmpeg_config: &FFmpegConfig,
     hls_config: &HlsConfig,
+    profile: &EncodingProfile,
     input_path: &Path,
     output_dir: &Path,
     metadata: &VideoMetadata,
 ) -> Result<Vec<String>> {
     info!(
-        "Starting HLS transcoding for {}x{} video",
-        metadata.width, metadata.height
+        "Starting HLS transcoding for {}x{} video (profile '{}')",
+        metadata.width, metadata.height, profile.name
     );
 
     // Select appropriate qualities
     let selected_presets = if hls_config.auto_quality_selection {
-        select_qualities_for_source(metadata)
+        profile.renditions_for_source(metadata)
     } else {
-        QUALITY_PRESETS.iter().collect()
+        profile.renditions.iter().collect()
     };
 
     if selected_presets.is_empty() {
@@ -333,7 +667,7 @@ pub async fn transcode_to_hls(
         selected_presets.len(),
         selected_presets
             .iter()
-            .map(|p| p.name)
+            .map(|p| p.name.as_ref())
             .collect::<Vec<_>>()
             .join(", ")
     );
@@ -343,11 +677,33 @@ pub async fn transcode_to_hls(
         .await
         .context("Failed to create output directory")?;
 
+    // A single audio stream stays muxed into the video variants; several
+    // become alternate renditions so no language is dropped
+    let streams = &metadata.audio_streams;
+    let filters = loudness_filters(ffmpeg_config, profile, input_path, streams).await;
+    let alternate_audio = streams.len() > 1;
+    let default_stream = default_audio_stream(streams);
+    let muxed_audio = AudioInput {
+        stream: None,
+        filter: filters.first().cloned().flatten(),
+    };
+    let variant_audio = (!alternate_audio).then_some(&muxed_audio);
+    let segment_duration = segment_duration(hls_config, profile);
+
     // Transcode each quality variant
     let mut generated_qualities = Vec::new();
     for preset in &selected_presets {
-        match transcode_quality_variant(ffmpeg_config, hls_config, input_path, output_dir, preset)
-            .await
+        match transcode_quality_variant(
+            ffmpeg_config,
+            hls_config,
+            profile,
+            input_path,
+            output_dir,
+            preset,
+            metadata,
+            variant_audio,
+        )
+        .await
         {
             Ok(_) => {
                 generated_qualities.push(preset.name.to_string());
@@ -366,6 +722,78 @@ pub async fn transcode_to_hls(
         anyhow::bail!("All quality transcoding attempts failed");
     }
 
+    // Alternate audio renditions, at the ladder's highest audio bitrate
+    let mut audio_tracks = Vec::new();
+    if alternate_audio {
+        let bitrate = selected_presets
+            .iter()
+            .map(|p| p.audio_bitrate)
+            .max()
+            .unwrap_or_else(default_audio_bitrate);
+        for (stream, filter) in streams.iter().zip(&filters) {
+            let track =
+                AudioTrackRendition::for_stream(stream, stream.index == default_stream, bitrate);
+            let input = AudioInput {
+                stream: Some(stream.index),
+                filter: filter.clone(),
+            };
+            match transcode_audio_rendition(
+                ffmpeg_config,
+                segment_duration,
+                input_path,
+                output_dir,
+                &track.name,
+                track.bitrate,
+                &input,
+            )
+            .await
+            {
+                Ok(()) => audio_tracks.push(track),
+                Err(e) => warn!(
+                    "Failed to transcode audio stream {}: {}. Continuing with other streams.",
+                    stream.index, e
+                ),
+            }
+        }
+
+        if audio_tracks.is_empty() {
+            anyhow::bail!("All audio rendition attempts failed");
+        }
+        if !audio_tracks.iter().any(|t| t.is_default) {
+            audio_tracks[0].is_default = true;
+        }
+    }
+
+    // Audio-only rendition is optional: a failure here does not fail the video
+    let audio_only_input = if alternate_audio {
+        AudioInput {
+            stream: Some(default_stream),
+            filter: filters.get(default_stream).cloned().flatten(),
+        }
+    } else {
+        muxed_audio.clone()
+    };
+    let audio_only = match (&profile.audio_only, &metadata.audio_codec) {
+        (Some(rendition), Some(_)) => match transcode_audio_rendition(
+            ffmpeg_config,
+            segment_duration,
+            input_path,
+            output_dir,
+            &rendition.name,
+            rendition.bitrate,
+            &audio_only_input,
+        )
+        .await
+        {
+            Ok(()) => Some(rendition),
+            Err(e) => {
+                warn!("Failed to transcode audio-only rendition: {}", e);
+                None
+            }
+        },
+        _ => None,
+    };
+
     // Generate master playlist
     let successful_presets: Vec<&QualityPreset> = selected_presets
         .iter()
@@ -373,7 +801,7 @@ pub async fn transcode_to_hls(
         .copied()
         .collect();
 
-    generate_master_playlist(output_dir, &successful_presets)
+    generate_master_playlist(output_dir, &successful_presets, &audio_tracks, audio_only)
         .await
         .context("Failed to generate master playlist")?;
 
@@ -414,6 +842,108 @@ pub fn calculate_transcode_progress(
     current_progress.round() as u8
 }
 
+/// Program date of the first segment in VOD playlists that have none
+///
+/// `EXT-X-DATERANGE` positions are dates, so VOD playlists get an
+/// `EXT-X-PROGRAM-DATE-TIME` anchored at the Unix epoch: a range's
+/// `START-DATE` then reads directly as its offset into the video.
+pub const VOD_PROGRAM_EPOCH: &str = "1970-01-01T00:00:00Z";
+
+/// A span of the timeline announced with `#EXT-X-DATERANGE`
+#[derive(Debug, Clone, PartialEq)]
+pub struct DateRange {
+    /// Unique within the playlist
+    pub id: String,
+    /// Start offset in seconds
+    pub start: f64,
+    /// Length in seconds
+    pub duration: f64,
+    /// Client attributes (`X-TITLE`, ...), written as quoted strings
+    pub attributes: Vec<(String, String)>,
+}
+
+/// Quoted-string attribute values may not contain `"` or line breaks
+pub(crate) fn quote_attribute(value: &str) -> String {
+    value
+        .chars()
+        .map(|c| match c {
+            '"' => '\'',
+            '\r' | '\n' => ' ',
+            c => c,
+        })
+        .collect()
+}
+
+/// Rewrite a media playlist so it carries exactly `ranges` of `class`
+///
+/// Date ranges of other classes are kept. New ranges go before the first
+/// segment, preceded by a program date at [`VOD_PROGRAM_EPOCH`] if the
+/// playlist has none; the epoch anchor is removed again once no date range
+/// is left.
+pub fn rewrite_dateranges(playlist: &str, class: &str, ranges: &[DateRange]) -> Result<String> {
+    let class_attribute = format!("CLASS=\"{}\"", class);
+    let epoch_tag = format!("#EXT-X-PROGRAM-DATE-TIME:{}", VOD_PROGRAM_EPOCH);
+
+    let mut lines: Vec<&str> = playlist
+        .lines()
+        .filter(|l| !(l.starts_with("#EXT-X-DATERANGE:") && l.contains(&class_attribute)))
+        .collect();
+    let has_other_ranges = lines.iter().any(|l| l.starts_with("#EXT-X-DATERANGE:"));
+    if ranges.is_empty() && !has_other_ranges {
+        lines.retain(|l| *l != epoch_tag);
+    }
+
+    // Ranges go right before the first segment's program date (or the
+    // segment itself)
+    let first_segment = lines
+        .iter()
+        .position(|l| l.starts_with("#EXTINF:"))
+        .unwrap_or(lines.len());
+    let program_date_line = lines[..first_segment]
+        .iter()
+        .position(|l| l.starts_with("#EXT-X-PROGRAM-DATE-TIME:"));
+    let program_date = program_date_line.map(|i| &lines[i]["#EXT-X-PROGRAM-DATE-TIME:".len()..]);
+    let insert_at = program_date_line.unwrap_or(first_segment);
+
+    let mut tags = String::new();
+    if !ranges.is_empty() {
+        let base = OffsetDateTime::parse(program_date.unwrap_or(VOD_PROGRAM_EPOCH), &Rfc3339)
+            .context("Unsupported EXT-X-PROGRAM-DATE-TIME in playlist")?;
+        for range in ranges {
+            let start = base + time::Duration::seconds_f64(range.start);
+            let _ = write!(
+                tags,
+                "#EXT-X-DATERANGE:ID=\"{}\",{},START-DATE=\"{}\",DURATION={:.3}",
+                quote_attribute(&range.id),
+                class_attribute,
+                start.format(&Rfc3339).context("Failed to format date range start")?,
+                range.duration
+            );
+            for (name, value) in &range.attributes {
+                let _ = write!(tags, ",{}=\"{}\"", name, quote_attribute(value));
+            }
+            tags.push('\n');
+        }
+        if program_date.is_none() {
+            tags.push_str(&epoch_tag);
+            tags.push('\n');
+        }
+    }
+
+    let mut out = String::new();
+    for (i, line) in lines.iter().enumerate() {
+        if i == insert_at {
+            out.push_str(&tags);
+        }
+        out.push_str(line);
+        out.push('\n');
+    }
+    if insert_at == lines.len() {
+        out.push_str(&tags);
+    }
+    Ok(out)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -443,6 +973,7 @@ mod tests {
             bitrate: Some(5000000),
             file_size: 10000000,
             format: "mp4".to_string(),
+            audio_streams: Vec::new(),
         };
         let qualities = select_qualities_for_source(&metadata_1080p);
         assert_eq!(qualities.len(), 4); // All 4 qualities
@@ -458,6 +989,7 @@ mod tests {
             bitrate: Some(2800000),
             file_size: 5000000,
             format: "mp4".to_string(),
+            audio_streams: Vec::new(),
         };
         let qualities = select_qualities_for_source(&metadata_720p);
         assert_eq!(qualities.len(), 3); // 720p, 480p, 360p
@@ -474,6 +1006,7 @@ mod tests {
             bitrate: Some(1400000),
             file_size: 3000000,
             format: "mp4".to_string(),
+            audio_streams: Vec::new(),
         };
         let qualities = select_qualities_for_source(&metadata_480p);
         assert_eq!(qualities.len(), 2); // 480p, 360p
@@ -489,6 +1022,7 @@ mod tests {
             bitrate: Some(800000),
             file_size: 2000000,
             format: "mp4".to_string(),
+            audio_streams: Vec::new(),
         };
         let qualities = select_qualities_for_source(&metadata_360p);
         assert_eq!(qualities.len(), 1); // Only 360p
@@ -505,6 +1039,150 @@ mod tests {
         assert_eq!(calculate_transcode_progress(4, 4, 50, 90), 90); // After 4th (done)
     }
 
+    #[test]
+    fn test_rate_control_args() {
+        let preset = &QUALITY_PRESETS[1]; // 720p
+        assert_eq!(
+            rate_control_args(&RateControl::Cbr, preset),
+            ["-b:v", "2800k", "-maxrate", "2800k", "-bufsize", "5600k"]
+        );
+        assert_eq!(
+            rate_control_args(&RateControl::Crf { crf: 21 }, preset),
+            ["-crf", "21", "-maxrate", "2800k", "-bufsize", "5600k"]
+        );
+    }
+
+    #[test]
+    fn test_keyframe_alignment_args() {
+        let args = keyframe_alignment_args(6, 29.97);
+        assert_eq!(args[1], "180");
+        assert_eq!(args[7], "expr:gte(t,n_forced*6)");
+    }
+
+    #[test]
+    fn test_rewrite_dateranges() {
+        let playlist = "#EXTM3U\n#EXT-X-VERSION:3\n#EXT-X-TARGETDURATION:6\n#EXTINF:6.000,\nsegment_000.ts\n#EXT-X-ENDLIST\n";
+        let ranges = [DateRange {
+            id: "chapter-1".to_string(),
+            start: 65.5,
+            duration: 10.0,
+            attributes: vec![("X-TITLE".to_string(), "Q&A \"live\"".to_string())],
+        }];
+
+        let rewritten = rewrite_dateranges(playlist, "chapter", &ranges).unwrap();
+        assert!(rewritten.contains(
+            "#EXT-X-TARGETDURATION:6\n#EXT-X-DATERANGE:ID=\"chapter-1\",CLASS=\"chapter\",START-DATE=\"1970-01-01T00:01:05.5Z\",DURATION=10.000,X-TITLE=\"Q&A 'live'\"\n#EXT-X-PROGRAM-DATE-TIME:1970-01-01T00:00:00Z\n#EXTINF:6.000,"
+        ));
+
+        // Idempotent, and removing every range restores the original
+        assert_eq!(rewrite_dateranges(&rewritten, "chapter", &ranges).unwrap(), rewritten);
+        assert_eq!(rewrite_dateranges(&rewritten, "chapter", &[]).unwrap(), playlist);
+    }
+
+    #[test]
+    fn test_rewrite_dateranges_uses_existing_program_date() {
+        let playlist = "#EXTM3U\n#EXT-X-PROGRAM-DATE-TIME:2026-04-01T10:00:00Z\n#EXTINF:6.000,\nsegment_000.ts\n";
+        let ranges = [DateRange {
+            id: "c".to_string(),
+            start: 90.0,
+            duration: 1.0,
+            attributes: Vec::new(),
+        }];
+        let rewritten = rewrite_dateranges(playlist, "chapter", &ranges).unwrap();
+        assert!(rewritten.contains("START-DATE=\"2026-04-01T10:01:30Z\""));
+        assert_eq!(rewritten.matches("#EXT-X-PROGRAM-DATE-TIME").count(), 1);
+    }
+
+    fn audio_stream(index: usize, language: Option<&str>, is_default: bool) -> AudioStreamInfo {
+        AudioStreamInfo {
+            index,
+            language: language.map(str::to_string),
+            is_default,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_audio_input_args() {
+        assert!(AudioInput::default().args().is_empty());
+        let input = AudioInput {
+            stream: Some(1),
+            filter: Some("loudnorm=I=-23".to_string()),
+        };
+        assert_eq!(input.args(), ["-map", "0:a:1", "-af", "loudnorm=I=-23"]);
+    }
+
+    #[test]
+    fn test_audio_track_rendition_for_stream() {
+        let track =
+            AudioTrackRendition::for_stream(&audio_stream(1, Some("deu"), false), false, 128);
+        assert_eq!(track.name, "audio_1");
+        assert_eq!(track.language.as_deref(), Some("deu"));
+        assert_eq!(track.label, "deu");
+
+        let titled = AudioStreamInfo {
+            title: Some("Director's commentary".to_string()),
+            ..audio_stream(2, None, false)
+        };
+        let track = AudioTrackRendition::for_stream(&titled, false, 128);
+        assert_eq!(track.language, None);
+        assert_eq!(track.label, "Director's commentary");
+
+        let track = AudioTrackRendition::for_stream(&audio_stream(0, None, true), true, 128);
+        assert_eq!(track.label, "Track 1");
+        assert!(track.is_default);
+    }
+
+    #[test]
+    fn test_default_audio_stream() {
+        assert_eq!(default_audio_stream(&[]), 0);
+        let streams = [
+            audio_stream(0, Some("eng"), false),
+            audio_stream(1, Some("fra"), true),
+        ];
+        assert_eq!(default_audio_stream(&streams), 1);
+        let streams = [
+            audio_stream(0, Some("eng"), false),
+            audio_stream(1, Some("fra"), false),
+        ];
+        assert_eq!(default_audio_stream(&streams), 0);
+    }
+
+    #[test]
+    fn test_render_master_playlist_muxed_audio() {
+        let presets = [&QUALITY_PRESETS[1]];
+        let audio_only = AudioOnlyRendition::default();
+        let master = render_master_playlist(&presets, &[], Some(&audio_only));
+        assert_eq!(
+            master,
+            "#EXTM3U\n#EXT-X-VERSION:3\n#EXT-X-STREAM-INF:BANDWIDTH=2928000,RESOLUTION=1280x720\n720p/index.m3u8\n#EXT-X-STREAM-INF:BANDWIDTH=64000,CODECS=\"mp4a.40.2\"\naudio/index.m3u8\n"
+        );
+    }
+
+    #[test]
+    fn test_render_master_playlist_alternate_audio() {
+        let presets = [&QUALITY_PRESETS[1], &QUALITY_PRESETS[3]];
+        let tracks = [
+            AudioTrackRendition::for_stream(&audio_stream(0, Some("en"), true), true, 128),
+            AudioTrackRendition::for_stream(&audio_stream(1, None, false), false, 128),
+        ];
+        let master = render_master_playlist(&presets, &tracks, None);
+        let lines: Vec<&str> = master.lines().collect();
+        assert_eq!(
+            lines[2],
+            "#EXT-X-MEDIA:TYPE=AUDIO,GROUP-ID=\"aud\",NAME=\"en\",LANGUAGE=\"en\",DEFAULT=YES,AUTOSELECT=YES,URI=\"audio_0/index.m3u8\""
+        );
+        assert_eq!(
+            lines[3],
+            "#EXT-X-MEDIA:TYPE=AUDIO,GROUP-ID=\"aud\",NAME=\"Track 2\",DEFAULT=NO,AUTOSELECT=YES,URI=\"audio_1/index.m3u8\""
+        );
+        assert_eq!(
+            lines[4],
+            "#EXT-X-STREAM-INF:BANDWIDTH=2928000,RESOLUTION=1280x720,AUDIO=\"aud\""
+        );
+        assert_eq!(lines[7], "360p/index.m3u8");
+    }
+
     #[test]
     fn test_hls_config_default() {
         let config = HlsConfig::default();
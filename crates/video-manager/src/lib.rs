@@ -1,14 +1,24 @@
 // Module declarations
+pub mod audio;
+pub mod chapters;
 pub mod cleanup;
+pub mod encoding;
 pub mod errors;
 pub mod ffmpeg;
 pub mod hls;
+pub mod live;
+pub mod live_streams;
+pub mod loudness;
 pub mod media_item_impl;
 pub mod metrics;
+pub mod previews;
 pub mod processing;
 pub mod progress;
+pub mod queue;
 pub mod retry;
 pub mod storage;
+pub mod subtitles;
+pub mod transcripts;
 pub mod upload;
 pub mod upload_v2;
 
@@ -18,7 +28,7 @@ pub use processing::{ProcessingContext, ProcessingStage, process_video};
 use askama::Template;
 use axum::{
     extract::{Multipart, Path, Query, State},
-    http::{header, StatusCode},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
     response::{Html, IntoResponse, Response},
     routing::{delete, get, post, put},
     Json, Router,
@@ -28,12 +38,11 @@ use serde::{Deserialize, Serialize};
 use serde_json;
 use sqlx::{Pool, Row, Sqlite};
 use std::{path::PathBuf, sync::Arc};
-use tokio_util::io::ReaderStream;
 use tower_sessions::Session;
 use tracing::{self, info};
 
 // Import access control functionality
-use access_control::{AccessContext, AccessControlService, Permission};
+use access_control::{AccessContext, AccessControlService, Permission, SignedUrlParams};
 use common::ResourceType;
 use db::media::MediaRepository;
 
@@ -191,10 +200,16 @@ pub struct VideoManagerState {
     pub audit_logger: metrics::AuditLogger,
     /// RTMP stream publishing token, loaded from env at construction time.
     pub rtmp_publish_token: String,
+    /// Persistent queue feeding the video processing workers
+    pub transcode_queue: queue::TranscodeQueue,
+    /// Speech-to-text for processed videos (disabled unless `ASR_BACKEND` is set)
+    pub transcriber: transcripts::Transcriber,
+    /// Recording of live streams into the media library
+    pub live: live::LiveRecorder,
 }
 
 impl VideoManagerState {
-    pub fn new(pool: Pool<Sqlite>, repo: Arc<dyn MediaRepository>, vault_repo: Arc<dyn db::vaults::VaultRepository>, storage_dir: PathBuf, http_client: Client, access_control: Arc<AccessControlService>) -> Self {
+    pub fn new(pool: Pool<Sqlite>, repo: Arc<dyn MediaRepository>, vault_repo: Arc<dyn db::vaults::VaultRepository>, job_repo: Arc<dyn db::jobs::JobRepository>, storage_dir: PathBuf, http_client: Client, access_control: Arc<AccessControlService>) -> Self {
         let progress_tracker = ProgressTracker::default();
 
         // Start automatic cleanup task (runs every 5 minutes)
@@ -223,6 +238,16 @@ impl VideoManagerState {
         // Initialize audit logger
         let audit_logger = metrics::AuditLogger::new();
 
+        // Transcoding queue (workers are started separately)
+        let transcode_queue = queue::TranscodeQueue::new(job_repo, queue::QueueConfig::from_env());
+
+        // Speech-to-text transcripts
+        let transcriber = transcripts::Transcriber::new(
+            transcripts::TranscriptConfig::from_env(),
+            ffmpeg_config.clone(),
+            http_client.clone(),
+        );
+
         Self {
             pool,
             repo,
@@ -237,8 +262,41 @@ impl VideoManagerState {
             metrics_store,
             audit_logger,
             rtmp_publish_token: rtmp_publish_token(),
+            transcode_queue,
+            transcriber,
+            live: live::LiveRecorder::new(live::LiveConfig::from_env()),
         }
     }
+
+    /// Store media through the given storage manager (and its backend)
+    /// instead of the local-disk default
+    pub fn with_user_storage(mut self, user_storage: common::storage::UserStorageManager) -> Self {
+        self.storage_config.user_storage = user_storage;
+        self
+    }
+
+    /// Let transcripts use the video owners' LLM providers
+    /// (OpenAI-compatible `/audio/transcriptions`)
+    pub fn with_llm_providers(mut self, repo: Arc<dyn db::llm_providers::LlmProviderRepository>) -> Self {
+        self.transcriber = self.transcriber.with_llm_providers(repo);
+        self
+    }
+
+    /// Resume interrupted jobs and spawn the transcoding worker pool
+    pub async fn start_transcode_workers(&self) -> anyhow::Result<()> {
+        self.transcode_queue
+            .start(queue::WorkerDeps {
+                storage_config: self.storage_config.clone(),
+                ffmpeg_config: self.ffmpeg_config.clone(),
+                hls_config: self.hls_config.clone(),
+                progress_tracker: self.progress_tracker.clone(),
+                metrics_store: self.metrics_store.clone(),
+                audit_logger: self.audit_logger.clone(),
+                media_repo: self.repo.clone(),
+                transcriber: self.transcriber.clone(),
+            })
+            .await
+    }
 }
 
 // -------------------------------
@@ -258,6 +316,7 @@ pub fn video_routes() -> Router<Arc<VideoManagerState>> {
         .route("/hls/{*path}", get(hls_proxy_handler))
         .route("/api/stream/validate", get(validate_stream_handler))
         .route("/api/stream/authorize", get(authorize_stream_handler))
+        .route("/api/stream/auth", post(live_streams::stream_auth_handler))
         .route("/api/mediamtx/status", get(mediamtx_status))
         // Video CRUD API
         .route("/api/videos", get(list_videos_api_handler))
@@ -279,6 +338,23 @@ pub fn video_routes() -> Router<Arc<VideoManagerState>> {
         )
         .route("/api/videos/{id}", put(update_video_handler))
         .route("/api/videos/{id}", delete(delete_video_handler))
+        // Transcoding job queue
+        .route("/api/jobs", get(list_jobs_handler))
+        .route("/api/jobs/{id}", get(get_job_handler).delete(cancel_job_handler))
+        // Live stream definitions and recorded sessions
+        .route(
+            "/api/live/streams",
+            get(live_streams::list_streams_handler).post(live_streams::create_stream_handler),
+        )
+        .route(
+            "/api/live/streams/{id}",
+            put(live_streams::update_stream_handler).delete(live_streams::delete_stream_handler),
+        )
+        .route(
+            "/api/live/streams/{id}/rotate-key",
+            post(live_streams::rotate_key_handler),
+        )
+        .route("/api/live/sessions", get(list_live_sessions_handler))
 }
 
 // -------------------------------
@@ -409,10 +485,11 @@ pub struct AccessCodeQuery {
 // Video Player Page Handler
 // -------------------------------
 
-#[tracing::instrument(skip(query, session, state))]
+#[tracing::instrument(skip(query, headers, session, state))]
 pub async fn video_player_handler(
     Path(slug): Path<String>,
     Query(query): Query<AccessCodeQuery>,
+    headers: HeaderMap,
     session: Session,
     State(state): State<Arc<VideoManagerState>>,
 ) -> Result<Html<String>, Response> {
@@ -449,7 +526,8 @@ pub async fn video_player_handler(
     let is_public = video.is_public == 1;
 
     // Build access context for modern access control
-    let mut context = AccessContext::new(ResourceType::Video, video_id);
+    let mut context =
+        AccessContext::new(ResourceType::Video, video_id).with_request_headers(&headers);
     if let Some(uid) = user_id {
         context = context.with_user(uid);
     }
@@ -481,6 +559,18 @@ pub async fn video_player_handler(
         .unwrap_or_default();
         return Err((StatusCode::UNAUTHORIZED, Html(html)).into_response());
     }
+    if !state
+        .access_control
+        .record_key_view(&decision, query.code.as_deref())
+        .await
+    {
+        info!(video_slug = %slug, "Access code view limit reached");
+        return Err((
+            StatusCode::GONE,
+            "This access code has reached its view limit",
+        )
+            .into_response());
+    }
 
     // Log successful access with layer information
     info!(
@@ -523,12 +613,14 @@ pub async fn live_test_handler(session: Session) -> Result<Html<String>, StatusC
 // HLS Proxy Handler for Live Streams and VOD
 // -------------------------------
 
-#[tracing::instrument(skip(query, session, state))]
+#[tracing::instrument(skip(query, signed, session, state))]
 pub async fn hls_proxy_handler(
     Path(path): Path<String>,
     Query(query): Query<AccessCodeQuery>,
+    Query(signed): Query<SignedUrlParams>,
     session: Session,
     State(state): State<Arc<VideoManagerState>>,
+    headers: HeaderMap,
 ) -> Result<Response, StatusCode> {
     // Parse the path to extract slug and file
     let parts: Vec<&str> = path.splitn(2, '/').collect();
@@ -540,20 +632,53 @@ pub async fn hls_proxy_handler(
     let file_path = parts[1];
 
     // Handle live stream - proxy to MediaMTX
-    if slug == LIVE_STREAM_KEY {
-        // Check authentication for live stream
+    let live_stream = if slug == LIVE_STREAM_KEY {
+        None
+    } else {
+        state
+            .repo
+            .get_live_stream_by_path(slug)
+            .await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+    };
+    if slug == LIVE_STREAM_KEY || live_stream.is_some() {
         let authenticated: bool = session
             .get("authenticated")
             .await
             .ok()
             .flatten()
             .unwrap_or(false);
+        let user_id: Option<String> = if authenticated {
+            session.get::<String>("user_id").await.ok().flatten()
+        } else {
+            None
+        };
 
-        if !authenticated {
-            println!("❌ HLS request rejected: not authenticated");
+        // The legacy stream is open to any signed-in user; defined streams
+        // follow their access setting
+        let allowed = match &live_stream {
+            None => authenticated,
+            Some(stream) => {
+                live_streams::can_view(&state, stream, user_id.as_deref(), query.code.as_deref())
+                    .await
+            }
+        };
+        if !allowed {
+            info!(stream = %slug, "Live HLS request rejected");
             return Err(StatusCode::UNAUTHORIZED);
         }
 
+        // Players poll the playlists, which makes them a viewer heartbeat
+        if file_path.ends_with(".m3u8") {
+            let viewer = match user_id {
+                Some(user_id) => Some(user_id),
+                None => live_viewer_id(&session).await,
+            };
+            if let Some(viewer) = viewer {
+                state.live.record_viewer(slug, &viewer);
+            }
+        }
+
         // Proxy request to MediaMTX
         let mediamtx_url = format!("{}/{}/{}", MEDIAMTX_HLS_URL, slug, file_path);
 
@@ -587,11 +712,23 @@ pub async fn hls_proxy_handler(
         };
 
         // Get response body
-        let bytes = response
+        let mut bytes = response
             .bytes()
             .await
             .map_err(|_| StatusCode::BAD_GATEWAY)?;
 
+        // Carry the access code into the segment and sub-playlist URIs
+        let code = query.code.as_deref().filter(|code| {
+            live_stream
+                .as_ref()
+                .is_some_and(|s| s.access_code.as_deref() == Some(*code))
+        });
+        if let (Some(code), true) = (code, file_path.ends_with(".m3u8")) {
+            let playlist = String::from_utf8_lossy(&bytes);
+            bytes = live_streams::append_query_to_playlist(&playlist, &format!("code={}", code))
+                .into();
+        }
+
         // Build response with proper headers
         return Ok(Response::builder()
             .status(StatusCode::OK)
@@ -638,54 +775,72 @@ pub async fn hls_proxy_handler(
         None
     };
 
-    // Build access context for modern access control
-    // For HLS streaming, we require Download permission
-    let mut context = AccessContext::new(ResourceType::Video, video_id);
-    if let Some(uid) = user_id {
-        context = context.with_user(uid);
-    }
-    if let Some(key) = query.code.clone() {
-        context = context.with_key(key);
-    }
+    // A valid signed URL grants access to every file of the video
+    let signed_query = if signed.is_present() {
+        match state.access_control.url_signer().verify(video_id, &signed) {
+            Ok(_) => signed.to_query(),
+            Err(e) => {
+                info!(video_slug = %slug, error = %e, "Signed HLS URL rejected");
+                None
+            }
+        }
+    } else {
+        None
+    };
 
-    // Check access using the 4-layer access control system
-    let decision = state
-        .access_control
-        .check_access(context, Permission::Read)
-        .await
-        .map_err(|e| {
-            info!(error = ?e, "Access control error for HLS stream");
-            StatusCode::INTERNAL_SERVER_ERROR
-        })?;
+    if signed_query.is_some() {
+        info!(video_slug = %slug, "Access granted to HLS stream via signed URL");
+    } else {
+        // Build access context for modern access control
+        // For HLS streaming, we require Download permission
+        let mut context =
+            AccessContext::new(ResourceType::Video, video_id).with_request_headers(&headers);
+        if let Some(uid) = user_id {
+            context = context.with_user(uid);
+        }
+        if let Some(key) = query.code.clone() {
+            context = context.with_key(key);
+        }
 
-    if !decision.granted {
-        // Fallback: workspace folder access code whose workspace owner also owns this vault
-        let vault_ok = if let (Some(ref code), Some(ref vid)) = (query.code.as_ref(), vault_id.as_ref()) {
-            state
-                .repo
-                .workspace_folder_code_grants_vault_via_owner(code, vid)
-                .await
-                .unwrap_or(false)
-        } else {
-            false
-        };
+        // Check access using the 4-layer access control system
+        let decision = state
+            .access_control
+            .check_access(context, Permission::Read)
+            .await
+            .map_err(|e| {
+                info!(error = ?e, "Access control error for HLS stream");
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?;
 
-        if !vault_ok {
-            info!(
-                video_slug = %slug,
-                file_path = %file_path,
-                reason = %decision.reason,
-                "Access denied to HLS stream"
-            );
-            return Err(StatusCode::UNAUTHORIZED);
+        if !decision.granted {
+            // Fallback: workspace folder access code whose workspace owner also owns this vault
+            let vault_ok = if let (Some(ref code), Some(ref vid)) = (query.code.as_ref(), vault_id.as_ref()) {
+                state
+                    .repo
+                    .workspace_folder_code_grants_vault_via_owner(code, vid)
+                    .await
+                    .unwrap_or(false)
+            } else {
+                false
+            };
+
+            if !vault_ok {
+                info!(
+                    video_slug = %slug,
+                    file_path = %file_path,
+                    reason = %decision.reason,
+                    "Access denied to HLS stream"
+                );
+                return Err(StatusCode::UNAUTHORIZED);
+            }
         }
-    }
 
-    info!(
-        video_slug = %slug,
-        access_layer = ?decision.layer,
-        "Access granted to HLS stream"
-    );
+        info!(
+            video_slug = %slug,
+            access_layer = ?decision.layer,
+            "Access granted to HLS stream"
+        );
+    }
 
     // Phase 4.5: Serve VOD file from vault-based storage
     // Use multi-location fallback to find HLS files in new nested structure
@@ -693,45 +848,75 @@ pub async fn hls_proxy_handler(
     let vault_id = vault_id.ok_or(StatusCode::INTERNAL_SERVER_ERROR)?;
 
     let hls_file = format!("{}/{}", slug, file_path);
-    let full_path = state
-        .storage_config
-        .user_storage
-        .find_media_file(
+    let user_storage = &state.storage_config.user_storage;
+    let full_path = user_storage
+        .find_stored_media_file(
             &vault_id,
             common::storage::MediaType::Video,
             &hls_file,
         )
+        .await
         .ok_or_else(|| {
             info!("HLS file not found: {}/{}", slug, file_path);
             StatusCode::NOT_FOUND
         })?;
 
-    // Check if file exists and read it
-    let file = tokio::fs::File::open(&full_path)
-        .await
-        .map_err(|_| StatusCode::NOT_FOUND)?;
-
     // Determine content type
     let content_type = if file_path.ends_with(".m3u8") {
         "application/vnd.apple.mpegurl"
     } else if file_path.ends_with(".ts") {
         "video/MP2T"
+    } else if file_path.ends_with(".vtt") {
+        "text/vtt; charset=utf-8"
     } else {
         "application/octet-stream"
     };
 
-    // Stream the file
-    let stream = ReaderStream::new(file);
-    let body = axum::body::Body::from_stream(stream);
-
-    Ok(Response::builder()
-        .status(StatusCode::OK)
-        .header(header::CONTENT_TYPE, content_type)
-        .header(header::CACHE_CONTROL, "max-age=3600")
-        .header(header::ACCESS_CONTROL_ALLOW_ORIGIN, "*")
-        .header(header::ACCESS_CONTROL_ALLOW_METHODS, "GET, OPTIONS")
-        .body(body)
-        .unwrap())
+    // Playlists change when subtitle tracks are added or removed
+    let cache_control = if file_path.ends_with(".m3u8") {
+        "no-cache"
+    } else {
+        "max-age=3600"
+    };
+
+    // Carry the signature into the segment and sub-playlist URIs
+    let mut response = match (signed_query, file_path.ends_with(".m3u8")) {
+        (Some(signed_query), true) => {
+            let local = user_storage
+                .fetch_local(&full_path)
+                .await
+                .ok_or(StatusCode::NOT_FOUND)?;
+            let playlist = tokio::fs::read_to_string(&local)
+                .await
+                .map_err(|_| StatusCode::NOT_FOUND)?;
+            let playlist = live_streams::append_query_to_playlist(&playlist, &signed_query);
+            ([(header::CONTENT_TYPE, content_type)], playlist).into_response()
+        }
+        // Stream the file from local disk or the storage backend
+        _ => common::range::serve_stored_file(user_storage, &full_path, content_type, &headers)
+            .await
+            .map_err(|_| StatusCode::NOT_FOUND)?,
+    };
+
+    let response_headers = response.headers_mut();
+    response_headers.insert(header::CACHE_CONTROL, HeaderValue::from_static(cache_control));
+    response_headers.insert(header::ACCESS_CONTROL_ALLOW_ORIGIN, HeaderValue::from_static("*"));
+    response_headers.insert(
+        header::ACCESS_CONTROL_ALLOW_METHODS,
+        HeaderValue::from_static("GET, OPTIONS"),
+    );
+
+    Ok(response)
+}
+
+/// Anonymous viewer ID kept in the session, for live viewer counts
+async fn live_viewer_id(session: &Session) -> Option<String> {
+    if let Ok(Some(id)) = session.get::<String>("live_viewer_id").await {
+        return Some(id);
+    }
+    let id = uuid::Uuid::new_v4().to_string();
+    session.insert("live_viewer_id", &id).await.ok()?;
+    Some(id)
 }
 
 // -------------------------------
@@ -928,6 +1113,7 @@ pub async fn video_upload_handler(
         state.audit_logger.clone(),
         state.repo.clone(),
         state.vault_repo.clone(),
+        state.transcode_queue.clone(),
     ));
 
     // Call the upload handler
@@ -1008,6 +1194,181 @@ pub async fn get_detailed_metrics_handler(
     Json(metrics.clone())
 }
 
+// -------------------------------
+// Transcoding Job Queue Handlers
+// -------------------------------
+
+#[derive(Debug, Deserialize)]
+pub struct JobListQuery {
+    pub status: Option<String>,
+    pub limit: Option<i64>,
+}
+
+/// Job plus its live progress (if a worker is currently running it)
+#[derive(Debug, Serialize)]
+pub struct JobResponse {
+    #[serde(flatten)]
+    pub job: db::jobs::TranscodeJob,
+    pub progress: Option<UploadProgress>,
+}
+
+/// GET /api/jobs - List the current user's transcoding jobs
+#[tracing::instrument(skip(session, state))]
+pub async fn list_jobs_handler(
+    session: Session,
+    State(state): State<Arc<VideoManagerState>>,
+    Query(query): Query<JobListQuery>,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    let user_id: String = session
+        .get("user_id")
+        .await
+        .ok()
+        .flatten()
+        .ok_or(StatusCode::UNAUTHORIZED)?;
+
+    let filter = db::jobs::JobListFilter {
+        user_id: Some(user_id),
+        status: query.status,
+        limit: query.limit.unwrap_or(100).clamp(1, 500),
+    };
+
+    let jobs = state
+        .transcode_queue
+        .repo()
+        .list_jobs(&filter)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to list transcoding jobs: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    let jobs: Vec<JobResponse> = jobs
+        .into_iter()
+        .map(|job| JobResponse {
+            progress: state.progress_tracker.get(&job.upload_id),
+            job,
+        })
+        .collect();
+
+    Ok(Json(serde_json::json!({
+        "workers": state.transcode_queue.config().workers,
+        "jobs": jobs,
+    })))
+}
+
+/// Load a job and check that it belongs to the session user
+async fn load_owned_job(
+    session: &Session,
+    state: &VideoManagerState,
+    id: i64,
+) -> Result<db::jobs::TranscodeJob, StatusCode> {
+    let user_id: String = session
+        .get("user_id")
+        .await
+        .ok()
+        .flatten()
+        .ok_or(StatusCode::UNAUTHORIZED)?;
+
+    let job = state
+        .transcode_queue
+        .repo()
+        .get_job(id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    // Report someone else's job as missing rather than forbidden
+    if job.user_id.as_deref() != Some(user_id.as_str()) {
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    Ok(job)
+}
+
+/// GET /api/jobs/{id} - Get a single transcoding job
+#[tracing::instrument(skip(session, state))]
+pub async fn get_job_handler(
+    session: Session,
+    State(state): State<Arc<VideoManagerState>>,
+    Path(id): Path<i64>,
+) -> Result<Json<JobResponse>, StatusCode> {
+    let job = load_owned_job(&session, &state, id).await?;
+    Ok(Json(JobResponse {
+        progress: state.progress_tracker.get(&job.upload_id),
+        job,
+    }))
+}
+
+/// DELETE /api/jobs/{id} - Cancel a job that has not started yet
+#[tracing::instrument(skip(session, state))]
+pub async fn cancel_job_handler(
+    session: Session,
+    State(state): State<Arc<VideoManagerState>>,
+    Path(id): Path<i64>,
+) -> Result<StatusCode, StatusCode> {
+    let job = load_owned_job(&session, &state, id).await?;
+    let user_id = job.user_id.clone().unwrap_or_default();
+
+    let cancelled = state
+        .transcode_queue
+        .repo()
+        .cancel_job(id, &user_id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    if !cancelled {
+        // Already running or finished
+        return Err(StatusCode::CONFLICT);
+    }
+
+    state
+        .repo
+        .update_media_status_error(&job.slug, "video")
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    state
+        .progress_tracker
+        .set_error(&job.upload_id, "Cancelled".to_string());
+    tokio::fs::remove_file(&job.source_path).await.ok();
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// -------------------------------
+// Live Session Handlers
+// -------------------------------
+
+#[derive(Debug, Deserialize)]
+pub struct LiveSessionQuery {
+    pub limit: Option<i64>,
+}
+
+/// GET /api/live/sessions - List the current user's recorded live streams
+#[tracing::instrument(skip(session, state))]
+pub async fn list_live_sessions_handler(
+    session: Session,
+    State(state): State<Arc<VideoManagerState>>,
+    Query(query): Query<LiveSessionQuery>,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    let user_id: String = session
+        .get("user_id")
+        .await
+        .ok()
+        .flatten()
+        .ok_or(StatusCode::UNAUTHORIZED)?;
+
+    let sessions = state
+        .repo
+        .list_live_sessions(&user_id, query.limit.unwrap_or(50).clamp(1, 500))
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to list live sessions: {}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(Json(serde_json::json!({ "sessions": sessions })))
+}
+
 // -------------------------------
 // Available Folders Handler
 // -------------------------------
@@ -0,0 +1,489 @@
+//! Live-stream recording (DVR)
+//!
+//! MediaMTX reports the lifecycle of a published stream through the
+//! `runOnReady` / `runOnNotReady` hooks in `mediamtx.yml`. When a stream goes
+//! live, [`stream_ready`] creates a media item in `live` status in the owner's
+//! default vault and records the stream to disk with ffmpeg. When it ends,
+//! [`stream_ended`] stops the recorder and hands the recording to the
+//! transcoding queue, so the session becomes a regular VOD item. Start/end
+//! times and viewer counts are kept in `live_sessions`.
+//!
+//! Streams defined in the database ([`crate::live_streams`]) are recorded
+//! into their owner's vault. The single `live` path is recorded once the user
+//! owning its recordings is named:
+//!
+//! ```text
+//! LIVE_STREAM_OWNER_ID=<user id>
+//! MEDIAMTX_RTMP_URL=rtmp://127.0.0.1:1935   # where the recorder pulls from
+//! ```
+
+use crate::VideoManagerState;
+use anyhow::{Context, Result};
+use db::media::{
+    LiveSession, MediaInsert, LIVE_SESSION_ENDED, LIVE_SESSION_FAILED, MEDIA_STATUS_LIVE,
+};
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::Stdio;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use time::OffsetDateTime;
+use tokio::io::AsyncWriteExt;
+use tokio::process::{Child, Command};
+use tracing::{error, info, warn};
+
+/// Viewers count as concurrent while their player polled within this window
+const VIEWER_WINDOW: Duration = Duration::from_secs(30);
+
+/// How long ffmpeg gets to finish the file on its own once the stream ended
+const RECORDER_STOP_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Live recording settings
+#[derive(Debug, Clone)]
+pub struct LiveConfig {
+    /// User who owns recordings of the `live` path; not recorded without one
+    pub owner_id: Option<String>,
+    /// RTMP base URL the recorder reads streams from
+    pub rtmp_url: String,
+}
+
+impl Default for LiveConfig {
+    fn default() -> Self {
+        Self {
+            owner_id: None,
+            rtmp_url: "rtmp://127.0.0.1:1935".to_string(),
+        }
+    }
+}
+
+impl LiveConfig {
+    /// Load from `LIVE_STREAM_OWNER_ID` and `MEDIAMTX_RTMP_URL`
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            owner_id: std::env::var("LIVE_STREAM_OWNER_ID")
+                .ok()
+                .filter(|v| !v.trim().is_empty()),
+            rtmp_url: std::env::var("MEDIAMTX_RTMP_URL")
+                .ok()
+                .filter(|v| !v.trim().is_empty())
+                .unwrap_or(defaults.rtmp_url),
+        }
+    }
+}
+
+/// Owner and visibility of the media item a stream is recorded into
+#[derive(Debug, Clone)]
+pub struct RecordingTarget {
+    pub owner_id: String,
+    /// Stream name used in the item's title
+    pub name: Option<String>,
+    pub is_public: bool,
+    pub group_id: Option<i32>,
+}
+
+/// Viewer counts of one stream
+#[derive(Debug, Default)]
+pub struct ViewerStats {
+    last_seen: HashMap<String, Instant>,
+    peak: usize,
+}
+
+impl ViewerStats {
+    /// Note that `viewer` fetched the playlist at `now`
+    pub fn record(&mut self, viewer: &str, now: Instant) {
+        self.last_seen.insert(viewer.to_string(), now);
+        self.peak = self.peak.max(self.current(now));
+    }
+
+    /// Viewers seen within [`VIEWER_WINDOW`] of `now`
+    pub fn current(&self, now: Instant) -> usize {
+        self.last_seen
+            .values()
+            .filter(|seen| now.saturating_duration_since(**seen) <= VIEWER_WINDOW)
+            .count()
+    }
+
+    /// Most viewers watching at the same time
+    pub fn peak(&self) -> usize {
+        self.peak
+    }
+
+    /// Distinct viewers over the whole session
+    pub fn total(&self) -> usize {
+        self.last_seen.len()
+    }
+}
+
+/// Running recorders and viewer counts, keyed by stream path
+#[derive(Clone, Default)]
+pub struct LiveRecorder {
+    config: LiveConfig,
+    recorders: Arc<Mutex<HashMap<String, Child>>>,
+    viewers: Arc<Mutex<HashMap<String, ViewerStats>>>,
+}
+
+impl LiveRecorder {
+    pub fn new(config: LiveConfig) -> Self {
+        Self {
+            config,
+            ..Default::default()
+        }
+    }
+
+    pub fn config(&self) -> &LiveConfig {
+        &self.config
+    }
+
+    /// Count a playlist request towards the stream's viewers
+    pub fn record_viewer(&self, stream_path: &str, viewer: &str) {
+        let mut viewers = self.viewers.lock().unwrap_or_else(|e| e.into_inner());
+        viewers
+            .entry(stream_path.to_string())
+            .or_default()
+            .record(viewer, Instant::now());
+    }
+
+    /// Take the final `(peak, total)` viewer counts of a stream
+    fn take_viewer_counts(&self, stream_path: &str) -> (i64, i64) {
+        let mut viewers = self.viewers.lock().unwrap_or_else(|e| e.into_inner());
+        viewers
+            .remove(stream_path)
+            .map(|stats| (stats.peak() as i64, stats.total() as i64))
+            .unwrap_or((0, 0))
+    }
+
+    fn insert_recorder(&self, stream_path: &str, child: Child) {
+        let mut recorders = self.recorders.lock().unwrap_or_else(|e| e.into_inner());
+        recorders.insert(stream_path.to_string(), child);
+    }
+
+    fn has_recorder(&self, stream_path: &str) -> bool {
+        let recorders = self.recorders.lock().unwrap_or_else(|e| e.into_inner());
+        recorders.contains_key(stream_path)
+    }
+
+    fn take_recorder(&self, stream_path: &str) -> Option<Child> {
+        let mut recorders = self.recorders.lock().unwrap_or_else(|e| e.into_inner());
+        recorders.remove(stream_path)
+    }
+}
+
+/// MediaMTX path names that are safe to put into the recorder URL
+pub fn is_valid_stream_path(path: &str) -> bool {
+    !path.is_empty()
+        && path.len() <= 128
+        && !path.starts_with('/')
+        && !path.split('/').any(|part| part.is_empty() || part == "..")
+        && path
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | '/'))
+}
+
+/// Title of the media item a stream is recorded into
+fn live_title(name: Option<&str>, started: OffsetDateTime) -> String {
+    format!(
+        "{} {:04}-{:02}-{:02} {:02}:{:02} UTC",
+        name.unwrap_or("Live stream"),
+        started.year(),
+        u8::from(started.month()),
+        started.day(),
+        started.hour(),
+        started.minute()
+    )
+}
+
+/// Slug of the media item a stream is recorded into
+fn live_slug(started: OffsetDateTime) -> String {
+    format!(
+        "live-{:04}{:02}{:02}-{:02}{:02}-{}",
+        started.year(),
+        u8::from(started.month()),
+        started.day(),
+        started.hour(),
+        started.minute(),
+        &uuid::Uuid::new_v4().to_string()[..8]
+    )
+}
+
+/// Pull the stream from MediaMTX and write it to `recording_path` unchanged
+fn spawn_recorder(
+    state: &VideoManagerState,
+    stream_path: &str,
+    recording_path: &Path,
+) -> Result<Child> {
+    let source = format!(
+        "{}/{}",
+        state.live.config.rtmp_url.trim_end_matches('/'),
+        stream_path
+    );
+    Command::new(&state.ffmpeg_config.ffmpeg_path)
+        .args(["-hide_banner", "-loglevel", "error"])
+        .args(["-i", &source])
+        .args(["-map", "0", "-c", "copy", "-f", "matroska"])
+        .arg(recording_path)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .kill_on_drop(true)
+        .spawn()
+        .context("Failed to start the live recorder")
+}
+
+/// Let ffmpeg finish the file, asking it to quit (`q`) if the input hangs
+async fn stop_recorder(mut child: Child) {
+    if tokio::time::timeout(RECORDER_STOP_TIMEOUT, child.wait())
+        .await
+        .is_ok()
+    {
+        return;
+    }
+    if let Some(mut stdin) = child.stdin.take() {
+        let _ = stdin.write_all(b"q").await;
+    }
+    if tokio::time::timeout(Duration::from_secs(5), child.wait())
+        .await
+        .is_err()
+    {
+        warn!("Live recorder did not stop, killing it");
+        let _ = child.kill().await;
+    }
+}
+
+/// A stream went live: create its media item and start recording
+///
+/// A stream that is already being recorded keeps its session. A session left
+/// open without a recorder (the end was never reported, or the server
+/// restarted) is finished first.
+pub async fn stream_ready(
+    state: &VideoManagerState,
+    stream_path: &str,
+    target: &RecordingTarget,
+    tenant_id: &str,
+) -> Result<Option<LiveSession>> {
+    if !is_valid_stream_path(stream_path) {
+        anyhow::bail!("Invalid stream path: {}", stream_path);
+    }
+
+    if let Some(session) = state.repo.get_active_live_session(stream_path).await? {
+        if state.live.has_recorder(stream_path) {
+            info!("Stream {} is already being recorded", stream_path);
+            return Ok(Some(session));
+        }
+        warn!(
+            "Finishing stale live session {} of {}",
+            session.id, stream_path
+        );
+        if let Err(e) = stream_ended(state, stream_path).await {
+            error!(
+                "Failed to finish stale live session {}: {:#}",
+                session.id, e
+            );
+        }
+    }
+    let owner_id = target.owner_id.clone();
+
+    let storage = &state.storage_config.user_storage;
+    let vault_id = common::services::vault_service::get_or_create_default_vault(
+        state.vault_repo.as_ref(),
+        storage,
+        &owner_id,
+    )
+    .await
+    .context("Failed to get or create vault")?;
+
+    let temp_dir = storage.temp_dir();
+    tokio::fs::create_dir_all(&temp_dir)
+        .await
+        .context("Failed to create temp directory")?;
+    let recording_path = temp_dir.join(format!("live-{}.mkv", uuid::Uuid::new_v4()));
+
+    let started = OffsetDateTime::now_utc();
+    let slug = live_slug(started);
+    let insert = MediaInsert {
+        slug: slug.clone(),
+        media_type: "video".to_string(),
+        video_type: Some("hls".to_string()),
+        title: live_title(target.name.as_deref(), started),
+        description: None,
+        filename: "original.mkv".to_string(),
+        original_filename: Some(format!("{}.mkv", slug)),
+        mime_type: "video/x-matroska".to_string(),
+        file_size: 0,
+        is_public: target.is_public as i32,
+        user_id: Some(owner_id),
+        group_id: target.group_id,
+        vault_id: Some(vault_id),
+        status: MEDIA_STATUS_LIVE.to_string(),
+        featured: 0,
+        category: None,
+        thumbnail_url: None,
+        allow_download: 1,
+        allow_comments: 1,
+        mature_content: 0,
+        tenant_id: tenant_id.to_string(),
+        content_hash: None,
+        file_hash: None,
+    };
+    let media_id = state.repo.insert_media_item(&insert).await?;
+    let session_id = state
+        .repo
+        .start_live_session(media_id, stream_path, &recording_path.to_string_lossy())
+        .await?;
+
+    match spawn_recorder(state, stream_path, &recording_path) {
+        Ok(child) => state.live.insert_recorder(stream_path, child),
+        Err(e) => {
+            error!("Stream {} will not be recorded: {:#}", stream_path, e);
+            state
+                .repo
+                .end_live_session(session_id, LIVE_SESSION_FAILED, 0, 0)
+                .await?;
+            state.repo.update_media_status_error(&slug, "video").await?;
+            return Err(e);
+        }
+    }
+
+    info!("Recording stream {} into {}", stream_path, slug);
+    Ok(state.repo.get_active_live_session(stream_path).await?)
+}
+
+/// A stream ended: stop recording and queue the recording for transcoding
+///
+/// Returns `None` when no session was recording the stream.
+pub async fn stream_ended(
+    state: &VideoManagerState,
+    stream_path: &str,
+) -> Result<Option<LiveSession>> {
+    let Some(session) = state.repo.get_active_live_session(stream_path).await? else {
+        return Ok(None);
+    };
+
+    match state.live.take_recorder(stream_path) {
+        Some(child) => stop_recorder(child).await,
+        // The server restarted while the stream was live
+        None => warn!("No recorder running for stream {}", stream_path),
+    }
+    let (peak_viewers, total_viewers) = state.live.take_viewer_counts(stream_path);
+
+    let recording_path = Path::new(&session.recording_path);
+    let size = tokio::fs::metadata(recording_path)
+        .await
+        .map(|m| m.len())
+        .unwrap_or(0);
+    if size == 0 {
+        warn!("Nothing was recorded from stream {}", stream_path);
+        let _ = tokio::fs::remove_file(recording_path).await;
+        state
+            .repo
+            .end_live_session(session.id, LIVE_SESSION_FAILED, peak_viewers, total_viewers)
+            .await?;
+        state
+            .repo
+            .update_media_status_error(&session.slug, "video")
+            .await?;
+        return Ok(Some(session));
+    }
+
+    state
+        .repo
+        .end_live_session(session.id, LIVE_SESSION_ENDED, peak_viewers, total_viewers)
+        .await?;
+
+    let item = state
+        .repo
+        .get_media_by_slug(&session.slug)
+        .await?
+        .context("Live media item disappeared")?;
+    let upload_id = uuid::Uuid::new_v4().to_string();
+    let original_filename = format!("{}.mkv", session.slug);
+
+    state.progress_tracker.init_upload(
+        upload_id.clone(),
+        session.slug.clone(),
+        Some(original_filename.clone()),
+        Some(size),
+    );
+    state.progress_tracker.update(
+        &upload_id,
+        crate::progress::ProgressStatus::Processing,
+        20,
+        "Queued for processing".to_string(),
+    );
+
+    let job = db::jobs::CreateTranscodeJob {
+        upload_id,
+        slug: session.slug.clone(),
+        vault_id: item.vault_id.unwrap_or_default(),
+        user_id: item.user_id,
+        source_path: session.recording_path.clone(),
+        original_filename,
+        is_public: item.is_public == 1,
+        priority: 0,
+        max_attempts: 3,
+    };
+    if let Err(e) = state.transcode_queue.enqueue(&job).await {
+        error!(
+            "Failed to queue transcoding of stream {}: {:#}",
+            stream_path, e
+        );
+        let _ = state
+            .repo
+            .update_media_status_error(&session.slug, "video")
+            .await;
+        return Err(e);
+    }
+
+    info!(
+        "Stream {} ended ({} bytes recorded, peak {} / total {} viewers); queued {} for transcoding",
+        stream_path, size, peak_viewers, total_viewers, session.slug
+    );
+    Ok(state.repo.get_live_session(session.media_id).await?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_viewer_stats() {
+        let start = Instant::now();
+        let mut stats = ViewerStats::default();
+        stats.record("alice", start);
+        stats.record("bob", start + Duration::from_secs(5));
+        stats.record("alice", start + Duration::from_secs(10));
+        assert_eq!(stats.current(start + Duration::from_secs(10)), 2);
+
+        // bob stopped watching, carol joined later
+        stats.record("carol", start + Duration::from_secs(60));
+        assert_eq!(stats.current(start + Duration::from_secs(60)), 1);
+        assert_eq!(stats.peak(), 2);
+        assert_eq!(stats.total(), 3);
+    }
+
+    #[test]
+    fn test_stream_paths() {
+        assert!(is_valid_stream_path("live"));
+        assert!(is_valid_stream_path("studio/cam-1"));
+        assert!(!is_valid_stream_path(""));
+        assert!(!is_valid_stream_path("/live"));
+        assert!(!is_valid_stream_path("live/../etc"));
+        assert!(!is_valid_stream_path("live?token=x"));
+    }
+
+    #[test]
+    fn test_live_title_and_slug() {
+        let started = OffsetDateTime::from_unix_timestamp(1_776_252_600).unwrap();
+        assert_eq!(
+            live_title(None, started),
+            "Live stream 2026-04-15 11:30 UTC"
+        );
+        assert_eq!(
+            live_title(Some("Town hall"), started),
+            "Town hall 2026-04-15 11:30 UTC"
+        );
+        let slug = live_slug(started);
+        assert!(slug.starts_with("live-20260415-1130-"));
+        assert_eq!(slug.len(), "live-20260415-1130-".len() + 8);
+    }
+}
@@ -0,0 +1,581 @@
+//! Live stream definitions and publish keys
+//!
+//! Every stream definition has its own MediaMTX path and publish key. The
+//! publisher sends the key with the stream (`rtmp://host:1935/{path}?key=…`)
+//! and MediaMTX checks it through the HTTP auth hook
+//! ([`stream_auth_handler`], `authMethod: http` in `mediamtx.yml`). Only the
+//! key's SHA-256 is stored; owners can rotate it and let it expire.
+//!
+//! Viewing through `/hls/{path}/` follows the stream's access setting:
+//! private (owner only), public, access code (`?code=`) or group members.
+//! The single `live` path with the `RTMP_PUBLISH_TOKEN` token keeps working.
+
+use crate::live::{LiveConfig, RecordingTarget};
+use crate::{VideoManagerState, LIVE_STREAM_KEY};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use db::media::{
+    LiveStream, LiveStreamSettings, LIVE_ACCESS_CODE, LIVE_ACCESS_GROUP, LIVE_ACCESS_PRIVATE,
+    LIVE_ACCESS_PUBLIC,
+};
+use db::DbError;
+use rand::Rng;
+use serde::Deserialize;
+use std::net::IpAddr;
+use std::sync::Arc;
+use subtle::ConstantTimeEq;
+use time::format_description::well_known::Rfc3339;
+use time::{OffsetDateTime, UtcOffset};
+use tower_sessions::Session;
+use tracing::{info, warn};
+
+/// Prefix of generated publish keys
+const PUBLISH_KEY_PREFIX: &str = "lsk";
+
+/// Random characters in generated stream paths and access codes
+const CHARSET: &[u8] = b"abcdefghijklmnopqrstuvwxyz0123456789";
+
+fn random_string(len: usize) -> String {
+    let mut rng = rand::thread_rng();
+    (0..len)
+        .map(|_| CHARSET[rng.gen_range(0..CHARSET.len())] as char)
+        .collect()
+}
+
+/// New MediaMTX path for a stream definition
+fn generate_stream_path() -> String {
+    format!("stream-{}", random_string(12))
+}
+
+/// New viewer access code
+fn generate_access_code() -> String {
+    random_string(10)
+}
+
+/// New publish key with its hash and display prefix
+fn generate_publish_key() -> (String, String, String) {
+    let key = api_keys::generator::generate_api_key(PUBLISH_KEY_PREFIX);
+    let hash = api_keys::generator::hash_api_key(&key);
+    let prefix = api_keys::generator::extract_prefix(&key);
+    (key, hash, prefix)
+}
+
+/// Normalise an RFC 3339 timestamp to the `YYYY-MM-DD HH:MM:SS` UTC form
+/// SQLite's `datetime('now')` compares against
+pub fn parse_expires_at(value: &str) -> Result<String, String> {
+    let parsed = OffsetDateTime::parse(value.trim(), &Rfc3339)
+        .map_err(|_| "expires_at must be an RFC 3339 timestamp".to_string())?
+        .to_offset(UtcOffset::UTC);
+    Ok(format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+        parsed.year(),
+        u8::from(parsed.month()),
+        parsed.day(),
+        parsed.hour(),
+        parsed.minute(),
+        parsed.second()
+    ))
+}
+
+/// Value of `name` in a query string such as MediaMTX's `$MTX_QUERY`
+pub fn query_param<'a>(query: &'a str, name: &str) -> Option<&'a str> {
+    query
+        .trim_start_matches('?')
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(k, _)| *k == name)
+        .map(|(_, v)| v)
+        .filter(|v| !v.is_empty())
+}
+
+/// Who may publish to a path
+#[derive(Debug, Clone)]
+pub enum Publisher {
+    /// The `live` path with the global `RTMP_PUBLISH_TOKEN`
+    Legacy,
+    /// A stream definition with its own key
+    Stream(Box<LiveStream>),
+}
+
+impl Publisher {
+    /// Where a session of this publisher is recorded, if at all
+    pub fn recording_target(&self, config: &LiveConfig) -> Option<RecordingTarget> {
+        match self {
+            Publisher::Legacy => config.owner_id.clone().map(|owner_id| RecordingTarget {
+                owner_id,
+                name: None,
+                is_public: false,
+                group_id: None,
+            }),
+            Publisher::Stream(stream) if stream.record => Some(RecordingTarget {
+                owner_id: stream.user_id.clone(),
+                name: Some(stream.name.clone()),
+                is_public: stream.access == LIVE_ACCESS_PUBLIC,
+                group_id: stream
+                    .group_id
+                    .filter(|_| stream.access == LIVE_ACCESS_GROUP),
+            }),
+            Publisher::Stream(_) => None,
+        }
+    }
+}
+
+/// Compare a presented secret in constant time
+fn secrets_match(presented: &str, expected: &str) -> bool {
+    presented.as_bytes().ct_eq(expected.as_bytes()).into()
+}
+
+/// Check a publish key for a path
+///
+/// Expiry only applies to new publishes (`allow_expired = false`); a stream
+/// that outlives its key can still report that it ended.
+pub async fn authorize_publish(
+    state: &VideoManagerState,
+    stream_path: &str,
+    key: Option<&str>,
+    allow_expired: bool,
+) -> Result<Option<Publisher>, DbError> {
+    let Some(key) = key.filter(|k| !k.is_empty()) else {
+        return Ok(None);
+    };
+
+    if let Some(stream) = state.repo.get_live_stream_by_path(stream_path).await? {
+        if !api_keys::generator::verify_key(key, &stream.key_hash) {
+            return Ok(None);
+        }
+        if stream.expired && !allow_expired {
+            info!("Publish key of stream {} has expired", stream_path);
+            return Ok(None);
+        }
+        return Ok(Some(Publisher::Stream(Box::new(stream))));
+    }
+
+    if stream_path == LIVE_STREAM_KEY && secrets_match(key, &state.rtmp_publish_token) {
+        return Ok(Some(Publisher::Legacy));
+    }
+    Ok(None)
+}
+
+/// Whether a viewer may watch a stream
+pub async fn can_view(
+    state: &VideoManagerState,
+    stream: &LiveStream,
+    user_id: Option<&str>,
+    code: Option<&str>,
+) -> bool {
+    if user_id == Some(stream.user_id.as_str()) {
+        return true;
+    }
+    match stream.access.as_str() {
+        LIVE_ACCESS_PUBLIC => true,
+        LIVE_ACCESS_CODE => match (code, stream.access_code.as_deref()) {
+            (Some(code), Some(expected)) => secrets_match(code, expected),
+            _ => false,
+        },
+        LIVE_ACCESS_GROUP => match (user_id, stream.group_id) {
+            (Some(user_id), Some(group_id)) => state
+                .access_control
+                .repository()
+                .is_user_in_group(user_id, group_id)
+                .await
+                .unwrap_or(false),
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+/// Append `query` to every URI in an HLS playlist
+///
+/// Players resolve segment and sub-playlist URIs relative to the playlist
+/// without its query string, so an access code or URL signature has to be
+/// carried into them.
+pub fn append_query_to_playlist(playlist: &str, query: &str) -> String {
+    let with_query = |uri: &str| {
+        let separator = if uri.contains('?') { '&' } else { '?' };
+        format!("{}{}{}", uri, separator, query)
+    };
+
+    let mut out = String::with_capacity(playlist.len());
+    for line in playlist.lines() {
+        if !line.is_empty() && !line.starts_with('#') {
+            out.push_str(&with_query(line));
+        } else if let Some((before, rest)) = line.split_once("URI=\"") {
+            match rest.split_once('"') {
+                Some((uri, after)) => {
+                    out.push_str(before);
+                    out.push_str("URI=\"");
+                    out.push_str(&with_query(uri));
+                    out.push('"');
+                    out.push_str(after);
+                }
+                None => out.push_str(line),
+            }
+        } else {
+            out.push_str(line);
+        }
+        out.push('\n');
+    }
+    out
+}
+
+// -------------------------------
+// MediaMTX Auth Hook
+// -------------------------------
+
+/// Request body of MediaMTX's HTTP authentication
+#[derive(Debug, Deserialize)]
+pub struct MediaMtxAuthRequest {
+    #[serde(default)]
+    pub password: Option<String>,
+    #[serde(default)]
+    pub ip: String,
+    /// `publish`, `read`, `playback`, `api`, `metrics` or `pprof`
+    pub action: String,
+    #[serde(default)]
+    pub path: String,
+    #[serde(default)]
+    pub query: String,
+}
+
+fn is_loopback(ip: &str) -> bool {
+    ip.parse::<IpAddr>()
+        .map(|ip| ip.is_loopback())
+        .unwrap_or(false)
+}
+
+/// POST /api/stream/auth - MediaMTX HTTP authentication hook
+///
+/// Publishing needs the stream's key (`?key=` or the password). Reading is
+/// open to this server (the recorder and the `/hls/` proxy, which enforces
+/// viewing permissions) and to public streams.
+#[tracing::instrument(skip(state, request), fields(action = %request.action, path = %request.path))]
+pub async fn stream_auth_handler(
+    State(state): State<Arc<VideoManagerState>>,
+    Json(request): Json<MediaMtxAuthRequest>,
+) -> StatusCode {
+    match request.action.as_str() {
+        "publish" => {
+            let key = query_param(&request.query, "key")
+                .or_else(|| query_param(&request.query, "token"))
+                .or(request.password.as_deref());
+            match authorize_publish(&state, &request.path, key, false).await {
+                Ok(Some(_)) => StatusCode::OK,
+                Ok(None) => {
+                    warn!("Publishing to {} rejected: invalid key", request.path);
+                    StatusCode::UNAUTHORIZED
+                }
+                Err(e) => {
+                    tracing::error!("Failed to check publish key: {}", e);
+                    StatusCode::INTERNAL_SERVER_ERROR
+                }
+            }
+        }
+        "read" | "playback" => {
+            // The legacy path has always been readable directly
+            if is_loopback(&request.ip) || request.path == LIVE_STREAM_KEY {
+                return StatusCode::OK;
+            }
+            match state.repo.get_live_stream_by_path(&request.path).await {
+                Ok(Some(stream)) if stream.access == LIVE_ACCESS_PUBLIC => StatusCode::OK,
+                Ok(_) => StatusCode::UNAUTHORIZED,
+                Err(e) => {
+                    tracing::error!("Failed to load live stream: {}", e);
+                    StatusCode::INTERNAL_SERVER_ERROR
+                }
+            }
+        }
+        _ if is_loopback(&request.ip) => StatusCode::OK,
+        _ => StatusCode::UNAUTHORIZED,
+    }
+}
+
+// -------------------------------
+// Stream Definition Handlers
+// -------------------------------
+
+type ApiResult<T> = Result<T, (StatusCode, String)>;
+
+fn internal_error(e: impl std::fmt::Display) -> (StatusCode, String) {
+    tracing::error!("Live stream error: {}", e);
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        "Internal server error".to_string(),
+    )
+}
+
+async fn session_user(session: &Session) -> ApiResult<String> {
+    session
+        .get::<String>("user_id")
+        .await
+        .ok()
+        .flatten()
+        .ok_or((StatusCode::UNAUTHORIZED, "Unauthorized".to_string()))
+}
+
+/// Load a stream definition and check that it belongs to the session user
+async fn load_owned_stream(
+    session: &Session,
+    state: &VideoManagerState,
+    id: i64,
+) -> ApiResult<LiveStream> {
+    let user_id = session_user(session).await?;
+    state
+        .repo
+        .get_live_stream(id)
+        .await
+        .map_err(internal_error)?
+        // Report someone else's stream as missing rather than forbidden
+        .filter(|stream| stream.user_id == user_id)
+        .ok_or((StatusCode::NOT_FOUND, "Stream not found".to_string()))
+}
+
+/// Stream definition settings sent by the owner
+#[derive(Debug, Deserialize)]
+pub struct LiveStreamRequest {
+    pub name: String,
+    /// `private` (default), `public`, `code` or `group`
+    pub access: Option<String>,
+    pub group_id: Option<i32>,
+    /// RFC 3339; omit for a key that never expires
+    pub expires_at: Option<String>,
+    pub record: Option<bool>,
+}
+
+/// Validate a request into settings, keeping an existing access code
+async fn settings_from_request(
+    state: &VideoManagerState,
+    user_id: &str,
+    request: LiveStreamRequest,
+    existing: Option<&LiveStream>,
+) -> ApiResult<LiveStreamSettings> {
+    let bad_request = |msg: &str| (StatusCode::BAD_REQUEST, msg.to_string());
+
+    let name = request.name.trim().to_string();
+    if name.is_empty() || name.chars().count() > 100 {
+        return Err(bad_request("Name must be 1-100 characters"));
+    }
+
+    let access = request
+        .access
+        .unwrap_or_else(|| LIVE_ACCESS_PRIVATE.to_string());
+    let (access_code, group_id) = match access.as_str() {
+        LIVE_ACCESS_PRIVATE | LIVE_ACCESS_PUBLIC => (None, None),
+        LIVE_ACCESS_CODE => (
+            Some(
+                existing
+                    .and_then(|s| s.access_code.clone())
+                    .unwrap_or_else(generate_access_code),
+            ),
+            None,
+        ),
+        LIVE_ACCESS_GROUP => {
+            let group_id = request
+                .group_id
+                .ok_or_else(|| bad_request("group_id is required for group access"))?;
+            let member = state
+                .access_control
+                .repository()
+                .is_user_in_group(user_id, group_id)
+                .await
+                .map_err(internal_error)?;
+            if !member {
+                return Err(bad_request("You are not a member of this group"));
+            }
+            (None, Some(group_id))
+        }
+        _ => {
+            return Err(bad_request(
+                "access must be one of private, public, code or group",
+            ))
+        }
+    };
+
+    let expires_at = request
+        .expires_at
+        .filter(|v| !v.trim().is_empty())
+        .map(|v| parse_expires_at(&v))
+        .transpose()
+        .map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+
+    Ok(LiveStreamSettings {
+        name,
+        expires_at,
+        access,
+        access_code,
+        group_id,
+        record: request.record.unwrap_or(true),
+    })
+}
+
+/// GET /api/live/streams - List the current user's stream definitions
+#[tracing::instrument(skip(session, state))]
+pub async fn list_streams_handler(
+    session: Session,
+    State(state): State<Arc<VideoManagerState>>,
+) -> ApiResult<Json<serde_json::Value>> {
+    let user_id = session_user(&session).await?;
+    let streams = state
+        .repo
+        .list_live_streams(&user_id)
+        .await
+        .map_err(internal_error)?;
+    Ok(Json(serde_json::json!({ "streams": streams })))
+}
+
+/// POST /api/live/streams - Create a stream definition
+///
+/// The publish key is only returned here and when it is rotated.
+#[tracing::instrument(skip(session, state, request))]
+pub async fn create_stream_handler(
+    session: Session,
+    State(state): State<Arc<VideoManagerState>>,
+    Json(request): Json<LiveStreamRequest>,
+) -> ApiResult<(StatusCode, Json<serde_json::Value>)> {
+    let user_id = session_user(&session).await?;
+    let settings = settings_from_request(&state, &user_id, request, None).await?;
+
+    let stream_path = generate_stream_path();
+    let (key, key_hash, key_prefix) = generate_publish_key();
+    let id = state
+        .repo
+        .create_live_stream(&user_id, &stream_path, &key_hash, &key_prefix, &settings)
+        .await
+        .map_err(internal_error)?;
+    let stream = state
+        .repo
+        .get_live_stream(id)
+        .await
+        .map_err(internal_error)?
+        .ok_or_else(|| internal_error("created stream disappeared"))?;
+
+    info!("Live stream {} created at {}", id, stream_path);
+    Ok((
+        StatusCode::CREATED,
+        Json(serde_json::json!({ "stream": stream, "publish_key": key })),
+    ))
+}
+
+/// PUT /api/live/streams/{id} - Update a stream definition
+#[tracing::instrument(skip(session, state, request))]
+pub async fn update_stream_handler(
+    session: Session,
+    State(state): State<Arc<VideoManagerState>>,
+    Path(id): Path<i64>,
+    Json(request): Json<LiveStreamRequest>,
+) -> ApiResult<Json<serde_json::Value>> {
+    let stream = load_owned_stream(&session, &state, id).await?;
+    let settings = settings_from_request(&state, &stream.user_id, request, Some(&stream)).await?;
+    state
+        .repo
+        .update_live_stream(id, &settings)
+        .await
+        .map_err(internal_error)?;
+    let stream = load_owned_stream(&session, &state, id).await?;
+    Ok(Json(serde_json::json!({ "stream": stream })))
+}
+
+/// POST /api/live/streams/{id}/rotate-key - Replace the publish key
+///
+/// A publisher that is already live keeps streaming; the new key is needed
+/// from the next publish on.
+#[tracing::instrument(skip(session, state))]
+pub async fn rotate_key_handler(
+    session: Session,
+    State(state): State<Arc<VideoManagerState>>,
+    Path(id): Path<i64>,
+) -> ApiResult<Json<serde_json::Value>> {
+    load_owned_stream(&session, &state, id).await?;
+    let (key, key_hash, key_prefix) = generate_publish_key();
+    state
+        .repo
+        .rotate_live_stream_key(id, &key_hash, &key_prefix)
+        .await
+        .map_err(internal_error)?;
+    let stream = load_owned_stream(&session, &state, id).await?;
+
+    info!("Publish key of live stream {} rotated", id);
+    Ok(Json(
+        serde_json::json!({ "stream": stream, "publish_key": key }),
+    ))
+}
+
+/// DELETE /api/live/streams/{id} - Delete a stream definition
+///
+/// Recordings of the stream stay in the media library.
+#[tracing::instrument(skip(session, state))]
+pub async fn delete_stream_handler(
+    session: Session,
+    State(state): State<Arc<VideoManagerState>>,
+    Path(id): Path<i64>,
+) -> ApiResult<StatusCode> {
+    load_owned_stream(&session, &state, id).await?;
+    state
+        .repo
+        .delete_live_stream(id)
+        .await
+        .map_err(internal_error)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_expires_at() {
+        assert_eq!(
+            parse_expires_at("2026-05-01T12:30:00+02:00").unwrap(),
+            "2026-05-01 10:30:00"
+        );
+        assert_eq!(
+            parse_expires_at("2026-05-01T10:30:00Z").unwrap(),
+            "2026-05-01 10:30:00"
+        );
+        assert!(parse_expires_at("tomorrow").is_err());
+    }
+
+    #[test]
+    fn test_query_param() {
+        assert_eq!(query_param("key=abc&x=1", "key"), Some("abc"));
+        assert_eq!(query_param("?token=t", "token"), Some("t"));
+        assert_eq!(query_param("key=", "key"), None);
+        assert_eq!(query_param("", "key"), None);
+    }
+
+    #[test]
+    fn test_append_query_to_playlist() {
+        let playlist = "#EXTM3U\n\
+            #EXT-X-MAP:URI=\"init.mp4\"\n\
+            #EXT-X-PART:DURATION=0.1,URI=\"part0.mp4\"\n\
+            #EXTINF:1.0,\n\
+            seg0.mp4\n\
+            stream.m3u8?_HLS_msn=3\n";
+        let out = append_query_to_playlist(playlist, "code=abc");
+        assert!(out.contains("#EXT-X-MAP:URI=\"init.mp4?code=abc\"\n"));
+        assert!(out.contains("URI=\"part0.mp4?code=abc\"\n"));
+        assert!(out.contains("\nseg0.mp4?code=abc\n"));
+        assert!(out.contains("\nstream.m3u8?_HLS_msn=3&code=abc\n"));
+        assert!(out.starts_with("#EXTM3U\n#EXT-X-MAP"));
+    }
+
+    #[test]
+    fn test_secrets_match() {
+        assert!(secrets_match("abc123", "abc123"));
+        assert!(!secrets_match("abc124", "abc123"));
+        assert!(!secrets_match("abc", "abc123"));
+        assert!(!secrets_match("abc", ""));
+    }
+
+    #[test]
+    fn test_generated_values() {
+        assert!(crate::live::is_valid_stream_path(&generate_stream_path()));
+        let (key, hash, prefix) = generate_publish_key();
+        assert!(key.starts_with("lsk_"));
+        assert!(key.starts_with(&prefix));
+        assert!(api_keys::generator::verify_key(&key, &hash));
+    }
+}
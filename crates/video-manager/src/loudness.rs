@@ -0,0 +1,251 @@
+//! EBU R128 loudness normalisation
+//!
+//! Recordings from different sources play at very different volumes. When an
+//! [`EncodingProfile`](crate::encoding::EncodingProfile) sets `loudness`, each
+//! audio stream is measured once with ffmpeg's `loudnorm` filter and the
+//! measured values are fed into a second, linear `loudnorm` pass applied while
+//! encoding the HLS renditions:
+//!
+//! ```yaml
+//! loudness:
+//!   integrated: -23   # LUFS (EBU R128); streaming services often use -16
+//!   true_peak: -1     # dBTP
+//!   range: 7          # LU
+//! ```
+
+use crate::ffmpeg::FFmpegConfig;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::process::Stdio;
+use tokio::process::Command;
+use tracing::info;
+
+/// Loudness targets for the `loudnorm` filter
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LoudnessTarget {
+    /// Integrated loudness in LUFS
+    #[serde(default = "default_integrated")]
+    pub integrated: f64,
+    /// Maximum true peak in dBTP
+    #[serde(default = "default_true_peak")]
+    pub true_peak: f64,
+    /// Loudness range in LU
+    #[serde(default = "default_range")]
+    pub range: f64,
+}
+
+fn default_integrated() -> f64 {
+    -23.0
+}
+
+fn default_true_peak() -> f64 {
+    -1.0
+}
+
+fn default_range() -> f64 {
+    7.0
+}
+
+impl Default for LoudnessTarget {
+    fn default() -> Self {
+        Self {
+            integrated: default_integrated(),
+            true_peak: default_true_peak(),
+            range: default_range(),
+        }
+    }
+}
+
+impl LoudnessTarget {
+    /// Check the targets against the ranges `loudnorm` accepts
+    pub fn validate(&self) -> Result<()> {
+        if !(-70.0..=-5.0).contains(&self.integrated) {
+            anyhow::bail!(
+                "Integrated loudness must be between -70 and -5 LUFS (got {})",
+                self.integrated
+            );
+        }
+        if !(-9.0..=0.0).contains(&self.true_peak) {
+            anyhow::bail!(
+                "True peak must be between -9 and 0 dBTP (got {})",
+                self.true_peak
+            );
+        }
+        if !(1.0..=50.0).contains(&self.range) {
+            anyhow::bail!(
+                "Loudness range must be between 1 and 50 LU (got {})",
+                self.range
+            );
+        }
+        Ok(())
+    }
+
+    /// Filter arguments shared by both passes
+    fn filter_base(&self) -> String {
+        format!(
+            "loudnorm=I={}:TP={}:LRA={}",
+            self.integrated, self.true_peak, self.range
+        )
+    }
+}
+
+/// First-pass measurement of one audio stream
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoudnessMeasurement {
+    /// Integrated loudness in LUFS
+    pub integrated: f64,
+    /// True peak in dBTP
+    pub true_peak: f64,
+    /// Loudness range in LU
+    pub range: f64,
+    /// Gating threshold in LUFS
+    pub threshold: f64,
+    /// Gain offset suggested for the second pass
+    pub target_offset: f64,
+}
+
+/// `loudnorm` reports its measurements as strings
+#[derive(Debug, Deserialize)]
+struct LoudnormReport {
+    input_i: String,
+    input_tp: String,
+    input_lra: String,
+    input_thresh: String,
+    target_offset: String,
+}
+
+/// Parse the JSON block `loudnorm=print_format=json` writes to stderr
+///
+/// Returns `None` when the block is missing or the stream is silent
+/// (`-inf`), in which case there is nothing to normalise.
+pub fn parse_loudnorm_output(stderr: &str) -> Option<LoudnessMeasurement> {
+    let start = stderr.rfind('{')?;
+    let end = stderr[start..].find('}')? + start;
+    let report: LoudnormReport = serde_json::from_str(&stderr[start..=end]).ok()?;
+
+    let value = |s: &str| s.trim().parse::<f64>().ok().filter(|v| v.is_finite());
+    Some(LoudnessMeasurement {
+        integrated: value(&report.input_i)?,
+        true_peak: value(&report.input_tp)?,
+        range: value(&report.input_lra)?,
+        threshold: value(&report.input_thresh)?,
+        target_offset: value(&report.target_offset)?,
+    })
+}
+
+/// Measure one audio stream (`stream` counts audio streams only)
+pub async fn measure_loudness(
+    config: &FFmpegConfig,
+    input_path: &Path,
+    stream: usize,
+    target: &LoudnessTarget,
+) -> Result<Option<LoudnessMeasurement>> {
+    let output = Command::new(&config.ffmpeg_path)
+        .args(["-hide_banner", "-nostats"])
+        .arg("-i")
+        .arg(input_path)
+        .args(["-map", &format!("0:a:{}", stream)])
+        .args([
+            "-af",
+            &format!("{}:print_format=json", target.filter_base()),
+        ])
+        .args(["-f", "null", "-"])
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .output()
+        .await
+        .context("Failed to execute FFmpeg for loudness measurement")?;
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if !output.status.success() {
+        anyhow::bail!(
+            "Loudness measurement failed with status: {}\nError: {}",
+            output.status,
+            stderr.lines().rev().take(5).collect::<Vec<_>>().join("\n")
+        );
+    }
+
+    let measurement = parse_loudnorm_output(&stderr);
+    if let Some(m) = &measurement {
+        info!(
+            "Audio stream {} measured at {:.1} LUFS, {:.1} dBTP, {:.1} LU",
+            stream, m.integrated, m.true_peak, m.range
+        );
+    }
+    Ok(measurement)
+}
+
+/// Second-pass `loudnorm` filter using the measured values
+pub fn loudnorm_filter(target: &LoudnessTarget, measured: &LoudnessMeasurement) -> String {
+    format!(
+        "{}:measured_I={}:measured_TP={}:measured_LRA={}:measured_thresh={}:offset={}:linear=true",
+        target.filter_base(),
+        measured.integrated,
+        measured.true_peak,
+        measured.range,
+        measured.threshold,
+        measured.target_offset
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const REPORT: &str = r#"[Parsed_loudnorm_0 @ 0x5581]
+{
+	"input_i" : "-27.61",
+	"input_tp" : "-4.47",
+	"input_lra" : "18.06",
+	"input_thresh" : "-39.20",
+	"output_i" : "-23.02",
+	"output_tp" : "-1.00",
+	"output_lra" : "6.20",
+	"output_thresh" : "-33.57",
+	"normalization_type" : "dynamic",
+	"target_offset" : "0.02"
+}
+"#;
+
+    #[test]
+    fn test_parse_loudnorm_output() {
+        let m = parse_loudnorm_output(REPORT).unwrap();
+        assert_eq!(m.integrated, -27.61);
+        assert_eq!(m.true_peak, -4.47);
+        assert_eq!(m.range, 18.06);
+        assert_eq!(m.threshold, -39.2);
+        assert_eq!(m.target_offset, 0.02);
+    }
+
+    #[test]
+    fn test_silent_or_missing_report() {
+        assert!(parse_loudnorm_output("no report here").is_none());
+        let silent = REPORT.replace("\"-27.61\"", "\"-inf\"");
+        assert!(parse_loudnorm_output(&silent).is_none());
+    }
+
+    #[test]
+    fn test_loudnorm_filter() {
+        let m = parse_loudnorm_output(REPORT).unwrap();
+        assert_eq!(
+            loudnorm_filter(&LoudnessTarget::default(), &m),
+            "loudnorm=I=-23:TP=-1:LRA=7:measured_I=-27.61:measured_TP=-4.47:measured_LRA=18.06:measured_thresh=-39.2:offset=0.02:linear=true"
+        );
+    }
+
+    #[test]
+    fn test_validate_targets() {
+        assert!(LoudnessTarget::default().validate().is_ok());
+        let loud = LoudnessTarget {
+            integrated: 0.0,
+            ..Default::default()
+        };
+        assert!(loud.validate().is_err());
+        let clipping = LoudnessTarget {
+            true_peak: 2.0,
+            ..Default::default()
+        };
+        assert!(clipping.validate().is_err());
+    }
+}
@@ -0,0 +1,433 @@
+//! Poster frame selection and seek-preview sprites
+//!
+//! Instead of grabbing a frame at a fixed offset (which often lands on a
+//! black slide or a fade), the poster is chosen among frames just after
+//! scene changes, preferring well-exposed frames with the most contrast.
+//!
+//! Seek previews are sprite sheets of small tiles, one tile every few
+//! seconds, described by a WebVTT thumbnail track:
+//!
+//! ```text
+//! {video_dir}/sprites/thumbnails.vtt
+//! {video_dir}/sprites/sprite_000.jpg        10x10 tiles of 160x90
+//! {video_dir}/sprites/sprite_001.jpg
+//! ```
+//!
+//! Each cue's payload is `sprite_000.jpg#xywh=160,0,160,90`, relative to
+//! the track, as understood by common players' thumbnail plugins.
+
+use crate::ffmpeg::{generate_poster, generate_thumbnail, get_poster_timestamp, FFmpegConfig};
+use crate::subtitles::{render_vtt, Cue};
+use anyhow::{Context, Result};
+use std::path::Path;
+use std::process::Stdio;
+use tokio::process::Command;
+use tracing::{debug, info, warn};
+
+/// Directory under the video directory that holds the sprite sheets
+pub const SPRITES_DIR: &str = "sprites";
+
+/// Thumbnail track inside [`SPRITES_DIR`]
+pub const SPRITES_VTT: &str = "thumbnails.vtt";
+
+/// Size of one preview tile
+pub const TILE_WIDTH: u32 = 160;
+pub const TILE_HEIGHT: u32 = 90;
+
+/// Tiles per sprite sheet row and column
+const SHEET_COLUMNS: u32 = 10;
+const SHEET_ROWS: u32 = 10;
+
+/// Shortest time between two preview tiles, in seconds
+const MIN_TILE_INTERVAL: f64 = 2.0;
+
+/// Most tiles generated for one video; longer videos get sparser tiles
+const MAX_TILES: f64 = 300.0;
+
+/// Scene score above which a frame counts as a cut (0-1)
+const SCENE_THRESHOLD: f64 = 0.3;
+
+/// Most poster candidates scored per video
+const MAX_POSTER_CANDIDATES: usize = 8;
+
+/// Size of the grayscale frames used to score poster candidates
+const SCORE_WIDTH: usize = 64;
+const SCORE_HEIGHT: usize = 36;
+
+/// File name of a sprite sheet
+pub fn sprite_file_name(index: u32) -> String {
+    format!("sprite_{:03}.jpg", index)
+}
+
+/// Whether `name` is a file that belongs in the sprites directory
+pub fn is_sprite_file(name: &str) -> bool {
+    if name == SPRITES_VTT {
+        return true;
+    }
+    name.strip_prefix("sprite_")
+        .and_then(|rest| rest.strip_suffix(".jpg"))
+        .is_some_and(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()))
+}
+
+/// Seconds between preview tiles for a video of `duration` seconds
+pub fn tile_interval(duration: f64) -> f64 {
+    (duration / MAX_TILES).ceil().max(MIN_TILE_INTERVAL)
+}
+
+/// WebVTT thumbnail track for sprite sheets cut every `interval` seconds
+pub fn render_sprite_vtt(duration: f64, interval: f64) -> String {
+    let per_sheet = SHEET_COLUMNS * SHEET_ROWS;
+    let tiles = (duration / interval).ceil().max(1.0) as u32;
+
+    let cues: Vec<Cue> = (0..tiles)
+        .map(|i| {
+            let position = i % per_sheet;
+            let x = (position % SHEET_COLUMNS) * TILE_WIDTH;
+            let y = (position / SHEET_COLUMNS) * TILE_HEIGHT;
+            Cue {
+                start: i as f64 * interval,
+                end: ((i + 1) as f64 * interval).min(duration),
+                settings: String::new(),
+                text: vec![format!(
+                    "{}#xywh={},{},{},{}",
+                    sprite_file_name(i / per_sheet),
+                    x,
+                    y,
+                    TILE_WIDTH,
+                    TILE_HEIGHT
+                )],
+            }
+        })
+        .collect();
+
+    render_vtt(&cues, None)
+}
+
+/// Generate the sprite sheets and thumbnail track under `{video_dir}/sprites`
+pub async fn generate_sprites(
+    config: &FFmpegConfig,
+    video_path: &Path,
+    video_dir: &Path,
+    duration: f64,
+) -> Result<()> {
+    if duration <= 0.0 {
+        anyhow::bail!("Cannot generate previews for a video without duration");
+    }
+    let sprites_dir = video_dir.join(SPRITES_DIR);
+    // Replace sheets from an earlier run, which may have had more tiles
+    let _ = tokio::fs::remove_dir_all(&sprites_dir).await;
+    tokio::fs::create_dir_all(&sprites_dir)
+        .await
+        .context("Failed to create sprites directory")?;
+
+    let interval = tile_interval(duration);
+    info!(
+        "Generating preview sprites every {}s: {:?} -> {:?}",
+        interval, video_path, sprites_dir
+    );
+
+    let filter = format!(
+        "fps=1/{},scale={w}:{h}:force_original_aspect_ratio=decrease,pad={w}:{h}:(ow-iw)/2:(oh-ih)/2,tile={}x{}",
+        interval,
+        SHEET_COLUMNS,
+        SHEET_ROWS,
+        w = TILE_WIDTH,
+        h = TILE_HEIGHT
+    );
+    let output = Command::new(&config.ffmpeg_path)
+        .args(["-v", "error", "-i"])
+        .arg(video_path)
+        .args(["-an", "-sn", "-vf", &filter])
+        .args(["-q:v", "5", "-start_number", "0"])
+        .args(["-threads", &config.threads.to_string()])
+        .arg("-y")
+        .arg(sprites_dir.join("sprite_%03d.jpg"))
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .output()
+        .await
+        .context("Failed to execute ffmpeg for sprite generation")?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!(
+            "FFmpeg sprite generation failed with status: {}\nError: {}",
+            output.status,
+            stderr.lines().take(10).collect::<Vec<_>>().join("\n")
+        );
+    }
+    if !sprites_dir.join(sprite_file_name(0)).exists() {
+        anyhow::bail!("No sprite sheet was created in {:?}", sprites_dir);
+    }
+
+    tokio::fs::write(
+        sprites_dir.join(SPRITES_VTT),
+        render_sprite_vtt(duration, interval),
+    )
+    .await
+    .context("Failed to write thumbnail track")?;
+
+    info!("Preview sprites generated: {:?}", sprites_dir);
+    Ok(())
+}
+
+// ── Poster selection ────────────────────────────────────────────────
+
+/// Timestamps of scene changes, from keyframes only to keep it fast
+pub async fn detect_scene_changes(config: &FFmpegConfig, video_path: &Path) -> Result<Vec<f64>> {
+    let filter = format!("scale=160:-2,select='gt(scene,{})',showinfo", SCENE_THRESHOLD);
+    let output = Command::new(&config.ffmpeg_path)
+        .args(["-hide_banner", "-nostats", "-skip_frame", "nokey", "-i"])
+        .arg(video_path)
+        .args(["-an", "-sn", "-vf", &filter, "-vsync", "vfr", "-f", "null", "-"])
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .output()
+        .await
+        .context("Failed to execute ffmpeg for scene detection")?;
+
+    if !output.status.success() {
+        anyhow::bail!("FFmpeg scene detection failed with status: {}", output.status);
+    }
+    Ok(parse_scene_times(&String::from_utf8_lossy(&output.stderr)))
+}
+
+/// Extract `pts_time` values from ffmpeg `showinfo` output
+fn parse_scene_times(log: &str) -> Vec<f64> {
+    log.lines()
+        .filter(|line| line.contains("Parsed_showinfo"))
+        .filter_map(|line| {
+            let value = line.split("pts_time:").nth(1)?.split_whitespace().next()?;
+            value.parse::<f64>().ok()
+        })
+        .collect()
+}
+
+/// Pick the timestamps worth scoring as poster frames
+///
+/// Frames shortly after scene changes, away from the very start and end,
+/// thinned out evenly to [`MAX_POSTER_CANDIDATES`]; the fixed-offset
+/// default is always included as a fallback.
+fn poster_candidates(duration: f64, scene_changes: &[f64]) -> Vec<f64> {
+    let fallback = get_poster_timestamp(duration).max(0.0);
+    let (earliest, latest) = (duration * 0.05, duration * 0.9);
+
+    let usable: Vec<f64> = scene_changes
+        .iter()
+        // Skip the transition itself
+        .map(|t| t + 0.5)
+        .filter(|t| *t >= earliest && *t <= latest)
+        .collect();
+
+    let mut candidates = Vec::with_capacity(MAX_POSTER_CANDIDATES);
+    if usable.len() < MAX_POSTER_CANDIDATES {
+        candidates.extend(usable);
+    } else {
+        let step = usable.len() as f64 / (MAX_POSTER_CANDIDATES - 1) as f64;
+        candidates.extend((0..MAX_POSTER_CANDIDATES - 1).map(|i| usable[(i as f64 * step) as usize]));
+    }
+    candidates.push(fallback);
+    candidates
+}
+
+/// How good a grayscale frame looks as a poster
+///
+/// The standard deviation of the luma (contrast) counts most; very dark or
+/// washed-out frames (black slides, fades to white) are heavily penalised.
+fn frame_score(pixels: &[u8]) -> f64 {
+    if pixels.is_empty() {
+        return 0.0;
+    }
+    let n = pixels.len() as f64;
+    let mean = pixels.iter().map(|&p| p as f64).sum::<f64>() / n;
+    let variance = pixels.iter().map(|&p| (p as f64 - mean).powi(2)).sum::<f64>() / n;
+    let contrast = variance.sqrt();
+
+    if !(24.0..=232.0).contains(&mean) {
+        contrast * 0.2
+    } else {
+        contrast
+    }
+}
+
+/// Decode one small grayscale frame at `timestamp`
+async fn grab_gray_frame(config: &FFmpegConfig, video_path: &Path, timestamp: f64) -> Result<Vec<u8>> {
+    let output = Command::new(&config.ffmpeg_path)
+        .args(["-v", "error", "-ss", &format!("{:.3}", timestamp), "-i"])
+        .arg(video_path)
+        .args(["-frames:v", "1", "-vf"])
+        .arg(format!("scale={}:{}", SCORE_WIDTH, SCORE_HEIGHT))
+        .args(["-pix_fmt", "gray", "-f", "rawvideo", "-"])
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .await
+        .context("Failed to execute ffmpeg for frame scoring")?;
+
+    if !output.status.success() || output.stdout.len() != SCORE_WIDTH * SCORE_HEIGHT {
+        anyhow::bail!("Could not decode a frame at {:.3}s", timestamp);
+    }
+    Ok(output.stdout)
+}
+
+/// Choose a representative poster timestamp for a video
+///
+/// Never fails: falls back to the fixed-offset default when scene detection
+/// or frame decoding does not work out.
+pub async fn pick_poster_timestamp(config: &FFmpegConfig, video_path: &Path, duration: f64) -> f64 {
+    let scene_changes = match detect_scene_changes(config, video_path).await {
+        Ok(times) => times,
+        Err(e) => {
+            warn!("Scene detection failed, using default poster offset: {:#}", e);
+            Vec::new()
+        }
+    };
+
+    let mut best: Option<(f64, f64)> = None;
+    for timestamp in poster_candidates(duration, &scene_changes) {
+        match grab_gray_frame(config, video_path, timestamp).await {
+            Ok(pixels) => {
+                let score = frame_score(&pixels);
+                debug!("Poster candidate {:.3}s scores {:.1}", timestamp, score);
+                if best.is_none_or(|(_, s)| score > s) {
+                    best = Some((timestamp, score));
+                }
+            }
+            Err(e) => debug!("Skipping poster candidate: {:#}", e),
+        }
+    }
+
+    let timestamp = best
+        .map(|(t, _)| t)
+        .unwrap_or_else(|| get_poster_timestamp(duration).max(0.0));
+    info!(
+        "Poster frame at {:.3}s ({} scene changes)",
+        timestamp,
+        scene_changes.len()
+    );
+    timestamp
+}
+
+// ── Poster images ───────────────────────────────────────────────────
+
+/// Convert a thumbnail JPEG to WebP next to it and remove the JPEG
+pub async fn convert_thumbnail_to_webp(jpeg_path: &Path) -> Result<()> {
+    let webp_path = jpeg_path.with_extension("webp");
+
+    // Read JPEG file
+    let jpeg_data = tokio::fs::read(jpeg_path).await?;
+    let img = image::load_from_memory(&jpeg_data)
+        .context("Failed to load JPEG thumbnail")?;
+
+    // Encode as WebP (lossless)
+    let mut webp_data = Vec::new();
+    let encoder = image::codecs::webp::WebPEncoder::new_lossless(&mut webp_data);
+    img.write_with_encoder(encoder)
+        .context("Failed to encode WebP")?;
+
+    // Write WebP file
+    tokio::fs::write(&webp_path, &webp_data)
+        .await
+        .context("Failed to write WebP thumbnail")?;
+
+    info!("Converted thumbnail to WebP: {:?}", webp_path);
+
+    // Remove old JPEG file
+    let _ = tokio::fs::remove_file(jpeg_path).await;
+
+    Ok(())
+}
+
+/// Write the poster (`poster.jpg`, up to 1920x1080) and the list thumbnail
+/// (`{slug}_thumb.webp`, 320x180) from the frame at `timestamp`
+///
+/// `source` may also be a still image, with `timestamp` 0.
+/// `thumbnail_path` is the `.jpg` path; the WebP file is written next to it.
+pub async fn write_poster_images(
+    config: &FFmpegConfig,
+    source: &Path,
+    timestamp: f64,
+    poster_path: &Path,
+    thumbnail_path: &Path,
+) -> Result<()> {
+    generate_poster(config, source, poster_path, timestamp, 1920, 1080, 85)
+        .await
+        .context("Poster generation failed")?;
+    generate_thumbnail(config, source, thumbnail_path, timestamp, 320, 180, 85)
+        .await
+        .context("Thumbnail generation failed")?;
+    convert_thumbnail_to_webp(thumbnail_path).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tile_interval() {
+        assert_eq!(tile_interval(60.0), 2.0);
+        assert_eq!(tile_interval(600.0), 2.0);
+        assert_eq!(tile_interval(3600.0), 12.0);
+    }
+
+    #[test]
+    fn test_render_sprite_vtt() {
+        let vtt = render_sprite_vtt(205.0, 2.0);
+        let cues: Vec<&str> = vtt.lines().filter(|l| l.contains("#xywh=")).collect();
+        assert_eq!(cues.len(), 103);
+        assert_eq!(cues[0], "sprite_000.jpg#xywh=0,0,160,90");
+        assert_eq!(cues[11], "sprite_000.jpg#xywh=160,90,160,90");
+        assert_eq!(cues[100], "sprite_001.jpg#xywh=0,0,160,90");
+        // The last cue ends with the video
+        assert!(vtt.contains("00:03:24.000 --> 00:03:25.000"));
+    }
+
+    #[test]
+    fn test_is_sprite_file() {
+        assert!(is_sprite_file("thumbnails.vtt"));
+        assert!(is_sprite_file("sprite_000.jpg"));
+        assert!(is_sprite_file("sprite_012.jpg"));
+        assert!(!is_sprite_file("sprite_.jpg"));
+        assert!(!is_sprite_file("sprite_../x.jpg"));
+        assert!(!is_sprite_file("poster.jpg"));
+    }
+
+    #[test]
+    fn test_parse_scene_times() {
+        let log = "\
+[Parsed_showinfo_2 @ 0x55] config in time_base: 1/1000, frame_rate: 25/1
+[Parsed_showinfo_2 @ 0x55] n:   0 pts:  12480 pts_time:12.48   duration: 40
+[Parsed_showinfo_2 @ 0x55] n:   1 pts:  61000 pts_time:61      duration: 40
+frame=    2 fps=0.0 q=-0.0 Lsize=N/A time=00:01:01.00";
+        assert_eq!(parse_scene_times(log), vec![12.48, 61.0]);
+    }
+
+    #[test]
+    fn test_poster_candidates() {
+        // No scene changes: only the fixed-offset default
+        assert_eq!(poster_candidates(100.0, &[]), vec![25.0]);
+
+        // Changes at the very start and end are skipped
+        let candidates = poster_candidates(100.0, &[0.0, 3.0, 40.0, 99.0]);
+        assert_eq!(candidates, vec![40.5, 25.0]);
+
+        // Many changes are thinned out
+        let changes: Vec<f64> = (10..90).map(|t| t as f64).collect();
+        let candidates = poster_candidates(100.0, &changes);
+        assert_eq!(candidates.len(), MAX_POSTER_CANDIDATES);
+        assert_eq!(candidates[0], 10.5);
+    }
+
+    #[test]
+    fn test_frame_score() {
+        let black = vec![0u8; 64];
+        let flat_grey = vec![128u8; 64];
+        let textured: Vec<u8> = (0..64).map(|i| if i % 2 == 0 { 60 } else { 200 }).collect();
+        let dark_noise: Vec<u8> = (0..64).map(|i| if i % 2 == 0 { 0 } else { 30 }).collect();
+
+        assert_eq!(frame_score(&black), 0.0);
+        assert_eq!(frame_score(&flat_grey), 0.0);
+        assert!(frame_score(&textured) > frame_score(&dark_noise));
+        assert!(frame_score(&dark_noise) > 0.0);
+    }
+}
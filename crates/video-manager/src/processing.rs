@@ -3,25 +3,35 @@
 //! This module orchestrates the complete video processing workflow:
 //! 1. Validate video file
 //! 2. Extract metadata using FFprobe
-//! 3. Generate thumbnails and poster images
-//! 4. Move file to permanent storage
-//! 5. Update database with all extracted information
+//! 3. Pick a representative frame for the thumbnail and poster images,
+//!    and generate seek-preview sprites
+//! 4. Transcode to HLS
+//! 5. Transcribe the audio track (when an ASR backend is configured)
+//! 6. Move file to permanent storage
+//! 7. Update database with all extracted information
 //!
-//! Processing runs in background tasks to avoid blocking upload responses.
+//! Processing is driven by the transcoding job queue (see [`crate::queue`]),
+//! which bounds how many videos are processed at once.
 
 use crate::cleanup::{cleanup_failed_video, cleanup_temp_upload, CleanupManager};
 use crate::ffmpeg::{
-    extract_metadata, generate_poster, generate_thumbnail, get_poster_timestamp,
-    get_thumbnail_timestamp, is_codec_supported, validate_video, FFmpegConfig, VideoMetadata,
+    extract_metadata, generate_poster, generate_thumbnail, is_codec_supported, validate_video,
+    FFmpegConfig, VideoMetadata,
 };
+use crate::encoding::EncodingProfile;
 use crate::hls::{transcode_to_hls, HlsConfig};
 use crate::metrics::{AuditEventType, AuditLogger, MetricsStore, Timer, UploadRecord};
 use crate::progress::{ProgressStatus, ProgressTracker};
 use crate::storage::{move_file, StorageConfig};
+use crate::previews;
+use crate::subtitles;
+use crate::transcripts::Transcriber;
+use crate::chapters;
+use crate::queue::WorkerDeps;
 use common::storage::MediaType as StorageMediaType;
 use anyhow::{Context, Result};
+use db::jobs::TranscodeJob;
 use db::media::MediaRepository;
-use sqlx::{Pool, Sqlite};
 use std::collections::HashMap;
 use std::sync::Arc;
 use std::path::{Path, PathBuf};
@@ -41,8 +51,12 @@ pub enum ProcessingStage {
     GeneratingThumbnail,
     /// Generating poster image
     GeneratingPoster,
+    /// Generating seek-preview sprite sheets
+    GeneratingSprites,
     /// Transcoding to HLS (multiple qualities)
     TranscodingHls,
+    /// Transcribing the audio track
+    Transcribing,
     /// Moving file to permanent storage
     MovingFile,
     /// Updating database
@@ -62,7 +76,9 @@ impl ProcessingStage {
             Self::ExtractingMetadata => 30,
             Self::GeneratingThumbnail => 40,
             Self::GeneratingPoster => 50,
+            Self::GeneratingSprites => 52,
             Self::TranscodingHls => 55, // Start of transcoding range (55-85)
+            Self::Transcribing => 87,
             Self::MovingFile => 90,
             Self::UpdatingDatabase => 95,
             Self::Complete => 100,
@@ -78,7 +94,9 @@ impl ProcessingStage {
             Self::ExtractingMetadata => "Extracting metadata",
             Self::GeneratingThumbnail => "Generating thumbnail",
             Self::GeneratingPoster => "Generating poster",
+            Self::GeneratingSprites => "Generating previews",
             Self::TranscodingHls => "Transcoding to HLS",
+            Self::Transcribing => "Transcribing audio",
             Self::MovingFile => "Moving to storage",
             Self::UpdatingDatabase => "Finalizing",
             Self::Complete => "Complete",
@@ -101,8 +119,8 @@ pub struct ProcessingContext {
     pub is_public: bool,
     /// Original filename
     pub original_filename: String,
-    /// Database pool
-    pub pool: Pool<Sqlite>,
+    /// Keep the source file if processing fails (another attempt will follow)
+    pub retain_source_on_failure: bool,
     /// Storage configuration
     pub storage_config: StorageConfig,
     /// FFmpeg configuration
@@ -119,6 +137,32 @@ pub struct ProcessingContext {
     pub user_id: Option<String>,
     /// Media repository for media_items queries
     pub media_repo: Arc<dyn MediaRepository>,
+    /// Speech-to-text for the transcript stage
+    pub transcriber: Transcriber,
+}
+
+impl ProcessingContext {
+    /// Build a processing context for a job claimed from the transcoding queue
+    pub fn from_job(job: &TranscodeJob, deps: &WorkerDeps) -> Self {
+        Self {
+            upload_id: job.upload_id.clone(),
+            slug: job.slug.clone(),
+            vault_id: job.vault_id.clone(),
+            temp_file_path: PathBuf::from(&job.source_path),
+            is_public: job.is_public,
+            original_filename: job.original_filename.clone(),
+            retain_source_on_failure: job.attempts < job.max_attempts,
+            storage_config: deps.storage_config.clone(),
+            ffmpeg_config: deps.ffmpeg_config.clone(),
+            hls_config: deps.hls_config.clone(),
+            progress_tracker: deps.progress_tracker.clone(),
+            metrics_store: deps.metrics_store.clone(),
+            audit_logger: deps.audit_logger.clone(),
+            user_id: job.user_id.clone(),
+            media_repo: deps.media_repo.clone(),
+            transcriber: deps.transcriber.clone(),
+        }
+    }
 }
 
 /// Process an uploaded video file
@@ -128,8 +172,9 @@ pub struct ProcessingContext {
 /// 2. Extracts metadata
 /// 3. Generates thumbnails and poster
 /// 4. Transcodes to HLS (multiple qualities)
-/// 5. Moves to permanent storage
-/// 6. Updates database
+/// 5. Transcribes the audio track
+/// 6. Moves to permanent storage
+/// 7. Updates database
 ///
 /// All stages update processing status in the database.
 pub async fn process_video(context: ProcessingContext) -> Result<()> {
@@ -158,8 +203,10 @@ pub async fn process_video(context: ProcessingContext) -> Result<()> {
     // Initialize cleanup manager to track resources
     let mut cleanup = CleanupManager::new(format!("process_video_{}", context.slug));
 
-    // Register temporary file for cleanup
-    cleanup.add_file(&context.temp_file_path);
+    // Register temporary file for cleanup, unless a retry still needs it
+    if !context.retain_source_on_failure {
+        cleanup.add_file(&context.temp_file_path);
+    }
 
     // Register video directory for cleanup in case of failure
     let video_dir = context
@@ -175,8 +222,7 @@ pub async fn process_video(context: ProcessingContext) -> Result<()> {
 
     // Update stage: Starting
     update_processing_status(
-        &context.pool,
-        &context.upload_id,
+        &context,
         ProcessingStage::Starting,
         None,
     )
@@ -226,8 +272,7 @@ pub async fn process_video(context: ProcessingContext) -> Result<()> {
             .await;
 
         update_processing_status(
-            &context.pool,
-            &context.upload_id,
+            &context,
             ProcessingStage::Error,
             Some(&error_msg),
         )
@@ -238,9 +283,7 @@ pub async fn process_video(context: ProcessingContext) -> Result<()> {
 
         // Cleanup on validation failure
         cleanup.cleanup().await;
-        cleanup_temp_upload(&context.storage_config.temp_dir, &context.upload_id)
-            .await
-            .ok();
+        discard_failed_source(&context).await;
 
         return Err(e);
     }
@@ -282,8 +325,7 @@ pub async fn process_video(context: ProcessingContext) -> Result<()> {
                 .record_error("metadata_extraction_error");
 
             update_processing_status(
-                &context.pool,
-                &context.upload_id,
+                &context,
                 ProcessingStage::Error,
                 Some(&error_msg),
             )
@@ -294,17 +336,27 @@ pub async fn process_video(context: ProcessingContext) -> Result<()> {
 
             // Cleanup on metadata extraction failure
             cleanup.cleanup().await;
-            cleanup_temp_upload(&context.storage_config.temp_dir, &context.upload_id)
-                .await
-                .ok();
+            discard_failed_source(&context).await;
 
             return Err(e);
         }
     };
 
+    // Thumbnail and poster show the same representative frame
+    let stage_timer = Timer::start("poster_selection");
+    let frame_timestamp =
+        previews::pick_poster_timestamp(&context.ffmpeg_config, &context.temp_file_path, metadata.duration)
+            .await;
+    let duration = stage_timer.stop();
+    context
+        .metrics_store
+        .write()
+        .await
+        .record_stage_timing("poster_selection", duration, true);
+
     // Stage 3: Generate thumbnail
     let stage_timer = Timer::start("thumbnail_generation");
-    match generate_thumbnail_stage(&context, &metadata).await {
+    match generate_thumbnail_stage(&context, frame_timestamp).await {
         Ok(_) => {
             let duration = stage_timer.stop();
             context.metrics_store.write().await.record_stage_timing(
@@ -327,7 +379,7 @@ pub async fn process_video(context: ProcessingContext) -> Result<()> {
 
     // Stage 4: Generate poster
     let stage_timer = Timer::start("poster_generation");
-    match generate_poster_stage(&context, &metadata).await {
+    match generate_poster_stage(&context, frame_timestamp).await {
         Ok(_) => {
             let duration = stage_timer.stop();
             context.metrics_store.write().await.record_stage_timing(
@@ -348,6 +400,19 @@ pub async fn process_video(context: ProcessingContext) -> Result<()> {
         }
     }
 
+    // Stage 4b: Generate seek-preview sprites
+    let stage_timer = Timer::start("sprite_generation");
+    let result = generate_sprites_stage(&context, &metadata).await;
+    let duration = stage_timer.stop();
+    if let Err(e) = &result {
+        warn!(error = %format!("{:#}", e), "Preview sprite generation failed (non-fatal)");
+    }
+    context.metrics_store.write().await.record_stage_timing(
+        "sprite_generation",
+        duration,
+        result.is_ok(),
+    );
+
     // Stage 5: Transcode to HLS
     let stage_timer = Timer::start("hls_transcoding");
     let hls_qualities = match transcode_hls_stage(&context, &metadata).await {
@@ -378,8 +443,7 @@ pub async fn process_video(context: ProcessingContext) -> Result<()> {
                 .record_error("hls_transcoding_error");
 
             update_processing_status(
-                &context.pool,
-                &context.upload_id,
+                &context,
                 ProcessingStage::Error,
                 Some(&error_msg),
             )
@@ -390,9 +454,7 @@ pub async fn process_video(context: ProcessingContext) -> Result<()> {
 
             // Cleanup on HLS transcoding failure
             cleanup.cleanup().await;
-            cleanup_temp_upload(&context.storage_config.temp_dir, &context.upload_id)
-                .await
-                .ok();
+            discard_failed_source(&context).await;
             cleanup_failed_video(
                 &context
                     .storage_config
@@ -411,7 +473,22 @@ pub async fn process_video(context: ProcessingContext) -> Result<()> {
         }
     };
 
-    // Stage 6: Move to permanent storage
+    // Stage 6: Transcribe audio (optional, non-fatal)
+    if context.transcriber.is_enabled() {
+        let stage_timer = Timer::start("transcription");
+        let result = transcribe_stage(&context).await;
+        let duration = stage_timer.stop();
+        if let Err(e) = &result {
+            warn!(error = %format!("{:#}", e), "Transcription failed (non-fatal)");
+        }
+        context.metrics_store.write().await.record_stage_timing(
+            "transcription",
+            duration,
+            result.is_ok(),
+        );
+    }
+
+    // Stage 7: Move to permanent storage
     let stage_timer = Timer::start("move_to_storage");
     let final_path = match move_to_storage_stage(&context).await {
         Ok(path) => {
@@ -441,8 +518,7 @@ pub async fn process_video(context: ProcessingContext) -> Result<()> {
                 .record_error("file_move_error");
 
             update_processing_status(
-                &context.pool,
-                &context.upload_id,
+                &context,
                 ProcessingStage::Error,
                 Some(&error_msg),
             )
@@ -453,9 +529,7 @@ pub async fn process_video(context: ProcessingContext) -> Result<()> {
 
             // Cleanup on file move failure
             cleanup.cleanup().await;
-            cleanup_temp_upload(&context.storage_config.temp_dir, &context.upload_id)
-                .await
-                .ok();
+            discard_failed_source(&context).await;
             cleanup_failed_video(
                 &context
                     .storage_config
@@ -474,7 +548,7 @@ pub async fn process_video(context: ProcessingContext) -> Result<()> {
         }
     };
 
-    // Stage 7: Update database
+    // Stage 8: Update database
     let stage_timer = Timer::start("update_database");
     if let Err(e) = update_database_stage(&context, &metadata, &final_path, &hls_qualities).await {
         let duration = stage_timer.stop();
@@ -494,8 +568,7 @@ pub async fn process_video(context: ProcessingContext) -> Result<()> {
             .record_error("database_update_error");
 
         update_processing_status(
-            &context.pool,
-            &context.upload_id,
+            &context,
             ProcessingStage::Error,
             Some(&error_msg),
         )
@@ -507,9 +580,7 @@ pub async fn process_video(context: ProcessingContext) -> Result<()> {
         // Note: Database update failed, but files are already in place
         // We should NOT cleanup the video files in this case
         // Just cleanup the temp file
-        cleanup_temp_upload(&context.storage_config.temp_dir, &context.upload_id)
-            .await
-            .ok();
+        discard_failed_source(&context).await;
 
         return Err(e);
     }
@@ -528,10 +599,9 @@ pub async fn process_video(context: ProcessingContext) -> Result<()> {
         .await
         .ok();
 
-    // Stage 8: Mark as complete
+    // Stage 9: Mark as complete
     update_processing_status(
-        &context.pool,
-        &context.upload_id,
+        &context,
         ProcessingStage::Complete,
         None,
     )
@@ -549,6 +619,15 @@ pub async fn process_video(context: ProcessingContext) -> Result<()> {
         .map(|m| m.len())
         .unwrap_or(0);
 
+    // Hand the finished video and its thumbnail over to the storage backend
+    let user_storage = &context.storage_config.user_storage;
+    user_storage
+        .offload_paths(&[
+            final_path.parent().unwrap_or(&final_path).to_path_buf(),
+            user_storage.get_thumbnail_path(&context.vault_id, StorageMediaType::Video, &context.slug),
+        ])
+        .await;
+
     // Create upload record
     let record = UploadRecord {
         upload_id: context.upload_id.clone(),
@@ -606,12 +685,20 @@ pub async fn process_video(context: ProcessingContext) -> Result<()> {
     Ok(())
 }
 
+/// Remove the uploaded source after a failure, unless a retry still needs it
+async fn discard_failed_source(context: &ProcessingContext) {
+    if !context.retain_source_on_failure {
+        cleanup_temp_upload(&context.storage_config.temp_dir, &context.upload_id)
+            .await
+            .ok();
+    }
+}
+
 /// Stage 1: Validate video file
 async fn validate_video_stage(context: &ProcessingContext) -> Result<()> {
     info!("Stage 1: Validating video");
     update_processing_status(
-        &context.pool,
-        &context.upload_id,
+        context,
         ProcessingStage::Validating,
         None,
     )
@@ -635,8 +722,7 @@ async fn validate_video_stage(context: &ProcessingContext) -> Result<()> {
 async fn extract_metadata_stage(context: &ProcessingContext) -> Result<VideoMetadata> {
     info!("Stage 2: Extracting metadata");
     update_processing_status(
-        &context.pool,
-        &context.upload_id,
+        context,
         ProcessingStage::ExtractingMetadata,
         None,
     )
@@ -675,14 +761,10 @@ async fn extract_metadata_stage(context: &ProcessingContext) -> Result<VideoMeta
 }
 
 /// Stage 3: Generate thumbnail
-async fn generate_thumbnail_stage(
-    context: &ProcessingContext,
-    metadata: &VideoMetadata,
-) -> Result<()> {
+async fn generate_thumbnail_stage(context: &ProcessingContext, timestamp: f64) -> Result<()> {
     info!("Stage 3: Generating thumbnail");
     update_processing_status(
-        &context.pool,
-        &context.upload_id,
+        context,
         ProcessingStage::GeneratingThumbnail,
         None,
     )
@@ -697,9 +779,6 @@ async fn generate_thumbnail_stage(
             .to_string(),
     );
 
-    // Calculate thumbnail timestamp (10% of duration)
-    let timestamp = get_thumbnail_timestamp(metadata.duration);
-
     // Write thumbnail to centralized thumbnails directory (matches serve_thumbnail lookup)
     let thumb_dir = context
         .storage_config
@@ -724,48 +803,16 @@ async fn generate_thumbnail_stage(
     .context("Thumbnail generation failed")?;
 
     // Convert JPEG to WebP for better compression (produces {slug}_thumb.webp)
-    convert_thumbnail_to_webp(&thumbnail_path).await?;
-
-    Ok(())
-}
-
-/// Convert thumbnail JPEG to WebP format
-async fn convert_thumbnail_to_webp(jpeg_path: &Path) -> Result<()> {
-    let webp_path = jpeg_path.with_extension("webp");
-
-    // Read JPEG file
-    let jpeg_data = tokio::fs::read(jpeg_path).await?;
-    let img = image::load_from_memory(&jpeg_data)
-        .context("Failed to load JPEG thumbnail")?;
-
-    // Encode as WebP (lossless)
-    let mut webp_data = Vec::new();
-    let encoder = image::codecs::webp::WebPEncoder::new_lossless(&mut webp_data);
-    img.write_with_encoder(encoder)
-        .context("Failed to encode WebP")?;
-
-    // Write WebP file
-    tokio::fs::write(&webp_path, &webp_data)
-        .await
-        .context("Failed to write WebP thumbnail")?;
-
-    info!("Converted thumbnail to WebP: {:?}", webp_path);
-
-    // Remove old JPEG file
-    let _ = tokio::fs::remove_file(jpeg_path).await;
+    previews::convert_thumbnail_to_webp(&thumbnail_path).await?;
 
     Ok(())
 }
 
 /// Stage 4: Generate poster
-async fn generate_poster_stage(
-    context: &ProcessingContext,
-    metadata: &VideoMetadata,
-) -> Result<()> {
+async fn generate_poster_stage(context: &ProcessingContext, timestamp: f64) -> Result<()> {
     info!("Stage 4: Generating poster");
     update_processing_status(
-        &context.pool,
-        &context.upload_id,
+        context,
         ProcessingStage::GeneratingPoster,
         None,
     )
@@ -778,9 +825,6 @@ async fn generate_poster_stage(
         ProcessingStage::GeneratingPoster.description().to_string(),
     );
 
-    // Calculate poster timestamp (25% of duration)
-    let timestamp = get_poster_timestamp(metadata.duration);
-
     // Determine output path
     let video_dir = context
         .storage_config
@@ -803,15 +847,67 @@ async fn generate_poster_stage(
     Ok(())
 }
 
+/// Stage 4b: Generate sprite sheets and the thumbnail track for seek previews
+async fn generate_sprites_stage(context: &ProcessingContext, metadata: &VideoMetadata) -> Result<()> {
+    info!("Stage 4b: Generating preview sprites");
+    update_processing_status(
+        context,
+        ProcessingStage::GeneratingSprites,
+        None,
+    )
+    .await?;
+
+    context.progress_tracker.update(
+        &context.upload_id,
+        ProgressStatus::Processing,
+        ProcessingStage::GeneratingSprites.progress(),
+        ProcessingStage::GeneratingSprites.description().to_string(),
+    );
+
+    let video_dir = context
+        .storage_config
+        .get_vault_video_dir(&context.vault_id, &context.slug);
+    previews::generate_sprites(
+        &context.ffmpeg_config,
+        &context.temp_file_path,
+        &video_dir,
+        metadata.duration,
+    )
+    .await
+}
+
 /// Stage 5: Transcode to HLS
+/// Rebuild subtitle renditions for the video's stored tracks
+async fn repackage_subtitles(context: &ProcessingContext, video_dir: &Path) -> Result<()> {
+    let Some(media) = context.media_repo.get_media_by_slug(&context.slug).await? else {
+        return Ok(());
+    };
+    let tracks = context.media_repo.list_subtitles(media.id as i64).await?;
+    if tracks.is_empty() {
+        return Ok(());
+    }
+    subtitles::package_subtitles(video_dir, &tracks).await
+}
+
+/// Rebuild the chapters track and playlist date ranges for the video's chapters
+async fn repackage_chapters(context: &ProcessingContext, video_dir: &Path, duration: f64) -> Result<()> {
+    let Some(media) = context.media_repo.get_media_by_slug(&context.slug).await? else {
+        return Ok(());
+    };
+    let chapters = context.media_repo.list_chapters(media.id as i64).await?;
+    if chapters.is_empty() {
+        return Ok(());
+    }
+    chapters::package_chapters(video_dir, &chapters, duration).await
+}
+
 async fn transcode_hls_stage(
     context: &ProcessingContext,
     metadata: &VideoMetadata,
 ) -> Result<Vec<String>> {
     info!("Stage 5: Transcoding to HLS");
     update_processing_status(
-        &context.pool,
-        &context.upload_id,
+        context,
         ProcessingStage::TranscodingHls,
         None,
     )
@@ -834,10 +930,17 @@ async fn transcode_hls_stage(
         .await
         .context("Failed to create video directory")?;
 
+    // Per-vault ladder from encoding.yaml, or the built-in default
+    let profile = EncodingProfile::load_for_vault(
+        &context.storage_config.user_storage,
+        &context.vault_id,
+    )?;
+
     // Transcode to HLS with multiple qualities
     let qualities = transcode_to_hls(
         &context.ffmpeg_config,
         &context.hls_config,
+        &profile,
         &context.temp_file_path,
         &video_dir,
         metadata,
@@ -850,6 +953,15 @@ async fn transcode_hls_stage(
         qualities.len()
     );
 
+    // The playlists were rewritten: re-attach subtitle tracks and chapters
+    // added before or during transcoding. Neither ever fails the video.
+    if let Err(e) = repackage_subtitles(context, &video_dir).await {
+        warn!("Failed to package subtitles for {}: {:#}", context.slug, e);
+    }
+    if let Err(e) = repackage_chapters(context, &video_dir, metadata.duration).await {
+        warn!("Failed to package chapters for {}: {:#}", context.slug, e);
+    }
+
     // Update progress tracker with quality info
     context.progress_tracker.update_metadata(
         &context.upload_id,
@@ -861,12 +973,50 @@ async fn transcode_hls_stage(
     Ok(qualities)
 }
 
-/// Stage 6: Move file to permanent storage
-async fn move_to_storage_stage(context: &ProcessingContext) -> Result<PathBuf> {
-    info!("Stage 6: Moving to permanent storage");
+/// Stage 6: Transcribe the audio track into segments, captions and search text
+async fn transcribe_stage(context: &ProcessingContext) -> Result<()> {
+    info!("Stage 6: Transcribing audio");
     update_processing_status(
-        &context.pool,
+        context,
+        ProcessingStage::Transcribing,
+        None,
+    )
+    .await?;
+
+    context.progress_tracker.update(
         &context.upload_id,
+        ProgressStatus::Processing,
+        ProcessingStage::Transcribing.progress(),
+        ProcessingStage::Transcribing.description().to_string(),
+    );
+
+    let media = context
+        .media_repo
+        .get_media_by_slug(&context.slug)
+        .await?
+        .context("Media item not found")?;
+    let video_dir = context
+        .storage_config
+        .get_vault_video_dir(&context.vault_id, &context.slug);
+
+    context
+        .transcriber
+        .transcribe_video(
+            context.media_repo.as_ref(),
+            media.id as i64,
+            &context.temp_file_path,
+            &video_dir,
+            context.user_id.as_deref(),
+        )
+        .await?;
+    Ok(())
+}
+
+/// Stage 7: Move file to permanent storage
+async fn move_to_storage_stage(context: &ProcessingContext) -> Result<PathBuf> {
+    info!("Stage 7: Moving to permanent storage");
+    update_processing_status(
+        context,
         ProcessingStage::MovingFile,
         None,
     )
@@ -906,17 +1056,16 @@ async fn move_to_storage_stage(context: &ProcessingContext) -> Result<PathBuf> {
     Ok(dest_path)
 }
 
-/// Stage 7: Update database with all metadata
+/// Stage 8: Update database with all metadata
 async fn update_database_stage(
     context: &ProcessingContext,
     metadata: &VideoMetadata,
-    final_path: &Path,
+    _final_path: &Path,
     _hls_qualities: &[String],
 ) -> Result<()> {
-    info!("Stage 7: Updating database");
+    info!("Stage 8: Updating database");
     update_processing_status(
-        &context.pool,
-        &context.upload_id,
+        context,
         ProcessingStage::UpdatingDatabase,
         None,
     )
@@ -929,58 +1078,8 @@ async fn update_database_stage(
         ProcessingStage::UpdatingDatabase.description().to_string(),
     );
 
-    // Calculate resolution string
-    let resolution = format!("{}x{}", metadata.width, metadata.height);
-
-    // Determine thumbnail and poster URLs
+    // Thumbnail URL served from the centralized thumbnails directory
     let thumbnail_url = format!("/media/{}/thumbnail", context.slug);
-    let poster_url = format!("/media/{}/thumbnail", context.slug); // poster not yet served separately
-
-    // HLS master playlist URL
-    let hls_url = format!("/hls/{}/master.m3u8", context.slug);
-
-    // Update video record (legacy table)
-    sqlx::query(
-        r#"
-        UPDATE videos
-        SET
-            duration = ?,
-            file_size = ?,
-            resolution = ?,
-            width = ?,
-            height = ?,
-            fps = ?,
-            codec = ?,
-            audio_codec = ?,
-            bitrate = ?,
-            format = ?,
-            thumbnail_url = ?,
-            poster_url = ?,
-            filename = ?,
-            processing_status = 'processing',
-            processing_progress = 95,
-            status = 'active'
-        WHERE upload_id = ?
-        "#,
-    )
-    .bind(metadata.duration as i64)
-    .bind(metadata.file_size as i64)
-    .bind(&resolution)
-    .bind(metadata.width as i32)
-    .bind(metadata.height as i32)
-    .bind(metadata.fps as i32)
-    .bind(&metadata.video_codec)
-    .bind(&metadata.audio_codec)
-    .bind(metadata.bitrate.map(|b| b as i64))
-    .bind(&metadata.format)
-    .bind(&thumbnail_url)
-    .bind(&poster_url)
-    .bind(final_path.file_name().and_then(|n| n.to_str()))
-    .bind(&hls_url)
-    .bind(&context.upload_id)
-    .execute(&context.pool)
-    .await
-    .context("Failed to update video record")?;
 
     // Update media_items table (unified table) with thumbnail URL
     context
@@ -992,43 +1091,29 @@ async fn update_database_stage(
     Ok(())
 }
 
-/// Update processing status in database
+/// Record a processing stage transition
+///
+/// Fine-grained progress lives in the in-memory [`ProgressTracker`]; the
+/// `media_items` row is only flagged as failed once no retry will follow.
 async fn update_processing_status(
-    pool: &Pool<Sqlite>,
-    upload_id: &str,
+    context: &ProcessingContext,
     stage: ProcessingStage,
     error_message: Option<&str>,
 ) -> Result<()> {
-    let status_str = match stage {
-        ProcessingStage::Complete => "complete",
-        ProcessingStage::Error => "error",
-        _ => "processing",
-    };
-
-    let progress = stage.progress();
-
-    sqlx::query(
-        r#"
-        UPDATE videos
-        SET
-            processing_status = ?,
-            processing_progress = ?,
-            processing_error = ?
-        WHERE upload_id = ?
-        "#,
-    )
-    .bind(status_str)
-    .bind(progress as i32)
-    .bind(error_message)
-    .bind(upload_id)
-    .execute(pool)
-    .await
-    .context("Failed to update processing status")?;
+    if stage == ProcessingStage::Error && !context.retain_source_on_failure {
+        context
+            .media_repo
+            .update_media_status_error(&context.slug, "video")
+            .await
+            .context("Failed to update processing status")?;
+    }
 
     debug!(
+        upload_id = %context.upload_id,
+        error = ?error_message,
         "Processing status updated: {} ({}%)",
         stage.description(),
-        progress
+        stage.progress()
     );
 
     Ok(())
@@ -1068,6 +1153,10 @@ mod tests {
             ProcessingStage::TranscodingHls.description(),
             "Transcoding to HLS"
         );
+        assert_eq!(
+            ProcessingStage::Transcribing.description(),
+            "Transcribing audio"
+        );
         assert_eq!(ProcessingStage::Complete.description(), "Complete");
         assert_eq!(ProcessingStage::Error.description(), "Error");
     }
@@ -0,0 +1,243 @@
+//! Persistent transcoding job queue
+//!
+//! Uploads enqueue a job instead of spawning `process_video` directly. A fixed
+//! pool of workers claims jobs from the `transcode_jobs` table in priority
+//! order, so the number of concurrent ffmpeg processes stays bounded no matter
+//! how many files arrive at once.
+//!
+//! Jobs survive restarts: anything left in `running` when the server stopped is
+//! put back into the queue by [`TranscodeQueue::start`].
+
+use crate::ffmpeg::FFmpegConfig;
+use crate::hls::HlsConfig;
+use crate::metrics::{AuditLogger, MetricsStore};
+use crate::processing::{process_video, ProcessingContext};
+use crate::progress::{ProgressStatus, ProgressTracker};
+use crate::storage::StorageConfig;
+use crate::transcripts::Transcriber;
+use anyhow::{Context, Result};
+use db::jobs::{CreateTranscodeJob, JobRepository, TranscodeJob, JOB_STATUS_QUEUED};
+use db::media::MediaRepository;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Notify;
+use tracing::{error, info, warn};
+
+/// Default number of concurrent transcoding workers
+const DEFAULT_WORKERS: usize = 2;
+
+/// Queue configuration
+#[derive(Debug, Clone)]
+pub struct QueueConfig {
+    /// Number of jobs processed concurrently
+    pub workers: usize,
+    /// How often idle workers re-check the database for new jobs
+    pub poll_interval: Duration,
+}
+
+impl Default for QueueConfig {
+    fn default() -> Self {
+        Self {
+            workers: DEFAULT_WORKERS,
+            poll_interval: Duration::from_secs(30),
+        }
+    }
+}
+
+impl QueueConfig {
+    /// Load from environment.
+    ///
+    /// `TRANSCODE_WORKERS` sets the worker count (minimum 1, default 2).
+    pub fn from_env() -> Self {
+        let workers = parse_worker_count(std::env::var("TRANSCODE_WORKERS").ok().as_deref());
+
+        Self {
+            workers,
+            ..Self::default()
+        }
+    }
+}
+
+/// Parse a worker count, falling back to the default and never returning zero
+fn parse_worker_count(value: Option<&str>) -> usize {
+    value
+        .and_then(|v| v.trim().parse::<usize>().ok())
+        .unwrap_or(DEFAULT_WORKERS)
+        .max(1)
+}
+
+/// Everything a worker needs to turn a [`TranscodeJob`] into a [`ProcessingContext`]
+#[derive(Clone)]
+pub struct WorkerDeps {
+    pub storage_config: StorageConfig,
+    pub ffmpeg_config: FFmpegConfig,
+    pub hls_config: HlsConfig,
+    pub progress_tracker: ProgressTracker,
+    pub metrics_store: MetricsStore,
+    pub audit_logger: AuditLogger,
+    pub media_repo: Arc<dyn MediaRepository>,
+    pub transcriber: Transcriber,
+}
+
+/// Handle to the transcoding job queue
+#[derive(Clone)]
+pub struct TranscodeQueue {
+    repo: Arc<dyn JobRepository>,
+    config: QueueConfig,
+    wake: Arc<Notify>,
+}
+
+impl TranscodeQueue {
+    /// Create a new queue handle. Workers are not started until [`start`](Self::start).
+    pub fn new(repo: Arc<dyn JobRepository>, config: QueueConfig) -> Self {
+        Self {
+            repo,
+            config,
+            wake: Arc::new(Notify::new()),
+        }
+    }
+
+    /// Job repository backing this queue
+    pub fn repo(&self) -> &Arc<dyn JobRepository> {
+        &self.repo
+    }
+
+    /// Queue configuration
+    pub fn config(&self) -> &QueueConfig {
+        &self.config
+    }
+
+    /// Persist a job and wake an idle worker. Returns the job ID.
+    pub async fn enqueue(&self, job: &CreateTranscodeJob) -> Result<i64> {
+        let id = self
+            .repo
+            .enqueue_job(job)
+            .await
+            .context("Failed to enqueue transcoding job")?;
+
+        info!(
+            job_id = id,
+            slug = %job.slug,
+            priority = job.priority,
+            "Transcoding job queued"
+        );
+
+        self.wake.notify_one();
+        Ok(id)
+    }
+
+    /// Requeue jobs interrupted by a previous shutdown and spawn the worker pool.
+    pub async fn start(&self, deps: WorkerDeps) -> Result<()> {
+        let resumed = self
+            .repo
+            .requeue_interrupted_jobs()
+            .await
+            .context("Failed to requeue interrupted jobs")?;
+
+        if resumed > 0 {
+            info!(count = resumed, "Resuming interrupted transcoding jobs");
+        }
+
+        for worker_id in 0..self.config.workers {
+            let queue = self.clone();
+            let deps = deps.clone();
+            tokio::spawn(async move {
+                queue.run_worker(worker_id, deps).await;
+            });
+        }
+
+        info!(workers = self.config.workers, "Transcoding workers started");
+        Ok(())
+    }
+
+    /// Worker loop: claim a job, process it, repeat. Sleeps until woken when idle.
+    async fn run_worker(&self, worker_id: usize, deps: WorkerDeps) {
+        loop {
+            match self.repo.claim_next_job().await {
+                Ok(Some(job)) => {
+                    self.run_job(worker_id, job, &deps).await;
+                }
+                Ok(None) => {
+                    tokio::select! {
+                        _ = self.wake.notified() => {}
+                        _ = tokio::time::sleep(self.config.poll_interval) => {}
+                    }
+                }
+                Err(e) => {
+                    error!(worker_id, error = %e, "Failed to claim transcoding job");
+                    tokio::time::sleep(self.config.poll_interval).await;
+                }
+            }
+        }
+    }
+
+    /// Run a single claimed job through the processing pipeline.
+    async fn run_job(&self, worker_id: usize, job: TranscodeJob, deps: &WorkerDeps) {
+        info!(
+            worker_id,
+            job_id = job.id,
+            slug = %job.slug,
+            attempt = job.attempts,
+            "Processing transcoding job"
+        );
+
+        // Resumed jobs have no in-memory progress entry yet
+        if deps.progress_tracker.get(&job.upload_id).is_none() {
+            deps.progress_tracker.init_upload(
+                job.upload_id.clone(),
+                job.slug.clone(),
+                Some(job.original_filename.clone()),
+                None,
+            );
+        }
+
+        let context = ProcessingContext::from_job(&job, deps);
+
+        match process_video(context).await {
+            Ok(()) => {
+                if let Err(e) = self.repo.complete_job(job.id).await {
+                    error!(job_id = job.id, error = %e, "Failed to mark job completed");
+                }
+            }
+            Err(e) => match self.repo.fail_job(job.id, &format!("{:#}", e)).await {
+                Ok(Some(status)) if status == JOB_STATUS_QUEUED => {
+                    warn!(job_id = job.id, error = %e, "Transcoding job failed, will retry");
+                    deps.progress_tracker.update(
+                        &job.upload_id,
+                        ProgressStatus::Processing,
+                        0,
+                        "Waiting to retry".to_string(),
+                    );
+                    self.wake.notify_one();
+                }
+                Ok(_) => {
+                    error!(job_id = job.id, error = %e, "Transcoding job failed permanently");
+                }
+                Err(db_err) => {
+                    error!(job_id = job.id, error = %db_err, "Failed to record job failure");
+                }
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_worker_count() {
+        assert_eq!(parse_worker_count(None), DEFAULT_WORKERS);
+        assert_eq!(parse_worker_count(Some("4")), 4);
+        assert_eq!(parse_worker_count(Some(" 3 ")), 3);
+        assert_eq!(parse_worker_count(Some("0")), 1);
+        assert_eq!(parse_worker_count(Some("lots")), DEFAULT_WORKERS);
+    }
+
+    #[test]
+    fn test_default_config() {
+        let config = QueueConfig::default();
+        assert_eq!(config.workers, DEFAULT_WORKERS);
+        assert!(config.poll_interval > Duration::ZERO);
+    }
+}
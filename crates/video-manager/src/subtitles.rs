@@ -0,0 +1,717 @@
+//! Subtitle and caption tracks
+//!
+//! Uploaded SubRip (`.srt`) and WebVTT (`.vtt`) files are normalised to
+//! WebVTT and stored next to the video:
+//!
+//! ```text
+//! {video_dir}/subtitles/{lang}.vtt              full track (MP4 <track>, federation)
+//! {video_dir}/subtitles/{lang}/index.m3u8       segmented HLS rendition
+//! {video_dir}/subtitles/{lang}/segment_000.vtt
+//! ```
+//!
+//! [`package_subtitles`] cuts every track along the video's own segment
+//! boundaries and adds `#EXT-X-MEDIA:TYPE=SUBTITLES` entries to `master.m3u8`.
+//! It runs after each subtitle change and after (re)transcoding, since
+//! [`generate_master_playlist`](crate::hls::generate_master_playlist) writes
+//! the master playlist from scratch.
+
+use crate::hls::quote_attribute;
+use anyhow::{Context, Result};
+use db::media::SubtitleTrack;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use tokio::fs;
+use tokio::io::AsyncReadExt;
+use tracing::{info, warn};
+
+/// Directory under the video directory that holds subtitle tracks
+pub const SUBTITLES_DIR: &str = "subtitles";
+
+/// Upper bound on an uploaded subtitle file
+pub const MAX_SUBTITLE_SIZE: usize = 2 * 1024 * 1024;
+
+/// Rendition group referenced by every `#EXT-X-STREAM-INF`
+const SUBTITLE_GROUP_ID: &str = "subs";
+
+/// Segment length used when the video playlist cannot be read
+const FALLBACK_SEGMENT_DURATION: f64 = 6.0;
+
+/// A single timed cue
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cue {
+    /// Start time in seconds
+    pub start: f64,
+    /// End time in seconds
+    pub end: f64,
+    /// WebVTT cue settings (`line:0 align:start` …), empty for SRT input
+    pub settings: String,
+    /// Cue payload, one entry per line
+    pub text: Vec<String>,
+}
+
+/// Validate and normalise a BCP 47 language tag (`en`, `pt-BR`, `zh-Hant`).
+///
+/// The result is also used as a file name, so only ASCII letters, digits and
+/// hyphens ever get through.
+pub fn normalize_language(tag: &str) -> Option<String> {
+    let tag = tag.trim().replace('_', "-");
+    if tag.is_empty() || tag.len() > 35 {
+        return None;
+    }
+
+    let mut parts = Vec::new();
+    for (i, sub) in tag.split('-').enumerate() {
+        if sub.is_empty() || sub.len() > 8 || !sub.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return None;
+        }
+        let normalized = match (i, sub.len()) {
+            (0, 2..=3) if sub.chars().all(|c| c.is_ascii_alphabetic()) => sub.to_ascii_lowercase(),
+            (0, _) => return None,
+            // Region: `BR`
+            (_, 2) => sub.to_ascii_uppercase(),
+            // Script: `Hant`
+            (_, 4) if sub.chars().all(|c| c.is_ascii_alphabetic()) => {
+                let lower = sub.to_ascii_lowercase();
+                lower[..1].to_ascii_uppercase() + &lower[1..]
+            }
+            _ => sub.to_ascii_lowercase(),
+        };
+        parts.push(normalized);
+    }
+
+    Some(parts.join("-"))
+}
+
+/// Parse `hh:mm:ss.mmm` / `mm:ss.mmm` (SRT's `,` separator is accepted too)
+fn parse_timestamp(value: &str) -> Option<f64> {
+    let value = value.trim().replace(',', ".");
+    let (clock, millis) = value.split_once('.')?;
+    if millis.len() != 3 || !millis.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+
+    let fields: Vec<&str> = clock.split(':').collect();
+    let (hours, minutes, seconds) = match fields.as_slice() {
+        [h, m, s] => (h.parse::<u64>().ok()?, *m, *s),
+        [m, s] => (0, *m, *s),
+        _ => return None,
+    };
+    if minutes.len() != 2 || seconds.len() != 2 {
+        return None;
+    }
+    let minutes: u64 = minutes.parse().ok()?;
+    let seconds: u64 = seconds.parse().ok()?;
+    if minutes > 59 || seconds > 59 {
+        return None;
+    }
+
+    Some((hours * 3600 + minutes * 60 + seconds) as f64 + millis.parse::<f64>().ok()? / 1000.0)
+}
+
+/// Format seconds as a WebVTT timestamp (`hh:mm:ss.mmm`)
+pub fn format_timestamp(seconds: f64) -> String {
+    let total_ms = (seconds.max(0.0) * 1000.0).round() as u64;
+    format!(
+        "{:02}:{:02}:{:02}.{:03}",
+        total_ms / 3_600_000,
+        (total_ms / 60_000) % 60,
+        (total_ms / 1000) % 60,
+        total_ms % 1000
+    )
+}
+
+/// Parse a `start --> end [settings]` timing line
+fn parse_timing_line(line: &str) -> Option<(f64, f64, String)> {
+    let (start, rest) = line.split_once("-->")?;
+    let rest = rest.trim();
+    let (end, settings) = match rest.split_once(char::is_whitespace) {
+        Some((end, settings)) => (end, settings.trim()),
+        None => (rest, ""),
+    };
+
+    // SRT coordinates (`X1:… X2:…`) have no WebVTT equivalent
+    let settings = settings
+        .split_whitespace()
+        .filter(|s| !s.starts_with('X') && !s.starts_with('Y'))
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    Some((parse_timestamp(start)?, parse_timestamp(end)?, settings))
+}
+
+/// Parse cues from SRT or WebVTT text.
+///
+/// Header, `NOTE`, `STYLE` and `REGION` blocks are skipped; so are blocks
+/// without a valid timing line. Cues are returned in start-time order.
+pub fn parse_cues(content: &str) -> Result<Vec<Cue>> {
+    let content = content.trim_start_matches('\u{feff}').replace("\r\n", "\n").replace('\r', "\n");
+
+    let mut cues = Vec::new();
+    for (index, block) in content.split("\n\n").enumerate() {
+        let lines: Vec<&str> = block.lines().filter(|l| !l.trim().is_empty()).collect();
+        let Some(first) = lines.first() else {
+            continue;
+        };
+        if (index == 0 && first.starts_with("WEBVTT"))
+            || first.starts_with("NOTE")
+            || first.starts_with("STYLE")
+            || first.starts_with("REGION")
+        {
+            continue;
+        }
+
+        // The optional cue identifier (SRT sequence number) precedes the timing line
+        let Some(timing_index) = lines.iter().take(2).position(|l| l.contains("-->")) else {
+            continue;
+        };
+        let Some((start, end, settings)) = parse_timing_line(lines[timing_index]) else {
+            continue;
+        };
+        if end <= start {
+            continue;
+        }
+
+        let text: Vec<String> = lines[timing_index + 1..]
+            .iter()
+            .map(|l| l.trim_end().replace("-->", "->"))
+            .collect();
+        if text.is_empty() {
+            continue;
+        }
+
+        cues.push(Cue {
+            start,
+            end,
+            settings,
+            text,
+        });
+    }
+
+    cues.sort_by(|a, b| a.start.total_cmp(&b.start));
+    Ok(cues)
+}
+
+/// Render cues as a WebVTT document.
+///
+/// `mpegts` adds an `X-TIMESTAMP-MAP` header, required for HLS segments so
+/// players can line the cues up with the video's transport stream clock.
+pub fn render_vtt<'a>(cues: impl IntoIterator<Item = &'a Cue>, mpegts: Option<u64>) -> String {
+    let mut out = String::from("WEBVTT\n");
+    if let Some(mpegts) = mpegts {
+        let _ = writeln!(out, "X-TIMESTAMP-MAP=MPEGTS:{},LOCAL:00:00:00.000", mpegts);
+    }
+
+    for cue in cues {
+        out.push('\n');
+        let _ = write!(
+            out,
+            "{} --> {}",
+            format_timestamp(cue.start),
+            format_timestamp(cue.end)
+        );
+        if !cue.settings.is_empty() {
+            out.push(' ');
+            out.push_str(&cue.settings);
+        }
+        out.push('\n');
+        for line in &cue.text {
+            out.push_str(line);
+            out.push('\n');
+        }
+    }
+
+    out
+}
+
+/// Convert SubRip text to WebVTT
+pub fn srt_to_vtt(content: &str) -> Result<String> {
+    let cues = parse_cues(content)?;
+    if cues.is_empty() {
+        anyhow::bail!("No subtitle cues found");
+    }
+    Ok(render_vtt(&cues, None))
+}
+
+/// Turn an uploaded `.srt` or `.vtt` file into a clean WebVTT document
+pub fn to_webvtt(filename: &str, bytes: &[u8]) -> Result<String> {
+    let extension = Path::new(filename)
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_ascii_lowercase());
+
+    let content = std::str::from_utf8(bytes).context("Subtitle file must be UTF-8 encoded")?;
+    let is_vtt = content.trim_start_matches('\u{feff}').starts_with("WEBVTT");
+
+    match extension.as_deref() {
+        Some("vtt") if !is_vtt => anyhow::bail!("WebVTT file is missing the WEBVTT header"),
+        Some("vtt") | Some("srt") => {}
+        _ => anyhow::bail!("Unsupported subtitle format (expected .srt or .vtt)"),
+    }
+
+    srt_to_vtt(content)
+}
+
+/// Path of the full WebVTT file for a language
+pub fn track_path(video_dir: &Path, language: &str) -> PathBuf {
+    video_dir
+        .join(SUBTITLES_DIR)
+        .join(format!("{}.vtt", language))
+}
+
+/// Store the full WebVTT track for a language
+pub async fn write_track(video_dir: &Path, language: &str, vtt: &str) -> Result<()> {
+    let path = track_path(video_dir, language);
+    fs::create_dir_all(video_dir.join(SUBTITLES_DIR))
+        .await
+        .context("Failed to create subtitles directory")?;
+    fs::write(&path, vtt)
+        .await
+        .with_context(|| format!("Failed to write subtitle track {:?}", path))?;
+    Ok(())
+}
+
+/// Remove a language's full track and its HLS rendition
+pub async fn remove_track(video_dir: &Path, language: &str) -> Result<()> {
+    let path = track_path(video_dir, language);
+    if path.exists() {
+        fs::remove_file(&path)
+            .await
+            .with_context(|| format!("Failed to remove subtitle track {:?}", path))?;
+    }
+
+    let rendition_dir = video_dir.join(SUBTITLES_DIR).join(language);
+    if rendition_dir.exists() {
+        fs::remove_dir_all(&rendition_dir)
+            .await
+            .with_context(|| format!("Failed to remove subtitle rendition {:?}", rendition_dir))?;
+    }
+    Ok(())
+}
+
+/// Segment timing of the transcoded video
+#[derive(Debug, Clone, Default, PartialEq)]
+struct VideoTimeline {
+    /// `#EXTINF` durations of the first variant
+    segments: Vec<f64>,
+    /// PTS of the first sample in the first `.ts` segment
+    mpegts: Option<u64>,
+}
+
+/// Read segment durations and the first segment file from a media playlist
+pub(crate) fn parse_media_playlist(content: &str) -> (Vec<f64>, Option<String>) {
+    let mut durations = Vec::new();
+    let mut first_segment = None;
+    for line in content.lines().map(str::trim) {
+        if let Some(value) = line.strip_prefix("#EXTINF:") {
+            if let Ok(duration) = value.trim_end_matches(',').split(',').next().unwrap_or("").parse() {
+                durations.push(duration);
+            }
+        } else if !line.is_empty() && !line.starts_with('#') && first_segment.is_none() {
+            first_segment = Some(line.to_string());
+        }
+    }
+    (durations, first_segment)
+}
+
+/// Lowest presentation timestamp among the first PES packets of a transport stream
+fn first_pts(ts: &[u8]) -> Option<u64> {
+    const PACKET: usize = 188;
+    let mut lowest: Option<u64> = None;
+
+    for packet in ts.chunks_exact(PACKET) {
+        if packet[0] != 0x47 || packet[1] & 0x40 == 0 {
+            continue;
+        }
+
+        // Skip the adaptation field when present
+        let mut offset = 4;
+        match (packet[3] >> 4) & 0x3 {
+            0b01 => {}
+            0b11 => offset += 1 + packet[4] as usize,
+            _ => continue,
+        }
+
+        let Some(pes) = packet.get(offset..) else {
+            continue;
+        };
+        if pes.len() < 14 || pes[..3] != [0, 0, 1] || pes[7] & 0x80 == 0 {
+            continue;
+        }
+
+        let pts = ((((pes[9] >> 1) & 0x07) as u64) << 30)
+            | ((pes[10] as u64) << 22)
+            | (((pes[11] >> 1) as u64) << 15)
+            | ((pes[12] as u64) << 7)
+            | ((pes[13] >> 1) as u64);
+        lowest = Some(lowest.map_or(pts, |l| l.min(pts)));
+    }
+
+    lowest
+}
+
+/// Derive the subtitle timeline from the first rendition in `master.m3u8`
+async fn read_video_timeline(video_dir: &Path, master: &str) -> VideoTimeline {
+    let Some(variant) = master
+        .lines()
+        .map(str::trim)
+        .find(|l| !l.is_empty() && !l.starts_with('#'))
+    else {
+        return VideoTimeline::default();
+    };
+
+    let variant_path = video_dir.join(variant);
+    let Ok(content) = fs::read_to_string(&variant_path).await else {
+        return VideoTimeline::default();
+    };
+    let (segments, first_segment) = parse_media_playlist(&content);
+
+    let mut mpegts = None;
+    if let (Some(segment), Some(parent)) = (first_segment, variant_path.parent()) {
+        if let Ok(file) = fs::File::open(parent.join(segment)).await {
+            let mut head = Vec::with_capacity(64 * 1024);
+            if file.take(64 * 1024).read_to_end(&mut head).await.is_ok() {
+                mpegts = first_pts(&head);
+            }
+        }
+    }
+
+    VideoTimeline { segments, mpegts }
+}
+
+/// Split cues into per-segment groups. A cue spanning a boundary is repeated
+/// in every segment it overlaps, as RFC 8216 requires.
+fn segment_cues<'a>(cues: &'a [Cue], durations: &[f64]) -> Vec<Vec<&'a Cue>> {
+    let mut segments = Vec::with_capacity(durations.len());
+    let mut start = 0.0;
+    for duration in durations {
+        let end = start + duration;
+        segments.push(
+            cues.iter()
+                .filter(|c| c.start < end && c.end > start)
+                .collect(),
+        );
+        start = end;
+    }
+    segments
+}
+
+/// Segment durations: the video's own, or a uniform grid covering every cue
+fn segment_durations(timeline: &VideoTimeline, cues: &[Cue]) -> Vec<f64> {
+    if !timeline.segments.is_empty() {
+        return timeline.segments.clone();
+    }
+
+    let last_end = cues.iter().map(|c| c.end).fold(0.0, f64::max);
+    let count = (last_end / FALLBACK_SEGMENT_DURATION).ceil().max(1.0) as usize;
+    vec![FALLBACK_SEGMENT_DURATION; count]
+}
+
+/// Write the segmented HLS rendition for one track
+async fn write_rendition(video_dir: &Path, language: &str, timeline: &VideoTimeline) -> Result<()> {
+    let vtt = fs::read_to_string(track_path(video_dir, language))
+        .await
+        .context("Failed to read subtitle track")?;
+    let cues = parse_cues(&vtt)?;
+    let durations = segment_durations(timeline, &cues);
+    // Without a transport stream to probe, assume cue time 0 is stream time 0
+    let mpegts = timeline.mpegts.or(Some(0));
+
+    let rendition_dir = video_dir.join(SUBTITLES_DIR).join(language);
+    if rendition_dir.exists() {
+        fs::remove_dir_all(&rendition_dir)
+            .await
+            .context("Failed to clear subtitle rendition")?;
+    }
+    fs::create_dir_all(&rendition_dir)
+        .await
+        .context("Failed to create subtitle rendition directory")?;
+
+    let target_duration = durations.iter().fold(0.0, |a: f64, &b| a.max(b)).ceil() as u64;
+    let mut playlist = format!(
+        "#EXTM3U\n#EXT-X-VERSION:3\n#EXT-X-TARGETDURATION:{}\n#EXT-X-MEDIA-SEQUENCE:0\n#EXT-X-PLAYLIST-TYPE:VOD\n",
+        target_duration.max(1)
+    );
+
+    for (i, (segment, duration)) in segment_cues(&cues, &durations)
+        .into_iter()
+        .zip(&durations)
+        .enumerate()
+    {
+        let name = format!("segment_{:03}.vtt", i);
+        fs::write(rendition_dir.join(&name), render_vtt(segment, mpegts))
+            .await
+            .context("Failed to write subtitle segment")?;
+        let _ = write!(playlist, "#EXTINF:{:.3},\n{}\n", duration, name);
+    }
+    playlist.push_str("#EXT-X-ENDLIST\n");
+
+    fs::write(rendition_dir.join("index.m3u8"), playlist)
+        .await
+        .context("Failed to write subtitle playlist")?;
+    Ok(())
+}
+
+/// Strip an attribute (`,NAME=value` or `,NAME="value"`) from a tag line
+fn remove_attribute(line: &str, name: &str) -> String {
+    let needle = format!(",{}=", name);
+    let Some(start) = line.find(&needle) else {
+        return line.to_string();
+    };
+    let value_start = start + needle.len();
+    let rest = &line[value_start..];
+    let value_len = if let Some(quoted) = rest.strip_prefix('"') {
+        quoted.find('"').map(|i| i + 2).unwrap_or(rest.len())
+    } else {
+        rest.find(',').unwrap_or(rest.len())
+    };
+    format!("{}{}", &line[..start], &line[value_start + value_len..])
+}
+
+/// Rewrite a master playlist so it advertises exactly `tracks`
+pub fn rewrite_master_playlist(master: &str, tracks: &[SubtitleTrack]) -> String {
+    let mut media = String::new();
+    for track in tracks {
+        let _ = writeln!(
+            media,
+            "#EXT-X-MEDIA:TYPE=SUBTITLES,GROUP-ID=\"{}\",NAME=\"{}\",LANGUAGE=\"{}\",DEFAULT={},AUTOSELECT=YES,URI=\"{}/{}/index.m3u8\"",
+            SUBTITLE_GROUP_ID,
+            quote_attribute(&track.label),
+            track.language,
+            if track.is_default { "YES" } else { "NO" },
+            SUBTITLES_DIR,
+            track.language
+        );
+    }
+
+    let mut out = String::new();
+    let mut media_written = false;
+    for line in master.lines() {
+        if line.starts_with("#EXT-X-MEDIA:") && line.contains("TYPE=SUBTITLES") {
+            continue;
+        }
+
+        if line.starts_with("#EXT-X-STREAM-INF:") {
+            if !media_written {
+                out.push_str(&media);
+                media_written = true;
+            }
+            let mut line = remove_attribute(line, "SUBTITLES");
+            if !tracks.is_empty() {
+                let _ = write!(line, ",SUBTITLES=\"{}\"", SUBTITLE_GROUP_ID);
+            }
+            out.push_str(&line);
+        } else {
+            out.push_str(line);
+        }
+        out.push('\n');
+    }
+
+    if !media_written {
+        out.push_str(&media);
+    }
+    out
+}
+
+/// Build HLS renditions for every stored track and update `master.m3u8`.
+///
+/// Tracks whose `.vtt` file is missing are left out. Videos without a master
+/// playlist (MP4, or HLS still transcoding) are skipped; they are packaged
+/// once transcoding finishes.
+pub async fn package_subtitles(video_dir: &Path, tracks: &[SubtitleTrack]) -> Result<()> {
+    let master_path = video_dir.join("master.m3u8");
+    let Ok(master) = fs::read_to_string(&master_path).await else {
+        return Ok(());
+    };
+
+    let timeline = read_video_timeline(video_dir, &master).await;
+
+    let mut packaged = Vec::new();
+    for track in tracks {
+        if !track_path(video_dir, &track.language).exists() {
+            warn!("Subtitle track {} has no file, skipping", track.language);
+            continue;
+        }
+        match write_rendition(video_dir, &track.language, &timeline).await {
+            Ok(()) => packaged.push(track.clone()),
+            Err(e) => warn!("Failed to package subtitle track {}: {:#}", track.language, e),
+        }
+    }
+
+    fs::write(&master_path, rewrite_master_playlist(&master, &packaged))
+        .await
+        .context("Failed to update master playlist")?;
+
+    info!(
+        "Packaged {} subtitle track(s) into {:?}",
+        packaged.len(),
+        master_path
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SRT: &str = "1\r\n00:00:01,000 --> 00:00:04,500\r\nHello\r\n\r\n2\r\n00:00:05,250 --> 00:00:07,000 X1:10 X2:20 Y1:5 Y2:6\r\nSecond line\r\nwrapped\r\n\r\n";
+
+    fn track(language: &str, label: &str, is_default: bool) -> SubtitleTrack {
+        SubtitleTrack {
+            id: 1,
+            media_id: 1,
+            language: language.to_string(),
+            label: label.to_string(),
+            is_default,
+            auto_generated: false,
+            created_at: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_normalize_language() {
+        assert_eq!(normalize_language("en").as_deref(), Some("en"));
+        assert_eq!(normalize_language("PT_br").as_deref(), Some("pt-BR"));
+        assert_eq!(normalize_language("zh-hant").as_deref(), Some("zh-Hant"));
+        assert_eq!(normalize_language("../etc"), None);
+        assert_eq!(normalize_language("e"), None);
+        assert_eq!(normalize_language("en-"), None);
+        assert_eq!(normalize_language(""), None);
+    }
+
+    #[test]
+    fn test_timestamps() {
+        assert_eq!(parse_timestamp("00:01:02,345"), Some(62.345));
+        assert_eq!(parse_timestamp("01:02.500"), Some(62.5));
+        assert_eq!(parse_timestamp("100:00:00.000"), Some(360000.0));
+        assert_eq!(parse_timestamp("00:60:00.000"), None);
+        assert_eq!(parse_timestamp("1:02.5"), None);
+        assert_eq!(format_timestamp(3725.5), "01:02:05.500");
+    }
+
+    #[test]
+    fn test_srt_to_vtt() {
+        let vtt = srt_to_vtt(SRT).unwrap();
+        assert_eq!(
+            vtt,
+            "WEBVTT\n\n00:00:01.000 --> 00:00:04.500\nHello\n\n00:00:05.250 --> 00:00:07.000\nSecond line\nwrapped\n"
+        );
+        assert!(srt_to_vtt("not subtitles").is_err());
+    }
+
+    #[test]
+    fn test_parse_vtt_skips_metadata_blocks() {
+        let vtt = "\u{feff}WEBVTT - title\n\nNOTE a comment\n\nSTYLE\n::cue { color: red }\n\nintro\n00:02.000 --> 00:03.000 align:start line:0\n<i>Hi</i>\n";
+        let cues = parse_cues(vtt).unwrap();
+        assert_eq!(cues.len(), 1);
+        assert_eq!(cues[0].start, 2.0);
+        assert_eq!(cues[0].settings, "align:start line:0");
+        assert_eq!(cues[0].text, vec!["<i>Hi</i>"]);
+    }
+
+    #[test]
+    fn test_to_webvtt_checks_format() {
+        assert!(to_webvtt("subs.srt", SRT.as_bytes()).is_ok());
+        assert!(to_webvtt("subs.VTT", b"WEBVTT\n\n00:01.000 --> 00:02.000\nHi\n").is_ok());
+        assert!(to_webvtt("subs.vtt", SRT.as_bytes()).is_err());
+        assert!(to_webvtt("subs.txt", SRT.as_bytes()).is_err());
+        assert!(to_webvtt("subs.srt", &[0xff, 0xfe, 0x00]).is_err());
+    }
+
+    #[test]
+    fn test_segment_cues_repeats_spanning_cues() {
+        let cues = parse_cues(SRT).unwrap();
+        let segments = segment_cues(&cues, &[4.0, 4.0, 4.0]);
+        assert_eq!(segments[0].len(), 1);
+        assert_eq!(segments[1].len(), 2);
+        assert!(segments[2].is_empty());
+
+        let timeline = VideoTimeline::default();
+        assert_eq!(segment_durations(&timeline, &cues), vec![6.0, 6.0]);
+    }
+
+    #[test]
+    fn test_parse_media_playlist() {
+        let playlist = "#EXTM3U\n#EXT-X-TARGETDURATION:6\n#EXTINF:6.006000,\nsegment_000.ts\n#EXTINF:2.5,\nsegment_001.ts\n#EXT-X-ENDLIST\n";
+        let (durations, first) = parse_media_playlist(playlist);
+        assert_eq!(durations, vec![6.006, 2.5]);
+        assert_eq!(first.as_deref(), Some("segment_000.ts"));
+    }
+
+    #[test]
+    fn test_first_pts() {
+        // One TS packet, payload-only, carrying a PES header with PTS = 126000
+        let pts: u64 = 126_000;
+        let mut packet = vec![0u8; 188];
+        packet[..4].copy_from_slice(&[0x47, 0x41, 0x00, 0x10]);
+        packet[4..13].copy_from_slice(&[0, 0, 1, 0xe0, 0, 0, 0x80, 0x80, 5]);
+        packet[13] = 0x21 | (((pts >> 30) as u8 & 0x07) << 1);
+        packet[14] = (pts >> 22) as u8;
+        packet[15] = (((pts >> 15) as u8) << 1) | 1;
+        packet[16] = (pts >> 7) as u8;
+        packet[17] = ((pts as u8) << 1) | 1;
+        assert_eq!(first_pts(&packet), Some(pts));
+        assert_eq!(first_pts(&[0u8; 188]), None);
+    }
+
+    #[test]
+    fn test_rewrite_master_playlist() {
+        let master = "#EXTM3U\n#EXT-X-VERSION:3\n#EXT-X-STREAM-INF:BANDWIDTH=2628000,RESOLUTION=1280x720\n720p/index.m3u8\n";
+        let tracks = [track("en", "English \"CC\"", true), track("fr", "Français", false)];
+
+        let rewritten = rewrite_master_playlist(master, &tracks);
+        assert!(rewritten.contains(
+            "#EXT-X-MEDIA:TYPE=SUBTITLES,GROUP-ID=\"subs\",NAME=\"English 'CC'\",LANGUAGE=\"en\",DEFAULT=YES,AUTOSELECT=YES,URI=\"subtitles/en/index.m3u8\"\n"
+        ));
+        assert!(rewritten.contains("LANGUAGE=\"fr\",DEFAULT=NO"));
+        assert!(rewritten.contains("RESOLUTION=1280x720,SUBTITLES=\"subs\"\n720p/index.m3u8"));
+
+        // Idempotent, and removing every track restores the original
+        assert_eq!(rewrite_master_playlist(&rewritten, &tracks), rewritten);
+        assert_eq!(rewrite_master_playlist(&rewritten, &[]), master);
+    }
+
+    #[tokio::test]
+    async fn test_package_subtitles() {
+        let dir = tempfile::tempdir().unwrap();
+        let video_dir = dir.path();
+        fs::create_dir_all(video_dir.join("720p")).await.unwrap();
+        fs::write(
+            video_dir.join("master.m3u8"),
+            "#EXTM3U\n#EXT-X-VERSION:3\n#EXT-X-STREAM-INF:BANDWIDTH=1,RESOLUTION=2x2\n720p/index.m3u8\n",
+        )
+        .await
+        .unwrap();
+        fs::write(
+            video_dir.join("720p/index.m3u8"),
+            "#EXTM3U\n#EXTINF:4.000,\nsegment_000.ts\n#EXTINF:4.000,\nsegment_001.ts\n#EXT-X-ENDLIST\n",
+        )
+        .await
+        .unwrap();
+
+        write_track(video_dir, "en", &srt_to_vtt(SRT).unwrap())
+            .await
+            .unwrap();
+        package_subtitles(video_dir, &[track("en", "English", true), track("de", "Deutsch", false)])
+            .await
+            .unwrap();
+
+        let playlist = fs::read_to_string(video_dir.join("subtitles/en/index.m3u8"))
+            .await
+            .unwrap();
+        assert_eq!(playlist.matches("#EXTINF:4.000,").count(), 2);
+        let segment = fs::read_to_string(video_dir.join("subtitles/en/segment_001.vtt"))
+            .await
+            .unwrap();
+        assert!(segment.starts_with("WEBVTT\nX-TIMESTAMP-MAP=MPEGTS:0,LOCAL:00:00:00.000\n"));
+        assert!(segment.contains("Second line"));
+
+        // Only the track with a file is advertised
+        let master = fs::read_to_string(video_dir.join("master.m3u8")).await.unwrap();
+        assert!(master.contains("LANGUAGE=\"en\""));
+        assert!(!master.contains("LANGUAGE=\"de\""));
+
+        remove_track(video_dir, "en").await.unwrap();
+        assert!(!video_dir.join("subtitles/en").exists());
+        assert!(!track_path(video_dir, "en").exists());
+    }
+}
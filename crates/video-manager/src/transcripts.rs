@@ -0,0 +1,888 @@
+//! Speech-to-text transcripts
+//!
+//! After transcoding, the audio track is extracted with ffmpeg and handed to
+//! an [`AsrBackend`]:
+//!
+//! - [`WhisperCppBackend`] runs a local whisper.cpp binary (`whisper-cli`)
+//! - [`OpenAiAsrBackend`] posts to an OpenAI-compatible
+//!   `/audio/transcriptions` endpoint, using the video owner's LLM provider
+//!
+//! The timestamped segments are stored in `media_transcript_segments`, turned
+//! into an auto-generated WebVTT caption track and indexed for media search.
+//!
+//! Configuration (environment):
+//!
+//! ```text
+//! ASR_BACKEND=whisper-cpp | openai      unset disables transcripts
+//! ASR_LANGUAGE=en                       spoken language (default: detect)
+//! WHISPER_CPP_BINARY=whisper-cli
+//! WHISPER_CPP_MODEL=/models/ggml-base.bin
+//! ASR_PROVIDER=openai                   LLM provider name (default: the owner's default)
+//! ASR_MODEL=whisper-1
+//! ```
+
+use crate::ffmpeg::FFmpegConfig;
+use crate::subtitles::{self, Cue};
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use db::llm_providers::LlmProviderRepository;
+use db::media::{MediaRepository, TranscriptSegment, TRANSCRIPT_FAILED, TRANSCRIPT_PENDING};
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::process::Command;
+use tracing::{info, warn};
+
+/// Upper bound on the transcript text indexed for search
+pub const MAX_TRANSCRIPT_TEXT_BYTES: usize = 2 * 1024 * 1024;
+
+/// Upload limit of OpenAI's `/audio/transcriptions`
+const OPENAI_MAX_AUDIO_BYTES: u64 = 25 * 1024 * 1024;
+
+/// Longest caption line before wrapping
+const CAPTION_LINE_WIDTH: usize = 42;
+
+/// Language recorded when neither the backend nor the config names one
+const UNDETERMINED_LANGUAGE: &str = "und";
+
+/// Audio container an ASR backend accepts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AudioFormat {
+    /// 16 kHz mono 16-bit PCM, what whisper.cpp reads natively
+    Wav,
+    /// 16 kHz mono low-bitrate MP3, small enough for upload limits
+    Mp3,
+}
+
+impl AudioFormat {
+    fn extension(&self) -> &'static str {
+        match self {
+            Self::Wav => "wav",
+            Self::Mp3 => "mp3",
+        }
+    }
+}
+
+/// Result of transcribing an audio file
+#[derive(Debug, Clone, Default)]
+pub struct AsrOutput {
+    /// Language reported by the backend, as it reported it
+    pub language: Option<String>,
+    pub segments: Vec<TranscriptSegment>,
+}
+
+/// A speech recognition engine
+#[async_trait]
+pub trait AsrBackend: Send + Sync {
+    /// Name stored with the transcript
+    fn name(&self) -> &'static str;
+
+    /// Audio format `transcribe` expects
+    fn audio_format(&self) -> AudioFormat;
+
+    /// Transcribe an audio file. `language` is a hint; `None` lets the
+    /// backend detect it.
+    async fn transcribe(&self, audio: &Path, language: Option<&str>) -> Result<AsrOutput>;
+}
+
+// ── whisper.cpp ─────────────────────────────────────────────────────
+
+/// Local whisper.cpp binary
+pub struct WhisperCppBackend {
+    pub binary: PathBuf,
+    pub model: PathBuf,
+}
+
+#[derive(Deserialize)]
+struct WhisperCppOutput {
+    #[serde(default)]
+    result: Option<WhisperCppResult>,
+    #[serde(default)]
+    transcription: Vec<WhisperCppSegment>,
+}
+
+#[derive(Deserialize)]
+struct WhisperCppResult {
+    language: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct WhisperCppSegment {
+    offsets: WhisperCppOffsets,
+    text: String,
+}
+
+/// Segment offsets in milliseconds
+#[derive(Deserialize)]
+struct WhisperCppOffsets {
+    from: u64,
+    to: u64,
+}
+
+/// Parse the JSON file written by `whisper-cli -oj`
+fn parse_whisper_cpp(json: &str) -> Result<AsrOutput> {
+    let output: WhisperCppOutput =
+        serde_json::from_str(json).context("Invalid whisper.cpp JSON output")?;
+    Ok(AsrOutput {
+        language: output.result.and_then(|r| r.language),
+        segments: output
+            .transcription
+            .into_iter()
+            .map(|s| TranscriptSegment {
+                start_time: s.offsets.from as f64 / 1000.0,
+                end_time: s.offsets.to as f64 / 1000.0,
+                text: s.text,
+            })
+            .collect(),
+    })
+}
+
+#[async_trait]
+impl AsrBackend for WhisperCppBackend {
+    fn name(&self) -> &'static str {
+        "whisper-cpp"
+    }
+
+    fn audio_format(&self) -> AudioFormat {
+        AudioFormat::Wav
+    }
+
+    async fn transcribe(&self, audio: &Path, language: Option<&str>) -> Result<AsrOutput> {
+        // `-of` takes the output path without the `.json` extension
+        let stem = audio.with_extension("");
+        let output = Command::new(&self.binary)
+            .arg("-m")
+            .arg(&self.model)
+            .arg("-f")
+            .arg(audio)
+            .args(["-l", language.unwrap_or("auto"), "-oj", "-np", "-of"])
+            .arg(&stem)
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .output()
+            .await
+            .with_context(|| format!("Failed to execute {:?}", self.binary))?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            anyhow::bail!(
+                "whisper.cpp failed with status: {}\nError: {}",
+                output.status,
+                stderr.lines().take(10).collect::<Vec<_>>().join("\n")
+            );
+        }
+
+        let json_path = stem.with_extension("json");
+        let json = tokio::fs::read_to_string(&json_path)
+            .await
+            .with_context(|| format!("whisper.cpp wrote no transcript to {:?}", json_path))?;
+        parse_whisper_cpp(&json)
+    }
+}
+
+// ── OpenAI-compatible endpoint ──────────────────────────────────────
+
+/// OpenAI-compatible `/audio/transcriptions` endpoint
+pub struct OpenAiAsrBackend {
+    pub client: reqwest::Client,
+    /// Base URL including the version (`https://api.openai.com/v1`)
+    pub api_url: String,
+    pub api_key: String,
+    pub model: String,
+}
+
+#[derive(Deserialize)]
+struct OpenAiTranscription {
+    language: Option<String>,
+    duration: Option<f64>,
+    #[serde(default)]
+    text: String,
+    #[serde(default)]
+    segments: Vec<OpenAiSegment>,
+}
+
+#[derive(Deserialize)]
+struct OpenAiSegment {
+    start: f64,
+    end: f64,
+    text: String,
+}
+
+/// Parse a `verbose_json` transcription response
+///
+/// Servers that ignore `verbose_json` return only `text`; it becomes a
+/// single segment spanning the reported duration.
+fn parse_openai(json: &str) -> Result<AsrOutput> {
+    let response: OpenAiTranscription =
+        serde_json::from_str(json).context("Invalid transcription response")?;
+    let mut segments: Vec<TranscriptSegment> = response
+        .segments
+        .into_iter()
+        .map(|s| TranscriptSegment {
+            start_time: s.start,
+            end_time: s.end,
+            text: s.text,
+        })
+        .collect();
+    if segments.is_empty() && !response.text.trim().is_empty() {
+        segments.push(TranscriptSegment {
+            start_time: 0.0,
+            end_time: response.duration.unwrap_or(0.0),
+            text: response.text,
+        });
+    }
+    Ok(AsrOutput {
+        language: response.language,
+        segments,
+    })
+}
+
+#[async_trait]
+impl AsrBackend for OpenAiAsrBackend {
+    fn name(&self) -> &'static str {
+        "openai"
+    }
+
+    fn audio_format(&self) -> AudioFormat {
+        AudioFormat::Mp3
+    }
+
+    async fn transcribe(&self, audio: &Path, language: Option<&str>) -> Result<AsrOutput> {
+        let bytes = tokio::fs::read(audio)
+            .await
+            .with_context(|| format!("Failed to read {:?}", audio))?;
+        if bytes.len() as u64 > OPENAI_MAX_AUDIO_BYTES {
+            anyhow::bail!(
+                "Audio track is {} bytes, over the {} byte upload limit",
+                bytes.len(),
+                OPENAI_MAX_AUDIO_BYTES
+            );
+        }
+
+        let file = reqwest::multipart::Part::bytes(bytes)
+            .file_name("audio.mp3")
+            .mime_str("audio/mpeg")?;
+        let mut form = reqwest::multipart::Form::new()
+            .part("file", file)
+            .text("model", self.model.clone())
+            .text("response_format", "verbose_json")
+            .text("timestamp_granularities[]", "segment");
+        if let Some(language) = language {
+            form = form.text("language", language.to_string());
+        }
+
+        let url = format!(
+            "{}/audio/transcriptions",
+            self.api_url.trim_end_matches('/')
+        );
+        let response = self
+            .client
+            .post(&url)
+            .bearer_auth(&self.api_key)
+            .multipart(form)
+            .timeout(Duration::from_secs(30 * 60))
+            .send()
+            .await
+            .with_context(|| format!("Failed to reach {}", url))?;
+
+        let status = response.status();
+        let body = response
+            .text()
+            .await
+            .context("Failed to read transcription response")?;
+        if !status.is_success() {
+            anyhow::bail!(
+                "Transcription request failed with status {}: {}",
+                status,
+                body.chars().take(500).collect::<String>()
+            );
+        }
+        parse_openai(&body)
+    }
+}
+
+// ── Configuration ───────────────────────────────────────────────────
+
+/// Which ASR backend to use
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AsrBackendKind {
+    WhisperCpp,
+    OpenAi,
+}
+
+impl AsrBackendKind {
+    /// Parse an `ASR_BACKEND` value
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "whisper-cpp" | "whisper.cpp" | "whisper" => Some(Self::WhisperCpp),
+            "openai" | "openai-compatible" => Some(Self::OpenAi),
+            _ => None,
+        }
+    }
+}
+
+/// Transcript configuration
+#[derive(Debug, Clone)]
+pub struct TranscriptConfig {
+    /// Backend to use; `None` disables transcripts
+    pub backend: Option<AsrBackendKind>,
+    /// Spoken language hint; `None` lets the backend detect it
+    pub language: Option<String>,
+    /// whisper.cpp binary
+    pub whisper_binary: PathBuf,
+    /// whisper.cpp model file (ggml)
+    pub whisper_model: Option<PathBuf>,
+    /// LLM provider name for the OpenAI-compatible backend; `None` uses the
+    /// video owner's default provider
+    pub provider: Option<String>,
+    /// Model requested from the OpenAI-compatible backend
+    pub model: String,
+}
+
+impl Default for TranscriptConfig {
+    fn default() -> Self {
+        Self {
+            backend: None,
+            language: None,
+            whisper_binary: PathBuf::from("whisper-cli"),
+            whisper_model: None,
+            provider: None,
+            model: "whisper-1".to_string(),
+        }
+    }
+}
+
+impl TranscriptConfig {
+    /// Load from environment (see the module documentation)
+    pub fn from_env() -> Self {
+        let var = |name: &str| {
+            std::env::var(name)
+                .ok()
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty())
+        };
+        let defaults = Self::default();
+
+        let backend = var("ASR_BACKEND").and_then(|value| {
+            let kind = AsrBackendKind::parse(&value);
+            if kind.is_none() {
+                warn!("Unknown ASR_BACKEND {:?}; transcripts are disabled", value);
+            }
+            kind
+        });
+
+        Self {
+            backend,
+            language: var("ASR_LANGUAGE").and_then(|l| subtitles::normalize_language(&l)),
+            whisper_binary: var("WHISPER_CPP_BINARY")
+                .map(PathBuf::from)
+                .unwrap_or(defaults.whisper_binary),
+            whisper_model: var("WHISPER_CPP_MODEL").map(PathBuf::from),
+            provider: var("ASR_PROVIDER"),
+            model: var("ASR_MODEL").unwrap_or(defaults.model),
+        }
+    }
+}
+
+// ── Transcriber ─────────────────────────────────────────────────────
+
+/// Outcome of a transcript run
+#[derive(Debug, Clone)]
+pub struct TranscriptSummary {
+    pub language: String,
+    pub segments: usize,
+    /// Whether the caption track was (re)written
+    pub captions: bool,
+}
+
+/// Runs transcripts for videos with the configured backend
+#[derive(Clone)]
+pub struct Transcriber {
+    config: Arc<TranscriptConfig>,
+    ffmpeg_config: FFmpegConfig,
+    http_client: reqwest::Client,
+    llm_providers: Option<Arc<dyn LlmProviderRepository>>,
+}
+
+impl Transcriber {
+    pub fn new(
+        config: TranscriptConfig,
+        ffmpeg_config: FFmpegConfig,
+        http_client: reqwest::Client,
+    ) -> Self {
+        Self {
+            config: Arc::new(config),
+            ffmpeg_config,
+            http_client,
+            llm_providers: None,
+        }
+    }
+
+    /// Resolve OpenAI-compatible endpoints through the users' LLM providers
+    pub fn with_llm_providers(mut self, repo: Arc<dyn LlmProviderRepository>) -> Self {
+        self.llm_providers = Some(repo);
+        self
+    }
+
+    /// Whether a backend is configured
+    pub fn is_enabled(&self) -> bool {
+        self.config.backend.is_some()
+    }
+
+    /// Name of the configured backend
+    pub fn backend_name(&self) -> &'static str {
+        match self.config.backend {
+            Some(AsrBackendKind::WhisperCpp) => "whisper-cpp",
+            Some(AsrBackendKind::OpenAi) => "openai",
+            None => "none",
+        }
+    }
+
+    /// Build the backend for a video owned by `user_id`
+    async fn backend(&self, user_id: Option<&str>) -> Result<Box<dyn AsrBackend>> {
+        match self.config.backend {
+            None => anyhow::bail!("No ASR backend configured (set ASR_BACKEND)"),
+            Some(AsrBackendKind::WhisperCpp) => {
+                let model = self
+                    .config
+                    .whisper_model
+                    .clone()
+                    .context("WHISPER_CPP_MODEL is not set")?;
+                Ok(Box::new(WhisperCppBackend {
+                    binary: self.config.whisper_binary.clone(),
+                    model,
+                }))
+            }
+            Some(AsrBackendKind::OpenAi) => {
+                let repo = self
+                    .llm_providers
+                    .as_ref()
+                    .context("LLM providers are not available for transcription")?;
+                let user_id =
+                    user_id.context("Video has no owner to resolve an LLM provider for")?;
+                let provider = match &self.config.provider {
+                    Some(name) => repo
+                        .get_provider_by_name(user_id, name)
+                        .await?
+                        .with_context(|| format!("LLM provider {:?} not found", name))?,
+                    None => repo
+                        .get_default_provider(user_id)
+                        .await?
+                        .context("No default LLM provider configured")?,
+                };
+                if provider.provider == "anthropic" {
+                    anyhow::bail!(
+                        "LLM provider {:?} has no speech-to-text endpoint",
+                        provider.name
+                    );
+                }
+                let api_key = llm_provider::crypto::decrypt_api_key(&provider.api_key_encrypted)?;
+                Ok(Box::new(OpenAiAsrBackend {
+                    client: self.http_client.clone(),
+                    api_url: provider.api_url,
+                    api_key,
+                    model: self.config.model.clone(),
+                }))
+            }
+        }
+    }
+
+    /// Transcribe a video and publish the result
+    ///
+    /// `source` is any file ffmpeg can read the audio from; `video_dir` must
+    /// hold the video's HLS playlists locally so captions can be packaged.
+    /// The run is recorded as pending, then complete or failed.
+    pub async fn transcribe_video(
+        &self,
+        media_repo: &dyn MediaRepository,
+        media_id: i64,
+        source: &Path,
+        video_dir: &Path,
+        user_id: Option<&str>,
+    ) -> Result<TranscriptSummary> {
+        media_repo
+            .set_transcript_status(media_id, self.backend_name(), TRANSCRIPT_PENDING, None)
+            .await?;
+
+        let result = self
+            .run(media_repo, media_id, source, video_dir, user_id)
+            .await;
+        if let Err(e) = &result {
+            let message = format!("{:#}", e);
+            if let Err(e) = media_repo
+                .set_transcript_status(
+                    media_id,
+                    self.backend_name(),
+                    TRANSCRIPT_FAILED,
+                    Some(&message),
+                )
+                .await
+            {
+                warn!("Failed to record transcript failure: {}", e);
+            }
+        }
+        result
+    }
+
+    async fn run(
+        &self,
+        media_repo: &dyn MediaRepository,
+        media_id: i64,
+        source: &Path,
+        video_dir: &Path,
+        user_id: Option<&str>,
+    ) -> Result<TranscriptSummary> {
+        let backend = self.backend(user_id).await?;
+
+        let work_dir = std::env::temp_dir()
+            .join("transcripts")
+            .join(uuid::Uuid::new_v4().to_string());
+        tokio::fs::create_dir_all(&work_dir)
+            .await
+            .context("Failed to create transcript work directory")?;
+        let audio = work_dir.join(format!("audio.{}", backend.audio_format().extension()));
+
+        let output = match extract_audio(
+            &self.ffmpeg_config,
+            source,
+            &audio,
+            backend.audio_format(),
+        )
+        .await
+        {
+            Ok(()) => {
+                backend
+                    .transcribe(&audio, self.config.language.as_deref())
+                    .await
+            }
+            Err(e) => Err(e),
+        };
+        if let Err(e) = tokio::fs::remove_dir_all(&work_dir).await {
+            warn!(
+                "Failed to remove transcript work directory {:?}: {}",
+                work_dir, e
+            );
+        }
+        let output = output?;
+
+        let segments = clean_segments(output.segments);
+        let language = output
+            .language
+            .as_deref()
+            .and_then(language_code)
+            .or_else(|| self.config.language.clone())
+            .unwrap_or_else(|| UNDETERMINED_LANGUAGE.to_string());
+
+        media_repo
+            .save_transcript(media_id, backend.name(), Some(&language), &segments)
+            .await?;
+
+        let captions =
+            publish_captions(media_repo, media_id, video_dir, &language, &segments).await?;
+
+        let text = transcript_text(&segments);
+        media_repo.set_media_text(media_id as i32, &text).await?;
+
+        info!(
+            "Transcribed media {} with {}: {} segments ({})",
+            media_id,
+            backend.name(),
+            segments.len(),
+            language
+        );
+        Ok(TranscriptSummary {
+            language,
+            segments: segments.len(),
+            captions,
+        })
+    }
+}
+
+/// Extract a 16 kHz mono audio track for speech recognition
+pub async fn extract_audio(
+    config: &FFmpegConfig,
+    source: &Path,
+    output_path: &Path,
+    format: AudioFormat,
+) -> Result<()> {
+    let codec: &[&str] = match format {
+        AudioFormat::Wav => &["-c:a", "pcm_s16le", "-f", "wav"],
+        AudioFormat::Mp3 => &["-c:a", "libmp3lame", "-b:a", "32k", "-f", "mp3"],
+    };
+
+    let output = Command::new(&config.ffmpeg_path)
+        .args(["-v", "error", "-i"])
+        .arg(source)
+        .args(["-map", "0:a:0", "-vn", "-ac", "1", "-ar", "16000"])
+        .args(codec)
+        .arg("-y")
+        .arg(output_path)
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .output()
+        .await
+        .context("Failed to execute ffmpeg for audio extraction")?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!(
+            "FFmpeg audio extraction failed with status: {}\nError: {}",
+            output.status,
+            stderr.lines().take(10).collect::<Vec<_>>().join("\n")
+        );
+    }
+
+    if !output_path.exists() {
+        anyhow::bail!("Audio file was not created: {:?}", output_path);
+    }
+    Ok(())
+}
+
+/// Write the auto-generated caption track and rebuild the HLS renditions
+///
+/// A track uploaded by the owner for the same language is left alone.
+async fn publish_captions(
+    media_repo: &dyn MediaRepository,
+    media_id: i64,
+    video_dir: &Path,
+    language: &str,
+    segments: &[TranscriptSegment],
+) -> Result<bool> {
+    let cues = segments_to_cues(segments);
+    if cues.is_empty() {
+        return Ok(false);
+    }
+
+    let tracks = media_repo.list_subtitles(media_id).await?;
+    let existing = tracks.iter().find(|t| t.language == language);
+    if existing.is_some_and(|t| !t.auto_generated) {
+        info!(
+            "Media {} already has an uploaded {} track; captions not replaced",
+            media_id, language
+        );
+        return Ok(false);
+    }
+
+    subtitles::write_track(video_dir, language, &subtitles::render_vtt(&cues, None)).await?;
+    media_repo
+        .upsert_subtitle(
+            media_id,
+            language,
+            &format!("{} (auto-generated)", language),
+            existing.is_some_and(|t| t.is_default),
+            true,
+        )
+        .await?;
+
+    let tracks = media_repo.list_subtitles(media_id).await?;
+    subtitles::package_subtitles(video_dir, &tracks).await?;
+    Ok(true)
+}
+
+/// Map a backend's language report (`en`, `english`) to a BCP 47 tag
+fn language_code(reported: &str) -> Option<String> {
+    let name = reported.trim().to_ascii_lowercase();
+    let code = match name.as_str() {
+        "arabic" => "ar",
+        "chinese" => "zh",
+        "czech" => "cs",
+        "danish" => "da",
+        "dutch" => "nl",
+        "english" => "en",
+        "finnish" => "fi",
+        "french" => "fr",
+        "german" => "de",
+        "greek" => "el",
+        "hindi" => "hi",
+        "italian" => "it",
+        "japanese" => "ja",
+        "korean" => "ko",
+        "norwegian" => "no",
+        "polish" => "pl",
+        "portuguese" => "pt",
+        "russian" => "ru",
+        "spanish" => "es",
+        "swedish" => "sv",
+        "turkish" => "tr",
+        "ukrainian" => "uk",
+        other if other.len() <= 3 || other.contains(['-', '_']) => other,
+        _ => return None,
+    };
+    subtitles::normalize_language(code)
+}
+
+/// Trim segment text and drop empty segments
+fn clean_segments(segments: Vec<TranscriptSegment>) -> Vec<TranscriptSegment> {
+    segments
+        .into_iter()
+        .filter_map(|mut segment| {
+            segment.text = segment
+                .text
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" ");
+            (!segment.text.is_empty()).then_some(segment)
+        })
+        .collect()
+}
+
+/// Caption cues for transcript segments, wrapped to caption line width
+fn segments_to_cues(segments: &[TranscriptSegment]) -> Vec<Cue> {
+    segments
+        .iter()
+        .filter(|s| s.end_time > s.start_time)
+        .map(|s| Cue {
+            start: s.start_time,
+            end: s.end_time,
+            settings: String::new(),
+            text: wrap_caption(&s.text, CAPTION_LINE_WIDTH),
+        })
+        .collect()
+}
+
+/// Break text into lines of at most `width` characters at word boundaries
+fn wrap_caption(text: &str, width: usize) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    let mut current = String::new();
+    for word in text.split_whitespace() {
+        if !current.is_empty() && current.chars().count() + 1 + word.chars().count() > width {
+            lines.push(std::mem::take(&mut current));
+        }
+        if !current.is_empty() {
+            current.push(' ');
+        }
+        current.push_str(word);
+    }
+    if !current.is_empty() {
+        lines.push(current);
+    }
+    lines
+}
+
+/// Plain transcript text for the search index
+fn transcript_text(segments: &[TranscriptSegment]) -> String {
+    let mut text = segments
+        .iter()
+        .map(|s| s.text.as_str())
+        .collect::<Vec<_>>()
+        .join(" ");
+    if text.len() > MAX_TRANSCRIPT_TEXT_BYTES {
+        let mut end = MAX_TRANSCRIPT_TEXT_BYTES;
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+        text.truncate(end);
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segment(start: f64, end: f64, text: &str) -> TranscriptSegment {
+        TranscriptSegment {
+            start_time: start,
+            end_time: end,
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_parse_whisper_cpp() {
+        let json = r#"{
+            "result": {"language": "en"},
+            "transcription": [
+                {"timestamps": {"from": "00:00:00,000", "to": "00:00:02,500"},
+                 "offsets": {"from": 0, "to": 2500}, "text": " Welcome to the workshop."},
+                {"timestamps": {"from": "00:00:02,500", "to": "00:00:05,000"},
+                 "offsets": {"from": 2500, "to": 5000}, "text": " Let's begin."}
+            ]
+        }"#;
+        let output = parse_whisper_cpp(json).unwrap();
+        assert_eq!(output.language.as_deref(), Some("en"));
+        assert_eq!(
+            output.segments,
+            vec![
+                segment(0.0, 2.5, " Welcome to the workshop."),
+                segment(2.5, 5.0, " Let's begin."),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_openai() {
+        let json = r#"{
+            "task": "transcribe", "language": "german", "duration": 4.2,
+            "text": "Hallo zusammen.",
+            "segments": [{"id": 0, "seek": 0, "start": 0.0, "end": 4.2, "text": " Hallo zusammen."}]
+        }"#;
+        let output = parse_openai(json).unwrap();
+        assert_eq!(output.language.as_deref(), Some("german"));
+        assert_eq!(output.segments, vec![segment(0.0, 4.2, " Hallo zusammen.")]);
+
+        // Plain `json` responses carry only the text
+        let output = parse_openai(r#"{"text": "Hello", "duration": 3.0}"#).unwrap();
+        assert_eq!(output.segments, vec![segment(0.0, 3.0, "Hello")]);
+    }
+
+    #[test]
+    fn test_language_code() {
+        assert_eq!(language_code("english").as_deref(), Some("en"));
+        assert_eq!(language_code("EN").as_deref(), Some("en"));
+        assert_eq!(language_code("pt_br").as_deref(), Some("pt-BR"));
+        assert_eq!(language_code("klingon"), None);
+    }
+
+    #[test]
+    fn test_segments_to_cues() {
+        let segments = clean_segments(vec![
+            segment(0.0, 2.0, "  Hello   there "),
+            segment(2.0, 2.0, "zero length"),
+            segment(3.0, 4.0, "   "),
+            segment(
+                4.0,
+                9.0,
+                "This sentence is long enough that it has to be wrapped over two lines",
+            ),
+        ]);
+        assert_eq!(segments.len(), 3);
+
+        let cues = segments_to_cues(&segments);
+        assert_eq!(cues.len(), 2);
+        assert_eq!(cues[0].text, vec!["Hello there"]);
+        assert_eq!(
+            cues[1].text,
+            vec![
+                "This sentence is long enough that it has",
+                "to be wrapped over two lines"
+            ]
+        );
+        assert!(subtitles::render_vtt(&cues, None).contains("00:00:04.000 --> 00:00:09.000"));
+    }
+
+    #[test]
+    fn test_transcript_text() {
+        let segments = vec![segment(0.0, 1.0, "one"), segment(1.0, 2.0, "two")];
+        assert_eq!(transcript_text(&segments), "one two");
+
+        let long = vec![segment(0.0, 1.0, &"é".repeat(MAX_TRANSCRIPT_TEXT_BYTES))];
+        let text = transcript_text(&long);
+        assert!(text.len() <= MAX_TRANSCRIPT_TEXT_BYTES);
+    }
+
+    #[test]
+    fn test_backend_kind_parse() {
+        assert_eq!(
+            AsrBackendKind::parse("whisper-cpp"),
+            Some(AsrBackendKind::WhisperCpp)
+        );
+        assert_eq!(
+            AsrBackendKind::parse(" OpenAI "),
+            Some(AsrBackendKind::OpenAi)
+        );
+        assert_eq!(AsrBackendKind::parse("vosk"), None);
+    }
+}
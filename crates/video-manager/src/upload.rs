@@ -4,11 +4,11 @@
 //! - File validation (type, size)
 //! - Temporary storage
 //! - Initial database record creation
-//! - Spawning background processing tasks
+//! - Queueing videos for background processing
 
 use crate::ffmpeg::FFmpegConfig;
 use crate::hls::HlsConfig;
-use crate::processing::{process_video, ProcessingContext};
+use crate::queue::TranscodeQueue;
 use crate::progress::ProgressTracker;
 use crate::storage::StorageConfig;
 use anyhow::{Context, Result};
@@ -93,6 +93,7 @@ pub struct UploadState {
     pub audit_logger: crate::metrics::AuditLogger,
     pub media_repo: Arc<dyn db::media::MediaRepository>,
     pub vault_repo: Arc<dyn db::vaults::VaultRepository>,
+    pub transcode_queue: TranscodeQueue,
 }
 
 impl UploadState {
@@ -106,6 +107,7 @@ impl UploadState {
         audit_logger: crate::metrics::AuditLogger,
         media_repo: Arc<dyn db::media::MediaRepository>,
         vault_repo: Arc<dyn db::vaults::VaultRepository>,
+        transcode_queue: TranscodeQueue,
     ) -> Self {
         Self {
             pool,
@@ -117,6 +119,7 @@ impl UploadState {
             audit_logger,
             media_repo,
             vault_repo,
+            transcode_queue,
         }
     }
 }
@@ -129,7 +132,7 @@ impl UploadState {
 /// 3. Validates file type and size
 /// 4. Saves file to temporary storage
 /// 5. Creates initial database record
-/// 6. Spawns background processing task
+/// 6. Queues the video for background processing
 /// 7. Returns upload ID for progress tracking
 pub async fn handle_video_upload(
     session: Session,
@@ -232,41 +235,39 @@ pub async fn handle_video_upload(
         }
     };
 
-    // Spawn background processing task
-    let processing_context = ProcessingContext {
+    // Hand off to the transcoding queue
+    let job = db::jobs::CreateTranscodeJob {
         upload_id: upload_id.clone(),
         slug: slug.clone(),
         vault_id: vault_id.clone(),
-        temp_file_path: upload_data.temp_file_path.clone(),
-        is_public: upload_data.is_public,
-        original_filename: upload_data.original_filename.clone(),
-        pool: state.pool.clone(),
-        storage_config: state.storage_config.clone(),
-        ffmpeg_config: state.ffmpeg_config.clone(),
-        hls_config: state.hls_config.clone(),
-        progress_tracker: state.progress_tracker.clone(),
-        metrics_store: state.metrics_store.clone(),
-        audit_logger: state.audit_logger.clone(),
         user_id: Some(user_id.clone()),
-        media_repo: state.media_repo.clone(),
+        source_path: upload_data.temp_file_path.to_string_lossy().to_string(),
+        original_filename: upload_data.original_filename.clone(),
+        is_public: upload_data.is_public,
+        priority: 0,
+        max_attempts: 3,
     };
 
-    // Spawn processing in background
-    tokio::spawn(async move {
-        info!(
-            "Starting background processing for upload_id: {}",
-            processing_context.upload_id
+    if let Err(e) = state.transcode_queue.enqueue(&job).await {
+        error!("Failed to queue video processing: {:#}", e);
+        state.progress_tracker.set_error(
+            &upload_id,
+            "Failed to queue video for processing.".to_string(),
         );
-        if let Err(e) = process_video(processing_context).await {
-            error!("Video processing failed: {}", e);
-        }
-    });
+        return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(UploadErrorResponse {
+                success: false,
+                error: "Failed to queue video for processing.".to_string(),
+            }),
+        ));
+    }
 
     Ok(Json(UploadResponse {
         success: true,
         upload_id: upload_id.clone(),
         slug: slug.clone(),
-        message: "Upload started, processing in background".to_string(),
+        message: "Upload complete, queued for processing".to_string(),
         progress_url: format!("/api/videos/upload/{}/progress", upload_id),
     }))
 }
@@ -563,6 +564,7 @@ pub async fn handle_video_upload_vms(
         audit_logger: state.audit_logger.clone(),
         media_repo: state.repo.clone(),
         vault_repo: state.vault_repo.clone(),
+        transcode_queue: state.transcode_queue.clone(),
     });
 
     // Call the original handler
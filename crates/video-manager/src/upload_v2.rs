@@ -5,7 +5,7 @@
 
 use crate::ffmpeg::FFmpegConfig;
 use crate::hls::HlsConfig;
-use crate::processing::{process_video, ProcessingContext};
+use crate::queue::TranscodeQueue;
 use crate::progress::ProgressTracker;
 use crate::storage::StorageConfig;
 use anyhow::{Context, Result};
@@ -88,6 +88,7 @@ pub struct UploadState {
     pub audit_logger: crate::metrics::AuditLogger,
     pub media_repo: Arc<dyn db::media::MediaRepository>,
     pub vault_repo: Arc<dyn db::vaults::VaultRepository>,
+    pub transcode_queue: TranscodeQueue,
 }
 
 impl UploadState {
@@ -101,6 +102,7 @@ impl UploadState {
         audit_logger: crate::metrics::AuditLogger,
         media_repo: Arc<dyn db::media::MediaRepository>,
         vault_repo: Arc<dyn db::vaults::VaultRepository>,
+        transcode_queue: TranscodeQueue,
     ) -> Self {
         Self {
             pool,
@@ -112,6 +114,7 @@ impl UploadState {
             audit_logger,
             media_repo,
             vault_repo,
+            transcode_queue,
         }
     }
 }
@@ -128,7 +131,7 @@ impl UploadState {
 /// 3. Uses media-core for file validation and metadata extraction
 /// 4. Saves file to temporary storage
 /// 5. Creates initial database record
-/// 6. Spawns background processing task
+/// 6. Queues the video for background processing
 /// 7. Returns upload ID for progress tracking
 pub async fn handle_video_upload_v2(
     session: Session,
@@ -258,41 +261,39 @@ pub async fn handle_video_upload_v2(
         }
     };
 
-    // Spawn background processing task
-    let processing_context = ProcessingContext {
+    // Hand off to the transcoding queue
+    let job = db::jobs::CreateTranscodeJob {
         upload_id: upload_id.clone(),
         slug: slug.clone(),
         vault_id: vault_id.clone(),
-        temp_file_path: temp_file_path.clone(),
-        is_public: upload_request.metadata.is_public,
-        original_filename: upload_request.original_filename.clone(),
-        pool: state.pool.clone(),
-        storage_config: state.storage_config.clone(),
-        ffmpeg_config: state.ffmpeg_config.clone(),
-        hls_config: state.hls_config.clone(),
-        progress_tracker: state.progress_tracker.clone(),
-        metrics_store: state.metrics_store.clone(),
-        audit_logger: state.audit_logger.clone(),
         user_id: Some(user_id.clone()),
-        media_repo: state.media_repo.clone(),
+        source_path: temp_file_path.to_string_lossy().to_string(),
+        original_filename: upload_request.original_filename.clone(),
+        is_public: upload_request.metadata.is_public,
+        priority: 0,
+        max_attempts: 3,
     };
 
-    // Spawn processing in background
-    tokio::spawn(async move {
-        info!(
-            "Starting background processing for upload_id: {}",
-            processing_context.upload_id
+    if let Err(e) = state.transcode_queue.enqueue(&job).await {
+        error!("Failed to queue video processing: {:#}", e);
+        state.progress_tracker.set_error(
+            &upload_id,
+            "Failed to queue video for processing.".to_string(),
         );
-        if let Err(e) = process_video(processing_context).await {
-            error!("Video processing failed: {}", e);
-        }
-    });
+        return Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(UploadErrorResponse {
+                success: false,
+                error: "Failed to queue video for processing.".to_string(),
+            }),
+        ));
+    }
 
     Ok(Json(UploadResponse {
         success: true,
         upload_id: upload_id.clone(),
         slug: slug.clone(),
-        message: "Upload started, processing in background".to_string(),
+        message: "Upload complete, queued for processing".to_string(),
         progress_url: format!("/api/videos/progress/{}", upload_id),
     }))
 }
@@ -4,8 +4,10 @@
 //! (site-overview, agent-registry, etc.) can share auth logic without
 //! depending on workspace-manager.
 
-use api_keys::middleware::{require_scope, AuthenticatedUser};
+use api_keys::middleware::{require_scope, require_scope_for, AuthenticatedUser};
+use api_keys::ResourceRef;
 use axum::{extract::Extension, http::StatusCode};
+use db::api_keys::path_within;
 use db::workspaces::WorkspaceRepository;
 use tower_sessions::Session;
 
@@ -56,6 +58,9 @@ pub async fn verify_workspace_ownership(
 }
 
 /// Check API key scope if authenticated via API key (session auth has full permissions).
+///
+/// Keys bound to a workspace, vault or folder must also cover the workspace and
+/// `path` named by the request URL.
 pub fn check_scope(
     user_ext: &Option<Extension<AuthenticatedUser>>,
     scope: &str,
@@ -65,3 +70,49 @@ pub fn check_scope(
     }
     Ok(())
 }
+
+/// Check API key scope for a resource the handler acts on, e.g. a path from the
+/// request body that the URL does not name (session auth has full permissions).
+pub fn check_scope_for(
+    user_ext: &Option<Extension<AuthenticatedUser>>,
+    scope: &str,
+    resource: &ResourceRef,
+) -> Result<(), StatusCode> {
+    if let Some(Extension(user)) = user_ext {
+        require_scope_for(user, scope, resource)?;
+    }
+    Ok(())
+}
+
+/// Check API key scope for a listing of `path`, which may be a folder above
+/// the one a key is bound to (session auth has full permissions).
+///
+/// Returns the folder a folder-bound key is limited to; the handler must then
+/// only show entries for which [`listing_visible`] holds.
+pub fn check_scope_for_listing(
+    user_ext: &Option<Extension<AuthenticatedUser>>,
+    scope: &str,
+    workspace_id: &str,
+    path: &str,
+) -> Result<Option<String>, StatusCode> {
+    let Some(Extension(user)) = user_ext else {
+        return Ok(None);
+    };
+    let folder = user.api_key.as_ref().and_then(|key| key.path_prefix.clone());
+    let target = match &folder {
+        // Above the key's folder: the key must cover the folder itself
+        Some(folder) if path_within(folder, path) => folder.as_str(),
+        _ => path,
+    };
+    require_scope_for(user, scope, &ResourceRef::workspace(workspace_id).with_path(target))?;
+    Ok(folder)
+}
+
+/// Whether a listing entry at `path` is visible to a key bound to `folder`:
+/// it lies inside the folder, or is a folder on the way down to it.
+pub fn listing_visible(folder: Option<&str>, path: &str) -> bool {
+    match folder {
+        Some(folder) => path_within(path, folder) || path_within(folder, path),
+        None => true,
+    }
+}
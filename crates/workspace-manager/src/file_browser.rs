@@ -177,16 +177,18 @@ pub fn list_dir(workspace_root: &Path, subpath: &str) -> Result<DirListing> {
     Ok(DirListing { folders, files })
 }
 
-/// Search files across the entire workspace by name substring (case-insensitive).
+/// Search files under `within` (relative to the workspace root, empty for the
+/// whole workspace) by name substring (case-insensitive).
 /// Returns up to `limit` matches sorted by relevance (exact name match first, then path match).
-pub fn search_files(workspace_root: &Path, query: &str, limit: usize) -> Vec<FileEntry> {
-    if !workspace_root.exists() || query.is_empty() {
+pub fn search_files(workspace_root: &Path, within: &str, query: &str, limit: usize) -> Vec<FileEntry> {
+    let search_root = workspace_root.join(within);
+    if !search_root.exists() || query.is_empty() {
         return Vec::new();
     }
 
     let query_lower = query.to_lowercase();
 
-    let mut results: Vec<(u8, FileEntry)> = WalkDir::new(workspace_root)
+    let mut results: Vec<(u8, FileEntry)> = WalkDir::new(&search_root)
         .into_iter()
         .filter_entry(|e| {
             // Skip hidden directories entirely
@@ -275,6 +277,7 @@ pub fn recent_files(workspace_root: &Path, limit: usize) -> Vec<FileEntry> {
 
     let mut file_entries: Vec<(u64, FileEntry)> = WalkDir::new(workspace_root)
         .into_iter()
+        .filter_entry(|e| !crate::trash::is_trash_dir(e))
         .filter_map(|e| e.ok())
         .filter(|e| e.file_type().is_file())
         .filter(|e| {
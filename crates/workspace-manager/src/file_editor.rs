@@ -20,6 +20,11 @@ fn safe_resolve(workspace_root: &Path, rel_path: &str) -> Result<PathBuf> {
         bail!("Empty file path");
     }
 
+    // The trash is only reachable through the trash endpoints
+    if clean.split('/').next() == Some(crate::trash::TRASH_DIR) {
+        bail!("Path is inside the trash");
+    }
+
     let target = workspace_root.join(clean);
 
     // Verify it stays within workspace root after resolution
@@ -85,17 +90,3 @@ pub fn save_bytes(workspace_root: &Path, rel_path: &str, data: &[u8]) -> Result<
     std::fs::write(&path, data)?;
     Ok(())
 }
-
-/// Delete a file or directory within the workspace.
-pub fn delete_path(workspace_root: &Path, rel_path: &str) -> Result<()> {
-    let path = safe_resolve(workspace_root, rel_path)?;
-    if !path.exists() {
-        bail!("Path not found: {:?}", path);
-    }
-    if path.is_dir() {
-        std::fs::remove_dir_all(&path)?;
-    } else {
-        std::fs::remove_file(&path)?;
-    }
-    Ok(())
-}
@@ -1,13 +1,17 @@
-use crate::helpers::{check_scope, require_auth, verify_workspace_ownership};
+use crate::helpers::{
+    check_scope, check_scope_for, check_scope_for_listing, listing_visible, require_auth,
+    verify_workspace_ownership,
+};
 use crate::{WorkspaceManagerState, SaveFileRequest, MkdirRequest, DeleteFileQuery, RenameFileRequest, CopyFileRequest, CreateFileRequest, SaveTextBody, SaveBpmnBody, BpmnSaveResponse, ServeFileQuery, UpdateFolderMetadataRequest, WorkspaceConfig};
 use crate::file_editor;
 use crate::file_browser;
+use crate::trash;
 use crate::workspace_access;
 use api_keys::middleware::AuthenticatedUser;
+use api_keys::ResourceRef;
 use axum::{
-    body::Body,
     extract::{Multipart, Path, Query, State},
-    http::{header, StatusCode},
+    http::{HeaderMap, StatusCode},
     response::{Json, Response},
     Extension,
 };
@@ -57,7 +61,11 @@ pub(crate) async fn save_file(
     State(state): State<Arc<WorkspaceManagerState>>,
     Json(request): Json<SaveFileRequest>,
 ) -> Result<StatusCode, StatusCode> {
-    check_scope(&user, "write")?;
+    check_scope_for(
+        &user,
+        "write",
+        &ResourceRef::workspace(&workspace_id).with_path(&request.path),
+    )?;
     let user_id = require_auth(&session).await?;
     verify_workspace_ownership(state.repo.as_ref(), &workspace_id, &user_id).await?;
 
@@ -78,7 +86,11 @@ pub(crate) async fn create_folder(
     State(state): State<Arc<WorkspaceManagerState>>,
     Json(request): Json<MkdirRequest>,
 ) -> Result<StatusCode, StatusCode> {
-    check_scope(&user, "write")?;
+    check_scope_for(
+        &user,
+        "write",
+        &ResourceRef::workspace(&workspace_id).with_path(&request.path),
+    )?;
     let user_id = require_auth(&session).await?;
     verify_workspace_ownership(state.repo.as_ref(), &workspace_id, &user_id).await?;
 
@@ -94,6 +106,8 @@ pub(crate) async fn create_folder(
 }
 
 /// DELETE /api/workspaces/{workspace_id}/files?path=...
+///
+/// Moves the file or folder to the workspace trash.
 pub(crate) async fn delete_file(
     user: Option<Extension<AuthenticatedUser>>,
     Path(workspace_id): Path<String>,
@@ -101,31 +115,15 @@ pub(crate) async fn delete_file(
     session: Session,
     State(state): State<Arc<WorkspaceManagerState>>,
 ) -> Result<StatusCode, StatusCode> {
-    check_scope(&user, "write")?;
+    check_scope_for(
+        &user,
+        "write",
+        &ResourceRef::workspace(&workspace_id).with_path(&query.path),
+    )?;
     let user_id = require_auth(&session).await?;
     verify_workspace_ownership(state.repo.as_ref(), &workspace_id, &user_id).await?;
 
-    let workspace_root = state.storage.workspace_root(&workspace_id);
-
-    // Check if it's a directory before deleting
-    let abs_path = workspace_root.join(&query.path);
-    let is_dir = abs_path.is_dir();
-
-    file_editor::delete_path(&workspace_root, &query.path).map_err(|e| {
-        warn!("Failed to delete path: {}", e);
-        StatusCode::BAD_REQUEST
-    })?;
-
-    // If it was a directory, remove from workspace.yaml and access codes
-    if is_dir {
-        if let Ok(mut config) = WorkspaceConfig::load(&workspace_root) {
-            config.remove_folder(&query.path);
-            if let Err(e) = config.save(&workspace_root) {
-                warn!("Failed to update workspace.yaml: {}", e);
-            }
-        }
-        state.repo.delete_access_code_folders_for_path(&workspace_id, &query.path).await.ok();
-    }
+    trash::move_to_trash(&state, &workspace_id, &query.path, &user_id).await?;
 
     Ok(StatusCode::NO_CONTENT)
 }
@@ -140,7 +138,7 @@ pub(crate) async fn list_dirs(
     session: Session,
     State(state): State<Arc<WorkspaceManagerState>>,
 ) -> Result<Json<Vec<serde_json::Value>>, StatusCode> {
-    check_scope(&user, "read")?;
+    let bound_folder = check_scope_for_listing(&user, "read", &workspace_id, "")?;
     let user_id = require_auth(&session).await?;
     verify_workspace_ownership(state.repo.as_ref(), &workspace_id, &user_id).await?;
 
@@ -152,11 +150,15 @@ pub(crate) async fn list_dirs(
     for e in walkdir::WalkDir::new(&workspace_root)
         .min_depth(1)
         .into_iter()
+        .filter_entry(|e| !trash::is_trash_dir(e))
         .filter_map(|e| e.ok())
         .filter(|e| e.file_type().is_dir())
     {
         if let Ok(rel) = e.path().strip_prefix(&workspace_root) {
             let path = rel.to_string_lossy().to_string();
+            if !listing_visible(bound_folder.as_deref(), &path) {
+                continue;
+            }
             dirs.push(serde_json::json!({ "path": path, "label": path }));
         }
     }
@@ -182,11 +184,11 @@ pub(crate) async fn list_files_handler(
     session: Session,
     State(state): State<Arc<WorkspaceManagerState>>,
 ) -> Result<Json<serde_json::Value>, StatusCode> {
-    check_scope(&user, "read")?;
+    let path = query.get("path").cloned().unwrap_or_default();
+    let bound_folder = check_scope_for_listing(&user, "read", &workspace_id, &path)?;
     let user_id = require_auth(&session).await?;
     verify_workspace_ownership(state.repo.as_ref(), &workspace_id, &user_id).await?;
 
-    let path = query.get("path").cloned().unwrap_or_default();
     let type_filter = query.get("type_filter").cloned().unwrap_or_default();
     let workspace_root = state.storage.workspace_root(&workspace_id);
 
@@ -196,6 +198,7 @@ pub(crate) async fn list_files_handler(
     let folders: Vec<serde_json::Value> = listing
         .folders
         .into_iter()
+        .filter(|f| listing_visible(bound_folder.as_deref(), &f.path))
         .filter(|f| {
             if type_filter.is_empty() {
                 return true;
@@ -214,6 +217,7 @@ pub(crate) async fn list_files_handler(
     let files: Vec<serde_json::Value> = listing
         .files
         .into_iter()
+        .filter(|f| listing_visible(bound_folder.as_deref(), &f.path))
         .filter(|f| {
             if type_filter.is_empty() {
                 return true;
@@ -245,7 +249,7 @@ pub(crate) async fn search_files_handler(
     session: Session,
     State(state): State<Arc<WorkspaceManagerState>>,
 ) -> Result<Json<serde_json::Value>, StatusCode> {
-    check_scope(&user, "read")?;
+    let bound_folder = check_scope_for_listing(&user, "read", &workspace_id, "")?;
     let user_id = require_auth(&session).await?;
     verify_workspace_ownership(state.repo.as_ref(), &workspace_id, &user_id).await?;
 
@@ -253,7 +257,9 @@ pub(crate) async fn search_files_handler(
     let type_filter = query.get("type_filter").cloned().unwrap_or_default();
     let workspace_root = state.storage.workspace_root(&workspace_id);
 
-    let results = file_browser::search_files(&workspace_root, &q, 50);
+    // A folder-bound key only searches its folder
+    let within = bound_folder.as_deref().unwrap_or("");
+    let results = file_browser::search_files(&workspace_root, within, &q, 50);
 
     let files: Vec<serde_json::Value> = results
         .into_iter()
@@ -289,16 +295,21 @@ pub(crate) async fn context_files_handler(
     session: Session,
     State(state): State<Arc<WorkspaceManagerState>>,
 ) -> Result<Json<serde_json::Value>, StatusCode> {
-    check_scope(&user, "read")?;
+    let path = query.get("path").cloned().unwrap_or_default();
+    let scope = query.get("scope").cloned().unwrap_or_else(|| "folder".to_string());
+    let recursive = scope == "workspace";
+    let listed = if recursive { "" } else { path.as_str() };
+    let bound_folder = check_scope_for_listing(&user, "read", &workspace_id, listed)?;
     let user_id = require_auth(&session).await?;
     verify_workspace_ownership(state.repo.as_ref(), &workspace_id, &user_id).await?;
 
-    let path = query.get("path").cloned().unwrap_or_default();
-    let scope = query.get("scope").cloned().unwrap_or_else(|| "folder".to_string());
     let workspace_root = state.storage.workspace_root(&workspace_id);
 
-    let recursive = scope == "workspace";
-    let subpath = if recursive { "" } else { &path };
+    // A folder-bound key reads the workspace scope from its folder only
+    let subpath = match &bound_folder {
+        Some(folder) if recursive => folder.as_str(),
+        _ => listed,
+    };
 
     // Max 50 KB per file, 100 KB total context
     let files = file_browser::collect_context_files(
@@ -308,6 +319,10 @@ pub(crate) async fn context_files_handler(
         50_000,
         100_000,
     );
+    let files: Vec<_> = files
+        .into_iter()
+        .filter(|f| listing_visible(bound_folder.as_deref(), &f.path))
+        .collect();
 
     Ok(Json(serde_json::json!({ "files": files })))
 }
@@ -324,7 +339,17 @@ pub(crate) async fn rename_file(
     State(state): State<Arc<WorkspaceManagerState>>,
     Json(request): Json<RenameFileRequest>,
 ) -> Result<StatusCode, StatusCode> {
-    check_scope(&user, "write")?;
+    check_scope_for(
+        &user,
+        "write",
+        &ResourceRef::workspace(&workspace_id).with_path(&request.from),
+    )?;
+    check_scope_for(
+        &user,
+        "write",
+        &ResourceRef::workspace(request.target_workspace_id.as_ref().unwrap_or(&workspace_id))
+            .with_path(&request.to),
+    )?;
     let user_id = require_auth(&session).await?;
     verify_workspace_ownership(state.repo.as_ref(), &workspace_id, &user_id).await?;
 
@@ -434,7 +459,17 @@ pub(crate) async fn copy_file(
     State(state): State<Arc<WorkspaceManagerState>>,
     Json(request): Json<CopyFileRequest>,
 ) -> Result<StatusCode, StatusCode> {
-    check_scope(&user, "write")?;
+    check_scope_for(
+        &user,
+        "write",
+        &ResourceRef::workspace(&workspace_id).with_path(&request.from),
+    )?;
+    check_scope_for(
+        &user,
+        "write",
+        &ResourceRef::workspace(request.target_workspace_id.as_ref().unwrap_or(&workspace_id))
+            .with_path(&request.to),
+    )?;
     let user_id = require_auth(&session).await?;
     verify_workspace_ownership(state.repo.as_ref(), &workspace_id, &user_id).await?;
 
@@ -531,7 +566,11 @@ pub(crate) async fn create_file(
     State(state): State<Arc<WorkspaceManagerState>>,
     Json(request): Json<CreateFileRequest>,
 ) -> Result<StatusCode, StatusCode> {
-    check_scope(&user, "write")?;
+    check_scope_for(
+        &user,
+        "write",
+        &ResourceRef::workspace(&workspace_id).with_path(&request.path),
+    )?;
     let user_id = require_auth(&session).await?;
     verify_workspace_ownership(state.repo.as_ref(), &workspace_id, &user_id).await?;
 
@@ -556,7 +595,11 @@ pub(crate) async fn save_text_content(
     State(state): State<Arc<WorkspaceManagerState>>,
     Json(body): Json<SaveTextBody>,
 ) -> Result<StatusCode, StatusCode> {
-    check_scope(&user, "write")?;
+    check_scope_for(
+        &user,
+        "write",
+        &ResourceRef::workspace(&workspace_id).with_path(&query.path),
+    )?;
     let user_id = require_auth(&session).await?;
     verify_workspace_ownership(state.repo.as_ref(), &workspace_id, &user_id).await?;
 
@@ -581,7 +624,11 @@ pub(crate) async fn save_bpmn_content(
     State(state): State<Arc<WorkspaceManagerState>>,
     Json(body): Json<SaveBpmnBody>,
 ) -> Result<Json<BpmnSaveResponse>, StatusCode> {
-    check_scope(&user, "write")?;
+    check_scope_for(
+        &user,
+        "write",
+        &ResourceRef::workspace(&workspace_id).with_path(&query.path),
+    )?;
     let user_id = require_auth(&session).await?;
     verify_workspace_ownership(state.repo.as_ref(), &workspace_id, &user_id).await?;
 
@@ -607,8 +654,13 @@ pub(crate) async fn serve_workspace_file(
     Query(query): Query<ServeFileQuery>,
     session: Session,
     State(state): State<Arc<WorkspaceManagerState>>,
+    headers: HeaderMap,
 ) -> Result<Response, StatusCode> {
-    check_scope(&user, "read")?;
+    check_scope_for(
+        &user,
+        "read",
+        &ResourceRef::workspace(&workspace_id).with_path(&query.path),
+    )?;
 
     // Try session auth first
     let session_ok = match require_auth(&session).await {
@@ -653,22 +705,16 @@ pub(crate) async fn serve_workspace_file(
         return Err(StatusCode::NOT_FOUND);
     }
 
-    let bytes = std::fs::read(&abs_path).map_err(|e| {
-        warn!("Failed to read file {:?}: {}", abs_path, e);
-        StatusCode::INTERNAL_SERVER_ERROR
-    })?;
-
     let mime_type = mime_guess::from_path(&abs_path)
         .first_or_octet_stream()
         .to_string();
 
-    let response = Response::builder()
-        .status(StatusCode::OK)
-        .header(header::CONTENT_TYPE, &mime_type)
-        .body(Body::from(bytes))
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-
-    Ok(response)
+    common::range::serve_file(&abs_path, &mime_type, &headers)
+        .await
+        .map_err(|e| {
+            warn!("Failed to read file {:?}: {}", abs_path, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })
 }
 
 /// GET /api/workspaces/{workspace_id}/folder-config?path=...
@@ -679,7 +725,11 @@ pub(crate) async fn get_folder_config(
     session: Session,
     State(state): State<Arc<WorkspaceManagerState>>,
 ) -> Result<Json<serde_json::Value>, StatusCode> {
-    check_scope(&user, "read")?;
+    check_scope_for(
+        &user,
+        "read",
+        &ResourceRef::workspace(&workspace_id).with_path(&query.path),
+    )?;
     let user_id = require_auth(&session).await?;
     verify_workspace_ownership(state.repo.as_ref(), &workspace_id, &user_id).await?;
 
@@ -728,7 +778,11 @@ pub(crate) async fn update_folder_metadata(
     State(state): State<Arc<WorkspaceManagerState>>,
     Json(request): Json<UpdateFolderMetadataRequest>,
 ) -> Result<StatusCode, StatusCode> {
-    check_scope(&user, "write")?;
+    check_scope_for(
+        &user,
+        "write",
+        &ResourceRef::workspace(&workspace_id).with_path(&request.path),
+    )?;
     let user_id = require_auth(&session).await?;
     verify_workspace_ownership(state.repo.as_ref(), &workspace_id, &user_id).await?;
 
@@ -929,7 +983,6 @@ pub(crate) async fn upload_file(
     State(state): State<Arc<WorkspaceManagerState>>,
     mut multipart: Multipart,
 ) -> Result<StatusCode, StatusCode> {
-    check_scope(&user, "write")?;
     let user_id = require_auth(&session).await?;
     verify_workspace_ownership(state.repo.as_ref(), &workspace_id, &user_id).await?;
 
@@ -969,6 +1022,11 @@ pub(crate) async fn upload_file(
     if path.trim().is_empty() {
         return Err(StatusCode::BAD_REQUEST);
     }
+    check_scope_for(
+        &user,
+        "write",
+        &ResourceRef::workspace(&workspace_id).with_path(&path),
+    )?;
 
     file_editor::save_bytes(&workspace_root, &path, &data).map_err(|e| {
         warn!("Upload failed for {}: {}", path, e);
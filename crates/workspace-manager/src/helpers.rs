@@ -2,6 +2,9 @@ use time::OffsetDateTime;
 
 // Re-export auth helpers from workspace-core so internal modules don't need to change imports.
 pub(crate) use workspace_core::auth::check_scope;
+pub(crate) use workspace_core::auth::check_scope_for;
+pub(crate) use workspace_core::auth::check_scope_for_listing;
+pub(crate) use workspace_core::auth::listing_visible;
 pub(crate) use workspace_core::auth::require_auth;
 pub(crate) use workspace_core::auth::verify_workspace_ownership;
 
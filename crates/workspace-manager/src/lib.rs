@@ -20,6 +20,7 @@ mod helpers;
 mod pages;
 mod presentation_handlers;
 mod publishing;
+mod trash;
 pub mod workspace_access;
 mod workspace_crud;
 
@@ -27,6 +28,7 @@ pub use file_browser::{collect_context_files, ContextFile, FileEntry, FolderEntr
 pub use folder_type_registry::{
     AgentRole, AppLink, FieldType, FolderTypeDefinition, FolderTypeRegistry, MetadataField,
 };
+pub use trash::start_trash_purge_task;
 // Re-export from workspace-core (canonical home)
 pub use workspace_core::{FolderConfig, FolderType, WorkspaceConfig};
 
@@ -264,6 +266,17 @@ pub struct WorkspaceDisplay {
     pub tags: Vec<String>,
 }
 
+#[derive(Clone)]
+pub struct TrashEntryDisplay {
+    pub id: i64,
+    pub original_path: String,
+    pub is_dir: bool,
+    pub size_str: String,
+    pub deleted_at_human: String,
+    /// Date the entry is purged for good
+    pub purge_after: String,
+}
+
 #[derive(Clone)]
 pub struct WorkspaceStats {
     pub image_count: usize,
@@ -333,6 +346,16 @@ pub struct WorkspaceBrowserTemplate {
     pub last_preview_url: String,
 }
 
+#[derive(Template)]
+#[template(path = "workspaces/trash.html")]
+pub struct WorkspaceTrashTemplate {
+    pub authenticated: bool,
+    pub workspace_id: String,
+    pub workspace_name: String,
+    pub entries: Vec<TrashEntryDisplay>,
+    pub retention_days: u64,
+}
+
 #[derive(Template)]
 #[template(path = "workspaces/image_viewer.html")]
 pub struct ImageViewerTemplate {
@@ -452,6 +475,18 @@ pub fn workspace_routes(state: Arc<WorkspaceManagerState>) -> Router {
             "/api/workspaces/{workspace_id}/files",
             delete(file_ops::delete_file),
         )
+        .route(
+            "/api/workspaces/{workspace_id}/trash",
+            get(trash::list_trash),
+        )
+        .route(
+            "/api/workspaces/{workspace_id}/trash/{entry_id}/restore",
+            post(trash::restore_entry),
+        )
+        .route(
+            "/api/workspaces/{workspace_id}/trash/{entry_id}",
+            delete(trash::delete_entry),
+        )
         .route(
             "/api/workspaces/{workspace_id}/files/new",
             post(file_ops::create_file),
@@ -591,6 +626,7 @@ pub fn workspace_routes(state: Arc<WorkspaceManagerState>) -> Router {
             "/workspaces/{workspace_id}/browse/{*path}",
             get(pages::file_browser_page),
         )
+        .route("/workspaces/{workspace_id}/trash", get(trash::trash_page))
         .route("/workspaces/{workspace_id}/edit", get(pages::open_file_page))
         .route(
             "/workspaces/{workspace_id}/edit-text",
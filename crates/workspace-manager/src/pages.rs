@@ -1,9 +1,11 @@
-use crate::helpers::{check_scope, require_auth, verify_workspace_ownership, format_human_date, monaco_language, agent_format_helper_html, parent_browse_url, typed_folder_browse_url, build_path_crumbs, count_files_in_dir};
+use crate::helpers::{check_scope, check_scope_for, check_scope_for_listing, listing_visible, require_auth, verify_workspace_ownership, format_human_date, monaco_language, agent_format_helper_html, parent_browse_url, typed_folder_browse_url, build_path_crumbs, count_files_in_dir};
 use crate::{WorkspaceManagerState, WorkspaceConfig, WorkspaceDisplay, WorkspaceStats, WorkspaceListTemplate, NewWorkspaceTemplate, WorkspaceDashboardTemplate, WorkspaceBrowserTemplate, ImageViewerTemplate, DrawioEditorTemplate, MermaidEditorTemplate, ExcalidrawEditorTemplate, MarkdownPreviewTemplate, AgentViewerTemplate};
 use crate::file_browser;
 use crate::file_editor;
 use workspace_core::FolderViewContext;
 use api_keys::middleware::AuthenticatedUser;
+use api_keys::ResourceRef;
+use db::api_keys::path_within;
 use askama::Template;
 use axum::{
     extract::{Path, Query, State},
@@ -125,7 +127,7 @@ pub(crate) async fn workspace_dashboard(
     session: Session,
     State(state): State<Arc<WorkspaceManagerState>>,
 ) -> Result<Html<String>, StatusCode> {
-    check_scope(&user, "read")?;
+    check_scope_for(&user, "read", &ResourceRef::workspace(&workspace_id))?;
     let user_id = require_auth(&session).await?;
     let (name, description) =
         verify_workspace_ownership(state.repo.as_ref(), &workspace_id, &user_id).await?;
@@ -144,6 +146,7 @@ pub(crate) async fn workspace_dashboard(
     if workspace_root.exists() {
         for entry in walkdir::WalkDir::new(&workspace_root)
             .into_iter()
+            .filter_entry(|e| !crate::trash::is_trash_dir(e))
             .filter_map(|e| e.ok())
             .filter(|e| e.file_type().is_file())
         {
@@ -271,9 +274,9 @@ pub(crate) async fn file_browser_page(
     State(state): State<Arc<WorkspaceManagerState>>,
     Query(query): Query<BrowseQuery>,
 ) -> Result<Response, StatusCode> {
-    check_scope(&user, "read")?;
     let (workspace_id, subpath) = path_parts;
-    file_browser_handler(workspace_id, subpath, session, state, query.files.as_deref() == Some("1")).await
+    let bound_folder = check_scope_for_listing(&user, "read", &workspace_id, &subpath)?;
+    file_browser_handler(workspace_id, subpath, bound_folder, session, state, query.files.as_deref() == Some("1")).await
 }
 
 /// GET /workspaces/{workspace_id}/browse
@@ -283,13 +286,16 @@ pub(crate) async fn file_browser_root_page(
     session: Session,
     State(state): State<Arc<WorkspaceManagerState>>,
 ) -> Result<Response, StatusCode> {
-    check_scope(&user, "read")?;
-    file_browser_handler(workspace_id, String::new(), session, state, false).await
+    let bound_folder = check_scope_for_listing(&user, "read", &workspace_id, "")?;
+    file_browser_handler(workspace_id, String::new(), bound_folder, session, state, false).await
 }
 
+/// `bound_folder` is the folder a folder-bound API key is limited to; above it
+/// only the folders on the way down are listed.
 async fn file_browser_handler(
     workspace_id: String,
     subpath: String,
+    bound_folder: Option<String>,
     session: Session,
     state: Arc<WorkspaceManagerState>,
     force_files: bool,
@@ -309,6 +315,12 @@ async fn file_browser_handler(
     // Load workspace config once — used for renderer lookup and folder type annotation
     let ws_config_opt = WorkspaceConfig::load(&workspace_root).ok();
 
+    // Folder views show the whole folder, so not above a key's bound folder
+    let force_files = force_files
+        || bound_folder
+            .as_deref()
+            .is_some_and(|folder| !path_within(&subpath, folder));
+
     // media-server folders redirect to the media library scoped to their vault
     if !force_files && !subpath.is_empty() {
         if let Some(ref ws_config) = ws_config_opt {
@@ -351,6 +363,12 @@ async fn file_browser_handler(
 
     let mut dir_listing =
         file_browser::list_dir(&workspace_root, &subpath).map_err(|_| StatusCode::NOT_FOUND)?;
+    dir_listing
+        .folders
+        .retain(|f| listing_visible(bound_folder.as_deref(), &f.path));
+    dir_listing
+        .files
+        .retain(|f| listing_visible(bound_folder.as_deref(), &f.path));
 
     // Annotate folders with their type info from workspace.yaml + registry.
     // Also resolve the type of the current directory being browsed.
@@ -504,12 +522,16 @@ pub(crate) async fn edit_text_file_page(
     session: Session,
     State(state): State<Arc<WorkspaceManagerState>>,
 ) -> Result<Html<String>, StatusCode> {
-    check_scope(&user, "read")?;
+    let file_path = query.file.unwrap_or_default();
+    check_scope_for(
+        &user,
+        "read",
+        &ResourceRef::workspace(&workspace_id).with_path(&file_path),
+    )?;
     let user_id = require_auth(&session).await?;
     let (workspace_name, _) =
         verify_workspace_ownership(state.repo.as_ref(), &workspace_id, &user_id).await?;
 
-    let file_path = query.file.unwrap_or_default();
     if file_path.is_empty() {
         return Err(StatusCode::BAD_REQUEST);
     }
@@ -593,12 +615,16 @@ pub(crate) async fn open_file_page(
     session: Session,
     State(state): State<Arc<WorkspaceManagerState>>,
 ) -> Result<Html<String>, StatusCode> {
-    check_scope(&user, "read")?;
+    let file_path = query.file.unwrap_or_default();
+    check_scope_for(
+        &user,
+        "read",
+        &ResourceRef::workspace(&workspace_id).with_path(&file_path),
+    )?;
     let user_id = require_auth(&session).await?;
     let (workspace_name, _) =
         verify_workspace_ownership(state.repo.as_ref(), &workspace_id, &user_id).await?;
 
-    let file_path = query.file.unwrap_or_default();
     if file_path.is_empty() {
         return Err(StatusCode::BAD_REQUEST);
     }
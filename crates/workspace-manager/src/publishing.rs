@@ -1,6 +1,7 @@
-use crate::helpers::{check_scope, require_auth, verify_workspace_ownership, slugify};
+use crate::helpers::{check_scope_for, check_scope_for_listing, require_auth, verify_workspace_ownership, slugify};
 use crate::{WorkspaceManagerState, WorkspaceConfig, MediaFolderInfo, PublishRequest, PublishResponse, PublishCourseRequest, PublishCourseResponse};
 use api_keys::middleware::AuthenticatedUser;
+use api_keys::ResourceRef;
 use axum::{
     extract::{Path, State},
     http::StatusCode,
@@ -9,6 +10,7 @@ use axum::{
 };
 use common::storage::MediaType;
 use course_processor::CourseConfig;
+use db::api_keys::path_within;
 use std::sync::Arc;
 use std::time::{SystemTime, UNIX_EPOCH};
 use tower_sessions::Session;
@@ -20,7 +22,7 @@ pub(crate) async fn list_media_folders(
     session: Session,
     State(state): State<Arc<WorkspaceManagerState>>,
 ) -> Result<Json<Vec<MediaFolderInfo>>, StatusCode> {
-    check_scope(&user, "read")?;
+    let bound_folder = check_scope_for_listing(&user, "read", &workspace_id, "")?;
     let user_id = require_auth(&session).await?;
     verify_workspace_ownership(state.repo.as_ref(), &workspace_id, &user_id).await?;
 
@@ -35,6 +37,11 @@ pub(crate) async fn list_media_folders(
         .folders
         .iter()
         .filter(|(_, fc)| fc.folder_type.as_str() == "media-server")
+        .filter(|(path, _)| {
+            bound_folder
+                .as_deref()
+                .is_none_or(|folder| path_within(path, folder))
+        })
         .filter_map(|(path, fc)| {
             let vault_id = fc.metadata.get("vault_id")?.as_str()?.to_string();
             if vault_id.is_empty() {
@@ -67,7 +74,11 @@ pub(crate) async fn publish_to_vault(
     State(state): State<Arc<WorkspaceManagerState>>,
     Json(request): Json<PublishRequest>,
 ) -> Result<Json<PublishResponse>, StatusCode> {
-    check_scope(&user, "write")?;
+    check_scope_for(
+        &user,
+        "write",
+        &ResourceRef::workspace(&workspace_id).with_path(&request.file_path),
+    )?;
     let user_id = require_auth(&session).await?;
     verify_workspace_ownership(state.repo.as_ref(), &workspace_id, &user_id).await?;
 
@@ -181,12 +192,15 @@ pub(crate) async fn publish_to_vault(
         MediaType::Image
     } else if mime_type.starts_with("video/") {
         MediaType::Video
+    } else if mime_type.starts_with("audio/") {
+        MediaType::Audio
     } else {
         MediaType::Document
     };
     let media_type_str = match media_type {
         MediaType::Image => "image",
         MediaType::Video => "video",
+        MediaType::Audio => "audio",
         MediaType::Document => "document",
     };
 
@@ -259,7 +273,11 @@ pub(crate) async fn publish_course(
     State(state): State<Arc<WorkspaceManagerState>>,
     Json(request): Json<PublishCourseRequest>,
 ) -> Result<Json<PublishCourseResponse>, StatusCode> {
-    check_scope(&user, "write")?;
+    check_scope_for(
+        &user,
+        "write",
+        &ResourceRef::workspace(&workspace_id).with_path(&request.folder_path),
+    )?;
     let user_id = require_auth(&session).await?;
     verify_workspace_ownership(state.repo.as_ref(), &workspace_id, &user_id).await?;
 
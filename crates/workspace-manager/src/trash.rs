@@ -0,0 +1,434 @@
+//! Workspace trash
+//!
+//! Deleting a file or folder moves it to `{workspace}/.trash/` and records it
+//! in `workspace_trash`, together with the workspace.yaml entries of a deleted
+//! folder. Entries can be restored to their original path until the retention
+//! period (`TRASH_RETENTION_DAYS`) runs out; the scheduled purge started by
+//! [`start_trash_purge_task`] then deletes them for good. Access code grants
+//! of a deleted folder are dropped on deletion and not restored.
+
+use crate::file_browser;
+use crate::file_editor;
+use crate::helpers::{
+    check_scope_for, check_scope_for_listing, format_human_date, require_auth,
+    verify_workspace_ownership,
+};
+use crate::{
+    FolderConfig, TrashEntryDisplay, WorkspaceConfig, WorkspaceManagerState, WorkspaceTrashTemplate,
+};
+use api_keys::middleware::AuthenticatedUser;
+use api_keys::ResourceRef;
+use askama::Template;
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{Html, Json},
+    Extension,
+};
+use db::api_keys::path_within;
+use db::workspaces::{NewWorkspaceTrashEntry, WorkspaceTrashEntry};
+use std::collections::HashMap;
+use std::path::{Path as FsPath, PathBuf};
+use std::sync::Arc;
+use tower_sessions::Session;
+use tracing::{error, info, warn};
+use video_manager::cleanup;
+
+/// Trash directory below the workspace root
+pub(crate) const TRASH_DIR: &str = ".trash";
+
+/// Whether a walkdir entry (walking from the workspace root) is the trash directory
+pub(crate) fn is_trash_dir(entry: &walkdir::DirEntry) -> bool {
+    entry.depth() == 1 && entry.file_name() == TRASH_DIR
+}
+
+/// Name of a trashed entry: deletion time plus the original base name, so
+/// repeated deletions of the same path do not collide
+fn trash_name(rel_path: &str, now: chrono::DateTime<chrono::Utc>) -> String {
+    let base = rel_path.rsplit('/').next().unwrap_or(rel_path);
+    format!("{}-{}", now.format("%Y%m%d%H%M%S%3f"), base)
+}
+
+/// Total size of a file or of the files below a directory
+fn path_size(path: &FsPath) -> u64 {
+    walkdir::WalkDir::new(path)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .filter_map(|e| e.metadata().ok())
+        .map(|m| m.len())
+        .sum()
+}
+
+/// Take the workspace.yaml entries of a folder and its sub-folders out of the config
+fn take_folder_configs(
+    config: &mut WorkspaceConfig,
+    folder: &str,
+) -> HashMap<String, FolderConfig> {
+    let prefix = format!("{}/", folder);
+    let keys: Vec<String> = config
+        .folders
+        .keys()
+        .filter(|k| *k == folder || k.starts_with(&prefix))
+        .cloned()
+        .collect();
+    keys.into_iter()
+        .filter_map(|k| config.folders.remove(&k).map(|v| (k, v)))
+        .collect()
+}
+
+/// Move a workspace file or folder to the trash
+pub(crate) async fn move_to_trash(
+    state: &WorkspaceManagerState,
+    workspace_id: &str,
+    rel_path: &str,
+    user_id: &str,
+) -> Result<(), StatusCode> {
+    let workspace_root = state.storage.workspace_root(workspace_id);
+    let abs_path = file_editor::safe_resolve_pub(&workspace_root, rel_path).map_err(|e| {
+        warn!("Failed to delete path: {}", e);
+        StatusCode::BAD_REQUEST
+    })?;
+    if !abs_path.exists() {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    let rel_path = rel_path.trim_matches('/');
+    let is_dir = abs_path.is_dir();
+    let size_bytes = path_size(&abs_path);
+
+    let trash_dir = workspace_root.join(TRASH_DIR);
+    let name = trash_name(rel_path, chrono::Utc::now());
+    let trash_path = trash_dir.join(&name);
+    std::fs::create_dir_all(&trash_dir)
+        .and_then(|_| std::fs::rename(&abs_path, &trash_path))
+        .map_err(|e| {
+            error!("Failed to move {:?} to the trash: {}", abs_path, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    // A deleted folder leaves workspace.yaml and its access codes
+    let mut folder_config = None;
+    if is_dir {
+        if let Ok(mut config) = WorkspaceConfig::load(&workspace_root) {
+            let folders = take_folder_configs(&mut config, rel_path);
+            if !folders.is_empty() {
+                folder_config = serde_yaml::to_string(&folders).ok();
+                if let Err(e) = config.save(&workspace_root) {
+                    warn!("Failed to update workspace.yaml: {}", e);
+                }
+            }
+        }
+        state
+            .repo
+            .delete_access_code_folders_for_path(workspace_id, rel_path)
+            .await
+            .ok();
+    }
+
+    let entry = NewWorkspaceTrashEntry {
+        workspace_id,
+        original_path: rel_path,
+        trash_name: &name,
+        is_dir,
+        size_bytes: size_bytes as i64,
+        folder_config: folder_config.as_deref(),
+        deleted_by: user_id,
+    };
+    if let Err(e) = state.repo.insert_trash_entry(&entry).await {
+        // Without a record the entry could never be restored nor purged
+        error!("Failed to record trash entry for {}: {}", rel_path, e);
+        if let Err(e) = std::fs::rename(&trash_path, &abs_path) {
+            error!("Failed to move {:?} back from the trash: {}", trash_path, e);
+        }
+        return Err(StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    info!(
+        "Moved {} in workspace {} to the trash",
+        rel_path, workspace_id
+    );
+    Ok(())
+}
+
+/// Delete a trash entry for good: stored file or folder and record
+async fn purge_entry(
+    state: &WorkspaceManagerState,
+    entry: &WorkspaceTrashEntry,
+) -> Result<(), String> {
+    let trash_path: PathBuf = state
+        .storage
+        .workspace_root(&entry.workspace_id)
+        .join(TRASH_DIR)
+        .join(&entry.trash_name);
+    cleanup::cleanup_trash_entry(&trash_path)
+        .await
+        .map_err(|e| e.to_string())?;
+    state
+        .repo
+        .delete_trash_entry(entry.id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Purge workspace trash entries older than `retention_days`
+///
+/// Returns the number of entries purged.
+pub async fn purge_expired(state: &WorkspaceManagerState, retention_days: u64) -> usize {
+    let expired = match state
+        .repo
+        .list_expired_trash_entries(retention_days as i64)
+        .await
+    {
+        Ok(expired) => expired,
+        Err(e) => {
+            error!("Failed to list expired workspace trash: {}", e);
+            return 0;
+        }
+    };
+
+    let mut purged = 0;
+    for entry in &expired {
+        match purge_entry(state, entry).await {
+            Ok(()) => purged += 1,
+            Err(e) => error!(
+                "Failed to purge {} from workspace {} trash: {}",
+                entry.original_path, entry.workspace_id, e
+            ),
+        }
+    }
+    purged
+}
+
+/// Spawn the scheduled purge of expired workspace trash
+pub fn start_trash_purge_task(state: Arc<WorkspaceManagerState>) {
+    cleanup::spawn_trash_purge("workspace", move |retention_days| {
+        let state = state.clone();
+        async move { purge_expired(&state, retention_days).await }
+    });
+}
+
+/// When a trash entry is purged (`deleted_at` plus the retention period)
+fn purge_after(deleted_at: &str, retention_days: u64) -> String {
+    chrono::NaiveDateTime::parse_from_str(deleted_at, "%Y-%m-%d %H:%M:%S")
+        .map(|deleted| {
+            (deleted + chrono::Duration::days(retention_days as i64))
+                .format("%Y-%m-%d")
+                .to_string()
+        })
+        .unwrap_or_default()
+}
+
+// ============================================================================
+// Handlers
+// ============================================================================
+
+/// Trash entries of a workspace, limited to those deleted from `bound_folder`
+/// for a folder-bound API key.
+async fn visible_entries(
+    state: &WorkspaceManagerState,
+    workspace_id: &str,
+    bound_folder: Option<&str>,
+) -> Result<Vec<WorkspaceTrashEntry>, StatusCode> {
+    let entries = state
+        .repo
+        .list_trash_entries(workspace_id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(entries
+        .into_iter()
+        .filter(|e| bound_folder.is_none_or(|folder| path_within(&e.original_path, folder)))
+        .collect())
+}
+
+/// GET /workspaces/{workspace_id}/trash
+pub(crate) async fn trash_page(
+    user: Option<Extension<AuthenticatedUser>>,
+    Path(workspace_id): Path<String>,
+    session: Session,
+    State(state): State<Arc<WorkspaceManagerState>>,
+) -> Result<Html<String>, StatusCode> {
+    let bound_folder = check_scope_for_listing(&user, "read", &workspace_id, "")?;
+    let user_id = require_auth(&session).await?;
+    let (workspace_name, _) =
+        verify_workspace_ownership(state.repo.as_ref(), &workspace_id, &user_id).await?;
+
+    let entries = visible_entries(&state, &workspace_id, bound_folder.as_deref()).await?;
+
+    let retention_days = cleanup::trash_retention_days();
+    let template = WorkspaceTrashTemplate {
+        authenticated: true,
+        workspace_id,
+        workspace_name,
+        retention_days,
+        entries: entries
+            .into_iter()
+            .map(|e| TrashEntryDisplay {
+                id: e.id,
+                size_str: file_browser::format_size(e.size_bytes.max(0) as u64),
+                deleted_at_human: format_human_date(&e.deleted_at),
+                purge_after: purge_after(&e.deleted_at, retention_days),
+                original_path: e.original_path,
+                is_dir: e.is_dir,
+            })
+            .collect(),
+    };
+
+    template.render().map(Html).map_err(|e| {
+        error!("Template error: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })
+}
+
+/// GET /api/workspaces/{workspace_id}/trash
+pub(crate) async fn list_trash(
+    user: Option<Extension<AuthenticatedUser>>,
+    Path(workspace_id): Path<String>,
+    session: Session,
+    State(state): State<Arc<WorkspaceManagerState>>,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    let bound_folder = check_scope_for_listing(&user, "read", &workspace_id, "")?;
+    let user_id = require_auth(&session).await?;
+    verify_workspace_ownership(state.repo.as_ref(), &workspace_id, &user_id).await?;
+
+    let entries = visible_entries(&state, &workspace_id, bound_folder.as_deref()).await?;
+
+    Ok(Json(serde_json::json!({
+        "retention_days": cleanup::trash_retention_days(),
+        "entries": entries,
+    })))
+}
+
+/// POST /api/workspaces/{workspace_id}/trash/{entry_id}/restore
+///
+/// Moves the entry back to its original path. Fails with 409 when something
+/// has been created at that path in the meantime.
+pub(crate) async fn restore_entry(
+    user: Option<Extension<AuthenticatedUser>>,
+    Path((workspace_id, entry_id)): Path<(String, i64)>,
+    session: Session,
+    State(state): State<Arc<WorkspaceManagerState>>,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    let user_id = require_auth(&session).await?;
+    verify_workspace_ownership(state.repo.as_ref(), &workspace_id, &user_id).await?;
+
+    let entry = state
+        .repo
+        .get_trash_entry(&workspace_id, entry_id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    check_scope_for(
+        &user,
+        "write",
+        &ResourceRef::workspace(&workspace_id).with_path(&entry.original_path),
+    )?;
+
+    let workspace_root = state.storage.workspace_root(&workspace_id);
+    let trash_path = workspace_root.join(TRASH_DIR).join(&entry.trash_name);
+    if !trash_path.exists() {
+        warn!("Trashed entry {:?} is missing", trash_path);
+        return Err(StatusCode::NOT_FOUND);
+    }
+    let target = file_editor::safe_resolve_pub(&workspace_root, &entry.original_path)
+        .map_err(|_| StatusCode::BAD_REQUEST)?;
+    if target.exists() {
+        return Err(StatusCode::CONFLICT);
+    }
+
+    if let Some(parent) = target.parent() {
+        std::fs::create_dir_all(parent).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    }
+    std::fs::rename(&trash_path, &target).map_err(|e| {
+        error!("Failed to restore {:?}: {}", target, e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    if let Some(yaml) = &entry.folder_config {
+        match serde_yaml::from_str::<HashMap<String, FolderConfig>>(yaml) {
+            Ok(folders) => {
+                if let Ok(mut config) = WorkspaceConfig::load(&workspace_root) {
+                    config.folders.extend(folders);
+                    if let Err(e) = config.save(&workspace_root) {
+                        warn!("Failed to update workspace.yaml: {}", e);
+                    }
+                }
+            }
+            Err(e) => warn!("Invalid folder config in trash entry {}: {}", entry.id, e),
+        }
+    }
+
+    state
+        .repo
+        .delete_trash_entry(entry.id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    info!(
+        "Restored {} in workspace {} from the trash",
+        entry.original_path, workspace_id
+    );
+    Ok(Json(serde_json::json!({ "path": entry.original_path })))
+}
+
+/// DELETE /api/workspaces/{workspace_id}/trash/{entry_id}
+pub(crate) async fn delete_entry(
+    user: Option<Extension<AuthenticatedUser>>,
+    Path((workspace_id, entry_id)): Path<(String, i64)>,
+    session: Session,
+    State(state): State<Arc<WorkspaceManagerState>>,
+) -> Result<StatusCode, StatusCode> {
+    let user_id = require_auth(&session).await?;
+    verify_workspace_ownership(state.repo.as_ref(), &workspace_id, &user_id).await?;
+
+    let entry = state
+        .repo
+        .get_trash_entry(&workspace_id, entry_id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    check_scope_for(
+        &user,
+        "write",
+        &ResourceRef::workspace(&workspace_id).with_path(&entry.original_path),
+    )?;
+
+    purge_entry(&state, &entry).await.map_err(|e| {
+        error!("Failed to purge trash entry {}: {}", entry.id, e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_trash_name_uses_base_name() {
+        let now = chrono::Utc.with_ymd_and_hms(2026, 4, 8, 12, 30, 0).unwrap();
+        assert_eq!(
+            trash_name("docs/notes.md", now),
+            "20260408123000000-notes.md"
+        );
+        assert_eq!(trash_name("slides", now), "20260408123000000-slides");
+    }
+
+    #[test]
+    fn test_take_folder_configs_includes_sub_folders() {
+        let mut config = WorkspaceConfig::new("Test".to_string(), String::new());
+        config.upsert_folder("course".to_string(), crate::FolderType::new("course"));
+        config.upsert_folder(
+            "course/slides".to_string(),
+            crate::FolderType::new("presentation"),
+        );
+        config.upsert_folder("courses".to_string(), crate::FolderType::new("course"));
+
+        let taken = take_folder_configs(&mut config, "course");
+        assert_eq!(taken.len(), 2);
+        assert!(taken.contains_key("course/slides"));
+        assert_eq!(config.folders.len(), 1);
+        assert!(config.folders.contains_key("courses"));
+    }
+}
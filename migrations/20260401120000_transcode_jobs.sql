@@ -0,0 +1,25 @@
+-- Persistent transcoding job queue (bounded worker pool, resume on restart)
+CREATE TABLE IF NOT EXISTS transcode_jobs (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    upload_id TEXT NOT NULL UNIQUE,
+    slug TEXT NOT NULL,
+    vault_id TEXT NOT NULL,
+    user_id TEXT,
+    source_path TEXT NOT NULL,
+    original_filename TEXT NOT NULL,
+    is_public INTEGER NOT NULL DEFAULT 0,
+    priority INTEGER NOT NULL DEFAULT 0,
+    status TEXT NOT NULL DEFAULT 'queued',
+    attempts INTEGER NOT NULL DEFAULT 0,
+    max_attempts INTEGER NOT NULL DEFAULT 3,
+    error TEXT,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    started_at TEXT,
+    finished_at TEXT,
+    updated_at TEXT NOT NULL DEFAULT (datetime('now')),
+    CHECK (status IN ('queued', 'running', 'completed', 'failed', 'cancelled'))
+);
+
+CREATE INDEX IF NOT EXISTS idx_transcode_jobs_claim ON transcode_jobs(status, priority DESC, id);
+CREATE INDEX IF NOT EXISTS idx_transcode_jobs_user ON transcode_jobs(user_id);
+CREATE INDEX IF NOT EXISTS idx_transcode_jobs_slug ON transcode_jobs(slug);
@@ -0,0 +1,16 @@
+-- Subtitle / caption tracks for videos.
+-- One WebVTT track per (media item, language); files live next to the video
+-- under {slug}/subtitles/ and are segmented into HLS renditions.
+
+CREATE TABLE IF NOT EXISTS media_subtitles (
+    id          INTEGER PRIMARY KEY AUTOINCREMENT,
+    media_id    INTEGER NOT NULL REFERENCES media_items(id) ON DELETE CASCADE,
+    language    TEXT    NOT NULL,
+    label       TEXT    NOT NULL,
+    is_default  INTEGER NOT NULL DEFAULT 0,
+    created_at  TEXT    NOT NULL DEFAULT (datetime('now')),
+    updated_at  TEXT    NOT NULL DEFAULT (datetime('now')),
+    UNIQUE (media_id, language)
+);
+
+CREATE INDEX IF NOT EXISTS idx_media_subtitles_media ON media_subtitles(media_id);
@@ -0,0 +1,13 @@
+-- Technical details for audio media items, filled in by ffprobe once the
+-- upload has been processed. Renditions and waveform files live under
+-- media/audio/{slug}/ next to the original.
+
+CREATE TABLE IF NOT EXISTS media_audio (
+    media_id     INTEGER PRIMARY KEY REFERENCES media_items(id) ON DELETE CASCADE,
+    duration     REAL,
+    bitrate      INTEGER,
+    channels     INTEGER,
+    sample_rate  INTEGER,
+    codec        TEXT,
+    updated_at   TEXT    NOT NULL DEFAULT (datetime('now'))
+);
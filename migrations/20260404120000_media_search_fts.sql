@@ -0,0 +1,104 @@
+-- Full-text search over media metadata and document contents.
+--
+-- media_fts is keyed by media_items.id (rowid) and kept in sync by the
+-- triggers below: item metadata and tags are indexed as they change, and
+-- document bodies (markdown/text files, extracted PDF text) are written to
+-- media_text by the application.
+
+CREATE TABLE IF NOT EXISTS media_text (
+    media_id    INTEGER PRIMARY KEY REFERENCES media_items(id) ON DELETE CASCADE,
+    body        TEXT    NOT NULL,
+    updated_at  TEXT    NOT NULL DEFAULT (datetime('now'))
+);
+
+CREATE VIRTUAL TABLE IF NOT EXISTS media_fts USING fts5(
+    title,
+    description,
+    category,
+    tags,
+    body,
+    tokenize = 'porter unicode61 remove_diacritics 2'
+);
+
+-- ── Media items ─────────────────────────────────────────────────────
+
+CREATE TRIGGER IF NOT EXISTS media_fts_item_insert
+AFTER INSERT ON media_items
+BEGIN
+    INSERT INTO media_fts (rowid, title, description, category, tags, body)
+    VALUES (NEW.id, NEW.title, NEW.description, NEW.category, NULL, NULL);
+END;
+
+CREATE TRIGGER IF NOT EXISTS media_fts_item_update
+AFTER UPDATE OF title, description, category ON media_items
+BEGIN
+    DELETE FROM media_fts WHERE rowid = NEW.id;
+    INSERT INTO media_fts (rowid, title, description, category, tags, body)
+    SELECT NEW.id, NEW.title, NEW.description, NEW.category,
+           (SELECT group_concat(tag, ' ') FROM media_tags WHERE media_id = NEW.id),
+           (SELECT body FROM media_text WHERE media_id = NEW.id);
+END;
+
+CREATE TRIGGER IF NOT EXISTS media_fts_item_delete
+AFTER DELETE ON media_items
+BEGIN
+    DELETE FROM media_fts WHERE rowid = OLD.id;
+    DELETE FROM media_text WHERE media_id = OLD.id;
+END;
+
+-- ── Tags ────────────────────────────────────────────────────────────
+
+CREATE TRIGGER IF NOT EXISTS media_fts_tag_insert
+AFTER INSERT ON media_tags
+BEGIN
+    DELETE FROM media_fts WHERE rowid = NEW.media_id;
+    INSERT INTO media_fts (rowid, title, description, category, tags, body)
+    SELECT m.id, m.title, m.description, m.category,
+           (SELECT group_concat(tag, ' ') FROM media_tags WHERE media_id = m.id),
+           (SELECT body FROM media_text WHERE media_id = m.id)
+    FROM media_items m WHERE m.id = NEW.media_id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS media_fts_tag_delete
+AFTER DELETE ON media_tags
+BEGIN
+    DELETE FROM media_fts WHERE rowid = OLD.media_id;
+    INSERT INTO media_fts (rowid, title, description, category, tags, body)
+    SELECT m.id, m.title, m.description, m.category,
+           (SELECT group_concat(tag, ' ') FROM media_tags WHERE media_id = m.id),
+           (SELECT body FROM media_text WHERE media_id = m.id)
+    FROM media_items m WHERE m.id = OLD.media_id;
+END;
+
+-- ── Document text ───────────────────────────────────────────────────
+
+CREATE TRIGGER IF NOT EXISTS media_fts_text_insert
+AFTER INSERT ON media_text
+BEGIN
+    DELETE FROM media_fts WHERE rowid = NEW.media_id;
+    INSERT INTO media_fts (rowid, title, description, category, tags, body)
+    SELECT m.id, m.title, m.description, m.category,
+           (SELECT group_concat(tag, ' ') FROM media_tags WHERE media_id = m.id),
+           NEW.body
+    FROM media_items m WHERE m.id = NEW.media_id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS media_fts_text_update
+AFTER UPDATE OF body ON media_text
+BEGIN
+    DELETE FROM media_fts WHERE rowid = NEW.media_id;
+    INSERT INTO media_fts (rowid, title, description, category, tags, body)
+    SELECT m.id, m.title, m.description, m.category,
+           (SELECT group_concat(tag, ' ') FROM media_tags WHERE media_id = m.id),
+           NEW.body
+    FROM media_items m WHERE m.id = NEW.media_id;
+END;
+
+-- ── Backfill ────────────────────────────────────────────────────────
+
+INSERT INTO media_fts (rowid, title, description, category, tags, body)
+SELECT m.id, m.title, m.description, m.category,
+       (SELECT group_concat(tag, ' ') FROM media_tags WHERE media_id = m.id),
+       NULL
+FROM media_items m
+WHERE m.id NOT IN (SELECT rowid FROM media_fts);
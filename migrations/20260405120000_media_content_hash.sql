@@ -0,0 +1,19 @@
+-- Content hashes for deduplication and integrity verification.
+--
+-- content_hash is the SHA-256 of the bytes as uploaded and identifies
+-- duplicate uploads within a vault. file_hash is the SHA-256 of the stored
+-- primary file (differs from content_hash for transcoded images) and is the
+-- baseline the integrity verifier compares the on-disk bytes against.
+-- Items uploaded before hashing existed get a file_hash on their first
+-- verification.
+
+ALTER TABLE media_items ADD COLUMN content_hash TEXT;
+ALTER TABLE media_items ADD COLUMN file_hash TEXT;
+
+-- Result of the last verification: 'ok', 'mismatch' or 'missing'
+-- (NULL = never verified)
+ALTER TABLE media_items ADD COLUMN integrity_status TEXT;
+ALTER TABLE media_items ADD COLUMN integrity_checked_at TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_media_items_vault_content_hash
+    ON media_items(vault_id, content_hash);
@@ -0,0 +1,16 @@
+-- Named chapters of videos.
+-- Chapters are published as a WebVTT chapters track ({slug}/chapters.vtt)
+-- and as EXT-X-DATERANGE tags in the HLS media playlists.
+
+CREATE TABLE IF NOT EXISTS media_chapters (
+    id          INTEGER PRIMARY KEY AUTOINCREMENT,
+    media_id    INTEGER NOT NULL REFERENCES media_items(id) ON DELETE CASCADE,
+    -- Offsets into the video in seconds; end_time NULL = until the next chapter
+    start_time  REAL    NOT NULL,
+    end_time    REAL,
+    title       TEXT    NOT NULL,
+    created_at  TEXT    NOT NULL DEFAULT (datetime('now')),
+    UNIQUE (media_id, start_time)
+);
+
+CREATE INDEX IF NOT EXISTS idx_media_chapters_media ON media_chapters(media_id);
@@ -0,0 +1,21 @@
+-- Capture metadata of image media items, read from EXIF at upload time.
+-- Kept in the database even when served originals are scrubbed, so owners
+-- can still filter by camera and capture date and see photos on a map.
+
+CREATE TABLE IF NOT EXISTS media_photo (
+    media_id       INTEGER PRIMARY KEY REFERENCES media_items(id) ON DELETE CASCADE,
+    captured_at    TEXT,
+    camera_make    TEXT,
+    camera_model   TEXT,
+    lens_model     TEXT,
+    gps_latitude   REAL,
+    gps_longitude  REAL,
+    updated_at     TEXT    NOT NULL DEFAULT (datetime('now'))
+);
+
+CREATE INDEX IF NOT EXISTS idx_media_photo_captured_at ON media_photo(captured_at);
+CREATE INDEX IF NOT EXISTS idx_media_photo_camera ON media_photo(camera_make, camera_model);
+
+-- Per-vault privacy policy: strip GPS and device EXIF from served originals
+-- (on by default)
+ALTER TABLE storage_vaults ADD COLUMN strip_exif INTEGER NOT NULL DEFAULT 1;
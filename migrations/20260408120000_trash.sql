@@ -0,0 +1,29 @@
+-- Recycle bin for media items and workspace files.
+-- Deleting a media item sets deleted_at and moves its files to the vault's
+-- .trash area; deleting a workspace file or folder moves it to the
+-- workspace's .trash directory. Both are purged after the retention period
+-- (TRASH_RETENTION_DAYS, 30 days by default) unless restored.
+
+ALTER TABLE media_items ADD COLUMN deleted_at TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_media_items_deleted_at
+    ON media_items(deleted_at) WHERE deleted_at IS NOT NULL;
+
+CREATE TABLE IF NOT EXISTS workspace_trash (
+    id             INTEGER PRIMARY KEY AUTOINCREMENT,
+    workspace_id   TEXT    NOT NULL REFERENCES workspaces(workspace_id) ON DELETE CASCADE,
+    -- Path relative to the workspace root before deletion
+    original_path  TEXT    NOT NULL,
+    -- Entry name under {workspace}/.trash/
+    trash_name     TEXT    NOT NULL,
+    is_dir         INTEGER NOT NULL DEFAULT 0,
+    size_bytes     INTEGER NOT NULL DEFAULT 0,
+    -- workspace.yaml entry of a deleted folder (YAML), restored with it
+    folder_config  TEXT,
+    deleted_by     TEXT,
+    deleted_at     TEXT    NOT NULL DEFAULT (datetime('now')),
+    UNIQUE (workspace_id, trash_name)
+);
+
+CREATE INDEX IF NOT EXISTS idx_workspace_trash_workspace ON workspace_trash(workspace_id);
+CREATE INDEX IF NOT EXISTS idx_workspace_trash_deleted_at ON workspace_trash(deleted_at);
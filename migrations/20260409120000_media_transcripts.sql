@@ -0,0 +1,29 @@
+-- Speech-to-text transcripts of videos.
+-- Segments are timestamped in seconds; the joined text is indexed through
+-- media_text, and captions are published as an auto-generated subtitle track.
+
+CREATE TABLE IF NOT EXISTS media_transcripts (
+    media_id    INTEGER PRIMARY KEY REFERENCES media_items(id) ON DELETE CASCADE,
+    -- 'pending', 'complete' or 'failed'
+    status      TEXT    NOT NULL,
+    -- BCP 47 tag, detected by the ASR backend or configured
+    language    TEXT,
+    backend     TEXT    NOT NULL,
+    error       TEXT,
+    created_at  TEXT    NOT NULL DEFAULT (datetime('now')),
+    updated_at  TEXT    NOT NULL DEFAULT (datetime('now'))
+);
+
+CREATE TABLE IF NOT EXISTS media_transcript_segments (
+    id          INTEGER PRIMARY KEY AUTOINCREMENT,
+    media_id    INTEGER NOT NULL REFERENCES media_items(id) ON DELETE CASCADE,
+    start_time  REAL    NOT NULL,
+    end_time    REAL    NOT NULL,
+    text        TEXT    NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_media_transcript_segments_media
+    ON media_transcript_segments(media_id, start_time);
+
+-- Tracks generated from a transcript; uploaded tracks are never replaced by them
+ALTER TABLE media_subtitles ADD COLUMN auto_generated INTEGER NOT NULL DEFAULT 0;
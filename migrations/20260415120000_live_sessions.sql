@@ -0,0 +1,24 @@
+-- Recorded live-stream sessions (DVR).
+-- Each session owns the media item it is recorded into: the item is created in
+-- 'live' status when the stream goes live and transcoded into a regular VOD
+-- item once the stream ends.
+
+CREATE TABLE IF NOT EXISTS live_sessions (
+    id              INTEGER PRIMARY KEY AUTOINCREMENT,
+    media_id        INTEGER NOT NULL REFERENCES media_items(id) ON DELETE CASCADE,
+    -- MediaMTX path the stream was published to
+    stream_path     TEXT    NOT NULL,
+    -- Recording on local disk until it is handed to the transcoding queue
+    recording_path  TEXT    NOT NULL,
+    -- 'live', 'ended' or 'failed'
+    status          TEXT    NOT NULL DEFAULT 'live',
+    started_at      TEXT    NOT NULL DEFAULT (datetime('now')),
+    ended_at        TEXT,
+    peak_viewers    INTEGER NOT NULL DEFAULT 0,
+    total_viewers   INTEGER NOT NULL DEFAULT 0
+);
+
+CREATE INDEX IF NOT EXISTS idx_live_sessions_path_status
+    ON live_sessions(stream_path, status);
+CREATE INDEX IF NOT EXISTS idx_live_sessions_media
+    ON live_sessions(media_id);
@@ -0,0 +1,27 @@
+-- Live stream definitions with per-stream publish keys.
+-- Each stream is published to its own MediaMTX path with its own key; MediaMTX
+-- checks keys through the HTTP auth hook (/api/stream/auth). Only the SHA-256
+-- of the key is stored.
+
+CREATE TABLE IF NOT EXISTS live_streams (
+    id              INTEGER PRIMARY KEY AUTOINCREMENT,
+    user_id         TEXT    NOT NULL,
+    name            TEXT    NOT NULL,
+    -- MediaMTX path, also the slug under /hls/{stream_path}/
+    stream_path     TEXT    NOT NULL UNIQUE,
+    key_hash        TEXT    NOT NULL,
+    key_prefix      TEXT    NOT NULL,
+    key_rotated_at  TEXT    NOT NULL DEFAULT (datetime('now')),
+    -- Publishing is refused after this time (NULL = never expires)
+    expires_at      TEXT,
+    -- Who may watch: 'private', 'public', 'code' or 'group'
+    access          TEXT    NOT NULL DEFAULT 'private',
+    access_code     TEXT,
+    group_id        INTEGER REFERENCES access_groups(id) ON DELETE SET NULL,
+    -- Record sessions into the owner's media library
+    record          INTEGER NOT NULL DEFAULT 1,
+    created_at      TEXT    NOT NULL DEFAULT (datetime('now')),
+    updated_at      TEXT    NOT NULL DEFAULT (datetime('now'))
+);
+
+CREATE INDEX IF NOT EXISTS idx_live_streams_user ON live_streams(user_id);
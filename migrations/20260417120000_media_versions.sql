@@ -0,0 +1,29 @@
+-- Media item versions.
+-- Replacing the file of a media item keeps its slug and archives the previous
+-- file as a numbered version: the file columns are copied here and the stored
+-- files are moved to the vault's `.versions/{slug}/v{version}/` area.
+-- Rolling back swaps an archived version with the current one.
+
+-- Version number of the file currently served
+ALTER TABLE media_items ADD COLUMN version INTEGER NOT NULL DEFAULT 1;
+
+CREATE TABLE IF NOT EXISTS media_versions (
+    id                  INTEGER PRIMARY KEY AUTOINCREMENT,
+    media_id            INTEGER NOT NULL REFERENCES media_items(id) ON DELETE CASCADE,
+    version             INTEGER NOT NULL,
+    filename            TEXT    NOT NULL,
+    original_filename   TEXT,
+    mime_type           TEXT    NOT NULL,
+    file_size           INTEGER NOT NULL,
+    video_type          TEXT,
+    thumbnail_url       TEXT,
+    content_hash        TEXT,
+    file_hash           TEXT,
+    -- Who archived the version, and why
+    archived_by         TEXT,
+    comment             TEXT,
+    archived_at         TEXT    NOT NULL DEFAULT (datetime('now')),
+    UNIQUE (media_id, version)
+);
+
+CREATE INDEX IF NOT EXISTS idx_media_versions_media ON media_versions(media_id);
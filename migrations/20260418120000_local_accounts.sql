@@ -0,0 +1,47 @@
+-- Built-in (local) accounts for instances without an identity provider.
+-- A local account is a regular `users` row (provider 'local') with a
+-- password and optional TOTP second factor. Accounts are created through
+-- signup invitations; tokens and recovery codes are stored as SHA-256 only.
+
+CREATE TABLE IF NOT EXISTS local_accounts (
+    user_id             TEXT    PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    email               TEXT    NOT NULL UNIQUE COLLATE NOCASE,
+    -- Argon2id PHC string
+    password_hash       TEXT    NOT NULL,
+    password_changed_at TEXT    NOT NULL DEFAULT (datetime('now')),
+    -- Consecutive failed logins; the account locks when it reaches the limit
+    failed_attempts     INTEGER NOT NULL DEFAULT 0,
+    locked_until        TEXT,
+    -- Base32 secret, set on enrollment and required once confirmed
+    totp_secret         TEXT,
+    totp_enabled        INTEGER NOT NULL DEFAULT 0,
+    -- Last accepted TOTP time step, so a code can't be used twice
+    totp_last_step      INTEGER,
+    created_at          TEXT    NOT NULL DEFAULT (datetime('now'))
+);
+
+CREATE TABLE IF NOT EXISTS local_recovery_codes (
+    id          INTEGER PRIMARY KEY AUTOINCREMENT,
+    user_id     TEXT    NOT NULL REFERENCES local_accounts(user_id) ON DELETE CASCADE,
+    code_hash   TEXT    NOT NULL,
+    created_at  TEXT    NOT NULL DEFAULT (datetime('now'))
+);
+
+CREATE INDEX IF NOT EXISTS idx_local_recovery_codes_user ON local_recovery_codes(user_id);
+
+CREATE TABLE IF NOT EXISTS local_signup_invitations (
+    token_hash  TEXT    PRIMARY KEY,
+    email       TEXT    NOT NULL COLLATE NOCASE,
+    -- Tenant the new account joins (NULL = platform)
+    tenant_id   TEXT    REFERENCES tenants(id) ON DELETE CASCADE,
+    invited_by  TEXT,
+    expires_at  TEXT    NOT NULL,
+    created_at  TEXT    NOT NULL DEFAULT (datetime('now'))
+);
+
+CREATE TABLE IF NOT EXISTS local_password_resets (
+    token_hash  TEXT    PRIMARY KEY,
+    user_id     TEXT    NOT NULL REFERENCES local_accounts(user_id) ON DELETE CASCADE,
+    expires_at  TEXT    NOT NULL,
+    created_at  TEXT    NOT NULL DEFAULT (datetime('now'))
+);
@@ -0,0 +1,19 @@
+-- Passkeys (WebAuthn credentials) for platform users.
+-- A user can register several passkeys; each is a discoverable credential,
+-- so login needs no username. Credential ids are stored base64url-encoded,
+-- public keys as COSE_Key bytes.
+
+CREATE TABLE IF NOT EXISTS passkey_credentials (
+    id              INTEGER PRIMARY KEY AUTOINCREMENT,
+    user_id         TEXT    NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    credential_id   TEXT    NOT NULL UNIQUE,
+    public_key      BLOB    NOT NULL,
+    -- Signature counter reported by the authenticator (0 for most synced passkeys)
+    sign_count      INTEGER NOT NULL DEFAULT 0,
+    -- Label chosen by the user, e.g. "MacBook" or "YubiKey"
+    name            TEXT    NOT NULL,
+    created_at      TEXT    NOT NULL DEFAULT (datetime('now')),
+    last_used_at    TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_passkey_credentials_user ON passkey_credentials(user_id);
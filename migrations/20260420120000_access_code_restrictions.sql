@@ -0,0 +1,31 @@
+-- Access code restrictions.
+-- A code can require a passphrase, limit how often it is claimed ("uses") and
+-- how often items are opened with it ("views"), only work from listed IPs or
+-- when the landing page is reached from listed referrer hosts, and burn after
+-- its first claim. Codes with a passphrase, use limit, referrer allowlist or
+-- burn flag must be claimed on the landing page; the claim sets a cookie
+-- whose token hash is stored below and checked on every item access.
+
+ALTER TABLE access_codes ADD COLUMN passphrase_hash TEXT;
+ALTER TABLE access_codes ADD COLUMN max_uses INTEGER;
+ALTER TABLE access_codes ADD COLUMN current_uses INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE access_codes ADD COLUMN max_views INTEGER;
+ALTER TABLE access_codes ADD COLUMN current_views INTEGER NOT NULL DEFAULT 0;
+-- Newline-separated IP addresses or CIDR ranges
+ALTER TABLE access_codes ADD COLUMN allowed_ips TEXT;
+-- Newline-separated host names (a leading "*." matches subdomains)
+ALTER TABLE access_codes ADD COLUMN allowed_referrers TEXT;
+ALTER TABLE access_codes ADD COLUMN burn_after_claim INTEGER NOT NULL DEFAULT 0;
+
+CREATE TABLE IF NOT EXISTS access_code_claims (
+    id              INTEGER PRIMARY KEY AUTOINCREMENT,
+    access_code_id  INTEGER NOT NULL REFERENCES access_codes(id) ON DELETE CASCADE,
+    -- SHA-256 (hex) of the claim cookie token
+    token_hash      TEXT    NOT NULL UNIQUE,
+    ip_address      TEXT,
+    user_agent      TEXT,
+    referer         TEXT,
+    created_at      TEXT    NOT NULL DEFAULT (datetime('now'))
+);
+
+CREATE INDEX IF NOT EXISTS idx_access_code_claims_code ON access_code_claims(access_code_id);
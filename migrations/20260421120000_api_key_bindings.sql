@@ -0,0 +1,12 @@
+-- API key bindings.
+-- A key can be bound to one workspace (optionally to a folder within it) or
+-- to one vault; its scopes then only apply there. It can also be limited to
+-- a list of client IPs. Unbound keys keep acting on everything their user
+-- owns.
+
+ALTER TABLE user_api_keys ADD COLUMN workspace_id TEXT;
+ALTER TABLE user_api_keys ADD COLUMN vault_id TEXT;
+-- Workspace-relative folder, without leading or trailing slashes
+ALTER TABLE user_api_keys ADD COLUMN path_prefix TEXT;
+-- Newline-separated IP addresses or CIDR ranges
+ALTER TABLE user_api_keys ADD COLUMN allowed_ips TEXT;
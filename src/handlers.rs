@@ -488,16 +488,108 @@ pub async fn dev_components_handler(session: Session) -> Result<Html<String>, St
     }
 }
 
-#[tracing::instrument]
-pub async fn webhook_stream_ready() -> StatusCode {
-    println!("\u{1f4e1} Stream is now live!");
-    StatusCode::OK
+/// Query string of the MediaMTX `runOnReady` / `runOnNotReady` hooks
+#[derive(Debug, serde::Deserialize)]
+pub struct StreamWebhookQuery {
+    /// MediaMTX path of the stream (`$MTX_PATH`)
+    path: Option<String>,
+    /// Publish key forwarded from the publisher's query (`$MTX_QUERY`)
+    key: Option<String>,
+    /// Global publish token of the legacy `live` path
+    token: Option<String>,
 }
 
-#[tracing::instrument]
-pub async fn webhook_stream_ended() -> StatusCode {
-    println!("\u{1f4e1} Stream has ended");
-    StatusCode::OK
+impl StreamWebhookQuery {
+    /// Stream path and publisher of an authorized hook call
+    async fn authorize(
+        &self,
+        state: &AppState,
+        allow_expired: bool,
+    ) -> Result<(String, video_manager::live_streams::Publisher), StatusCode> {
+        let path = self
+            .path
+            .clone()
+            .unwrap_or_else(|| video_manager::LIVE_STREAM_KEY.to_string());
+        if !video_manager::live::is_valid_stream_path(&path) {
+            return Err(StatusCode::BAD_REQUEST);
+        }
+        let key = self.key.as_deref().or(self.token.as_deref());
+        match video_manager::live_streams::authorize_publish(
+            &state.video_state,
+            &path,
+            key,
+            allow_expired,
+        )
+        .await
+        {
+            Ok(Some(publisher)) => Ok((path, publisher)),
+            Ok(None) => {
+                tracing::warn!("Stream webhook for {} rejected: invalid key", path);
+                Err(StatusCode::UNAUTHORIZED)
+            }
+            Err(e) => {
+                tracing::error!("Failed to check publish key: {}", e);
+                Err(StatusCode::INTERNAL_SERVER_ERROR)
+            }
+        }
+    }
+}
+
+/// A stream went live: start recording it into the owner's vault
+#[tracing::instrument(skip(state))]
+pub async fn webhook_stream_ready(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<StreamWebhookQuery>,
+) -> StatusCode {
+    let (path, publisher) = match query.authorize(&state, false).await {
+        Ok(authorized) => authorized,
+        Err(status) => return status,
+    };
+    tracing::info!("\u{1f4e1} Stream {} is now live", path);
+
+    let Some(target) = publisher.recording_target(state.video_state.live.config()) else {
+        tracing::info!("Stream {} is not recorded", path);
+        return StatusCode::OK;
+    };
+
+    let tenant_id = state
+        .auth_state
+        .repo
+        .get_user_tenant_id(&target.owner_id)
+        .await
+        .ok()
+        .flatten()
+        .unwrap_or_else(|| "platform".to_string());
+
+    match video_manager::live::stream_ready(&state.video_state, &path, &target, &tenant_id).await
+    {
+        Ok(_) => StatusCode::OK,
+        Err(e) => {
+            tracing::error!("Failed to start recording stream {}: {:#}", path, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    }
+}
+
+/// A stream ended: turn its recording into a VOD item
+#[tracing::instrument(skip(state))]
+pub async fn webhook_stream_ended(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<StreamWebhookQuery>,
+) -> StatusCode {
+    let (path, _) = match query.authorize(&state, true).await {
+        Ok(authorized) => authorized,
+        Err(status) => return status,
+    };
+    tracing::info!("\u{1f4e1} Stream {} has ended", path);
+
+    match video_manager::live::stream_ended(&state.video_state, &path).await {
+        Ok(_) => StatusCode::OK,
+        Err(e) => {
+            tracing::error!("Failed to finish recording stream {}: {:#}", path, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    }
 }
 
 pub async fn serve_static_excluding_gallery(
@@ -45,8 +45,8 @@ use appstore::{AppstoreState, AppTemplateRegistry, appstore_routes};
 use federation::{federation_consumer_routes, federation_server_routes, FederationState};
 
 use media_manager::{
-    folder_access_routes, media_routes, media_serving_routes, media_upload_routes,
-    MediaManagerState,
+    folder_access_routes, media_routes, media_serving_routes, media_signing_routes, media_tus_routes,
+    media_upload_routes, MediaManagerState,
 };
 use video_manager::{rtmp_publish_token, video_routes, VideoManagerState};
 use media_viewer::{gallery_routes, MediaViewerState};
@@ -165,20 +165,40 @@ async fn main() -> anyhow::Result<()> {
     // Initialize Access Control Service with audit logging
     let access_control = Arc::new(AccessControlService::with_audit_enabled(database.clone(), database.clone(), true));
     println!("\u{1f510} Access Control Service initialized with audit logging enabled");
+    if access_control.url_signer().is_ephemeral() {
+        println!(
+            "\u{26a0}\u{fe0f}  {} not set: signed media URLs use a random key and stop working on restart",
+            access_control::signed_urls::SIGNING_KEY_ENV
+        );
+    }
+
+    // Media storage: local disk, or an S3-compatible bucket (STORAGE_BACKEND=s3)
+    let user_storage = Arc::new(common::storage::UserStorageManager::from_env(
+        storage_dir.clone(),
+    )?);
+    println!("\u{1f4be} Media storage backend: {}", user_storage.backend().name());
 
     // Initialize module states
         let video_state = Arc::new(VideoManagerState::new(
         pool.clone(),
         database.clone(),
         database.clone(),
+        database.clone(),
         storage_dir.clone(),
         http_client,
         access_control.clone(),
-    ));
-
-    let user_storage = Arc::new(common::storage::UserStorageManager::new(
-        storage_dir.clone(),
-    ));
+    )
+    .with_user_storage((*user_storage).clone())
+    .with_llm_providers(database.clone()));
+
+    // Transcoding workers (resumes jobs interrupted by the last shutdown)
+    match video_state.start_transcode_workers().await {
+        Ok(()) => println!(
+            "\u{1f3ac} Transcoding queue: {} worker(s)",
+            video_state.transcode_queue.config().workers
+        ),
+        Err(e) => println!("\u{26a0}\u{fe0f}  Transcoding queue failed to start: {}", e),
+    }
 
     // Initialize OIDC configuration
     let oidc_config = OidcConfig::from_env();
@@ -193,8 +213,12 @@ async fn main() -> anyhow::Result<()> {
         println!("\u{2705} Production configuration validated \u{2014} all secrets are set");
     }
 
+    // Rate Limiting (TD-010); the Auth class also sets the local account lockout
+    let rate_limit = RateLimitConfig::from_env();
+
     let auth_state = match AuthState::new(oidc_config.clone(), database.clone()).await {
         Ok(state) => {
+            let state = state.with_lockout(rate_limit.auth_lockout);
             if state.oidc_client.read().await.is_some() {
                 println!("\u{2705} OIDC authentication enabled");
             } else {
@@ -205,7 +229,10 @@ async fn main() -> anyhow::Result<()> {
         Err(e) => {
             println!("\u{26a0}\u{fe0f}  Failed to initialize OIDC: {}", e);
             println!("   Using emergency login only");
-            Arc::new(AuthState::new_without_oidc(oidc_config, database.clone()))
+            Arc::new(
+                AuthState::new_without_oidc(oidc_config, database.clone())
+                    .with_lockout(rate_limit.auth_lockout),
+            )
         }
     };
 
@@ -217,6 +244,7 @@ async fn main() -> anyhow::Result<()> {
     let mut workspace_state = WorkspaceManagerState::new(database.clone(), database.clone(), user_storage.clone(), sites_dir.clone(), database.clone());
     workspace_renderers::register_all(&mut workspace_state, database.clone(), database.clone(), (*user_storage).clone());
     let workspace_state = Arc::new(workspace_state);
+    workspace_manager::start_trash_purge_task(workspace_state.clone());
 
     let site_handler_state = Arc::new(site_overview::SiteHandlerState {
         repo: database.clone(),
@@ -240,7 +268,16 @@ async fn main() -> anyhow::Result<()> {
         video_state.progress_tracker.clone(),
         video_state.metrics_store.clone(),
         video_state.audit_logger.clone(),
-    ));
+        video_state.transcode_queue.clone(),
+    )
+    .with_transcriber(video_state.transcriber.clone()));
+        media_manager::tus::start_expiration_task((*media_manager_state).clone());
+        media_manager::integrity::start_verification_task((*media_manager_state).clone());
+        media_manager::trash::start_purge_task((*media_manager_state).clone());
+        media_manager::content_index::spawn_text_backfill(
+            database.clone(),
+            (*user_storage).clone(),
+        );
         println!("\u{1f4c1} Media Manager initialized (images with original + WebP support, HLS video transcoding)");
 
     let docs_root = std::env::var("DOCS_ROOT")
@@ -323,8 +360,6 @@ async fn main() -> anyhow::Result<()> {
 
     println!("\u{1f36a} Session: SQLite (sessions.db), secure={}, expiry=7d", session_secure);
 
-    // Rate Limiting (TD-010)
-    let rate_limit = RateLimitConfig::from_env();
     rate_limit.print_summary();
 
     let dev_routes_enabled = std::env::var("ENABLE_DEV_ROUTES")
@@ -389,6 +424,14 @@ async fn main() -> anyhow::Result<()> {
                 ));
             if let Some(layer) = rate_limit.upload_layer() { r.layer(layer) } else { r }
         })
+        .merge({
+            let r = media_tus_routes()
+                .with_state((*media_manager_state).clone())
+                .route_layer(axum::middleware::from_fn_with_state(
+                    api_key_repo.clone(), api_key_or_session_auth,
+                ));
+            if let Some(layer) = rate_limit.general_layer() { r.layer(layer) } else { r }
+        })
         .merge({
             let r = media_serving_routes()
                 .with_state((*media_manager_state).clone())
@@ -397,6 +440,14 @@ async fn main() -> anyhow::Result<()> {
                 ));
             if let Some(layer) = rate_limit.media_serving_layer() { r.layer(layer) } else { r }
         })
+        .merge({
+            let r = media_signing_routes()
+                .with_state((*media_manager_state).clone())
+                .route_layer(axum::middleware::from_fn_with_state(
+                    api_key_repo.clone(), api_key_or_session_auth,
+                ));
+            if let Some(layer) = rate_limit.general_layer() { r.layer(layer) } else { r }
+        })
         .merge(video_routes().with_state(video_state))
         .merge(gallery_routes(mv_state));
 
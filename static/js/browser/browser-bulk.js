@@ -163,7 +163,7 @@ submitMove = async function () {
 async function bulkDelete() {
     const n = _selected.size;
     if (n === 0) return;
-    if (!confirm(`Delete ${n} file${n > 1 ? 's' : ''}? This cannot be undone.`)) return;
+    if (!confirm(`Delete ${n} file${n > 1 ? 's' : ''}? They can be restored from the trash.`)) return;
 
     const paths = Array.from(_selected);
     let failed = 0;
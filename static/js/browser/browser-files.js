@@ -343,7 +343,7 @@ async function submitMove() {
 
 async function deleteItem(path, isDir) {
     const kind = isDir ? 'folder' : 'file';
-    if (!confirm(`Delete this ${kind}? This cannot be undone.`)) return;
+    if (!confirm(`Move this ${kind} to the trash?`)) return;
     try {
         const r = await fetch(`/api/workspaces/${WORKSPACE_ID}/files?path=${encodeURIComponent(path)}`, {
             method: 'DELETE',
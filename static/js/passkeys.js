@@ -0,0 +1,81 @@
+/**
+ * Passkeys (WebAuthn)
+ * Browser side of the passkey ceremonies served by /api/passkeys/*
+ *
+ * Usage:
+ *   <script src="/static/js/passkeys.js"></script>
+ *   if (Passkeys.supported()) {
+ *       await Passkeys.register('MacBook');   // logged-in user adds a passkey
+ *       await Passkeys.login('/media');       // logs in and redirects
+ *   }
+ *
+ * Binary fields travel base64url-encoded in both directions.
+ * Failed requests throw an Error with the server's message.
+ */
+
+const Passkeys = {
+    supported() {
+        return !!(window.PublicKeyCredential && navigator.credentials);
+    },
+
+    toBase64url(buffer) {
+        const bytes = new Uint8Array(buffer);
+        let binary = '';
+        for (const b of bytes) binary += String.fromCharCode(b);
+        return btoa(binary).replace(/\+/g, '-').replace(/\//g, '_').replace(/=+$/, '');
+    },
+
+    fromBase64url(value) {
+        const base64 = value.replace(/-/g, '+').replace(/_/g, '/');
+        const binary = atob(base64 + '='.repeat((4 - (base64.length % 4)) % 4));
+        return Uint8Array.from(binary, (c) => c.charCodeAt(0));
+    },
+
+    async post(url, body) {
+        const resp = await fetch(url, {
+            method: 'POST',
+            headers: { 'Content-Type': 'application/json' },
+            body: JSON.stringify(body || {}),
+        });
+        const data = await resp.json().catch(() => ({}));
+        if (!resp.ok) throw new Error(data.error || `Request failed (${resp.status})`);
+        return data;
+    },
+
+    /** Create a passkey for the logged-in user. */
+    async register(name) {
+        const { publicKey } = await this.post('/api/passkeys/register/options');
+        publicKey.challenge = this.fromBase64url(publicKey.challenge);
+        publicKey.user.id = this.fromBase64url(publicKey.user.id);
+        publicKey.excludeCredentials = publicKey.excludeCredentials.map((c) => ({
+            ...c,
+            id: this.fromBase64url(c.id),
+        }));
+
+        const credential = await navigator.credentials.create({ publicKey });
+        return this.post('/api/passkeys/register', {
+            name,
+            credential_id: this.toBase64url(credential.rawId),
+            client_data_json: this.toBase64url(credential.response.clientDataJSON),
+            attestation_object: this.toBase64url(credential.response.attestationObject),
+        });
+    },
+
+    /** Log in with any passkey of this site, then follow the redirect. */
+    async login(returnTo) {
+        const { publicKey } = await this.post('/api/passkeys/login/options');
+        publicKey.challenge = this.fromBase64url(publicKey.challenge);
+
+        const credential = await navigator.credentials.get({ publicKey });
+        const response = credential.response;
+        const data = await this.post('/api/passkeys/login', {
+            credential_id: this.toBase64url(credential.rawId),
+            client_data_json: this.toBase64url(response.clientDataJSON),
+            authenticator_data: this.toBase64url(response.authenticatorData),
+            signature: this.toBase64url(response.signature),
+            user_handle: response.userHandle ? this.toBase64url(response.userHandle) : null,
+            return_to: returnTo || null,
+        });
+        window.location.href = data.redirect;
+    },
+};
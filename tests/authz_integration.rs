@@ -71,7 +71,8 @@ async fn test_pool() -> SqlitePool {
             created_at        TEXT    NOT NULL DEFAULT (datetime('now')),
             updated_at        TEXT    NOT NULL DEFAULT (datetime('now')),
             published_at      TEXT,
-            tenant_id         TEXT    NOT NULL DEFAULT 'platform'
+            tenant_id         TEXT    NOT NULL DEFAULT 'platform',
+            deleted_at        TEXT
         );
         CREATE TABLE IF NOT EXISTS media_tags (
             id         INTEGER PRIMARY KEY AUTOINCREMENT,